@@ -43,7 +43,7 @@ impl TestCase for LoginTest {
         let ui_signal = Signal {
             id: new_entity_id(),
             run_id,
-            test_id,
+            test_id: Some(test_id),
             signal_type: SignalType::UI,
             timestamp: chrono::Utc::now(),
             latency_ms: Some(50),
@@ -56,6 +56,8 @@ impl TestCase for LoginTest {
             .unwrap()
             .clone(),
             created_at: BiTemporalTime::now(),
+            sequence: 0,
+            correlation_id: None,
         };
         council.record(ui_signal);
 
@@ -71,7 +73,7 @@ impl TestCase for LoginTest {
         let api_signal = Signal {
             id: new_entity_id(),
             run_id,
-            test_id,
+            test_id: Some(test_id),
             signal_type: SignalType::API,
             timestamp: chrono::Utc::now(),
             latency_ms: Some(100),
@@ -85,6 +87,8 @@ impl TestCase for LoginTest {
             .unwrap()
             .clone(),
             created_at: BiTemporalTime::now(),
+            sequence: 0,
+            correlation_id: None,
         };
         council.record(api_signal);
 
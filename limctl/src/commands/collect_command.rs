@@ -6,10 +6,17 @@ use liminalqa_core::{
     types::EntityId,
 };
 use liminalqa_db::LiminalDB;
+use liminalqa_runner::ingest::{signals_ndjson, SIGNALS_FILE};
 use std::fs;
-use std::path::PathBuf;
+use std::path::Path;
 
 pub async fn execute(db: &LiminalDB, run_id: &str) -> Result<()> {
+    collect(db, run_id, Path::new("collected_artifacts"))
+}
+
+/// Write a run with its tests, signals and artifacts under `out_dir/{run_id}`,
+/// in the layout of the file-system ingest backend
+fn collect(db: &LiminalDB, run_id: &str, out_dir: &Path) -> Result<()> {
     println!("📦 Collecting artifacts for run: {}", run_id);
 
     // Convert run_id string to EntityId
@@ -47,7 +54,7 @@ pub async fn execute(db: &LiminalDB, run_id: &str) -> Result<()> {
         let run_signals: Vec<Signal> = all_signal_ids
             .into_iter()
             .filter_map(|id| {
-                if let Ok(Some(signal)) = db.get_entity::<Signal>(id) {
+                if let Ok(Some(signal)) = db.get_signal(id) {
                     if signal.run_id == entity_id {
                         Some(signal)
                     } else {
@@ -77,7 +84,7 @@ pub async fn execute(db: &LiminalDB, run_id: &str) -> Result<()> {
             .collect();
 
         // Create a directory for collected artifacts
-        let artifacts_dir = PathBuf::from("collected_artifacts").join(run_id);
+        let artifacts_dir = out_dir.join(run_id);
         fs::create_dir_all(&artifacts_dir).context("Failed to create artifacts directory")?;
 
         // Save run information
@@ -97,12 +104,9 @@ pub async fn execute(db: &LiminalDB, run_id: &str) -> Result<()> {
         );
 
         // Save signals information
-        let signals_info_path = artifacts_dir.join("signals.json");
-        fs::write(
-            &signals_info_path,
-            serde_json::to_string_pretty(&run_signals)?,
-        )
-        .context("Failed to save signals information")?;
+        let signals_info_path = artifacts_dir.join(SIGNALS_FILE);
+        fs::write(&signals_info_path, signals_ndjson(&run_signals)?)
+            .context("Failed to save signals information")?;
         println!(
             "   Saved {} signal(s) information to: {}",
             run_signals.len(),
@@ -152,3 +156,52 @@ pub async fn execute(db: &LiminalDB, run_id: &str) -> Result<()> {
         anyhow::bail!("Run not found: {}", run_id);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use liminalqa_core::{temporal::BiTemporalTime, types::SignalType};
+    use liminalqa_runner::ingest::IngestFs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_collected_signals_read_back_like_fs_ingest() -> Result<()> {
+        let db_dir = TempDir::new()?;
+        let out_dir = TempDir::new()?;
+        let db = LiminalDB::open(db_dir.path())?;
+
+        let run = Run {
+            id: EntityId::new(),
+            build_id: EntityId::new(),
+            plan_name: "checkout".to_string(),
+            env: Default::default(),
+            started_at: Utc::now(),
+            ended_at: None,
+            runner_version: "test".to_string(),
+            liminal_os_version: None,
+            created_at: BiTemporalTime::now(),
+        };
+        db.put_run(&run)?;
+        db.put_signal(&Signal {
+            id: EntityId::new(),
+            run_id: run.id,
+            test_id: None,
+            signal_type: SignalType::API,
+            timestamp: Utc::now(),
+            latency_ms: Some(120),
+            payload_ref: None,
+            metadata: Default::default(),
+            created_at: BiTemporalTime::now(),
+            sequence: 0,
+            correlation_id: None,
+        })?;
+
+        collect(&db, &run.id.to_string(), out_dir.path())?;
+
+        let signals = IngestFs::new(out_dir.path().to_path_buf()).read_signals(&run.id)?;
+        assert_eq!(signals.len(), 1);
+        assert_eq!(signals[0].latency_ms, Some(120));
+        Ok(())
+    }
+}
@@ -0,0 +1,23 @@
+//! Delete command
+
+use anyhow::{Context, Result};
+use liminalqa_core::types::EntityId;
+use liminalqa_db::LiminalDB;
+
+pub async fn execute_run(db: &LiminalDB, run_id_str: &str) -> Result<()> {
+    let run_id = EntityId::from_string(run_id_str).context("Invalid run ID format")?;
+
+    println!("🗑️  Deleting run: {}", run_id);
+
+    let counts = db.delete_run_cascade(run_id)?;
+    db.flush()?;
+
+    if counts.total() == 0 {
+        anyhow::bail!("Run {} not found", run_id);
+    }
+    println!(
+        "✅ Deleted {} run, {} tests, {} signals, {} artifacts, {} facts",
+        counts.runs, counts.tests, counts.signals, counts.artifacts, counts.facts
+    );
+    Ok(())
+}
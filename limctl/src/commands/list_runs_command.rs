@@ -28,12 +28,7 @@ pub async fn execute(db: &LiminalDB) -> Result<()> {
                 r.id.to_string(),
                 r.plan_name,
                 r.started_at.format("%Y-%m-%d %H:%M:%S").to_string(),
-                if r.ended_at.is_some() {
-                    "Completed"
-                } else {
-                    "Running"
-                }
-                .to_string(),
+                db.run_status(r.id)?.to_string(),
             ]);
         }
     }
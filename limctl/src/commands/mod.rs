@@ -1,10 +1,16 @@
 //! CLI commands
 
 pub mod collect_command;
+pub mod delete_command;
 pub mod init_command;
 pub mod list_runs_command;
 pub mod list_systems_command;
 pub mod list_tests_command;
 pub mod query_command;
+pub mod reindex_command;
 pub mod report_command;
+pub mod rescore_command;
 pub mod run_command;
+pub mod stats_command;
+pub mod tail_command;
+pub mod validate_command;
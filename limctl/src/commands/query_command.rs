@@ -3,14 +3,21 @@
 use anyhow::{Context, Result};
 use comfy_table::{modifiers::UTF8_ROUND_CORNERS, presets::UTF8_FULL, Table};
 use liminalqa_core::{
+    facts::Fact,
     temporal::{TimeRange, TimeshiftQuery},
     types::EntityId,
 };
 use liminalqa_db::{LiminalDB, Query, QueryResult};
 use serde::{Deserialize, Serialize};
 use std::fs;
+use std::io::Write;
 use std::path::Path;
 
+use crate::QueryOutput;
+
+/// Rows shown by the table output; json and ndjson print every fact
+const TABLE_ROWS: usize = 20;
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct QuerySpec {
     pub entity_types: Option<Vec<String>>,
@@ -33,8 +40,16 @@ pub struct TimeshiftSpec {
     pub tx_time: String,    // ISO 8601 datetime string
 }
 
-pub async fn execute(db: &LiminalDB, query_path: &Path) -> Result<()> {
-    println!("🔍 Executing query from: {}", query_path.display());
+pub async fn execute(db: &LiminalDB, query_path: &Path, output: QueryOutput) -> Result<()> {
+    // Machine-readable output owns stdout, so progress goes to stderr
+    let progress = |line: String| {
+        if output == QueryOutput::Table {
+            println!("{}", line);
+        } else {
+            eprintln!("{}", line);
+        }
+    };
+    progress(format!("🔍 Executing query from: {}", query_path.display()));
 
     let query_content = fs::read_to_string(query_path).context(format!(
         "Failed to read query file: {}",
@@ -123,44 +138,128 @@ pub async fn execute(db: &LiminalDB, query_path: &Path) -> Result<()> {
     // Execute the query
     let result: QueryResult = query.execute(db)?;
 
-    // Display the results
-    println!("✅ Query executed successfully");
-    println!("📊 Found {} facts", result.total);
+    progress("✅ Query executed successfully".to_string());
+    progress(format!("📊 Found {} facts", result.total));
+
+    let stdout = std::io::stdout();
+    write_facts(&mut stdout.lock(), &result.facts, output)
+}
+
+/// Write `facts` to `out` in the requested encoding
+fn write_facts(out: &mut impl Write, facts: &[Fact], output: QueryOutput) -> Result<()> {
+    match output {
+        QueryOutput::Json => {
+            serde_json::to_writer_pretty(&mut *out, facts)?;
+            writeln!(out)?;
+        }
+        QueryOutput::Ndjson => {
+            for fact in facts {
+                serde_json::to_writer(&mut *out, fact)?;
+                writeln!(out)?;
+            }
+        }
+        QueryOutput::Table => {
+            if facts.is_empty() {
+                writeln!(out, "No facts found matching the query criteria.")?;
+                return Ok(());
+            }
+
+            let mut table = Table::new();
+            table
+                .load_preset(UTF8_FULL)
+                .apply_modifier(UTF8_ROUND_CORNERS)
+                .set_header(vec![
+                    "Entity ID",
+                    "Attribute",
+                    "Value",
+                    "Valid Time",
+                    "Tx Time",
+                ]);
+
+            for fact in facts.iter().take(TABLE_ROWS) {
+                table.add_row(vec![
+                    fact.entity_id.to_string(),
+                    fact.attribute.to_string(),
+                    fact.value.to_string(),
+                    fact.time.valid_time.format("%Y-%m-%d %H:%M:%S").to_string(),
+                    fact.time.tx_time.format("%Y-%m-%d %H:%M:%S").to_string(),
+                ]);
+            }
 
-    if result.facts.is_empty() {
-        println!("No facts found matching the query criteria.");
-        return Ok(());
+            writeln!(out, "{}", table)?;
+
+            if facts.len() > TABLE_ROWS {
+                writeln!(out, "... and {} more results", facts.len() - TABLE_ROWS)?;
+            }
+        }
     }
 
-    // Create a table to display the results
-    let mut table = Table::new();
-    table
-        .load_preset(UTF8_FULL)
-        .apply_modifier(UTF8_ROUND_CORNERS)
-        .set_header(vec![
-            "Entity ID",
-            "Attribute",
-            "Value",
-            "Valid Time",
-            "Tx Time",
-        ]);
-
-    for fact in result.facts.iter().take(20) {
-        // Limit to first 20 results for readability
-        table.add_row(vec![
-            fact.entity_id.to_string(),
-            fact.attribute.to_string(),
-            fact.value.to_string(),
-            fact.time.valid_time.format("%Y-%m-%d %H:%M:%S").to_string(),
-            fact.time.tx_time.format("%Y-%m-%d %H:%M:%S").to_string(),
-        ]);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use liminalqa_core::facts::Attribute;
+
+    fn sample_facts() -> Vec<Fact> {
+        vec![
+            Fact::new(
+                EntityId::new(),
+                Attribute::TestGuidance,
+                serde_json::json!("login works"),
+            ),
+            Fact::new(
+                EntityId::new(),
+                Attribute::TestStatus,
+                serde_json::json!("fail"),
+            ),
+        ]
     }
 
-    println!("{}", table);
+    fn render(facts: &[Fact], output: QueryOutput) -> Result<String> {
+        let mut out = Vec::new();
+        write_facts(&mut out, facts, output)?;
+        Ok(String::from_utf8(out)?)
+    }
 
-    if result.facts.len() > 20 {
-        println!("... and {} more results", result.facts.len() - 20);
+    #[test]
+    fn test_json_output_is_one_array() -> Result<()> {
+        let facts = sample_facts();
+        let parsed: Vec<Fact> = serde_json::from_str(&render(&facts, QueryOutput::Json)?)?;
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].entity_id, facts[0].entity_id);
+        assert_eq!(parsed[1].value, serde_json::json!("fail"));
+        Ok(())
     }
 
-    Ok(())
+    #[test]
+    fn test_ndjson_output_has_one_fact_per_line() -> Result<()> {
+        let facts = sample_facts();
+        let rendered = render(&facts, QueryOutput::Ndjson)?;
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines.len(), 2);
+        for (line, fact) in lines.iter().zip(&facts) {
+            let parsed: Fact = serde_json::from_str(line)?;
+            assert_eq!(parsed.entity_id, fact.entity_id);
+            assert_eq!(parsed.attribute, fact.attribute);
+        }
+        assert!(render(&[], QueryOutput::Ndjson)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_table_output_has_columns_and_rows() -> Result<()> {
+        let facts = sample_facts();
+        let rendered = render(&facts, QueryOutput::Table)?;
+        for header in ["Entity ID", "Attribute", "Value", "Valid Time"] {
+            assert!(rendered.contains(header), "missing column {}", header);
+        }
+        assert!(rendered.contains(&facts[0].entity_id.to_string()));
+        assert!(rendered.contains(":test/status"));
+        assert!(rendered.contains("\"login works\""));
+
+        assert!(render(&[], QueryOutput::Table)?.contains("No facts found"));
+        Ok(())
+    }
 }
@@ -0,0 +1,14 @@
+//! Reindex command
+
+use anyhow::Result;
+use liminalqa_db::LiminalDB;
+
+pub async fn execute(db: &LiminalDB) -> Result<()> {
+    println!("🔧 Rebuilding secondary indexes...");
+
+    db.rebuild_indexes()?;
+    db.flush()?;
+
+    println!("✅ Indexes rebuilt");
+    Ok(())
+}
@@ -2,8 +2,9 @@
 
 use anyhow::{Context, Result};
 use liminalqa_core::{
-    entities::{EntityType, Run, Test},
-    types::EntityId,
+    entities::{Artifact, EntityType, Run, Test},
+    report::format_duration_ms,
+    types::{EntityId, RunStatus},
 };
 use liminalqa_db::LiminalDB;
 use std::fs;
@@ -54,10 +55,26 @@ pub async fn execute(
 
         println!("   Found {} tests for this run", run_tests.len());
 
+        let mut run_artifacts = Vec::new();
+        for id in db.get_entities_by_type(EntityType::Artifact)? {
+            if let Ok(Some(artifact)) = db.get_entity::<Artifact>(id) {
+                if artifact.run_id == entity_id {
+                    run_artifacts.push(artifact);
+                }
+            }
+        }
+
+        let status = db.run_status(entity_id)?;
         let report_content = match format {
-            crate::ReportFormat::Html => generate_html_report(&run, &run_tests)?,
-            crate::ReportFormat::Json => generate_json_report(&run, &run_tests)?,
-            crate::ReportFormat::Markdown => generate_markdown_report(&run, &run_tests)?,
+            crate::ReportFormat::Html => {
+                generate_html_report(&run, status, &run_tests, &run_artifacts)?
+            }
+            crate::ReportFormat::Json => {
+                generate_json_report(&run, status, &run_tests, &run_artifacts)?
+            }
+            crate::ReportFormat::Markdown => {
+                generate_markdown_report(&run, status, &run_tests, &run_artifacts)?
+            }
         };
 
         match output {
@@ -80,7 +97,17 @@ pub async fn execute(
     }
 }
 
-fn generate_html_report(run: &Run, tests: &[Test]) -> Result<String> {
+/// Display name of an artifact type, e.g. `apiresponse`
+fn artifact_kind(artifact: &Artifact) -> String {
+    format!("{:?}", artifact.artifact_type).to_lowercase()
+}
+
+fn generate_html_report(
+    run: &Run,
+    status: RunStatus,
+    tests: &[Test],
+    artifacts: &[Artifact],
+) -> Result<String> {
     let passed_count = tests.iter().filter(|t| t.status.is_pass()).count();
     let failed_count = tests.len() - passed_count;
 
@@ -112,14 +139,7 @@ fn generate_html_report(run: &Run, tests: &[Test]) -> Result<String> {
     if let Some(end_time) = run.ended_at {
         html.push_str(&format!("<p><strong>End Time:</strong> {}</p>\n", end_time));
     }
-    html.push_str(&format!(
-        "<p><strong>Status:</strong> {}</p>\n",
-        if run.ended_at.is_some() {
-            "Completed"
-        } else {
-            "Running"
-        }
-    ));
+    html.push_str(&format!("<p><strong>Status:</strong> {}</p>\n", status));
     html.push_str(&format!(
         "<p><strong>Results:</strong> <span class=\"passed\">{} passed</span>, <span class=\"failed\">{} failed</span></p>\n",
         passed_count, failed_count
@@ -128,7 +148,7 @@ fn generate_html_report(run: &Run, tests: &[Test]) -> Result<String> {
 
     html.push_str("<h2>Test Results</h2>\n");
     html.push_str("<table>\n");
-    html.push_str("<thead>\n<tr><th>Name</th><th>Suite</th><th>Status</th><th>Duration (ms)</th><th>Started</th></tr>\n</thead>\n");
+    html.push_str("<thead>\n<tr><th>Name</th><th>Suite</th><th>Status</th><th>Duration</th><th>Started</th></tr>\n</thead>\n");
     html.push_str("<tbody>\n");
 
     for test in tests {
@@ -143,18 +163,38 @@ fn generate_html_report(run: &Run, tests: &[Test]) -> Result<String> {
             test.suite,
             status_class,
             format!("{:?}", test.status).to_lowercase(),
-            test.duration_ms,
+            format_duration_ms(test.duration_ms as f64),
             test.started_at.format("%H:%M:%S%.3f")
         ));
     }
 
     html.push_str("</tbody>\n</table>\n");
+
+    if !artifacts.is_empty() {
+        html.push_str("<h2>Artifacts</h2>\n");
+        html.push_str("<ul>\n");
+        for artifact in artifacts {
+            html.push_str(&format!(
+                "<li>{}: <a href=\"{}\">{}</a></li>\n",
+                artifact_kind(artifact),
+                artifact.artifact_ref.location.href(),
+                artifact.artifact_ref.location
+            ));
+        }
+        html.push_str("</ul>\n");
+    }
+
     html.push_str("\n</body>\n</html>");
 
     Ok(html)
 }
 
-fn generate_json_report(run: &Run, tests: &[Test]) -> Result<String> {
+fn generate_json_report(
+    run: &Run,
+    status: RunStatus,
+    tests: &[Test],
+    artifacts: &[Artifact],
+) -> Result<String> {
     #[derive(serde::Serialize, serde::Deserialize)]
     struct RunSummary {
         id: String,
@@ -162,6 +202,7 @@ fn generate_json_report(run: &Run, tests: &[Test]) -> Result<String> {
         started_at: chrono::DateTime<chrono::Utc>,
         ended_at: Option<chrono::DateTime<chrono::Utc>>,
         completed: bool,
+        status: RunStatus,
     }
 
     #[derive(serde::Serialize, serde::Deserialize)]
@@ -182,11 +223,19 @@ fn generate_json_report(run: &Run, tests: &[Test]) -> Result<String> {
         guidance: String,
     }
 
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct ArtifactItem {
+        kind: String,
+        location: liminalqa_core::types::ArtifactLocation,
+        href: String,
+    }
+
     #[derive(serde::Serialize, serde::Deserialize)]
     struct Report {
         run: RunSummary,
         summary: TestSummary,
         tests: Vec<TestItem>,
+        artifacts: Vec<ArtifactItem>,
     }
 
     let report = Report {
@@ -195,7 +244,8 @@ fn generate_json_report(run: &Run, tests: &[Test]) -> Result<String> {
             plan_name: run.plan_name.clone(),
             started_at: run.started_at,
             ended_at: run.ended_at,
-            completed: run.ended_at.is_some(),
+            completed: status == RunStatus::Completed,
+            status,
         },
         summary: TestSummary {
             total: tests.len(),
@@ -214,12 +264,25 @@ fn generate_json_report(run: &Run, tests: &[Test]) -> Result<String> {
                 guidance: test.guidance.clone(),
             })
             .collect(),
+        artifacts: artifacts
+            .iter()
+            .map(|artifact| ArtifactItem {
+                kind: artifact_kind(artifact),
+                location: artifact.artifact_ref.location.clone(),
+                href: artifact.artifact_ref.location.href(),
+            })
+            .collect(),
     };
 
     Ok(serde_json::to_string_pretty(&report)?)
 }
 
-fn generate_markdown_report(run: &Run, tests: &[Test]) -> Result<String> {
+fn generate_markdown_report(
+    run: &Run,
+    status: RunStatus,
+    tests: &[Test],
+    artifacts: &[Artifact],
+) -> Result<String> {
     let passed_count = tests.iter().filter(|t| t.status.is_pass()).count();
     let failed_count = tests.len() - passed_count;
 
@@ -232,22 +295,15 @@ fn generate_markdown_report(run: &Run, tests: &[Test]) -> Result<String> {
     if let Some(end_time) = run.ended_at {
         md.push_str(&format!("**End Time:** {}\n\n", end_time));
     }
-    md.push_str(&format!(
-        "**Status:** {}\n\n",
-        if run.ended_at.is_some() {
-            "Completed"
-        } else {
-            "Running"
-        }
-    ));
+    md.push_str(&format!("**Status:** {}\n\n", status));
     md.push_str(&format!(
         "**Results:** {} passed, {} failed\n\n",
         passed_count, failed_count
     ));
 
     md.push_str("## Test Results\n\n");
-    md.push_str("| Name | Suite | Status | Duration (ms) | Started |\n");
-    md.push_str("|------|-------|--------|---------------|---------|\n");
+    md.push_str("| Name | Suite | Status | Duration | Started |\n");
+    md.push_str("|------|-------|--------|----------|---------|\n");
 
     for test in tests {
         let status = if test.status.is_pass() {
@@ -260,10 +316,22 @@ fn generate_markdown_report(run: &Run, tests: &[Test]) -> Result<String> {
             test.name,
             test.suite,
             status,
-            test.duration_ms,
+            format_duration_ms(test.duration_ms as f64),
             test.started_at.format("%H:%M:%S%.3f")
         ));
     }
 
+    if !artifacts.is_empty() {
+        md.push_str("\n## Artifacts\n\n");
+        for artifact in artifacts {
+            md.push_str(&format!(
+                "- {}: [{}]({})\n",
+                artifact_kind(artifact),
+                artifact.artifact_ref.location,
+                artifact.artifact_ref.location.href()
+            ));
+        }
+    }
+
     Ok(md)
 }
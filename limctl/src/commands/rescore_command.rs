@@ -0,0 +1,18 @@
+//! Rescore command
+
+use anyhow::Result;
+use liminalqa_core::resonance::FlakeDetector;
+use liminalqa_db::LiminalDB;
+
+pub async fn execute(db: &LiminalDB, window_size: usize, threshold: f64) -> Result<()> {
+    println!(
+        "🔁 Rescoring flake resonance (window={}, threshold={})...",
+        window_size, threshold
+    );
+
+    let changed = db.rescore_all_resonance(&FlakeDetector::new(window_size, threshold))?;
+    db.flush()?;
+
+    println!("✅ {} resonance records updated", changed);
+    Ok(())
+}
@@ -1,15 +1,19 @@
 //! Run command
 
 use anyhow::{Context, Result};
+use futures::{stream, StreamExt, TryStreamExt};
 use liminalqa_core::{
-    entities::{Run, Test},
+    entities::{EntityType, Resonance, Run, Test},
     temporal::BiTemporalTime,
     types::{EntityId, Environment, TestStatus},
 };
 use liminalqa_db::LiminalDB;
-use liminalqa_runner::TestRunner;
+use liminalqa_runner::{ingest::TestOutcome, runner::ExecutionResult, Reflection, TestRunner};
 use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashSet;
 use std::fs;
+use std::future::Future;
 use std::path::Path;
 use tracing::info;
 
@@ -17,9 +21,16 @@ use tracing::info;
 pub struct TestPlan {
     pub name: String,
     pub environment: Option<Environment>,
+    /// Suites executed at once; tests within a suite always run in order
+    #[serde(default = "default_parallelism")]
+    pub parallelism: usize,
     pub tests: Vec<TestDefinition>,
 }
 
+fn default_parallelism() -> usize {
+    1
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct TestDefinition {
     pub name: String,
@@ -27,7 +38,94 @@ pub struct TestDefinition {
     pub guidance: String,
 }
 
-pub async fn execute(db: &LiminalDB, plan_path: &Path) -> Result<()> {
+/// Exit code used when a run falls below `--min-pass-rate`
+pub const PASS_RATE_EXIT_CODE: i32 = 2;
+
+/// CI gate on the pass rate of a completed run
+#[derive(Debug, Clone)]
+pub struct PassRateGate {
+    /// Minimum pass rate, in percent (0-100)
+    pub min_pass_rate: f64,
+    /// Leave quarantined (flaky) tests out of the pass rate
+    pub allow_flaky: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PassRateVerdict {
+    pub pass_rate: f64,
+    pub counted: usize,
+    pub excluded: usize,
+    pub passed: bool,
+}
+
+impl PassRateGate {
+    /// Compute the pass rate of `tests` and compare it with the threshold.
+    ///
+    /// Skipped tests never count. With `allow_flaky`, tests with a `Flake`
+    /// status or flagged by a resonance record are excluded as well. An empty
+    /// set passes the gate.
+    pub fn evaluate(&self, db: &LiminalDB, tests: &[Test]) -> Result<PassRateVerdict> {
+        let quarantined = if self.allow_flaky {
+            quarantined_tests(db)?
+        } else {
+            HashSet::new()
+        };
+
+        let counted: Vec<&Test> = tests
+            .iter()
+            .filter(|t| t.status != TestStatus::Skip)
+            .filter(|t| {
+                !(self.allow_flaky
+                    && (t.status == TestStatus::Flake
+                        || quarantined.contains(&(t.name.clone(), t.suite.clone()))))
+            })
+            .collect();
+
+        let pass_rate = if counted.is_empty() {
+            100.0
+        } else {
+            let passed = counted.iter().filter(|t| t.status.is_pass()).count();
+            passed as f64 * 100.0 / counted.len() as f64
+        };
+
+        Ok(PassRateVerdict {
+            pass_rate,
+            counted: counted.len(),
+            excluded: tests.len() - counted.len(),
+            passed: pass_rate >= self.min_pass_rate,
+        })
+    }
+}
+
+/// (name, suite) of every test flagged flaky by an unresolved resonance record
+fn quarantined_tests(db: &LiminalDB) -> Result<HashSet<(String, String)>> {
+    let mut quarantined = HashSet::new();
+    for id in db.get_entities_by_type(EntityType::Resonance)? {
+        let Some(resonance) = db.get_entity::<Resonance>(id)? else {
+            continue;
+        };
+        if db.resonance_resolved_at(id)?.is_some() {
+            continue;
+        }
+        for test_id in resonance.affected_tests {
+            if let Some(test) = db.get_entity::<Test>(test_id)? {
+                quarantined.insert((test.name, test.suite));
+            }
+        }
+    }
+    Ok(quarantined)
+}
+
+/// Execute a test plan, returning the gate verdict when a gate was given.
+///
+/// With `resume`, tests of the plan already recorded for that run are not
+/// executed again; the run continues where it was interrupted.
+pub async fn execute(
+    db: &LiminalDB,
+    plan_path: &Path,
+    gate: Option<&PassRateGate>,
+    resume: Option<&str>,
+) -> Result<Option<PassRateVerdict>> {
     println!("📋 Loading test plan: {}", plan_path.display());
 
     let plan_content = fs::read_to_string(plan_path).context(format!(
@@ -46,40 +144,21 @@ pub async fn execute(db: &LiminalDB, plan_path: &Path) -> Result<()> {
         plan.tests.len()
     );
 
-    // Create a new run
-    let run_id = EntityId::new();
-    let run = Run {
-        id: run_id,
-        build_id: EntityId::new(),
-        plan_name: plan.name,
-        env: plan.environment.unwrap_or_default(),
-        started_at: chrono::Utc::now(),
-        ended_at: None,
-        runner_version: env!("CARGO_PKG_VERSION").to_string(),
-        liminal_os_version: None,
-        created_at: BiTemporalTime::now(),
-    };
-
-    // Store the run in the database
-    db.put_run(&run)?;
-    println!("✅ Created run: {}", run.id);
-
-    // Execute tests
-    let _runner = TestRunner::new(run_id);
-    let mut results = Vec::new();
-
-    for test_def in plan.tests {
-        println!("🧪 Executing test: {}::{}", test_def.suite, test_def.name);
+    let resume = resume
+        .map(EntityId::from_string)
+        .transpose()
+        .context("Invalid run ID format for --resume")?;
 
+    let results = run_plan(db, plan, resume, |run_id, test_def| {
         // For now, create a mock test execution
         // In a real implementation, this would use the TestRunner to execute actual tests
-        let test_id = EntityId::new();
+        let _runner = TestRunner::new(run_id);
         let test = Test {
-            id: test_id,
+            id: EntityId::new(),
             run_id,
-            name: test_def.name,
-            suite: test_def.suite,
-            guidance: test_def.guidance,
+            name: test_def.name.clone(),
+            suite: test_def.suite.clone(),
+            guidance: test_def.guidance.clone(),
             status: TestStatus::Pass, // For now, assuming all pass
             duration_ms: 100,         // Mock duration
             error: None,
@@ -87,16 +166,16 @@ pub async fn execute(db: &LiminalDB, plan_path: &Path) -> Result<()> {
             completed_at: chrono::Utc::now(),
             created_at: BiTemporalTime::now(),
         };
-
-        // Store the test result in the database
-        db.put_test(&test)?;
-        results.push(test);
-    }
-
-    // Update run to mark as completed
-    let mut completed_run = run;
-    completed_run.ended_at = Some(chrono::Utc::now());
-    db.put_run(&completed_run)?;
+        let result = ExecutionResult {
+            reflection: Reflection::from_test(&test),
+            test,
+            signals: vec![],
+            attempts: 1,
+            sla_ms: None,
+        };
+        async move { Ok(result) }
+    })
+    .await?;
 
     println!("✅ Completed run with {} tests", results.len());
     println!(
@@ -111,5 +190,550 @@ pub async fn execute(db: &LiminalDB, plan_path: &Path) -> Result<()> {
             .count()
     );
 
+    let Some(gate) = gate else {
+        return Ok(None);
+    };
+
+    let verdict = gate.evaluate(db, &results)?;
+    if verdict.passed {
+        println!(
+            "✅ Pass rate {:.1}% meets minimum {:.1}%",
+            verdict.pass_rate, gate.min_pass_rate
+        );
+    } else {
+        println!(
+            "❌ Pass rate {:.1}% is below minimum {:.1}%",
+            verdict.pass_rate, gate.min_pass_rate
+        );
+    }
+    if verdict.excluded > 0 {
+        println!(
+            "   ({} tests excluded from the pass rate)",
+            verdict.excluded
+        );
+    }
+
+    Ok(Some(verdict))
+}
+
+/// Execute every test of `plan` with `execute_test`, checkpointing each
+/// result to the database as soon as it finishes.
+///
+/// Up to `plan.parallelism` suites run at once; the tests of a suite run one
+/// after another in plan order. A fresh run is created unless `resume` names
+/// an interrupted run of the same plan, in which case tests already recorded
+/// for it (matched by name and suite) are skipped. Returns the tests of the
+/// whole run, including tests completed before the interruption.
+async fn run_plan<F, Fut>(
+    db: &LiminalDB,
+    plan: TestPlan,
+    resume: Option<EntityId>,
+    execute_test: F,
+) -> Result<Vec<Test>>
+where
+    F: FnMut(EntityId, &TestDefinition) -> Fut,
+    Fut: Future<Output = Result<ExecutionResult>>,
+{
+    let (run, mut results) = match resume {
+        Some(run_id) => {
+            let run: Run = db
+                .get_entity(run_id)?
+                .with_context(|| format!("Run not found: {}", run_id))?;
+            if run.plan_name != plan.name {
+                anyhow::bail!(
+                    "Run {} belongs to plan '{}', not '{}'",
+                    run_id,
+                    run.plan_name,
+                    plan.name
+                );
+            }
+            let completed = completed_tests(db, run_id)?;
+            println!(
+                "⏯️  Resuming run {}: {} tests already completed",
+                run_id,
+                completed.len()
+            );
+            (run, completed)
+        }
+        None => {
+            let run = Run {
+                id: EntityId::new(),
+                build_id: EntityId::new(),
+                plan_name: plan.name.clone(),
+                env: plan.environment.clone().unwrap_or_default(),
+                started_at: chrono::Utc::now(),
+                ended_at: None,
+                runner_version: env!("CARGO_PKG_VERSION").to_string(),
+                liminal_os_version: None,
+                created_at: BiTemporalTime::now(),
+            };
+
+            // Store the run in the database
+            db.put_run(&run)?;
+            db.flush()?;
+            println!("✅ Created run: {}", run.id);
+            (run, Vec::new())
+        }
+    };
+
+    let done: HashSet<(String, String)> = results
+        .iter()
+        .map(|t| (t.name.clone(), t.suite.clone()))
+        .collect();
+
+    // Pending tests grouped by suite, suites in order of first appearance
+    let mut suites: Vec<Vec<&TestDefinition>> = Vec::new();
+    for test_def in &plan.tests {
+        if done.contains(&(test_def.name.clone(), test_def.suite.clone())) {
+            println!(
+                "⏭️  Skipping completed test: {}::{}",
+                test_def.suite, test_def.name
+            );
+            continue;
+        }
+        match suites.iter_mut().find(|s| s[0].suite == test_def.suite) {
+            Some(suite) => suite.push(test_def),
+            None => suites.push(vec![test_def]),
+        }
+    }
+
+    // Suites share the closure; it is only borrowed while starting a test
+    let execute_test = RefCell::new(execute_test);
+    let run_id = run.id;
+    let executed: Vec<Vec<Test>> = stream::iter(suites)
+        .map(|suite| {
+            let execute_test = &execute_test;
+            async move {
+                let mut tests = Vec::with_capacity(suite.len());
+                for test_def in suite {
+                    println!("🧪 Executing test: {}::{}", test_def.suite, test_def.name);
+                    let pending = (execute_test.borrow_mut())(run_id, test_def);
+                    let result = pending.await?;
+
+                    // Checkpoint: a crash after this point does not re-run the test
+                    checkpoint(db, &result)?;
+                    db.flush()?;
+                    tests.push(result.test);
+                }
+                Ok::<_, anyhow::Error>(tests)
+            }
+        })
+        .buffered(plan.parallelism.max(1))
+        .try_collect()
+        .await?;
+    results.extend(executed.into_iter().flatten());
+
+    // Update run to mark as completed
+    let mut completed_run = run;
+    completed_run.ended_at = Some(chrono::Utc::now());
+    db.put_run(&completed_run)?;
+
+    Ok(results)
+}
+
+/// Store an executed test along with the facts of its [`TestOutcome`]
+fn checkpoint(db: &LiminalDB, result: &ExecutionResult) -> Result<()> {
+    db.put_test(&result.test)?;
+    let outcome = TestOutcome::from(result);
+    if let Some(score) = outcome.alignment_score {
+        db.put_test_alignment(&result.test, score)?;
+    }
+    if let Some(sla_ms) = outcome.sla_ms {
+        db.put_test_sla(&result.test, sla_ms)?;
+    }
+    if let Some(attempts) = outcome.attempts {
+        db.put_test_attempts(&result.test, attempts)?;
+    }
     Ok(())
 }
+
+/// Tests already recorded for `run_id`
+fn completed_tests(db: &LiminalDB, run_id: EntityId) -> Result<Vec<Test>> {
+    let mut tests = Vec::new();
+    for id in db.get_entities_by_type(EntityType::Test)? {
+        if let Some(test) = db.get_entity::<Test>(id)? {
+            if test.run_id == run_id {
+                tests.push(test);
+            }
+        }
+    }
+    Ok(tests)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use liminalqa_core::types::ResonancePattern;
+    use std::time::{Duration, Instant};
+    use tempfile::TempDir;
+
+    fn make_test(name: &str, status: TestStatus) -> Test {
+        Test {
+            id: EntityId::new(),
+            run_id: EntityId::new(),
+            name: name.to_string(),
+            suite: "suite".to_string(),
+            guidance: "".to_string(),
+            status,
+            duration_ms: 100,
+            error: None,
+            started_at: chrono::Utc::now(),
+            completed_at: chrono::Utc::now(),
+            created_at: BiTemporalTime::now(),
+        }
+    }
+
+    fn gate(min_pass_rate: f64, allow_flaky: bool) -> PassRateGate {
+        PassRateGate {
+            min_pass_rate,
+            allow_flaky,
+        }
+    }
+
+    #[test]
+    fn test_pass_rate_below_and_above_threshold() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+
+        // 3 of 4 counted tests pass; the skipped one is ignored
+        let tests = vec![
+            make_test("a", TestStatus::Pass),
+            make_test("b", TestStatus::Pass),
+            make_test("c", TestStatus::Pass),
+            make_test("d", TestStatus::Fail),
+            make_test("e", TestStatus::Skip),
+        ];
+
+        let below = gate(80.0, false).evaluate(&db, &tests)?;
+        assert_eq!(below.pass_rate, 75.0);
+        assert!(!below.passed);
+
+        let above = gate(75.0, false).evaluate(&db, &tests)?;
+        assert!(above.passed);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_allow_flaky_excludes_quarantined_tests() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+
+        // "d" was flagged flaky in an earlier run
+        let earlier = make_test("d", TestStatus::Fail);
+        db.put_test(&earlier)?;
+        db.put_resonance(&Resonance {
+            id: EntityId::new(),
+            detector: "flake".to_string(),
+            pattern: ResonancePattern {
+                pattern_id: EntityId::new(),
+                description: "Flaky test detected: d".to_string(),
+                score: 0.5,
+                occurrences: 1,
+                first_seen: chrono::Utc::now(),
+                last_seen: chrono::Utc::now(),
+            },
+            affected_tests: vec![earlier.id],
+            root_cause: None,
+            created_at: BiTemporalTime::now(),
+        })?;
+
+        let tests = vec![
+            make_test("a", TestStatus::Pass),
+            make_test("b", TestStatus::Pass),
+            make_test("c", TestStatus::Flake),
+            make_test("d", TestStatus::Fail),
+        ];
+
+        assert!(!gate(100.0, false).evaluate(&db, &tests)?.passed);
+
+        let verdict = gate(100.0, true).evaluate(&db, &tests)?;
+        assert_eq!(verdict.counted, 2);
+        assert_eq!(verdict.excluded, 2);
+        assert!(verdict.passed);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_execute_reports_gate_verdict() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path().join("db"))?;
+        let plan_path = temp_dir.path().join("plan.yaml");
+        fs::write(
+            &plan_path,
+            "name: smoke\ntests:\n  - name: login\n    suite: auth\n    guidance: logs in\n",
+        )?;
+
+        assert_eq!(execute(&db, &plan_path, None, None).await?, None);
+
+        let verdict = execute(&db, &plan_path, Some(&gate(90.0, false)), None).await?;
+        assert!(verdict.is_some_and(|v| v.passed));
+
+        Ok(())
+    }
+
+    fn plan(names: &[&str]) -> TestPlan {
+        TestPlan {
+            name: "smoke".to_string(),
+            environment: None,
+            parallelism: 1,
+            tests: names
+                .iter()
+                .map(|name| TestDefinition {
+                    name: name.to_string(),
+                    suite: "auth".to_string(),
+                    guidance: "".to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    fn passing(run_id: EntityId, test_def: &TestDefinition) -> ExecutionResult {
+        let mut test = make_test(&test_def.name, TestStatus::Pass);
+        test.run_id = run_id;
+        test.suite = test_def.suite.clone();
+        ExecutionResult {
+            reflection: Reflection::from_test(&test),
+            test,
+            signals: vec![],
+            attempts: 1,
+            sla_ms: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resume_skips_checkpointed_tests() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+        let names = ["login", "logout", "signup", "reset"];
+
+        // Interrupted while executing the third test
+        let mut run_id = None;
+        let interrupted = run_plan(&db, plan(&names), None, |id, test_def| {
+            run_id = Some(id);
+            let result = if test_def.name == "signup" {
+                Err(anyhow::anyhow!("runner crashed"))
+            } else {
+                Ok(passing(id, test_def))
+            };
+            async move { result }
+        })
+        .await;
+        assert!(interrupted.is_err());
+        let run_id = run_id.expect("the run started");
+        assert_eq!(completed_tests(&db, run_id)?.len(), 2);
+
+        let mut executed = Vec::new();
+        let results = run_plan(&db, plan(&names), Some(run_id), |id, test_def| {
+            assert_eq!(id, run_id);
+            executed.push(test_def.name.clone());
+            let result = passing(id, test_def);
+            async move { Ok(result) }
+        })
+        .await?;
+
+        assert_eq!(executed, ["signup", "reset"]);
+        assert_eq!(results.len(), 4);
+        assert_eq!(completed_tests(&db, run_id)?.len(), 4);
+        let run: Run = db.get_entity(run_id)?.expect("run stored");
+        assert!(run.ended_at.is_some());
+
+        // Only runs of the same plan can be resumed
+        let mut other = plan(&names);
+        other.name = "nightly".to_string();
+        let resumed = run_plan(&db, other, Some(run_id), |id, t| {
+            let result = passing(id, t);
+            async move { Ok(result) }
+        });
+        assert!(resumed.await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_records_alignment_scores() -> Result<()> {
+        use liminalqa_core::facts::Attribute;
+
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+
+        let tests = run_plan(&db, plan(&["login", "logout"]), None, |id, test_def| {
+            let mut result = passing(id, test_def);
+            if test_def.name == "login" {
+                result.reflection.alignment_score = Some(0.5);
+            }
+            async move { Ok(result) }
+        })
+        .await?;
+
+        let ids: Vec<EntityId> = tests.iter().map(|t| t.id).collect();
+        let scored: Vec<(EntityId, serde_json::Value)> = db
+            .scan_facts_by_entities(&ids)?
+            .into_iter()
+            .filter(|f| f.attribute == Attribute::TestAlignment)
+            .map(|f| (f.entity_id, f.value))
+            .collect();
+        assert_eq!(scored, [(tests[0].id, serde_json::json!(0.5))]);
+
+        Ok(())
+    }
+
+    /// A test that takes `delay_ms` under a guidance with an SLA of `sla_ms`,
+    /// its step failing `transient_failures` times before it succeeds
+    struct SlowTest {
+        name: String,
+        delay_ms: u64,
+        sla_ms: u64,
+        transient_failures: usize,
+    }
+
+    #[async_trait::async_trait]
+    impl liminalqa_runner::runner::TestCase for SlowTest {
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        fn suite(&self) -> &str {
+            "auth"
+        }
+
+        fn guidance(&self) -> liminalqa_runner::Guidance {
+            liminalqa_runner::Guidance::new("Answers in time").with_sla(self.sla_ms)
+        }
+
+        async fn execute(
+            &self,
+            navigator: &liminalqa_runner::CoNavigator,
+            _council: &mut liminalqa_runner::InnerCouncil,
+        ) -> Result<()> {
+            let tries = std::sync::atomic::AtomicUsize::new(0);
+            navigator
+                .execute_with_retry(|| async {
+                    let tried = tries.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                    if tried < self.transient_failures {
+                        anyhow::bail!("transient failure")
+                    }
+                    Ok(())
+                })
+                .await?;
+            tokio::time::sleep(Duration::from_millis(self.delay_ms)).await;
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_guidance_sla_reaches_report() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+
+        let tests = run_plan(&db, plan(&["login", "logout"]), None, |id, test_def| {
+            let runner = TestRunner::new(id);
+            let case = SlowTest {
+                name: test_def.name.clone(),
+                delay_ms: if test_def.name == "login" { 60 } else { 0 },
+                sla_ms: 30,
+                transient_failures: 0,
+            };
+            async move { runner.execute(&case).await }
+        })
+        .await?;
+
+        let report = liminalqa_db::report::build_report(&db, tests[0].run_id)?;
+        let breached: Vec<(&str, u64)> = report
+            .sla_breaches
+            .iter()
+            .map(|b| (b.name.as_str(), b.sla_ms))
+            .collect();
+        assert_eq!(breached, [("login", 30)]);
+        assert!(report.sla_breaches[0].duration_ms >= 60);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_navigation_attempts_reach_report() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+
+        let tests = run_plan(&db, plan(&["login", "logout"]), None, |id, test_def| {
+            let runner = TestRunner::new(id)
+                .with_navigator(liminalqa_runner::CoNavigator::new().with_retry_delay(1));
+            let case = SlowTest {
+                name: test_def.name.clone(),
+                delay_ms: 0,
+                sla_ms: 1000,
+                transient_failures: if test_def.name == "login" { 2 } else { 0 },
+            };
+            async move { runner.execute(&case).await }
+        })
+        .await?;
+
+        let report = liminalqa_db::report::build_report(&db, tests[0].run_id)?;
+        assert_eq!(report.summary.passed, 2);
+        assert_eq!(report.summary.passed_with_retries, 1);
+        let retried: Vec<(&str, u32)> = report
+            .retried_passes
+            .iter()
+            .map(|r| (r.name.as_str(), r.attempts))
+            .collect();
+        assert_eq!(retried, [("login", 3)]);
+
+        Ok(())
+    }
+
+    /// Two suites of two tests that each take `delay`
+    fn two_suites(parallelism: usize) -> TestPlan {
+        let mut plan = plan(&["login", "logout", "pay", "refund"]);
+        plan.parallelism = parallelism;
+        plan.tests[2].suite = "payments".to_string();
+        plan.tests[3].suite = "payments".to_string();
+        plan
+    }
+
+    async fn run_slowly(db: &LiminalDB, plan: TestPlan, delay: Duration) -> Result<Vec<Test>> {
+        run_plan(db, plan, None, |id, test_def| {
+            let result = passing(id, test_def);
+            async move {
+                tokio::time::sleep(delay).await;
+                Ok(result)
+            }
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_parallel_suites_finish_faster_than_serial() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+        let delay = Duration::from_millis(100);
+
+        let start = Instant::now();
+        let serial = run_slowly(&db, two_suites(1), delay).await?;
+        let serial_elapsed = start.elapsed();
+
+        let start = Instant::now();
+        let parallel = run_slowly(&db, two_suites(2), delay).await?;
+        let parallel_elapsed = start.elapsed();
+
+        assert_eq!(serial.len(), 4);
+        assert_eq!(parallel.len(), 4);
+        assert!(
+            parallel_elapsed < serial_elapsed,
+            "parallel {:?} vs serial {:?}",
+            parallel_elapsed,
+            serial_elapsed
+        );
+
+        // Every result is recorded for its own run, each suite in plan order
+        let run_id = parallel[0].run_id;
+        assert!(parallel.iter().all(|t| t.run_id == run_id));
+        let names: Vec<&str> = parallel.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["login", "logout", "pay", "refund"]);
+        assert_eq!(completed_tests(&db, run_id)?.len(), 4);
+        let run: Run = db.get_entity(run_id)?.expect("run stored");
+        assert!(run.ended_at.is_some());
+
+        Ok(())
+    }
+}
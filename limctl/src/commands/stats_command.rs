@@ -0,0 +1,410 @@
+//! Stats command
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use comfy_table::{modifiers::UTF8_ROUND_CORNERS, presets::UTF8_FULL, Table};
+use liminalqa_core::{
+    baseline::DriftDetector,
+    entities::{EntityType, Run, Test},
+    report::format_duration_ms,
+    resonance::FlakeDetector,
+    types::{EntityId, TestStatus},
+};
+use liminalqa_db::LiminalDB;
+use std::collections::{BTreeMap, HashSet};
+
+/// Number of entries in each top list
+const TOP: usize = 5;
+
+/// Aggregate health of the runs started since a point in time
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stats {
+    pub since: Option<DateTime<Utc>>,
+    pub runs: usize,
+    pub tests: usize,
+    pub passed: usize,
+    /// Passed tests among those not skipped, in percent; 100 without any
+    pub pass_rate: f64,
+    /// Highest flake scores first, see [`FlakeDetector::calculate_score`]
+    pub flakiest: Vec<FlakyTest>,
+    /// Highest mean duration first
+    pub slowest: Vec<SlowTest>,
+    /// Newest first
+    pub drift_alerts: Vec<DriftAlert>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlakyTest {
+    pub name: String,
+    pub suite: String,
+    pub score: f64,
+    pub executions: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlowTest {
+    pub name: String,
+    pub suite: String,
+    /// Mean duration of the executions that ran, skips left out
+    pub mean_ms: f64,
+    pub executions: usize,
+}
+
+/// An execution slower than its test's baseline allows
+#[derive(Debug, Clone, PartialEq)]
+pub struct DriftAlert {
+    pub name: String,
+    pub suite: String,
+    pub run_id: EntityId,
+    pub at: DateTime<Utc>,
+    pub duration_ms: u64,
+    pub z_score: f64,
+}
+
+/// Parse `--since`: a duration back from now (`30m`, `12h`, `7d`) or an
+/// RFC 3339 timestamp
+pub fn parse_since(s: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(at) = DateTime::parse_from_rfc3339(s) {
+        return Ok(at.with_timezone(&Utc));
+    }
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(|| format!("`{}` has no unit (m, h or d)", s))?;
+    let (amount, unit) = s.split_at(split);
+    let amount: i64 = amount
+        .parse()
+        .map_err(|_| format!("`{}` is not a duration or timestamp", s))?;
+    let duration = match unit {
+        "m" => chrono::Duration::minutes(amount),
+        "h" => chrono::Duration::hours(amount),
+        "d" => chrono::Duration::days(amount),
+        _ => return Err(format!("`{}` has an unknown unit (m, h or d)", s)),
+    };
+    Ok(Utc::now() - duration)
+}
+
+pub async fn execute(db: &LiminalDB, since: Option<DateTime<Utc>>) -> Result<()> {
+    match since {
+        Some(since) => println!(
+            "📈 Health across runs since {}\n",
+            since.format("%Y-%m-%d %H:%M:%S UTC")
+        ),
+        None => println!("📈 Health across all runs\n"),
+    }
+
+    let stats = compute(db, since)?;
+    if stats.runs == 0 {
+        println!("No runs found.");
+        return Ok(());
+    }
+    println!("{}", render(&stats));
+    Ok(())
+}
+
+/// Aggregate the runs started at or after `since`, or all runs
+pub fn compute(db: &LiminalDB, since: Option<DateTime<Utc>>) -> Result<Stats> {
+    let mut run_ids = HashSet::new();
+    for id in db.get_entities_by_type(EntityType::Run)? {
+        if let Some(run) = db.get_entity::<Run>(id)? {
+            if since.is_none_or(|since| run.started_at >= since) {
+                run_ids.insert(run.id);
+            }
+        }
+    }
+
+    let mut by_test: BTreeMap<(String, String), Vec<Test>> = BTreeMap::new();
+    for id in db.get_entities_by_type(EntityType::Test)? {
+        if let Some(test) = db.get_entity::<Test>(id)? {
+            if run_ids.contains(&test.run_id) {
+                by_test
+                    .entry((test.name.clone(), test.suite.clone()))
+                    .or_default()
+                    .push(test);
+            }
+        }
+    }
+
+    let tests: Vec<&Test> = by_test.values().flatten().collect();
+    let total = tests.len();
+    let counted = tests
+        .iter()
+        .filter(|t| t.status != TestStatus::Skip)
+        .count();
+    let passed = tests.iter().filter(|t| t.status.is_pass()).count();
+    let pass_rate = if counted == 0 {
+        100.0
+    } else {
+        passed as f64 * 100.0 / counted as f64
+    };
+
+    let flake_detector = FlakeDetector::default();
+    let drift_detector = DriftDetector::default();
+    let mut flakiest = Vec::new();
+    let mut slowest = Vec::new();
+    let mut drift_alerts = Vec::new();
+    for ((name, suite), executions) in &mut by_test {
+        executions.sort_by_key(|t| t.started_at);
+
+        let statuses: Vec<TestStatus> = executions.iter().map(|t| t.status).collect();
+        let score = flake_detector.calculate_score(&statuses);
+        if score > 0.0 {
+            flakiest.push(FlakyTest {
+                name: name.clone(),
+                suite: suite.clone(),
+                score,
+                executions: executions.len(),
+            });
+        }
+
+        let ran: Vec<f64> = executions
+            .iter()
+            .filter(|t| t.status != TestStatus::Skip)
+            .map(|t| t.duration_ms as f64)
+            .collect();
+        if !ran.is_empty() {
+            slowest.push(SlowTest {
+                name: name.clone(),
+                suite: suite.clone(),
+                mean_ms: ran.iter().sum::<f64>() / ran.len() as f64,
+                executions: ran.len(),
+            });
+        }
+
+        if let Some(baseline) = db.get_baseline(name, suite)? {
+            for test in executions.iter() {
+                if drift_detector.exceeds(test.duration_ms as f64, &baseline) {
+                    let point = drift_detector.data_point(test, Some(&baseline));
+                    drift_alerts.push(DriftAlert {
+                        name: name.clone(),
+                        suite: suite.clone(),
+                        run_id: test.run_id,
+                        at: point.at,
+                        duration_ms: point.duration_ms,
+                        z_score: point.z_score,
+                    });
+                }
+            }
+        }
+    }
+
+    flakiest.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.name.cmp(&b.name)));
+    flakiest.truncate(TOP);
+    slowest.sort_by(|a, b| b.mean_ms.total_cmp(&a.mean_ms).then(a.name.cmp(&b.name)));
+    slowest.truncate(TOP);
+    drift_alerts.sort_by_key(|a| std::cmp::Reverse(a.at));
+    drift_alerts.truncate(TOP);
+
+    Ok(Stats {
+        since,
+        runs: run_ids.len(),
+        tests: total,
+        passed,
+        pass_rate,
+        flakiest,
+        slowest,
+        drift_alerts,
+    })
+}
+
+fn table(header: Vec<&str>) -> Table {
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .apply_modifier(UTF8_ROUND_CORNERS)
+        .set_header(header);
+    table
+}
+
+/// The dashboard printed by `limctl stats`
+pub fn render(stats: &Stats) -> String {
+    let mut out = String::new();
+    out.push_str(&format!("Runs:      {}\n", stats.runs));
+    out.push_str(&format!(
+        "Tests:     {} ({} passed)\n",
+        stats.tests, stats.passed
+    ));
+    out.push_str(&format!("Pass rate: {:.1}%\n", stats.pass_rate));
+
+    out.push_str("\n🎲 Flakiest tests\n");
+    if stats.flakiest.is_empty() {
+        out.push_str("None.\n");
+    } else {
+        let mut flakiest = table(vec!["Test", "Suite", "Flake score", "Executions"]);
+        for test in &stats.flakiest {
+            flakiest.add_row(vec![
+                test.name.clone(),
+                test.suite.clone(),
+                format!("{:.2}", test.score),
+                test.executions.to_string(),
+            ]);
+        }
+        out.push_str(&format!("{flakiest}\n"));
+    }
+
+    out.push_str("\n🐢 Slowest tests\n");
+    let mut slowest = table(vec!["Test", "Suite", "Mean duration", "Executions"]);
+    for test in &stats.slowest {
+        slowest.add_row(vec![
+            test.name.clone(),
+            test.suite.clone(),
+            format_duration_ms(test.mean_ms),
+            test.executions.to_string(),
+        ]);
+    }
+    out.push_str(&format!("{slowest}\n"));
+
+    out.push_str("\n📉 Recent drift alerts\n");
+    if stats.drift_alerts.is_empty() {
+        out.push_str("None.\n");
+    } else {
+        let mut alerts = table(vec!["Test", "Suite", "Run ID", "Started", "Duration", "σ"]);
+        for alert in &stats.drift_alerts {
+            alerts.add_row(vec![
+                alert.name.clone(),
+                alert.suite.clone(),
+                alert.run_id.to_string(),
+                alert.at.format("%Y-%m-%d %H:%M:%S").to_string(),
+                format_duration_ms(alert.duration_ms as f64),
+                format!("{:+.1}", alert.z_score),
+            ]);
+        }
+        out.push_str(&format!("{alerts}\n"));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use liminalqa_core::{baseline::Baseline, temporal::BiTemporalTime};
+    use tempfile::TempDir;
+
+    fn put_run(db: &LiminalDB, started_at: DateTime<Utc>) -> Result<EntityId> {
+        let run = Run {
+            id: EntityId::new(),
+            build_id: EntityId::new(),
+            plan_name: "checkout".to_string(),
+            env: Default::default(),
+            started_at,
+            ended_at: None,
+            runner_version: "test".to_string(),
+            liminal_os_version: None,
+            created_at: BiTemporalTime::now(),
+        };
+        db.put_run(&run)?;
+        Ok(run.id)
+    }
+
+    fn put_test(
+        db: &LiminalDB,
+        run_id: EntityId,
+        name: &str,
+        status: TestStatus,
+        duration_ms: u64,
+        started_at: DateTime<Utc>,
+    ) -> Result<()> {
+        db.put_test(&Test {
+            id: EntityId::new(),
+            run_id,
+            name: name.to_string(),
+            suite: "checkout".to_string(),
+            guidance: String::new(),
+            status,
+            duration_ms,
+            error: None,
+            started_at,
+            completed_at: started_at,
+            created_at: BiTemporalTime::now(),
+        })
+    }
+
+    #[test]
+    fn test_stats_aggregate_across_runs() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+        let now = Utc::now();
+
+        // An old run outside the window: its failures do not count
+        let old = put_run(&db, now - chrono::Duration::days(30))?;
+        put_test(
+            &db,
+            old,
+            "test_pay",
+            TestStatus::Fail,
+            100,
+            now - chrono::Duration::days(30),
+        )?;
+
+        db.upsert_baseline(&Baseline::from_samples(
+            "test_pay",
+            "checkout",
+            &[
+                100.0, 110.0, 90.0, 100.0, 105.0, 95.0, 100.0, 100.0, 110.0, 90.0,
+            ],
+        ))?;
+        let statuses = [TestStatus::Pass, TestStatus::Fail, TestStatus::Pass];
+        for (i, status) in statuses.into_iter().enumerate() {
+            let at = now - chrono::Duration::hours(3 - i as i64);
+            let run = put_run(&db, at)?;
+            // test_pay flips every run and drifts in the last one
+            let pay_ms = if i == 2 { 900 } else { 100 };
+            put_test(&db, run, "test_pay", status, pay_ms, at)?;
+            put_test(&db, run, "test_cart", TestStatus::Pass, 50, at)?;
+            put_test(&db, run, "test_refund", TestStatus::Pass, 2_000, at)?;
+            put_test(&db, run, "test_legacy", TestStatus::Skip, 0, at)?;
+            if i == 0 {
+                put_test(&db, run, "test_refund", TestStatus::Skip, 0, at)?;
+            }
+        }
+
+        let stats = compute(&db, Some(now - chrono::Duration::days(1)))?;
+        assert_eq!(stats.runs, 3);
+        assert_eq!(stats.tests, 13);
+        assert_eq!(stats.passed, 8);
+        // 8 of the 9 tests not skipped
+        assert!((stats.pass_rate - 800.0 / 9.0).abs() < 1e-9);
+
+        assert_eq!(stats.flakiest.len(), 1);
+        assert_eq!(stats.flakiest[0].name, "test_pay");
+        assert_eq!(stats.flakiest[0].executions, 3);
+
+        let slowest: Vec<&str> = stats.slowest.iter().map(|t| t.name.as_str()).collect();
+        // Skipped executions neither rank nor lower the mean
+        assert_eq!(slowest, ["test_refund", "test_pay", "test_cart"]);
+        assert_eq!(stats.slowest[0].mean_ms, 2_000.0);
+        assert_eq!(stats.slowest[0].executions, 3);
+        assert!((stats.slowest[1].mean_ms - 1100.0 / 3.0).abs() < 1e-9);
+
+        assert_eq!(stats.drift_alerts.len(), 1);
+        assert_eq!(stats.drift_alerts[0].name, "test_pay");
+        assert_eq!(stats.drift_alerts[0].duration_ms, 900);
+
+        let dashboard = render(&stats);
+        assert!(dashboard.contains("Runs:      3"));
+        assert!(dashboard.contains("Pass rate: 88.9%"));
+        assert!(dashboard.contains("test_refund"));
+
+        // Without a window the old run counts too
+        let all = compute(&db, None)?;
+        assert_eq!(all.runs, 4);
+        assert_eq!(all.passed, 8);
+        assert_eq!(all.flakiest[0].executions, 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_since() {
+        let since = parse_since("7d").expect("duration");
+        let expected = Utc::now() - chrono::Duration::days(7);
+        assert!((since - expected).num_seconds().abs() < 5);
+        assert_eq!(
+            parse_since("2026-01-01T00:00:00Z").expect("timestamp"),
+            DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z")
+                .expect("valid")
+                .with_timezone(&Utc)
+        );
+        assert!(parse_since("7").is_err());
+        assert!(parse_since("7w").is_err());
+    }
+}
@@ -0,0 +1,357 @@
+//! Tail command: follow a run as its tests and signals arrive
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use liminalqa_core::{
+    entities::{Signal, Test},
+    report::format_duration_ms,
+    types::{EntityId, RunStatus, TestStatus},
+};
+use serde::{de::DeserializeOwned, Deserialize};
+use std::collections::{HashMap, HashSet};
+use std::io::{IsTerminal, Write};
+use std::time::Duration;
+
+/// Something that happened in a tailed run
+#[derive(Debug, Clone)]
+pub enum TailEvent {
+    Test(Test),
+    Signal(Signal),
+    /// The run is no longer running; always the last event
+    Ended(RunStatus),
+}
+
+/// Where the events of a tailed run come from
+#[async_trait]
+pub trait TailSource {
+    /// Events since the previous poll, oldest first
+    async fn poll(&mut self) -> Result<Vec<TailEvent>>;
+}
+
+/// Polls the ingest API for tests and signals of a run not seen yet.
+///
+/// Goes through the server rather than the database, which the server holds
+/// open while the run is live. Signals are read past the last sequence
+/// number seen, so each poll only transfers what is new.
+pub struct HttpTailSource {
+    client: reqwest::Client,
+    url: String,
+    token: Option<String>,
+    run_id: EntityId,
+    seen_tests: HashSet<EntityId>,
+    last_sequence: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct RunStatusResponse {
+    status: RunStatus,
+}
+
+impl HttpTailSource {
+    pub fn new(url: impl Into<String>, token: Option<String>, run_id: EntityId) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: url.into().trim_end_matches('/').to_string(),
+            token,
+            run_id,
+            seen_tests: HashSet::new(),
+            last_sequence: None,
+        }
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        let url = format!("{}{}", self.url, path);
+        let mut request = self.client.get(&url);
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach {}", url))?;
+        let status = response.status();
+        if !status.is_success() {
+            anyhow::bail!("GET {} failed: {}", url, status);
+        }
+        response
+            .json()
+            .await
+            .with_context(|| format!("Invalid response from {}", url))
+    }
+}
+
+#[async_trait]
+impl TailSource for HttpTailSource {
+    async fn poll(&mut self) -> Result<Vec<TailEvent>> {
+        // Read before the tests and signals, so whatever was stored before
+        // the run ended is printed before it exits
+        let status = self
+            .get::<RunStatusResponse>(&format!("/api/runs/{}/status", self.run_id))
+            .await?
+            .status;
+
+        let mut tests: Vec<Test> = self
+            .get(&format!("/api/tests?run_id={}", self.run_id))
+            .await?;
+        tests.retain(|t| !self.seen_tests.contains(&t.id));
+        let mut signals_path = format!("/api/runs/{}/signals", self.run_id);
+        if let Some(after) = self.last_sequence {
+            signals_path.push_str(&format!("?after={}", after));
+        }
+        let signals: Vec<Signal> = self.get(&signals_path).await?;
+
+        self.seen_tests.extend(tests.iter().map(|t| t.id));
+        if let Some(last) = signals.iter().map(|s| s.sequence).max() {
+            self.last_sequence = Some(last);
+        }
+        let mut events: Vec<TailEvent> = tests.into_iter().map(TailEvent::Test).collect();
+        events.extend(signals.into_iter().map(TailEvent::Signal));
+        if status != RunStatus::Running {
+            events.push(TailEvent::Ended(status));
+        }
+        Ok(events)
+    }
+}
+
+pub async fn execute(
+    url: &str,
+    token: Option<String>,
+    run_id_str: &str,
+    interval: Duration,
+) -> Result<()> {
+    let run_id = EntityId::from_string(run_id_str).context("Invalid run ID format")?;
+
+    println!("📡 Tailing run: {}\n", run_id);
+
+    let color = std::io::stdout().is_terminal();
+    let mut source = HttpTailSource::new(url, token, run_id);
+    tail(&mut source, &mut std::io::stdout(), interval, color).await?;
+
+    Ok(())
+}
+
+/// Print the events of `source` every `interval` until the run ends,
+/// returning how it ended. Test lines are colored by status when `color`.
+pub async fn tail(
+    source: &mut impl TailSource,
+    out: &mut impl Write,
+    interval: Duration,
+    color: bool,
+) -> Result<RunStatus> {
+    let mut test_names: HashMap<EntityId, String> = HashMap::new();
+    loop {
+        for event in source.poll().await? {
+            match event {
+                TailEvent::Test(test) => {
+                    writeln!(out, "{}", test_line(&test, color))?;
+                    test_names.insert(test.id, test.name);
+                }
+                TailEvent::Signal(signal) => {
+                    let test = signal.test_id.and_then(|id| test_names.get(&id));
+                    writeln!(out, "{}", signal_line(&signal, test))?;
+                }
+                TailEvent::Ended(status) => {
+                    writeln!(out, "\n🏁 Run {}", status)?;
+                    out.flush()?;
+                    return Ok(status);
+                }
+            }
+        }
+        out.flush()?;
+        tokio::time::sleep(interval).await;
+    }
+}
+
+fn test_line(test: &Test, color: bool) -> String {
+    let (icon, ansi) = match test.status {
+        TestStatus::Pass => ("✓", "32"),
+        TestStatus::Fail | TestStatus::Timeout => ("✗", "31"),
+        _ => ("•", "33"),
+    };
+    let status = format!("{:?}", test.status).to_lowercase();
+    let line = format!(
+        "{} {}/{} {} ({})",
+        icon,
+        test.suite,
+        test.name,
+        status,
+        format_duration_ms(test.duration_ms as f64)
+    );
+    if color {
+        format!("\x1b[{}m{}\x1b[0m", ansi, line)
+    } else {
+        line
+    }
+}
+
+fn signal_line(signal: &Signal, test: Option<&String>) -> String {
+    let kind = format!("{:?}", signal.signal_type).to_lowercase();
+    let mut line = format!("    ↳ {}", kind);
+    if let Some(latency_ms) = signal.latency_ms {
+        line.push_str(&format!(" {}", format_duration_ms(latency_ms as f64)));
+    }
+    match test {
+        Some(name) => line.push_str(&format!(" [{}]", name)),
+        None if signal.is_run_level() => line.push_str(" [run]"),
+        None => {}
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use liminalqa_core::{temporal::BiTemporalTime, types::SignalType};
+    use std::collections::VecDeque;
+
+    /// Hands out scripted batches of events, one per poll
+    struct MockSource {
+        batches: VecDeque<Vec<TailEvent>>,
+        polls: usize,
+    }
+
+    #[async_trait]
+    impl TailSource for MockSource {
+        async fn poll(&mut self) -> Result<Vec<TailEvent>> {
+            self.polls += 1;
+            Ok(self.batches.pop_front().unwrap_or_default())
+        }
+    }
+
+    fn make_test(name: &str, status: TestStatus) -> Test {
+        Test {
+            id: EntityId::new(),
+            run_id: EntityId::new(),
+            name: name.to_string(),
+            suite: "checkout".to_string(),
+            guidance: String::new(),
+            status,
+            duration_ms: 120,
+            error: None,
+            started_at: Utc::now(),
+            completed_at: Utc::now(),
+            created_at: BiTemporalTime::now(),
+        }
+    }
+
+    fn make_signal(test_id: Option<EntityId>) -> Signal {
+        Signal {
+            id: EntityId::new(),
+            run_id: EntityId::new(),
+            test_id,
+            signal_type: SignalType::API,
+            timestamp: Utc::now(),
+            latency_ms: Some(250),
+            payload_ref: None,
+            metadata: Default::default(),
+            created_at: BiTemporalTime::now(),
+            sequence: 0,
+            correlation_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_prints_events_and_exits_on_completion() -> Result<()> {
+        let pay = make_test("test_pay", TestStatus::Pass);
+        let refund = make_test("test_refund", TestStatus::Fail);
+        let mut source = MockSource {
+            batches: VecDeque::from([
+                vec![TailEvent::Test(pay.clone())],
+                vec![],
+                vec![
+                    TailEvent::Signal(make_signal(Some(pay.id))),
+                    TailEvent::Test(refund),
+                ],
+                vec![TailEvent::Ended(RunStatus::Completed)],
+                vec![TailEvent::Test(make_test(
+                    "never_printed",
+                    TestStatus::Pass,
+                ))],
+            ]),
+            polls: 0,
+        };
+
+        let mut out = Vec::new();
+        let status = tail(&mut source, &mut out, Duration::ZERO, false).await?;
+        assert_eq!(status, RunStatus::Completed);
+        assert_eq!(source.polls, 4);
+
+        let out = String::from_utf8(out)?;
+        let lines: Vec<&str> = out.lines().filter(|l| !l.is_empty()).collect();
+        assert_eq!(
+            lines,
+            [
+                "✓ checkout/test_pay pass (120ms)",
+                "    ↳ api 250ms [test_pay]",
+                "✗ checkout/test_refund fail (120ms)",
+                "🏁 Run Completed",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_lines_colored_by_status() {
+        assert!(test_line(&make_test("a", TestStatus::Pass), true).starts_with("\x1b[32m"));
+        assert!(test_line(&make_test("b", TestStatus::Fail), true).starts_with("\x1b[31m"));
+        assert!(test_line(&make_test("c", TestStatus::Skip), true).starts_with("\x1b[33m"));
+        assert_eq!(
+            signal_line(&make_signal(None), None),
+            "    ↳ api 250ms [run]"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_http_source_stops_once_run_completes() -> Result<()> {
+        use liminalqa_core::entities::Run;
+        use liminalqa_db::LiminalDB;
+        use std::sync::Arc;
+
+        let temp_dir = tempfile::TempDir::new()?;
+        let db = Arc::new(LiminalDB::open(temp_dir.path())?);
+        let metrics = Arc::new(liminalqa_core::metrics::MetricsRegistry::new());
+        let app = liminalqa_ingest::app(liminalqa_ingest::AppState::new(db.clone(), None, metrics));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}", listener.local_addr()?);
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let mut run = Run {
+            id: EntityId::new(),
+            build_id: EntityId::new(),
+            plan_name: "smoke".to_string(),
+            env: Default::default(),
+            started_at: Utc::now(),
+            ended_at: None,
+            runner_version: "test".to_string(),
+            liminal_os_version: None,
+            created_at: BiTemporalTime::now(),
+        };
+        db.put_run(&run)?;
+        let mut test = make_test("test_pay", TestStatus::Pass);
+        test.run_id = run.id;
+        db.put_test(&test)?;
+
+        // The server holds the database open; the source never opens it
+        let mut source = HttpTailSource::new(url, None, run.id);
+        let events = source.poll().await?;
+        assert!(matches!(events.as_slice(), [TailEvent::Test(t)] if t.id == test.id));
+        assert!(source.poll().await?.is_empty());
+
+        let mut signal = make_signal(Some(test.id));
+        signal.run_id = run.id;
+        db.put_signal(&signal)?;
+        let events = source.poll().await?;
+        assert!(matches!(events.as_slice(), [TailEvent::Signal(s)] if s.id == signal.id));
+        assert!(source.poll().await?.is_empty());
+
+        run.ended_at = Some(Utc::now());
+        db.put_run(&run)?;
+        let events = source.poll().await?;
+        assert!(matches!(
+            events.as_slice(),
+            [TailEvent::Ended(RunStatus::Completed)]
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,202 @@
+//! Validate command
+
+use anyhow::{Context, Result};
+use liminalqa_runner::Observable;
+use serde_yaml::{Mapping, Value};
+use std::collections::{hash_map::Entry, HashMap};
+use std::fs;
+use std::path::Path;
+
+/// Check a plan without running it, printing every problem found
+pub async fn execute(plan_path: &Path) -> Result<()> {
+    println!("🔍 Validating test plan: {}", plan_path.display());
+
+    let content = fs::read_to_string(plan_path).context(format!(
+        "Failed to read test plan file: {}",
+        plan_path.display()
+    ))?;
+
+    let errors = validate_plan(&content);
+    if errors.is_empty() {
+        println!("✅ Plan is valid");
+        return Ok(());
+    }
+
+    for error in &errors {
+        println!("   ✗ {}", error);
+    }
+    anyhow::bail!("Plan {} has {} error(s)", plan_path.display(), errors.len())
+}
+
+/// Every structural problem of a plan, each prefixed with where it is (e.g.
+/// `tests[1].observables[0].type`). Empty when the plan is valid.
+pub fn validate_plan(content: &str) -> Vec<String> {
+    let plan: Value = match serde_yaml::from_str(content) {
+        Ok(plan) => plan,
+        Err(e) => return vec![format!("invalid YAML: {}", e)],
+    };
+    let Some(plan) = plan.as_mapping() else {
+        return vec!["plan must be a mapping".to_string()];
+    };
+
+    let mut errors = Vec::new();
+    require_string(plan, "name", "name", &mut errors);
+    match plan.get("parallelism") {
+        None => {}
+        Some(Value::Number(n)) if n.as_u64().is_some_and(|n| n > 0) => {}
+        Some(_) => errors.push("parallelism: must be a positive integer".to_string()),
+    }
+
+    let tests = match plan.get("tests") {
+        Some(Value::Sequence(tests)) => tests,
+        Some(_) => {
+            errors.push("tests: must be a list".to_string());
+            return errors;
+        }
+        None => {
+            errors.push("tests: missing required field".to_string());
+            return errors;
+        }
+    };
+
+    // Test name -> index of its first definition
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    for (i, test) in tests.iter().enumerate() {
+        let path = format!("tests[{}]", i);
+        let Some(test) = test.as_mapping() else {
+            errors.push(format!("{}: must be a mapping", path));
+            continue;
+        };
+        for field in ["name", "suite", "guidance"] {
+            require_string(test, field, &format!("{}.{}", path, field), &mut errors);
+        }
+        if let Some(name) = test.get("name").and_then(Value::as_str) {
+            match seen.entry(name) {
+                Entry::Occupied(first) => errors.push(format!(
+                    "{}.name: duplicate test name '{}' (first defined at tests[{}])",
+                    path,
+                    name,
+                    first.get()
+                )),
+                Entry::Vacant(slot) => {
+                    slot.insert(i);
+                }
+            }
+        }
+        match test.get("observables") {
+            None => {}
+            Some(Value::Sequence(observables)) => {
+                for (j, observable) in observables.iter().enumerate() {
+                    validate_observable(
+                        observable,
+                        &format!("{}.observables[{}]", path, j),
+                        &mut errors,
+                    );
+                }
+            }
+            Some(_) => errors.push(format!("{}.observables: must be a list", path)),
+        }
+    }
+
+    errors
+}
+
+fn validate_observable(observable: &Value, path: &str, errors: &mut Vec<String>) {
+    let Some(observable) = observable.as_mapping() else {
+        errors.push(format!("{}: must be a mapping", path));
+        return;
+    };
+    let Some(kind) = observable.get("type").and_then(Value::as_str) else {
+        errors.push(format!("{}.type: missing required field", path));
+        return;
+    };
+    let Some(fields) = Observable::required_plan_fields(kind) else {
+        let known: Vec<&str> = Observable::PLAN_TYPES.iter().map(|(t, _)| *t).collect();
+        errors.push(format!(
+            "{}.type: unknown observable type '{}' (expected one of: {})",
+            path,
+            kind,
+            known.join(", ")
+        ));
+        return;
+    };
+    for field in fields {
+        if !observable.contains_key(*field) {
+            errors.push(format!(
+                "{}.{}: missing required field for '{}'",
+                path, field, kind
+            ));
+        }
+    }
+}
+
+fn require_string(map: &Mapping, field: &str, path: &str, errors: &mut Vec<String>) {
+    match map.get(field) {
+        Some(Value::String(s)) if !s.trim().is_empty() => {}
+        Some(Value::String(_)) => errors.push(format!("{}: must not be empty", path)),
+        Some(_) => errors.push(format!("{}: must be a string", path)),
+        None => errors.push(format!("{}: missing required field", path)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reports_every_error_at_once() {
+        let plan = "
+name: checkout
+tests:
+  - name: test_pay
+    suite: checkout
+    guidance: Payment succeeds
+    observables:
+      - type: api_status
+        endpoint: /api/pay
+        status: 200
+  - name: test_pay
+    suite: checkout
+    guidance: Payment succeeds again
+    observables:
+      - type: api_stauts
+        endpoint: /api/pay
+      - type: ui_visible
+  - suite: checkout
+    guidance: Nameless
+";
+        let errors = validate_plan(plan);
+        assert_eq!(
+            errors,
+            [
+                "tests[1].name: duplicate test name 'test_pay' (first defined at tests[0])",
+                "tests[1].observables[0].type: unknown observable type 'api_stauts' \
+                 (expected one of: ui_visible, ui_contains_text, api_status, ws_message, \
+                 grpc_success, custom)",
+                "tests[1].observables[1].selector: missing required field for 'ui_visible'",
+                "tests[2].name: missing required field",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_valid_plan_has_no_errors() {
+        let plan = "
+name: smoke
+parallelism: 2
+tests:
+  - name: test_login
+    suite: auth
+    guidance: User can log in
+    observables:
+      - type: ui_visible
+        selector: login-button
+";
+        assert!(validate_plan(plan).is_empty());
+        assert_eq!(validate_plan("tests: [").len(), 1);
+        assert_eq!(
+            validate_plan("name: smoke\nparallelism: 0\ntests: []\n"),
+            ["parallelism: must be a positive integer"]
+        );
+    }
+}
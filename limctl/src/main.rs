@@ -2,11 +2,20 @@
 //!
 //! Usage:
 //!   limctl run <plan.yaml>       — Execute test plan
+//!     [--min-pass-rate N] [--allow-flaky]  — Exit 2 below N% pass rate
+//!     [--resume <run-id>]                   — Continue an interrupted run
 //!   limctl collect <run-id>      — Collect artifacts from run
 //!   limctl report <run-id>       — Generate reflection report
+//!   limctl tail <run-id>         — Follow a run until it ends
+//!     [--interval-ms N]                     — Poll every N milliseconds
+//!     [--url URL] [--token T]               — Ingest server to read from
+//!   limctl stats                — Aggregate health across runs
+//!     [--since 7d]                          — Only runs started since then
 //!   limctl query <query.json>    — Query LIMINAL-DB
+//!     [--output table|json|ndjson]
 //!   limctl list runs             — List all runs
 //!   limctl list tests <run-id>   — List tests for a run
+//!   limctl reindex               — Rebuild secondary indexes
 
 use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
@@ -47,6 +56,24 @@ enum Commands {
     Run {
         /// Path to test plan YAML
         plan: PathBuf,
+
+        /// Exit with code 2 if the pass rate (in percent) falls below this
+        #[arg(long, value_parser = parse_percentage)]
+        min_pass_rate: Option<f64>,
+
+        /// Leave quarantined (flaky) tests out of the pass rate
+        #[arg(long, requires = "min_pass_rate")]
+        allow_flaky: bool,
+
+        /// Continue an interrupted run, skipping tests it already completed
+        #[arg(long, value_name = "RUN_ID")]
+        resume: Option<String>,
+    },
+
+    /// Check a test plan YAML without running it
+    Validate {
+        /// Path to test plan YAML
+        plan: PathBuf,
     },
 
     /// Collect artifacts from a run
@@ -69,10 +96,47 @@ enum Commands {
         output: Option<PathBuf>,
     },
 
+    /// Print tests and signals of a run as they arrive, until it ends.
+    /// Reads from the ingest server, which holds the database while the run
+    /// is live.
+    Tail {
+        /// Run ID
+        run_id: String,
+
+        /// Base URL of the ingest server
+        #[arg(
+            long,
+            env = "LIMINAL_INGEST_URL",
+            default_value = "http://localhost:8080"
+        )]
+        url: String,
+
+        /// Bearer token for the ingest server
+        #[arg(long, env = "LIMINAL_AUTH_TOKEN")]
+        token: Option<String>,
+
+        /// How often to look for new tests and signals
+        #[arg(long, default_value_t = 1000)]
+        interval_ms: u64,
+    },
+
+    /// Print aggregate health across runs: pass rate, flakiest and
+    /// slowest tests, recent drift alerts
+    Stats {
+        /// Only runs started since then: a duration back from now (`30m`,
+        /// `12h`, `7d`) or an RFC 3339 timestamp
+        #[arg(long, value_parser = stats_command::parse_since)]
+        since: Option<chrono::DateTime<chrono::Utc>>,
+    },
+
     /// Query LIMINAL-DB
     Query {
         /// Query JSON file
         query: PathBuf,
+
+        /// Output encoding of the matching facts
+        #[arg(short, long, default_value = "table")]
+        output: QueryOutput,
     },
 
     /// List entities
@@ -81,6 +145,29 @@ enum Commands {
         entity: ListEntity,
     },
 
+    /// Delete entities and everything attached to them
+    Delete {
+        #[command(subcommand)]
+        entity: DeleteEntity,
+    },
+
+    /// Rebuild secondary indexes from primary data
+    Reindex {
+        /// Signal metadata keys to index (comma-separated)
+        #[arg(long, env = "LIMINAL_SIGNAL_INDEX_KEYS", value_delimiter = ',')]
+        signal_index_keys: Vec<String>,
+    },
+
+    /// Recompute flake resonance for every test
+    Rescore {
+        /// Number of most recent executions scored per test
+        #[arg(long, default_value_t = 10)]
+        window: usize,
+        /// Flip rate above which a test is flagged as flaky
+        #[arg(long, default_value_t = 0.3)]
+        threshold: f64,
+    },
+
     /// Initialize a new LiminalQA project
     Init {
         /// Project directory
@@ -104,6 +191,15 @@ enum ListEntity {
     Systems,
 }
 
+#[derive(Subcommand)]
+enum DeleteEntity {
+    /// Delete a run with its tests, signals, artifacts and facts
+    Run {
+        /// Run ID
+        run_id: String,
+    },
+}
+
 #[derive(Debug, Clone, clap::ValueEnum)]
 enum ReportFormat {
     Html,
@@ -111,6 +207,25 @@ enum ReportFormat {
     Markdown,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum QueryOutput {
+    /// Human-readable columns
+    Table,
+    /// Pretty-printed JSON array
+    Json,
+    /// One JSON fact per line, for piping
+    Ndjson,
+}
+
+fn parse_percentage(s: &str) -> Result<f64, String> {
+    let value: f64 = s.parse().map_err(|_| format!("`{}` is not a number", s))?;
+    if (0.0..=100.0).contains(&value) {
+        Ok(value)
+    } else {
+        Err(format!("`{}` is not between 0 and 100", s))
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
@@ -130,14 +245,43 @@ async fn main() -> Result<()> {
         .finish();
     tracing::subscriber::set_global_default(subscriber)?;
 
+    // Tailing goes through the ingest server, which holds the database
+    if let Commands::Tail {
+        run_id,
+        url,
+        token,
+        interval_ms,
+    } = cli.command
+    {
+        let interval = std::time::Duration::from_millis(interval_ms);
+        return tail_command::execute(&url, token, &run_id, interval).await;
+    }
+
     // Open database
     let db = LiminalDB::open(&cli.db_path)
         .context(format!("Failed to open database at {:?}", cli.db_path))?;
 
     // Execute command
     match cli.command {
-        Commands::Run { plan } => {
-            run_command::execute(&db, &plan).await?;
+        Commands::Run {
+            plan,
+            min_pass_rate,
+            allow_flaky,
+            resume,
+        } => {
+            let gate = min_pass_rate.map(|min_pass_rate| run_command::PassRateGate {
+                min_pass_rate,
+                allow_flaky,
+            });
+            let verdict =
+                run_command::execute(&db, &plan, gate.as_ref(), resume.as_deref()).await?;
+            if verdict.is_some_and(|v| !v.passed) {
+                db.flush()?;
+                std::process::exit(run_command::PASS_RATE_EXIT_CODE);
+            }
+        }
+        Commands::Validate { plan } => {
+            validate_command::execute(&plan).await?;
         }
         Commands::Collect { run_id } => {
             collect_command::execute(&db, &run_id).await?;
@@ -149,8 +293,12 @@ async fn main() -> Result<()> {
         } => {
             report_command::execute(&db, &run_id, format, output).await?;
         }
-        Commands::Query { query } => {
-            query_command::execute(&db, &query).await?;
+        Commands::Tail { .. } => unreachable!("handled before opening the database"),
+        Commands::Stats { since } => {
+            stats_command::execute(&db, since).await?;
+        }
+        Commands::Query { query, output } => {
+            query_command::execute(&db, &query, output).await?;
         }
         Commands::List { entity } => match entity {
             ListEntity::Runs => {
@@ -163,6 +311,18 @@ async fn main() -> Result<()> {
                 list_systems_command::execute(&db).await?;
             }
         },
+        Commands::Delete { entity } => match entity {
+            DeleteEntity::Run { run_id } => {
+                delete_command::execute_run(&db, &run_id).await?;
+            }
+        },
+        Commands::Reindex { signal_index_keys } => {
+            let db = db.with_indexed_signal_meta_keys(signal_index_keys);
+            reindex_command::execute(&db).await?;
+        }
+        Commands::Rescore { window, threshold } => {
+            rescore_command::execute(&db, window, threshold).await?;
+        }
         Commands::Init { directory } => {
             init_command::execute(&directory).await?;
         }
@@ -1,3 +1,87 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::{
+    entities::{Signal, Test},
+    types::{EntityId, SignalType},
+};
+
+/// Duration baseline of one test (name + suite), from its recent executions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Baseline {
+    pub name: String,
+    pub suite: String,
+    pub mean_ms: f64,
+    pub stddev_ms: f64,
+    /// Number of executions the statistics were computed from
+    pub sample_size: usize,
+    /// Computed from too few samples to be trusted; drift detection skips
+    /// provisional baselines. Set by the database on upsert.
+    pub provisional: bool,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Baseline {
+    /// Baseline of `durations_ms`, not yet marked provisional
+    pub fn from_samples(
+        name: impl Into<String>,
+        suite: impl Into<String>,
+        durations_ms: &[f64],
+    ) -> Self {
+        let (mean_ms, stddev_ms) = DriftDetector::default().calculate_stats(durations_ms);
+        Self {
+            name: name.into(),
+            suite: suite.into(),
+            mean_ms,
+            stddev_ms,
+            sample_size: durations_ms.len(),
+            provisional: false,
+            updated_at: Utc::now(),
+        }
+    }
+}
+
+/// Range of durations a baseline considers normal: mean ± `sigma` stddevs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BaselineBand {
+    pub mean_ms: f64,
+    pub stddev_ms: f64,
+    pub sigma: f64,
+    pub lower_ms: f64,
+    pub upper_ms: f64,
+    pub sample_size: usize,
+    pub provisional: bool,
+}
+
+/// One execution of a test, placed against its baseline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DriftDataPoint {
+    pub test_id: EntityId,
+    pub run_id: EntityId,
+    pub at: DateTime<Utc>,
+    pub duration_ms: u64,
+    /// Distance from the baseline mean in stddevs; 0 without a baseline
+    pub z_score: f64,
+    /// See [`DriftDetector::is_drift_from`]
+    pub drift: bool,
+}
+
+/// A signal far slower than the latency baseline of its test and signal
+/// type, recorded as a `:signal/latency_anomaly` fact on the signal
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LatencyAnomaly {
+    pub signal_type: SignalType,
+    pub latency_ms: u64,
+    pub mean_ms: f64,
+    pub stddev_ms: f64,
+    pub z_score: f64,
+    pub sigma: f64,
+}
+
+/// Drift threshold used when nothing else is configured, in stddevs
+pub const DEFAULT_SIGMA_THRESHOLD: f64 = 2.0;
+
 pub struct DriftDetector {
     sigma_threshold: f64,
 }
@@ -5,16 +89,112 @@ pub struct DriftDetector {
 impl Default for DriftDetector {
     fn default() -> Self {
         Self {
-            sigma_threshold: 2.0,
+            sigma_threshold: DEFAULT_SIGMA_THRESHOLD,
         }
     }
 }
 
+/// Drift thresholds per suite, falling back to a default for suites not
+/// listed. Noisy suites (UI, end-to-end) can be given a wider band than
+/// unit suites.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DriftThresholds {
+    pub default_sigma: f64,
+    pub per_suite: HashMap<String, f64>,
+}
+
+impl Default for DriftThresholds {
+    fn default() -> Self {
+        Self::new(DEFAULT_SIGMA_THRESHOLD)
+    }
+}
+
+impl DriftThresholds {
+    pub fn new(default_sigma: f64) -> Self {
+        Self {
+            default_sigma,
+            per_suite: HashMap::new(),
+        }
+    }
+
+    /// Use `sigma` for the tests of `suite`
+    pub fn with_suite(mut self, suite: impl Into<String>, sigma: f64) -> Self {
+        self.per_suite.insert(suite.into(), sigma);
+        self
+    }
+
+    /// Parse `suite=sigma` pairs separated by commas (`ui=3.5,unit=1.5`) on
+    /// top of `default_sigma`. Thresholds must be positive.
+    pub fn parse(default_sigma: f64, spec: &str) -> anyhow::Result<Self> {
+        let mut thresholds = Self::new(default_sigma);
+        for entry in spec.split(',').filter(|e| !e.trim().is_empty()) {
+            let (suite, sigma) = entry
+                .split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("Invalid drift threshold entry: {}", entry))?;
+            let sigma: f64 = sigma
+                .trim()
+                .parse()
+                .map_err(|e| anyhow::anyhow!("Invalid drift threshold for {}: {}", suite, e))?;
+            if !(sigma.is_finite() && sigma > 0.0) {
+                anyhow::bail!("Drift threshold for {} must be positive", suite.trim());
+            }
+            thresholds = thresholds.with_suite(suite.trim(), sigma);
+        }
+        Ok(thresholds)
+    }
+
+    pub fn sigma_for(&self, suite: &str) -> f64 {
+        self.per_suite
+            .get(suite)
+            .copied()
+            .unwrap_or(self.default_sigma)
+    }
+
+    /// Detector judging the tests of `suite`
+    pub fn detector_for(&self, suite: &str) -> DriftDetector {
+        DriftDetector::new(self.sigma_for(suite))
+    }
+}
+
 impl DriftDetector {
     pub fn new(sigma_threshold: f64) -> Self {
         Self { sigma_threshold }
     }
 
+    pub fn sigma_threshold(&self) -> f64 {
+        self.sigma_threshold
+    }
+
+    /// The band outside which a duration drifts from `baseline`. A negative
+    /// lower bound is clamped to zero.
+    pub fn band(&self, baseline: &Baseline) -> BaselineBand {
+        let spread = self.sigma_threshold * baseline.stddev_ms;
+        BaselineBand {
+            mean_ms: baseline.mean_ms,
+            stddev_ms: baseline.stddev_ms,
+            sigma: self.sigma_threshold,
+            lower_ms: (baseline.mean_ms - spread).max(0.0),
+            upper_ms: baseline.mean_ms + spread,
+            sample_size: baseline.sample_size,
+            provisional: baseline.provisional,
+        }
+    }
+
+    /// `test` as a point of its drift series against `baseline`
+    pub fn data_point(&self, test: &Test, baseline: Option<&Baseline>) -> DriftDataPoint {
+        let duration = test.duration_ms as f64;
+        DriftDataPoint {
+            test_id: test.id,
+            run_id: test.run_id,
+            at: test.started_at,
+            duration_ms: test.duration_ms,
+            z_score: baseline.map_or(0.0, |b| {
+                self.calculate_z_score(duration, b.mean_ms, b.stddev_ms)
+            }),
+            drift: baseline.is_some_and(|b| self.is_drift_from(duration, b)),
+        }
+    }
+
     pub fn calculate_z_score(&self, current: f64, mean: f64, stddev: f64) -> f64 {
         if stddev == 0.0 {
             return 0.0;
@@ -26,6 +206,39 @@ impl DriftDetector {
         self.calculate_z_score(current, mean, stddev).abs() > self.sigma_threshold
     }
 
+    /// Whether `current` drifted from `baseline`; never for provisional
+    /// baselines
+    pub fn is_drift_from(&self, current: f64, baseline: &Baseline) -> bool {
+        !baseline.provisional && self.is_drift(current, baseline.mean_ms, baseline.stddev_ms)
+    }
+
+    /// Whether `current` lies more than the threshold above `baseline`;
+    /// never for provisional baselines
+    pub fn exceeds(&self, current: f64, baseline: &Baseline) -> bool {
+        current > baseline.mean_ms && self.is_drift_from(current, baseline)
+    }
+
+    /// The anomaly of `signal`'s latency against `baseline`, `None` without
+    /// a latency or when it is not exceeded
+    pub fn latency_anomaly(&self, signal: &Signal, baseline: &Baseline) -> Option<LatencyAnomaly> {
+        let latency_ms = signal.latency_ms?;
+        if !self.exceeds(latency_ms as f64, baseline) {
+            return None;
+        }
+        Some(LatencyAnomaly {
+            signal_type: signal.signal_type,
+            latency_ms,
+            mean_ms: baseline.mean_ms,
+            stddev_ms: baseline.stddev_ms,
+            z_score: self.calculate_z_score(
+                latency_ms as f64,
+                baseline.mean_ms,
+                baseline.stddev_ms,
+            ),
+            sigma: self.sigma_threshold,
+        })
+    }
+
     pub fn calculate_stats(&self, history: &[f64]) -> (f64, f64) {
         if history.is_empty() {
             return (0.0, 0.0);
@@ -78,4 +291,67 @@ mod tests {
         // 75 is -2.5 sigma -> Drift (abs)
         assert!(detector.is_drift(75.0, mean, stddev));
     }
+
+    #[test]
+    fn test_only_slower_latencies_are_anomalies() {
+        let detector = DriftDetector::new(3.0);
+        let baseline = Baseline::from_samples("t", "s", &[90.0, 100.0, 110.0]);
+        let signal = |latency_ms| Signal {
+            id: EntityId::new(),
+            run_id: EntityId::new(),
+            test_id: None,
+            signal_type: SignalType::API,
+            timestamp: Utc::now(),
+            latency_ms,
+            payload_ref: None,
+            metadata: Default::default(),
+            created_at: crate::temporal::BiTemporalTime::now(),
+            sequence: 0,
+            correlation_id: None,
+        };
+
+        let anomaly = detector
+            .latency_anomaly(&signal(Some(150)), &baseline)
+            .expect("5 sigma slower");
+        assert_eq!(anomaly.z_score, 5.0);
+        assert_eq!(anomaly.signal_type, SignalType::API);
+
+        assert!(detector
+            .latency_anomaly(&signal(Some(120)), &baseline)
+            .is_none());
+        assert!(detector
+            .latency_anomaly(&signal(Some(10)), &baseline)
+            .is_none());
+        assert!(detector.latency_anomaly(&signal(None), &baseline).is_none());
+    }
+
+    #[test]
+    fn test_suite_thresholds_judge_same_z_score_differently() {
+        let thresholds = DriftThresholds::parse(2.0, "unit=1.5, ui=3").unwrap();
+        let baseline = Baseline::from_samples("t", "s", &[90.0, 100.0, 110.0]);
+
+        // 125ms is 2.5 sigma above the mean
+        assert!(thresholds
+            .detector_for("unit")
+            .is_drift_from(125.0, &baseline));
+        assert!(thresholds
+            .detector_for("api")
+            .is_drift_from(125.0, &baseline));
+        assert!(!thresholds
+            .detector_for("ui")
+            .is_drift_from(125.0, &baseline));
+
+        assert!(DriftThresholds::parse(2.0, "ui").is_err());
+        assert!(DriftThresholds::parse(2.0, "ui=-1").is_err());
+    }
+
+    #[test]
+    fn test_provisional_baseline_never_drifts() {
+        let detector = DriftDetector::new(2.0);
+        let mut baseline = Baseline::from_samples("t", "s", &[90.0, 100.0, 110.0]);
+        assert!(detector.is_drift_from(200.0, &baseline));
+
+        baseline.provisional = true;
+        assert!(!detector.is_drift_from(200.0, &baseline));
+    }
 }
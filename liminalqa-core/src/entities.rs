@@ -106,6 +106,8 @@ pub struct Test {
     pub suite: String,
     pub guidance: String, // Test intention
     pub status: TestStatus,
+    /// Supported up to [`MAX_DURATION_MS`], the range of the Postgres
+    /// `bigint` column
     pub duration_ms: u64,
     pub error: Option<TestError>,
     pub started_at: chrono::DateTime<chrono::Utc>,
@@ -113,6 +115,17 @@ pub struct Test {
     pub created_at: BiTemporalTime,
 }
 
+/// Longest test duration that survives every store, `i64::MAX` ms
+pub const MAX_DURATION_MS: u64 = i64::MAX as u64;
+
+impl Test {
+    /// `duration_ms` as a signed integer for stores and reports that use
+    /// one, saturating at [`MAX_DURATION_MS`]
+    pub fn duration_ms_i64(&self) -> i64 {
+        i64::try_from(self.duration_ms).unwrap_or(i64::MAX)
+    }
+}
+
 impl Entity for Test {
     fn id(&self) -> EntityId {
         self.id
@@ -146,6 +159,21 @@ pub enum ArtifactType {
     Trace,
 }
 
+impl ArtifactType {
+    /// Parse an artifact kind label. Unknown kinds are treated as traces.
+    pub fn from_label(label: &str) -> Self {
+        match label.to_lowercase().as_str() {
+            "screenshot" => Self::Screenshot,
+            "apiresponse" => Self::ApiResponse,
+            "wsmessage" => Self::WsMessage,
+            "grpctrace" => Self::GrpcTrace,
+            "log" => Self::Log,
+            "video" => Self::Video,
+            _ => Self::Trace,
+        }
+    }
+}
+
 impl Entity for Artifact {
     fn id(&self) -> EntityId {
         self.id
@@ -155,18 +183,54 @@ impl Entity for Artifact {
     }
 }
 
+/// Progress reported by a test before it finishes (stored as a
+/// `:test/progress` fact on the test)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TestProgress {
+    pub test_id: EntityId,
+    pub run_id: EntityId,
+    /// Phase just completed, e.g. "login" or "checkout"
+    pub phase: String,
+    pub percent: Option<u8>,
+    pub message: Option<String>,
+    pub at: chrono::DateTime<chrono::Utc>,
+}
+
 /// Signal (UI/API/WS/gRPC observation)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Signal {
     pub id: EntityId,
     pub run_id: EntityId,
-    pub test_id: EntityId,
+    /// `None` for run-level signals (infra events, dropped DB connections)
+    /// that are not tied to one test and apply to the whole run
+    #[serde(default)]
+    pub test_id: Option<EntityId>,
     pub signal_type: SignalType,
     pub timestamp: chrono::DateTime<chrono::Utc>,
     pub latency_ms: Option<u64>,
     pub payload_ref: Option<ArtifactRef>,
-    pub metadata: std::collections::HashMap<String, serde_json::Value>,
+    pub metadata: std::collections::BTreeMap<String, serde_json::Value>,
     pub created_at: BiTemporalTime,
+    /// Position in its run's ingest order, assigned by the database when
+    /// the signal is stored. Breaks ties between equal timestamps.
+    #[serde(default)]
+    pub sequence: u64,
+    /// Shared by signals of one logical operation across services, to stitch
+    /// them together
+    #[serde(default)]
+    pub correlation_id: Option<String>,
+}
+
+impl Signal {
+    /// Sort key ordering signals by timestamp, then by ingest sequence
+    pub fn ordering_key(&self) -> (chrono::DateTime<chrono::Utc>, u64) {
+        (self.timestamp, self.sequence)
+    }
+
+    /// Whether the signal applies to the whole run rather than one test
+    pub fn is_run_level(&self) -> bool {
+        self.test_id.is_none()
+    }
 }
 
 impl Entity for Signal {
@@ -182,6 +246,9 @@ impl Entity for Signal {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Resonance {
     pub id: EntityId,
+    /// [`PatternDetector::name`](crate::resonance::PatternDetector::name) of
+    /// the detector that found the pattern
+    pub detector: String,
     pub pattern: ResonancePattern,
     pub affected_tests: Vec<EntityId>,
     pub root_cause: Option<String>,
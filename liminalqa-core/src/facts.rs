@@ -11,16 +11,15 @@ pub struct Fact {
     pub attribute: Attribute,
     pub value: Value,
     pub time: BiTemporalTime,
+    /// Tombstone: every fact with the same [`FactKey`] recorded up to this
+    /// one's transaction time no longer holds from then on
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub retracted: bool,
 }
 
 impl Fact {
     pub fn new(entity_id: EntityId, attribute: Attribute, value: Value) -> Self {
-        Self {
-            entity_id,
-            attribute,
-            value,
-            time: BiTemporalTime::now(),
-        }
+        Self::with_time(entity_id, attribute, value, BiTemporalTime::now())
     }
 
     pub fn with_time(
@@ -34,10 +33,68 @@ impl Fact {
             attribute,
             value,
             time,
+            retracted: false,
+        }
+    }
+
+    /// Tombstone retracting `key` at `time`
+    pub fn tombstone(key: FactKey, time: BiTemporalTime) -> Self {
+        Self {
+            entity_id: key.entity_id,
+            attribute: key.attribute,
+            value: Value::Null,
+            time,
+            retracted: true,
+        }
+    }
+
+    pub fn key(&self) -> FactKey {
+        FactKey {
+            entity_id: self.entity_id,
+            attribute: self.attribute.clone(),
         }
     }
 }
 
+/// What a fact is about: one attribute of one entity. Later facts with the
+/// same key supersede earlier ones.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FactKey {
+    pub entity_id: EntityId,
+    pub attribute: Attribute,
+}
+
+impl FactKey {
+    pub fn new(entity_id: EntityId, attribute: Attribute) -> Self {
+        Self {
+            entity_id,
+            attribute,
+        }
+    }
+}
+
+/// Namespaces of the predefined attributes, which custom attributes may
+/// not use
+pub const RESERVED_NAMESPACES: &[&str] = &[
+    "test",
+    "ui",
+    "api",
+    "ws",
+    "grpc",
+    "signal",
+    "run",
+    "resonance",
+];
+
+/// Why a custom attribute was rejected
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum AttributeError {
+    #[error("custom attribute `{0}` must be namespaced as `namespace/name`")]
+    MissingNamespace(String),
+    #[error("custom attribute `{0}` uses the reserved namespace `{1}`")]
+    ReservedNamespace(String, String),
+}
+
 /// Predefined attributes (extensible via custom namespace)
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Attribute {
@@ -50,6 +107,20 @@ pub enum Attribute {
     TestError,
     #[serde(rename = ":test/guidance")]
     TestGuidance,
+    #[serde(rename = ":test/progress")]
+    TestProgress,
+    /// Fraction of the guidance observables a test met, in `0..=1`
+    #[serde(rename = ":test/alignment")]
+    TestAlignment,
+    /// Team or person responsible for a test
+    #[serde(rename = ":test/owner")]
+    TestOwner,
+    /// Longest a test is expected to take, in milliseconds
+    #[serde(rename = ":test/sla_ms")]
+    TestSla,
+    /// Attempts the test took, more than one when it was retried
+    #[serde(rename = ":test/attempts")]
+    TestAttempts,
 
     // UI attributes
     #[serde(rename = ":ui/screenshot")]
@@ -81,6 +152,14 @@ pub enum Attribute {
     #[serde(rename = ":grpc/latency")]
     GrpcLatency,
 
+    // Signal attributes
+    /// Latency far above the baseline of the signal's test and type
+    #[serde(rename = ":signal/latency_anomaly")]
+    SignalLatencyAnomaly,
+    /// Required metadata keys the signal's kind expects but it lacks
+    #[serde(rename = ":signal/schema_violation")]
+    SignalSchemaViolation,
+
     // Run attributes
     #[serde(rename = ":run/env")]
     RunEnv,
@@ -90,17 +169,89 @@ pub enum Attribute {
     RunEndedAt,
     #[serde(rename = ":run/status")]
     RunStatus,
+    /// Free-form CI metadata of the run (PR number, commit message, ...)
+    #[serde(rename = ":run/annotations")]
+    RunAnnotations,
 
     // Resonance attributes
     #[serde(rename = ":resonance/pattern")]
     ResonancePattern,
     #[serde(rename = ":resonance/score")]
     ResonanceScore,
+    /// Set once the pattern stopped holding; the value names the detector
+    #[serde(rename = ":resonance/resolved")]
+    ResonanceResolved,
 
     // Custom attribute
+    /// Namespaced as `namespace/name` (optionally with a leading `:`),
+    /// outside the [`RESERVED_NAMESPACES`]; build with [`Attribute::custom`]
     Custom(String),
 }
 
+impl Attribute {
+    /// Every predefined attribute
+    pub const BUILT_IN: &'static [Attribute] = &[
+        Self::TestStatus,
+        Self::TestDuration,
+        Self::TestError,
+        Self::TestGuidance,
+        Self::TestProgress,
+        Self::TestAlignment,
+        Self::TestOwner,
+        Self::TestSla,
+        Self::TestAttempts,
+        Self::UiScreenshot,
+        Self::UiInteraction,
+        Self::ApiResponse,
+        Self::ApiStatusCode,
+        Self::ApiLatency,
+        Self::WsMessage,
+        Self::WsLatency,
+        Self::WsConnectionState,
+        Self::GrpcMethod,
+        Self::GrpcStatus,
+        Self::GrpcLatency,
+        Self::SignalLatencyAnomaly,
+        Self::SignalSchemaViolation,
+        Self::RunEnv,
+        Self::RunStartedAt,
+        Self::RunEndedAt,
+        Self::RunStatus,
+        Self::RunAnnotations,
+        Self::ResonancePattern,
+        Self::ResonanceScore,
+        Self::ResonanceResolved,
+    ];
+
+    /// Custom attribute `name`, rejected unless [`Self::validate`] passes
+    pub fn custom(name: impl Into<String>) -> Result<Self, AttributeError> {
+        let attribute = Self::Custom(name.into());
+        attribute.validate()?;
+        Ok(attribute)
+    }
+
+    /// Check a custom attribute is namespaced outside the
+    /// [`RESERVED_NAMESPACES`], so it cannot shadow a predefined one.
+    /// Predefined attributes are always valid.
+    pub fn validate(&self) -> Result<(), AttributeError> {
+        let Self::Custom(name) = self else {
+            return Ok(());
+        };
+        let (namespace, local) = name
+            .split_once('/')
+            .ok_or_else(|| AttributeError::MissingNamespace(name.clone()))?;
+        let namespace = namespace.strip_prefix(':').unwrap_or(namespace);
+        if namespace.is_empty() || local.is_empty() {
+            return Err(AttributeError::MissingNamespace(name.clone()));
+        }
+        let lowered = namespace.to_ascii_lowercase();
+        if RESERVED_NAMESPACES.contains(&lowered.as_str()) {
+            return Err(AttributeError::ReservedNamespace(name.clone(), lowered));
+        }
+        Ok(())
+    }
+}
+
 impl std::fmt::Display for Attribute {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -130,3 +281,54 @@ impl FactBatch {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_built_in_attributes_round_trip() {
+        for attribute in Attribute::BUILT_IN {
+            let json = serde_json::to_string(attribute).unwrap();
+            let back: Attribute = serde_json::from_str(&json).unwrap();
+            assert_eq!(&back, attribute);
+
+            // Displayed by name, within a reserved namespace
+            let name = attribute.to_string();
+            assert_eq!(json, format!("\"{}\"", name));
+            let namespace = name
+                .strip_prefix(':')
+                .and_then(|n| n.split_once('/'))
+                .map(|(namespace, _)| namespace)
+                .expect("namespaced attribute");
+            assert!(RESERVED_NAMESPACES.contains(&namespace), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_custom_attribute_needs_free_namespace() {
+        let custom = Attribute::custom(":perf/cls").unwrap();
+        assert_eq!(custom.to_string(), ":perf/cls");
+        let json = serde_json::to_string(&custom).unwrap();
+        assert_eq!(serde_json::from_str::<Attribute>(&json).unwrap(), custom);
+        assert!(Attribute::custom("billing/invoice_id").is_ok());
+
+        assert_eq!(
+            Attribute::custom(":test/status"),
+            Err(AttributeError::ReservedNamespace(
+                ":test/status".to_string(),
+                "test".to_string()
+            ))
+        );
+        assert!(matches!(
+            Attribute::custom("API/latency"),
+            Err(AttributeError::ReservedNamespace(..))
+        ));
+        for name in ["status", ":/status", "perf/"] {
+            assert_eq!(
+                Attribute::custom(name),
+                Err(AttributeError::MissingNamespace(name.to_string()))
+            );
+        }
+    }
+}
@@ -11,6 +11,7 @@ pub mod facts;
 pub mod metrics;
 pub mod report;
 pub mod resonance;
+pub mod signal_schema;
 pub mod temporal;
 pub mod types;
 
@@ -5,10 +5,12 @@
 
 use prometheus_client::encoding::text::encode;
 use prometheus_client::metrics::counter::Counter;
-use prometheus_client::metrics::family::Family;
+use prometheus_client::metrics::family::{Family, MetricConstructor};
 use prometheus_client::metrics::gauge::Gauge;
 use prometheus_client::metrics::histogram::{exponential_buckets, Histogram};
 use prometheus_client::registry::Registry;
+use serde_json::{json, Map, Value};
+use std::borrow::Cow;
 use std::sync::Arc;
 
 /// Labels for test metrics
@@ -26,6 +28,80 @@ pub struct BaselineLabels {
     pub suite: String,
 }
 
+/// Labels for per-suite metrics
+#[derive(Clone, Debug, Hash, PartialEq, Eq, prometheus_client::encoding::EncodeLabelSet)]
+pub struct SuiteLabels {
+    pub suite: String,
+}
+
+/// Labels for HTTP request metrics
+///
+/// `route` is the matched route template (e.g. `/runs/:id`), never the raw
+/// request path, to keep label cardinality bounded.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, prometheus_client::encoding::EncodeLabelSet)]
+pub struct HttpLabels {
+    pub method: String,
+    pub route: String,
+    pub status: u16,
+}
+
+/// Labels for connection metrics: the protocol served (`http`)
+#[derive(Clone, Debug, Hash, PartialEq, Eq, prometheus_client::encoding::EncodeLabelSet)]
+pub struct ConnectionLabels {
+    pub kind: String,
+}
+
+/// Labels for batch ingest metrics: `ok`, `partial` or `error`
+#[derive(Clone, Debug, Hash, PartialEq, Eq, prometheus_client::encoding::EncodeLabelSet)]
+pub struct OutcomeLabels {
+    pub outcome: String,
+}
+
+/// Histogram constructor with explicit bucket upper bounds
+#[derive(Clone, Debug)]
+pub struct HistogramBuckets(pub Vec<f64>);
+
+impl HistogramBuckets {
+    /// Default buckets of `liminalqa_ingest_batch_duration_seconds`: 5 ms to
+    /// about 40 s
+    pub fn batch_duration() -> Self {
+        Self(exponential_buckets(0.005, 2.0, 14).collect())
+    }
+}
+
+impl MetricConstructor<Histogram> for HistogramBuckets {
+    fn new_metric(&self) -> Histogram {
+        Histogram::new(self.0.iter().copied())
+    }
+}
+
+/// Options of a [`MetricsRegistry`]
+#[derive(Clone, Debug)]
+pub struct MetricsConfig {
+    /// Bucket upper bounds (in seconds) of
+    /// `liminalqa_ingest_batch_duration_seconds`
+    pub batch_duration_buckets: HistogramBuckets,
+    /// Labels attached to every exported metric, e.g. `instance` or `region`
+    pub const_labels: Vec<(String, String)>,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            batch_duration_buckets: HistogramBuckets::batch_duration(),
+            const_labels: Vec::new(),
+        }
+    }
+}
+
+impl MetricsConfig {
+    /// Attach `name="value"` to every exported metric
+    pub fn with_const_label(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.const_labels.push((name.into(), value.into()));
+        self
+    }
+}
+
 /// Global metrics registry for LiminalQA
 pub struct MetricsRegistry {
     registry: Registry,
@@ -40,6 +116,21 @@ pub struct MetricsRegistry {
     pub baseline_duration_mean: Family<BaselineLabels, Gauge>,
     pub baseline_duration_stddev: Family<BaselineLabels, Gauge>,
 
+    // Resonance metrics
+    pub flaky_detections: Family<SuiteLabels, Counter>,
+
+    // HTTP metrics
+    pub http_requests: Family<HttpLabels, Counter>,
+    pub http_request_duration: Family<HttpLabels, Histogram>,
+    pub active_connections: Family<ConnectionLabels, Gauge>,
+    pub connections_total: Family<ConnectionLabels, Counter>,
+
+    // Ingest metrics
+    pub ingest_batch_duration: Family<OutcomeLabels, Histogram, HistogramBuckets>,
+
+    // Runner metrics
+    pub retries: Counter,
+
     // System metrics
     pub active_tests: Gauge,
     pub total_findings: Counter,
@@ -48,7 +139,29 @@ pub struct MetricsRegistry {
 impl MetricsRegistry {
     /// Create a new metrics registry with all standard metrics
     pub fn new() -> Self {
-        let mut registry = Registry::default();
+        Self::with_batch_duration_buckets(HistogramBuckets::batch_duration())
+    }
+
+    /// Like [`MetricsRegistry::new`], with custom bucket upper bounds (in
+    /// seconds) for `liminalqa_ingest_batch_duration_seconds`
+    pub fn with_batch_duration_buckets(batch_duration_buckets: HistogramBuckets) -> Self {
+        Self::with_config(MetricsConfig {
+            batch_duration_buckets,
+            ..MetricsConfig::default()
+        })
+    }
+
+    /// Create a registry with all standard metrics, configured by `config`
+    pub fn with_config(config: MetricsConfig) -> Self {
+        let MetricsConfig {
+            batch_duration_buckets,
+            const_labels,
+        } = config;
+        let mut registry = Registry::with_labels(
+            const_labels
+                .into_iter()
+                .map(|(name, value)| (Cow::Owned(name), Cow::Owned(value))),
+        );
 
         // Test counters
         let tests_total = Family::<TestLabels, Counter>::default();
@@ -97,6 +210,63 @@ impl MetricsRegistry {
             baseline_duration_stddev.clone(),
         );
 
+        // Resonance counters
+        let flaky_detections = Family::<SuiteLabels, Counter>::default();
+        registry.register(
+            "liminalqa_flaky_detections",
+            "Total number of tests flagged as flaky",
+            flaky_detections.clone(),
+        );
+
+        // HTTP request metrics
+        let http_requests = Family::<HttpLabels, Counter>::default();
+        registry.register(
+            "liminalqa_http_requests",
+            "Total number of HTTP requests handled",
+            http_requests.clone(),
+        );
+
+        let http_request_duration = Family::<HttpLabels, Histogram>::new_with_constructor(|| {
+            Histogram::new(exponential_buckets(0.001, 2.0, 15))
+        });
+        registry.register(
+            "liminalqa_http_request_duration_seconds",
+            "HTTP request handling duration in seconds",
+            http_request_duration.clone(),
+        );
+
+        // Connection metrics
+        let active_connections = Family::<ConnectionLabels, Gauge>::default();
+        registry.register(
+            "liminalqa_active_connections",
+            "Number of currently open client connections",
+            active_connections.clone(),
+        );
+
+        let connections_total = Family::<ConnectionLabels, Counter>::default();
+        registry.register(
+            "liminalqa_connections",
+            "Total number of client connections accepted",
+            connections_total.clone(),
+        );
+
+        // Batch ingest duration, parse to flush
+        let ingest_batch_duration =
+            Family::<OutcomeLabels, Histogram, _>::new_with_constructor(batch_duration_buckets);
+        registry.register(
+            "liminalqa_ingest_batch_duration_seconds",
+            "Server-side duration of a whole batch ingest in seconds",
+            ingest_batch_duration.clone(),
+        );
+
+        // Runner retries
+        let retries = Counter::default();
+        registry.register(
+            "liminalqa_retries",
+            "Total number of operations retried by the runner",
+            retries.clone(),
+        );
+
         // Gauges
         let active_tests = Gauge::default();
         registry.register(
@@ -120,17 +290,133 @@ impl MetricsRegistry {
             test_duration,
             baseline_duration_mean,
             baseline_duration_stddev,
+            flaky_detections,
+            http_requests,
+            http_request_duration,
+            active_connections,
+            connections_total,
+            ingest_batch_duration,
+            retries,
             active_tests,
             total_findings,
         }
     }
 
-    /// Export metrics in Prometheus text format
+    /// Export metrics in Prometheus text format.
+    ///
+    /// Never panics: should encoding fail, whatever was encoded so far is
+    /// returned (see [`MetricsRegistry::try_export`] to observe the error).
     pub fn export(&self) -> String {
         let mut buffer = String::new();
-        encode(&mut buffer, &self.registry).unwrap();
+        let _ = encode(&mut buffer, &self.registry);
         buffer
     }
+
+    /// Export metrics in Prometheus text format, failing if any metric
+    /// cannot be encoded
+    pub fn try_export(&self) -> Result<String, std::fmt::Error> {
+        let mut buffer = String::new();
+        encode(&mut buffer, &self.registry)?;
+        Ok(buffer)
+    }
+
+    /// Snapshot current metric values as structured JSON.
+    ///
+    /// Metric families are keyed by name, each carrying its `type`, `help`
+    /// and `samples` (sample name, labels and value). Histograms keep their
+    /// `_bucket`/`_sum`/`_count` samples. Serialized compactly, one snapshot
+    /// per line makes a JSON Lines archive.
+    pub fn snapshot_json(&self) -> Value {
+        let mut families = Map::new();
+        for line in self.export().lines() {
+            if let Some(rest) = line.strip_prefix("# TYPE ") {
+                if let Some((name, kind)) = rest.split_once(' ') {
+                    family_entry(&mut families, name)["type"] = json!(kind);
+                }
+            } else if let Some(rest) = line.strip_prefix("# HELP ") {
+                if let Some((name, help)) = rest.split_once(' ') {
+                    family_entry(&mut families, name)["help"] = json!(help);
+                }
+            } else if let Some((sample, labels, value)) = parse_sample(line) {
+                let family = families
+                    .keys()
+                    .filter(|name| sample.starts_with(name.as_str()))
+                    .max_by_key(|name| name.len())
+                    .cloned()
+                    .unwrap_or_else(|| sample.to_string());
+                if let Some(samples) =
+                    family_entry(&mut families, &family)["samples"].as_array_mut()
+                {
+                    samples.push(json!({
+                        "name": sample,
+                        "labels": labels,
+                        "value": value,
+                    }));
+                }
+            }
+        }
+
+        json!({
+            "timestamp": chrono::Utc::now(),
+            "metrics": families,
+        })
+    }
+}
+
+fn family_entry<'a>(families: &'a mut Map<String, Value>, name: &str) -> &'a mut Value {
+    families
+        .entry(name)
+        .or_insert_with(|| json!({ "type": "unknown", "help": "", "samples": [] }))
+}
+
+/// Parse a text-format sample line: `name{label="value",...} value`
+fn parse_sample(line: &str) -> Option<(&str, Map<String, Value>, Value)> {
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    let name_end = line.find(['{', ' '])?;
+    let name = &line[..name_end];
+    let mut labels = Map::new();
+    let mut rest = &line[name_end..];
+
+    if let Some(label_body) = rest.strip_prefix('{') {
+        let mut chars = label_body.char_indices();
+        let mut key = String::new();
+        let mut end = None;
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '}' => {
+                    end = Some(i + 1);
+                    break;
+                }
+                ',' => {}
+                '=' => {
+                    // Quoted value with backslash escapes
+                    chars.next();
+                    let mut value = String::new();
+                    while let Some((_, c)) = chars.next() {
+                        match c {
+                            '\\' => match chars.next() {
+                                Some((_, 'n')) => value.push('\n'),
+                                Some((_, other)) => value.push(other),
+                                None => return None,
+                            },
+                            '"' => break,
+                            other => value.push(other),
+                        }
+                    }
+                    labels.insert(std::mem::take(&mut key), json!(value));
+                }
+                other => key.push(other),
+            }
+        }
+        rest = &label_body[end?..];
+    }
+
+    let value: f64 = rest.split_whitespace().next()?.parse().ok()?;
+    let value = serde_json::Number::from_f64(value).map_or(Value::Null, Value::Number);
+    Some((name, labels, value))
 }
 
 impl Default for MetricsRegistry {
@@ -167,4 +453,99 @@ mod tests {
         assert!(output.contains("liminalqa_tests_total"));
         assert!(output.contains("liminalqa_active_tests"));
     }
+
+    #[test]
+    fn test_const_labels_on_every_metric() {
+        let metrics = MetricsRegistry::with_config(
+            MetricsConfig::default()
+                .with_const_label("instance", "ingest-1")
+                .with_const_label("region", "eu-west"),
+        );
+        metrics.active_tests.set(2);
+        metrics
+            .tests_total
+            .get_or_create(&TestLabels {
+                name: "test_example".to_string(),
+                suite: "unit".to_string(),
+                status: "pass".to_string(),
+            })
+            .inc();
+
+        let output = metrics.export();
+        assert!(
+            output.contains(r#"liminalqa_active_tests{instance="ingest-1",region="eu-west"} 2"#)
+        );
+        let tests_total = output
+            .lines()
+            .find(|l| l.starts_with("liminalqa_tests_total") && !l.starts_with('#'))
+            .expect("tests_total sample");
+        assert!(tests_total.contains(r#"instance="ingest-1""#));
+        assert!(tests_total.contains(r#"name="test_example""#));
+
+        let snapshot = metrics.snapshot_json();
+        let active = &snapshot["metrics"]["liminalqa_active_tests"]["samples"][0];
+        assert_eq!(active["labels"]["region"], "eu-west");
+    }
+
+    #[test]
+    fn test_registries_are_independent_within_one_process() {
+        // Every registry owns its collectors, so constructing several in one
+        // process (as tests do) never collides
+        let first = MetricsRegistry::new();
+        let second = MetricsRegistry::new();
+
+        first.active_tests.set(3);
+        second.active_tests.set(7);
+
+        let first_export = first.try_export().expect("first registry encodes");
+        let second_export = second.try_export().expect("second registry encodes");
+        assert!(first_export.contains("liminalqa_active_tests 3"));
+        assert!(second_export.contains("liminalqa_active_tests 7"));
+        assert_eq!(first.export(), first_export);
+    }
+
+    #[test]
+    fn test_snapshot_json_contains_counter_value() {
+        let metrics = MetricsRegistry::new();
+        let labels = TestLabels {
+            name: "test_example".to_string(),
+            suite: "unit".to_string(),
+            status: "pass".to_string(),
+        };
+        metrics.tests_total.get_or_create(&labels).inc();
+        metrics.tests_total.get_or_create(&labels).inc();
+        metrics.test_duration.get_or_create(&labels).observe(0.25);
+        metrics.active_tests.set(3);
+
+        let snapshot = metrics.snapshot_json();
+        let tests_total = &snapshot["metrics"]["liminalqa_tests_total"];
+        assert_eq!(tests_total["type"], "counter");
+        let sample = &tests_total["samples"][0];
+        assert_eq!(sample["labels"]["name"], "test_example");
+        assert_eq!(sample["labels"]["suite"], "unit");
+        assert_eq!(sample["value"], 2.0);
+
+        let active = &snapshot["metrics"]["liminalqa_active_tests"];
+        assert_eq!(active["type"], "gauge");
+        assert_eq!(active["samples"][0]["value"], 3.0);
+
+        // Histograms keep their bucket samples
+        let duration = &snapshot["metrics"]["liminalqa_test_duration_seconds"];
+        assert_eq!(duration["type"], "histogram");
+        let samples = duration["samples"].as_array().expect("histogram samples");
+        assert!(samples
+            .iter()
+            .any(|s| s["name"] == "liminalqa_test_duration_seconds_count" && s["value"] == 1.0));
+    }
+
+    #[test]
+    fn test_parse_sample_unescapes_labels() {
+        let (name, labels, value) =
+            parse_sample(r#"liminalqa_http_requests_total{route="/a\"b",status="200"} 4"#)
+                .expect("sample should parse");
+        assert_eq!(name, "liminalqa_http_requests_total");
+        assert_eq!(labels["route"], "/a\"b");
+        assert_eq!(labels["status"], "200");
+        assert_eq!(value, 4.0);
+    }
 }
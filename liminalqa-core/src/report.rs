@@ -1,21 +1,89 @@
 //! Report data structures for Reflection
 
-use chrono::{DateTime, Utc};
+use anyhow::Result;
+use chrono::{DateTime, DurationRound, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{
+    entities::{Test, TestProgress},
+    types::{EntityId, RunStatus, TestStatus},
+};
+
+/// Schema version stamped on every serialized [`ReflectionReport`].
+///
+/// Versioning policy: bump the major component when a change can break an
+/// existing consumer (a field is removed, renamed or changes type/meaning),
+/// and the minor component when fields are only added. Consumers should
+/// reject majors they do not know and ignore unknown fields otherwise.
+///
+/// Fields added by each minor version:
+///
+/// - 1.1: `summary.flaky_failures`
+/// - 1.2: `failure_clusters`
+/// - 1.3: `causality_window`
+/// - 1.4: `comparison`
+/// - 1.5: causality trail signal `sequence`
+/// - 1.6: run `status`
+/// - 1.7: causality trail signal `likely_cause`
+/// - 1.8: causality trail signal `correlation_id`
+/// - 1.9: `alignment`
+/// - 1.10: causality trail `owner`
+/// - 1.11: `sla_breaches`
+/// - 1.12: `summary.passed_with_retries` and `retried_passes`
+/// - 1.13: `in_progress`
+pub const REPORT_SCHEMA_VERSION: &str = "1.13";
+
+/// Reports written before `schema_version` existed have the 1.0 shape
+fn default_schema_version() -> String {
+    "1.0".to_string()
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReflectionReport {
+    /// See [`REPORT_SCHEMA_VERSION`]. Reports written before the field
+    /// existed have the 1.0 shape and deserialize as such.
+    #[serde(default = "default_schema_version")]
+    pub schema_version: String,
     pub run_id: String,
     pub plan_name: String,
     pub started_at: DateTime<Utc>,
     pub ended_at: Option<DateTime<Utc>>,
+    /// `None` in reports written before the field existed
+    #[serde(default)]
+    pub status: Option<RunStatus>,
     pub summary: TestSummary,
     pub timeline: Vec<TimelineBucket>,
     pub top_slow_tests: Vec<SlowTest>,
     pub causality_trails: Vec<CausalityTrail>,
+    /// Window the causality trails were collected with
+    #[serde(default)]
+    pub causality_window: CausalityWindow,
+    /// Failing tests grouped by a signal they share (see [`cluster_failures`])
+    #[serde(default)]
+    pub failure_clusters: Vec<FailureCluster>,
+    /// Deltas against the previous run of the same plan, `None` for the
+    /// first run of a plan
+    #[serde(default)]
+    pub comparison: Option<RunComparison>,
+    /// How well tests met their guidance, least aligned first; only tests
+    /// with a recorded score are listed
+    #[serde(default)]
+    pub alignment: Vec<TestAlignment>,
+    /// Tests that took longer than their SLA, furthest over first. Unlike
+    /// drift this is judged against a fixed budget, not the test's history.
+    #[serde(default)]
+    pub sla_breaches: Vec<SlaBreach>,
+    /// Passing tests that needed more than one attempt, most attempts first
+    #[serde(default)]
+    pub retried_passes: Vec<RetriedPass>,
+    /// Latest phase reported by each test that has no result yet, most
+    /// recent first
+    #[serde(default)]
+    pub in_progress: Vec<TestProgress>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TestSummary {
     pub total: i64,
     pub passed: i64,
@@ -23,6 +91,74 @@ pub struct TestSummary {
     pub flake: i64,
     pub timeout: i64,
     pub skip: i64,
+    /// Failures (fail or timeout) of tests already known to be flaky
+    #[serde(default)]
+    pub flaky_failures: i64,
+    /// Passes that needed retries; counted in `passed` as well
+    #[serde(default)]
+    pub passed_with_retries: i64,
+}
+
+/// Raw pass rate in percent: passed over all tests. An empty run rates 100,
+/// as it does for `limctl`'s pass-rate gate and stats.
+pub fn pass_rate(summary: &TestSummary) -> f64 {
+    if summary.total > 0 {
+        summary.passed as f64 * 100.0 / summary.total as f64
+    } else {
+        100.0
+    }
+}
+
+/// Pass rate in percent that discounts failures of known-flaky tests.
+///
+/// `flaky_failures` are dropped from the denominator (capped at the number
+/// of fail and timeout results), so a flaky failure neither passes nor
+/// fails the run. A run whose only failures are flaky rates 100, as does
+/// an empty run.
+pub fn weighted_pass_rate(summary: &TestSummary, flaky_failures: i64) -> f64 {
+    let discounted = flaky_failures.clamp(0, summary.failed + summary.timeout);
+    let counted = summary.total - discounted;
+    if counted > 0 {
+        summary.passed as f64 * 100.0 / counted as f64
+    } else {
+        100.0
+    }
+}
+
+/// Decimals [`format_duration_ms`] keeps at most
+pub const DURATION_PRECISION: usize = 2;
+
+/// Duration for display, in the unit that suits its magnitude: `500µs`,
+/// `1.5ms`, `5s`, `2m 5s`. Negative and NaN durations show as `0µs`.
+pub fn format_duration_ms(ms: f64) -> String {
+    format_duration_ms_with_precision(ms, DURATION_PRECISION)
+}
+
+/// [`format_duration_ms`] keeping at most `precision` decimals for
+/// milliseconds and seconds; trailing zeros are dropped
+pub fn format_duration_ms_with_precision(ms: f64, precision: usize) -> String {
+    let ms = ms.max(0.0);
+    let decimals = |value: f64| {
+        let formatted = format!("{:.*}", precision, value);
+        if formatted.contains('.') {
+            formatted
+                .trim_end_matches('0')
+                .trim_end_matches('.')
+                .to_string()
+        } else {
+            formatted
+        }
+    };
+    if ms < 1.0 {
+        format!("{:.0}µs", ms * 1000.0)
+    } else if ms < 1000.0 {
+        format!("{}ms", decimals(ms))
+    } else if ms < 60_000.0 {
+        format!("{}s", decimals(ms / 1000.0))
+    } else {
+        let secs = (ms / 1000.0).round() as u64;
+        format!("{}m {}s", secs / 60, secs % 60)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,19 +168,125 @@ pub struct TimelineBucket {
     pub count: i64,
 }
 
+/// Number of tests listed in `top_slow_tests`
+pub const TOP_SLOW_TESTS: usize = 10;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SlowTest {
     pub name: String,
     pub suite: String,
-    pub duration_ms: i32,
+    pub duration_ms: i64,
     pub status: String,
 }
 
+/// Fraction of its guidance observables a test met
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TestAlignment {
+    pub name: String,
+    pub suite: String,
+    pub score: f64,
+}
+
+/// A test that took longer than its SLA
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SlaBreach {
+    pub name: String,
+    pub suite: String,
+    pub duration_ms: u64,
+    pub sla_ms: u64,
+}
+
+/// A test that passed only after being retried
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetriedPass {
+    pub name: String,
+    pub suite: String,
+    pub attempts: u32,
+}
+
+/// How far before and after a failure a signal joins its causality trail
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CausalityWindow {
+    pub before_secs: i64,
+    pub after_secs: i64,
+}
+
+impl CausalityWindow {
+    pub fn new(before_secs: i64, after_secs: i64) -> Self {
+        Self {
+            before_secs,
+            after_secs,
+        }
+    }
+
+    /// Whether a signal `diff_secs` after the failure (negative: before) is
+    /// inside the window, bounds included
+    pub fn contains(&self, diff_secs: i64) -> bool {
+        (-self.before_secs..=self.after_secs).contains(&diff_secs)
+    }
+}
+
+/// ±5 minutes, the window reports used before it was configurable
+impl Default for CausalityWindow {
+    fn default() -> Self {
+        Self::new(5 * 60, 5 * 60)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CausalityTrail {
     pub test_name: String,
     pub test_failed_at: DateTime<Utc>,
     pub signals: Vec<NearbySignal>,
+    /// Owner of the failed test, from its `:test/owner` fact
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+}
+
+impl CausalityTrail {
+    /// Flag the signal most likely to have caused the failure, see
+    /// [`NearbySignal::cause_score`]. Any previous flag is cleared; on a tie
+    /// the earlier signal in the trail wins.
+    pub fn mark_likely_cause(&mut self) {
+        let mut best: Option<(usize, f64)> = None;
+        for (i, signal) in self.signals.iter_mut().enumerate() {
+            signal.likely_cause = false;
+            let score = signal.cause_score();
+            if best.is_none_or(|(_, top)| score > top) {
+                best = Some((i, score));
+            }
+        }
+        if let Some((i, _)) = best {
+            self.signals[i].likely_cause = true;
+        }
+    }
+
+    /// Move signals sharing a correlation id next to each other, each group
+    /// taking the place of its first member. Signals without one keep their
+    /// position relative to the groups.
+    pub fn group_by_correlation(&mut self) {
+        let group_start: Vec<usize> = (0..self.signals.len())
+            .map(|i| match &self.signals[i].correlation_id {
+                Some(id) => self
+                    .signals
+                    .iter()
+                    .position(|s| s.correlation_id.as_ref() == Some(id))
+                    .unwrap_or(i),
+                None => i,
+            })
+            .collect();
+        let mut keyed: Vec<(usize, NearbySignal)> = group_start
+            .into_iter()
+            .zip(self.signals.drain(..))
+            .collect();
+        keyed.sort_by_key(|(start, _)| *start);
+        self.signals = keyed.into_iter().map(|(_, s)| s).collect();
+    }
+
+    /// The signal flagged by [`CausalityTrail::mark_likely_cause`]
+    pub fn likely_cause(&self) -> Option<&NearbySignal> {
+        self.signals.iter().find(|s| s.likely_cause)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -54,4 +296,442 @@ pub struct NearbySignal {
     pub value: Option<f64>,
     pub meta: serde_json::Value,
     pub time_diff_seconds: i32,
+    /// Ingest sequence of the signal within its run, ordering signals with
+    /// equal timestamps (absent where the store does not assign one)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<u64>,
+    /// Top candidate of its trail, see [`CausalityTrail::mark_likely_cause`]
+    #[serde(default)]
+    pub likely_cause: bool,
+    /// See [`crate::entities::Signal::correlation_id`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<String>,
+}
+
+impl NearbySignal {
+    /// Whether the metadata reports an error: an `error` or `exception`
+    /// entry, a 5xx `status`/`status_code`, or an error `level`/`severity`
+    pub fn is_error(&self) -> bool {
+        let meta = &self.meta;
+        let present = |key: &str| meta.get(key).is_some_and(|v| !v.is_null());
+        let server_error = |key: &str| {
+            meta.get(key)
+                .and_then(serde_json::Value::as_u64)
+                .is_some_and(|code| code >= 500)
+        };
+        let error_level = |key: &str| {
+            meta.get(key)
+                .and_then(serde_json::Value::as_str)
+                .is_some_and(|l| {
+                    matches!(l.to_lowercase().as_str(), "error" | "fatal" | "critical")
+                })
+        };
+        present("error")
+            || present("exception")
+            || server_error("status")
+            || server_error("status_code")
+            || error_level("level")
+            || error_level("severity")
+    }
+
+    /// How likely the signal is to have caused the failure. Error-ness
+    /// dominates: any error signal outranks every benign one. Within each
+    /// group closer signals rank higher, and signals after the failure count
+    /// half since a cause precedes its effect.
+    pub fn cause_score(&self) -> f64 {
+        let mut proximity = 1.0 / (1.0 + f64::from(self.time_diff_seconds.unsigned_abs()));
+        if self.time_diff_seconds > 0 {
+            proximity /= 2.0;
+        }
+        if self.is_error() {
+            proximity + 1.0
+        } else {
+            proximity
+        }
+    }
+}
+
+/// Test outcome changes since the previous run of the same plan.
+///
+/// Fail and timeout count as failing; pass and flake count as passing.
+/// Test names are sorted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunComparison {
+    pub previous_run_id: String,
+    pub previous_started_at: DateTime<Utc>,
+    /// Passed in the previous run, failing now
+    pub regressions: Vec<String>,
+    /// Failing now and absent from the previous run
+    pub new_failures: Vec<String>,
+    /// Failing in the previous run, passing now
+    pub fixed: Vec<String>,
+}
+
+impl RunComparison {
+    /// Compare `(test name, status)` outcomes of two runs, statuses being
+    /// the lowercase labels used throughout the report
+    pub fn between(
+        previous_run_id: impl Into<String>,
+        previous_started_at: DateTime<Utc>,
+        previous: &[(String, String)],
+        current: &[(String, String)],
+    ) -> Self {
+        let failing = |status: &str| matches!(status, "fail" | "timeout");
+        let passing = |status: &str| matches!(status, "pass" | "flake");
+        let before: std::collections::HashMap<&str, &str> = previous
+            .iter()
+            .map(|(name, status)| (name.as_str(), status.as_str()))
+            .collect();
+
+        let mut comparison = Self {
+            previous_run_id: previous_run_id.into(),
+            previous_started_at,
+            ..Default::default()
+        };
+        for (name, status) in current {
+            match before.get(name.as_str()) {
+                Some(&old) if passing(old) && failing(status) => {
+                    comparison.regressions.push(name.clone())
+                }
+                Some(&old) if failing(old) && passing(status) => {
+                    comparison.fixed.push(name.clone())
+                }
+                None if failing(status) => comparison.new_failures.push(name.clone()),
+                _ => {}
+            }
+        }
+        comparison.regressions.sort();
+        comparison.new_failures.sort();
+        comparison.fixed.sort();
+        comparison
+    }
+}
+
+/// One run executed against a build, with its test rollup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildRun {
+    pub run_id: String,
+    pub plan_name: String,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub status: RunStatus,
+    pub summary: TestSummary,
+}
+
+/// Failing tests that share one nearby signal, its likely root cause
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailureCluster {
+    /// The shared signal; `time_diff_seconds` is relative to the first test
+    pub signal: NearbySignal,
+    pub test_names: Vec<String>,
+}
+
+/// Group failing tests whose causality trails contain the same signal.
+///
+/// Signals are matched by kind, timestamp and metadata. The signal shared by
+/// the most tests forms the first cluster, and a test joins at most one
+/// cluster. Signals near a single failure explain nothing beyond its own
+/// trail and produce no cluster.
+pub fn cluster_failures(trails: &[CausalityTrail]) -> Vec<FailureCluster> {
+    let mut candidates: Vec<(&NearbySignal, Vec<&str>)> = Vec::new();
+    for trail in trails {
+        for signal in &trail.signals {
+            let same = |s: &NearbySignal| {
+                s.kind == signal.kind && s.at == signal.at && s.meta == signal.meta
+            };
+            match candidates.iter_mut().find(|(s, _)| same(s)) {
+                Some((_, tests)) if !tests.contains(&trail.test_name.as_str()) => {
+                    tests.push(&trail.test_name)
+                }
+                Some(_) => {}
+                None => candidates.push((signal, vec![&trail.test_name])),
+            }
+        }
+    }
+    candidates.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then(a.0.at.cmp(&b.0.at)));
+
+    let mut clustered: Vec<&str> = Vec::new();
+    let mut clusters = Vec::new();
+    for (signal, tests) in candidates {
+        let tests: Vec<&str> = tests
+            .into_iter()
+            .filter(|t| !clustered.contains(t))
+            .collect();
+        if tests.len() < 2 {
+            continue;
+        }
+        clustered.extend(&tests);
+        clusters.push(FailureCluster {
+            signal: signal.clone(),
+            test_names: tests.into_iter().map(str::to_string).collect(),
+        });
+    }
+    clusters
+}
+
+/// Status of a test as reports spell it, e.g. `xfail`
+fn status_str(status: TestStatus) -> String {
+    format!("{:?}", status).to_lowercase()
+}
+
+/// Summary of `tests`; `is_flaky` tells the tests already known to be flaky
+/// and `attempts` the attempts of each test, where known
+pub fn test_summary(
+    tests: &[Test],
+    is_flaky: impl Fn(&Test) -> bool,
+    attempts: &HashMap<EntityId, u32>,
+) -> TestSummary {
+    let count = |status: TestStatus| tests.iter().filter(|t| t.status == status).count() as i64;
+    TestSummary {
+        total: tests.len() as i64,
+        passed: count(TestStatus::Pass),
+        failed: count(TestStatus::Fail),
+        flake: count(TestStatus::Flake),
+        timeout: count(TestStatus::Timeout),
+        skip: count(TestStatus::Skip),
+        flaky_failures: tests
+            .iter()
+            .filter(|t| matches!(t.status, TestStatus::Fail | TestStatus::Timeout))
+            .filter(|t| is_flaky(t))
+            .count() as i64,
+        passed_with_retries: retried_passes(tests, attempts).len() as i64,
+    }
+}
+
+/// Passing tests that took more than one attempt, most attempts first
+pub fn retried_passes(tests: &[Test], attempts: &HashMap<EntityId, u32>) -> Vec<RetriedPass> {
+    let mut retried: Vec<RetriedPass> = tests
+        .iter()
+        .filter(|t| t.status == TestStatus::Pass)
+        .filter_map(|t| {
+            let attempts = *attempts.get(&t.id)?;
+            (attempts > 1).then(|| RetriedPass {
+                name: t.name.clone(),
+                suite: t.suite.clone(),
+                attempts,
+            })
+        })
+        .collect();
+    retried.sort_by(|a, b| {
+        b.attempts
+            .cmp(&a.attempts)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    retried
+}
+
+/// Per-minute counts of completed tests by status
+pub fn timeline(tests: &[Test]) -> Result<Vec<TimelineBucket>> {
+    let mut buckets: BTreeMap<(DateTime<Utc>, String), i64> = BTreeMap::new();
+    for test in tests {
+        let bucket = test
+            .completed_at
+            .duration_trunc(chrono::Duration::minutes(1))?;
+        *buckets
+            .entry((bucket, status_str(test.status)))
+            .or_default() += 1;
+    }
+
+    Ok(buckets
+        .into_iter()
+        .map(|((bucket, status), count)| TimelineBucket {
+            bucket,
+            status,
+            count,
+        })
+        .collect())
+}
+
+/// The [`TOP_SLOW_TESTS`] slowest of `tests`, slowest first
+pub fn top_slow_tests(tests: &[Test]) -> Vec<SlowTest> {
+    let mut sorted: Vec<&Test> = tests.iter().collect();
+    sorted.sort_by_key(|t| std::cmp::Reverse(t.duration_ms));
+    sorted
+        .into_iter()
+        .take(TOP_SLOW_TESTS)
+        .map(|t| SlowTest {
+            name: t.name.clone(),
+            suite: t.suite.clone(),
+            duration_ms: t.duration_ms_i64(),
+            status: status_str(t.status),
+        })
+        .collect()
+}
+
+/// `(test name, status)` of every test, as [`RunComparison::between`] takes
+/// them
+pub fn outcomes(tests: &[Test]) -> Vec<(String, String)> {
+    tests
+        .iter()
+        .map(|t| (t.name.clone(), status_str(t.status)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_report() -> ReflectionReport {
+        ReflectionReport {
+            schema_version: REPORT_SCHEMA_VERSION.to_string(),
+            run_id: "run-1".to_string(),
+            plan_name: "smoke".to_string(),
+            started_at: Utc::now(),
+            ended_at: None,
+            status: Some(RunStatus::Running),
+            summary: TestSummary {
+                total: 1,
+                passed: 1,
+                failed: 0,
+                flake: 0,
+                timeout: 0,
+                skip: 0,
+                flaky_failures: 0,
+                passed_with_retries: 0,
+            },
+            timeline: vec![],
+            top_slow_tests: vec![],
+            causality_trails: vec![],
+            causality_window: CausalityWindow::default(),
+            failure_clusters: vec![],
+            alignment: vec![],
+            sla_breaches: vec![],
+            retried_passes: vec![],
+            in_progress: vec![],
+            comparison: None,
+        }
+    }
+
+    #[test]
+    fn test_schema_version_in_rendered_json() {
+        let json = serde_json::to_value(sample_report()).unwrap();
+        assert_eq!(json["schema_version"], REPORT_SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_missing_schema_version_defaults_to_1_0() {
+        let mut json = serde_json::to_value(sample_report()).unwrap();
+        json.as_object_mut()
+            .expect("report serializes to an object")
+            .remove("schema_version");
+
+        let report: ReflectionReport = serde_json::from_value(json).unwrap();
+        assert_eq!(report.schema_version, "1.0");
+    }
+
+    #[test]
+    fn test_weighted_pass_rate_discounts_flaky_failures() {
+        let summary = TestSummary {
+            total: 10,
+            passed: 9,
+            failed: 1,
+            flake: 0,
+            timeout: 0,
+            skip: 0,
+            flaky_failures: 1,
+            passed_with_retries: 0,
+        };
+        let threshold = 95.0;
+
+        assert_eq!(pass_rate(&summary), 90.0);
+        assert!(pass_rate(&summary) < threshold);
+
+        let weighted = weighted_pass_rate(&summary, summary.flaky_failures);
+        assert_eq!(weighted, 100.0);
+        assert!(weighted >= threshold);
+
+        // Only real failures can be discounted
+        assert_eq!(weighted_pass_rate(&summary, 5), 100.0);
+        assert_eq!(weighted_pass_rate(&summary, 0), 90.0);
+
+        let empty = TestSummary {
+            total: 0,
+            passed: 0,
+            failed: 0,
+            flake: 0,
+            timeout: 0,
+            skip: 0,
+            flaky_failures: 0,
+            passed_with_retries: 0,
+        };
+        assert_eq!(pass_rate(&empty), 100.0);
+        assert_eq!(weighted_pass_rate(&empty, 0), 100.0);
+    }
+
+    fn nearby(diff_secs: i32, meta: serde_json::Value) -> NearbySignal {
+        NearbySignal {
+            kind: "api".to_string(),
+            at: Utc::now() + chrono::Duration::seconds(diff_secs.into()),
+            value: None,
+            meta,
+            time_diff_seconds: diff_secs,
+            sequence: None,
+            likely_cause: false,
+            correlation_id: None,
+        }
+    }
+
+    #[test]
+    fn test_likely_cause_prefers_errors_then_proximity() {
+        let mut trail = CausalityTrail {
+            test_name: "test_pay".to_string(),
+            test_failed_at: Utc::now(),
+            owner: None,
+            signals: vec![
+                nearby(0, serde_json::json!({"status": 200})),
+                nearby(1, serde_json::json!({"error": "reset"})),
+                nearby(-30, serde_json::json!({"status": 503})),
+            ],
+        };
+        trail.mark_likely_cause();
+        // Both errors beat the benign signal; the one before the failure
+        // wins despite being further away
+        let cause = trail.likely_cause().expect("a likely cause");
+        assert_eq!(cause.time_diff_seconds, 1);
+        assert_eq!(trail.signals.iter().filter(|s| s.likely_cause).count(), 1);
+
+        trail.signals.remove(1);
+        trail.mark_likely_cause();
+        assert_eq!(trail.likely_cause().map(|s| s.time_diff_seconds), Some(-30));
+
+        trail.signals.clear();
+        trail.mark_likely_cause();
+        assert!(trail.likely_cause().is_none());
+    }
+
+    #[test]
+    fn test_correlated_signals_are_grouped() {
+        let correlated = |diff_secs: i32, id: Option<&str>| NearbySignal {
+            correlation_id: id.map(str::to_string),
+            ..nearby(diff_secs, serde_json::json!({}))
+        };
+        let mut trail = CausalityTrail {
+            test_name: "test_pay".to_string(),
+            test_failed_at: Utc::now(),
+            owner: None,
+            signals: vec![
+                correlated(0, Some("req-1")),
+                correlated(-1, None),
+                correlated(2, Some("req-2")),
+                correlated(-3, Some("req-1")),
+                correlated(4, Some("req-2")),
+            ],
+        };
+        trail.group_by_correlation();
+        let diffs: Vec<i32> = trail.signals.iter().map(|s| s.time_diff_seconds).collect();
+        assert_eq!(diffs, [0, -3, -1, 2, 4]);
+    }
+
+    #[test]
+    fn test_format_duration_adapts_unit() {
+        assert_eq!(format_duration_ms(0.5), "500µs");
+        assert_eq!(format_duration_ms(50.0), "50ms");
+        assert_eq!(format_duration_ms(1.25), "1.25ms");
+        assert_eq!(format_duration_ms(5_000.0), "5s");
+        assert_eq!(format_duration_ms(5_250.0), "5.25s");
+        assert_eq!(format_duration_ms(125_000.0), "2m 5s");
+        assert_eq!(format_duration_ms(-3.0), "0µs");
+        assert_eq!(format_duration_ms_with_precision(1_234.5, 0), "1s");
+        assert_eq!(format_duration_ms_with_precision(12.3456, 3), "12.346ms");
+    }
 }
@@ -1,8 +1,96 @@
-use crate::types::TestStatus;
+use std::sync::Arc;
+
+use crate::{
+    entities::{Resonance, Test},
+    temporal::BiTemporalTime,
+    types::{EntityId, ResonancePattern, TestStatus},
+};
+
+/// Analyzer looking for a pattern of instability in the execution history of
+/// one test
+pub trait PatternDetector: Send + Sync {
+    /// Short name identifying the detector in logs and metrics
+    fn name(&self) -> &str;
+
+    /// Patterns found in `history`, the executions of one test (same name
+    /// and suite), newest first. Empty when nothing was found.
+    fn detect(&self, history: &[Test]) -> Vec<Resonance>;
+
+    /// Whether `resonance`, found earlier, no longer holds for `history`
+    /// (newest first). Detectors that never resolve their patterns keep the
+    /// default.
+    fn resolves(&self, _resonance: &Resonance, _history: &[Test]) -> bool {
+        false
+    }
+}
+
+/// Detectors run over every ingested test
+#[derive(Clone)]
+pub struct DetectorRegistry {
+    detectors: Vec<Arc<dyn PatternDetector>>,
+}
+
+impl DetectorRegistry {
+    /// A registry without any detector
+    pub fn empty() -> Self {
+        Self {
+            detectors: Vec::new(),
+        }
+    }
+
+    /// Also run `detector`
+    pub fn with_detector(mut self, detector: impl PatternDetector + 'static) -> Self {
+        self.detectors.push(Arc::new(detector));
+        self
+    }
+
+    pub fn detectors(&self) -> &[Arc<dyn PatternDetector>] {
+        &self.detectors
+    }
+
+    /// Patterns found in `history` by every detector, with the name of the
+    /// detector that found each, also recorded as [`Resonance::detector`]
+    pub fn detect(&self, history: &[Test]) -> Vec<(&str, Resonance)> {
+        self.detectors
+            .iter()
+            .flat_map(|d| {
+                d.detect(history).into_iter().map(|mut r| {
+                    r.detector = d.name().to_string();
+                    (d.name(), r)
+                })
+            })
+            .collect()
+    }
+
+    /// Name of the detector of `resonance` if its pattern no longer holds
+    pub fn resolves(&self, resonance: &Resonance, history: &[Test]) -> Option<&str> {
+        self.detectors
+            .iter()
+            .filter(|d| d.name() == resonance.detector)
+            .find(|d| d.resolves(resonance, history))
+            .map(|d| d.name())
+    }
+}
+
+/// The built-in detectors: [`FlakeDetector`]
+impl Default for DetectorRegistry {
+    fn default() -> Self {
+        Self::empty().with_detector(FlakeDetector::default())
+    }
+}
+
+impl std::fmt::Debug for DetectorRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.detectors.iter().map(|d| d.name()))
+            .finish()
+    }
+}
 
 pub struct FlakeDetector {
     window_size: usize,
     threshold: f64,
+    stable_windows: usize,
 }
 
 impl Default for FlakeDetector {
@@ -10,18 +98,44 @@ impl Default for FlakeDetector {
         Self {
             window_size: 10,
             threshold: 0.3,
+            stable_windows: Self::DEFAULT_STABLE_WINDOWS,
         }
     }
 }
 
 impl FlakeDetector {
+    /// Non-flaky windows needed before a flake resonance is resolved
+    pub const DEFAULT_STABLE_WINDOWS: usize = 5;
+
     pub fn new(window_size: usize, threshold: f64) -> Self {
         Self {
             window_size,
             threshold,
+            stable_windows: Self::DEFAULT_STABLE_WINDOWS,
         }
     }
 
+    /// Resolve a flake resonance once the windows ending at each of the
+    /// latest `stable_windows` executions all score as not flaky
+    pub fn with_stable_windows(mut self, stable_windows: usize) -> Self {
+        self.stable_windows = stable_windows.max(1);
+        self
+    }
+
+    pub fn stable_windows(&self) -> usize {
+        self.stable_windows
+    }
+
+    /// Executions scored at once
+    pub fn window_size(&self) -> usize {
+        self.window_size
+    }
+
+    /// Score above which a test is flagged flaky
+    pub fn threshold(&self) -> f64 {
+        self.threshold
+    }
+
     pub fn calculate_score(&self, history: &[TestStatus]) -> f64 {
         if history.len() < 2 {
             return 0.0;
@@ -65,11 +179,202 @@ impl FlakeDetector {
     pub fn is_flaky(&self, history: &[TestStatus]) -> bool {
         self.calculate_score(history) > self.threshold
     }
+
+    /// Whether `history` (newest first) has been stable long enough to clear
+    /// a flaky label: the window ending at each of the latest
+    /// `stable_windows` executions is not flaky. Shorter histories are never
+    /// stable.
+    pub fn is_stable(&self, history: &[TestStatus]) -> bool {
+        history.len() >= self.stable_windows
+            && (0..self.stable_windows).all(|start| {
+                let end = (start + self.window_size).min(history.len());
+                !self.is_flaky(&history[start..end])
+            })
+    }
+
+    /// Score of the newest window of `history` (newest first)
+    pub fn score_latest(&self, history: &[Test]) -> f64 {
+        // `calculate_score` keeps the tail of its input, which would be the
+        // oldest executions here
+        let statuses: Vec<TestStatus> = history
+            .iter()
+            .take(self.window_size)
+            .map(|t| t.status)
+            .collect();
+        self.calculate_score(&statuses)
+    }
+}
+
+impl FlakeDetector {
+    /// [`PatternDetector::name`] of the flake detector
+    pub const NAME: &'static str = "flake";
+
+    /// Start of the description of every resonance the detector produces;
+    /// how resonances stored before [`Resonance::detector`] are attributed
+    pub const DESCRIPTION_PREFIX: &'static str = "Flaky test detected";
+
+    /// Whether `resonance` was produced by a flake detector
+    pub fn produced(resonance: &Resonance) -> bool {
+        resonance.detector == Self::NAME
+    }
+
+    /// Description of a flake resonance for test `name`
+    pub fn describe(name: &str, score: f64) -> String {
+        format!(
+            "{}: {} (Score: {:.2})",
+            Self::DESCRIPTION_PREFIX,
+            name,
+            score
+        )
+    }
+}
+
+impl PatternDetector for FlakeDetector {
+    fn name(&self) -> &str {
+        Self::NAME
+    }
+
+    fn detect(&self, history: &[Test]) -> Vec<Resonance> {
+        let Some(latest) = history.first() else {
+            return Vec::new();
+        };
+        let score = self.score_latest(history);
+        if score <= self.threshold {
+            return Vec::new();
+        }
+
+        let now = chrono::Utc::now();
+        vec![Resonance {
+            id: EntityId::new(),
+            detector: Self::NAME.to_string(),
+            pattern: ResonancePattern {
+                pattern_id: EntityId::new(),
+                description: Self::describe(&latest.name, score),
+                score,
+                occurrences: 1,
+                first_seen: now,
+                last_seen: now,
+            },
+            affected_tests: vec![latest.id],
+            root_cause: None,
+            created_at: BiTemporalTime::now(),
+        }]
+    }
+
+    fn resolves(&self, resonance: &Resonance, history: &[Test]) -> bool {
+        let statuses: Vec<TestStatus> = history.iter().map(|t| t.status).collect();
+        Self::produced(resonance) && self.is_stable(&statuses)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::{Datelike, Weekday};
+
+    fn test_at(status: TestStatus, started_at: chrono::DateTime<chrono::Utc>) -> Test {
+        Test {
+            id: EntityId::new(),
+            run_id: EntityId::new(),
+            name: "test_checkout".to_string(),
+            suite: "payments".to_string(),
+            guidance: String::new(),
+            status,
+            duration_ms: 100,
+            error: None,
+            started_at,
+            completed_at: started_at,
+            created_at: BiTemporalTime::now(),
+        }
+    }
+
+    /// Flags tests that only ever fail on weekends
+    struct WeekendFailures;
+
+    impl PatternDetector for WeekendFailures {
+        fn name(&self) -> &str {
+            "weekend"
+        }
+
+        fn detect(&self, history: &[Test]) -> Vec<Resonance> {
+            let is_weekend =
+                |t: &&Test| matches!(t.started_at.weekday(), Weekday::Sat | Weekday::Sun);
+            let failures: Vec<&Test> = history.iter().filter(|t| !t.status.is_pass()).collect();
+            if failures.is_empty() || !failures.iter().all(is_weekend) {
+                return Vec::new();
+            }
+            let now = chrono::Utc::now();
+            vec![Resonance {
+                id: EntityId::new(),
+                detector: "weekend".to_string(),
+                pattern: ResonancePattern {
+                    pattern_id: EntityId::new(),
+                    description: "Fails only on weekends".to_string(),
+                    score: 1.0,
+                    occurrences: failures.len() as u32,
+                    first_seen: now,
+                    last_seen: now,
+                },
+                affected_tests: failures.iter().map(|t| t.id).collect(),
+                root_cause: None,
+                created_at: BiTemporalTime::now(),
+            }]
+        }
+    }
+
+    #[test]
+    fn test_registry_runs_custom_detectors() {
+        // 2024-06-01 is a Saturday
+        let saturday = chrono::DateTime::parse_from_rfc3339("2024-06-01T10:00:00Z")
+            .expect("valid timestamp")
+            .with_timezone(&chrono::Utc);
+        let day = chrono::Duration::days(1);
+        let history = vec![
+            test_at(TestStatus::Pass, saturday + day * 3),
+            test_at(TestStatus::Fail, saturday + day),
+            test_at(TestStatus::Pass, saturday - day),
+            test_at(TestStatus::Fail, saturday),
+        ];
+
+        let registry = DetectorRegistry::default().with_detector(WeekendFailures);
+        let found = registry.detect(&history);
+        assert_eq!(found.len(), 1);
+        let (name, resonance) = &found[0];
+        assert_eq!(*name, "weekend");
+        assert_eq!(resonance.affected_tests, vec![history[1].id, history[3].id]);
+
+        // A weekday failure breaks the pattern
+        let mut history = history;
+        history[0].status = TestStatus::Fail;
+        assert!(registry.detect(&history).is_empty());
+    }
+
+    #[test]
+    fn test_flake_detector_reports_latest_execution() {
+        let now = chrono::Utc::now();
+        let history: Vec<Test> = (0..5)
+            .map(|i| {
+                let status = if i % 2 == 0 {
+                    TestStatus::Pass
+                } else {
+                    TestStatus::Fail
+                };
+                test_at(status, now - chrono::Duration::minutes(i))
+            })
+            .collect();
+
+        let found = FlakeDetector::default().detect(&history);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].affected_tests, vec![history[0].id]);
+        assert!(FlakeDetector::default().detect(&history[..1]).is_empty());
+
+        // Recognised by its detector, whatever the description says
+        let mut reworded = found[0].clone();
+        reworded.pattern.description = "Unstable: test_checkout".to_string();
+        assert!(FlakeDetector::produced(&reworded));
+        reworded.detector = "weekend".to_string();
+        assert!(!FlakeDetector::produced(&reworded));
+    }
 
     #[test]
     fn test_flaky_detection() {
@@ -114,4 +419,20 @@ mod tests {
         assert_eq!(detector.calculate_score(&few_switches), 0.2);
         assert!(!detector.is_flaky(&few_switches));
     }
+
+    #[test]
+    fn test_stable_after_consecutive_non_flaky_windows() {
+        use TestStatus::{Fail, Pass};
+        let detector = FlakeDetector::new(4, 0.3).with_stable_windows(2);
+        let flaky = [Pass, Fail, Pass, Fail, Pass];
+        assert!(detector.is_flaky(&flaky));
+        assert!(!detector.is_stable(&flaky));
+
+        // Each pass slides the newest window further from the failures
+        let with_passes =
+            |n: usize| -> Vec<TestStatus> { std::iter::repeat_n(Pass, n).chain(flaky).collect() };
+        assert!(!detector.is_stable(&with_passes(2)));
+        assert!(detector.is_stable(&with_passes(3)));
+        assert!(!detector.is_stable(&[Pass]));
+    }
 }
@@ -0,0 +1,146 @@
+//! Expected metadata of each signal kind, checked on ingest
+
+use crate::types::SignalType;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// What ingest does with a signal missing required metadata
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SchemaEnforcement {
+    /// Metadata is not checked
+    #[default]
+    Off,
+    /// The signal is stored and the missing keys recorded as a
+    /// `:signal/schema_violation` fact
+    Flag,
+    /// The request is refused
+    Reject,
+}
+
+impl std::str::FromStr for SchemaEnforcement {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "off" => Ok(Self::Off),
+            "flag" => Ok(Self::Flag),
+            "reject" => Ok(Self::Reject),
+            other => anyhow::bail!("Unknown schema enforcement '{}' (off, flag, reject)", other),
+        }
+    }
+}
+
+/// Metadata keys a signal of its kind lacked
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaViolation {
+    pub signal_type: SignalType,
+    pub missing: Vec<String>,
+}
+
+/// Required metadata keys per signal kind.
+///
+/// API signals need `method` and `status`, gRPC signals `method` and `code`;
+/// other kinds need nothing unless configured.
+#[derive(Debug, Clone)]
+pub struct SignalSchemas {
+    pub enforcement: SchemaEnforcement,
+    required: HashMap<SignalType, Vec<String>>,
+}
+
+impl Default for SignalSchemas {
+    fn default() -> Self {
+        Self::new(SchemaEnforcement::Off)
+    }
+}
+
+impl SignalSchemas {
+    pub fn new(enforcement: SchemaEnforcement) -> Self {
+        let required = HashMap::from([
+            (
+                SignalType::API,
+                vec!["method".to_string(), "status".to_string()],
+            ),
+            (
+                SignalType::GRPC,
+                vec!["method".to_string(), "code".to_string()],
+            ),
+        ]);
+        Self {
+            enforcement,
+            required,
+        }
+    }
+
+    /// Require `keys` of every signal of `signal_type`, replacing the
+    /// keys required so far
+    pub fn with_required<I, S>(mut self, signal_type: SignalType, keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.required
+            .insert(signal_type, keys.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Parse `kind=key|key` entries separated by commas
+    /// (`api=method|status|url,ws=channel`) on top of the built-in schemas.
+    /// An entry with no keys (`api=`) drops the requirement.
+    pub fn parse(enforcement: SchemaEnforcement, spec: &str) -> anyhow::Result<Self> {
+        let mut schemas = Self::new(enforcement);
+        for entry in spec.split(',').filter(|e| !e.trim().is_empty()) {
+            let (kind, keys) = entry
+                .split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("Invalid signal schema entry: {}", entry))?;
+            let keys = keys.split('|').map(str::trim).filter(|k| !k.is_empty());
+            schemas = schemas.with_required(SignalType::from_label(kind.trim()), keys);
+        }
+        Ok(schemas)
+    }
+
+    pub fn required(&self, signal_type: SignalType) -> &[String] {
+        self.required
+            .get(&signal_type)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// The violation of a signal of `signal_type` with `metadata`, if it
+    /// lacks required keys; `None` when enforcement is off
+    pub fn check(
+        &self,
+        signal_type: SignalType,
+        metadata: &BTreeMap<String, serde_json::Value>,
+    ) -> Option<SchemaViolation> {
+        if self.enforcement == SchemaEnforcement::Off {
+            return None;
+        }
+        let missing: Vec<String> = self
+            .required(signal_type)
+            .iter()
+            .filter(|key| metadata.get(*key).is_none_or(|v| v.is_null()))
+            .cloned()
+            .collect();
+        (!missing.is_empty()).then_some(SchemaViolation {
+            signal_type,
+            missing,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_overrides_builtin_keys() -> anyhow::Result<()> {
+        let schemas = SignalSchemas::parse(SchemaEnforcement::Reject, "ws=channel, grpc=")?;
+        assert_eq!(schemas.required(SignalType::WebSocket), ["channel"]);
+        assert!(schemas.required(SignalType::GRPC).is_empty());
+        assert_eq!(schemas.required(SignalType::API), ["method", "status"]);
+        assert!(SignalSchemas::parse(SchemaEnforcement::Flag, "api").is_err());
+        assert!("strict".parse::<SchemaEnforcement>().is_err());
+        Ok(())
+    }
+}
@@ -1,7 +1,7 @@
 //! Core type definitions
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// ULID-based unique identifier
 pub type EntityId = ulid::Ulid;
@@ -11,6 +11,81 @@ pub fn new_entity_id() -> EntityId {
     ulid::Ulid::new()
 }
 
+/// Source of entity IDs, so fixtures can pin the IDs they create
+pub trait IdGenerator: std::fmt::Debug + Send + Sync {
+    fn next_id(&self) -> EntityId;
+}
+
+/// Random ULIDs, as produced by [`new_entity_id`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RandomIdGenerator;
+
+impl IdGenerator for RandomIdGenerator {
+    fn next_id(&self) -> EntityId {
+        new_entity_id()
+    }
+}
+
+/// Deterministic IDs for tests and golden files.
+///
+/// Generators with the same seed yield the same sequence. IDs sort in the
+/// order they were generated: the n-th ID carries timestamp
+/// `SEEDED_EPOCH_MS + n` and seed-derived random bits.
+#[derive(Debug)]
+pub struct SeededIdGenerator {
+    seed: u64,
+    next: std::sync::atomic::AtomicU64,
+}
+
+/// Timestamp of the first ID of a [`SeededIdGenerator`] (2024-01-01T00:00:00Z)
+pub const SEEDED_EPOCH_MS: u64 = 1_704_067_200_000;
+
+impl SeededIdGenerator {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            next: std::sync::atomic::AtomicU64::new(0),
+        }
+    }
+}
+
+impl IdGenerator for SeededIdGenerator {
+    fn next_id(&self) -> EntityId {
+        let n = self.next.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let high = splitmix64(self.seed ^ n.wrapping_mul(2));
+        let low = splitmix64(self.seed ^ n.wrapping_mul(2).wrapping_add(1));
+        let random = (u128::from(high) << 64 | u128::from(low)) & ((1 << 80) - 1);
+        ulid::Ulid::from_parts(SEEDED_EPOCH_MS + n, random)
+    }
+}
+
+/// SplitMix64 finalizer: spreads a counter into well-mixed bits
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Lifecycle state of a run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RunStatus {
+    Running,
+    Completed,
+    Cancelled,
+}
+
+impl std::fmt::Display for RunStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Running => "Running",
+            Self::Completed => "Completed",
+            Self::Cancelled => "Cancelled",
+        })
+    }
+}
+
 /// Test status enumeration
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -27,10 +102,33 @@ impl TestStatus {
     pub fn is_pass(&self) -> bool {
         matches!(self, Self::Pass)
     }
+
+    /// Parse a status label as sent by runners (`passed`, `error`, ...).
+    /// Unknown labels are treated as skipped.
+    pub fn from_label(label: &str) -> Self {
+        match label.to_lowercase().as_str() {
+            "pass" | "passed" | "success" => Self::Pass,
+            "fail" | "failed" | "error" => Self::Fail,
+            "xfail" => Self::XFail,
+            "flake" | "flaky" => Self::Flake,
+            "timeout" => Self::Timeout,
+            _ => Self::Skip,
+        }
+    }
+
+    /// Like [`TestStatus::from_label`], but `aliases` (keyed by lowercase
+    /// label) take precedence, so importers can map their framework's own
+    /// vocabulary (`xpass`, `expected_failure`, ...)
+    pub fn from_label_with_aliases(label: &str, aliases: &HashMap<String, TestStatus>) -> Self {
+        aliases
+            .get(&label.to_lowercase())
+            .copied()
+            .unwrap_or_else(|| Self::from_label(label))
+    }
 }
 
 /// Signal type classification
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum SignalType {
     UI,
@@ -42,16 +140,187 @@ pub enum SignalType {
     System,
 }
 
+impl SignalType {
+    /// Parse a signal kind label (`ws`, `db`, ...). Unknown kinds are
+    /// treated as system signals.
+    pub fn from_label(label: &str) -> Self {
+        match label.to_lowercase().as_str() {
+            "ui" => Self::UI,
+            "api" => Self::API,
+            "websocket" | "ws" => Self::WebSocket,
+            "grpc" => Self::GRPC,
+            "database" | "db" => Self::Database,
+            "network" => Self::Network,
+            _ => Self::System,
+        }
+    }
+}
+
 /// Error classification
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// In JSON, any shape is accepted (see [`TestError::from_value`]); `error_type`
+/// is read as a legacy alias of `kind`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct TestError {
-    pub error_type: String,
+    /// Error class, e.g. `AssertionError` or `timeout`
+    pub kind: String,
     pub message: String,
     pub stack_trace: Option<String>,
     pub source_location: Option<SourceLocation>,
+    /// Anything else reported with the error, kept verbatim
+    #[serde(with = "json_details")]
+    pub details: Option<serde_json::Value>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Shape of a structured error in binary (non self-describing) formats
+#[derive(Deserialize)]
+struct StructuredTestError {
+    kind: String,
+    message: String,
+    stack_trace: Option<String>,
+    source_location: Option<SourceLocation>,
+    #[serde(with = "json_details")]
+    details: Option<serde_json::Value>,
+}
+
+impl TestError {
+    /// Kind given to errors whose JSON shape carries no message
+    pub const UNKNOWN_KIND: &'static str = "unknown";
+
+    pub fn new(kind: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            kind: kind.into(),
+            message: message.into(),
+            stack_trace: None,
+            source_location: None,
+            details: None,
+        }
+    }
+
+    /// Interpret a free-form JSON error without losing information.
+    ///
+    /// A string is the message. An object with a string `message` is read as
+    /// a structured error (`kind`, falling back to `error_type` or `type`;
+    /// `stack_trace` or `stack`); its remaining fields are kept in `details`.
+    /// Any other value becomes an error of kind [`TestError::UNKNOWN_KIND`]
+    /// with the value itself as `details`.
+    pub fn from_value(value: serde_json::Value) -> Self {
+        use serde_json::Value;
+
+        const KNOWN_FIELDS: [&str; 8] = [
+            "kind",
+            "error_type",
+            "type",
+            "message",
+            "stack_trace",
+            "stack",
+            "source_location",
+            "details",
+        ];
+
+        let mut fields = match value {
+            Value::String(message) => return Self::new("error", message),
+            Value::Object(fields) if fields.get("message").is_some_and(Value::is_string) => fields,
+            other => {
+                return Self {
+                    details: Some(other.clone()),
+                    ..Self::new(Self::UNKNOWN_KIND, other.to_string())
+                }
+            }
+        };
+
+        // Known fields left empty carry nothing worth keeping
+        for key in KNOWN_FIELDS {
+            if fields.get(key) == Some(&Value::Null) {
+                fields.remove(key);
+            }
+        }
+
+        let mut take_string = |keys: &[&str]| {
+            keys.iter().find_map(|key| match fields.get(*key) {
+                Some(Value::String(_)) => match fields.remove(*key) {
+                    Some(Value::String(s)) => Some(s),
+                    _ => None,
+                },
+                _ => None,
+            })
+        };
+        let message = take_string(&["message"]).unwrap_or_default();
+        let kind = take_string(&["kind", "error_type", "type"]).unwrap_or_else(|| "error".into());
+        let stack_trace = take_string(&["stack_trace", "stack"]);
+
+        let source_location = fields
+            .get("source_location")
+            .and_then(|v| serde_json::from_value(v.clone()).ok());
+        if source_location.is_some() {
+            fields.remove("source_location");
+        }
+
+        // An explicit `details` is kept as is unless other unknown fields
+        // need to be kept alongside it
+        let details = match fields.len() {
+            0 => None,
+            1 if fields.contains_key("details") => fields.remove("details"),
+            _ => Some(Value::Object(fields)),
+        };
+
+        Self {
+            kind,
+            message,
+            stack_trace,
+            source_location,
+            details,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for TestError {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            return serde_json::Value::deserialize(deserializer).map(Self::from_value);
+        }
+        let e = StructuredTestError::deserialize(deserializer)?;
+        Ok(Self {
+            kind: e.kind,
+            message: e.message,
+            stack_trace: e.stack_trace,
+            source_location: e.source_location,
+            details: e.details,
+        })
+    }
+}
+
+/// `details` as plain JSON in human-readable formats, and as JSON text in
+/// binary ones (which cannot carry arbitrary JSON values)
+mod json_details {
+    use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        details: &Option<serde_json::Value>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            return details.serialize(serializer);
+        }
+        details
+            .as_ref()
+            .map(serde_json::Value::to_string)
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<serde_json::Value>, D::Error> {
+        if deserializer.is_human_readable() {
+            return Option::<serde_json::Value>::deserialize(deserializer);
+        }
+        Option::<String>::deserialize(deserializer)?
+            .map(|text| serde_json::from_str(&text).map_err(D::Error::custom))
+            .transpose()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SourceLocation {
     pub file: String,
     pub line: u32,
@@ -59,17 +328,223 @@ pub struct SourceLocation {
 }
 
 /// Environment snapshot
-pub type Environment = HashMap<String, String>;
+///
+/// Well-known keys get typed fields; everything else lands in `extra`.
+/// Serializes as a flat string map (`{"browser": "chrome", "region": "eu"}`),
+/// the shape runs have always been stored and ingested in.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Environment {
+    pub browser: Option<String>,
+    pub os: Option<String>,
+    pub node: Option<String>,
+    pub extra: BTreeMap<String, String>,
+}
+
+/// Why an ingested environment was rejected
+#[derive(Debug, thiserror::Error)]
+pub enum EnvironmentError {
+    #[error("env must be a JSON object")]
+    NotAnObject,
+    #[error("env value for `{0}` must be a string, number or boolean")]
+    NestedValue(String),
+}
+
+impl Environment {
+    /// Build from flat key/value pairs, normalizing well-known keys.
+    ///
+    /// Keys are trimmed; `browser`, `os` and `node` match case-insensitively
+    /// (along with the aliases `browser_name`, `platform` and
+    /// `node_version`) and blank values for them are dropped.
+    pub fn from_pairs<I, K, V>(pairs: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: Into<String>,
+    {
+        let mut env = Self::default();
+        for (key, value) in pairs {
+            let key = key.as_ref().trim();
+            let value: String = value.into();
+            let known = match key.to_ascii_lowercase().as_str() {
+                "browser" | "browser_name" => Some(&mut env.browser),
+                "os" | "platform" => Some(&mut env.os),
+                "node" | "node_version" => Some(&mut env.node),
+                _ => None,
+            };
+            match known {
+                Some(field) => {
+                    let value = value.trim();
+                    if !value.is_empty() {
+                        *field = Some(value.to_string());
+                    }
+                }
+                None if !key.is_empty() => {
+                    env.extra.insert(key.to_string(), value);
+                }
+                None => {}
+            }
+        }
+        env
+    }
+
+    /// Validate and normalize an environment received over the wire.
+    ///
+    /// Numbers and booleans are stringified and nulls dropped; nested arrays
+    /// or objects are rejected.
+    pub fn from_json(value: &serde_json::Value) -> Result<Self, EnvironmentError> {
+        let object = value.as_object().ok_or(EnvironmentError::NotAnObject)?;
+        let mut pairs = Vec::with_capacity(object.len());
+        for (key, value) in object {
+            let value = match value {
+                serde_json::Value::Null => continue,
+                serde_json::Value::String(s) => s.clone(),
+                serde_json::Value::Bool(_) | serde_json::Value::Number(_) => value.to_string(),
+                _ => return Err(EnvironmentError::NestedValue(key.clone())),
+            };
+            pairs.push((key.as_str(), value));
+        }
+        Ok(Self::from_pairs(pairs))
+    }
+
+    /// Flat key/value view, the serialized shape
+    pub fn to_map(&self) -> BTreeMap<String, String> {
+        let mut map = self.extra.clone();
+        for (key, value) in [
+            ("browser", &self.browser),
+            ("os", &self.os),
+            ("node", &self.node),
+        ] {
+            if let Some(value) = value {
+                map.insert(key.to_string(), value.clone());
+            }
+        }
+        map
+    }
+}
+
+impl Serialize for Environment {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_map().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Environment {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        BTreeMap::<String, String>::deserialize(deserializer).map(Self::from_pairs)
+    }
+}
+
+/// Where an artifact's bytes live
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactLocation {
+    /// Path on the local filesystem
+    Local(std::path::PathBuf),
+    /// Any URL (`https://`, `gs://`, ...)
+    Url(String),
+    /// Object in an S3 bucket
+    S3 { bucket: String, key: String },
+}
+
+impl ArtifactLocation {
+    /// Parse a legacy `path` string: `s3://bucket/key` becomes [`Self::S3`],
+    /// other `scheme://` strings [`Self::Url`], anything else [`Self::Local`].
+    pub fn parse(path: &str) -> Self {
+        if let Some(rest) = path.strip_prefix("s3://") {
+            if let Some((bucket, key)) = rest.split_once('/') {
+                return Self::S3 {
+                    bucket: bucket.to_string(),
+                    key: key.to_string(),
+                };
+            }
+        }
+        if path.contains("://") {
+            return Self::Url(path.to_string());
+        }
+        Self::Local(path.into())
+    }
+
+    /// Link a browser can follow: object-store locations map to their HTTPS
+    /// endpoints, local paths are returned as-is.
+    pub fn href(&self) -> String {
+        match self {
+            Self::Local(path) => path.display().to_string(),
+            Self::Url(url) => match url.strip_prefix("gs://") {
+                Some(object) => format!("https://storage.googleapis.com/{}", object),
+                None => url.clone(),
+            },
+            Self::S3 { bucket, key } => format!("https://{}.s3.amazonaws.com/{}", bucket, key),
+        }
+    }
+}
+
+impl std::fmt::Display for ArtifactLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Local(path) => write!(f, "{}", path.display()),
+            Self::Url(url) => write!(f, "{}", url),
+            Self::S3 { bucket, key } => write!(f, "s3://{}/{}", bucket, key),
+        }
+    }
+}
 
 /// Artifact reference (content-addressed)
+///
+/// Serialized with a `path` string next to `location` so readers that only
+/// know `path` keep working; when `location` is missing it is parsed from
+/// `path` (see [`ArtifactLocation::parse`]).
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "ArtifactRefRepr", into = "ArtifactRefRepr")]
 pub struct ArtifactRef {
     pub sha256: String,
-    pub path: String,
+    pub location: ArtifactLocation,
     pub size_bytes: u64,
     pub mime_type: Option<String>,
 }
 
+impl ArtifactRef {
+    /// Compatibility alias for the former `path` field
+    pub fn path(&self) -> String {
+        self.location.to_string()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct ArtifactRefRepr {
+    sha256: String,
+    #[serde(default)]
+    path: String,
+    #[serde(default)]
+    location: Option<ArtifactLocation>,
+    size_bytes: u64,
+    mime_type: Option<String>,
+}
+
+impl From<ArtifactRefRepr> for ArtifactRef {
+    fn from(repr: ArtifactRefRepr) -> Self {
+        Self {
+            sha256: repr.sha256,
+            location: repr
+                .location
+                .unwrap_or_else(|| ArtifactLocation::parse(&repr.path)),
+            size_bytes: repr.size_bytes,
+            mime_type: repr.mime_type,
+        }
+    }
+}
+
+impl From<ArtifactRef> for ArtifactRefRepr {
+    fn from(artifact_ref: ArtifactRef) -> Self {
+        Self {
+            sha256: artifact_ref.sha256,
+            path: artifact_ref.location.to_string(),
+            location: Some(artifact_ref.location),
+            size_bytes: artifact_ref.size_bytes,
+            mime_type: artifact_ref.mime_type,
+        }
+    }
+}
+
 /// Resonance pattern (for flake detection)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResonancePattern {
@@ -80,3 +555,197 @@ pub struct ResonancePattern {
     pub first_seen: chrono::DateTime<chrono::Utc>,
     pub last_seen: chrono::DateTime<chrono::Utc>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn artifact_ref(location: ArtifactLocation) -> ArtifactRef {
+        ArtifactRef {
+            sha256: "abc123".to_string(),
+            location,
+            size_bytes: 1024,
+            mime_type: Some("image/png".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_artifact_location_round_trip() {
+        let locations = [
+            ArtifactLocation::Local("/var/liminal/shot.png".into()),
+            ArtifactLocation::Url("https://cdn.example.com/shot.png".to_string()),
+            ArtifactLocation::S3 {
+                bucket: "qa-artifacts".to_string(),
+                key: "runs/1/shot.png".to_string(),
+            },
+        ];
+
+        for location in locations {
+            let json = serde_json::to_value(artifact_ref(location.clone())).unwrap();
+            // Legacy readers still find `path`
+            assert_eq!(json["path"], location.to_string());
+
+            let decoded: ArtifactRef = serde_json::from_value(json).unwrap();
+            assert_eq!(decoded.location, location);
+            assert_eq!(ArtifactLocation::parse(&decoded.path()), location);
+        }
+    }
+
+    #[test]
+    fn test_artifact_ref_from_legacy_path() {
+        let legacy = serde_json::json!({
+            "sha256": "abc123",
+            "path": "s3://qa-artifacts/runs/1/shot.png",
+            "size_bytes": 1024,
+            "mime_type": null
+        });
+
+        let decoded: ArtifactRef = serde_json::from_value(legacy).unwrap();
+        assert_eq!(
+            decoded.location,
+            ArtifactLocation::S3 {
+                bucket: "qa-artifacts".to_string(),
+                key: "runs/1/shot.png".to_string(),
+            }
+        );
+        assert_eq!(
+            decoded.location.href(),
+            "https://qa-artifacts.s3.amazonaws.com/runs/1/shot.png"
+        );
+    }
+
+    #[test]
+    fn test_environment_round_trips_known_and_extra_keys() {
+        let json = serde_json::json!({
+            "Browser": " chrome ",
+            "platform": "linux",
+            "node": 20,
+            "region": "eu-west-1",
+            "headless": true,
+            "proxy": null,
+        });
+
+        let env = Environment::from_json(&json).expect("env should be valid");
+        assert_eq!(env.browser.as_deref(), Some("chrome"));
+        assert_eq!(env.os.as_deref(), Some("linux"));
+        assert_eq!(env.node.as_deref(), Some("20"));
+        assert_eq!(
+            env.extra.get("region").map(String::as_str),
+            Some("eu-west-1")
+        );
+        assert_eq!(env.extra.get("headless").map(String::as_str), Some("true"));
+        assert!(!env.extra.contains_key("proxy"));
+
+        // Serialized back as the flat map, with normalized known keys
+        let value = serde_json::to_value(&env).expect("serialize env");
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "browser": "chrome",
+                "os": "linux",
+                "node": "20",
+                "region": "eu-west-1",
+                "headless": "true",
+            })
+        );
+        let decoded: Environment = serde_json::from_value(value).expect("deserialize env");
+        assert_eq!(decoded, env);
+
+        assert!(Environment::from_json(&serde_json::json!(["chrome"])).is_err());
+        assert!(
+            Environment::from_json(&serde_json::json!({ "browser": { "name": "chrome" } }))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_seeded_generators_repeat_the_same_ids() {
+        let a = SeededIdGenerator::new(42);
+        let b = SeededIdGenerator::new(42);
+        let other = SeededIdGenerator::new(43);
+
+        let ids_a: Vec<EntityId> = (0..5).map(|_| a.next_id()).collect();
+        let ids_b: Vec<EntityId> = (0..5).map(|_| b.next_id()).collect();
+        let ids_other: Vec<EntityId> = (0..5).map(|_| other.next_id()).collect();
+
+        assert_eq!(ids_a, ids_b);
+        assert_ne!(ids_a, ids_other);
+        // Unique and in generation order
+        assert!(ids_a.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(ids_a[0].timestamp_ms(), SEEDED_EPOCH_MS);
+    }
+
+    #[test]
+    fn test_status_aliases_take_precedence() {
+        let aliases = HashMap::from([
+            ("error".to_string(), TestStatus::Fail),
+            ("xpass".to_string(), TestStatus::XFail),
+            ("passed".to_string(), TestStatus::Flake),
+        ]);
+
+        assert_eq!(
+            TestStatus::from_label_with_aliases("ERROR", &aliases),
+            TestStatus::Fail
+        );
+        assert_eq!(
+            TestStatus::from_label_with_aliases("xpass", &aliases),
+            TestStatus::XFail
+        );
+        assert_eq!(
+            TestStatus::from_label_with_aliases("passed", &aliases),
+            TestStatus::Flake
+        );
+        // Labels without an alias keep the built-in mapping
+        assert_eq!(
+            TestStatus::from_label_with_aliases("timeout", &aliases),
+            TestStatus::Timeout
+        );
+        assert_eq!(TestStatus::from_label("xpass"), TestStatus::Skip);
+    }
+
+    #[test]
+    fn test_structured_error_round_trip() {
+        let error = TestError {
+            stack_trace: Some("at checkout.rs:12".to_string()),
+            source_location: Some(SourceLocation {
+                file: "checkout.rs".to_string(),
+                line: 12,
+                column: None,
+            }),
+            details: Some(serde_json::json!({"expected": 200, "actual": 500})),
+            ..TestError::new("AssertionError", "expected 200, got 500")
+        };
+
+        let json = serde_json::to_value(&error).unwrap();
+        assert_eq!(json["kind"], "AssertionError");
+        assert_eq!(json["details"]["actual"], 500);
+        let decoded: TestError = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded, error);
+    }
+
+    #[test]
+    fn test_legacy_errors_are_read_losslessly() {
+        let legacy: TestError = serde_json::from_value(serde_json::json!({
+            "error_type": "Timeout",
+            "message": "no response after 30s",
+            "stack_trace": null,
+            "source_location": null,
+            "endpoint": "/api/pay",
+        }))
+        .unwrap();
+        assert_eq!(legacy.kind, "Timeout");
+        assert_eq!(legacy.message, "no response after 30s");
+        assert_eq!(
+            legacy.details,
+            Some(serde_json::json!({"endpoint": "/api/pay"}))
+        );
+
+        let text: TestError = serde_json::from_value(serde_json::json!("boom")).unwrap();
+        assert_eq!(text, TestError::new("error", "boom"));
+
+        let shapeless = serde_json::json!({"code": 7, "reasons": ["a", "b"]});
+        let unknown: TestError = serde_json::from_value(shapeless.clone()).unwrap();
+        assert_eq!(unknown.kind, TestError::UNKNOWN_KIND);
+        assert_eq!(unknown.details, Some(shapeless));
+    }
+}
@@ -0,0 +1,120 @@
+//! Storage backends behind one interface
+//!
+//! [`LiminalDB`] (sled) implements [`Storage`] here; the Postgres store of
+//! `services/liminal-ingest` implements it as well, and that service writes
+//! runs and tests through `Arc<dyn Storage>` when configured for sled.
+//!
+//! The trait covers runs, tests and test history only. `liminalqa-ingest`
+//! keeps using [`LiminalDB`] directly: its handlers rely on facts, indexes
+//! and batch transactions that have no Postgres counterpart.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use liminalqa_core::entities::{Run, Test};
+
+use crate::storage::LiminalDB;
+
+/// Operations every storage backend supports
+#[async_trait]
+pub trait Storage: Send + Sync {
+    /// Short backend name for logs (`sled`, `postgres`)
+    fn backend(&self) -> &'static str;
+
+    /// Store a run, replacing an earlier record with the same id
+    async fn put_run(&self, run: &Run) -> Result<()>;
+
+    /// Store test executions; `created_at.valid_time` is when each result
+    /// became true
+    async fn put_tests(&self, tests: &[Test]) -> Result<()>;
+
+    /// Executions of a test (name + suite), newest first, at most `limit`
+    async fn test_history(&self, name: &str, suite: &str, limit: usize) -> Result<Vec<Test>>;
+}
+
+#[async_trait]
+impl Storage for LiminalDB {
+    fn backend(&self) -> &'static str {
+        "sled"
+    }
+
+    async fn put_run(&self, run: &Run) -> Result<()> {
+        LiminalDB::put_run(self, run)
+    }
+
+    async fn put_tests(&self, tests: &[Test]) -> Result<()> {
+        for test in tests {
+            self.put_test(test)?;
+        }
+        Ok(())
+    }
+
+    async fn test_history(&self, name: &str, suite: &str, limit: usize) -> Result<Vec<Test>> {
+        self.get_test_history(name, suite, limit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+    use liminalqa_core::{
+        entities::EntityType,
+        temporal::BiTemporalTime,
+        types::{EntityId, TestStatus},
+    };
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn execution(run_id: EntityId, status: TestStatus, minutes_ago: i64) -> Test {
+        let started_at = Utc::now() - Duration::minutes(minutes_ago);
+        Test {
+            id: EntityId::new(),
+            run_id,
+            name: "test_login".to_string(),
+            suite: "auth".to_string(),
+            guidance: String::new(),
+            status,
+            duration_ms: 100,
+            error: None,
+            started_at,
+            completed_at: started_at + Duration::milliseconds(100),
+            created_at: BiTemporalTime::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sled_backend_through_trait_object() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = Arc::new(LiminalDB::open(temp_dir.path())?);
+        let storage: Arc<dyn Storage> = db.clone();
+        assert_eq!(storage.backend(), "sled");
+
+        let run = Run {
+            id: EntityId::new(),
+            build_id: EntityId::new(),
+            plan_name: "smoke".to_string(),
+            env: Default::default(),
+            started_at: Utc::now(),
+            ended_at: None,
+            runner_version: "1.0.0".to_string(),
+            liminal_os_version: None,
+            created_at: BiTemporalTime::now(),
+        };
+        storage.put_run(&run).await?;
+        storage
+            .put_tests(&[
+                execution(run.id, TestStatus::Pass, 3),
+                execution(run.id, TestStatus::Fail, 2),
+                execution(run.id, TestStatus::Pass, 1),
+            ])
+            .await?;
+        let history = storage.test_history("test_login", "auth", 2).await?;
+        let statuses: Vec<TestStatus> = history.iter().map(|t| t.status).collect();
+        assert_eq!(statuses, [TestStatus::Pass, TestStatus::Fail]);
+
+        let stored: Option<Run> = db.get_entity(run.id)?;
+        assert_eq!(stored.map(|r| r.plan_name), Some("smoke".to_string()));
+        assert_eq!(db.get_entities_by_type(EntityType::Test)?.len(), 3);
+        Ok(())
+    }
+}
@@ -0,0 +1,46 @@
+//! Typed database errors
+//!
+//! Returned inside `anyhow::Error`; match with `err.downcast_ref::<DbError>()`.
+
+use liminalqa_core::types::{EntityId, RunStatus};
+use std::path::PathBuf;
+
+/// Errors callers may want to tell apart from I/O or encoding failures
+#[derive(Debug, thiserror::Error)]
+pub enum DbError {
+    /// A fact value exceeds the configured size limit
+    #[error(
+        "value of fact {attribute} is {size} bytes, over the {limit} byte limit; \
+         store large payloads as artifacts and reference them from the fact"
+    )]
+    FactValueTooLarge {
+        attribute: String,
+        size: usize,
+        limit: usize,
+    },
+
+    /// Another process (or another handle in this one) holds the lock on
+    /// the database directory
+    #[error(
+        "database at {} is already open elsewhere; stop the other limctl or \
+         ingest server using it, or point this one at a different directory",
+        path.display()
+    )]
+    AlreadyOpen { path: PathBuf },
+
+    /// No run with this id is stored
+    #[error("run not found: {0}")]
+    RunNotFound(EntityId),
+
+    /// The run already completed or was already cancelled
+    #[error("run {run_id} is already {status:?}")]
+    RunAlreadyEnded { run_id: EntityId, status: RunStatus },
+
+    /// The run changed since the version the caller based its update on
+    #[error("run {run_id} is at version {actual}, not {expected}; re-read it and retry")]
+    RunVersionConflict {
+        run_id: EntityId,
+        expected: u64,
+        actual: u64,
+    },
+}
@@ -0,0 +1,173 @@
+//! Entity layouts written by earlier versions
+//!
+//! Entities are bincode-encoded, which is positional: a struct that gains a
+//! field no longer decodes records written before. Each superseded layout is
+//! kept here and upgraded on read, see [`decode_entity`].
+
+use anyhow::Result;
+use bincode::Options;
+use chrono::{DateTime, Utc};
+use liminalqa_core::{
+    entities::{Artifact, ArtifactType, Resonance, Test},
+    resonance::FlakeDetector,
+    temporal::BiTemporalTime,
+    types::{
+        ArtifactLocation, ArtifactRef, EntityId, ResonancePattern, SourceLocation, TestError,
+        TestStatus,
+    },
+};
+use serde::{Deserialize, Serialize};
+
+/// `TestError` before it gained `kind` (then `error_type`) and `details`
+#[derive(Serialize, Deserialize)]
+pub(crate) struct TestErrorV1 {
+    pub error_type: String,
+    pub message: String,
+    pub stack_trace: Option<String>,
+    pub source_location: Option<SourceLocation>,
+}
+
+/// `Test` carrying a [`TestErrorV1`]
+#[derive(Serialize, Deserialize)]
+pub(crate) struct TestV1 {
+    pub id: EntityId,
+    pub run_id: EntityId,
+    pub name: String,
+    pub suite: String,
+    pub guidance: String,
+    pub status: TestStatus,
+    pub duration_ms: u64,
+    pub error: Option<TestErrorV1>,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: DateTime<Utc>,
+    pub created_at: BiTemporalTime,
+}
+
+impl From<TestV1> for Test {
+    fn from(old: TestV1) -> Self {
+        Test {
+            id: old.id,
+            run_id: old.run_id,
+            name: old.name,
+            suite: old.suite,
+            guidance: old.guidance,
+            status: old.status,
+            duration_ms: old.duration_ms,
+            error: old.error.map(|e| TestError {
+                kind: e.error_type,
+                message: e.message,
+                stack_trace: e.stack_trace,
+                source_location: e.source_location,
+                details: None,
+            }),
+            started_at: old.started_at,
+            completed_at: old.completed_at,
+            created_at: old.created_at,
+        }
+    }
+}
+
+/// `ArtifactRef` before it gained `location`
+#[derive(Serialize, Deserialize)]
+pub(crate) struct ArtifactRefV1 {
+    pub sha256: String,
+    pub path: String,
+    pub size_bytes: u64,
+    pub mime_type: Option<String>,
+}
+
+/// `Artifact` carrying an [`ArtifactRefV1`]
+#[derive(Serialize, Deserialize)]
+pub(crate) struct ArtifactV1 {
+    pub id: EntityId,
+    pub run_id: EntityId,
+    pub test_id: EntityId,
+    pub artifact_ref: ArtifactRefV1,
+    pub artifact_type: ArtifactType,
+    pub description: Option<String>,
+    pub created_at: BiTemporalTime,
+}
+
+impl From<ArtifactV1> for Artifact {
+    fn from(old: ArtifactV1) -> Self {
+        Artifact {
+            id: old.id,
+            run_id: old.run_id,
+            test_id: old.test_id,
+            artifact_ref: ArtifactRef {
+                sha256: old.artifact_ref.sha256,
+                location: ArtifactLocation::parse(&old.artifact_ref.path),
+                size_bytes: old.artifact_ref.size_bytes,
+                mime_type: old.artifact_ref.mime_type,
+            },
+            artifact_type: old.artifact_type,
+            description: old.description,
+            created_at: old.created_at,
+        }
+    }
+}
+
+/// `Resonance` before it recorded its `detector`
+#[derive(Serialize, Deserialize)]
+pub(crate) struct ResonanceV1 {
+    pub id: EntityId,
+    pub pattern: ResonancePattern,
+    pub affected_tests: Vec<EntityId>,
+    pub root_cause: Option<String>,
+    pub created_at: BiTemporalTime,
+}
+
+impl From<ResonanceV1> for Resonance {
+    /// The flake detector was the only built-in one, and its resonances are
+    /// recognised by their description; others keep an empty detector
+    fn from(old: ResonanceV1) -> Self {
+        let detector = if old
+            .pattern
+            .description
+            .starts_with(FlakeDetector::DESCRIPTION_PREFIX)
+        {
+            FlakeDetector::NAME.to_string()
+        } else {
+            String::new()
+        };
+        Resonance {
+            id: old.id,
+            detector,
+            pattern: old.pattern,
+            affected_tests: old.affected_tests,
+            root_cause: old.root_cause,
+            created_at: old.created_at,
+        }
+    }
+}
+
+/// Decode a bincode entity, falling back to superseded layouts
+///
+/// A failed test written with [`TestErrorV1`], an artifact written with
+/// [`ArtifactRefV1`] or a [`ResonanceV1`] does not decode as its current
+/// type; it is upgraded and then decoded as `T`. A layout must consume the record exactly, so an old
+/// record is not misread as a newer layout that happens to fit its prefix.
+/// Records that match no layout report the error of the current one.
+pub(crate) fn decode_entity<T: for<'de> Deserialize<'de>>(bytes: &[u8]) -> Result<T> {
+    let err = match exact().deserialize(bytes) {
+        Ok(entity) => return Ok(entity),
+        Err(err) => err,
+    };
+    let upgraded = if let Ok(old) = exact().deserialize::<TestV1>(bytes) {
+        bincode::serialize(&Test::from(old))?
+    } else if let Ok(old) = exact().deserialize::<ArtifactV1>(bytes) {
+        bincode::serialize(&Artifact::from(old))?
+    } else if let Ok(old) = exact().deserialize::<ResonanceV1>(bytes) {
+        bincode::serialize(&Resonance::from(old))?
+    } else {
+        return Err(err.into());
+    };
+    exact().deserialize(&upgraded).map_err(|_| err.into())
+}
+
+/// The options of `bincode::serialize`, refusing trailing bytes
+fn exact() -> impl Options {
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .reject_trailing_bytes()
+}
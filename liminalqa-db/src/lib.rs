@@ -6,12 +6,26 @@
 //! - Causality walks (trace root causes)
 //! - Efficient indexing for time-based queries
 
+pub mod backend;
+pub mod error;
 pub mod index;
+mod legacy;
 pub mod query;
+pub mod report;
+pub mod spillover;
 pub mod storage;
 
-pub use query::{Query, QueryResult};
-pub use storage::LiminalDB;
+pub use backend::Storage;
+pub use error::DbError;
+pub use query::{Query, QueryCache, QueryCacheStats, QueryResult, ValueOp, ValuePredicate};
+pub use report::{
+    build_owner_report, build_report, build_report_at, build_report_with_window, build_runs,
+    cache_run_summary, previous_run,
+};
+pub use storage::{
+    CachedRunSummary, DeleteCounts, FactPage, LiminalDB, SledConfig, SledMode, TimelineBucket,
+    DEFAULT_MAX_FACT_VALUE_BYTES, DEFAULT_MIN_BASELINE_SAMPLES, LATENCY_BASELINE_WINDOW,
+};
 
 use anyhow::Result;
 
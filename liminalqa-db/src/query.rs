@@ -1,14 +1,105 @@
 //! Query interface for bi-temporal data
 
 use anyhow::Result;
+use chrono::Utc;
 use liminalqa_core::{
+    facts::{Attribute, Fact},
     temporal::{TimeRange, TimeshiftQuery},
     types::EntityId,
 };
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::Mutex;
 
 use crate::storage::LiminalDB;
 
+/// Comparison applied to a fact's JSON value
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", content = "value", rename_all = "snake_case")]
+pub enum ValueOp {
+    Eq(Value),
+    Ne(Value),
+    Gt(f64),
+    Gte(f64),
+    Lt(f64),
+    Lte(f64),
+}
+
+/// Filter on fact values, optionally restricted to one attribute.
+///
+/// Values are only compared with values of the same JSON type: a fact whose
+/// value has another type is excluded, whatever the operator (`ne` included).
+/// The ordering operators apply to numbers only, and numbers compare by
+/// value, so `1000` equals `1000.0`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValuePredicate {
+    /// Facts of other attributes are excluded when set
+    pub attribute: Option<Attribute>,
+    #[serde(flatten)]
+    pub op: ValueOp,
+}
+
+impl ValuePredicate {
+    pub fn new(attribute: Attribute, op: ValueOp) -> Self {
+        Self {
+            attribute: Some(attribute),
+            op,
+        }
+    }
+
+    /// Facts of `attribute` whose value equals `value`
+    pub fn eq(attribute: Attribute, value: impl Into<Value>) -> Self {
+        Self::new(attribute, ValueOp::Eq(value.into()))
+    }
+
+    /// Facts of `attribute` with a numeric value greater than `n`
+    pub fn gt(attribute: Attribute, n: f64) -> Self {
+        Self::new(attribute, ValueOp::Gt(n))
+    }
+
+    /// Facts of `attribute` with a numeric value less than `n`
+    pub fn lt(attribute: Attribute, n: f64) -> Self {
+        Self::new(attribute, ValueOp::Lt(n))
+    }
+
+    pub fn matches(&self, fact: &Fact) -> bool {
+        if self
+            .attribute
+            .as_ref()
+            .is_some_and(|a| *a != fact.attribute)
+        {
+            return false;
+        }
+
+        let number = fact.value.as_f64();
+        match &self.op {
+            ValueOp::Eq(expected) => {
+                same_type(&fact.value, expected) && values_equal(&fact.value, expected)
+            }
+            ValueOp::Ne(expected) => {
+                same_type(&fact.value, expected) && !values_equal(&fact.value, expected)
+            }
+            ValueOp::Gt(n) => number.is_some_and(|v| v > *n),
+            ValueOp::Gte(n) => number.is_some_and(|v| v >= *n),
+            ValueOp::Lt(n) => number.is_some_and(|v| v < *n),
+            ValueOp::Lte(n) => number.is_some_and(|v| v <= *n),
+        }
+    }
+}
+
+fn same_type(a: &Value, b: &Value) -> bool {
+    std::mem::discriminant(a) == std::mem::discriminant(b)
+}
+
+fn values_equal(a: &Value, b: &Value) -> bool {
+    match (a.as_f64(), b.as_f64()) {
+        (Some(x), Some(y)) => x == y,
+        _ => a == b,
+    }
+}
+
 /// Query builder
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Query {
@@ -16,6 +107,8 @@ pub struct Query {
     pub valid_time_range: Option<TimeRange>,
     pub tx_time_range: Option<TimeRange>,
     pub timeshift: Option<TimeshiftQuery>,
+    /// Applied after the time filters, before the limit
+    pub value_predicate: Option<ValuePredicate>,
     pub limit: Option<usize>,
 }
 
@@ -26,6 +119,7 @@ impl Query {
             valid_time_range: None,
             tx_time_range: None,
             timeshift: None,
+            value_predicate: None,
             limit: None,
         }
     }
@@ -50,13 +144,36 @@ impl Query {
         self
     }
 
+    /// Keep only facts whose value satisfies `predicate`
+    pub fn value_predicate(mut self, predicate: ValuePredicate) -> Self {
+        self.value_predicate = Some(predicate);
+        self
+    }
+
     pub fn limit(mut self, n: usize) -> Self {
         self.limit = Some(n);
         self
     }
 
-    /// Execute the query against a database
+    /// Execute the query against a database, served from its query cache
+    /// when enabled (see [`LiminalDB::with_query_cache`])
     pub fn execute(&self, db: &LiminalDB) -> Result<QueryResult> {
+        let Some(cache) = db.query_cache() else {
+            return self.run(db);
+        };
+        let key = serde_json::to_vec(self)?;
+        if let Some(result) = cache.get(&key) {
+            return Ok(result);
+        }
+        // A write while running invalidates the cache; don't store results
+        // that may predate it
+        let generation = cache.generation();
+        let result = self.run(db)?;
+        cache.insert(key, result.clone(), generation);
+        Ok(result)
+    }
+
+    fn run(&self, db: &LiminalDB) -> Result<QueryResult> {
         // Step 1: Get candidate facts based on primary filter
         let mut facts = if let Some(ref entity_ids) = self.entity_ids {
             db.scan_facts_by_entities(entity_ids)?
@@ -84,6 +201,18 @@ impl Query {
             });
         }
 
+        // Tombstones are never results; the facts they retract are hidden
+        // from views at or after the retraction
+        let view_tx = self
+            .timeshift
+            .as_ref()
+            .map_or_else(Utc::now, |timeshift| timeshift.tx_time);
+        let mut facts = db.without_retracted(facts, view_tx)?;
+
+        if let Some(ref predicate) = self.value_predicate {
+            facts.retain(|f| predicate.matches(f));
+        }
+
         // Step 3: Apply limit
         if let Some(limit) = self.limit {
             facts.truncate(limit);
@@ -99,15 +228,138 @@ impl Default for Query {
     }
 }
 
+/// Hit and miss counts of a [`QueryCache`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueryCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+}
+
+/// Least recently used cache of query results, keyed by a hash of the
+/// serialized query. Cleared on every write that can change a result.
+#[derive(Debug)]
+pub struct QueryCache {
+    capacity: usize,
+    state: Mutex<QueryCacheState>,
+}
+
+#[derive(Debug, Default)]
+struct QueryCacheState {
+    entries: HashMap<u64, CachedQuery>,
+    /// Bumped on every invalidation
+    generation: u64,
+    /// Logical clock ordering entries by last use
+    tick: u64,
+    hits: u64,
+    misses: u64,
+}
+
+#[derive(Debug)]
+struct CachedQuery {
+    /// The serialized query, to tell hash collisions apart
+    query: Vec<u8>,
+    result: QueryResult,
+    last_used: u64,
+}
+
+impl QueryCache {
+    /// Cache holding up to `capacity` results (at least 1)
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            state: Mutex::default(),
+        }
+    }
+
+    pub fn stats(&self) -> QueryCacheStats {
+        let state = self.lock();
+        QueryCacheStats {
+            hits: state.hits,
+            misses: state.misses,
+            entries: state.entries.len(),
+        }
+    }
+
+    /// Drop every cached result
+    pub fn invalidate(&self) {
+        let mut state = self.lock();
+        state.entries.clear();
+        state.generation += 1;
+    }
+
+    fn generation(&self) -> u64 {
+        self.lock().generation
+    }
+
+    fn get(&self, query: &[u8]) -> Option<QueryResult> {
+        let mut state = self.lock();
+        state.tick += 1;
+        let tick = state.tick;
+        let result = state
+            .entries
+            .get_mut(&hash(query))
+            .filter(|entry| entry.query == query)
+            .map(|entry| {
+                entry.last_used = tick;
+                entry.result.clone()
+            });
+        match result {
+            Some(_) => state.hits += 1,
+            None => state.misses += 1,
+        }
+        result
+    }
+
+    /// Store `result`, unless the cache was invalidated since `generation`
+    fn insert(&self, query: Vec<u8>, result: QueryResult, generation: u64) {
+        let mut state = self.lock();
+        if state.generation != generation {
+            return;
+        }
+        let key = hash(&query);
+        if !state.entries.contains_key(&key) && state.entries.len() >= self.capacity {
+            let oldest = state
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| *key);
+            if let Some(oldest) = oldest {
+                state.entries.remove(&oldest);
+            }
+        }
+        state.tick += 1;
+        let last_used = state.tick;
+        state.entries.insert(
+            key,
+            CachedQuery {
+                query,
+                result,
+                last_used,
+            },
+        );
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, QueryCacheState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn hash(query: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    query.hash(&mut hasher);
+    hasher.finish()
+}
+
 /// Query result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QueryResult {
-    pub facts: Vec<liminalqa_core::facts::Fact>,
+    pub facts: Vec<Fact>,
     pub total: usize,
 }
 
 impl QueryResult {
-    pub fn new(facts: Vec<liminalqa_core::facts::Fact>) -> Self {
+    pub fn new(facts: Vec<Fact>) -> Self {
         let total = facts.len();
         Self { facts, total }
     }
@@ -118,7 +370,7 @@ mod tests {
     use super::*;
     use chrono::Utc;
     use liminalqa_core::{
-        facts::{Attribute, Fact},
+        facts::{Attribute, Fact, FactKey},
         temporal::{BiTemporalTime, TimeRange, TimeshiftQuery},
         types::EntityId,
     };
@@ -287,6 +539,88 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_retracted_fact_visible_only_before_retraction() -> Result<()> {
+        let (_dir, db) = create_test_db()?;
+        let entity = EntityId::new();
+        db.put_fact(&create_test_fact_with_tx_time(
+            entity,
+            Attribute::TestStatus,
+            1,
+            20,
+            20,
+        ))?;
+        db.put_fact(&create_test_fact_with_tx_time(
+            entity,
+            Attribute::TestDuration,
+            100,
+            20,
+            20,
+        ))?;
+
+        let key = FactKey::new(entity, Attribute::TestStatus);
+        db.retract_fact(&key, Utc::now() - chrono::Duration::minutes(10))?;
+
+        // Current view: the status is gone and the tombstone is not a result
+        let current = Query::new().for_entities(vec![entity]).execute(&db)?;
+        assert_eq!(current.total, 1);
+        assert_eq!(current.facts[0].attribute, Attribute::TestDuration);
+
+        // Before the retraction, the status was still known
+        let before = Query::new()
+            .for_entities(vec![entity])
+            .timeshift(TimeshiftQuery::at(
+                Utc::now() - chrono::Duration::minutes(15),
+            ))
+            .execute(&db)?;
+        assert_eq!(before.total, 2);
+        assert!(before.facts.iter().all(|f| !f.retracted));
+
+        // A fact recorded after the retraction holds again
+        db.put_fact(&create_test_fact(entity, Attribute::TestStatus, 2, 0))?;
+        let current = Query::new()
+            .for_entities(vec![entity])
+            .value_predicate(ValuePredicate::eq(Attribute::TestStatus, 2))
+            .execute(&db)?;
+        assert_eq!(current.total, 1);
+
+        // Nothing recorded under this key
+        let missing = FactKey::new(EntityId::new(), Attribute::TestStatus);
+        assert!(db.retract_fact(&missing, Utc::now()).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_repeated_query_served_from_cache_until_write() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?.with_query_cache(8);
+        let entity_id = EntityId::new();
+        db.put_fact(&create_test_fact(entity_id, Attribute::TestStatus, 1, 10))?;
+
+        let query = Query::new().for_entities(vec![entity_id]);
+        assert_eq!(query.execute(&db)?.total, 1);
+        assert_eq!(query.execute(&db)?.total, 1);
+        let stats = db.query_cache().map(QueryCache::stats).unwrap_or_default();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 1, 1));
+
+        // A write drops the cached result, so the new fact is seen
+        db.put_fact(&create_test_fact(entity_id, Attribute::TestDuration, 2, 5))?;
+        assert_eq!(db.query_cache().map(|c| c.stats().entries), Some(0));
+        assert_eq!(query.execute(&db)?.total, 2);
+        let stats = db.query_cache().map(QueryCache::stats).unwrap_or_default();
+        assert_eq!((stats.hits, stats.misses), (1, 2));
+
+        // Least recently used results are evicted beyond the capacity
+        let db = db.with_query_cache(1);
+        query.execute(&db)?;
+        Query::new().limit(1).execute(&db)?;
+        query.execute(&db)?;
+        let stats = db.query_cache().map(QueryCache::stats).unwrap_or_default();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (0, 3, 1));
+        Ok(())
+    }
+
     #[test]
     fn test_query_combined_filters() -> Result<()> {
         let (_dir, db) = create_test_db()?;
@@ -312,4 +646,104 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_query_status_equals_fail() -> Result<()> {
+        let (_dir, db) = create_test_db()?;
+        let failed = EntityId::new();
+        let passed = EntityId::new();
+
+        db.put_fact(&Fact::new(
+            failed,
+            Attribute::TestStatus,
+            serde_json::json!("fail"),
+        ))?;
+        db.put_fact(&Fact::new(
+            passed,
+            Attribute::TestStatus,
+            serde_json::json!("pass"),
+        ))?;
+        // Same value under another attribute is not a status
+        db.put_fact(&Fact::new(
+            passed,
+            Attribute::TestError,
+            serde_json::json!("fail"),
+        ))?;
+        // Type mismatch: excluded rather than compared
+        db.put_fact(&Fact::new(
+            passed,
+            Attribute::TestStatus,
+            serde_json::json!(0),
+        ))?;
+
+        let query = Query::new().value_predicate(ValuePredicate::eq(Attribute::TestStatus, "fail"));
+        let result = query.execute(&db)?;
+        assert_eq!(result.total, 1);
+        assert_eq!(result.facts[0].entity_id, failed);
+
+        let not_failed = ValuePredicate::new(Attribute::TestStatus, ValueOp::Ne("fail".into()));
+        let result = Query::new().value_predicate(not_failed).execute(&db)?;
+        assert_eq!(result.total, 1);
+        assert_eq!(result.facts[0].value, serde_json::json!("pass"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_query_duration_greater_than() -> Result<()> {
+        let (_dir, db) = create_test_db()?;
+        let entity1 = EntityId::new();
+
+        db.put_fact(&create_test_fact(entity1, Attribute::TestDuration, 800, 30))?;
+        db.put_fact(&create_test_fact(
+            entity1,
+            Attribute::TestDuration,
+            1500,
+            20,
+        ))?;
+        db.put_fact(&create_test_fact(
+            entity1,
+            Attribute::TestDuration,
+            1000,
+            10,
+        ))?;
+        db.put_fact(&create_test_fact(entity1, Attribute::TestStatus, 5000, 10))?;
+        db.put_fact(&Fact::new(
+            entity1,
+            Attribute::TestDuration,
+            serde_json::json!("2000"),
+        ))?;
+
+        let query =
+            Query::new().value_predicate(ValuePredicate::gt(Attribute::TestDuration, 1000.0));
+        let result = query.execute(&db)?;
+        assert_eq!(result.total, 1);
+        assert_eq!(result.facts[0].value, serde_json::json!(1500));
+
+        // Applied before the limit, after the time filters
+        let start = Utc::now() - chrono::Duration::minutes(15);
+        let query = Query::new()
+            .valid_time_range(TimeRange::from(start))
+            .value_predicate(ValuePredicate::lt(Attribute::TestDuration, 1200.0))
+            .limit(5);
+        let result = query.execute(&db)?;
+        assert_eq!(result.total, 1);
+        assert_eq!(result.facts[0].value, serde_json::json!(1000));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_value_predicate_json_shape() {
+        let predicate: ValuePredicate = serde_json::from_value(serde_json::json!({
+            "attribute": ":test/duration",
+            "op": "gt",
+            "value": 1000,
+        }))
+        .unwrap();
+        assert_eq!(
+            predicate,
+            ValuePredicate::gt(Attribute::TestDuration, 1000.0)
+        );
+    }
 }
@@ -0,0 +1,1135 @@
+//! Reflection report read model
+//!
+//! Builds a [`ReflectionReport`] for a run straight from LIMINAL-DB. The
+//! report can be computed as of any past transaction time, which replays
+//! what the database knew at that moment.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use liminalqa_core::{
+    entities::{EntityType, Resonance, Run, Signal, Test, TestProgress},
+    facts::Attribute,
+    report::*,
+    types::{EntityId, RunStatus, TestStatus},
+};
+use std::collections::{HashMap, HashSet};
+
+use crate::storage::LiminalDB;
+
+/// Build the report for a run from everything currently known
+pub fn build_report(db: &LiminalDB, run_id: EntityId) -> Result<ReflectionReport> {
+    build_report_at(db, run_id, Utc::now())
+}
+
+/// Build the report for a run as it looked at transaction time `as_of`.
+///
+/// Entities and facts learned after `as_of` are ignored. `:test/status` and
+/// `:test/duration` facts override the values stored on the test entity,
+/// the latest one known at `as_of` winning.
+pub fn build_report_at(
+    db: &LiminalDB,
+    run_id: EntityId,
+    as_of: DateTime<Utc>,
+) -> Result<ReflectionReport> {
+    build_report_with_window(db, run_id, as_of, CausalityWindow::default())
+}
+
+/// [`build_report_at`] collecting causality trails over `window`
+pub fn build_report_with_window(
+    db: &LiminalDB,
+    run_id: EntityId,
+    as_of: DateTime<Utc>,
+    window: CausalityWindow,
+) -> Result<ReflectionReport> {
+    build(db, run_id, as_of, window, None)
+}
+
+/// [`build_report_with_window`] restricted to the tests `owner` owned at
+/// `as_of`. Signals of the whole run still feed the causality trails; the
+/// comparison only looks at the owner's tests of the previous run.
+pub fn build_owner_report(
+    db: &LiminalDB,
+    run_id: EntityId,
+    as_of: DateTime<Utc>,
+    window: CausalityWindow,
+    owner: &str,
+) -> Result<ReflectionReport> {
+    build(db, run_id, as_of, window, Some(owner))
+}
+
+fn build(
+    db: &LiminalDB,
+    run_id: EntityId,
+    as_of: DateTime<Utc>,
+    window: CausalityWindow,
+    owner: Option<&str>,
+) -> Result<ReflectionReport> {
+    let run: Run = db
+        .get_entity(run_id)?
+        .with_context(|| format!("Run not found: {}", run_id))?;
+    if run.created_at.tx_time > as_of {
+        anyhow::bail!("Run {} was not known at {}", run_id, as_of);
+    }
+
+    let status = db.run_status_at(run_id, as_of)?;
+    let (tests, owners) = owned_tests_as_of(db, run_id, as_of, owner)?;
+    let signals = signals_as_of(db, run_id, as_of)?;
+    let cached = match (owner, status) {
+        (None, RunStatus::Completed) => db
+            .cached_run_summary(run_id)?
+            .filter(|cached| cached.computed_at <= as_of),
+        _ => None,
+    };
+    let attempts = attempts_as_of(db, &tests, as_of)?;
+    let summary = match cached {
+        Some(cached) => cached.summary,
+        None => summarize(&tests, &flaky_tests_as_of(db, as_of)?, &attempts),
+    };
+    let causality_trails = causality_trails(&tests, &owners, &signals, window);
+    let comparison = match previous_run(db, &run, as_of)? {
+        Some(previous) => {
+            let (previous_tests, _) = owned_tests_as_of(db, previous.id, as_of, owner)?;
+            Some(RunComparison::between(
+                previous.id.to_string(),
+                previous.started_at,
+                &outcomes(&previous_tests),
+                &outcomes(&tests),
+            ))
+        }
+        None => None,
+    };
+
+    Ok(ReflectionReport {
+        schema_version: REPORT_SCHEMA_VERSION.to_string(),
+        run_id: run_id.to_string(),
+        plan_name: run.plan_name,
+        started_at: run.started_at,
+        ended_at: run.ended_at,
+        status: Some(status),
+        summary,
+        timeline: timeline(&tests)?,
+        top_slow_tests: top_slow_tests(&tests),
+        failure_clusters: cluster_failures(&causality_trails),
+        causality_trails,
+        causality_window: window,
+        comparison,
+        alignment: alignment_as_of(db, &tests, as_of)?,
+        sla_breaches: sla_breaches_as_of(db, &tests, as_of)?,
+        retried_passes: retried_passes(&tests, &attempts),
+        in_progress: progress_as_of(db, run_id, &tests, as_of, owner)?,
+    })
+}
+
+/// Compute the summary of a completed run from everything currently known
+/// and cache it for later reports, see [`LiminalDB::cached_run_summary`].
+///
+/// Called when a run is stored with an end time. The cache is dropped when
+/// a test of the run or a status correction is stored afterwards; resonances
+/// found later do not update `flaky_failures` until then.
+pub fn cache_run_summary(db: &LiminalDB, run_id: EntityId) -> Result<TestSummary> {
+    let now = Utc::now();
+    let tests = tests_as_of(db, run_id, now)?;
+    let summary = summarize(
+        &tests,
+        &flaky_tests_as_of(db, now)?,
+        &attempts_as_of(db, &tests, now)?,
+    );
+    db.put_run_summary(run_id, &summary)?;
+    Ok(summary)
+}
+
+/// Every run executed against `build_id` with its test summary, oldest
+/// first. Completed runs use their cached summary.
+pub fn build_runs(db: &LiminalDB, build_id: EntityId) -> Result<Vec<BuildRun>> {
+    let now = Utc::now();
+    let flaky = flaky_tests_as_of(db, now)?;
+    let mut build_runs = Vec::new();
+    for run in db.runs_for_build(build_id)? {
+        let status = db.run_status_at(run.id, now)?;
+        let cached = match status {
+            RunStatus::Completed => db.cached_run_summary(run.id)?,
+            _ => None,
+        };
+        let summary = match cached {
+            Some(cached) => cached.summary,
+            None => {
+                let tests = tests_as_of(db, run.id, now)?;
+                summarize(&tests, &flaky, &attempts_as_of(db, &tests, now)?)
+            }
+        };
+        build_runs.push(BuildRun {
+            run_id: run.id.to_string(),
+            plan_name: run.plan_name,
+            started_at: run.started_at,
+            ended_at: run.ended_at,
+            status,
+            summary,
+        });
+    }
+    Ok(build_runs)
+}
+
+/// Most recent run of the same plan that started before `run`, among the
+/// runs known at `as_of`
+pub fn previous_run(db: &LiminalDB, run: &Run, as_of: DateTime<Utc>) -> Result<Option<Run>> {
+    let mut previous: Option<Run> = None;
+    for id in db.get_entities_by_type(EntityType::Run)? {
+        let Some(candidate) = db.get_entity::<Run>(id)? else {
+            continue;
+        };
+        if candidate.plan_name != run.plan_name
+            || candidate.started_at >= run.started_at
+            || candidate.created_at.tx_time > as_of
+        {
+            continue;
+        }
+        if previous
+            .as_ref()
+            .is_none_or(|p| candidate.started_at > p.started_at)
+        {
+            previous = Some(candidate);
+        }
+    }
+    Ok(previous)
+}
+
+/// Tests of a run known at `as_of`, only `owner`'s when given, with the
+/// owner of each test known at `as_of`
+fn owned_tests_as_of(
+    db: &LiminalDB,
+    run_id: EntityId,
+    as_of: DateTime<Utc>,
+    owner: Option<&str>,
+) -> Result<(Vec<Test>, HashMap<EntityId, String>)> {
+    let mut tests = tests_as_of(db, run_id, as_of)?;
+    let ids: Vec<EntityId> = tests.iter().map(|t| t.id).collect();
+    let owners = db.test_owners_at(&ids, as_of)?;
+    if let Some(owner) = owner {
+        tests.retain(|t| owners.get(&t.id).is_some_and(|o| o == owner));
+    }
+    Ok((tests, owners))
+}
+
+/// Latest progress known at `as_of` of each test of the run without a result
+/// among `tests`, most recent first; only `owner`'s tests when given
+fn progress_as_of(
+    db: &LiminalDB,
+    run_id: EntityId,
+    tests: &[Test],
+    as_of: DateTime<Utc>,
+    owner: Option<&str>,
+) -> Result<Vec<TestProgress>> {
+    let finished: HashSet<EntityId> = tests.iter().map(|t| t.id).collect();
+    let mut ids = db.progress_tests_of_run(run_id)?;
+    ids.retain(|id| !finished.contains(id));
+    if let Some(owner) = owner {
+        let owners = db.test_owners_at(&ids, as_of)?;
+        ids.retain(|id| owners.get(id).is_some_and(|o| o == owner));
+    }
+
+    let mut latest: HashMap<EntityId, (DateTime<Utc>, TestProgress)> = HashMap::new();
+    for fact in db.facts_as_of(&ids, as_of)? {
+        if fact.attribute != Attribute::TestProgress {
+            continue;
+        }
+        let progress: TestProgress = serde_json::from_value(fact.value)?;
+        let newer = latest
+            .get(&progress.test_id)
+            .is_none_or(|(tx_time, current)| {
+                (progress.at, fact.time.tx_time) > (current.at, *tx_time)
+            });
+        if newer {
+            latest.insert(progress.test_id, (fact.time.tx_time, progress));
+        }
+    }
+
+    let mut progress: Vec<TestProgress> = latest.into_values().map(|(_, p)| p).collect();
+    progress.sort_by(|a, b| b.at.cmp(&a.at).then_with(|| a.test_id.cmp(&b.test_id)));
+    Ok(progress)
+}
+
+/// Tests of a run known at `as_of`, with status/duration facts applied
+fn tests_as_of(db: &LiminalDB, run_id: EntityId, as_of: DateTime<Utc>) -> Result<Vec<Test>> {
+    let mut tests = Vec::new();
+    for id in db.get_entities_by_type(EntityType::Test)? {
+        if let Some(test) = db.get_entity::<Test>(id)? {
+            if test.run_id == run_id && test.created_at.tx_time <= as_of {
+                tests.push(test);
+            }
+        }
+    }
+
+    let ids: Vec<EntityId> = tests.iter().map(|t| t.id).collect();
+    let mut facts = db.facts_as_of(&ids, as_of)?;
+    facts.sort_by_key(|f| f.time.tx_time);
+
+    let by_id: HashMap<EntityId, usize> =
+        tests.iter().enumerate().map(|(i, t)| (t.id, i)).collect();
+    for fact in facts {
+        let Some(&i) = by_id.get(&fact.entity_id) else {
+            continue;
+        };
+        match fact.attribute {
+            Attribute::TestStatus => {
+                if let Ok(status) = serde_json::from_value::<TestStatus>(fact.value) {
+                    tests[i].status = status;
+                }
+            }
+            Attribute::TestDuration => {
+                if let Some(duration_ms) = fact.value.as_u64() {
+                    tests[i].duration_ms = duration_ms;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(tests)
+}
+
+/// Latest `:test/alignment` score of each test known at `as_of`, least
+/// aligned first
+fn alignment_as_of(
+    db: &LiminalDB,
+    tests: &[Test],
+    as_of: DateTime<Utc>,
+) -> Result<Vec<TestAlignment>> {
+    let ids: Vec<EntityId> = tests.iter().map(|t| t.id).collect();
+    let mut latest: HashMap<EntityId, (DateTime<Utc>, f64)> = HashMap::new();
+    for fact in db.facts_as_of(&ids, as_of)? {
+        if fact.attribute != Attribute::TestAlignment {
+            continue;
+        }
+        let Some(score) = fact.value.as_f64() else {
+            continue;
+        };
+        let newer = latest
+            .get(&fact.entity_id)
+            .is_none_or(|(tx_time, _)| fact.time.tx_time >= *tx_time);
+        if newer {
+            latest.insert(fact.entity_id, (fact.time.tx_time, score));
+        }
+    }
+
+    let mut alignment: Vec<TestAlignment> = tests
+        .iter()
+        .filter_map(|t| {
+            latest.get(&t.id).map(|(_, score)| TestAlignment {
+                name: t.name.clone(),
+                suite: t.suite.clone(),
+                score: *score,
+            })
+        })
+        .collect();
+    alignment.sort_by(|a, b| {
+        a.score
+            .total_cmp(&b.score)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    Ok(alignment)
+}
+
+/// Tests that took longer than their latest `:test/sla_ms` known at
+/// `as_of`, furthest over first
+fn sla_breaches_as_of(
+    db: &LiminalDB,
+    tests: &[Test],
+    as_of: DateTime<Utc>,
+) -> Result<Vec<SlaBreach>> {
+    let ids: Vec<EntityId> = tests.iter().map(|t| t.id).collect();
+    let mut latest: HashMap<EntityId, (DateTime<Utc>, u64)> = HashMap::new();
+    for fact in db.facts_as_of(&ids, as_of)? {
+        if fact.attribute != Attribute::TestSla {
+            continue;
+        }
+        let Some(sla_ms) = fact.value.as_u64() else {
+            continue;
+        };
+        let newer = latest
+            .get(&fact.entity_id)
+            .is_none_or(|(tx_time, _)| fact.time.tx_time >= *tx_time);
+        if newer {
+            latest.insert(fact.entity_id, (fact.time.tx_time, sla_ms));
+        }
+    }
+
+    let mut breaches: Vec<SlaBreach> = tests
+        .iter()
+        .filter_map(|t| {
+            let (_, sla_ms) = latest.get(&t.id)?;
+            (t.duration_ms > *sla_ms).then(|| SlaBreach {
+                name: t.name.clone(),
+                suite: t.suite.clone(),
+                duration_ms: t.duration_ms,
+                sla_ms: *sla_ms,
+            })
+        })
+        .collect();
+    breaches.sort_by(|a, b| {
+        (b.duration_ms - b.sla_ms)
+            .cmp(&(a.duration_ms - a.sla_ms))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    Ok(breaches)
+}
+
+fn signals_as_of(db: &LiminalDB, run_id: EntityId, as_of: DateTime<Utc>) -> Result<Vec<Signal>> {
+    let mut signals = Vec::new();
+    for id in db.get_entities_by_type(EntityType::Signal)? {
+        if let Some(signal) = db.get_signal(id)? {
+            if signal.run_id == run_id && signal.created_at.tx_time <= as_of {
+                signals.push(signal);
+            }
+        }
+    }
+    signals.sort_by_key(Signal::ordering_key);
+    Ok(signals)
+}
+
+/// Attempts of each of `tests` as known at `as_of`, from the latest
+/// `:test/attempts` fact; tests without one are left out
+fn attempts_as_of(
+    db: &LiminalDB,
+    tests: &[Test],
+    as_of: DateTime<Utc>,
+) -> Result<HashMap<EntityId, u32>> {
+    let ids: Vec<EntityId> = tests.iter().map(|t| t.id).collect();
+    let mut latest: HashMap<EntityId, (DateTime<Utc>, u32)> = HashMap::new();
+    for fact in db.facts_as_of(&ids, as_of)? {
+        if fact.attribute != Attribute::TestAttempts {
+            continue;
+        }
+        let Some(attempts) = fact.value.as_u64().and_then(|v| u32::try_from(v).ok()) else {
+            continue;
+        };
+        let newer = latest
+            .get(&fact.entity_id)
+            .is_none_or(|(tx_time, _)| fact.time.tx_time >= *tx_time);
+        if newer {
+            latest.insert(fact.entity_id, (fact.time.tx_time, attempts));
+        }
+    }
+    Ok(latest
+        .into_iter()
+        .map(|(id, (_, attempts))| (id, attempts))
+        .collect())
+}
+
+/// (name, suite) of every test a resonance record known at `as_of` flagged,
+/// unless it was resolved by then
+fn flaky_tests_as_of(db: &LiminalDB, as_of: DateTime<Utc>) -> Result<HashSet<(String, String)>> {
+    let mut flaky = HashSet::new();
+    for id in db.get_entities_by_type(EntityType::Resonance)? {
+        let Some(resonance) = db.get_entity::<Resonance>(id)? else {
+            continue;
+        };
+        if resonance.created_at.tx_time > as_of
+            || db
+                .resonance_resolved_at(id)?
+                .is_some_and(|resolved| resolved <= as_of)
+        {
+            continue;
+        }
+        for test_id in resonance.affected_tests {
+            if let Some(test) = db.get_entity::<Test>(test_id)? {
+                flaky.insert((test.name, test.suite));
+            }
+        }
+    }
+    Ok(flaky)
+}
+
+/// [`test_summary`] with the flaky tests given by name and suite
+fn summarize(
+    tests: &[Test],
+    flaky: &HashSet<(String, String)>,
+    attempts: &HashMap<EntityId, u32>,
+) -> TestSummary {
+    test_summary(
+        tests,
+        |t| flaky.contains(&(t.name.clone(), t.suite.clone())),
+        attempts,
+    )
+}
+
+/// Signals within `window` of each failed or timed-out test, closest first
+/// with correlated signals grouped, and the likely cause of each failure
+/// flagged
+fn causality_trails(
+    tests: &[Test],
+    owners: &HashMap<EntityId, String>,
+    signals: &[Signal],
+    window: CausalityWindow,
+) -> Vec<CausalityTrail> {
+    let mut failed: Vec<&Test> = tests
+        .iter()
+        .filter(|t| matches!(t.status, TestStatus::Fail | TestStatus::Timeout))
+        .collect();
+    failed.sort_by(|a, b| a.name.cmp(&b.name));
+
+    failed
+        .into_iter()
+        .map(|test| {
+            let mut nearby: Vec<NearbySignal> = signals
+                .iter()
+                .filter_map(|s| {
+                    let diff = (s.timestamp - test.completed_at).num_seconds();
+                    window.contains(diff).then(|| NearbySignal {
+                        kind: format!("{:?}", s.signal_type).to_lowercase(),
+                        at: s.timestamp,
+                        value: s.latency_ms.map(|v| v as f64),
+                        meta: serde_json::to_value(&s.metadata).unwrap_or_default(),
+                        time_diff_seconds: diff as i32,
+                        sequence: Some(s.sequence),
+                        likely_cause: false,
+                        correlation_id: s.correlation_id.clone(),
+                    })
+                })
+                .collect();
+            nearby.sort_by_key(|s| (s.time_diff_seconds.abs(), s.at, s.sequence));
+
+            let mut trail = CausalityTrail {
+                test_name: test.name.clone(),
+                test_failed_at: test.completed_at,
+                signals: nearby,
+                owner: owners.get(&test.id).cloned(),
+            };
+            trail.group_by_correlation();
+            trail.mark_likely_cause();
+            trail
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use liminalqa_core::{
+        facts::{Fact, FactKey},
+        temporal::BiTemporalTime,
+        types::SignalType,
+    };
+    use tempfile::TempDir;
+
+    fn known_at(tx_time: DateTime<Utc>) -> BiTemporalTime {
+        BiTemporalTime::with_times(tx_time, tx_time)
+    }
+
+    fn make_run(tx_time: DateTime<Utc>) -> Run {
+        Run {
+            id: EntityId::new(),
+            build_id: EntityId::new(),
+            plan_name: "smoke".to_string(),
+            env: Default::default(),
+            started_at: tx_time,
+            ended_at: None,
+            runner_version: "test".to_string(),
+            liminal_os_version: None,
+            created_at: known_at(tx_time),
+        }
+    }
+
+    fn make_test(run_id: EntityId, name: &str, tx_time: DateTime<Utc>) -> Test {
+        Test {
+            id: EntityId::new(),
+            run_id,
+            name: name.to_string(),
+            suite: "checkout".to_string(),
+            guidance: "".to_string(),
+            status: TestStatus::Pass,
+            duration_ms: 100,
+            error: None,
+            started_at: tx_time,
+            completed_at: tx_time,
+            created_at: known_at(tx_time),
+        }
+    }
+
+    #[test]
+    fn test_build_report_at_excludes_later_knowledge() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+
+        let t0 = Utc::now() - Duration::hours(2);
+        let as_of = t0 + Duration::minutes(30);
+        let later = t0 + Duration::hours(1);
+
+        let run = make_run(t0);
+        db.put_run(&run)?;
+        let test = make_test(run.id, "test_pay", t0);
+        db.put_test(&test)?;
+        // Learned after `as_of`: a re-triage marking the test failed, and a
+        // test that was only reported late
+        db.put_fact(&Fact::with_time(
+            test.id,
+            Attribute::TestStatus,
+            serde_json::json!("fail"),
+            known_at(later),
+        ))?;
+        db.put_test(&make_test(run.id, "test_refund", later))?;
+
+        let past = build_report_at(&db, run.id, as_of)?;
+        assert_eq!(past.summary.total, 1);
+        assert_eq!(past.summary.passed, 1);
+        assert_eq!(past.summary.failed, 0);
+        assert!(past.causality_trails.is_empty());
+
+        let current = build_report(&db, run.id)?;
+        assert_eq!(current.summary.total, 2);
+        assert_eq!(current.summary.passed, 1);
+        assert_eq!(current.summary.failed, 1);
+        assert_eq!(current.causality_trails.len(), 1);
+        assert_eq!(current.causality_trails[0].test_name, "test_pay");
+
+        // Nothing was known about the run before it was ingested
+        assert!(build_report_at(&db, run.id, t0 - Duration::minutes(1)).is_err());
+
+        Ok(())
+    }
+
+    fn make_signal(
+        run_id: EntityId,
+        signal_type: SignalType,
+        at: DateTime<Utc>,
+        meta: serde_json::Value,
+    ) -> Signal {
+        Signal {
+            id: EntityId::new(),
+            run_id,
+            test_id: None,
+            signal_type,
+            timestamp: at,
+            latency_ms: None,
+            payload_ref: None,
+            metadata: serde_json::from_value(meta).unwrap_or_default(),
+            created_at: known_at(at),
+            sequence: 0,
+            correlation_id: None,
+        }
+    }
+
+    #[test]
+    fn test_retracted_status_is_left_out_of_report_and_revisions() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+
+        let t0 = Utc::now() - Duration::hours(2);
+        let run = make_run(t0);
+        db.put_run(&run)?;
+        let test = make_test(run.id, "test_pay", t0);
+        db.put_test(&test)?;
+        // A re-triage marking the test failed, retracted an hour later
+        let triaged = t0 + Duration::minutes(10);
+        db.put_fact(&Fact::with_time(
+            test.id,
+            Attribute::TestStatus,
+            serde_json::json!("fail"),
+            known_at(triaged),
+        ))?;
+        let retracted = t0 + Duration::hours(1);
+        db.retract_fact(&FactKey::new(test.id, Attribute::TestStatus), retracted)?;
+
+        let report = build_report(&db, run.id)?;
+        assert_eq!((report.summary.passed, report.summary.failed), (1, 0));
+        assert!(db.status_revisions(run.id, t0, Utc::now())?.is_empty());
+
+        // Views before the retraction still see the re-triage
+        let before = retracted - Duration::minutes(1);
+        assert_eq!(build_report_at(&db, run.id, before)?.summary.failed, 1);
+        assert_eq!(db.status_revisions(run.id, t0, before)?.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_failures_near_one_signal_form_a_cluster() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+
+        let t0 = Utc::now() - Duration::hours(2);
+        let run = make_run(t0);
+        db.put_run(&run)?;
+        for (name, offset_secs) in [
+            ("test_pay", 0),
+            ("test_refund", 10),
+            ("test_cart", 20),
+            ("test_login", 1800),
+        ] {
+            let mut test = make_test(run.id, name, t0 + Duration::seconds(offset_secs));
+            test.status = TestStatus::Fail;
+            db.put_test(&test)?;
+        }
+        db.put_signal(&make_signal(
+            run.id,
+            SignalType::Database,
+            t0 + Duration::seconds(5),
+            serde_json::json!({"error": "connection refused"}),
+        ))?;
+        // Only near test_login, so it explains that failure alone
+        db.put_signal(&make_signal(
+            run.id,
+            SignalType::API,
+            t0 + Duration::seconds(1790),
+            serde_json::json!({"status": 500}),
+        ))?;
+
+        let report = build_report(&db, run.id)?;
+        assert_eq!(report.causality_trails.len(), 4);
+        assert_eq!(report.failure_clusters.len(), 1);
+        let cluster = &report.failure_clusters[0];
+        assert_eq!(cluster.signal.kind, "database");
+        assert_eq!(cluster.signal.meta["error"], "connection refused");
+        let mut names = cluster.test_names.clone();
+        names.sort();
+        assert_eq!(names, ["test_cart", "test_pay", "test_refund"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_causality_window_bounds_trail_signals() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+
+        let t0 = Utc::now() - Duration::hours(2);
+        let run = make_run(t0);
+        db.put_run(&run)?;
+        let mut test = make_test(run.id, "test_pay", t0);
+        test.status = TestStatus::Fail;
+        db.put_test(&test)?;
+        for offset_secs in [-120, 30, 200] {
+            db.put_signal(&make_signal(
+                run.id,
+                SignalType::API,
+                t0 + Duration::seconds(offset_secs),
+                serde_json::json!({}),
+            ))?;
+        }
+
+        let diffs = |window: CausalityWindow| -> Result<Vec<i32>> {
+            let report = build_report_with_window(&db, run.id, Utc::now(), window)?;
+            assert_eq!(report.causality_window, window);
+            let mut diffs: Vec<i32> = report.causality_trails[0]
+                .signals
+                .iter()
+                .map(|s| s.time_diff_seconds)
+                .collect();
+            diffs.sort();
+            Ok(diffs)
+        };
+
+        assert_eq!(diffs(CausalityWindow::new(60, 300))?, [30, 200]);
+        assert_eq!(diffs(CausalityWindow::new(180, 60))?, [-120, 30]);
+        assert_eq!(diffs(CausalityWindow::default())?, [-120, 30, 200]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_equal_timestamps_ordered_by_sequence() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+
+        let t0 = Utc::now() - Duration::hours(2);
+        let run = make_run(t0);
+        db.put_run(&run)?;
+        let mut test = make_test(run.id, "test_pay", t0);
+        test.status = TestStatus::Fail;
+        db.put_test(&test)?;
+
+        let at = t0 + Duration::seconds(3);
+        let mut sequences = Vec::new();
+        for step in ["connect", "query", "timeout"] {
+            sequences.push(db.put_signal(&make_signal(
+                run.id,
+                SignalType::Database,
+                at,
+                serde_json::json!({ "step": step }),
+            ))?);
+        }
+        assert_eq!(sequences, [1, 2, 3]);
+        // Sequences are per run
+        let other_run = EntityId::new();
+        assert_eq!(
+            db.put_signal(&make_signal(
+                other_run,
+                SignalType::API,
+                at,
+                serde_json::json!({})
+            ))?,
+            1
+        );
+
+        for _ in 0..3 {
+            let report = build_report(&db, run.id)?;
+            let steps: Vec<&str> = report.causality_trails[0]
+                .signals
+                .iter()
+                .map(|s| s.meta["step"].as_str().unwrap_or_default())
+                .collect();
+            assert_eq!(steps, ["connect", "query", "timeout"]);
+            assert_eq!(report.causality_trails[0].signals[2].sequence, Some(3));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_error_signal_marked_as_likely_cause() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+
+        let t0 = Utc::now() - Duration::hours(2);
+        let run = make_run(t0);
+        db.put_run(&run)?;
+        let mut test = make_test(run.id, "test_pay", t0);
+        test.status = TestStatus::Fail;
+        db.put_test(&test)?;
+        db.put_signal(&make_signal(
+            run.id,
+            SignalType::Database,
+            t0 - Duration::seconds(2),
+            serde_json::json!({"error": "connection refused"}),
+        ))?;
+        db.put_signal(&make_signal(
+            run.id,
+            SignalType::UI,
+            t0 - Duration::milliseconds(500),
+            serde_json::json!({"event": "click"}),
+        ))?;
+
+        let report = build_report(&db, run.id)?;
+        let trail = &report.causality_trails[0];
+        assert_eq!(trail.signals.len(), 2);
+        // Closest first, but the error is the likely cause
+        assert_eq!(trail.signals[0].kind, "ui");
+        assert!(!trail.signals[0].likely_cause);
+        let cause = trail.likely_cause().expect("a likely cause");
+        assert_eq!(cause.kind, "database");
+        assert_eq!(cause.time_diff_seconds, -2);
+
+        let json = serde_json::to_value(&report)?;
+        assert_eq!(
+            json["causality_trails"][0]["signals"][1]["likely_cause"],
+            true
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_report_lists_alignment_scores() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+
+        let t0 = Utc::now() - Duration::hours(2);
+        let run = make_run(t0);
+        db.put_run(&run)?;
+        let login = make_test(run.id, "test_login", t0);
+        let pay = make_test(run.id, "test_pay", t0);
+        let unguided = make_test(run.id, "test_health", t0);
+        for test in [&login, &pay, &unguided] {
+            db.put_test(test)?;
+        }
+        db.put_test_alignment(&login, 1.0)?;
+        db.put_test_alignment(&pay, 2.0 / 3.0)?;
+        assert!(db.put_test_alignment(&pay, 1.5).is_err());
+
+        let report = build_report(&db, run.id)?;
+        let scores: Vec<(&str, String)> = report
+            .alignment
+            .iter()
+            .map(|a| (a.name.as_str(), format!("{:.2}", a.score)))
+            .collect();
+        assert_eq!(
+            scores,
+            [
+                ("test_pay", "0.67".to_string()),
+                ("test_login", "1.00".to_string())
+            ]
+        );
+        // Known from the tests' transaction time
+        assert_eq!(build_report_at(&db, run.id, t0)?.alignment.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_report_flags_sla_breach_within_noisy_baseline() -> Result<()> {
+        use liminalqa_core::baseline::{Baseline, DriftDetector};
+
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+
+        let t0 = Utc::now() - Duration::hours(2);
+        let run = make_run(t0);
+        db.put_run(&run)?;
+        let mut export = make_test(run.id, "test_export", t0);
+        export.duration_ms = 6_000;
+        let mut import = make_test(run.id, "test_import", t0);
+        import.duration_ms = 4_000;
+        for test in [&export, &import] {
+            db.put_test(test)?;
+        }
+
+        // 6s is ordinary for this test's noisy history, so no drift
+        let baseline = Baseline::from_samples(
+            "test_export",
+            "checkout",
+            &[3_000.0, 8_000.0, 4_000.0, 7_500.0, 5_000.0],
+        );
+        assert!(!DriftDetector::default().is_drift_from(6_000.0, &baseline));
+
+        db.put_test_sla(&export, 5_000)?;
+        db.put_test_sla(&import, 5_000)?;
+        assert!(db.put_test_sla(&import, 0).is_err());
+
+        let report = build_report(&db, run.id)?;
+        assert_eq!(
+            report.sla_breaches,
+            [SlaBreach {
+                name: "test_export".to_string(),
+                suite: "checkout".to_string(),
+                duration_ms: 6_000,
+                sla_ms: 5_000,
+            }]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_report_separates_passes_with_retries() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+
+        let t0 = Utc::now() - Duration::hours(2);
+        let mut run = make_run(t0);
+        db.put_run(&run)?;
+        let login = make_test(run.id, "test_login", t0);
+        let checkout = make_test(run.id, "test_checkout", t0);
+        for test in [&login, &checkout] {
+            db.put_test(test)?;
+        }
+        db.put_test_attempts(&login, 1)?;
+        db.put_test_attempts(&checkout, 3)?;
+        assert!(db.put_test_attempts(&checkout, 0).is_err());
+
+        let report = build_report(&db, run.id)?;
+        assert_eq!(
+            (report.summary.passed, report.summary.passed_with_retries),
+            (2, 1)
+        );
+        assert_eq!(
+            report.retried_passes,
+            [RetriedPass {
+                name: "test_checkout".to_string(),
+                suite: "checkout".to_string(),
+                attempts: 3,
+            }]
+        );
+
+        // The cached summary of a completed run keeps the distinction
+        run.ended_at = Some(t0 + Duration::minutes(1));
+        db.put_run(&run)?;
+        let cached = db.cached_run_summary(run.id)?.expect("cached summary");
+        assert_eq!(cached.summary.passed_with_retries, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_report_shows_progress_of_unfinished_tests() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+
+        let t0 = Utc::now() - Duration::hours(2);
+        let run = make_run(t0);
+        db.put_run(&run)?;
+        let login = make_test(run.id, "test_login", t0);
+        let checkout = make_test(run.id, "test_checkout", t0);
+        let before_progress = Utc::now();
+        for (test, phase, offset_mins) in [
+            (&login, "submit", 1),
+            (&checkout, "cart", 1),
+            (&checkout, "payment", 2),
+        ] {
+            db.put_test_progress(&TestProgress {
+                test_id: test.id,
+                run_id: run.id,
+                phase: phase.to_string(),
+                percent: None,
+                message: None,
+                at: t0 + Duration::minutes(offset_mins),
+            })?;
+        }
+        db.put_test(&login)?;
+
+        let report = build_report(&db, run.id)?;
+        let phases: Vec<(EntityId, &str)> = report
+            .in_progress
+            .iter()
+            .map(|p| (p.test_id, p.phase.as_str()))
+            .collect();
+        assert_eq!(phases, [(checkout.id, "payment")]);
+        assert!(build_report_at(&db, run.id, before_progress)?
+            .in_progress
+            .is_empty());
+
+        // Finishing the test takes it out of the list
+        db.put_test(&checkout)?;
+        assert!(build_report(&db, run.id)?.in_progress.is_empty());
+
+        db.delete_run_cascade(run.id)?;
+        assert!(db.progress_tests_of_run(run.id)?.is_empty());
+        assert!(db.get_test_progress(checkout.id)?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_report_compares_to_previous_run_of_plan() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+
+        let t0 = Utc::now() - Duration::hours(3);
+        let put_run = |run: &Run, outcomes: &[(&str, TestStatus)]| -> Result<()> {
+            db.put_run(run)?;
+            for &(name, status) in outcomes {
+                let mut test = make_test(run.id, name, run.started_at);
+                test.status = status;
+                db.put_test(&test)?;
+            }
+            Ok(())
+        };
+
+        let first = make_run(t0);
+        put_run(
+            &first,
+            &[
+                ("test_pay", TestStatus::Pass),
+                ("test_refund", TestStatus::Fail),
+                ("test_cart", TestStatus::Pass),
+            ],
+        )?;
+        // Another plan in between is not a baseline
+        let mut other_plan = make_run(t0 + Duration::minutes(30));
+        other_plan.plan_name = "nightly".to_string();
+        put_run(&other_plan, &[("test_pay", TestStatus::Fail)])?;
+        let second = make_run(t0 + Duration::hours(1));
+        put_run(
+            &second,
+            &[
+                ("test_pay", TestStatus::Fail),
+                ("test_refund", TestStatus::Pass),
+                ("test_cart", TestStatus::Pass),
+                ("test_login", TestStatus::Timeout),
+            ],
+        )?;
+
+        assert!(build_report(&db, first.id)?.comparison.is_none());
+
+        let comparison = build_report(&db, second.id)?
+            .comparison
+            .expect("second run has a previous run");
+        assert_eq!(comparison.previous_run_id, first.id.to_string());
+        assert_eq!(comparison.regressions, ["test_pay"]);
+        assert_eq!(comparison.new_failures, ["test_login"]);
+        assert_eq!(comparison.fixed, ["test_refund"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_report_counts_flaky_failures() -> Result<()> {
+        use liminalqa_core::types::ResonancePattern;
+
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+
+        let t0 = Utc::now() - Duration::hours(2);
+        let earlier_run = make_run(t0);
+        db.put_run(&earlier_run)?;
+        let earlier = make_test(earlier_run.id, "test_pay", t0);
+        db.put_test(&earlier)?;
+        db.put_resonance(&Resonance {
+            id: EntityId::new(),
+            detector: "flake".to_string(),
+            pattern: ResonancePattern {
+                pattern_id: EntityId::new(),
+                description: "Flaky test detected: test_pay".to_string(),
+                score: 0.8,
+                occurrences: 3,
+                first_seen: t0,
+                last_seen: t0,
+            },
+            affected_tests: vec![earlier.id],
+            root_cause: None,
+            created_at: known_at(t0),
+        })?;
+
+        let run = make_run(t0);
+        db.put_run(&run)?;
+        for name in ["test_pay", "test_refund"] {
+            let mut test = make_test(run.id, name, t0);
+            test.status = TestStatus::Fail;
+            db.put_test(&test)?;
+        }
+        db.put_test(&make_test(run.id, "test_cart", t0))?;
+
+        let report = build_report(&db, run.id)?;
+        assert_eq!(report.summary.failed, 2);
+        assert_eq!(report.summary.flaky_failures, 1);
+        assert!(
+            weighted_pass_rate(&report.summary, report.summary.flaky_failures)
+                > pass_rate(&report.summary)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_completed_run_reports_cached_summary() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+
+        let t0 = Utc::now() - Duration::hours(1);
+        let mut run = make_run(t0);
+        db.put_run(&run)?;
+        let mut failing = make_test(run.id, "test_pay", t0);
+        failing.status = TestStatus::Fail;
+        db.put_test(&failing)?;
+        db.put_test(&make_test(run.id, "test_refund", t0))?;
+        assert!(db.cached_run_summary(run.id)?.is_none());
+
+        // Completing the run caches the summary a fresh computation yields
+        run.ended_at = Some(Utc::now());
+        db.put_run(&run)?;
+        let cached = db
+            .cached_run_summary(run.id)?
+            .expect("summary cached on completion");
+        let fresh = summarize(
+            &tests_as_of(&db, run.id, Utc::now())?,
+            &HashSet::new(),
+            &HashMap::new(),
+        );
+        assert_eq!(cached.summary, fresh);
+        assert_eq!(build_report(&db, run.id)?.summary, fresh);
+
+        // Reports of the completed run read the cache rather than the tests
+        let mut marked = fresh.clone();
+        marked.skip = 42;
+        db.put_run_summary(run.id, &marked)?;
+        assert_eq!(build_report(&db, run.id)?.summary.skip, 42);
+        // ...except as of before it was cached
+        assert_eq!(build_report_at(&db, run.id, t0)?.summary, fresh);
+
+        // A late test drops the cache; the summary is live again
+        db.put_test(&make_test(run.id, "test_cart", t0))?;
+        assert!(db.cached_run_summary(run.id)?.is_none());
+        assert_eq!(build_report(&db, run.id)?.summary.total, 3);
+
+        Ok(())
+    }
+}
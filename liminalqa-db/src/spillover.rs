@@ -0,0 +1,137 @@
+//! Spill oversized signal metadata to payload files
+
+use anyhow::{Context, Result};
+use liminalqa_core::{
+    entities::{Artifact, ArtifactType, Signal},
+    types::{ArtifactLocation, ArtifactRef, EntityId, SignalType},
+};
+use sha2::{Digest, Sha256};
+use std::{collections::BTreeMap, path::PathBuf};
+
+/// Default largest inline signal metadata, in serialized JSON bytes (16 KiB)
+pub const DEFAULT_MAX_INLINE_META_BYTES: usize = 16 * 1024;
+
+/// Bytes of the serialized metadata kept inline as a preview
+pub const PREVIEW_BYTES: usize = 512;
+
+/// Metadata key holding the preview of a spilled payload
+pub const PREVIEW_KEY: &str = "_preview";
+
+/// Metadata key holding the size of a spilled payload, in bytes
+pub const SPILLED_BYTES_KEY: &str = "_spilled_bytes";
+
+/// Writes signal metadata larger than `max_inline_bytes` to `dir` and keeps
+/// only a truncated preview inline
+#[derive(Debug, Clone)]
+pub struct SignalSpillover {
+    pub dir: PathBuf,
+    pub max_inline_bytes: usize,
+}
+
+impl SignalSpillover {
+    pub fn new(dir: impl Into<PathBuf>, max_inline_bytes: usize) -> Self {
+        Self {
+            dir: dir.into(),
+            max_inline_bytes,
+        }
+    }
+
+    /// Path of the spilled payload with hash `sha256`
+    pub fn path(&self, sha256: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", sha256))
+    }
+
+    /// Whether `payload_ref` points at a payload spilled here, at the path
+    /// its sha256 addresses
+    pub fn holds(&self, payload_ref: &ArtifactRef) -> bool {
+        matches!(&payload_ref.location, ArtifactLocation::Local(path)
+            if *path == self.path(&payload_ref.sha256))
+    }
+
+    /// Spill the metadata of `signal` if it is too large.
+    ///
+    /// The full metadata is written as JSON to `{dir}/{sha256}.json` and
+    /// referenced from `payload_ref`; the inline metadata is replaced by
+    /// [`PREVIEW_KEY`] and [`SPILLED_BYTES_KEY`]. Returns the artifact
+    /// recording the payload, for the caller to store with the signal; a
+    /// run-level signal's payload is filed under its run id as the test.
+    pub fn apply(&self, signal: &mut Signal) -> Result<Option<Artifact>> {
+        let payload = serde_json::to_vec(&signal.metadata)?;
+        if payload.len() <= self.max_inline_bytes {
+            return Ok(None);
+        }
+
+        let sha256 = format!("{:x}", Sha256::digest(&payload));
+        std::fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create {}", self.dir.display()))?;
+        let path = self.path(&sha256);
+        // Content-addressed: an existing file already holds these bytes
+        if !path.exists() {
+            std::fs::write(&path, &payload)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+
+        let preview = String::from_utf8_lossy(truncate_utf8(&payload, PREVIEW_BYTES));
+        let payload_ref = ArtifactRef {
+            sha256,
+            location: ArtifactLocation::Local(path),
+            size_bytes: payload.len() as u64,
+            mime_type: Some("application/json".to_string()),
+        };
+        signal.payload_ref = Some(payload_ref.clone());
+        signal.metadata = BTreeMap::from([
+            (PREVIEW_KEY.to_string(), serde_json::json!(preview)),
+            (
+                SPILLED_BYTES_KEY.to_string(),
+                serde_json::json!(payload.len()),
+            ),
+        ]);
+        Ok(Some(Artifact {
+            id: EntityId::new(),
+            run_id: signal.run_id,
+            test_id: signal.test_id.unwrap_or(signal.run_id),
+            artifact_ref: payload_ref,
+            artifact_type: match signal.signal_type {
+                SignalType::API => ArtifactType::ApiResponse,
+                SignalType::WebSocket => ArtifactType::WsMessage,
+                SignalType::GRPC => ArtifactType::GrpcTrace,
+                _ => ArtifactType::Trace,
+            },
+            description: Some(format!("Metadata of signal {}", signal.id)),
+            created_at: signal.created_at,
+        }))
+    }
+}
+
+/// Read the bytes a signal's `payload_ref` points to, checked against its
+/// sha256. Only payloads on the local file system can be read; callers
+/// serving them check the location first (see [`SignalSpillover::holds`]).
+pub fn fetch_payload(payload_ref: &ArtifactRef) -> Result<Vec<u8>> {
+    let ArtifactLocation::Local(path) = &payload_ref.location else {
+        anyhow::bail!("Payload {} is not stored locally", payload_ref.location);
+    };
+    let payload =
+        std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let sha256 = format!("{:x}", Sha256::digest(&payload));
+    if !sha256.eq_ignore_ascii_case(&payload_ref.sha256) {
+        anyhow::bail!("Payload {} does not match its sha256", path.display());
+    }
+    Ok(payload)
+}
+
+/// Read back the full metadata a spilled signal's `payload_ref` points to
+pub fn resolve_payload(payload_ref: &ArtifactRef) -> Result<BTreeMap<String, serde_json::Value>> {
+    Ok(serde_json::from_slice(&fetch_payload(payload_ref)?)?)
+}
+
+/// Longest prefix of `bytes` up to `max` bytes that ends on a char boundary
+fn truncate_utf8(bytes: &[u8], max: usize) -> &[u8] {
+    if bytes.len() <= max {
+        return bytes;
+    }
+    let mut end = max;
+    while end > 0 && (bytes[end] & 0xC0) == 0x80 {
+        end -= 1;
+    }
+    &bytes[..end]
+}
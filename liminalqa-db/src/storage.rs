@@ -1,49 +1,467 @@
 //! Storage layer implementation
 
 use anyhow::{Context, Result};
-use liminalqa_core::{entities::*, facts::*, types::EntityId};
+use chrono::{DateTime, Utc};
+use liminalqa_core::{
+    baseline::{Baseline, DriftDataPoint, DriftDetector, LatencyAnomaly},
+    entities::*,
+    facts::*,
+    report::TestSummary,
+    resonance::{FlakeDetector, PatternDetector},
+    signal_schema::SchemaViolation,
+    temporal::BiTemporalTime,
+    types::{EntityId, RunStatus, SignalType, TestStatus},
+};
 use serde::{Deserialize, Serialize};
+use sled::{transaction::ConflictableTransactionError, Transactional};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::path::Path;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
+
+use crate::error::DbError;
+use crate::legacy::decode_entity;
+use crate::query::QueryCache;
+
+/// Page size used when the eager scans walk the facts tree
+const FACT_PAGE_SIZE: usize = 1024;
+
+/// Default number of samples below which a baseline is provisional
+pub const DEFAULT_MIN_BASELINE_SAMPLES: usize = 5;
+
+/// Latest signal latencies kept per test and signal type for its baseline
+pub const LATENCY_BASELINE_WINDOW: usize = 50;
+
+/// Default limit on the serialized size of a fact value (64 KiB)
+pub const DEFAULT_MAX_FACT_VALUE_BYTES: usize = 64 * 1024;
+
+/// Sled storage mode, see [`sled::Mode`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SledMode {
+    /// Favor smaller files over write throughput (sled's default)
+    #[default]
+    LowSpace,
+    HighThroughput,
+}
+
+impl From<SledMode> for sled::Mode {
+    fn from(mode: SledMode) -> Self {
+        match mode {
+            SledMode::LowSpace => sled::Mode::LowSpace,
+            SledMode::HighThroughput => sled::Mode::HighThroughput,
+        }
+    }
+}
+
+/// Options passed to sled by [`LiminalDB::open_with_config`]. The defaults
+/// match sled's own, which is what [`LiminalDB::open`] uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SledConfig {
+    /// Page cache size in bytes
+    pub cache_capacity: u64,
+    pub mode: SledMode,
+    /// Background flush interval, `None` to flush only on demand
+    pub flush_every_ms: Option<u64>,
+}
+
+impl Default for SledConfig {
+    fn default() -> Self {
+        Self {
+            cache_capacity: 1024 * 1024 * 1024,
+            mode: SledMode::LowSpace,
+            flush_every_ms: Some(500),
+        }
+    }
+}
+
+/// Signals of one type within one time bucket, returned by
+/// [`LiminalDB::signal_timeline`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimelineBucket {
+    pub start: DateTime<Utc>,
+    pub signal_type: SignalType,
+    pub count: u64,
+    /// Mean latency of the signals that reported one
+    pub avg_latency_ms: Option<f64>,
+}
+
+/// One page of facts returned by [`LiminalDB::scan_facts_page`]
+#[derive(Debug, Clone)]
+pub struct FactPage {
+    pub facts: Vec<Fact>,
+    /// Cursor for the next page, `None` when there are no more facts
+    pub next: Option<EntityId>,
+}
+
+/// Stored latency baseline with the samples it follows
+#[derive(Serialize, Deserialize)]
+struct LatencyBaselineRecord {
+    baseline: Baseline,
+    samples: Vec<f64>,
+}
+
+/// Test summary of a completed run as computed at `computed_at`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedRunSummary {
+    pub summary: TestSummary,
+    pub computed_at: DateTime<Utc>,
+}
+
+/// Number of records removed by [`LiminalDB::delete_run_cascade`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeleteCounts {
+    pub runs: usize,
+    pub tests: usize,
+    pub signals: usize,
+    pub artifacts: usize,
+    pub facts: usize,
+}
+
+impl DeleteCounts {
+    pub fn total(&self) -> usize {
+        self.runs + self.tests + self.signals + self.artifacts + self.facts
+    }
+}
 
 /// Main database handle
 pub struct LiminalDB {
     db: sled::Db,
     // Trees (indexes)
     entities: sled::Tree,
+    /// Type of every stored entity by id (not an index: never rebuilt), so
+    /// [`LiminalDB::rebuild_indexes`] knows how to decode each one
+    entity_types: sled::Tree,
     facts: sled::Tree,
     valid_time_index: sled::Tree,
     tx_time_index: sled::Tree,
     entity_type_index: sled::Tree,
     test_name_index: sled::Tree,
     test_history_index: sled::Tree,
+    signal_meta_index: sled::Tree,
+    /// Tests by `:test/owner` fact
+    test_owner_index: sled::Tree,
+    /// Tombstone transaction times by fact key, see [`LiminalDB::retract_fact`]
+    retraction_index: sled::Tree,
+    /// Runs by the build they ran against
+    build_run_index: sled::Tree,
+    /// Resonances by the name and suite of the tests they affect
+    resonance_test_index: sled::Tree,
+    /// Tests and signals by the run they belong to, signals in sequence
+    /// order
+    run_entity_index: sled::Tree,
+    /// Last signal sequence number handed out per run (not an index: never
+    /// rebuilt)
+    signal_sequences: sled::Tree,
+    /// Latest duration baseline per test, keyed by `name:suite`
+    baselines: sled::Tree,
+    /// Latency baseline per test and signal type with the samples it was
+    /// computed from, keyed by `name:suite:type`
+    latency_baselines: sled::Tree,
+    /// Test summary of each completed run, see [`LiminalDB::cached_run_summary`]
+    run_summaries: sled::Tree,
+    /// Update counter of each run, see [`LiminalDB::run_version`]
+    run_versions: sled::Tree,
+    /// Signal metadata keys extracted into `signal_meta_index` on write
+    indexed_signal_meta_keys: Vec<String>,
+    /// Largest accepted fact value, in serialized JSON bytes
+    max_fact_value_bytes: usize,
+    /// Baselines from fewer samples are stored as provisional
+    min_baseline_samples: usize,
+    /// Results of repeated queries; disabled when `None`
+    query_cache: Option<QueryCache>,
+    /// Derive facts from run, test and signal writes
+    entity_facts: bool,
 }
 
 impl LiminalDB {
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::open_with_config(path, SledConfig::default())
+    }
+
+    /// Open the database with explicit sled options. Fails with
+    /// [`DbError::AlreadyOpen`] when another process has it open.
+    pub fn open_with_config<P: AsRef<Path>>(path: P, config: SledConfig) -> Result<Self> {
         let path_ref = path.as_ref();
         info!("Opening LIMINAL-DB at: {}", path_ref.display());
 
-        let db = sled::open(path_ref).context("Failed to open sled database")?;
+        let db = sled::Config::new()
+            .path(path_ref)
+            .cache_capacity(config.cache_capacity)
+            .mode(config.mode.into())
+            .flush_every_ms(config.flush_every_ms)
+            .open()
+            .map_err(|e| {
+                if is_lock_error(&e) {
+                    anyhow::Error::new(DbError::AlreadyOpen {
+                        path: path_ref.to_path_buf(),
+                    })
+                } else {
+                    anyhow::Error::new(e).context("Failed to open sled database")
+                }
+            })?;
 
         let entities = db.open_tree("entities")?;
+        let entity_types = db.open_tree("entity_types")?;
         let facts = db.open_tree("facts")?;
         let valid_time_index = db.open_tree("idx_valid_time")?;
         let tx_time_index = db.open_tree("idx_tx_time")?;
         let entity_type_index = db.open_tree("idx_entity_type")?;
         let test_name_index = db.open_tree("idx_test_name")?;
         let test_history_index = db.open_tree("idx_test_history")?;
+        let signal_meta_index = db.open_tree("idx_signal_meta")?;
+        let test_owner_index = db.open_tree("idx_test_owner")?;
+        let build_run_index = db.open_tree("idx_build_runs")?;
+        let resonance_test_index = db.open_tree("idx_resonance_tests")?;
+        let run_entity_index = db.open_tree("idx_run_entities")?;
+        let retraction_index = db.open_tree("idx_retractions")?;
+        let signal_sequences = db.open_tree("signal_sequences")?;
+        let baselines = db.open_tree("baselines")?;
+        let latency_baselines = db.open_tree("latency_baselines")?;
+        let run_summaries = db.open_tree("run_summaries")?;
+        let run_versions = db.open_tree("run_versions")?;
 
-        Ok(Self {
+        let liminal = Self {
             db,
             entities,
+            entity_types,
             facts,
             valid_time_index,
             tx_time_index,
             entity_type_index,
             test_name_index,
             test_history_index,
-        })
+            signal_meta_index,
+            test_owner_index,
+            retraction_index,
+            build_run_index,
+            resonance_test_index,
+            run_entity_index,
+            signal_sequences,
+            baselines,
+            latency_baselines,
+            run_summaries,
+            run_versions,
+            indexed_signal_meta_keys: Vec::new(),
+            max_fact_value_bytes: DEFAULT_MAX_FACT_VALUE_BYTES,
+            min_baseline_samples: DEFAULT_MIN_BASELINE_SAMPLES,
+            query_cache: None,
+            entity_facts: false,
+        };
+        // Entities stored before their types were recorded
+        if liminal.entity_types.is_empty() {
+            for item in liminal.entity_type_index.iter() {
+                let (key, id_bytes) = item?;
+                let key = std::str::from_utf8(&key)?;
+                let (name, _) = key
+                    .split_once(':')
+                    .with_context(|| format!("Malformed entity type key '{}'", key))?;
+                liminal.entity_types.insert(id_bytes, name.as_bytes())?;
+            }
+        }
+        // Resonances stored before the index existed
+        if liminal.resonance_test_index.is_empty() {
+            for id in liminal.get_entities_by_type(EntityType::Resonance)? {
+                if let Some(resonance) = liminal.get_entity::<Resonance>(id)? {
+                    liminal.index_resonance(&resonance)?;
+                }
+            }
+        }
+        Ok(liminal)
+    }
+
+    /// Index the given signal metadata keys so they can be queried with
+    /// [`LiminalDB::scan_signals_by_meta`]. Only signals stored after the
+    /// keys are configured are indexed.
+    pub fn with_indexed_signal_meta_keys<I, S>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.indexed_signal_meta_keys = keys.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Reject fact values larger than `limit` serialized JSON bytes (see
+    /// [`DbError::FactValueTooLarge`]). Defaults to
+    /// [`DEFAULT_MAX_FACT_VALUE_BYTES`].
+    pub fn with_max_fact_value_bytes(mut self, limit: usize) -> Self {
+        self.max_fact_value_bytes = limit;
+        self
+    }
+
+    /// Store baselines computed from fewer than `min` samples as
+    /// provisional (see [`LiminalDB::upsert_baseline`]). Defaults to
+    /// [`DEFAULT_MIN_BASELINE_SAMPLES`].
+    pub fn with_min_baseline_samples(mut self, min: usize) -> Self {
+        self.min_baseline_samples = min;
+        self
+    }
+
+    /// Cache the results of up to `capacity` distinct queries, dropped on
+    /// every fact write; a capacity of 0 disables the cache (the default)
+    pub fn with_query_cache(mut self, capacity: usize) -> Self {
+        self.query_cache = (capacity > 0).then(|| QueryCache::new(capacity));
+        self
+    }
+
+    /// Also record run, test and signal writes as facts (`:run/started_at`,
+    /// `:test/status`, `:api/latency`, ...), so the fact log holds the full
+    /// bi-temporal history; storing an entity again records only the values
+    /// that changed. Off by default: entities alone are faster to write.
+    pub fn with_entity_facts(mut self, enabled: bool) -> Self {
+        self.entity_facts = enabled;
+        self
+    }
+
+    /// The facts `derive` makes of writing `entity`, when entity facts are
+    /// enabled.
+    ///
+    /// Values the `stored` version of the entity already derived are left
+    /// out, so storing a run again records only what changed. Every fact is
+    /// checked against the fact limits, letting the caller refuse the write
+    /// before anything of it is stored.
+    fn entity_facts<T>(
+        &self,
+        entity: &T,
+        stored: impl FnOnce() -> Result<Option<T>>,
+        derive: impl Fn(&T) -> Result<Vec<Fact>>,
+    ) -> Result<Vec<Fact>> {
+        if !self.entity_facts {
+            return Ok(Vec::new());
+        }
+        let mut facts = derive(entity)?;
+        if let Some(stored) = stored()? {
+            let recorded = derive(&stored)?;
+            facts.retain(|f| {
+                !recorded
+                    .iter()
+                    .any(|r| r.attribute == f.attribute && r.value == f.value)
+            });
+        }
+        for fact in &facts {
+            self.check_fact(fact)?;
+        }
+        Ok(facts)
+    }
+
+    /// The query cache, when enabled
+    pub fn query_cache(&self) -> Option<&QueryCache> {
+        self.query_cache.as_ref()
+    }
+
+    /// Store the baseline of a test, replacing the previous one.
+    ///
+    /// A baseline from fewer samples than the configured minimum is stored
+    /// with `provisional` set, so drift detection skips it. Returns the
+    /// baseline as stored.
+    pub fn upsert_baseline(&self, baseline: &Baseline) -> Result<Baseline> {
+        let baseline = Baseline {
+            provisional: baseline.sample_size < self.min_baseline_samples,
+            ..baseline.clone()
+        };
+        self.baselines.insert(
+            baseline_key(&baseline.name, &baseline.suite).as_bytes(),
+            bincode::serialize(&baseline)?,
+        )?;
+        Ok(baseline)
+    }
+
+    /// The stored baseline of a test, if any
+    pub fn get_baseline(&self, name: &str, suite: &str) -> Result<Option<Baseline>> {
+        match self.baselines.get(baseline_key(name, suite).as_bytes())? {
+            Some(bytes) => Ok(Some(bincode::deserialize(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Add a signal latency to the baseline of `name` in `suite` for
+    /// `signal_type`, which follows the latest [`LATENCY_BASELINE_WINDOW`]
+    /// latencies. Returns the updated baseline, provisional like
+    /// [`LiminalDB::upsert_baseline`] while samples are few.
+    pub fn record_signal_latency(
+        &self,
+        name: &str,
+        suite: &str,
+        signal_type: SignalType,
+        latency_ms: u64,
+    ) -> Result<Baseline> {
+        let key = latency_baseline_key(name, suite, signal_type)?;
+        let mut samples = match self.latency_baselines.get(key.as_bytes())? {
+            Some(bytes) => bincode::deserialize::<LatencyBaselineRecord>(&bytes)?.samples,
+            None => Vec::new(),
+        };
+        samples.push(latency_ms as f64);
+        if samples.len() > LATENCY_BASELINE_WINDOW {
+            samples.drain(..samples.len() - LATENCY_BASELINE_WINDOW);
+        }
+        let mut baseline = Baseline::from_samples(name, suite, &samples);
+        baseline.provisional = baseline.sample_size < self.min_baseline_samples;
+        self.latency_baselines.insert(
+            key.as_bytes(),
+            bincode::serialize(&LatencyBaselineRecord {
+                baseline: baseline.clone(),
+                samples,
+            })?,
+        )?;
+        Ok(baseline)
+    }
+
+    /// The latency baseline of `name` in `suite` for `signal_type`, if any
+    /// latency was recorded
+    pub fn get_latency_baseline(
+        &self,
+        name: &str,
+        suite: &str,
+        signal_type: SignalType,
+    ) -> Result<Option<Baseline>> {
+        let key = latency_baseline_key(name, suite, signal_type)?;
+        match self.latency_baselines.get(key.as_bytes())? {
+            Some(bytes) => Ok(Some(
+                bincode::deserialize::<LatencyBaselineRecord>(&bytes)?.baseline,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// Record `anomaly` as a `:signal/latency_anomaly` fact of the signal,
+    /// known from the signal's own transaction time
+    pub fn put_latency_anomaly(&self, signal: &Signal, anomaly: &LatencyAnomaly) -> Result<()> {
+        self.put_fact(&Fact::with_time(
+            signal.id,
+            Attribute::SignalLatencyAnomaly,
+            serde_json::to_value(anomaly)?,
+            signal.created_at,
+        ))
+    }
+
+    /// Record the metadata `signal` lacks for its kind as a
+    /// `:signal/schema_violation` fact, known when the signal is
+    pub fn put_schema_violation(&self, signal: &Signal, violation: &SchemaViolation) -> Result<()> {
+        self.put_fact(&Fact::with_time(
+            signal.id,
+            Attribute::SignalSchemaViolation,
+            serde_json::to_value(violation)?,
+            signal.created_at,
+        ))
+    }
+
+    /// Executions of a test started at or after `since`, oldest first, each
+    /// placed against the test's stored baseline by `detector`
+    pub fn get_drift_data(
+        &self,
+        name: &str,
+        suite: &str,
+        since: DateTime<Utc>,
+        detector: &DriftDetector,
+    ) -> Result<Vec<DriftDataPoint>> {
+        let baseline = self.get_baseline(name, suite)?;
+        let mut history = self.get_test_history(name, suite, usize::MAX)?;
+        history.retain(|t| t.started_at >= since);
+        history.reverse();
+        Ok(history
+            .iter()
+            .map(|t| detector.data_point(t, baseline.as_ref()))
+            .collect())
     }
 
     /// Store a system entity
@@ -58,27 +476,358 @@ impl LiminalDB {
 
     /// Store a run entity
     pub fn put_run(&self, run: &Run) -> Result<()> {
-        self.put_entity(EntityType::Run, run.id, run)
+        self.put_run_if_version(run, None).map(|_| ())
+    }
+
+    /// Store a run entity provided the stored run is still at
+    /// `expected_version` (any version when `None`), returning its new
+    /// version. Fails with [`DbError::RunVersionConflict`] when another
+    /// update got in first; the caller re-reads the run and retries.
+    pub fn put_run_if_version(&self, run: &Run, expected_version: Option<u64>) -> Result<u64> {
+        let facts = self.entity_facts(run, || self.get_entity(run.id), run_facts)?;
+        let version = self.bump_run_version(run.id, expected_version)?;
+        self.put_entity(EntityType::Run, run.id, run)?;
+        self.index_run(run)?;
+        for fact in &facts {
+            self.put_fact(fact)?;
+        }
+        if run.ended_at.is_some() {
+            crate::report::cache_run_summary(self, run.id)?;
+        }
+        Ok(version)
+    }
+
+    /// How many times a run was stored or had its status changed; 0 for a
+    /// run never stored
+    pub fn run_version(&self, run_id: EntityId) -> Result<u64> {
+        decode_run_version(self.run_versions.get(run_id.to_bytes())?.as_deref())
+    }
+
+    /// Move the version of a run on by one, atomically and provided it is
+    /// still `expected` (unless `None`), returning the new version
+    fn bump_run_version(&self, run_id: EntityId, expected: Option<u64>) -> Result<u64> {
+        let key = run_id.to_bytes();
+        let mut current = self.run_versions.get(key)?;
+        loop {
+            let version = decode_run_version(current.as_deref())?;
+            if let Some(expected) = expected.filter(|e| *e != version) {
+                return Err(DbError::RunVersionConflict {
+                    run_id,
+                    expected,
+                    actual: version,
+                }
+                .into());
+            }
+            let next = version + 1;
+            match self
+                .run_versions
+                .compare_and_swap(key, current, Some(&next.to_be_bytes()))?
+            {
+                Ok(()) => return Ok(next),
+                Err(conflict) => current = conflict.current,
+            }
+        }
+    }
+
+    /// Store the test summary of a completed run, computed now
+    pub fn put_run_summary(&self, run_id: EntityId, summary: &TestSummary) -> Result<()> {
+        let cached = CachedRunSummary {
+            summary: summary.clone(),
+            computed_at: Utc::now(),
+        };
+        self.run_summaries
+            .insert(run_id.to_bytes(), serde_json::to_vec(&cached)?)?;
+        Ok(())
+    }
+
+    /// The cached test summary of a run, if it completed and nothing
+    /// changing its summary was stored since
+    pub fn cached_run_summary(&self, run_id: EntityId) -> Result<Option<CachedRunSummary>> {
+        match self.run_summaries.get(run_id.to_bytes())? {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Drop the cached summary of a run; the next report computes it live
+    pub fn invalidate_run_summary(&self, run_id: EntityId) -> Result<()> {
+        self.run_summaries.remove(run_id.to_bytes())?;
+        Ok(())
+    }
+
+    /// Mark an in-progress run as cancelled.
+    ///
+    /// The run entity is left untouched; a `:run/status` fact records the
+    /// cancellation so reports built as of an earlier transaction time still
+    /// see the run as running. Fails with [`DbError::RunNotFound`] or, when
+    /// the run completed or was cancelled before, [`DbError::RunAlreadyEnded`].
+    pub fn cancel_run(&self, run_id: EntityId) -> Result<()> {
+        self.cancel_run_if_version(run_id, None).map(|_| ())
+    }
+
+    /// [`LiminalDB::cancel_run`] provided the run is still at
+    /// `expected_version`, see [`LiminalDB::put_run_if_version`]
+    pub fn cancel_run_if_version(
+        &self,
+        run_id: EntityId,
+        expected_version: Option<u64>,
+    ) -> Result<u64> {
+        let status = self.run_status(run_id)?;
+        if status != RunStatus::Running {
+            return Err(DbError::RunAlreadyEnded { run_id, status }.into());
+        }
+        let version = self.bump_run_version(run_id, expected_version)?;
+        self.put_fact(&Fact::new(
+            run_id,
+            Attribute::RunStatus,
+            serde_json::to_value(RunStatus::Cancelled)?,
+        ))?;
+        Ok(version)
+    }
+
+    /// Current status of a run
+    pub fn run_status(&self, run_id: EntityId) -> Result<RunStatus> {
+        self.run_status_at(run_id, Utc::now())
+    }
+
+    /// Status of a run as known at transaction time `as_of`: the latest
+    /// `:run/status` fact, else completed once the run has an end time
+    pub fn run_status_at(&self, run_id: EntityId, as_of: DateTime<Utc>) -> Result<RunStatus> {
+        let run: Run = self
+            .get_entity(run_id)?
+            .ok_or(DbError::RunNotFound(run_id))?;
+
+        let recorded = self
+            .facts_as_of(&[run_id], as_of)?
+            .into_iter()
+            .filter(|f| f.attribute == Attribute::RunStatus)
+            .filter_map(|f| Some((f.time, serde_json::from_value::<RunStatus>(f.value).ok()?)))
+            .max_by_key(|(time, _)| (time.valid_time, time.tx_time));
+        Ok(match recorded {
+            Some((_, status)) => status,
+            None if run.ended_at.is_some() => RunStatus::Completed,
+            None => RunStatus::Running,
+        })
+    }
+
+    /// Record how well `test` met its guidance as a `:test/alignment` fact,
+    /// known from the test's own transaction time. `score` is the fraction
+    /// of observables satisfied and must be within `0..=1`.
+    pub fn put_test_alignment(&self, test: &Test, score: f64) -> Result<()> {
+        if !(0.0..=1.0).contains(&score) {
+            anyhow::bail!("Alignment score must be within 0..=1, got {}", score);
+        }
+        self.put_fact(&Fact::with_time(
+            test.id,
+            Attribute::TestAlignment,
+            serde_json::json!(score),
+            test.created_at,
+        ))
+    }
+
+    /// Record the owner of `test` as a `:test/owner` fact, known from the
+    /// test's own transaction time
+    pub fn put_test_owner(&self, test: &Test, owner: &str) -> Result<()> {
+        if owner.trim().is_empty() {
+            anyhow::bail!("Test owner must not be empty");
+        }
+        self.put_fact(&Fact::with_time(
+            test.id,
+            Attribute::TestOwner,
+            serde_json::json!(owner),
+            test.created_at,
+        ))
+    }
+
+    /// Record the SLA of `test`, the longest it is expected to take, as a
+    /// `:test/sla_ms` fact known from the test's own transaction time
+    pub fn put_test_sla(&self, test: &Test, sla_ms: u64) -> Result<()> {
+        if sla_ms == 0 {
+            anyhow::bail!("Test SLA must be positive");
+        }
+        self.put_fact(&Fact::with_time(
+            test.id,
+            Attribute::TestSla,
+            serde_json::json!(sla_ms),
+            test.created_at,
+        ))
+    }
+
+    /// Record how many attempts `test` took as a `:test/attempts` fact,
+    /// known from the test's own transaction time
+    pub fn put_test_attempts(&self, test: &Test, attempts: u32) -> Result<()> {
+        if attempts == 0 {
+            anyhow::bail!("Test attempts must be positive");
+        }
+        self.put_fact(&Fact::with_time(
+            test.id,
+            Attribute::TestAttempts,
+            serde_json::json!(attempts),
+            test.created_at,
+        ))
+    }
+
+    /// Owner of each of `test_ids` as known at `as_of`: the latest
+    /// `:test/owner` fact. Tests without an owner are left out.
+    pub fn test_owners_at(
+        &self,
+        test_ids: &[EntityId],
+        as_of: DateTime<Utc>,
+    ) -> Result<HashMap<EntityId, String>> {
+        let mut latest: HashMap<EntityId, (DateTime<Utc>, String)> = HashMap::new();
+        for fact in self.facts_as_of(test_ids, as_of)? {
+            if fact.attribute != Attribute::TestOwner {
+                continue;
+            }
+            let Some(owner) = fact.value.as_str() else {
+                continue;
+            };
+            let newer = latest
+                .get(&fact.entity_id)
+                .is_none_or(|(tx_time, _)| fact.time.tx_time >= *tx_time);
+            if newer {
+                latest.insert(fact.entity_id, (fact.time.tx_time, owner.to_string()));
+            }
+        }
+        Ok(latest
+            .into_iter()
+            .map(|(id, (_, owner))| (id, owner))
+            .collect())
+    }
+
+    /// Every run executed against `build_id`, ordered by start time
+    pub fn runs_for_build(&self, build_id: EntityId) -> Result<Vec<Run>> {
+        let mut runs = Vec::new();
+        for item in self.build_run_index.scan_prefix(build_run_prefix(build_id)) {
+            let (_, id_bytes) = item?;
+            let id = EntityId::from_bytes(id_bytes.as_ref().try_into()?);
+            // A run stored again against another build leaves its old entry
+            if let Some(run) = self.get_entity::<Run>(id)? {
+                if run.build_id == build_id {
+                    runs.push(run);
+                }
+            }
+        }
+        runs.sort_by_key(|r| (r.started_at, r.id));
+        Ok(runs)
+    }
+
+    /// Record the annotations of `run` (PR number, commit message,
+    /// triggering user, ...) as a `:run/annotations` fact, known from the
+    /// run's own transaction time. Replaces the annotations recorded before.
+    pub fn put_run_annotations(
+        &self,
+        run: &Run,
+        annotations: &HashMap<String, String>,
+    ) -> Result<()> {
+        if annotations.keys().any(|key| key.trim().is_empty()) {
+            anyhow::bail!("Run annotation keys must not be empty");
+        }
+        self.put_fact(&Fact::with_time(
+            run.id,
+            Attribute::RunAnnotations,
+            serde_json::to_value(annotations)?,
+            run.created_at,
+        ))
+    }
+
+    /// Current annotations of each of `run_ids`: the latest
+    /// `:run/annotations` fact. Runs never annotated are left out.
+    pub fn run_annotations(
+        &self,
+        run_ids: &[EntityId],
+    ) -> Result<HashMap<EntityId, HashMap<String, String>>> {
+        let mut latest: HashMap<EntityId, (DateTime<Utc>, HashMap<String, String>)> =
+            HashMap::new();
+        let facts = self.without_retracted(self.scan_facts_by_entities(run_ids)?, Utc::now())?;
+        for fact in facts {
+            if fact.attribute != Attribute::RunAnnotations {
+                continue;
+            }
+            let Ok(annotations) = serde_json::from_value(fact.value) else {
+                continue;
+            };
+            let newer = latest
+                .get(&fact.entity_id)
+                .is_none_or(|(tx_time, _)| fact.time.tx_time >= *tx_time);
+            if newer {
+                latest.insert(fact.entity_id, (fact.time.tx_time, annotations));
+            }
+        }
+        Ok(latest
+            .into_iter()
+            .map(|(id, (_, annotations))| (id, annotations))
+            .collect())
+    }
+
+    /// Every run with its current annotations, ordered by start time. With
+    /// `annotation`, only runs annotated with that key and value.
+    pub fn annotated_runs(
+        &self,
+        annotation: Option<(&str, &str)>,
+    ) -> Result<Vec<(Run, HashMap<String, String>)>> {
+        let ids = self.get_entities_by_type(EntityType::Run)?;
+        let mut annotations = self.run_annotations(&ids)?;
+        let mut runs = Vec::new();
+        for id in ids {
+            let run_annotations = annotations.remove(&id).unwrap_or_default();
+            if let Some((key, value)) = annotation {
+                if run_annotations.get(key).map(String::as_str) != Some(value) {
+                    continue;
+                }
+            }
+            if let Some(run) = self.get_entity::<Run>(id)? {
+                runs.push((run, run_annotations));
+            }
+        }
+        runs.sort_by_key(|(r, _)| (r.started_at, r.id));
+        Ok(runs)
+    }
+
+    /// Tests currently owned by `owner`, ordered by start time
+    pub fn tests_by_owner(&self, owner: &str) -> Result<Vec<Test>> {
+        let mut ids = Vec::new();
+        for item in self.test_owner_index.scan_prefix(test_owner_key(owner)?) {
+            let (_, id_bytes) = item?;
+            ids.push(EntityId::from_bytes(id_bytes.as_ref().try_into()?));
+        }
+        // The index keeps every owner a test ever had
+        let owners = self.test_owners_at(&ids, Utc::now())?;
+        let mut tests = Vec::new();
+        for id in ids {
+            if owners.get(&id).map(String::as_str) != Some(owner) {
+                continue;
+            }
+            if let Some(test) = self.get_entity::<Test>(id)? {
+                tests.push(test);
+            }
+        }
+        tests.sort_by_key(|t| (t.started_at, t.id));
+        Ok(tests)
     }
 
     /// Store a test entity
     pub fn put_test(&self, test: &Test) -> Result<()> {
+        let facts = self.entity_facts(test, || self.get_entity(test.id), test_facts)?;
         self.put_entity(EntityType::Test, test.id, test)?;
+        self.index_test(test)?;
+        for fact in &facts {
+            self.put_fact(fact)?;
+        }
+        self.invalidate_run_summary(test.run_id)
+    }
 
+    fn index_test(&self, test: &Test) -> Result<()> {
         // Create secondary index for name lookup
-        let index_key = format!("idx:test_name:{}:{}", test.run_id, test.name);
         self.test_name_index
-            .insert(index_key.as_bytes(), &test.id.to_bytes())?;
+            .insert(test_name_key(test).as_bytes(), &test.id.to_bytes())?;
 
         // Create index for history lookup (name + suite + time)
-        let history_key = format!(
-            "idx:history:{}:{}:{}",
-            test.name,
-            test.suite,
-            test.started_at.timestamp_millis()
-        );
         self.test_history_index
-            .insert(history_key.as_bytes(), &test.id.to_bytes())?;
+            .insert(test_history_key(test).as_bytes(), &test.id.to_bytes())?;
+
+        self.run_entity_index
+            .insert(run_test_key(test).as_bytes(), &test.id.to_bytes())?;
 
         Ok(())
     }
@@ -109,6 +858,151 @@ impl LiminalDB {
         Ok(tests)
     }
 
+    /// Record an intermediate phase of a test that is still running
+    pub fn put_test_progress(&self, progress: &TestProgress) -> Result<()> {
+        self.put_fact(&Fact::with_time(
+            progress.test_id,
+            Attribute::TestProgress,
+            serde_json::to_value(progress)?,
+            liminalqa_core::temporal::BiTemporalTime::with_valid_time(progress.at),
+        ))
+    }
+
+    /// Tests of `run_id` that reported progress, whether or not they have
+    /// finished since
+    pub fn progress_tests_of_run(&self, run_id: EntityId) -> Result<Vec<EntityId>> {
+        let mut ids = Vec::new();
+        for item in self
+            .run_entity_index
+            .scan_prefix(run_progress_prefix(run_id))
+        {
+            let (_, id_bytes) = item?;
+            ids.push(EntityId::from_bytes(id_bytes.as_ref().try_into()?));
+        }
+        Ok(ids)
+    }
+
+    /// Progress reported by a test, in the order the phases happened
+    pub fn get_test_progress(&self, test_id: EntityId) -> Result<Vec<TestProgress>> {
+        let mut facts: Vec<Fact> = self
+            .without_retracted(self.scan_facts_by_entities(&[test_id])?, Utc::now())?
+            .into_iter()
+            .filter(|f| f.attribute == Attribute::TestProgress)
+            .collect();
+        facts.sort_by_key(|f| (f.time.valid_time, f.time.tx_time));
+
+        facts
+            .into_iter()
+            .map(|f| Ok(serde_json::from_value(f.value)?))
+            .collect()
+    }
+
+    /// Status changes a test went through across its runs, oldest first.
+    ///
+    /// Each entry is `(at, from, to)`. Statuses come from every recorded
+    /// execution of `name` in `suite` plus any `:test/status` facts that
+    /// re-triaged them, ordered by valid time. Only the `limit` most recent
+    /// transitions are returned.
+    pub fn test_status_transitions(
+        &self,
+        name: &str,
+        suite: &str,
+        limit: usize,
+    ) -> Result<Vec<(DateTime<Utc>, TestStatus, TestStatus)>> {
+        let history = self.get_test_history(name, suite, usize::MAX)?;
+        let ids: Vec<EntityId> = history.iter().map(|t| t.id).collect();
+
+        let mut changes: Vec<(DateTime<Utc>, TestStatus)> =
+            history.iter().map(|t| (t.completed_at, t.status)).collect();
+        for fact in self.without_retracted(self.scan_facts_by_entities(&ids)?, Utc::now())? {
+            if fact.attribute != Attribute::TestStatus {
+                continue;
+            }
+            if let Ok(status) = serde_json::from_value::<TestStatus>(fact.value) {
+                changes.push((fact.time.valid_time, status));
+            }
+        }
+        changes.sort_by_key(|(at, _)| *at);
+
+        let transitions: Vec<_> = changes
+            .windows(2)
+            .filter(|w| w[0].1 != w[1].1)
+            .map(|w| (w[1].0, w[0].1, w[1].1))
+            .collect();
+        let skip = transitions.len().saturating_sub(limit);
+        Ok(transitions.into_iter().skip(skip).collect())
+    }
+
+    /// Tests of a run whose status was revised between two transaction times.
+    ///
+    /// Each entry is `(test, old, new)`: the status known as of `tx_from` and
+    /// as of `tx_to`. The status known as of a transaction time is the one
+    /// with the latest valid time among the recorded execution and the
+    /// `:test/status` facts written up to then. Tests not yet recorded at
+    /// `tx_from` are not reported.
+    pub fn status_revisions(
+        &self,
+        run_id: EntityId,
+        tx_from: DateTime<Utc>,
+        tx_to: DateTime<Utc>,
+    ) -> Result<Vec<(Test, TestStatus, TestStatus)>> {
+        let prefix = format!("idx:test_name:{}:", run_id);
+        let mut tests = Vec::new();
+        for item in self.test_name_index.scan_prefix(prefix.as_bytes()) {
+            let (_, id_bytes) = item?;
+            let id = EntityId::from_bytes(id_bytes.as_ref().try_into()?);
+            if let Some(test) = self.get_entity::<Test>(id)? {
+                tests.push(test);
+            }
+        }
+
+        let ids: Vec<EntityId> = tests.iter().map(|t| t.id).collect();
+        // Status facts of each test as seen from a transaction time
+        let status_facts = |tx: DateTime<Utc>| -> Result<_> {
+            let mut facts: HashMap<EntityId, Vec<(BiTemporalTime, TestStatus)>> = HashMap::new();
+            for fact in self.facts_as_of(&ids, tx)? {
+                if fact.attribute != Attribute::TestStatus {
+                    continue;
+                }
+                if let Ok(status) = serde_json::from_value::<TestStatus>(fact.value) {
+                    facts
+                        .entry(fact.entity_id)
+                        .or_default()
+                        .push((fact.time, status));
+                }
+            }
+            Ok(facts)
+        };
+        let facts_from = status_facts(tx_from)?;
+        let facts_to = status_facts(tx_to)?;
+
+        let mut revisions = Vec::new();
+        for test in tests {
+            let recorded = (
+                BiTemporalTime::with_times(test.completed_at, test.created_at.tx_time),
+                test.status,
+            );
+            let status_as_of =
+                |tx: DateTime<Utc>,
+                 facts: &HashMap<EntityId, Vec<(BiTemporalTime, TestStatus)>>| {
+                    std::iter::once(&recorded)
+                        .chain(facts.get(&test.id).into_iter().flatten())
+                        .filter(|(time, _)| time.tx_time <= tx)
+                        .max_by_key(|(time, _)| (time.valid_time, time.tx_time))
+                        .map(|(_, status)| *status)
+                };
+            if let (Some(old), Some(new)) = (
+                status_as_of(tx_from, &facts_from),
+                status_as_of(tx_to, &facts_to),
+            ) {
+                if old != new {
+                    revisions.push((test, old, new));
+                }
+            }
+        }
+        Ok(revisions)
+    }
+
     /// Find test ID by name within a specific run
     ///
     /// # Arguments
@@ -131,322 +1025,3212 @@ impl LiminalDB {
         }
     }
 
-    /// Store an artifact entity
-    pub fn put_artifact(&self, artifact: &Artifact) -> Result<()> {
-        self.put_entity(EntityType::Artifact, artifact.id, artifact)
-    }
+    /// Bucket test durations into fixed-width buckets of `bucket_ms`.
+    ///
+    /// Returns `(bucket_start_ms, count)` pairs ordered by bucket, omitting
+    /// empty buckets. `suite` and `run_id` narrow the set when given. Skipped
+    /// tests never executed, so their duration is null and they are left out
+    /// rather than being counted in the first bucket.
+    pub fn duration_histogram(
+        &self,
+        suite: Option<&str>,
+        run_id: Option<EntityId>,
+        bucket_ms: u32,
+    ) -> Result<Vec<(u32, u64)>> {
+        if bucket_ms == 0 {
+            anyhow::bail!("bucket_ms must be greater than zero");
+        }
 
-    /// Store a signal entity
-    pub fn put_signal(&self, signal: &Signal) -> Result<()> {
-        self.put_entity(EntityType::Signal, signal.id, signal)
-    }
+        let mut buckets: std::collections::BTreeMap<u32, u64> = std::collections::BTreeMap::new();
+        for id in self.get_entities_by_type(EntityType::Test)? {
+            let Some(test) = self.get_entity::<Test>(id)? else {
+                continue;
+            };
+            if test.status == TestStatus::Skip
+                || suite.is_some_and(|s| s != test.suite)
+                || run_id.is_some_and(|r| r != test.run_id)
+            {
+                continue;
+            }
 
-    /// Store a resonance entity
-    pub fn put_resonance(&self, resonance: &Resonance) -> Result<()> {
-        self.put_entity(EntityType::Resonance, resonance.id, resonance)
+            let bucket = test.duration_ms / u64::from(bucket_ms) * u64::from(bucket_ms);
+            *buckets
+                .entry(u32::try_from(bucket).unwrap_or(u32::MAX))
+                .or_default() += 1;
+        }
+
+        Ok(buckets.into_iter().collect())
     }
 
-    /// Generic entity storage
-    fn put_entity<T: Serialize>(
-        &self,
-        entity_type: EntityType,
-        id: EntityId,
-        entity: &T,
-    ) -> Result<()> {
-        let key = id.to_bytes();
-        let value = bincode::serialize(entity)?;
+    /// Count the signals of a run per fixed-width time bucket of `bucket_ms`
+    /// and signal type.
+    ///
+    /// Buckets are aligned to the Unix epoch, ordered by start then by
+    /// [`SignalType`] declaration order, and empty buckets are omitted.
+    pub fn signal_timeline(&self, run_id: EntityId, bucket_ms: u64) -> Result<Vec<TimelineBucket>> {
+        if bucket_ms == 0 {
+            anyhow::bail!("bucket_ms must be greater than zero");
+        }
+        let width = i64::try_from(bucket_ms).context("bucket_ms is too large")?;
 
-        self.entities.insert(key, value)?;
+        // (count, latency sum, signals with a latency) per (bucket, type)
+        let mut buckets: HashMap<(i64, SignalType), (u64, u64, u64)> = HashMap::new();
+        for signal in self.signals_of_run(run_id, None)? {
+            let start = signal.timestamp.timestamp_millis().div_euclid(width) * width;
+            let entry = buckets.entry((start, signal.signal_type)).or_default();
+            entry.0 += 1;
+            if let Some(latency) = signal.latency_ms {
+                entry.1 += latency;
+                entry.2 += 1;
+            }
+        }
 
-        // Index by entity type
-        let type_key = format!("{}:{}", entity_type_to_str(entity_type), id);
-        self.entity_type_index.insert(type_key.as_bytes(), &key)?;
+        let mut timeline = Vec::with_capacity(buckets.len());
+        for ((start, signal_type), (count, latency_sum, latencies)) in buckets {
+            timeline.push(TimelineBucket {
+                start: DateTime::from_timestamp_millis(start)
+                    .context("Signal timestamp out of range")?,
+                signal_type,
+                count,
+                avg_latency_ms: (latencies > 0).then(|| latency_sum as f64 / latencies as f64),
+            });
+        }
+        timeline.sort_by_key(|b| (b.start, b.signal_type));
+        Ok(timeline)
+    }
 
-        debug!("Stored entity: type={:?}, id={}", entity_type, id);
-        Ok(())
+    /// Store an artifact entity
+    pub fn put_artifact(&self, artifact: &Artifact) -> Result<()> {
+        self.put_entity(EntityType::Artifact, artifact.id, artifact)
     }
 
-    /// Store a fact
+    /// Store a signal entity, assigning it the next sequence number of its
+    /// run (any `sequence` set by the caller is replaced). Returns the
+    /// assigned sequence.
+    pub fn put_signal(&self, signal: &Signal) -> Result<u64> {
+        let signal = Signal {
+            sequence: self.reserve_signal_sequences(signal.run_id, 1)?,
+            ..signal.clone()
+        };
+        let facts = self.entity_facts(&signal, || self.get_signal(signal.id), signal_facts)?;
+        // Signal metadata holds serde_json::Value, which bincode can't decode
+        self.put_entity_bytes(EntityType::Signal, signal.id, serde_json::to_vec(&signal)?)?;
+        self.index_signal(&signal)?;
+        for fact in &facts {
+            self.put_fact(fact)?;
+        }
+        Ok(signal.sequence)
+    }
+
+    /// Atomically reserve `count` consecutive sequence numbers for signals of
+    /// `run_id`, returning the first. Sequences start at 1.
+    fn reserve_signal_sequences(&self, run_id: EntityId, count: u64) -> Result<u64> {
+        let last = self
+            .signal_sequences
+            .update_and_fetch(run_id.to_bytes(), |old| {
+                let old = old
+                    .and_then(|b| <[u8; 8]>::try_from(b).ok())
+                    .map_or(0, u64::from_be_bytes);
+                Some((old + count).to_be_bytes().to_vec())
+            })?
+            .context("Signal sequence counter missing after update")?;
+        let last = u64::from_be_bytes(
+            last.as_ref()
+                .try_into()
+                .context("Corrupt signal sequence counter")?,
+        );
+        Ok(last - count + 1)
+    }
+
+    fn index_signal(&self, signal: &Signal) -> Result<()> {
+        // Index configured metadata keys and the correlation id
+        for index_key in self.signal_meta_index_keys(signal)? {
+            self.signal_meta_index
+                .insert(index_key.as_bytes(), &signal.id.to_bytes())?;
+        }
+
+        self.run_entity_index
+            .insert(run_signal_key(signal).as_bytes(), &signal.id.to_bytes())?;
+
+        Ok(())
+    }
+
+    /// Every test of `run_id`, in completion order
+    pub fn tests_of_run(&self, run_id: EntityId) -> Result<Vec<Test>> {
+        let mut tests = Vec::new();
+        for item in self.run_entity_index.scan_prefix(run_test_prefix(run_id)) {
+            let (_, id_bytes) = item?;
+            let id = EntityId::from_bytes(id_bytes.as_ref().try_into()?);
+            if let Some(test) = self.get_entity::<Test>(id)? {
+                tests.push(test);
+            }
+        }
+        tests.sort_by_key(|t| (t.completed_at, t.id));
+        Ok(tests)
+    }
+
+    /// Signals of `run_id` in sequence order, only those with a sequence
+    /// number above `after` when given. Pass the last sequence seen to read
+    /// only what arrived since.
+    pub fn signals_of_run(&self, run_id: EntityId, after: Option<u64>) -> Result<Vec<Signal>> {
+        let prefix = run_signal_prefix(run_id);
+        let start = match after {
+            Some(after) => format!("{}{:020}", prefix, after.saturating_add(1)),
+            None => prefix.clone(),
+        };
+        let mut signals = Vec::new();
+        for item in self.run_entity_index.range(start.as_bytes()..) {
+            let (key, id_bytes) = item?;
+            if !key.starts_with(prefix.as_bytes()) {
+                break;
+            }
+            let id = EntityId::from_bytes(id_bytes.as_ref().try_into()?);
+            if let Some(signal) = self.get_signal(id)? {
+                signals.push(signal);
+            }
+        }
+        Ok(signals)
+    }
+
+    /// Keys of `signal` in `signal_meta_index`, which also holds the
+    /// correlation id index under its own prefix
+    fn signal_meta_index_keys(&self, signal: &Signal) -> Result<Vec<String>> {
+        let mut keys = Vec::new();
+        for key in &self.indexed_signal_meta_keys {
+            if let Some(value) = signal.metadata.get(key) {
+                keys.push(signal_meta_key(key, value)? + &signal.id.to_string());
+            }
+        }
+        if let Some(correlation_id) = &signal.correlation_id {
+            keys.push(signal_correlation_key(correlation_id)? + &signal.id.to_string());
+        }
+        Ok(keys)
+    }
+
+    /// Signals sharing `correlation_id`, in timestamp then sequence order
+    pub fn get_signals_by_correlation(&self, correlation_id: &str) -> Result<Vec<Signal>> {
+        let prefix = signal_correlation_key(correlation_id)?;
+        let mut signals = Vec::new();
+        for item in self.signal_meta_index.scan_prefix(prefix.as_bytes()) {
+            let (_, id_bytes) = item?;
+            let signal_id = EntityId::from_bytes(id_bytes.as_ref().try_into()?);
+            if let Some(signal) = self.get_signal(signal_id)? {
+                signals.push(signal);
+            }
+        }
+        signals.sort_by_key(Signal::ordering_key);
+
+        Ok(signals)
+    }
+
+    /// Find signals whose metadata `key` equals `value`.
+    ///
+    /// `key` must be one of the indexed keys (see
+    /// [`LiminalDB::with_indexed_signal_meta_keys`]). Values are compared by
+    /// their JSON form, so `500` and `"500"` are different values.
+    pub fn scan_signals_by_meta(
+        &self,
+        key: &str,
+        value: &serde_json::Value,
+    ) -> Result<Vec<Signal>> {
+        if !self.indexed_signal_meta_keys.iter().any(|k| k == key) {
+            anyhow::bail!("Signal metadata key '{}' is not indexed", key);
+        }
+
+        let prefix = signal_meta_key(key, value)?;
+        let mut signals = Vec::new();
+        for item in self.signal_meta_index.scan_prefix(prefix.as_bytes()) {
+            let (_, id_bytes) = item?;
+            let signal_id = EntityId::from_bytes(id_bytes.as_ref().try_into()?);
+            if let Some(signal) = self.get_signal(signal_id)? {
+                signals.push(signal);
+            }
+        }
+
+        Ok(signals)
+    }
+
+    /// Store a resonance entity
+    pub fn put_resonance(&self, resonance: &Resonance) -> Result<()> {
+        let stale = match self.get_entity::<Resonance>(resonance.id)? {
+            Some(previous) => self.resonance_test_keys(&previous)?,
+            None => Vec::new(),
+        };
+        self.put_entity(EntityType::Resonance, resonance.id, resonance)?;
+        let keys = self.index_resonance(resonance)?;
+        for key in stale.iter().filter(|key| !keys.contains(key)) {
+            self.resonance_test_index.remove(key.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn remove_resonance(&self, resonance: &Resonance) -> Result<()> {
+        for key in self.resonance_test_keys(resonance)? {
+            self.resonance_test_index.remove(key.as_bytes())?;
+        }
+        self.remove_entity(EntityType::Resonance, resonance.id)
+    }
+
+    /// Index `resonance` under the tests it affects, returning the keys
+    fn index_resonance(&self, resonance: &Resonance) -> Result<Vec<String>> {
+        let keys = self.resonance_test_keys(resonance)?;
+        for key in &keys {
+            self.resonance_test_index
+                .insert(key.as_bytes(), &resonance.id.to_bytes())?;
+        }
+        Ok(keys)
+    }
+
+    /// Index keys of `resonance`, one per name and suite of its affected
+    /// tests; ids of no stored test are skipped
+    fn resonance_test_keys(&self, resonance: &Resonance) -> Result<Vec<String>> {
+        let mut keys = BTreeSet::new();
+        for test_id in &resonance.affected_tests {
+            let type_key = entity_type_key(EntityType::Test, *test_id);
+            if !self.entity_type_index.contains_key(type_key.as_bytes())? {
+                continue;
+            }
+            if let Some(test) = self.get_entity::<Test>(*test_id)? {
+                keys.insert(format!(
+                    "{}{}",
+                    resonance_test_prefix(&test.name, &test.suite),
+                    resonance.id
+                ));
+            }
+        }
+        Ok(keys.into_iter().collect())
+    }
+
+    /// Mark a resonance as resolved by `detector`: its pattern no longer
+    /// holds, so it stops flagging the tests it affects from now on
+    pub fn resolve_resonance(&self, id: EntityId, detector: &str) -> Result<()> {
+        self.put_fact(&Fact::new(
+            id,
+            Attribute::ResonanceResolved,
+            serde_json::json!(detector),
+        ))
+    }
+
+    /// When a resonance was first marked resolved, `None` while it is open
+    pub fn resonance_resolved_at(&self, id: EntityId) -> Result<Option<DateTime<Utc>>> {
+        Ok(self
+            .without_retracted(self.scan_facts_by_entities(&[id])?, Utc::now())?
+            .into_iter()
+            .filter(|f| f.attribute == Attribute::ResonanceResolved)
+            .map(|f| f.time.tx_time)
+            .min())
+    }
+
+    /// Unresolved resonances affecting any execution of `name` in `suite`
+    pub fn open_resonances_for_test(&self, name: &str, suite: &str) -> Result<Vec<Resonance>> {
+        let prefix = resonance_test_prefix(name, suite);
+        let mut open = Vec::new();
+        for item in self.resonance_test_index.scan_prefix(prefix.as_bytes()) {
+            let (_, id_bytes) = item?;
+            let id = EntityId::from_bytes(id_bytes.as_ref().try_into()?);
+            let Some(resonance) = self.get_entity::<Resonance>(id)? else {
+                continue;
+            };
+            if self.resonance_resolved_at(id)?.is_none() {
+                open.push(resonance);
+            }
+        }
+        Ok(open)
+    }
+
+    /// The `limit` highest-scoring unresolved resonances, highest first;
+    /// equal scores are ordered by resonance id
+    pub fn open_resonances_by_score(&self, limit: usize) -> Result<Vec<Resonance>> {
+        let mut open = Vec::new();
+        for id in self.get_entities_by_type(EntityType::Resonance)? {
+            let Some(resonance) = self.get_entity::<Resonance>(id)? else {
+                continue;
+            };
+            if self.resonance_resolved_at(id)?.is_none() {
+                open.push(resonance);
+            }
+        }
+
+        let by_score = |a: &Resonance, b: &Resonance| {
+            b.pattern
+                .score
+                .total_cmp(&a.pattern.score)
+                .then(a.id.cmp(&b.id))
+        };
+        // Only the kept entries need sorting
+        if limit < open.len() {
+            open.select_nth_unstable_by(limit, by_score);
+            open.truncate(limit);
+        }
+        open.sort_by(by_score);
+        Ok(open)
+    }
+
+    /// Recompute flake resonance for every test with `detector`, e.g. after
+    /// changing its window or threshold.
+    ///
+    /// Each test (name + suite) is scored over its full history. A flagged
+    /// test gets its existing flake resonance updated (or a new one), a test
+    /// no longer flagged loses it. Resonances of other detectors are left
+    /// alone. Returns how many resonance records were created, updated or
+    /// removed.
+    pub fn rescore_all_resonance(&self, detector: &FlakeDetector) -> Result<usize> {
+        let mut tests: BTreeMap<(String, String), HashSet<EntityId>> = BTreeMap::new();
+        for id in self.get_entities_by_type(EntityType::Test)? {
+            if let Some(test) = self.get_entity::<Test>(id)? {
+                tests.entry((test.name, test.suite)).or_default().insert(id);
+            }
+        }
+
+        let mut existing = Vec::new();
+        for id in self.get_entities_by_type(EntityType::Resonance)? {
+            if let Some(resonance) = self.get_entity::<Resonance>(id)? {
+                if FlakeDetector::produced(&resonance) {
+                    existing.push(resonance);
+                }
+            }
+        }
+
+        let mut changed = 0;
+        for ((name, suite), ids) in &tests {
+            let (mut current, rest): (Vec<Resonance>, Vec<Resonance>) = existing
+                .into_iter()
+                .partition(|r| r.affected_tests.iter().any(|id| ids.contains(id)));
+            existing = rest;
+            current.sort_by_key(|r| r.id);
+
+            let history = self.get_test_history(name, suite, usize::MAX)?;
+            let mut found = detector.detect(&history).into_iter();
+            let mut current = current.into_iter();
+            match (found.next(), current.next()) {
+                (Some(fresh), Some(mut kept)) => {
+                    if kept.pattern.score != fresh.pattern.score
+                        || kept.affected_tests != fresh.affected_tests
+                    {
+                        kept.pattern.score = fresh.pattern.score;
+                        kept.pattern.description = fresh.pattern.description;
+                        kept.pattern.last_seen = fresh.pattern.last_seen;
+                        kept.affected_tests = fresh.affected_tests;
+                        self.put_resonance(&kept)?;
+                        changed += 1;
+                    }
+                }
+                (Some(fresh), None) => {
+                    self.put_resonance(&fresh)?;
+                    changed += 1;
+                }
+                (None, Some(stale)) => {
+                    self.remove_resonance(&stale)?;
+                    changed += 1;
+                }
+                (None, None) => {}
+            }
+            // Earlier ingests may have left duplicates behind
+            for duplicate in current {
+                self.remove_resonance(&duplicate)?;
+                changed += 1;
+            }
+        }
+
+        info!("Rescored flake resonance: {} records changed", changed);
+        Ok(changed)
+    }
+
+    /// Resonances ordered by pattern score, highest first.
+    ///
+    /// `suite` keeps resonances affecting at least one test of that suite and
+    /// `min_score` drops patterns scoring below it. At most `limit` entries
+    /// are returned; equal scores are ordered by resonance id.
+    pub fn get_resonance_scores(
+        &self,
+        suite: Option<&str>,
+        min_score: Option<f64>,
+        limit: usize,
+    ) -> Result<Vec<Resonance>> {
+        let mut resonances = Vec::new();
+        for id in self.get_entities_by_type(EntityType::Resonance)? {
+            let Some(resonance) = self.get_entity::<Resonance>(id)? else {
+                continue;
+            };
+            if min_score.is_some_and(|min| resonance.pattern.score < min) {
+                continue;
+            }
+            if let Some(suite) = suite {
+                let mut in_suite = false;
+                for test_id in &resonance.affected_tests {
+                    if self
+                        .get_entity::<Test>(*test_id)?
+                        .is_some_and(|t| t.suite == suite)
+                    {
+                        in_suite = true;
+                        break;
+                    }
+                }
+                if !in_suite {
+                    continue;
+                }
+            }
+            resonances.push(resonance);
+        }
+
+        resonances.sort_by(|a, b| {
+            b.pattern
+                .score
+                .total_cmp(&a.pattern.score)
+                .then(a.id.cmp(&b.id))
+        });
+        resonances.truncate(limit);
+        Ok(resonances)
+    }
+
+    /// Get a signal by ID (signals are stored as JSON, see `put_signal`)
+    pub fn get_signal(&self, id: EntityId) -> Result<Option<Signal>> {
+        match self.entities.get(id.to_bytes())? {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Generic entity storage
+    fn put_entity<T: Serialize>(
+        &self,
+        entity_type: EntityType,
+        id: EntityId,
+        entity: &T,
+    ) -> Result<()> {
+        self.put_entity_bytes(entity_type, id, bincode::serialize(entity)?)
+    }
+
+    fn put_entity_bytes(
+        &self,
+        entity_type: EntityType,
+        id: EntityId,
+        value: Vec<u8>,
+    ) -> Result<()> {
+        let key = id.to_bytes();
+
+        self.entities.insert(key, value)?;
+        self.entity_types
+            .insert(key, entity_type_to_str(entity_type).as_bytes())?;
+        self.index_entity_type(entity_type, id)?;
+
+        debug!("Stored entity: type={:?}, id={}", entity_type, id);
+        Ok(())
+    }
+
+    fn remove_entity(&self, entity_type: EntityType, id: EntityId) -> Result<()> {
+        self.entities.remove(id.to_bytes())?;
+        self.entity_types.remove(id.to_bytes())?;
+        self.entity_type_index
+            .remove(entity_type_key(entity_type, id).as_bytes())?;
+        Ok(())
+    }
+
+    /// Type recorded for entity `id` when it was stored
+    fn stored_entity_type(&self, id: EntityId) -> Result<Option<EntityType>> {
+        let Some(value) = self.entity_types.get(id.to_bytes())? else {
+            return Ok(None);
+        };
+        let name = std::str::from_utf8(&value)?;
+        Ok(Some(str_to_entity_type(name).with_context(|| {
+            format!("Unknown type '{}' of entity {}", name, id)
+        })?))
+    }
+
+    fn index_run(&self, run: &Run) -> Result<()> {
+        self.build_run_index
+            .insert(build_run_key(run).as_bytes(), &run.id.to_bytes())?;
+        Ok(())
+    }
+
+    fn index_entity_type(&self, entity_type: EntityType, id: EntityId) -> Result<()> {
+        self.entity_type_index
+            .insert(entity_type_key(entity_type, id).as_bytes(), &id.to_bytes())?;
+        Ok(())
+    }
+
+    /// Store a run with its tests, signals and artifacts atomically.
+    ///
+    /// Every entity and index entry is written in one sled transaction, so
+    /// either the whole batch becomes visible or none of it does. Signals and
+    /// artifacts must already point at their tests. Signals get consecutive
+    /// sequence numbers of the run, in slice order.
+    pub fn put_run_batch(
+        &self,
+        run: &Run,
+        tests: &[Test],
+        signals: &[Signal],
+        artifacts: &[Artifact],
+    ) -> Result<()> {
+        let mut entities = sled::Batch::default();
+        let mut entity_types = sled::Batch::default();
+        let mut types = sled::Batch::default();
+        let mut names = sled::Batch::default();
+        let mut history = sled::Batch::default();
+        let mut signal_meta = sled::Batch::default();
+        let mut build_runs = sled::Batch::default();
+        let mut run_entities = sled::Batch::default();
+        let mut facts = sled::Batch::default();
+        let mut valid_times = sled::Batch::default();
+        let mut tx_times = sled::Batch::default();
+
+        // Derived facts go into the same transaction as their entities
+        let mut derived = self.entity_facts(run, || self.get_entity(run.id), run_facts)?;
+        for test in tests {
+            derived.extend(self.entity_facts(test, || self.get_entity(test.id), test_facts)?);
+        }
+        for signal in signals {
+            derived.extend(self.entity_facts(
+                signal,
+                || self.get_signal(signal.id),
+                signal_facts,
+            )?);
+        }
+        for fact in &derived {
+            let fact_id = EntityId::new();
+            let (vt_key, tx_key) = fact_time_keys(fact_id, fact);
+            facts.insert(&fact_id.to_bytes(), serde_json::to_vec(fact)?);
+            valid_times.insert(vt_key.as_bytes(), &fact_id.to_bytes());
+            tx_times.insert(tx_key.as_bytes(), &fact_id.to_bytes());
+        }
+
+        let mut stage = |entity_type: EntityType, id: EntityId, value: Vec<u8>| {
+            entities.insert(&id.to_bytes(), value);
+            entity_types.insert(&id.to_bytes(), entity_type_to_str(entity_type).as_bytes());
+            types.insert(entity_type_key(entity_type, id).as_bytes(), &id.to_bytes());
+        };
+
+        stage(EntityType::Run, run.id, bincode::serialize(run)?);
+        build_runs.insert(build_run_key(run).as_bytes(), &run.id.to_bytes());
+        for test in tests {
+            stage(EntityType::Test, test.id, bincode::serialize(test)?);
+            names.insert(test_name_key(test).as_bytes(), &test.id.to_bytes());
+            history.insert(test_history_key(test).as_bytes(), &test.id.to_bytes());
+            run_entities.insert(run_test_key(test).as_bytes(), &test.id.to_bytes());
+        }
+        let first_sequence = match signals.first() {
+            Some(_) => self.reserve_signal_sequences(run.id, signals.len() as u64)?,
+            None => 0,
+        };
+        for (sequence, signal) in (first_sequence..).zip(signals) {
+            let signal = Signal {
+                sequence,
+                ..signal.clone()
+            };
+            stage(EntityType::Signal, signal.id, serde_json::to_vec(&signal)?);
+            for index_key in self.signal_meta_index_keys(&signal)? {
+                signal_meta.insert(index_key.as_bytes(), &signal.id.to_bytes());
+            }
+            run_entities.insert(run_signal_key(&signal).as_bytes(), &signal.id.to_bytes());
+        }
+        for artifact in artifacts {
+            stage(
+                EntityType::Artifact,
+                artifact.id,
+                bincode::serialize(artifact)?,
+            );
+        }
+
+        (
+            &self.entities,
+            &self.entity_types,
+            &self.entity_type_index,
+            &self.test_name_index,
+            &self.test_history_index,
+            &self.signal_meta_index,
+            &self.build_run_index,
+            &self.run_entity_index,
+            &self.facts,
+            &self.valid_time_index,
+            &self.tx_time_index,
+        )
+            .transaction(
+                |(
+                    entities_tx,
+                    entity_types_tx,
+                    types_tx,
+                    names_tx,
+                    history_tx,
+                    meta_tx,
+                    build_runs_tx,
+                    run_entities_tx,
+                    facts_tx,
+                    vt_tx,
+                    tx_tx,
+                )| {
+                    entities_tx.apply_batch(&entities)?;
+                    entity_types_tx.apply_batch(&entity_types)?;
+                    types_tx.apply_batch(&types)?;
+                    names_tx.apply_batch(&names)?;
+                    history_tx.apply_batch(&history)?;
+                    meta_tx.apply_batch(&signal_meta)?;
+                    build_runs_tx.apply_batch(&build_runs)?;
+                    run_entities_tx.apply_batch(&run_entities)?;
+                    facts_tx.apply_batch(&facts)?;
+                    vt_tx.apply_batch(&valid_times)?;
+                    tx_tx.apply_batch(&tx_times)?;
+                    Ok::<_, ConflictableTransactionError>(())
+                },
+            )
+            .map_err(|e| anyhow::anyhow!("Run batch transaction failed: {:?}", e))?;
+        if !derived.is_empty() {
+            if let Some(cache) = &self.query_cache {
+                cache.invalidate();
+            }
+        }
+        self.bump_run_version(run.id, None)?;
+        if run.ended_at.is_some() {
+            crate::report::cache_run_summary(self, run.id)?;
+        } else {
+            self.invalidate_run_summary(run.id)?;
+        }
+
+        info!(
+            "Stored run batch {}: {} tests, {} signals, {} artifacts",
+            run.id,
+            tests.len(),
+            signals.len(),
+            artifacts.len()
+        );
+        Ok(())
+    }
+
+    /// The most recently started run of every distinct plan, ordered by plan
+    /// name. Runs starting at the same instant are told apart by id.
+    pub fn latest_run_per_plan(&self) -> Result<Vec<Run>> {
+        let mut latest: std::collections::BTreeMap<String, Run> = std::collections::BTreeMap::new();
+        for id in self.get_entities_by_type(EntityType::Run)? {
+            let Some(run) = self.get_entity::<Run>(id)? else {
+                continue;
+            };
+            let newer = latest
+                .get(&run.plan_name)
+                .is_none_or(|current| (run.started_at, run.id) > (current.started_at, current.id));
+            if newer {
+                latest.insert(run.plan_name.clone(), run);
+            }
+        }
+        Ok(latest.into_values().collect())
+    }
+
+    /// Delete a run and everything attached to it: its tests, signals and
+    /// artifacts, every fact about any of them, their index entries and the
+    /// run's signal sequence counter.
+    ///
+    /// All removals happen in one sled transaction. Resonance records are
+    /// kept, since they span runs. Deleting an unknown run removes nothing
+    /// and returns zero counts.
+    pub fn delete_run_cascade(&self, run_id: EntityId) -> Result<DeleteCounts> {
+        let mut counts = DeleteCounts::default();
+        let mut entities = sled::Batch::default();
+        let mut types = sled::Batch::default();
+        let mut names = sled::Batch::default();
+        let mut history = sled::Batch::default();
+        let mut signal_meta = sled::Batch::default();
+        let mut build_runs = sled::Batch::default();
+        let mut entity_types = sled::Batch::default();
+        let mut doomed = std::collections::HashSet::new();
+
+        let mut unstage = |entity_type: EntityType, id: EntityId| {
+            entities.remove(&id.to_bytes());
+            entity_types.remove(&id.to_bytes());
+            types.remove(entity_type_key(entity_type, id).as_bytes());
+            doomed.insert(id);
+        };
+
+        if let Some(run) = self.get_entity::<Run>(run_id)? {
+            unstage(EntityType::Run, run_id);
+            build_runs.remove(build_run_key(&run).as_bytes());
+            counts.runs = 1;
+        }
+        for id in self.get_entities_by_type(EntityType::Test)? {
+            let Some(test) = self.get_entity::<Test>(id)? else {
+                continue;
+            };
+            if test.run_id == run_id {
+                unstage(EntityType::Test, id);
+                names.remove(test_name_key(&test).as_bytes());
+                history.remove(test_history_key(&test).as_bytes());
+                counts.tests += 1;
+            }
+        }
+        for id in self.get_entities_by_type(EntityType::Signal)? {
+            let Some(signal) = self.get_signal(id)? else {
+                continue;
+            };
+            if signal.run_id == run_id {
+                unstage(EntityType::Signal, id);
+                for index_key in self.signal_meta_index_keys(&signal)? {
+                    signal_meta.remove(index_key.as_bytes());
+                }
+                counts.signals += 1;
+            }
+        }
+        for id in self.get_entities_by_type(EntityType::Artifact)? {
+            let Some(artifact) = self.get_entity::<Artifact>(id)? else {
+                continue;
+            };
+            if artifact.run_id == run_id {
+                unstage(EntityType::Artifact, id);
+                counts.artifacts += 1;
+            }
+        }
+        // Progress of tests that never finished
+        doomed.extend(self.progress_tests_of_run(run_id)?);
+
+        let mut facts = sled::Batch::default();
+        let mut valid_times = sled::Batch::default();
+        let mut tx_times = sled::Batch::default();
+        let mut owners = sled::Batch::default();
+        let mut retractions = sled::Batch::default();
+        for item in self.facts.iter() {
+            let (key, value) = item?;
+            let fact: Fact = serde_json::from_slice(&value)?;
+            if !doomed.contains(&fact.entity_id) {
+                continue;
+            }
+            let fact_id = EntityId::from_bytes(key.as_ref().try_into()?);
+            let (vt_key, tx_key) = fact_time_keys(fact_id, &fact);
+            facts.remove(key);
+            valid_times.remove(vt_key.as_bytes());
+            tx_times.remove(tx_key.as_bytes());
+            if let Some(owner_key) = fact_owner_key(&fact)? {
+                owners.remove(owner_key.as_bytes());
+            }
+            if fact.retracted {
+                retractions.remove(retraction_key(&fact.key(), fact_id)?.as_bytes());
+            }
+            counts.facts += 1;
+        }
+
+        (
+            &self.entities,
+            &self.entity_type_index,
+            &self.test_name_index,
+            &self.test_history_index,
+            &self.signal_meta_index,
+            &self.facts,
+            &self.valid_time_index,
+            &self.tx_time_index,
+            &self.test_owner_index,
+            &self.retraction_index,
+            &self.build_run_index,
+            &self.signal_sequences,
+            &self.run_summaries,
+            &self.run_versions,
+        )
+            .transaction(
+                |(
+                    entities_tx,
+                    types_tx,
+                    names_tx,
+                    history_tx,
+                    meta_tx,
+                    facts_tx,
+                    vt_tx,
+                    tx_tx,
+                    owners_tx,
+                    retractions_tx,
+                    build_runs_tx,
+                    sequences_tx,
+                    summaries_tx,
+                    versions_tx,
+                )| {
+                    entities_tx.apply_batch(&entities)?;
+                    types_tx.apply_batch(&types)?;
+                    names_tx.apply_batch(&names)?;
+                    history_tx.apply_batch(&history)?;
+                    meta_tx.apply_batch(&signal_meta)?;
+                    facts_tx.apply_batch(&facts)?;
+                    vt_tx.apply_batch(&valid_times)?;
+                    tx_tx.apply_batch(&tx_times)?;
+                    owners_tx.apply_batch(&owners)?;
+                    retractions_tx.apply_batch(&retractions)?;
+                    build_runs_tx.apply_batch(&build_runs)?;
+                    sequences_tx.remove(&run_id.to_bytes())?;
+                    summaries_tx.remove(&run_id.to_bytes())?;
+                    versions_tx.remove(&run_id.to_bytes())?;
+                    Ok::<_, ConflictableTransactionError>(())
+                },
+            )
+            .map_err(|e| anyhow::anyhow!("Run delete transaction failed: {:?}", e))?;
+        // Outside the transaction, which takes no more trees; an entry left
+        // behind names a deleted entity and is skipped on read
+        self.entity_types.apply_batch(entity_types)?;
+        for prefix in [
+            run_test_prefix(run_id),
+            run_signal_prefix(run_id),
+            run_progress_prefix(run_id),
+        ] {
+            for key in self.run_entity_index.scan_prefix(prefix).keys() {
+                self.run_entity_index.remove(key?)?;
+            }
+        }
+        if let Some(cache) = &self.query_cache {
+            cache.invalidate();
+        }
+
+        info!(
+            "Deleted run {}: {} tests, {} signals, {} artifacts, {} facts",
+            run_id, counts.tests, counts.signals, counts.artifacts, counts.facts
+        );
+        Ok(counts)
+    }
+
+    /// Clear and rebuild every secondary index from the primary `facts` and
+    /// `entities` trees.
+    ///
+    /// Use after an index tree was lost or corrupted, or to backfill an index
+    /// added after data was written (e.g. newly configured signal metadata
+    /// keys). Each entity is decoded as the type recorded for it when it was
+    /// stored.
+    pub fn rebuild_indexes(&self) -> Result<()> {
+        for index in [
+            &self.valid_time_index,
+            &self.tx_time_index,
+            &self.entity_type_index,
+            &self.test_name_index,
+            &self.test_history_index,
+            &self.signal_meta_index,
+            &self.test_owner_index,
+            &self.retraction_index,
+            &self.build_run_index,
+            &self.resonance_test_index,
+            &self.run_entity_index,
+        ] {
+            index.clear()?;
+        }
+
+        let mut fact_count = 0;
+        for item in self.facts.iter() {
+            let (key, value) = item?;
+            let fact_id = EntityId::from_bytes(key.as_ref().try_into()?);
+            let fact: Fact = serde_json::from_slice(&value)?;
+            self.index_fact(fact_id, &fact)?;
+            fact_count += 1;
+        }
+
+        let mut entity_count = 0;
+        let mut resonances = Vec::new();
+        for item in self.entities.iter() {
+            let (key, value) = item?;
+            let id = EntityId::from_bytes(key.as_ref().try_into()?);
+            let Some(entity_type) = self.stored_entity_type(id)? else {
+                warn!("Skipping entity {} of unknown type while reindexing", id);
+                continue;
+            };
+
+            self.index_entity_type(entity_type, id)?;
+            match entity_type {
+                EntityType::Run => self.index_run(&decode_entity(&value)?)?,
+                EntityType::Test => self.index_test(&decode_entity(&value)?)?,
+                EntityType::Signal => self.index_signal(&serde_json::from_slice(&value)?)?,
+                EntityType::Resonance => resonances.push(decode_entity::<Resonance>(&value)?),
+                _ => {}
+            }
+            entity_count += 1;
+        }
+        // Once the types of their affected tests are indexed again
+        for resonance in &resonances {
+            self.index_resonance(resonance)?;
+        }
+
+        info!(
+            "Rebuilt indexes: {} facts, {} entities",
+            fact_count, entity_count
+        );
+        Ok(())
+    }
+
+    /// Store a fact, rejecting custom attributes that fail
+    /// [`Attribute::validate`]
     pub fn put_fact(&self, fact: &Fact) -> Result<()> {
+        self.check_fact(fact)?;
+
         let fact_id = EntityId::new();
         let key = fact_id.to_bytes();
         // Use JSON for facts because Fact contains serde_json::Value which bincode can't handle
         let value = serde_json::to_vec(fact)?;
 
-        self.facts.insert(key, value)?;
+        self.facts.insert(key, value)?;
+        self.index_fact(fact_id, fact)?;
+        if let Some(cache) = &self.query_cache {
+            cache.invalidate();
+        }
+        if fact.attribute == Attribute::TestStatus {
+            // A corrected status changes the summary of the test's run
+            if let Some(test) = self.get_entity::<Test>(fact.entity_id).ok().flatten() {
+                self.invalidate_run_summary(test.run_id)?;
+            }
+        }
+
+        debug!(
+            "Stored fact: entity_id={}, attribute={}",
+            fact.entity_id, fact.attribute
+        );
+        Ok(())
+    }
+
+    /// Refuse a fact with an invalid attribute or a value over the size limit
+    fn check_fact(&self, fact: &Fact) -> Result<()> {
+        fact.attribute.validate()?;
+        let size = serde_json::to_vec(&fact.value)?.len();
+        if size > self.max_fact_value_bytes {
+            return Err(DbError::FactValueTooLarge {
+                attribute: fact.attribute.to_string(),
+                size,
+                limit: self.max_fact_value_bytes,
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    fn index_fact(&self, fact_id: EntityId, fact: &Fact) -> Result<()> {
+        let key = fact_id.to_bytes();
+        let (vt_key, tx_key) = fact_time_keys(fact_id, fact);
+
+        // Index by valid_time
+        self.valid_time_index.insert(vt_key.as_bytes(), &key)?;
+
+        // Index by tx_time
+        self.tx_time_index.insert(tx_key.as_bytes(), &key)?;
+
+        if let Some(owner_key) = fact_owner_key(fact)? {
+            self.test_owner_index
+                .insert(owner_key.as_bytes(), &fact.entity_id.to_bytes())?;
+        }
+
+        if fact.retracted {
+            self.retraction_index.insert(
+                retraction_key(&fact.key(), fact_id)?.as_bytes(),
+                serde_json::to_vec(&fact.time.tx_time)?,
+            )?;
+        }
+
+        // A test reports progress before it is stored with its result
+        if fact.attribute == Attribute::TestProgress && !fact.retracted {
+            let progress: TestProgress = serde_json::from_value(fact.value.clone())?;
+            self.run_entity_index.insert(
+                run_progress_key(&progress).as_bytes(),
+                &progress.test_id.to_bytes(),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Retract every fact recorded for `key` up to `as_of`, by writing a
+    /// tombstone valid and recorded at `as_of`.
+    ///
+    /// Nothing is deleted: queries as of an earlier transaction time still see
+    /// the retracted facts, see [`LiminalDB::without_retracted`]. Errors if no
+    /// fact with this key was recorded by `as_of`.
+    pub fn retract_fact(&self, key: &FactKey, as_of: DateTime<Utc>) -> Result<()> {
+        let live = self
+            .scan_facts_by_entities(&[key.entity_id])?
+            .iter()
+            .any(|f| !f.retracted && f.attribute == key.attribute && f.time.tx_time <= as_of);
+        if !live {
+            anyhow::bail!(
+                "No fact {} of entity {} to retract as of {}",
+                key.attribute,
+                key.entity_id,
+                as_of
+            );
+        }
+        self.put_fact(&Fact::tombstone(
+            key.clone(),
+            BiTemporalTime::with_times(as_of, as_of),
+        ))
+    }
+
+    /// Transaction time of the latest tombstone of `key` recorded by `as_of`,
+    /// `None` if it was not retracted then
+    pub fn retracted_at(
+        &self,
+        key: &FactKey,
+        as_of: DateTime<Utc>,
+    ) -> Result<Option<DateTime<Utc>>> {
+        let mut latest = None;
+        for item in self.retraction_index.scan_prefix(retraction_prefix(key)?) {
+            let (_, value) = item?;
+            let tx_time: DateTime<Utc> = serde_json::from_slice(&value)?;
+            if tx_time <= as_of && latest.is_none_or(|latest| tx_time > latest) {
+                latest = Some(tx_time);
+            }
+        }
+        Ok(latest)
+    }
+
+    /// Facts about `entity_ids` as known at transaction time `as_of`:
+    /// recorded by then and not retracted by then, see
+    /// [`LiminalDB::without_retracted`]
+    pub fn facts_as_of(&self, entity_ids: &[EntityId], as_of: DateTime<Utc>) -> Result<Vec<Fact>> {
+        let mut facts = self.scan_facts_by_entities(entity_ids)?;
+        facts.retain(|f| f.time.tx_time <= as_of);
+        self.without_retracted(facts, as_of)
+    }
+
+    /// `facts` as a view at transaction time `as_of` sees them: tombstones
+    /// are dropped, and so is every fact a tombstone recorded by `as_of`
+    /// retracted
+    pub fn without_retracted(&self, facts: Vec<Fact>, as_of: DateTime<Utc>) -> Result<Vec<Fact>> {
+        let mut retractions: HashMap<FactKey, Option<DateTime<Utc>>> = HashMap::new();
+        let mut live = Vec::with_capacity(facts.len());
+        for fact in facts {
+            if fact.retracted {
+                continue;
+            }
+            let key = fact.key();
+            let retracted_at = match retractions.get(&key) {
+                Some(retracted_at) => *retracted_at,
+                None => {
+                    let retracted_at = self.retracted_at(&key, as_of)?;
+                    retractions.insert(key, retracted_at);
+                    retracted_at
+                }
+            };
+            if retracted_at.is_none_or(|at| fact.time.tx_time > at) {
+                live.push(fact);
+            }
+        }
+        Ok(live)
+    }
+
+    /// Store multiple facts in batch
+    pub fn put_fact_batch(&self, batch: &FactBatch) -> Result<()> {
+        for fact in &batch.facts {
+            self.put_fact(fact)?;
+        }
+        info!("Stored fact batch: {} facts", batch.facts.len());
+        Ok(())
+    }
+
+    /// Get entity by ID
+    pub fn get_entity<T: for<'de> Deserialize<'de>>(&self, id: EntityId) -> Result<Option<T>> {
+        let key = id.to_bytes();
+        match self.entities.get(key)? {
+            Some(bytes) => {
+                let entity = decode_entity(&bytes)?;
+                Ok(Some(entity))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Get all entities of a specific type
+    pub fn get_entities_by_type(&self, entity_type: EntityType) -> Result<Vec<EntityId>> {
+        let prefix = format!("{}:", entity_type_to_str(entity_type));
+        let mut ids = Vec::new();
+
+        for item in self.entity_type_index.scan_prefix(prefix.as_bytes()) {
+            let (key, _) = item?;
+            let key_str = String::from_utf8_lossy(&key);
+            if let Some(id_str) = key_str.split(':').nth(1) {
+                if let Ok(id) = EntityId::from_string(id_str) {
+                    ids.push(id);
+                }
+            }
+        }
+
+        Ok(ids)
+    }
+
+    /// Flush all pending writes
+    pub fn flush(&self) -> Result<()> {
+        self.db.flush()?;
+        Ok(())
+    }
+
+    /// Scan all facts (unfiltered)
+    pub fn scan_facts(&self) -> Result<Vec<Fact>> {
+        let mut facts = Vec::new();
+        let mut after = None;
+        loop {
+            let page = self.scan_facts_page(after, FACT_PAGE_SIZE)?;
+            facts.extend(page.facts);
+            match page.next {
+                Some(next) => after = Some(next),
+                None => return Ok(facts),
+            }
+        }
+    }
+
+    /// Read one page of facts in key order, starting after the `after` cursor.
+    ///
+    /// Each call opens a fresh iterator, so processing a large tree page by
+    /// page never pins a single long-lived snapshot. Pass the returned
+    /// `next` cursor to continue; it is `None` once the tree is exhausted.
+    pub fn scan_facts_page(&self, after: Option<EntityId>, limit: usize) -> Result<FactPage> {
+        use std::ops::Bound;
+
+        let start = match after {
+            Some(cursor) => Bound::Excluded(cursor.to_bytes().to_vec()),
+            None => Bound::Unbounded,
+        };
+
+        let mut facts = Vec::new();
+        let mut last_key = None;
+        for item in self
+            .facts
+            .range::<Vec<u8>, _>((start, Bound::Unbounded))
+            .take(limit)
+        {
+            let (key, value) = item?;
+            facts.push(serde_json::from_slice(&value)?);
+            last_key = Some(EntityId::from_bytes(key.as_ref().try_into()?));
+        }
+
+        let next = if facts.len() == limit { last_key } else { None };
+        Ok(FactPage { facts, next })
+    }
+
+    /// Scan facts for specific entities
+    pub fn scan_facts_by_entities(&self, entity_ids: &[EntityId]) -> Result<Vec<Fact>> {
+        let mut facts = Vec::new();
+        let mut after = None;
+        loop {
+            let page = self.scan_facts_page(after, FACT_PAGE_SIZE)?;
+            facts.extend(
+                page.facts
+                    .into_iter()
+                    .filter(|f| entity_ids.contains(&f.entity_id)),
+            );
+            match page.next {
+                Some(next) => after = Some(next),
+                None => return Ok(facts),
+            }
+        }
+    }
+
+    /// Scan facts within valid_time range
+    pub fn scan_facts_by_valid_time(
+        &self,
+        start_ms: i64,
+        end_ms: Option<i64>,
+    ) -> Result<Vec<Fact>> {
+        let mut facts = Vec::new();
+
+        // Scan all items in the valid_time_index and filter by range
+        for item in self.valid_time_index.iter() {
+            let (key, fact_key) = item?;
+            let key_str = String::from_utf8_lossy(&key);
+
+            // Parse timestamp from key: "{timestamp}:{entity_id}:{fact_id}"
+            if let Some(ts_str) = key_str.split(':').next() {
+                if let Ok(ts) = ts_str.parse::<i64>() {
+                    // Check if timestamp is in range
+                    #[allow(clippy::unnecessary_map_or)]
+                    #[allow(clippy::needless_bool)]
+                    let in_range = if ts >= start_ms && end_ms.map_or(true, |end| ts <= end) {
+                        true
+                    } else {
+                        false
+                    };
+
+                    if in_range {
+                        // Get the actual fact
+                        if let Some(fact_bytes) = self.facts.get(&fact_key)? {
+                            let fact: Fact = serde_json::from_slice(&fact_bytes)?;
+                            facts.push(fact);
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(facts)
+    }
+}
+
+/// Keys of a fact in the valid_time and tx_time indexes:
+/// `{timestamp}:{entity_id}:{fact_id}`
+fn fact_time_keys(fact_id: EntityId, fact: &Fact) -> (String, String) {
+    let key =
+        |at: DateTime<Utc>| format!("{}:{}:{}", at.timestamp_millis(), fact.entity_id, fact_id);
+    (key(fact.time.valid_time), key(fact.time.tx_time))
+}
+
+fn baseline_key(name: &str, suite: &str) -> String {
+    format!("{}:{}", name, suite)
+}
+
+/// Signal types are named as in their JSON form
+fn latency_baseline_key(name: &str, suite: &str, signal_type: SignalType) -> Result<String> {
+    Ok(format!(
+        "{}:{}",
+        baseline_key(name, suite),
+        serde_json::to_value(signal_type)?
+            .as_str()
+            .unwrap_or_default()
+    ))
+}
+
+fn entity_type_key(entity_type: EntityType, id: EntityId) -> String {
+    format!("{}:{}", entity_type_to_str(entity_type), id)
+}
+
+fn test_name_key(test: &Test) -> String {
+    format!("idx:test_name:{}:{}", test.run_id, test.name)
+}
+
+/// Prefix of the resonance index keys of the test `name` in `suite`
+fn resonance_test_prefix(name: &str, suite: &str) -> String {
+    format!("idx:resonance:{}:{}:", name, suite)
+}
+
+fn test_history_key(test: &Test) -> String {
+    format!(
+        "idx:history:{}:{}:{}",
+        test.name,
+        test.suite,
+        test.started_at.timestamp_millis()
+    )
+}
+
+/// Prefix of the build index keys of the runs of `build_id`
+fn build_run_prefix(build_id: EntityId) -> String {
+    format!("idx:build_run:{}:", build_id)
+}
+
+fn build_run_key(run: &Run) -> String {
+    format!("{}{}", build_run_prefix(run.build_id), run.id)
+}
+
+/// Prefix of the run index keys of the tests of `run_id`
+fn run_test_prefix(run_id: EntityId) -> String {
+    format!("idx:run_test:{}:", run_id)
+}
+
+fn run_test_key(test: &Test) -> String {
+    format!("{}{}", run_test_prefix(test.run_id), test.id)
+}
+
+fn run_progress_prefix(run_id: EntityId) -> String {
+    format!("idx:run_progress:{}:", run_id)
+}
+
+fn run_progress_key(progress: &TestProgress) -> String {
+    format!(
+        "{}{}",
+        run_progress_prefix(progress.run_id),
+        progress.test_id
+    )
+}
+
+/// Prefix of the run index keys of the signals of `run_id`, which end in the
+/// zero-padded sequence number so they sort in sequence order
+fn run_signal_prefix(run_id: EntityId) -> String {
+    format!("idx:run_signal:{}:", run_id)
+}
+
+fn run_signal_key(signal: &Signal) -> String {
+    format!(
+        "{}{:020}",
+        run_signal_prefix(signal.run_id),
+        signal.sequence
+    )
+}
+
+/// Prefix of a signal metadata index key: `idx:signal_meta:{key}:{json value}:`
+fn signal_meta_key(key: &str, value: &serde_json::Value) -> Result<String> {
+    Ok(format!(
+        "idx:signal_meta:{}:{}:",
+        key,
+        serde_json::to_string(value)?
+    ))
+}
+
+/// The id is JSON-quoted so one id is never a prefix of another's key
+fn signal_correlation_key(correlation_id: &str) -> Result<String> {
+    Ok(format!(
+        "idx:signal_correlation:{}:",
+        serde_json::to_string(correlation_id)?
+    ))
+}
+
+/// Facts of a run write, true from the run's start (its end for
+/// `:run/ended_at`)
+fn run_facts(run: &Run) -> Result<Vec<Fact>> {
+    let at = |valid_time| BiTemporalTime {
+        valid_time,
+        tx_time: run.created_at.tx_time,
+    };
+    let mut facts = vec![
+        Fact::with_time(
+            run.id,
+            Attribute::RunStartedAt,
+            serde_json::to_value(run.started_at)?,
+            at(run.started_at),
+        ),
+        Fact::with_time(
+            run.id,
+            Attribute::RunEnv,
+            serde_json::to_value(&run.env)?,
+            at(run.started_at),
+        ),
+    ];
+    if let Some(ended_at) = run.ended_at {
+        facts.push(Fact::with_time(
+            run.id,
+            Attribute::RunEndedAt,
+            serde_json::to_value(ended_at)?,
+            at(ended_at),
+        ));
+    }
+    Ok(facts)
+}
+
+/// Facts of a test write, true from the test's completion so a recorded
+/// status never reads as a correction of itself
+fn test_facts(test: &Test) -> Result<Vec<Fact>> {
+    let at = BiTemporalTime {
+        valid_time: test.completed_at,
+        tx_time: test.created_at.tx_time,
+    };
+    let mut facts = vec![
+        Fact::with_time(
+            test.id,
+            Attribute::TestStatus,
+            serde_json::to_value(test.status)?,
+            at,
+        ),
+        Fact::with_time(
+            test.id,
+            Attribute::TestDuration,
+            serde_json::json!(test.duration_ms),
+            at,
+        ),
+    ];
+    if !test.guidance.is_empty() {
+        facts.push(Fact::with_time(
+            test.id,
+            Attribute::TestGuidance,
+            serde_json::json!(test.guidance),
+            at,
+        ));
+    }
+    if let Some(error) = &test.error {
+        facts.push(Fact::with_time(
+            test.id,
+            Attribute::TestError,
+            serde_json::to_value(error)?,
+            at,
+        ));
+    }
+    Ok(facts)
+}
+
+/// Latency fact of a signal write, for the signal types that have one
+fn signal_facts(signal: &Signal) -> Result<Vec<Fact>> {
+    let attribute = match signal.signal_type {
+        SignalType::API => Attribute::ApiLatency,
+        SignalType::WebSocket => Attribute::WsLatency,
+        SignalType::GRPC => Attribute::GrpcLatency,
+        _ => return Ok(Vec::new()),
+    };
+    let Some(latency_ms) = signal.latency_ms else {
+        return Ok(Vec::new());
+    };
+    Ok(vec![Fact::with_time(
+        signal.id,
+        attribute,
+        serde_json::json!(latency_ms),
+        BiTemporalTime {
+            valid_time: signal.timestamp,
+            tx_time: signal.created_at.tx_time,
+        },
+    )])
+}
+
+/// Whether sled failed to open because another handle holds its file lock
+fn is_lock_error(err: &sled::Error) -> bool {
+    matches!(err, sled::Error::Io(e) if e.to_string().starts_with("could not acquire lock"))
+}
+
+fn decode_run_version(bytes: Option<&[u8]>) -> Result<u64> {
+    match bytes {
+        Some(bytes) => Ok(u64::from_be_bytes(bytes.try_into()?)),
+        None => Ok(0),
+    }
+}
+
+/// Prefix of the owner index keys of `owner`, JSON-quoted like
+/// [`signal_correlation_key`]
+fn test_owner_key(owner: &str) -> Result<String> {
+    Ok(format!("idx:test_owner:{}:", serde_json::to_string(owner)?))
+}
+
+/// Owner index key of a `:test/owner` fact, `None` for other facts
+fn fact_owner_key(fact: &Fact) -> Result<Option<String>> {
+    match (&fact.attribute, fact.value.as_str()) {
+        (Attribute::TestOwner, Some(owner)) => Ok(Some(format!(
+            "{}{}",
+            test_owner_key(owner)?,
+            fact.entity_id
+        ))),
+        _ => Ok(None),
+    }
+}
+
+/// Prefix of the retraction index keys of `key`, with the attribute
+/// JSON-quoted like [`test_owner_key`]
+fn retraction_prefix(key: &FactKey) -> Result<String> {
+    Ok(format!(
+        "idx:retraction:{}:{}:",
+        key.entity_id,
+        serde_json::to_string(&key.attribute)?
+    ))
+}
+
+fn retraction_key(key: &FactKey, fact_id: EntityId) -> Result<String> {
+    Ok(format!("{}{}", retraction_prefix(key)?, fact_id))
+}
+
+fn entity_type_to_str(et: EntityType) -> &'static str {
+    match et {
+        EntityType::System => "system",
+        EntityType::Build => "build",
+        EntityType::Run => "run",
+        EntityType::Test => "test",
+        EntityType::Artifact => "artifact",
+        EntityType::Signal => "signal",
+        EntityType::Resonance => "resonance",
+    }
+}
+
+fn str_to_entity_type(name: &str) -> Option<EntityType> {
+    [
+        EntityType::System,
+        EntityType::Build,
+        EntityType::Run,
+        EntityType::Test,
+        EntityType::Artifact,
+        EntityType::Signal,
+        EntityType::Resonance,
+    ]
+    .into_iter()
+    .find(|et| entity_type_to_str(*et) == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use liminalqa_core::temporal::BiTemporalTime;
+    use tempfile::TempDir;
+
+    /// Open the database at `path` again once a dropped handle's background
+    /// flush has let go of its lock
+    fn reopen(path: &Path) -> Result<LiminalDB> {
+        for _ in 0..50 {
+            match LiminalDB::open(path) {
+                Err(e) if matches!(e.downcast_ref(), Some(DbError::AlreadyOpen { .. })) => {
+                    std::thread::sleep(std::time::Duration::from_millis(20));
+                }
+                result => return result,
+            }
+        }
+        LiminalDB::open(path)
+    }
+
+    #[test]
+    fn test_open_with_custom_cache_capacity() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let config = SledConfig {
+            cache_capacity: 4 * 1024 * 1024,
+            mode: SledMode::HighThroughput,
+            flush_every_ms: None,
+        };
+        let db = LiminalDB::open_with_config(temp_dir.path(), config)?;
+
+        let run = Run {
+            id: EntityId::new(),
+            build_id: EntityId::new(),
+            plan_name: "smoke".to_string(),
+            env: Default::default(),
+            started_at: Utc::now(),
+            ended_at: None,
+            runner_version: "1.0.0".to_string(),
+            liminal_os_version: None,
+            created_at: BiTemporalTime::now(),
+        };
+        db.put_run(&run)?;
+        db.flush()?;
+
+        let stored: Option<Run> = db.get_entity(run.id)?;
+        assert_eq!(stored.map(|r| r.plan_name), Some("smoke".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_locked_database_is_already_open() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+
+        let Err(err) = LiminalDB::open(temp_dir.path()) else {
+            panic!("directory is locked");
+        };
+        assert!(matches!(
+            err.downcast_ref::<DbError>(),
+            Some(DbError::AlreadyOpen { path }) if path == temp_dir.path()
+        ));
+        assert!(err.to_string().contains("already open elsewhere"));
+
+        // The first handle is unaffected
+        db.flush()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_entity_writes_derive_facts_only_when_enabled() -> Result<()> {
+        let write_entities = |db: &LiminalDB| -> Result<(Run, Test, Signal)> {
+            let run = Run {
+                id: EntityId::new(),
+                build_id: EntityId::new(),
+                plan_name: "smoke".to_string(),
+                env: Default::default(),
+                started_at: Utc::now(),
+                ended_at: Some(Utc::now()),
+                runner_version: "1.0.0".to_string(),
+                liminal_os_version: None,
+                created_at: BiTemporalTime::now(),
+            };
+            let test = Test {
+                id: EntityId::new(),
+                run_id: run.id,
+                name: "test_login".to_string(),
+                suite: "auth".to_string(),
+                guidance: String::new(),
+                status: TestStatus::Fail,
+                duration_ms: 420,
+                error: None,
+                started_at: Utc::now(),
+                completed_at: Utc::now(),
+                created_at: BiTemporalTime::now(),
+            };
+            let signal = Signal {
+                id: EntityId::new(),
+                run_id: run.id,
+                test_id: Some(test.id),
+                signal_type: SignalType::API,
+                timestamp: Utc::now(),
+                latency_ms: Some(80),
+                payload_ref: None,
+                metadata: Default::default(),
+                created_at: BiTemporalTime::now(),
+                sequence: 0,
+                correlation_id: None,
+            };
+            db.put_run(&run)?;
+            db.put_test(&test)?;
+            db.put_signal(&signal)?;
+            Ok((run, test, signal))
+        };
+
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+        write_entities(&db)?;
+        assert_eq!(crate::Query::new().execute(&db)?.total, 0);
+
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?.with_entity_facts(true);
+        let (run, test, signal) = write_entities(&db)?;
+
+        let attributes = |id| -> Result<Vec<Attribute>> {
+            let result = crate::Query::new().for_entities(vec![id]).execute(&db)?;
+            let mut attributes: Vec<Attribute> =
+                result.facts.into_iter().map(|f| f.attribute).collect();
+            attributes.sort_by_key(|a| a.to_string());
+            Ok(attributes)
+        };
+        assert_eq!(
+            attributes(run.id)?,
+            [
+                Attribute::RunEndedAt,
+                Attribute::RunEnv,
+                Attribute::RunStartedAt
+            ]
+        );
+        assert_eq!(
+            attributes(test.id)?,
+            [Attribute::TestDuration, Attribute::TestStatus]
+        );
+        assert_eq!(attributes(signal.id)?, [Attribute::ApiLatency]);
+
+        let failed = crate::Query::new()
+            .value_predicate(crate::ValuePredicate::eq(
+                Attribute::TestStatus,
+                serde_json::json!("fail"),
+            ))
+            .execute(&db)?;
+        assert_eq!(failed.facts.len(), 1);
+        assert_eq!(failed.facts[0].entity_id, test.id);
+        // Recording a status is not a correction of it
+        assert!(db
+            .test_status_transitions("test_login", "auth", 10)?
+            .is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_storing_a_run_again_records_only_changed_facts() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?.with_entity_facts(true);
+        let mut run = Run {
+            id: EntityId::new(),
+            build_id: EntityId::new(),
+            plan_name: "smoke".to_string(),
+            env: Default::default(),
+            started_at: Utc::now(),
+            ended_at: None,
+            runner_version: "1.0.0".to_string(),
+            liminal_os_version: None,
+            created_at: BiTemporalTime::now(),
+        };
+        let attributes = |db: &LiminalDB, id| -> Result<Vec<Attribute>> {
+            let mut attributes: Vec<Attribute> = db
+                .scan_facts_by_entities(&[id])?
+                .into_iter()
+                .map(|f| f.attribute)
+                .collect();
+            attributes.sort_by_key(|a| a.to_string());
+            Ok(attributes)
+        };
+
+        db.put_run(&run)?;
+        db.put_run(&run)?;
+        assert_eq!(
+            attributes(&db, run.id)?,
+            [Attribute::RunEnv, Attribute::RunStartedAt]
+        );
+
+        run.ended_at = Some(Utc::now());
+        db.put_run(&run)?;
+        assert_eq!(
+            attributes(&db, run.id)?,
+            [
+                Attribute::RunEndedAt,
+                Attribute::RunEnv,
+                Attribute::RunStartedAt
+            ]
+        );
+
+        // The same holds for a run stored again in a batch
+        db.put_run_batch(&run, &[], &[], &[])?;
+        assert_eq!(attributes(&db, run.id)?.len(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_oversized_entity_fact_refuses_the_write() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?
+            .with_entity_facts(true)
+            .with_max_fact_value_bytes(64);
+        let run = Run {
+            id: EntityId::new(),
+            build_id: EntityId::new(),
+            plan_name: "smoke".to_string(),
+            env: liminalqa_core::types::Environment {
+                extra: [("notes".to_string(), "x".repeat(100))].into(),
+                ..Default::default()
+            },
+            started_at: Utc::now(),
+            ended_at: None,
+            runner_version: "1.0.0".to_string(),
+            liminal_os_version: None,
+            created_at: BiTemporalTime::now(),
+        };
+
+        for result in [db.put_run(&run), db.put_run_batch(&run, &[], &[], &[])] {
+            let err = result.expect_err("the env fact is over the limit");
+            assert!(matches!(
+                err.downcast_ref::<DbError>(),
+                Some(DbError::FactValueTooLarge { .. })
+            ));
+        }
+        assert!(db.get_entity::<Run>(run.id)?.is_none());
+        assert_eq!(db.run_version(run.id)?, 0);
+        assert!(db.scan_facts()?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_batch_facts_are_stored_with_the_batch() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?.with_entity_facts(true);
+        let run = Run {
+            id: EntityId::new(),
+            build_id: EntityId::new(),
+            plan_name: "smoke".to_string(),
+            env: Default::default(),
+            started_at: Utc::now(),
+            ended_at: None,
+            runner_version: "1.0.0".to_string(),
+            liminal_os_version: None,
+            created_at: BiTemporalTime::now(),
+        };
+        let test = Test {
+            id: EntityId::new(),
+            run_id: run.id,
+            name: "test_login".to_string(),
+            suite: "auth".to_string(),
+            guidance: String::new(),
+            status: TestStatus::Fail,
+            duration_ms: 420,
+            error: None,
+            started_at: Utc::now(),
+            completed_at: Utc::now(),
+            created_at: BiTemporalTime::now(),
+        };
+        db.put_run_batch(&run, std::slice::from_ref(&test), &[], &[])?;
+
+        // Facts of the batch are indexed like any other fact
+        let result = crate::Query::new()
+            .for_entities(vec![test.id])
+            .execute(&db)?;
+        let attributes: HashSet<Attribute> =
+            result.facts.into_iter().map(|f| f.attribute).collect();
+        assert_eq!(
+            attributes,
+            HashSet::from([Attribute::TestStatus, Attribute::TestDuration])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_store_and_retrieve_test() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+
+        let test = Test {
+            id: EntityId::new(),
+            run_id: EntityId::new(),
+            name: "test_login".to_string(),
+            suite: "auth".to_string(),
+            guidance: "User should be able to log in with valid credentials".to_string(),
+            status: liminalqa_core::types::TestStatus::Pass,
+            duration_ms: 1234,
+            error: None,
+            started_at: chrono::Utc::now(),
+            completed_at: chrono::Utc::now(),
+            created_at: BiTemporalTime::now(),
+        };
+
+        db.put_test(&test)?;
+
+        let retrieved: Option<Test> = db.get_entity(test.id)?;
+        assert!(retrieved.is_some());
+        // TODO(Phase 4): Refactor error handling
+        #[allow(clippy::disallowed_methods)]
+        {
+            assert_eq!(retrieved.unwrap().name, "test_login");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_structured_error_survives_storage() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+
+        let error = liminalqa_core::types::TestError {
+            stack_trace: Some("at pay.rs:40".to_string()),
+            details: Some(serde_json::json!({"status": 500, "retries": [1, 2]})),
+            ..liminalqa_core::types::TestError::new("HttpError", "upstream failed")
+        };
+        let test = Test {
+            id: EntityId::new(),
+            run_id: EntityId::new(),
+            name: "test_pay".to_string(),
+            suite: "checkout".to_string(),
+            guidance: String::new(),
+            status: liminalqa_core::types::TestStatus::Fail,
+            duration_ms: 80,
+            error: Some(error.clone()),
+            started_at: chrono::Utc::now(),
+            completed_at: chrono::Utc::now(),
+            created_at: BiTemporalTime::now(),
+        };
+        db.put_test(&test)?;
+
+        let stored = db
+            .get_entity::<Test>(test.id)?
+            .expect("test should be stored");
+        assert_eq!(stored.error, Some(error));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lookup_by_name_success() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+
+        let run_id = EntityId::new();
+        let test = Test {
+            id: EntityId::new(),
+            run_id,
+            name: "test_user_login".to_string(),
+            suite: "auth".to_string(),
+            guidance: "User should be able to log in".to_string(),
+            status: liminalqa_core::types::TestStatus::Pass,
+            duration_ms: 100,
+            error: None,
+            started_at: chrono::Utc::now(),
+            completed_at: chrono::Utc::now(),
+            created_at: BiTemporalTime::now(),
+        };
+
+        db.put_test(&test)?;
+
+        // Lookup should succeed
+        let found = db.find_test_by_name(run_id, "test_user_login")?;
+        assert_eq!(found, Some(test.id));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lookup_by_name_not_found() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+
+        let run_id = EntityId::new();
+        let found = db.find_test_by_name(run_id, "nonexistent")?;
+        assert_eq!(found, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lookup_different_runs_same_name() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+
+        let run1 = EntityId::new();
+        let run2 = EntityId::new();
+
+        let test1 = Test {
+            id: EntityId::new(),
+            run_id: run1,
+            name: "shared_name".to_string(),
+            suite: "suite1".to_string(),
+            guidance: "".to_string(),
+            status: liminalqa_core::types::TestStatus::Pass,
+            duration_ms: 100,
+            error: None,
+            started_at: chrono::Utc::now(),
+            completed_at: chrono::Utc::now(),
+            created_at: BiTemporalTime::now(),
+        };
+
+        let test2 = Test {
+            id: EntityId::new(),
+            run_id: run2,
+            name: "shared_name".to_string(),
+            suite: "suite2".to_string(),
+            guidance: "".to_string(),
+            status: liminalqa_core::types::TestStatus::Pass,
+            duration_ms: 200,
+            error: None,
+            started_at: chrono::Utc::now(),
+            completed_at: chrono::Utc::now(),
+            created_at: BiTemporalTime::now(),
+        };
+
+        db.put_test(&test1)?;
+        db.put_test(&test2)?;
+
+        // Same name in different runs should resolve to different test_ids
+        let found1 = db.find_test_by_name(run1, "shared_name")?;
+        let found2 = db.find_test_by_name(run2, "shared_name")?;
+
+        assert_eq!(found1, Some(test1.id));
+        assert_eq!(found2, Some(test2.id));
+        assert_ne!(test1.id, test2.id);
+
+        Ok(())
+    }
+
+    fn make_test(
+        run_id: EntityId,
+        suite: &str,
+        status: liminalqa_core::types::TestStatus,
+        duration_ms: u64,
+    ) -> Test {
+        Test {
+            id: EntityId::new(),
+            run_id,
+            name: format!("test_{}", duration_ms),
+            suite: suite.to_string(),
+            guidance: "".to_string(),
+            status,
+            duration_ms,
+            error: None,
+            started_at: chrono::Utc::now(),
+            completed_at: chrono::Utc::now(),
+            created_at: BiTemporalTime::now(),
+        }
+    }
+
+    #[test]
+    fn test_duration_histogram_buckets() -> Result<()> {
+        use liminalqa_core::types::TestStatus;
+
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+
+        let run = EntityId::new();
+        let other_run = EntityId::new();
+        for duration in [5, 40, 99, 100, 150, 420] {
+            db.put_test(&make_test(run, "api", TestStatus::Pass, duration))?;
+        }
+        // Skipped tests have no duration and must not land in the first bucket
+        db.put_test(&make_test(run, "api", TestStatus::Skip, 0))?;
+        db.put_test(&make_test(run, "ui", TestStatus::Fail, 10))?;
+        db.put_test(&make_test(other_run, "api", TestStatus::Pass, 120))?;
+
+        let histogram = db.duration_histogram(Some("api"), Some(run), 100)?;
+        assert_eq!(histogram, vec![(0, 3), (100, 2), (400, 1)]);
+
+        let all_suites = db.duration_histogram(None, Some(run), 100)?;
+        assert_eq!(all_suites, vec![(0, 4), (100, 2), (400, 1)]);
+
+        let all_runs = db.duration_histogram(Some("api"), None, 100)?;
+        assert_eq!(all_runs, vec![(0, 3), (100, 3), (400, 1)]);
+
+        assert!(db.duration_histogram(None, None, 0).is_err());
+
+        Ok(())
+    }
+
+    fn make_resonance(score: f64, affected_tests: Vec<EntityId>) -> Resonance {
+        Resonance {
+            id: EntityId::new(),
+            detector: "flake".to_string(),
+            pattern: liminalqa_core::types::ResonancePattern {
+                pattern_id: EntityId::new(),
+                description: format!("score {}", score),
+                score,
+                occurrences: 1,
+                first_seen: chrono::Utc::now(),
+                last_seen: chrono::Utc::now(),
+            },
+            affected_tests,
+            root_cause: None,
+            created_at: BiTemporalTime::now(),
+        }
+    }
+
+    #[test]
+    fn test_resonance_scores_filtered_and_sorted() -> Result<()> {
+        use liminalqa_core::types::TestStatus;
+
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+
+        let run = EntityId::new();
+        let api = make_test(run, "api", TestStatus::Fail, 10);
+        let ui = make_test(run, "ui", TestStatus::Fail, 20);
+        db.put_test(&api)?;
+        db.put_test(&ui)?;
+
+        for (score, test) in [
+            (0.3, &api),
+            (0.9, &api),
+            (0.6, &ui),
+            (0.8, &ui),
+            (0.5, &api),
+        ] {
+            db.put_resonance(&make_resonance(score, vec![test.id]))?;
+        }
+
+        let scores = |resonances: Vec<Resonance>| -> Vec<f64> {
+            resonances.iter().map(|r| r.pattern.score).collect()
+        };
+
+        assert_eq!(
+            scores(db.get_resonance_scores(None, None, 100)?),
+            vec![0.9, 0.8, 0.6, 0.5, 0.3]
+        );
+        assert_eq!(
+            scores(db.get_resonance_scores(Some("api"), None, 100)?),
+            vec![0.9, 0.5, 0.3]
+        );
+        assert_eq!(
+            scores(db.get_resonance_scores(None, Some(0.6), 100)?),
+            vec![0.9, 0.8, 0.6]
+        );
+        assert_eq!(
+            scores(db.get_resonance_scores(Some("ui"), Some(0.7), 100)?),
+            vec![0.8]
+        );
+        assert_eq!(
+            scores(db.get_resonance_scores(None, None, 2)?),
+            vec![0.9, 0.8]
+        );
+        assert!(db.get_resonance_scores(Some("db"), None, 100)?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_resonances_are_found_by_test() -> Result<()> {
+        use liminalqa_core::types::TestStatus;
+
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+        let api = make_test(EntityId::new(), "api", TestStatus::Fail, 10);
+        let api_rerun = make_test(EntityId::new(), "api", TestStatus::Pass, 10);
+        let ui = make_test(EntityId::new(), "ui", TestStatus::Fail, 10);
+        for test in [&api, &api_rerun, &ui] {
+            db.put_test(test)?;
+        }
+        let mut flaky = make_resonance(0.9, vec![api.id, api_rerun.id]);
+        let resolved = make_resonance(0.5, vec![api_rerun.id]);
+        db.put_resonance(&flaky)?;
+        db.put_resonance(&resolved)?;
+        db.put_resonance(&make_resonance(0.7, vec![ui.id]))?;
+        db.resolve_resonance(resolved.id, "flake")?;
+
+        let open = |db: &LiminalDB, suite: &str| -> Result<Vec<EntityId>> {
+            Ok(db
+                .open_resonances_for_test(&api.name, suite)?
+                .iter()
+                .map(|r| r.id)
+                .collect())
+        };
+        assert_eq!(open(&db, "api")?, [flaky.id]);
+
+        // Moved to other tests, it leaves the index of the old ones
+        flaky.affected_tests = vec![ui.id];
+        db.put_resonance(&flaky)?;
+        assert!(open(&db, "api")?.is_empty());
+        assert_eq!(open(&db, "ui")?.len(), 2);
+
+        // A database written before the index has it filled on open
+        db.resonance_test_index.clear()?;
+        drop(db);
+        let db = reopen(temp_dir.path())?;
+        assert_eq!(open(&db, "ui")?.len(), 2);
+        Ok(())
+    }
+
+    fn make_signal(status: serde_json::Value) -> Signal {
+        Signal {
+            id: EntityId::new(),
+            run_id: EntityId::new(),
+            test_id: Some(EntityId::new()),
+            signal_type: liminalqa_core::types::SignalType::API,
+            timestamp: chrono::Utc::now(),
+            latency_ms: Some(10),
+            payload_ref: None,
+            metadata: std::collections::BTreeMap::from([
+                ("status".to_string(), status),
+                ("path".to_string(), serde_json::json!("/login")),
+            ]),
+            created_at: BiTemporalTime::now(),
+            sequence: 0,
+            correlation_id: None,
+        }
+    }
+
+    #[test]
+    fn test_get_signals_by_correlation() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+
+        let correlated = |id: Option<&str>, offset_ms: i64| Signal {
+            timestamp: chrono::Utc::now() + chrono::Duration::milliseconds(offset_ms),
+            correlation_id: id.map(str::to_string),
+            ..make_signal(serde_json::json!(200))
+        };
+        // One request crossing two services, stored out of order
+        let backend = correlated(Some("req-1"), 20);
+        let frontend = correlated(Some("req-1"), 0);
+        db.put_signal(&backend)?;
+        db.put_signal(&frontend)?;
+        db.put_signal(&correlated(Some("req-10"), 10))?;
+        db.put_signal(&correlated(None, 5))?;
+
+        let ids: Vec<EntityId> = db
+            .get_signals_by_correlation("req-1")?
+            .into_iter()
+            .map(|s| s.id)
+            .collect();
+        assert_eq!(ids, [frontend.id, backend.id]);
+        assert_eq!(db.get_signals_by_correlation("req-10")?.len(), 1);
+        assert!(db.get_signals_by_correlation("req")?.is_empty());
+
+        // The index is rebuilt with the others
+        db.signal_meta_index.clear()?;
+        db.rebuild_indexes()?;
+        assert_eq!(db.get_signals_by_correlation("req-1")?.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_signals_by_indexed_meta() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?.with_indexed_signal_meta_keys(["status"]);
+
+        let server_error = make_signal(serde_json::json!(500));
+        db.put_signal(&server_error)?;
+        db.put_signal(&make_signal(serde_json::json!(200)))?;
+        db.put_signal(&make_signal(serde_json::json!("500")))?;
+
+        let found = db.scan_signals_by_meta("status", &serde_json::json!(500))?;
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, server_error.id);
+
+        assert!(db
+            .scan_signals_by_meta("status", &serde_json::json!(404))?
+            .is_empty());
+
+        // Keys that are not configured cannot be queried
+        assert!(db
+            .scan_signals_by_meta("path", &serde_json::json!("/login"))
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_facts_page_reassembles_full_scan() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+
+        let entity = EntityId::new();
+        for i in 0..25 {
+            db.put_fact(&Fact::new(
+                entity,
+                Attribute::TestDuration,
+                serde_json::json!(i),
+            ))?;
+        }
+
+        let mut paged = Vec::new();
+        let mut pages = 0;
+        let mut after = None;
+        loop {
+            let page = db.scan_facts_page(after, 10)?;
+            assert!(page.facts.len() <= 10);
+            pages += 1;
+            paged.extend(page.facts);
+            match page.next {
+                Some(next) => after = Some(next),
+                None => break,
+            }
+        }
+
+        let full = db.scan_facts()?;
+        assert_eq!(pages, 3);
+        assert_eq!(paged.len(), 25);
+        assert_eq!(serde_json::to_value(&paged)?, serde_json::to_value(&full)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_artifact_location_survives_storage() -> Result<()> {
+        use liminalqa_core::types::{ArtifactLocation, ArtifactRef};
+
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+
+        let location = ArtifactLocation::S3 {
+            bucket: "qa-artifacts".to_string(),
+            key: "runs/1/trace.zip".to_string(),
+        };
+        let artifact = Artifact {
+            id: EntityId::new(),
+            run_id: EntityId::new(),
+            test_id: EntityId::new(),
+            artifact_ref: ArtifactRef {
+                sha256: "abc123".to_string(),
+                location: location.clone(),
+                size_bytes: 2048,
+                mime_type: None,
+            },
+            artifact_type: ArtifactType::Trace,
+            description: None,
+            created_at: BiTemporalTime::now(),
+        };
+        db.put_artifact(&artifact)?;
+
+        let stored = db
+            .get_entity::<Artifact>(artifact.id)?
+            .expect("artifact should be stored");
+        assert_eq!(stored.artifact_ref.location, location);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_environment_survives_storage() -> Result<()> {
+        use liminalqa_core::types::Environment;
+
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+
+        let env = Environment::from_pairs([
+            ("browser", "firefox"),
+            ("os", "macos"),
+            ("grid_url", "http://selenium-hub:4444"),
+        ]);
+        let run = Run {
+            id: EntityId::new(),
+            build_id: EntityId::new(),
+            plan_name: "smoke".to_string(),
+            env: env.clone(),
+            started_at: chrono::Utc::now(),
+            ended_at: None,
+            runner_version: "test".to_string(),
+            liminal_os_version: None,
+            created_at: BiTemporalTime::now(),
+        };
+        db.put_run(&run)?;
+
+        let stored: Run = db.get_entity(run.id)?.context("run should exist")?;
+        assert_eq!(stored.env, env);
+        assert_eq!(stored.env.browser.as_deref(), Some("firefox"));
+        assert_eq!(
+            stored.env.extra.get("grid_url").map(String::as_str),
+            Some("http://selenium-hub:4444")
+        );
+        assert_eq!(db.stored_entity_type(run.id)?, Some(EntityType::Run));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rebuild_indexes_restores_queries() -> Result<()> {
+        use liminalqa_core::types::TestStatus;
+
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?.with_indexed_signal_meta_keys(["status"]);
+
+        let run = EntityId::new();
+        db.put_run(&Run {
+            id: run,
+            build_id: EntityId::new(),
+            plan_name: "smoke".to_string(),
+            env: Default::default(),
+            started_at: chrono::Utc::now(),
+            ended_at: None,
+            runner_version: "test".to_string(),
+            liminal_os_version: None,
+            created_at: BiTemporalTime::now(),
+        })?;
+        let test = make_test(run, "api", TestStatus::Pass, 120);
+        db.put_test(&test)?;
+        db.put_signal(&make_signal(serde_json::json!(500)))?;
+        db.put_fact(&Fact::new(
+            test.id,
+            Attribute::TestDuration,
+            serde_json::json!(120),
+        ))?;
+        db.put_resonance(&make_resonance(0.9, vec![test.id]))?;
+
+        // Lose every secondary index
+        for index in [
+            &db.valid_time_index,
+            &db.tx_time_index,
+            &db.entity_type_index,
+            &db.test_name_index,
+            &db.test_history_index,
+            &db.signal_meta_index,
+            &db.resonance_test_index,
+        ] {
+            index.clear()?;
+        }
+        assert!(db.get_entities_by_type(EntityType::Test)?.is_empty());
+        assert_eq!(db.find_test_by_name(run, &test.name)?, None);
+        assert!(db.scan_facts_by_valid_time(0, None)?.is_empty());
+
+        db.rebuild_indexes()?;
+
+        assert_eq!(db.get_entities_by_type(EntityType::Run)?, vec![run]);
+        assert_eq!(db.get_entities_by_type(EntityType::Test)?, vec![test.id]);
+        assert_eq!(db.get_entities_by_type(EntityType::Signal)?.len(), 1);
+        assert_eq!(db.find_test_by_name(run, &test.name)?, Some(test.id));
+        assert_eq!(db.get_test_history(&test.name, "api", 10)?.len(), 1);
+        assert_eq!(db.open_resonances_for_test(&test.name, "api")?.len(), 1);
+        assert_eq!(db.scan_facts_by_valid_time(0, None)?.len(), 1);
+        assert_eq!(
+            db.scan_signals_by_meta("status", &serde_json::json!(500))?
+                .len(),
+            1
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_entity_types_are_recorded_for_databases_without_them() -> Result<()> {
+        use liminalqa_core::types::TestStatus;
+
+        let temp_dir = TempDir::new()?;
+        let test = make_test(EntityId::new(), "api", TestStatus::Pass, 120);
+        {
+            let db = LiminalDB::open(temp_dir.path())?;
+            db.put_test(&test)?;
+            db.put_signal(&make_signal(serde_json::json!(200)))?;
+            // As written before entity types were recorded
+            db.entity_types.clear()?;
+            db.flush()?;
+        }
+
+        let db = reopen(temp_dir.path())?;
+        assert_eq!(db.stored_entity_type(test.id)?, Some(EntityType::Test));
+        db.entity_type_index.clear()?;
+        db.rebuild_indexes()?;
+        assert_eq!(db.get_entities_by_type(EntityType::Test)?, vec![test.id]);
+        assert_eq!(db.get_entities_by_type(EntityType::Signal)?.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_status_transitions_across_runs() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+
+        let t0 = Utc::now() - chrono::Duration::hours(3);
+        let mut times = Vec::new();
+        for (hour, status) in [
+            (0, TestStatus::Pass),
+            (1, TestStatus::Fail),
+            (2, TestStatus::Pass),
+        ] {
+            let at = t0 + chrono::Duration::hours(hour);
+            let mut test = make_test(EntityId::new(), "checkout", status, 100);
+            test.name = "test_pay".to_string();
+            test.started_at = at;
+            test.completed_at = at;
+            db.put_test(&test)?;
+            times.push(at);
+        }
+
+        let transitions = db.test_status_transitions("test_pay", "checkout", 10)?;
+        assert_eq!(
+            transitions,
+            vec![
+                (times[1], TestStatus::Pass, TestStatus::Fail),
+                (times[2], TestStatus::Fail, TestStatus::Pass),
+            ]
+        );
+
+        let latest = db.test_status_transitions("test_pay", "checkout", 1)?;
+        assert_eq!(latest, vec![(times[2], TestStatus::Fail, TestStatus::Pass)]);
+
+        assert!(db
+            .test_status_transitions("test_pay", "other", 10)?
+            .is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_status_revisions_between_tx_times() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+
+        let recorded = Utc::now() - chrono::Duration::hours(2);
+        let run_id = EntityId::new();
+        let mut flaky = make_test(run_id, "checkout", TestStatus::Fail, 100);
+        flaky.completed_at = recorded;
+        flaky.created_at = BiTemporalTime::with_times(recorded, recorded);
+        let mut stable = make_test(run_id, "checkout", TestStatus::Pass, 200);
+        stable.created_at = flaky.created_at;
+        db.put_test(&flaky)?;
+        db.put_test(&stable)?;
+        // Same test name in another run must not be reported
+        db.put_test(&make_test(
+            EntityId::new(),
+            "checkout",
+            TestStatus::Fail,
+            100,
+        ))?;
+
+        // A rerun an hour later revises the failure to a pass
+        let rerun = recorded + chrono::Duration::hours(1);
+        db.put_fact(&Fact::with_time(
+            flaky.id,
+            Attribute::TestStatus,
+            serde_json::to_value(TestStatus::Pass)?,
+            BiTemporalTime::with_times(rerun, rerun),
+        ))?;
+
+        let before = recorded + chrono::Duration::minutes(30);
+        let revisions = db.status_revisions(run_id, before, Utc::now())?;
+        assert_eq!(revisions.len(), 1);
+        let (test, old, new) = &revisions[0];
+        assert_eq!(test.id, flaky.id);
+        assert_eq!((*old, *new), (TestStatus::Fail, TestStatus::Pass));
+
+        // Nothing was revised after the rerun, nor before the test existed
+        assert!(db.status_revisions(run_id, rerun, Utc::now())?.is_empty());
+        assert!(db
+            .status_revisions(run_id, recorded - chrono::Duration::hours(1), before)?
+            .is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_signal_timeline_buckets_by_time_and_type() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+
+        let run_id = EntityId::new();
+        let t0 = DateTime::from_timestamp_millis(1_700_000_000_000).expect("valid timestamp");
+        for (offset_ms, signal_type, latency_ms) in [
+            (0, SignalType::API, Some(100)),
+            (400, SignalType::API, Some(300)),
+            (900, SignalType::UI, None),
+            (1500, SignalType::API, None),
+        ] {
+            let mut signal = make_signal(serde_json::json!(200));
+            signal.run_id = run_id;
+            signal.signal_type = signal_type;
+            signal.timestamp = t0 + chrono::Duration::milliseconds(offset_ms);
+            signal.latency_ms = latency_ms;
+            db.put_signal(&signal)?;
+        }
+        // Signals of other runs are not counted
+        db.put_signal(&make_signal(serde_json::json!(200)))?;
+
+        let timeline = db.signal_timeline(run_id, 1000)?;
+        let summary: Vec<_> = timeline
+            .iter()
+            .map(|b| (b.start, b.signal_type, b.count, b.avg_latency_ms))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (t0, SignalType::UI, 1, None),
+                (t0, SignalType::API, 2, Some(200.0)),
+                (t0 + chrono::Duration::seconds(1), SignalType::API, 1, None),
+            ]
+        );
+        assert!(db.signal_timeline(run_id, 0).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cancel_running_run() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+
+        let run = Run {
+            id: EntityId::new(),
+            build_id: EntityId::new(),
+            plan_name: "smoke".to_string(),
+            env: Default::default(),
+            started_at: Utc::now(),
+            ended_at: None,
+            runner_version: "test".to_string(),
+            liminal_os_version: None,
+            created_at: BiTemporalTime::now(),
+        };
+        db.put_run(&run)?;
+        assert_eq!(db.run_status(run.id)?, RunStatus::Running);
+
+        let before_cancel = Utc::now();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        db.cancel_run(run.id)?;
+        assert_eq!(db.run_status(run.id)?, RunStatus::Cancelled);
+        // Bi-temporal: what was known before the cancellation is unchanged
+        assert_eq!(db.run_status_at(run.id, before_cancel)?, RunStatus::Running);
+        assert_eq!(
+            crate::report::build_report(&db, run.id)?.status,
+            Some(RunStatus::Cancelled)
+        );
+
+        let again = db.cancel_run(run.id).expect_err("run is already cancelled");
+        assert!(matches!(
+            again.downcast_ref::<DbError>(),
+            Some(DbError::RunAlreadyEnded {
+                status: RunStatus::Cancelled,
+                ..
+            })
+        ));
+        let missing = db.cancel_run(EntityId::new()).expect_err("no such run");
+        assert!(matches!(
+            missing.downcast_ref::<DbError>(),
+            Some(DbError::RunNotFound(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_updates_check_expected_version() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+
+        let run = Run {
+            id: EntityId::new(),
+            build_id: EntityId::new(),
+            plan_name: "smoke".to_string(),
+            env: Default::default(),
+            started_at: Utc::now(),
+            ended_at: None,
+            runner_version: "test".to_string(),
+            liminal_os_version: None,
+            created_at: BiTemporalTime::now(),
+        };
+        assert_eq!(db.run_version(run.id)?, 0);
+        db.put_run_batch(&run, &[], &[], &[])?;
+        assert_eq!(db.put_run_if_version(&run, Some(1))?, 2);
+
+        let stale = db
+            .put_run_if_version(&run, Some(1))
+            .expect_err("version moved on");
+        assert!(matches!(
+            stale.downcast_ref::<DbError>(),
+            Some(DbError::RunVersionConflict {
+                expected: 1,
+                actual: 2,
+                ..
+            })
+        ));
+        assert_eq!(db.cancel_run_if_version(run.id, Some(2))?, 3);
+
+        db.delete_run_cascade(run.id)?;
+        assert_eq!(db.run_version(run.id)?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rescore_all_resonance_follows_threshold() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+
+        let t0 = Utc::now() - chrono::Duration::hours(1);
+        let histories = [
+            // 9 switches in 10 runs
+            ("test_oscillating", "PFPFPFPFPF"),
+            // 2 switches in 10 runs
+            ("test_wobbly", "PPPFFFPPPP"),
+            ("test_stable", "PPPPPPPPPP"),
+        ];
+        for (name, history) in histories {
+            for (i, status) in history.chars().enumerate() {
+                let status = if status == 'P' {
+                    TestStatus::Pass
+                } else {
+                    TestStatus::Fail
+                };
+                let mut test = make_test(EntityId::new(), "checkout", status, 100);
+                test.name = name.to_string();
+                test.started_at = t0 + chrono::Duration::minutes(i as i64);
+                db.put_test(&test)?;
+            }
+        }
+        // Resonance of another detector is never touched
+        let custom = Resonance {
+            id: EntityId::new(),
+            detector: "weekend".to_string(),
+            pattern: liminalqa_core::types::ResonancePattern {
+                pattern_id: EntityId::new(),
+                description: "Fails on weekends".to_string(),
+                score: 1.0,
+                occurrences: 1,
+                first_seen: t0,
+                last_seen: t0,
+            },
+            affected_tests: db
+                .get_test_history("test_stable", "checkout", 1)?
+                .iter()
+                .map(|t| t.id)
+                .collect(),
+            root_cause: None,
+            created_at: BiTemporalTime::now(),
+        };
+        db.put_resonance(&custom)?;
+
+        let flagged = |db: &LiminalDB| -> Result<Vec<String>> {
+            let mut names = Vec::new();
+            for r in db.get_resonance_scores(None, None, usize::MAX)? {
+                if FlakeDetector::produced(&r) {
+                    let test: Test = db
+                        .get_entity(r.affected_tests[0])?
+                        .expect("affected test stored");
+                    names.push(test.name);
+                }
+            }
+            names.sort();
+            Ok(names)
+        };
+
+        assert_eq!(db.rescore_all_resonance(&FlakeDetector::new(10, 0.3))?, 1);
+        assert_eq!(flagged(&db)?, vec!["test_oscillating"]);
+        // Rescoring with the same parameters changes nothing
+        assert_eq!(db.rescore_all_resonance(&FlakeDetector::new(10, 0.3))?, 0);
+
+        assert_eq!(db.rescore_all_resonance(&FlakeDetector::new(10, 0.1))?, 1);
+        assert_eq!(flagged(&db)?, vec!["test_oscillating", "test_wobbly"]);
+
+        assert_eq!(db.rescore_all_resonance(&FlakeDetector::new(10, 0.95))?, 2);
+        assert!(flagged(&db)?.is_empty());
+        assert_eq!(
+            db.get_entities_by_type(EntityType::Resonance)?,
+            vec![custom.id]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_put_run_batch_stores_and_indexes_everything() -> Result<()> {
+        use liminalqa_core::types::TestStatus;
+
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?.with_indexed_signal_meta_keys(["status"]);
+
+        let run = Run {
+            id: EntityId::new(),
+            build_id: EntityId::new(),
+            plan_name: "smoke".to_string(),
+            env: Default::default(),
+            started_at: chrono::Utc::now(),
+            ended_at: None,
+            runner_version: "test".to_string(),
+            liminal_os_version: None,
+            created_at: BiTemporalTime::now(),
+        };
+        let test = make_test(run.id, "api", TestStatus::Pass, 120);
+        let mut signal = make_signal(serde_json::json!(503));
+        signal.run_id = run.id;
+        signal.test_id = Some(test.id);
 
-        // Index by valid_time
-        let vt_key = format!(
-            "{}:{}:{}",
-            fact.time.valid_time.timestamp_millis(),
-            fact.entity_id,
-            fact_id
-        );
-        self.valid_time_index.insert(vt_key.as_bytes(), &key)?;
+        db.put_run_batch(&run, std::slice::from_ref(&test), &[signal], &[])?;
 
-        // Index by tx_time
-        let tx_key = format!(
-            "{}:{}:{}",
-            fact.time.tx_time.timestamp_millis(),
-            fact.entity_id,
-            fact_id
+        assert!(db.get_entity::<Run>(run.id)?.is_some());
+        assert_eq!(db.find_test_by_name(run.id, &test.name)?, Some(test.id));
+        assert_eq!(db.get_test_history(&test.name, "api", 10)?.len(), 1);
+        assert_eq!(db.get_entities_by_type(EntityType::Signal)?.len(), 1);
+        assert_eq!(
+            db.scan_signals_by_meta("status", &serde_json::json!(503))?
+                .len(),
+            1
         );
-        self.tx_time_index.insert(tx_key.as_bytes(), &key)?;
 
-        debug!(
-            "Stored fact: entity_id={}, attribute={}",
-            fact.entity_id, fact.attribute
-        );
         Ok(())
     }
 
-    /// Store multiple facts in batch
-    pub fn put_fact_batch(&self, batch: &FactBatch) -> Result<()> {
-        for fact in &batch.facts {
-            self.put_fact(fact)?;
-        }
-        info!("Stored fact batch: {} facts", batch.facts.len());
+    #[test]
+    fn test_progress_reads_back_in_phase_order() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+
+        let test_id = EntityId::new();
+        let t0 = chrono::Utc::now();
+        let progress = |phase: &str, offset_secs: i64| TestProgress {
+            test_id,
+            run_id: EntityId::new(),
+            phase: phase.to_string(),
+            percent: None,
+            message: None,
+            at: t0 + chrono::Duration::seconds(offset_secs),
+        };
+        // Reported out of order
+        db.put_test_progress(&progress("checkout", 10))?;
+        db.put_test_progress(&progress("login", 5))?;
+        db.put_fact(&Fact::new(
+            test_id,
+            Attribute::TestDuration,
+            serde_json::json!(10),
+        ))?;
+
+        let phases: Vec<String> = db
+            .get_test_progress(test_id)?
+            .into_iter()
+            .map(|p| p.phase)
+            .collect();
+        assert_eq!(phases, ["login", "checkout"]);
+        assert!(db.get_test_progress(EntityId::new())?.is_empty());
+
         Ok(())
     }
 
-    /// Get entity by ID
-    pub fn get_entity<T: for<'de> Deserialize<'de>>(&self, id: EntityId) -> Result<Option<T>> {
-        let key = id.to_bytes();
-        match self.entities.get(key)? {
-            Some(bytes) => {
-                let entity = bincode::deserialize(&bytes)?;
-                Ok(Some(entity))
+    #[test]
+    fn test_fact_value_size_limit() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?.with_max_fact_value_bytes(100);
+
+        // A JSON string serializes with its two quotes
+        let value_of_size = |size: usize| serde_json::json!("x".repeat(size - 2));
+        let entity_id = EntityId::new();
+
+        db.put_fact(&Fact::new(
+            entity_id,
+            Attribute::ApiResponse,
+            value_of_size(100),
+        ))?;
+
+        let err = db
+            .put_fact(&Fact::new(
+                entity_id,
+                Attribute::ApiResponse,
+                value_of_size(101),
+            ))
+            .expect_err("oversized value should be rejected");
+        match err.downcast_ref::<DbError>() {
+            Some(DbError::FactValueTooLarge { size, limit, .. }) => {
+                assert_eq!((*size, *limit), (101, 100));
             }
-            None => Ok(None),
+            other => panic!("unexpected error: {:?}", other),
         }
+        assert!(err.to_string().contains("artifacts"));
+        assert_eq!(db.scan_facts_by_entities(&[entity_id])?.len(), 1);
+
+        Ok(())
     }
 
-    /// Get all entities of a specific type
-    pub fn get_entities_by_type(&self, entity_type: EntityType) -> Result<Vec<EntityId>> {
-        let prefix = format!("{}:", entity_type_to_str(entity_type));
-        let mut ids = Vec::new();
+    #[test]
+    fn test_delete_run_cascade_leaves_other_runs() -> Result<()> {
+        use liminalqa_core::types::{ArtifactLocation, ArtifactRef, TestStatus};
 
-        for item in self.entity_type_index.scan_prefix(prefix.as_bytes()) {
-            let (key, _) = item?;
-            let key_str = String::from_utf8_lossy(&key);
-            if let Some(id_str) = key_str.split(':').nth(1) {
-                if let Ok(id) = EntityId::from_string(id_str) {
-                    ids.push(id);
-                }
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?.with_indexed_signal_meta_keys(["status"]);
+
+        let seed = |suite: &str| -> Result<(Run, Test)> {
+            let run = Run {
+                id: EntityId::new(),
+                build_id: EntityId::new(),
+                plan_name: "smoke".to_string(),
+                env: Default::default(),
+                started_at: chrono::Utc::now(),
+                ended_at: None,
+                runner_version: "test".to_string(),
+                liminal_os_version: None,
+                created_at: BiTemporalTime::now(),
+            };
+            let test = make_test(run.id, suite, TestStatus::Fail, 120);
+            let mut signal = make_signal(serde_json::json!(503));
+            signal.run_id = run.id;
+            signal.test_id = Some(test.id);
+            let artifact = Artifact {
+                id: EntityId::new(),
+                run_id: run.id,
+                test_id: test.id,
+                artifact_ref: ArtifactRef {
+                    sha256: "abc123".to_string(),
+                    location: ArtifactLocation::Local("trace.zip".into()),
+                    size_bytes: 2048,
+                    mime_type: None,
+                },
+                artifact_type: ArtifactType::Trace,
+                description: None,
+                created_at: BiTemporalTime::now(),
+            };
+            db.put_run_batch(&run, std::slice::from_ref(&test), &[signal], &[artifact])?;
+            db.put_fact(&Fact::new(
+                test.id,
+                Attribute::TestStatus,
+                serde_json::json!("pass"),
+            ))?;
+            db.put_fact(&Fact::new(
+                run.id,
+                Attribute::RunEndedAt,
+                serde_json::json!(chrono::Utc::now()),
+            ))?;
+            Ok((run, test))
+        };
+        let (doomed, doomed_test) = seed("checkout")?;
+        let (kept, kept_test) = seed("login")?;
+
+        let counts = db.delete_run_cascade(doomed.id)?;
+        assert_eq!(
+            counts,
+            DeleteCounts {
+                runs: 1,
+                tests: 1,
+                signals: 1,
+                artifacts: 1,
+                facts: 2,
             }
-        }
+        );
 
-        Ok(ids)
-    }
+        assert!(db.get_entity::<Run>(doomed.id)?.is_none());
+        assert!(db.get_entity::<Test>(doomed_test.id)?.is_none());
+        assert_eq!(db.find_test_by_name(doomed.id, &doomed_test.name)?, None);
+        assert!(db
+            .get_test_history(&doomed_test.name, "checkout", 10)?
+            .is_empty());
+        assert!(db
+            .scan_facts_by_entities(&[doomed.id, doomed_test.id])?
+            .is_empty());
+        let remaining = |entity_type| db.get_entities_by_type(entity_type).map(|ids| ids.len());
+        assert_eq!(remaining(EntityType::Run)?, 1);
+        assert_eq!(remaining(EntityType::Test)?, 1);
+        assert_eq!(remaining(EntityType::Signal)?, 1);
+        assert_eq!(remaining(EntityType::Artifact)?, 1);
+        assert_eq!(
+            db.scan_signals_by_meta("status", &serde_json::json!(503))?
+                .len(),
+            1
+        );
+        assert_eq!(db.scan_facts_by_valid_time(0, None)?.len(), 2);
+
+        assert!(db.get_entity::<Run>(kept.id)?.is_some());
+        assert_eq!(
+            db.find_test_by_name(kept.id, &kept_test.name)?,
+            Some(kept_test.id)
+        );
+        assert_eq!(
+            db.scan_facts_by_entities(&[kept.id, kept_test.id])?.len(),
+            2
+        );
+
+        assert_eq!(db.delete_run_cascade(doomed.id)?.total(), 0);
 
-    /// Flush all pending writes
-    pub fn flush(&self) -> Result<()> {
-        self.db.flush()?;
         Ok(())
     }
 
-    /// Scan all facts (unfiltered)
-    pub fn scan_facts(&self) -> Result<Vec<Fact>> {
-        let mut facts = Vec::new();
-        for item in self.facts.iter() {
-            let (_, value) = item?;
-            let fact: Fact = serde_json::from_slice(&value)?;
-            facts.push(fact);
-        }
-        Ok(facts)
-    }
+    #[test]
+    fn test_latest_run_per_plan() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
 
-    /// Scan facts for specific entities
-    pub fn scan_facts_by_entities(&self, entity_ids: &[EntityId]) -> Result<Vec<Fact>> {
-        let mut facts = Vec::new();
-        for item in self.facts.iter() {
-            let (_, value) = item?;
-            let fact: Fact = serde_json::from_slice(&value)?;
-            if entity_ids.contains(&fact.entity_id) {
-                facts.push(fact);
-            }
-        }
-        Ok(facts)
-    }
+        let t0 = chrono::Utc::now();
+        let put = |plan: &str, offset_mins: i64| -> Result<Run> {
+            let run = Run {
+                id: EntityId::new(),
+                build_id: EntityId::new(),
+                plan_name: plan.to_string(),
+                env: Default::default(),
+                started_at: t0 + chrono::Duration::minutes(offset_mins),
+                ended_at: None,
+                runner_version: "test".to_string(),
+                liminal_os_version: None,
+                created_at: BiTemporalTime::now(),
+            };
+            db.put_run(&run)?;
+            Ok(run)
+        };
+        put("smoke", 0)?;
+        let newest_smoke = put("smoke", 20)?;
+        put("smoke", 10)?;
+        let newest_nightly = put("nightly", -30)?;
+        put("nightly", -60)?;
 
-    /// Scan facts within valid_time range
-    pub fn scan_facts_by_valid_time(
-        &self,
-        start_ms: i64,
-        end_ms: Option<i64>,
-    ) -> Result<Vec<Fact>> {
-        let mut facts = Vec::new();
+        let latest: Vec<(String, EntityId)> = db
+            .latest_run_per_plan()?
+            .into_iter()
+            .map(|r| (r.plan_name, r.id))
+            .collect();
+        assert_eq!(
+            latest,
+            [
+                ("nightly".to_string(), newest_nightly.id),
+                ("smoke".to_string(), newest_smoke.id),
+            ]
+        );
 
-        // Scan all items in the valid_time_index and filter by range
-        for item in self.valid_time_index.iter() {
-            let (key, fact_key) = item?;
-            let key_str = String::from_utf8_lossy(&key);
+        Ok(())
+    }
 
-            // Parse timestamp from key: "{timestamp}:{entity_id}:{fact_id}"
-            if let Some(ts_str) = key_str.split(':').next() {
-                if let Ok(ts) = ts_str.parse::<i64>() {
-                    // Check if timestamp is in range
-                    #[allow(clippy::unnecessary_map_or)]
-                    #[allow(clippy::needless_bool)]
-                    let in_range = if ts >= start_ms && end_ms.map_or(true, |end| ts <= end) {
-                        true
-                    } else {
-                        false
-                    };
+    #[test]
+    fn test_baseline_below_min_samples_is_provisional() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?.with_min_baseline_samples(3);
 
-                    if in_range {
-                        // Get the actual fact
-                        if let Some(fact_bytes) = self.facts.get(&fact_key)? {
-                            let fact: Fact = serde_json::from_slice(&fact_bytes)?;
-                            facts.push(fact);
-                        }
-                    }
-                }
-            }
-        }
+        let single =
+            db.upsert_baseline(&Baseline::from_samples("test_pay", "checkout", &[120.0]))?;
+        assert!(single.provisional);
+        assert_eq!(single.stddev_ms, 0.0);
+        let stored = db
+            .get_baseline("test_pay", "checkout")?
+            .expect("baseline should be stored");
+        assert!(stored.provisional);
+        assert_eq!(stored.sample_size, 1);
 
-        Ok(facts)
-    }
-}
+        let settled = db.upsert_baseline(&Baseline::from_samples(
+            "test_pay",
+            "checkout",
+            &[120.0, 130.0, 110.0],
+        ))?;
+        assert!(!settled.provisional);
+        assert_eq!(db.get_baseline("test_pay", "checkout")?, Some(settled));
+        assert_eq!(db.get_baseline("test_pay", "other")?, None);
 
-fn entity_type_to_str(et: EntityType) -> &'static str {
-    match et {
-        EntityType::System => "system",
-        EntityType::Build => "build",
-        EntityType::Run => "run",
-        EntityType::Test => "test",
-        EntityType::Artifact => "artifact",
-        EntityType::Signal => "signal",
-        EntityType::Resonance => "resonance",
+        Ok(())
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use liminalqa_core::temporal::BiTemporalTime;
-    use tempfile::TempDir;
 
     #[test]
-    fn test_store_and_retrieve_test() -> Result<()> {
+    fn test_tests_by_owner_follow_reassignment() -> Result<()> {
+        use liminalqa_core::types::TestStatus;
+
         let temp_dir = TempDir::new()?;
         let db = LiminalDB::open(temp_dir.path())?;
+        let run = EntityId::new();
+        let pay = make_test(run, "checkout", TestStatus::Fail, 900);
+        let login = make_test(run, "auth", TestStatus::Pass, 120);
+        for test in [&pay, &login] {
+            db.put_test(test)?;
+        }
+        db.put_test_owner(&pay, "payments")?;
+        db.put_test_owner(&login, "payments")?;
+        assert!(db.put_test_owner(&login, " ").is_err());
 
-        let test = Test {
-            id: EntityId::new(),
-            run_id: EntityId::new(),
-            name: "test_login".to_string(),
-            suite: "auth".to_string(),
-            guidance: "User should be able to log in with valid credentials".to_string(),
-            status: liminalqa_core::types::TestStatus::Pass,
-            duration_ms: 1234,
-            error: None,
-            started_at: chrono::Utc::now(),
-            completed_at: chrono::Utc::now(),
-            created_at: BiTemporalTime::now(),
-        };
+        let ids = |tests: Vec<Test>| tests.into_iter().map(|t| t.id).collect::<Vec<_>>();
+        assert_eq!(ids(db.tests_by_owner("payments")?).len(), 2);
+        assert!(db.tests_by_owner("pay")?.is_empty());
 
-        db.put_test(&test)?;
+        // A later owner fact moves the test; the old index entry is ignored
+        db.put_fact(&Fact::new(
+            login.id,
+            Attribute::TestOwner,
+            serde_json::json!("identity"),
+        ))?;
+        assert_eq!(ids(db.tests_by_owner("payments")?), vec![pay.id]);
+        assert_eq!(ids(db.tests_by_owner("identity")?), vec![login.id]);
+        let owners = db.test_owners_at(&[pay.id, login.id], login.created_at.tx_time)?;
+        assert_eq!(owners.get(&login.id).map(String::as_str), Some("payments"));
 
-        let retrieved: Option<Test> = db.get_entity(test.id)?;
-        assert!(retrieved.is_some());
-        // TODO(Phase 4): Refactor error handling
-        #[allow(clippy::disallowed_methods)]
-        {
-            assert_eq!(retrieved.unwrap().name, "test_login");
-        }
+        db.test_owner_index.clear()?;
+        db.rebuild_indexes()?;
+        assert_eq!(ids(db.tests_by_owner("identity")?), vec![login.id]);
 
+        db.delete_run_cascade(run)?;
+        assert!(db.tests_by_owner("payments")?.is_empty());
+        assert_eq!(db.test_owner_index.len(), 0);
         Ok(())
     }
 
     #[test]
-    fn test_lookup_by_name_success() -> Result<()> {
+    fn test_runs_for_build_via_index() -> Result<()> {
         let temp_dir = TempDir::new()?;
         let db = LiminalDB::open(temp_dir.path())?;
-
-        let run_id = EntityId::new();
-        let test = Test {
+        let build_id = EntityId::new();
+        let make_run = |plan: &str, minutes_ago: i64| Run {
             id: EntityId::new(),
-            run_id,
-            name: "test_user_login".to_string(),
-            suite: "auth".to_string(),
-            guidance: "User should be able to log in".to_string(),
-            status: liminalqa_core::types::TestStatus::Pass,
-            duration_ms: 100,
-            error: None,
-            started_at: chrono::Utc::now(),
-            completed_at: chrono::Utc::now(),
+            build_id,
+            plan_name: plan.to_string(),
+            env: Default::default(),
+            started_at: chrono::Utc::now() - chrono::Duration::minutes(minutes_ago),
+            ended_at: None,
+            runner_version: "test".to_string(),
+            liminal_os_version: None,
             created_at: BiTemporalTime::now(),
         };
+        let smoke = make_run("smoke", 30);
+        let nightly = make_run("nightly", 60);
+        let e2e = make_run("e2e", 10);
+        let mut other = make_run("smoke", 5);
+        other.build_id = EntityId::new();
+        db.put_run(&smoke)?;
+        db.put_run(&nightly)?;
+        db.put_run(&other)?;
+        db.put_run_batch(&e2e, &[], &[], &[])?;
 
-        db.put_test(&test)?;
+        let ids = |runs: Vec<Run>| runs.into_iter().map(|r| r.id).collect::<Vec<_>>();
+        assert_eq!(
+            ids(db.runs_for_build(build_id)?),
+            vec![nightly.id, smoke.id, e2e.id]
+        );
+        assert_eq!(ids(db.runs_for_build(other.build_id)?), vec![other.id]);
 
-        // Lookup should succeed
-        let found = db.find_test_by_name(run_id, "test_user_login")?;
-        assert_eq!(found, Some(test.id));
+        db.build_run_index.clear()?;
+        db.rebuild_indexes()?;
+        assert_eq!(db.runs_for_build(build_id)?.len(), 3);
 
+        db.delete_run_cascade(smoke.id)?;
+        assert_eq!(ids(db.runs_for_build(build_id)?), vec![nightly.id, e2e.id]);
+        assert_eq!(db.build_run_index.len(), 3);
         Ok(())
     }
 
     #[test]
-    fn test_lookup_by_name_not_found() -> Result<()> {
+    fn test_tests_and_signals_of_run_via_index() -> Result<()> {
+        use liminalqa_core::types::TestStatus;
+
         let temp_dir = TempDir::new()?;
         let db = LiminalDB::open(temp_dir.path())?;
+        let run = EntityId::new();
+        let signal_of = |run_id: EntityId| Signal {
+            run_id,
+            ..make_signal(serde_json::json!(200))
+        };
 
-        let run_id = EntityId::new();
-        let found = db.find_test_by_name(run_id, "nonexistent")?;
-        assert_eq!(found, None);
+        let first = make_test(run, "api", TestStatus::Pass, 10);
+        db.put_test(&first)?;
+        db.put_test(&make_test(EntityId::new(), "api", TestStatus::Pass, 20))?;
+        db.put_signal(&signal_of(run))?;
+        db.put_signal(&signal_of(EntityId::new()))?;
+        let second = make_test(run, "api", TestStatus::Fail, 30);
+        let batched = signal_of(run);
+        db.put_run_batch(
+            &Run {
+                id: run,
+                build_id: EntityId::new(),
+                plan_name: "smoke".to_string(),
+                env: Default::default(),
+                started_at: chrono::Utc::now(),
+                ended_at: None,
+                runner_version: "test".to_string(),
+                liminal_os_version: None,
+                created_at: BiTemporalTime::now(),
+            },
+            std::slice::from_ref(&second),
+            std::slice::from_ref(&batched),
+            &[],
+        )?;
+
+        let ids = |tests: Vec<Test>| tests.into_iter().map(|t| t.id).collect::<Vec<_>>();
+        assert_eq!(ids(db.tests_of_run(run)?), vec![first.id, second.id]);
+        let sequences =
+            |signals: Vec<Signal>| signals.into_iter().map(|s| s.sequence).collect::<Vec<_>>();
+        assert_eq!(sequences(db.signals_of_run(run, None)?), vec![1, 2]);
+        assert_eq!(sequences(db.signals_of_run(run, Some(1))?), vec![2]);
+        assert!(db.signals_of_run(run, Some(2))?.is_empty());
 
+        db.run_entity_index.clear()?;
+        db.rebuild_indexes()?;
+        assert_eq!(db.tests_of_run(run)?.len(), 2);
+        assert_eq!(sequences(db.signals_of_run(run, None)?), vec![1, 2]);
+
+        db.delete_run_cascade(run)?;
+        assert!(db.tests_of_run(run)?.is_empty());
+        assert!(db.signals_of_run(run, None)?.is_empty());
+        assert_eq!(db.run_entity_index.len(), 2);
         Ok(())
     }
 
     #[test]
-    fn test_lookup_different_runs_same_name() -> Result<()> {
+    fn test_failed_test_in_legacy_error_layout_still_decodes() -> Result<()> {
+        use crate::legacy::{TestErrorV1, TestV1};
+        use liminalqa_core::types::{SourceLocation, TestStatus};
+
         let temp_dir = TempDir::new()?;
         let db = LiminalDB::open(temp_dir.path())?;
 
-        let run1 = EntityId::new();
-        let run2 = EntityId::new();
-
-        let test1 = Test {
+        // Bytes as written before `TestError` gained `kind` and `details`
+        let started_at = Utc::now();
+        let old = TestV1 {
             id: EntityId::new(),
-            run_id: run1,
-            name: "shared_name".to_string(),
-            suite: "suite1".to_string(),
-            guidance: "".to_string(),
-            status: liminalqa_core::types::TestStatus::Pass,
-            duration_ms: 100,
-            error: None,
-            started_at: chrono::Utc::now(),
-            completed_at: chrono::Utc::now(),
+            run_id: EntityId::new(),
+            name: "test_checkout".to_string(),
+            suite: "payments".to_string(),
+            guidance: String::new(),
+            status: TestStatus::Fail,
+            duration_ms: 250,
+            error: Some(TestErrorV1 {
+                error_type: "AssertionError".to_string(),
+                message: "expected 200, got 500".to_string(),
+                stack_trace: None,
+                source_location: Some(SourceLocation {
+                    file: "checkout.rs".to_string(),
+                    line: 42,
+                    column: None,
+                }),
+            }),
+            started_at,
+            completed_at: started_at + chrono::Duration::milliseconds(250),
             created_at: BiTemporalTime::now(),
         };
+        let id = old.id;
+        let bytes = bincode::serialize(&old)?;
+        assert!(bincode::deserialize::<Test>(&bytes).is_err());
+        db.put_entity_bytes(EntityType::Test, id, bytes)?;
+        db.rebuild_indexes()?;
 
-        let test2 = Test {
+        let test: Test = db.get_entity(id)?.context("legacy test")?;
+        let error = test.error.context("legacy error")?;
+        assert_eq!(error.kind, "AssertionError");
+        assert_eq!(error.message, "expected 200, got 500");
+        assert_eq!(error.source_location.map(|l| l.line), Some(42));
+        assert_eq!(error.details, None);
+
+        let history = db.get_test_history("test_checkout", "payments", 10)?;
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].id, id);
+        Ok(())
+    }
+
+    #[test]
+    fn test_artifact_in_legacy_ref_layout_still_decodes() -> Result<()> {
+        use crate::legacy::{ArtifactRefV1, ArtifactV1};
+        use liminalqa_core::types::ArtifactLocation;
+
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+
+        // Bytes as written before `ArtifactRef` gained `location`
+        let old = ArtifactV1 {
             id: EntityId::new(),
-            run_id: run2,
-            name: "shared_name".to_string(),
-            suite: "suite2".to_string(),
-            guidance: "".to_string(),
-            status: liminalqa_core::types::TestStatus::Pass,
-            duration_ms: 200,
-            error: None,
-            started_at: chrono::Utc::now(),
-            completed_at: chrono::Utc::now(),
+            run_id: EntityId::new(),
+            test_id: EntityId::new(),
+            artifact_ref: ArtifactRefV1 {
+                sha256: "abc123".to_string(),
+                path: "s3://qa-artifacts/runs/1/shot.png".to_string(),
+                size_bytes: 1024,
+                mime_type: Some("image/png".to_string()),
+            },
+            artifact_type: ArtifactType::Screenshot,
+            description: Some("login page".to_string()),
             created_at: BiTemporalTime::now(),
         };
+        let id = old.id;
+        db.put_entity_bytes(EntityType::Artifact, id, bincode::serialize(&old)?)?;
 
-        db.put_test(&test1)?;
-        db.put_test(&test2)?;
+        let artifact: Artifact = db.get_entity(id)?.context("legacy artifact")?;
+        assert_eq!(artifact.artifact_ref.sha256, "abc123");
+        assert_eq!(
+            artifact.artifact_ref.location,
+            ArtifactLocation::S3 {
+                bucket: "qa-artifacts".to_string(),
+                key: "runs/1/shot.png".to_string(),
+            }
+        );
+        assert_eq!(artifact.artifact_ref.size_bytes, 1024);
+        assert_eq!(
+            artifact.artifact_ref.mime_type.as_deref(),
+            Some("image/png")
+        );
+        assert_eq!(artifact.description.as_deref(), Some("login page"));
+        Ok(())
+    }
 
-        // Same name in different runs should resolve to different test_ids
-        let found1 = db.find_test_by_name(run1, "shared_name")?;
-        let found2 = db.find_test_by_name(run2, "shared_name")?;
+    #[test]
+    fn test_resonance_without_detector_still_decodes() -> Result<()> {
+        use crate::legacy::ResonanceV1;
 
-        assert_eq!(found1, Some(test1.id));
-        assert_eq!(found2, Some(test2.id));
-        assert_ne!(test1.id, test2.id);
+        let temp_dir = TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+
+        // Bytes as written before `Resonance` recorded its detector
+        let mut ids = Vec::new();
+        for description in ["Flaky test detected: test_pay (Score: 0.50)", "Slow"] {
+            let fresh = make_resonance(0.5, vec![EntityId::new()]);
+            let old = ResonanceV1 {
+                id: fresh.id,
+                pattern: liminalqa_core::types::ResonancePattern {
+                    description: description.to_string(),
+                    ..fresh.pattern
+                },
+                affected_tests: fresh.affected_tests,
+                root_cause: None,
+                created_at: fresh.created_at,
+            };
+            db.put_entity_bytes(EntityType::Resonance, old.id, bincode::serialize(&old)?)?;
+            ids.push(old.id);
+        }
 
+        let flake: Resonance = db.get_entity(ids[0])?.context("legacy flake")?;
+        assert_eq!(flake.detector, FlakeDetector::NAME);
+        assert!(FlakeDetector::produced(&flake));
+        let other: Resonance = db.get_entity(ids[1])?.context("legacy resonance")?;
+        assert_eq!(other.detector, "");
+        assert_eq!(other.pattern.description, "Slow");
         Ok(())
     }
 }
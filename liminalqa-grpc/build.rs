@@ -1,4 +1,10 @@
+use std::{env, path::PathBuf};
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    tonic_build::compile_protos("../proto/liminalqa/v1/ingest.proto")?;
+    // The descriptor set backs the server reflection service
+    let out_dir = PathBuf::from(env::var("OUT_DIR")?);
+    tonic_build::configure()
+        .file_descriptor_set_path(out_dir.join("liminalqa_descriptor.bin"))
+        .compile(&["../proto/liminalqa/v1/ingest.proto"], &["../proto"])?;
     Ok(())
 }
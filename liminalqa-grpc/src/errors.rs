@@ -0,0 +1,119 @@
+//! Structured error details, following the `google.rpc` rich error model.
+//!
+//! A failed call carries a `google.rpc.Status` in its
+//! `grpc-status-details-bin` trailer whose details hold one
+//! `google.rpc.ErrorInfo`, so clients can branch on a stable reason code
+//! instead of parsing the message.
+
+use std::collections::HashMap;
+use tonic::{codegen::Bytes, Code, Status};
+
+/// `ErrorInfo.domain` of every error raised by this service
+pub const ERROR_DOMAIN: &str = "liminalqa.dev";
+
+const ERROR_INFO_TYPE_URL: &str = "type.googleapis.com/google.rpc.ErrorInfo";
+
+/// `google.rpc.ErrorInfo`
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ErrorInfo {
+    #[prost(string, tag = "1")]
+    pub reason: String,
+    #[prost(string, tag = "2")]
+    pub domain: String,
+    #[prost(map = "string, string", tag = "3")]
+    pub metadata: HashMap<String, String>,
+}
+
+/// `google.rpc.Status`, the payload of `grpc-status-details-bin`
+#[derive(Clone, PartialEq, prost::Message)]
+struct RpcStatus {
+    #[prost(int32, tag = "1")]
+    code: i32,
+    #[prost(string, tag = "2")]
+    message: String,
+    #[prost(message, repeated, tag = "3")]
+    details: Vec<prost_types::Any>,
+}
+
+/// Why a call failed, sent as `ErrorInfo.reason`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorReason {
+    /// A field of the request could not be parsed
+    InvalidArgument,
+    /// A required message of the request is absent
+    MissingField,
+    /// A batch entry names a test that is not in the batch
+    TestNotFound,
+    /// The database refused a write
+    DbWriteFailed,
+}
+
+impl ErrorReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorReason::InvalidArgument => "INVALID_ARGUMENT",
+            ErrorReason::MissingField => "MISSING_FIELD",
+            ErrorReason::TestNotFound => "TEST_NOT_FOUND",
+            ErrorReason::DbWriteFailed => "DB_WRITE_FAILED",
+        }
+    }
+
+    pub fn code(self) -> Code {
+        match self {
+            ErrorReason::InvalidArgument | ErrorReason::MissingField => Code::InvalidArgument,
+            ErrorReason::TestNotFound => Code::NotFound,
+            ErrorReason::DbWriteFailed => Code::Internal,
+        }
+    }
+
+    /// A status with this reason's code, `message` and an `ErrorInfo` detail
+    pub fn status(self, message: impl Into<String>) -> Status {
+        let message = message.into();
+        let info = ErrorInfo {
+            reason: self.as_str().to_string(),
+            domain: ERROR_DOMAIN.to_string(),
+            metadata: HashMap::new(),
+        };
+        let details = RpcStatus {
+            code: self.code() as i32,
+            message: message.clone(),
+            details: vec![prost_types::Any {
+                type_url: ERROR_INFO_TYPE_URL.to_string(),
+                value: prost::Message::encode_to_vec(&info),
+            }],
+        };
+        Status::with_details(
+            self.code(),
+            message,
+            Bytes::from(prost::Message::encode_to_vec(&details)),
+        )
+    }
+}
+
+/// The `ErrorInfo` detail of a status, if it carries one
+pub fn error_info(status: &Status) -> Option<ErrorInfo> {
+    let details: RpcStatus = prost::Message::decode(status.details()).ok()?;
+    details
+        .details
+        .iter()
+        .find(|any| any.type_url == ERROR_INFO_TYPE_URL)
+        .and_then(|any| prost::Message::decode(any.value.as_slice()).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_info_round_trip() {
+        let status = ErrorReason::TestNotFound.status("Test 'test_pay' not found in batch");
+        assert_eq!(status.code(), Code::NotFound);
+        assert_eq!(status.message(), "Test 'test_pay' not found in batch");
+
+        let info = error_info(&status).expect("error info detail");
+        assert_eq!(info.reason, "TEST_NOT_FOUND");
+        assert_eq!(info.domain, ERROR_DOMAIN);
+
+        assert_eq!(error_info(&Status::internal("plain")), None);
+    }
+}
@@ -0,0 +1,24 @@
+//! Standard gRPC health checking and server reflection
+
+use crate::{IngestServiceServer, MyIngestService, FILE_DESCRIPTOR_SET};
+use tonic_health::pb::health_server::{Health, HealthServer};
+use tonic_reflection::server::{ServerReflection, ServerReflectionServer};
+
+/// `grpc.health.v1.Health`, reporting the ingest service as SERVING
+pub async fn health_service() -> HealthServer<impl Health> {
+    let (mut reporter, service) = tonic_health::server::health_reporter();
+    reporter
+        .set_serving::<IngestServiceServer<MyIngestService>>()
+        .await;
+    service
+}
+
+/// `grpc.reflection.v1alpha.ServerReflection` over the LiminalQA and health
+/// protos, so tools like grpcurl can list and describe the services
+pub fn reflection_service(
+) -> Result<ServerReflectionServer<impl ServerReflection>, tonic_reflection::server::Error> {
+    tonic_reflection::server::Builder::configure()
+        .register_encoded_file_descriptor_set(FILE_DESCRIPTOR_SET)
+        .register_encoded_file_descriptor_set(tonic_health::pb::FILE_DESCRIPTOR_SET)
+        .build()
+}
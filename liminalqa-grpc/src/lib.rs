@@ -4,10 +4,21 @@ pub mod liminalqa {
     }
 }
 
+/// Encoded descriptors of the LiminalQA protos, served by reflection
+pub const FILE_DESCRIPTOR_SET: &[u8] = tonic::include_file_descriptor_set!("liminalqa_descriptor");
+
+pub mod errors;
+pub mod introspection;
 pub mod server;
+pub mod tls;
 
+pub use errors::{error_info, ErrorInfo, ErrorReason};
+pub use introspection::{health_service, reflection_service};
 pub use liminalqa::v1::ingest_service_server::{IngestService, IngestServiceServer};
 pub use liminalqa::v1::{
-    IngestRunRequest, IngestRunResponse, IngestTestsRequest, IngestTestsResponse, Signal, SignalAck,
+    BatchArtifact, BatchCounts, BatchSignal, IngestBatchRequest, IngestBatchResponse,
+    IngestRunRequest, IngestRunResponse, IngestTestsRequest, IngestTestsResponse, Signal,
+    SignalAck,
 };
 pub use server::MyIngestService;
+pub use tls::MtlsConfig;
@@ -1,10 +1,22 @@
+// tonic::Status is large, but it is what every handler returns
+#![allow(clippy::result_large_err)]
+
+use crate::errors::ErrorReason;
 use crate::liminalqa::v1::{
-    ingest_service_server::IngestService, IngestRunRequest, IngestRunResponse, IngestTestsRequest,
-    IngestTestsResponse, Signal, SignalAck,
+    ingest_service_server::IngestService, BatchArtifact, BatchCounts, BatchSignal,
+    IngestBatchRequest, IngestBatchResponse, IngestRunRequest, IngestRunResponse,
+    IngestTestsRequest, IngestTestsResponse, Signal, SignalAck, Test as TestMessage,
+};
+use chrono::{DateTime, TimeZone, Utc};
+use liminalqa_core::{
+    entities::{Artifact, ArtifactType, Run, Test},
+    temporal::BiTemporalTime,
+    types::{
+        ArtifactLocation, ArtifactRef, EntityId, Environment, SignalType, TestError, TestStatus,
+    },
 };
-use chrono::TimeZone;
-use liminalqa_core::types::EntityId;
-use liminalqa_db::LiminalDB;
+use liminalqa_db::{spillover::SignalSpillover, LiminalDB};
+use std::collections::HashMap;
 use std::pin::Pin;
 use std::sync::Arc;
 use tokio_stream::Stream;
@@ -13,11 +25,33 @@ use tonic::{Request, Response, Status};
 
 pub struct MyIngestService {
     db: Arc<LiminalDB>,
+    /// Extra test status labels, keyed by lowercase label
+    status_aliases: HashMap<String, TestStatus>,
+    /// Where oversized signal metadata is spilled; kept inline when `None`
+    signal_spillover: Option<Arc<SignalSpillover>>,
 }
 
 impl MyIngestService {
     pub fn new(db: Arc<LiminalDB>) -> Self {
-        Self { db }
+        Self {
+            db,
+            status_aliases: HashMap::new(),
+            signal_spillover: None,
+        }
+    }
+
+    /// Spill signal metadata over the configured size to payload files, as
+    /// REST ingest does
+    pub fn with_signal_spillover(mut self, spillover: Arc<SignalSpillover>) -> Self {
+        self.signal_spillover = Some(spillover);
+        self
+    }
+
+    /// Map test status labels to statuses on ingest (see
+    /// [`TestStatus::from_label_with_aliases`])
+    pub fn with_status_aliases(mut self, aliases: HashMap<String, TestStatus>) -> Self {
+        self.status_aliases = aliases;
+        self
     }
 }
 
@@ -27,47 +61,12 @@ impl IngestService for MyIngestService {
         &self,
         request: Request<IngestRunRequest>,
     ) -> Result<Response<IngestRunResponse>, Status> {
-        let req = request.into_inner();
-
-        let run_id = liminalqa_core::types::new_entity_id();
-
-        let build_id = EntityId::from_string(&req.build_id)
-            .map_err(|e| Status::invalid_argument(format!("Invalid build_id: {}", e)))?;
+        let run = run_from_request(request.into_inner())?;
+        let run_id = run.id;
 
-        let env: std::collections::HashMap<String, String> = serde_json::from_str(&req.env)
-            .map_err(|e| Status::invalid_argument(format!("Invalid env JSON: {}", e)))?;
-
-        let started_at = chrono::Utc
-            .timestamp_millis_opt(req.started_at)
-            .single()
-            .ok_or_else(|| Status::invalid_argument("Invalid started_at timestamp"))?;
-
-        let ended_at = if let Some(ts) = req.ended_at {
-            Some(
-                chrono::Utc
-                    .timestamp_millis_opt(ts)
-                    .single()
-                    .ok_or_else(|| Status::invalid_argument("Invalid ended_at timestamp"))?,
-            )
-        } else {
-            None
-        };
-
-        let run = liminalqa_core::entities::Run {
-            id: run_id,
-            build_id,
-            plan_name: req.plan_name,
-            env,
-            started_at,
-            ended_at,
-            runner_version: req.runner_version,
-            liminal_os_version: req.liminal_os_version,
-            created_at: liminalqa_core::temporal::BiTemporalTime::now(),
-        };
-
-        self.db
-            .put_run(&run)
-            .map_err(|e| Status::internal(format!("Failed to store run: {}", e)))?;
+        self.db.put_run(&run).map_err(|e| {
+            ErrorReason::DbWriteFailed.status(format!("Failed to store run: {}", e))
+        })?;
 
         Ok(Response::new(IngestRunResponse {
             run_id: run_id.to_string(),
@@ -81,7 +80,7 @@ impl IngestService for MyIngestService {
         let req = request.into_inner();
 
         let _run_id = EntityId::from_string(&req.run_id)
-            .map_err(|e| Status::invalid_argument(format!("Invalid run_id: {}", e)))?;
+            .map_err(|e| ErrorReason::InvalidArgument.status(format!("Invalid run_id: {}", e)))?;
 
         // TODO: Implement test ingestion mapping from proto Test to entity Test
 
@@ -117,4 +116,494 @@ impl IngestService for MyIngestService {
 
         Ok(Response::new(Box::pin(output) as Self::StreamSignalsStream))
     }
+
+    async fn ingest_batch(
+        &self,
+        request: Request<IngestBatchRequest>,
+    ) -> Result<Response<IngestBatchResponse>, Status> {
+        let req = request.into_inner();
+
+        // Validate and resolve everything before writing, so a bad reference
+        // leaves the database untouched
+        let run = run_from_request(
+            req.run
+                .ok_or_else(|| ErrorReason::MissingField.status("Missing run"))?,
+        )?;
+        let (tests, test_facts): (Vec<Test>, Vec<TestFacts>) = req
+            .tests
+            .into_iter()
+            .map(|t| test_from_message(run.id, t, &self.status_aliases))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .unzip();
+        let test_id_map: HashMap<String, EntityId> =
+            tests.iter().map(|t| (t.name.clone(), t.id)).collect();
+        let mut signals = req
+            .signals
+            .into_iter()
+            .map(|s| signal_from_batch(run.id, &test_id_map, s))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut artifacts = req
+            .artifacts
+            .into_iter()
+            .map(|a| artifact_from_batch(run.id, &test_id_map, a))
+            .collect::<Result<Vec<_>, _>>()?;
+        // Spilled payloads are stored as artifacts of the same batch
+        if let Some(spillover) = &self.signal_spillover {
+            for signal in &mut signals {
+                let spilled = spillover.apply(signal).map_err(|e| {
+                    ErrorReason::DbWriteFailed
+                        .status(format!("Failed to spill signal metadata: {}", e))
+                })?;
+                artifacts.extend(spilled);
+            }
+        }
+
+        self.db
+            .put_run_batch(&run, &tests, &signals, &artifacts)
+            .map_err(|e| {
+                ErrorReason::DbWriteFailed.status(format!("Failed to store batch: {}", e))
+            })?;
+        for (test, facts) in tests.iter().zip(&test_facts) {
+            facts.store(&self.db, test).map_err(|e| {
+                ErrorReason::DbWriteFailed.status(format!("Failed to store test facts: {}", e))
+            })?;
+        }
+
+        Ok(Response::new(IngestBatchResponse {
+            run_id: run.id.to_string(),
+            counts: Some(BatchCounts {
+                run: 1,
+                tests: tests.len() as u32,
+                signals: signals.len() as u32,
+                artifacts: artifacts.len() as u32,
+            }),
+            test_id_map: test_id_map
+                .into_iter()
+                .map(|(name, id)| (name, id.to_string()))
+                .collect(),
+        }))
+    }
+}
+
+fn timestamp(ms: i64, field: &str) -> Result<DateTime<Utc>, Status> {
+    Utc.timestamp_millis_opt(ms)
+        .single()
+        .ok_or_else(|| ErrorReason::InvalidArgument.status(format!("Invalid {} timestamp", field)))
+}
+
+fn run_from_request(req: IngestRunRequest) -> Result<Run, Status> {
+    let build_id = EntityId::from_string(&req.build_id)
+        .map_err(|e| ErrorReason::InvalidArgument.status(format!("Invalid build_id: {}", e)))?;
+
+    let env: serde_json::Value = serde_json::from_str(&req.env)
+        .map_err(|e| ErrorReason::InvalidArgument.status(format!("Invalid env JSON: {}", e)))?;
+    let env = Environment::from_json(&env)
+        .map_err(|e| ErrorReason::InvalidArgument.status(format!("Invalid env JSON: {}", e)))?;
+
+    Ok(Run {
+        id: liminalqa_core::types::new_entity_id(),
+        build_id,
+        plan_name: req.plan_name,
+        env,
+        started_at: timestamp(req.started_at, "started_at")?,
+        ended_at: req
+            .ended_at
+            .map(|ts| timestamp(ts, "ended_at"))
+            .transpose()?,
+        runner_version: req.runner_version,
+        liminal_os_version: req.liminal_os_version,
+        created_at: BiTemporalTime::now(),
+    })
+}
+
+/// Unset (zero) test timestamps default to now, as over REST
+fn test_timestamp(ms: i64, field: &str) -> Result<DateTime<Utc>, Status> {
+    if ms == 0 {
+        Ok(Utc::now())
+    } else {
+        timestamp(ms, field)
+    }
+}
+
+/// What a test message reports beyond the entity, recorded as facts of the
+/// test once it is stored
+struct TestFacts {
+    alignment_score: Option<f64>,
+    sla_ms: Option<u64>,
+    attempts: Option<u32>,
+}
+
+impl TestFacts {
+    fn store(&self, db: &LiminalDB, test: &Test) -> anyhow::Result<()> {
+        if let Some(score) = self.alignment_score {
+            db.put_test_alignment(test, score)?;
+        }
+        if let Some(sla_ms) = self.sla_ms {
+            db.put_test_sla(test, sla_ms)?;
+        }
+        if let Some(attempts) = self.attempts {
+            db.put_test_attempts(test, attempts)?;
+        }
+        Ok(())
+    }
+}
+
+fn test_from_message(
+    run_id: EntityId,
+    msg: TestMessage,
+    status_aliases: &HashMap<String, TestStatus>,
+) -> Result<(Test, TestFacts), Status> {
+    let id = match msg.id.as_deref() {
+        Some(id) => EntityId::from_string(id)
+            .map_err(|e| ErrorReason::InvalidArgument.status(format!("Invalid test id: {}", e)))?,
+        None => EntityId::new(),
+    };
+    if msg
+        .alignment_score
+        .is_some_and(|score| !(0.0..=1.0).contains(&score))
+    {
+        return Err(ErrorReason::InvalidArgument.status(format!(
+            "Alignment score of test '{}' must be within 0..=1",
+            msg.name
+        )));
+    }
+    if msg.sla_ms == Some(0) {
+        return Err(ErrorReason::InvalidArgument
+            .status(format!("SLA of test '{}' must be positive", msg.name)));
+    }
+    if msg.attempts == Some(0) {
+        return Err(ErrorReason::InvalidArgument
+            .status(format!("Attempts of test '{}' must be positive", msg.name)));
+    }
+    let facts = TestFacts {
+        alignment_score: msg.alignment_score,
+        sla_ms: msg.sla_ms,
+        attempts: msg.attempts,
+    };
+
+    let test = Test {
+        id,
+        run_id,
+        status: TestStatus::from_label_with_aliases(&msg.status, status_aliases),
+        duration_ms: msg.duration_ms,
+        error: msg
+            .error_message
+            .map(|message| TestError::new("error", message)),
+        started_at: test_timestamp(msg.started_at, "started_at")?,
+        completed_at: test_timestamp(msg.completed_at, "completed_at")?,
+        name: msg.name,
+        suite: msg.suite,
+        guidance: msg.guidance,
+        created_at: BiTemporalTime::now(),
+    };
+    Ok((test, facts))
+}
+
+/// Resolve a batch reference to a test by explicit id or by name in the batch
+fn resolve_test_id(
+    test_id_map: &HashMap<String, EntityId>,
+    test_id: Option<&str>,
+    test_name: Option<&str>,
+) -> Result<EntityId, Status> {
+    match (test_id, test_name) {
+        (Some(id), _) => EntityId::from_string(id)
+            .map_err(|e| ErrorReason::InvalidArgument.status(format!("Invalid test_id: {}", e))),
+        (None, Some(name)) => test_id_map.get(name).copied().ok_or_else(|| {
+            ErrorReason::TestNotFound.status(format!("Test '{}' not found in batch", name))
+        }),
+        (None, None) => {
+            Err(ErrorReason::InvalidArgument.status("Either test_id or test_name must be provided"))
+        }
+    }
+}
+
+fn signal_from_batch(
+    run_id: EntityId,
+    test_id_map: &HashMap<String, EntityId>,
+    msg: BatchSignal,
+) -> Result<liminalqa_core::entities::Signal, Status> {
+    Ok(liminalqa_core::entities::Signal {
+        id: EntityId::new(),
+        run_id,
+        // A signal naming no test is a run-level signal
+        test_id: match (msg.test_id.as_deref(), msg.test_name.as_deref()) {
+            (None, None) => None,
+            (test_id, test_name) => Some(resolve_test_id(test_id_map, test_id, test_name)?),
+        },
+        signal_type: SignalType::from_label(&msg.signal_type),
+        timestamp: timestamp(msg.timestamp, "signal")?,
+        latency_ms: msg.latency_ms,
+        payload_ref: None,
+        metadata: msg
+            .metadata
+            .into_iter()
+            .map(|(k, v)| (k, serde_json::Value::String(v)))
+            .collect(),
+        created_at: BiTemporalTime::now(),
+        sequence: 0, // Assigned when stored
+        correlation_id: msg.correlation_id,
+    })
+}
+
+fn artifact_from_batch(
+    run_id: EntityId,
+    test_id_map: &HashMap<String, EntityId>,
+    msg: BatchArtifact,
+) -> Result<Artifact, Status> {
+    Ok(Artifact {
+        id: EntityId::new(),
+        run_id,
+        test_id: resolve_test_id(
+            test_id_map,
+            msg.test_id.as_deref(),
+            msg.test_name.as_deref(),
+        )?,
+        artifact_ref: ArtifactRef {
+            sha256: msg.sha256,
+            location: ArtifactLocation::parse(&msg.location),
+            size_bytes: msg.size_bytes.filter(|&v| v >= 0).unwrap_or(0) as u64,
+            mime_type: msg.mime_type,
+        },
+        artifact_type: ArtifactType::from_label(&msg.kind),
+        description: None,
+        created_at: BiTemporalTime::now(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use liminalqa_core::entities::EntityType;
+    use tempfile::TempDir;
+
+    fn service() -> anyhow::Result<(TempDir, Arc<LiminalDB>, MyIngestService)> {
+        let temp_dir = TempDir::new()?;
+        let db = Arc::new(LiminalDB::open(temp_dir.path())?);
+        let service = MyIngestService::new(db.clone());
+        Ok((temp_dir, db, service))
+    }
+
+    fn batch_request(signal_test: &str) -> IngestBatchRequest {
+        let test = |name: &str, status: &str| TestMessage {
+            name: name.to_string(),
+            suite: "checkout".to_string(),
+            guidance: String::new(),
+            status: status.to_string(),
+            duration_ms: 100,
+            error_message: None,
+            started_at: 0,
+            completed_at: 0,
+            id: None,
+            alignment_score: None,
+            sla_ms: None,
+            attempts: None,
+        };
+
+        IngestBatchRequest {
+            run: Some(IngestRunRequest {
+                build_id: EntityId::new().to_string(),
+                plan_name: "smoke".to_string(),
+                env: r#"{"browser": "chrome"}"#.to_string(),
+                started_at: Utc::now().timestamp_millis(),
+                ended_at: None,
+                runner_version: "test".to_string(),
+                liminal_os_version: None,
+            }),
+            tests: vec![test("test_pay", "pass"), test("test_refund", "fail")],
+            signals: vec![BatchSignal {
+                test_id: None,
+                test_name: Some(signal_test.to_string()),
+                signal_type: "api".to_string(),
+                timestamp: Utc::now().timestamp_millis(),
+                latency_ms: Some(40),
+                metadata: HashMap::from([("status".to_string(), "500".to_string())]),
+                correlation_id: None,
+            }],
+            artifacts: vec![BatchArtifact {
+                test_id: None,
+                test_name: Some("test_refund".to_string()),
+                kind: "screenshot".to_string(),
+                sha256: "abc123".to_string(),
+                location: "s3://qa-artifacts/refund.png".to_string(),
+                size_bytes: Some(2048),
+                mime_type: Some("image/png".to_string()),
+            }],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ingest_batch_stores_everything() -> anyhow::Result<()> {
+        let (_dir, db, service) = service()?;
+
+        let response = service
+            .ingest_batch(Request::new(batch_request("test_pay")))
+            .await?
+            .into_inner();
+
+        let counts = response.counts.expect("counts should be present");
+        assert_eq!(
+            (counts.run, counts.tests, counts.signals, counts.artifacts),
+            (1, 2, 1, 1)
+        );
+        assert_eq!(response.test_id_map.len(), 2);
+
+        let run_id = EntityId::from_string(&response.run_id)?;
+        let pay_id = EntityId::from_string(&response.test_id_map["test_pay"])?;
+        assert_eq!(db.find_test_by_name(run_id, "test_pay")?, Some(pay_id));
+        let signal_id = db.get_entities_by_type(EntityType::Signal)?[0];
+        let signal = db.get_signal(signal_id)?.expect("signal should be stored");
+        assert_eq!(signal.test_id, Some(pay_id));
+        assert_eq!(db.get_entities_by_type(EntityType::Artifact)?.len(), 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ingest_batch_records_test_facts() -> anyhow::Result<()> {
+        use liminalqa_core::facts::Attribute;
+
+        let (_dir, db, ingest) = service()?;
+        let mut request = batch_request("test_pay");
+        request.tests[0].alignment_score = Some(0.75);
+        request.tests[0].sla_ms = Some(250);
+        request.tests[0].attempts = Some(2);
+
+        let response = ingest
+            .ingest_batch(Request::new(request))
+            .await?
+            .into_inner();
+        let pay_id = EntityId::from_string(&response.test_id_map["test_pay"])?;
+        let refund_id = EntityId::from_string(&response.test_id_map["test_refund"])?;
+        let facts: Vec<(EntityId, Attribute, serde_json::Value)> = db
+            .scan_facts_by_entities(&[pay_id, refund_id])?
+            .into_iter()
+            .filter(|f| {
+                matches!(
+                    f.attribute,
+                    Attribute::TestAlignment | Attribute::TestSla | Attribute::TestAttempts
+                )
+            })
+            .map(|f| (f.entity_id, f.attribute, f.value))
+            .collect();
+        assert_eq!(facts.len(), 3);
+        assert!(facts.contains(&(pay_id, Attribute::TestAttempts, serde_json::json!(2))));
+        assert!(facts.contains(&(pay_id, Attribute::TestAlignment, serde_json::json!(0.75))));
+        assert!(facts.contains(&(pay_id, Attribute::TestSla, serde_json::json!(250))));
+
+        // An out-of-range score rejects the batch before anything is stored
+        let (_dir, db, ingest) = service()?;
+        let mut request = batch_request("test_pay");
+        request.tests[1].alignment_score = Some(1.5);
+        let status = ingest
+            .ingest_batch(Request::new(request))
+            .await
+            .expect_err("score above 1 should be rejected");
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert!(db.get_entities_by_type(EntityType::Run)?.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ingest_batch_spills_oversized_signal_metadata() -> anyhow::Result<()> {
+        use liminalqa_core::entities::Artifact;
+
+        let (_dir, db, ingest) = service()?;
+        let spill_dir = TempDir::new()?;
+        let ingest =
+            ingest.with_signal_spillover(Arc::new(SignalSpillover::new(spill_dir.path(), 256)));
+        let mut request = batch_request("test_pay");
+        request.signals[0]
+            .metadata
+            .insert("response_body".to_string(), "x".repeat(1024));
+
+        let response = ingest
+            .ingest_batch(Request::new(request))
+            .await?
+            .into_inner();
+        let pay_id = EntityId::from_string(&response.test_id_map["test_pay"])?;
+
+        let signal_id = db.get_entities_by_type(EntityType::Signal)?[0];
+        let signal = db.get_signal(signal_id)?.expect("signal should be stored");
+        let payload_ref = signal.payload_ref.expect("metadata should be spilled");
+        assert!(!signal.metadata.contains_key("response_body"));
+        let mut spilled = Vec::new();
+        for id in db.get_entities_by_type(EntityType::Artifact)? {
+            let artifact: Artifact = db.get_entity(id)?.expect("artifact should be stored");
+            if artifact.artifact_ref.sha256 == payload_ref.sha256 {
+                spilled.push(artifact);
+            }
+        }
+        assert_eq!(spilled.len(), 1);
+        assert_eq!(spilled[0].test_id, pay_id);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ingest_batch_rolls_back_on_bad_reference() -> anyhow::Result<()> {
+        let (_dir, db, service) = service()?;
+
+        let status = service
+            .ingest_batch(Request::new(batch_request("test_missing")))
+            .await
+            .expect_err("unknown test reference should be rejected");
+        assert_eq!(status.code(), tonic::Code::NotFound);
+
+        // Nothing from the batch was stored
+        for entity_type in [
+            EntityType::Run,
+            EntityType::Test,
+            EntityType::Signal,
+            EntityType::Artifact,
+        ] {
+            assert!(db.get_entities_by_type(entity_type)?.is_empty());
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_storage_failure_carries_reason_code() -> anyhow::Result<()> {
+        use liminalqa_core::{entities::Resonance, types::ResonancePattern};
+
+        let (_dir, db, service) = service()?;
+        service
+            .ingest_batch(Request::new(batch_request("test_pay")))
+            .await?;
+
+        // A resonance naming a signal as its test cannot be summarized, so
+        // storing a completed run fails inside the database
+        let signal_id = db.get_entities_by_type(EntityType::Signal)?[0];
+        db.put_resonance(&Resonance {
+            id: EntityId::new(),
+            detector: "flake".to_string(),
+            pattern: ResonancePattern {
+                pattern_id: EntityId::new(),
+                description: "broken".to_string(),
+                score: 1.0,
+                occurrences: 1,
+                first_seen: Utc::now(),
+                last_seen: Utc::now(),
+            },
+            affected_tests: vec![signal_id],
+            root_cause: None,
+            created_at: BiTemporalTime::now(),
+        })?;
+        let mut run = batch_request("test_pay").run.expect("run");
+        run.ended_at = Some(Utc::now().timestamp_millis());
+
+        let status = service
+            .ingest_run(Request::new(run))
+            .await
+            .expect_err("the write should fail");
+        assert_eq!(status.code(), tonic::Code::Internal);
+        assert!(status.message().starts_with("Failed to store run"));
+        let info = crate::errors::error_info(&status).expect("error info detail");
+        assert_eq!(info.reason, "DB_WRITE_FAILED");
+        assert_eq!(info.domain, crate::errors::ERROR_DOMAIN);
+
+        Ok(())
+    }
 }
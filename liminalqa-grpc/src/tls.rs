@@ -0,0 +1,70 @@
+//! Mutual TLS for the gRPC server
+
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+use tonic::transport::{Certificate, Identity, ServerTlsConfig};
+
+/// Server certificate and the CA client certificates must be signed by.
+///
+/// With this set, the server only accepts TLS connections presenting a valid
+/// client certificate; without it, gRPC is served in plaintext.
+#[derive(Debug, Clone)]
+pub struct MtlsConfig {
+    /// PEM certificate (chain) of the server
+    pub cert_path: PathBuf,
+    /// PEM private key of the server
+    pub key_path: PathBuf,
+    /// PEM CA certificate(s) trusted to sign client certificates
+    pub client_ca_path: PathBuf,
+}
+
+impl MtlsConfig {
+    pub fn new(
+        cert_path: impl Into<PathBuf>,
+        key_path: impl Into<PathBuf>,
+        client_ca_path: impl Into<PathBuf>,
+    ) -> Self {
+        Self {
+            cert_path: cert_path.into(),
+            key_path: key_path.into(),
+            client_ca_path: client_ca_path.into(),
+        }
+    }
+
+    /// Read from LIMINAL_GRPC_TLS_CERT, LIMINAL_GRPC_TLS_KEY and
+    /// LIMINAL_GRPC_TLS_CLIENT_CA. `None` when none is set; setting only some
+    /// of them is an error rather than a silent fallback to plaintext.
+    pub fn from_env() -> Result<Option<Self>> {
+        let vars = [
+            "LIMINAL_GRPC_TLS_CERT",
+            "LIMINAL_GRPC_TLS_KEY",
+            "LIMINAL_GRPC_TLS_CLIENT_CA",
+        ]
+        .map(|name| (name, std::env::var(name).ok()));
+        match vars {
+            [(_, None), (_, None), (_, None)] => Ok(None),
+            [(_, Some(cert)), (_, Some(key)), (_, Some(ca))] => Ok(Some(Self::new(cert, key, ca))),
+            vars => {
+                let missing: Vec<&str> = vars
+                    .iter()
+                    .filter(|(_, value)| value.is_none())
+                    .map(|(name, _)| *name)
+                    .collect();
+                anyhow::bail!("gRPC mTLS is partially configured, missing {:?}", missing)
+            }
+        }
+    }
+
+    /// Load the PEM files into a tonic server TLS config requiring client
+    /// certificates
+    pub fn server_tls_config(&self) -> Result<ServerTlsConfig> {
+        let read = |path: &PathBuf| {
+            std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))
+        };
+        let identity = Identity::from_pem(read(&self.cert_path)?, read(&self.key_path)?);
+        let client_ca = Certificate::from_pem(read(&self.client_ca_path)?);
+        Ok(ServerTlsConfig::new()
+            .identity(identity)
+            .client_ca_root(client_ca))
+    }
+}
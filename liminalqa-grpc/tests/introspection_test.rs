@@ -0,0 +1,78 @@
+use liminalqa_db::LiminalDB;
+use liminalqa_grpc::{health_service, reflection_service, IngestServiceServer, MyIngestService};
+use std::sync::Arc;
+use tokio_stream::{wrappers::TcpListenerStream, StreamExt};
+use tonic::transport::{Channel, Server};
+use tonic_health::pb::{
+    health_check_response::ServingStatus, health_client::HealthClient, HealthCheckRequest,
+};
+use tonic_reflection::pb::{
+    server_reflection_client::ServerReflectionClient, server_reflection_request::MessageRequest,
+    server_reflection_response::MessageResponse, ServerReflectionRequest,
+};
+
+/// Serve health, reflection and ingest on an ephemeral port
+async fn start_server() -> anyhow::Result<(tempfile::TempDir, Channel)> {
+    let temp_dir = tempfile::TempDir::new()?;
+    let db = Arc::new(LiminalDB::open(temp_dir.path())?);
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let router = Server::builder()
+        .add_service(health_service().await)
+        .add_service(reflection_service()?)
+        .add_service(IngestServiceServer::new(MyIngestService::new(db)));
+    tokio::spawn(router.serve_with_incoming(TcpListenerStream::new(listener)));
+
+    let channel = Channel::from_shared(format!("http://{}", addr))?
+        .connect()
+        .await?;
+    Ok((temp_dir, channel))
+}
+
+#[tokio::test]
+async fn test_health_reports_ingest_service_serving() -> anyhow::Result<()> {
+    let (_dir, channel) = start_server().await?;
+    let mut client = HealthClient::new(channel);
+
+    let response = client
+        .check(HealthCheckRequest {
+            service: "liminalqa.v1.IngestService".to_string(),
+        })
+        .await?
+        .into_inner();
+    assert_eq!(response.status(), ServingStatus::Serving);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reflection_lists_services() -> anyhow::Result<()> {
+    let (_dir, channel) = start_server().await?;
+    let mut client = ServerReflectionClient::new(channel);
+
+    let request = ServerReflectionRequest {
+        host: String::new(),
+        message_request: Some(MessageRequest::ListServices(String::new())),
+    };
+    let mut responses = client
+        .server_reflection_info(tokio_stream::iter([request]))
+        .await?
+        .into_inner();
+    let response = responses
+        .next()
+        .await
+        .ok_or_else(|| anyhow::anyhow!("no reflection response"))??;
+
+    let Some(MessageResponse::ListServicesResponse(list)) = response.message_response else {
+        anyhow::bail!(
+            "unexpected reflection response: {:?}",
+            response.message_response
+        );
+    };
+    let names: Vec<String> = list.service.into_iter().map(|s| s.name).collect();
+    assert!(names.contains(&"liminalqa.v1.IngestService".to_string()));
+    assert!(names.contains(&"grpc.health.v1.Health".to_string()));
+
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use liminalqa_grpc::{health_service, MtlsConfig};
+use rcgen::{BasicConstraints, CertificateParams, IsCa, KeyPair};
+use std::path::Path;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::transport::{Certificate, Channel, ClientTlsConfig, Identity, Server};
+use tonic_health::pb::{health_client::HealthClient, HealthCheckRequest};
+
+struct Pki {
+    ca: String,
+    server: (String, String),
+    client: (String, String),
+}
+
+/// A CA, a server certificate for localhost and a client certificate, all
+/// signed by the CA
+fn generate_pki() -> anyhow::Result<Pki> {
+    let ca_key = KeyPair::generate()?;
+    let mut ca_params = CertificateParams::new(Vec::new())?;
+    ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    let ca = ca_params.self_signed(&ca_key)?;
+
+    let server_key = KeyPair::generate()?;
+    let server = CertificateParams::new(vec!["localhost".to_string()])?.signed_by(
+        &server_key,
+        &ca,
+        &ca_key,
+    )?;
+    let client_key = KeyPair::generate()?;
+    let client =
+        CertificateParams::new(vec!["runner".to_string()])?.signed_by(&client_key, &ca, &ca_key)?;
+
+    Ok(Pki {
+        ca: ca.pem(),
+        server: (server.pem(), server_key.serialize_pem()),
+        client: (client.pem(), client_key.serialize_pem()),
+    })
+}
+
+/// Serve health over mTLS on an ephemeral port, returning its address
+async fn start_server(dir: &Path, pki: &Pki) -> anyhow::Result<std::net::SocketAddr> {
+    let config = MtlsConfig::new(
+        dir.join("server.pem"),
+        dir.join("server.key"),
+        dir.join("ca.pem"),
+    );
+    std::fs::write(&config.cert_path, &pki.server.0)?;
+    std::fs::write(&config.key_path, &pki.server.1)?;
+    std::fs::write(&config.client_ca_path, &pki.ca)?;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let router = Server::builder()
+        .tls_config(config.server_tls_config()?)?
+        .add_service(health_service().await);
+    tokio::spawn(router.serve_with_incoming(TcpListenerStream::new(listener)));
+    Ok(addr)
+}
+
+async fn check_health(
+    addr: std::net::SocketAddr,
+    tls: ClientTlsConfig,
+) -> anyhow::Result<tonic_health::pb::HealthCheckResponse> {
+    let channel = Channel::from_shared(format!("https://localhost:{}", addr.port()))?
+        .tls_config(tls)?
+        .connect()
+        .await?;
+    let response = HealthClient::new(channel)
+        .check(HealthCheckRequest {
+            service: String::new(),
+        })
+        .await?;
+    Ok(response.into_inner())
+}
+
+#[tokio::test]
+async fn test_mtls_requires_client_certificate() -> anyhow::Result<()> {
+    let dir = tempfile::TempDir::new()?;
+    let pki = generate_pki()?;
+    let addr = start_server(dir.path(), &pki).await?;
+    let client_tls = || {
+        ClientTlsConfig::new()
+            .ca_certificate(Certificate::from_pem(&pki.ca))
+            .domain_name("localhost")
+    };
+
+    let with_cert = client_tls().identity(Identity::from_pem(&pki.client.0, &pki.client.1));
+    check_health(addr, with_cert).await?;
+
+    assert!(check_health(addr, client_tls()).await.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_missing_pem_files_are_reported() {
+    let config = MtlsConfig::new("/nonexistent/server.pem", "server.key", "ca.pem");
+    let err = config.server_tls_config().unwrap_err();
+    assert!(err.to_string().contains("/nonexistent/server.pem"));
+}
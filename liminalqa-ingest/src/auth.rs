@@ -0,0 +1,197 @@
+//! Bearer token authentication: static shared token or JWT
+
+use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Permission granted to a token
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    /// Write runs, tests, signals and artifacts (`/ingest/*`) and cancel
+    /// runs (`/runs/:id/cancel`)
+    Ingest,
+    /// Read data and metrics (`/query`, `/api/*`, `/metrics`)
+    Query,
+    /// Everything, including routes of the other scopes
+    Admin,
+}
+
+impl Scope {
+    /// Every scope, what the static `auth_token` grants
+    pub const ALL: [Scope; 3] = [Scope::Ingest, Scope::Query, Scope::Admin];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Scope::Ingest => "ingest",
+            Scope::Query => "query",
+            Scope::Admin => "admin",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label.trim().to_lowercase().as_str() {
+            "ingest" => Some(Scope::Ingest),
+            "query" => Some(Scope::Query),
+            "admin" => Some(Scope::Admin),
+            _ => None,
+        }
+    }
+
+    /// Scope a request to `path` needs; unknown paths need `admin`
+    pub fn required_for(path: &str) -> Scope {
+        if path.starts_with("/ingest/") || path.starts_with("/runs/") {
+            Scope::Ingest
+        } else if path == "/query" || path.starts_with("/api/") || path.starts_with("/metrics") {
+            Scope::Query
+        } else {
+            Scope::Admin
+        }
+    }
+}
+
+/// Scopes of the request's token, attached as a request extension by the
+/// auth middleware (every scope when auth is disabled)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrantedScopes(pub HashSet<Scope>);
+
+impl GrantedScopes {
+    pub fn allows(&self, required: Scope) -> bool {
+        allows(&self.0, required)
+    }
+}
+
+/// Whether a token holding `granted` may use a route needing `required`
+pub fn allows(granted: &HashSet<Scope>, required: Scope) -> bool {
+    granted.contains(&required) || granted.contains(&Scope::Admin)
+}
+
+/// Parse space- or comma-separated scope labels; an unknown label is an
+/// error rather than a scope quietly left out
+pub fn parse_scopes(labels: &str) -> anyhow::Result<HashSet<Scope>> {
+    labels
+        .split([' ', ','])
+        .filter(|label| !label.trim().is_empty())
+        .map(|label| {
+            Scope::from_label(label).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Unknown scope '{}' (expected ingest, query or admin)",
+                    label.trim()
+                )
+            })
+        })
+        .collect()
+}
+
+/// Claims read from a validated JWT
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: Option<String>,
+    pub exp: u64,
+    pub iss: Option<String>,
+    pub aud: Option<serde_json::Value>,
+    /// Space-separated scopes (OAuth 2.0 `scope` claim); none when absent
+    #[serde(default)]
+    pub scope: Option<String>,
+}
+
+impl Claims {
+    /// Scopes of this service in the `scope` claim; labels the IdP issued
+    /// for other services are ignored
+    pub fn scopes(&self) -> HashSet<Scope> {
+        self.scope
+            .as_deref()
+            .unwrap_or_default()
+            .split([' ', ','])
+            .filter_map(Scope::from_label)
+            .collect()
+    }
+}
+
+/// Validation settings for JWT bearer tokens issued by an IdP
+#[derive(Clone)]
+pub struct JwtConfig {
+    key: DecodingKey,
+    validation: Validation,
+}
+
+impl JwtConfig {
+    /// Tokens signed with a shared HMAC secret
+    pub fn hs256(secret: &[u8]) -> Self {
+        Self::new(DecodingKey::from_secret(secret), Algorithm::HS256)
+    }
+
+    /// Tokens signed with the private half of an RSA public key (PEM)
+    pub fn rs256_pem(public_key_pem: &[u8]) -> anyhow::Result<Self> {
+        let key = DecodingKey::from_rsa_pem(public_key_pem)
+            .map_err(|e| anyhow::anyhow!("Invalid RS256 public key: {}", e))?;
+        Ok(Self::new(key, Algorithm::RS256))
+    }
+
+    fn new(key: DecodingKey, algorithm: Algorithm) -> Self {
+        let mut validation = Validation::new(algorithm);
+        // Audience is only checked once one is configured
+        validation.validate_aud = false;
+        Self { key, validation }
+    }
+
+    /// Require the `iss` claim to equal `issuer`
+    pub fn with_issuer(mut self, issuer: impl Into<String>) -> Self {
+        self.validation.set_issuer(&[issuer.into()]);
+        self.validation
+            .required_spec_claims
+            .insert("iss".to_string());
+        self
+    }
+
+    /// Require the `aud` claim to contain `audience`
+    pub fn with_audience(mut self, audience: impl Into<String>) -> Self {
+        self.validation.set_audience(&[audience.into()]);
+        self.validation.validate_aud = true;
+        self.validation
+            .required_spec_claims
+            .insert("aud".to_string());
+        self
+    }
+
+    /// Check signature, expiry and the configured issuer and audience
+    pub fn validate(&self, token: &str) -> Result<Claims, jsonwebtoken::errors::Error> {
+        decode::<Claims>(token, &self.key, &self.validation).map(|data| data.claims)
+    }
+}
+
+impl std::fmt::Debug for JwtConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JwtConfig")
+            .field("algorithms", &self.validation.algorithms)
+            .field("iss", &self.validation.iss)
+            .field("aud", &self.validation.aud)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_scopes_rejects_unknown_labels() {
+        assert_eq!(
+            parse_scopes("ingest, query").unwrap(),
+            HashSet::from([Scope::Ingest, Scope::Query])
+        );
+        assert!(parse_scopes("").unwrap().is_empty());
+        let err = parse_scopes("ingest,qeury").unwrap_err();
+        assert!(err.to_string().contains("'qeury'"), "{}", err);
+
+        // A JWT may carry scopes of other services
+        let claims = Claims {
+            sub: None,
+            exp: 0,
+            iss: None,
+            aud: None,
+            scope: Some("openid query".to_string()),
+        };
+        assert_eq!(claims.scopes(), HashSet::from([Scope::Query]));
+    }
+}
@@ -1,12 +1,17 @@
 use liminalqa_core::{
-    baseline::DriftDetector,
-    entities::Test,
+    baseline::{Baseline, DriftThresholds},
+    entities::{Signal, Test},
     metrics::{BaselineLabels, SharedMetrics},
 };
 use liminalqa_db::LiminalDB;
 use tracing::{info, warn};
 
-pub fn check_baseline_drift(db: &LiminalDB, metrics: &SharedMetrics, test: &Test) {
+pub fn check_baseline_drift(
+    db: &LiminalDB,
+    metrics: &SharedMetrics,
+    thresholds: &DriftThresholds,
+    test: &Test,
+) {
     // 1. Get history (durations)
     // We need enough samples for meaningful stats. e.g. 50?
     let history = match db.get_test_history(&test.name, &test.suite, 50) {
@@ -23,9 +28,16 @@ pub fn check_baseline_drift(db: &LiminalDB, metrics: &SharedMetrics, test: &Test
 
     let durations: Vec<f64> = history.iter().map(|t| t.duration_ms as f64).collect();
 
-    // 2. Calculate Stats
-    let detector = DriftDetector::default();
-    let (mean, stddev) = detector.calculate_stats(&durations);
+    // 2. Calculate and store the baseline (provisional with too few samples)
+    let detector = thresholds.detector_for(&test.suite);
+    let baseline =
+        match db.upsert_baseline(&Baseline::from_samples(&test.name, &test.suite, &durations)) {
+            Ok(baseline) => baseline,
+            Err(e) => {
+                warn!("Failed to store baseline for {}: {}", test.name, e);
+                return;
+            }
+        };
 
     // 3. Update Metrics
     let labels = BaselineLabels {
@@ -37,20 +49,55 @@ pub fn check_baseline_drift(db: &LiminalDB, metrics: &SharedMetrics, test: &Test
     metrics
         .baseline_duration_mean
         .get_or_create(&labels)
-        .set(mean as i64);
+        .set(baseline.mean_ms as i64);
 
     metrics
         .baseline_duration_stddev
         .get_or_create(&labels)
-        .set(stddev as i64);
+        .set(baseline.stddev_ms as i64);
 
-    // 4. Check Drift (logging only, Prometheus handles alerts)
+    // 4. Check Drift (logging only, Prometheus handles alerts); provisional
+    // baselines are skipped
     let current_duration = test.duration_ms as f64;
 
-    if detector.is_drift(current_duration, mean, stddev) {
+    if detector.is_drift_from(current_duration, &baseline) {
         info!(
             "Drift detected for test {} (Duration: {}ms, Mean: {:.1}ms, StdDev: {:.1}ms)",
-            test.name, current_duration, mean, stddev
+            test.name, current_duration, baseline.mean_ms, baseline.stddev_ms
         );
     }
 }
+
+/// Flag `signal` when its latency exceeds the latency baseline of its test
+/// and signal type, then add the latency to that baseline. Run-level
+/// signals and signals without a latency have no baseline.
+pub fn check_signal_latency(
+    db: &LiminalDB,
+    thresholds: &DriftThresholds,
+    signal: &Signal,
+) -> anyhow::Result<()> {
+    let (Some(test_id), Some(latency_ms)) = (signal.test_id, signal.latency_ms) else {
+        return Ok(());
+    };
+    let Some(test) = db.get_entity::<Test>(test_id)? else {
+        return Ok(());
+    };
+
+    // Judged against the baseline before this latency joins it, so an
+    // outlier does not widen its own band
+    let baseline = db.get_latency_baseline(&test.name, &test.suite, signal.signal_type)?;
+    if let Some(anomaly) = baseline.and_then(|b| {
+        thresholds
+            .detector_for(&test.suite)
+            .latency_anomaly(signal, &b)
+    }) {
+        info!(
+            "Latency anomaly for {:?} signal of test {} ({}ms, mean {:.1}ms, z {:.1})",
+            signal.signal_type, test.name, latency_ms, anomaly.mean_ms, anomaly.z_score
+        );
+        db.put_latency_anomaly(signal, &anomaly)?;
+    }
+
+    db.record_signal_latency(&test.name, &test.suite, signal.signal_type, latency_ms)?;
+    Ok(())
+}
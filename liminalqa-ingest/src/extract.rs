@@ -0,0 +1,112 @@
+//! Request extractors
+
+use crate::{ApiResponse, AppState};
+use axum::{
+    async_trait,
+    body::Bytes,
+    extract::{FromRequest, FromRequestParts, Request},
+    http::{header, request::Parts, StatusCode},
+    Json,
+};
+use liminalqa_db::LiminalDB;
+use serde::de::DeserializeOwned;
+use std::sync::Arc;
+
+/// Header selecting the tenant database for a request
+pub const TENANT_HEADER: &str = "x-tenant-id";
+
+/// Database of the tenant named by the `X-Tenant-Id` header.
+///
+/// Requests without the header, or with [`crate::DEFAULT_TENANT`], use the
+/// default database in [`AppState::db`]. Unknown tenants are rejected with
+/// a 400.
+pub struct TenantDb(pub Arc<LiminalDB>);
+
+#[async_trait]
+impl FromRequestParts<AppState> for TenantDb {
+    type Rejection = (StatusCode, Json<ApiResponse>);
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let Some(header) = parts.headers.get(TENANT_HEADER) else {
+            return Ok(TenantDb(state.db.clone()));
+        };
+
+        let tenant = header.to_str().map_err(|_| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::error("Invalid X-Tenant-Id header")),
+            )
+        })?;
+
+        state.tenant_db(tenant).map(TenantDb).ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::error(format!("Unknown tenant: {}", tenant))),
+            )
+        })
+    }
+}
+
+/// JSON body extractor that reports where deserialization failed.
+///
+/// Behaves like [`axum::Json`], but a body that does not match the DTO is
+/// rejected with a 422 whose message names the JSON path of the first
+/// offending field (e.g. `tests[0].duration_ms`).
+pub struct JsonBody<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequest<S> for JsonBody<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, Json<ApiResponse>);
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let is_json = req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("application/json"));
+        if !is_json {
+            return Err((
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                Json(ApiResponse::error(
+                    "Expected request with `Content-Type: application/json`",
+                )),
+            ));
+        }
+
+        let bytes = Bytes::from_request(req, state).await.map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::error(format!(
+                    "Failed to read request body: {}",
+                    e
+                ))),
+            )
+        })?;
+
+        let de = &mut serde_json::Deserializer::from_slice(&bytes);
+        serde_path_to_error::deserialize(de)
+            .map(JsonBody)
+            .map_err(|e| {
+                let status = if e.inner().is_syntax() || e.inner().is_eof() {
+                    StatusCode::BAD_REQUEST
+                } else {
+                    StatusCode::UNPROCESSABLE_ENTITY
+                };
+                (
+                    status,
+                    Json(ApiResponse::error(format!(
+                        "Invalid JSON at `{}`: {}",
+                        e.path(),
+                        e.inner()
+                    ))),
+                )
+            })
+    }
+}
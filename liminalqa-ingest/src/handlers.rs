@@ -1,18 +1,35 @@
 //! HTTP request handlers
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
-use liminalqa_core::{entities::*, metrics::TestLabels, temporal::BiTemporalTime, types::*};
+use axum::{
+    extract::{self, Path, State},
+    http::{header, HeaderMap, StatusCode},
+    response::IntoResponse,
+    Extension, Json,
+};
+use liminalqa_core::{
+    entities::*,
+    metrics::TestLabels,
+    signal_schema::{SchemaEnforcement, SignalSchemas},
+    temporal::BiTemporalTime,
+    types::*,
+};
 use liminalqa_db::{
     query::{Query, QueryResult},
-    LiminalDB,
+    DbError, LiminalDB,
 };
 use serde::{Deserialize, Serialize};
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 use crate::{
-    baseline::check_baseline_drift, resonance::check_and_record_flakiness, ApiResponse, AppState,
+    auth::{GrantedScopes, Scope},
+    baseline::{check_baseline_drift, check_signal_latency},
+    extract::{JsonBody, TenantDb},
+    http_metrics::BatchOutcome,
+    jobs::JobAccepted,
+    resonance::check_and_record_patterns,
+    ApiResponse, AppState,
 };
 
 // --- DTOs ---
@@ -26,6 +43,14 @@ pub struct RunDto {
     pub env: serde_json::Value,
     pub started_at: chrono::DateTime<chrono::Utc>,
     pub runner_version: Option<String>,
+    /// CI metadata such as the PR number or the triggering user, stored as
+    /// a `:run/annotations` fact
+    #[serde(default)]
+    pub annotations: HashMap<String, String>,
+    /// Backdated knowledge time, see [`known_at`]. In a batch it applies to
+    /// every entity of the batch.
+    #[serde(default)]
+    pub tx_time: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 /// POST /ingest/tests — Ingest multiple tests
@@ -35,6 +60,9 @@ pub struct TestsDto {
     pub tests: Vec<TestDtoItem>,
     #[allow(dead_code)]
     pub valid_from: chrono::DateTime<chrono::Utc>,
+    /// Backdated knowledge time, see [`known_at`]
+    #[serde(default)]
+    pub tx_time: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -43,10 +71,37 @@ pub struct TestDtoItem {
     pub suite: String,
     pub guidance: Option<String>,
     pub status: String,
-    pub duration_ms: Option<i32>,
-    pub error: Option<serde_json::Value>,
+    /// Non-negative, up to `i64::MAX`
+    pub duration_ms: Option<i64>,
+    pub error: Option<TestError>,
     pub started_at: Option<chrono::DateTime<chrono::Utc>>,
     pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Fraction of the guidance observables the test met, in `0..=1`
+    #[serde(default)]
+    pub alignment_score: Option<f64>,
+    /// Team or person responsible for the test
+    #[serde(default)]
+    pub owner: Option<String>,
+    /// Longest the test is expected to take, reported as an SLA breach
+    /// when exceeded
+    #[serde(default)]
+    pub sla_ms: Option<u64>,
+    /// Attempts the test took; a pass after more than one is reported
+    /// apart from clean passes
+    #[serde(default)]
+    pub attempts: Option<u32>,
+}
+
+/// POST /ingest/tests/:id/progress — Report a phase of a running test
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TestProgressDto {
+    /// Run the test belongs to
+    pub run_id: EntityId,
+    pub phase: String,
+    pub percent: Option<u8>,
+    pub message: Option<String>,
+    /// When the phase completed; defaults to now
+    pub at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 /// POST /ingest/signals — Ingest signals
@@ -54,6 +109,9 @@ pub struct TestDtoItem {
 pub struct SignalsDto {
     pub run_id: EntityId,
     pub signals: Vec<SignalDtoItem>,
+    /// Backdated knowledge time, see [`known_at`]
+    #[serde(default)]
+    pub tx_time: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -65,6 +123,13 @@ pub struct SignalDtoItem {
     pub value: Option<f64>,
     pub meta: Option<serde_json::Value>,
     pub at: chrono::DateTime<chrono::Utc>,
+    #[serde(default)]
+    pub correlation_id: Option<String>,
+    /// Full payload kept out of `meta`, e.g. a large API response body:
+    /// the `artifact_id` returned by `/ingest/artifacts/upload`. Read it back
+    /// with `GET /api/signals/:id/payload`.
+    #[serde(default)]
+    pub payload_artifact_id: Option<EntityId>,
 }
 
 /// POST /ingest/artifacts — Ingest artifacts
@@ -72,6 +137,9 @@ pub struct SignalDtoItem {
 pub struct ArtifactsDto {
     pub run_id: EntityId,
     pub artifacts: Vec<ArtifactDtoItem>,
+    /// Backdated knowledge time, see [`known_at`]
+    #[serde(default)]
+    pub tx_time: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -80,7 +148,11 @@ pub struct ArtifactDtoItem {
     pub test_name: Option<String>,
     pub kind: String,
     pub path_sha256: String,
+    /// Legacy location string, used when `location` is absent
+    #[serde(default)]
     pub path: String,
+    #[serde(default)]
+    pub location: Option<ArtifactLocation>,
     pub size_bytes: Option<i64>,
     pub mime_type: Option<String>,
 }
@@ -104,7 +176,11 @@ pub struct BatchIngestResponse {
     pub message: String,
     pub counts: BatchCounts,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub test_id_map: Option<HashMap<String, EntityId>>,
+    pub test_id_map: Option<BTreeMap<String, EntityId>>,
+    /// Signals and artifacts attached to each test, keyed by test name
+    /// (or test id when the test is not part of the batch)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub per_test: Option<BTreeMap<String, TestAttachmentCounts>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub partial_counts: Option<BatchCounts>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -119,15 +195,84 @@ pub struct BatchCounts {
     pub artifacts: usize,
 }
 
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq, Eq)]
+pub struct TestAttachmentCounts {
+    pub signals: usize,
+    pub artifacts: usize,
+}
+
 // --- Helper Functions ---
 
-fn create_run_from_dto(dto: &RunDto) -> Result<Run, String> {
-    let env = match serde_json::from_value::<std::collections::HashMap<String, String>>(
-        dto.env.clone(),
-    ) {
-        Ok(env) => env,
-        Err(e) => return Err(format!("Invalid env format: {}", e)),
+/// Bitemporal time of entities ingested with an optional `tx_time` override.
+///
+/// Importing history from another system needs entities to carry the time
+/// they were originally known, so timeshift queries replay the old system's
+/// view. Backdating rewrites what the database claims to have known, so only
+/// tokens with the `admin` scope may do it; a future `tx_time` is rejected.
+/// Routes outside the auth middleware (no [`GrantedScopes`]) have auth off.
+pub(crate) fn known_at(
+    scopes: Option<&GrantedScopes>,
+    tx_time: Option<chrono::DateTime<chrono::Utc>>,
+) -> Result<BiTemporalTime, (StatusCode, Json<ApiResponse>)> {
+    let Some(tx_time) = tx_time else {
+        return Ok(BiTemporalTime::now());
     };
+    if scopes.is_some_and(|s| !s.allows(Scope::Admin)) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(ApiResponse::error(
+                "Forbidden: overriding tx_time needs the 'admin' scope",
+            )),
+        ));
+    }
+    if tx_time > chrono::Utc::now() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(format!(
+                "tx_time {} is in the future",
+                tx_time
+            ))),
+        ));
+    }
+    Ok(BiTemporalTime::with_times(tx_time, tx_time))
+}
+
+/// Reject alignment scores outside `0..=1` and blank owners before anything
+/// is stored
+fn check_test_items(tests: &[TestDtoItem]) -> Result<(), String> {
+    for t in tests {
+        if t.alignment_score
+            .is_some_and(|score| !(0.0..=1.0).contains(&score))
+        {
+            return Err(format!(
+                "Alignment score of test '{}' must be within 0..=1",
+                t.name
+            ));
+        }
+        if t.owner.as_ref().is_some_and(|o| o.trim().is_empty()) {
+            return Err(format!("Owner of test '{}' must not be empty", t.name));
+        }
+        if t.sla_ms == Some(0) {
+            return Err(format!("SLA of test '{}' must be positive", t.name));
+        }
+        if t.duration_ms.is_some_and(|d| d < 0) {
+            return Err(format!(
+                "Duration of test '{}' must not be negative",
+                t.name
+            ));
+        }
+        if t.attempts == Some(0) {
+            return Err(format!("Attempts of test '{}' must be positive", t.name));
+        }
+    }
+    Ok(())
+}
+
+fn create_run_from_dto(dto: &RunDto, created_at: BiTemporalTime) -> Result<Run, String> {
+    let env = Environment::from_json(&dto.env).map_err(|e| format!("Invalid env format: {}", e))?;
+    if dto.annotations.keys().any(|key| key.trim().is_empty()) {
+        return Err("Run annotation keys must not be empty".to_string());
+    }
 
     Ok(Run {
         id: dto.run_id,
@@ -141,19 +286,59 @@ fn create_run_from_dto(dto: &RunDto) -> Result<Run, String> {
             .clone()
             .unwrap_or_else(|| "unknown".to_string()),
         liminal_os_version: None,
-        created_at: BiTemporalTime::now(),
+        created_at,
     })
 }
 
-fn create_test_from_dto(run_id: EntityId, item: &TestDtoItem) -> Test {
-    let status = match item.status.to_lowercase().as_str() {
-        "pass" | "passed" | "success" => TestStatus::Pass,
-        "fail" | "failed" | "error" => TestStatus::Fail,
-        "xfail" => TestStatus::XFail,
-        "flake" | "flaky" => TestStatus::Flake,
-        "timeout" => TestStatus::Timeout,
-        _ => TestStatus::Skip,
+/// Store `run` with the annotations of its DTO, provided the stored run is
+/// still at `expected_version`; returns the run's new version
+fn store_run(
+    db: &LiminalDB,
+    run: &Run,
+    dto: &RunDto,
+    expected_version: Option<u64>,
+) -> anyhow::Result<u64> {
+    let version = db.put_run_if_version(run, expected_version)?;
+    if !dto.annotations.is_empty() {
+        db.put_run_annotations(run, &dto.annotations)?;
+    }
+    Ok(version)
+}
+
+/// The run version an `If-Match` header expects, as sent back in `ETag`
+/// (`"3"`); `None` without the header, which updates unconditionally
+pub(crate) fn expected_run_version(
+    headers: &HeaderMap,
+) -> Result<Option<u64>, (StatusCode, Json<ApiResponse>)> {
+    let Some(value) = headers.get(header::IF_MATCH) else {
+        return Ok(None);
     };
+    value
+        .to_str()
+        .ok()
+        .and_then(|v| v.trim().trim_matches('"').parse().ok())
+        .map(Some)
+        .ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::error(
+                    "If-Match must hold a run version, e.g. \"3\"",
+                )),
+            )
+        })
+}
+
+fn run_version_etag(version: u64) -> [(header::HeaderName, String); 1] {
+    [(header::ETAG, format!("\"{}\"", version))]
+}
+
+pub(crate) fn create_test_from_dto(
+    run_id: EntityId,
+    item: &TestDtoItem,
+    status_aliases: &HashMap<String, TestStatus>,
+    created_at: BiTemporalTime,
+) -> Test {
+    let status = TestStatus::from_label_with_aliases(&item.status, status_aliases);
 
     Test {
         id: EntityId::new(),
@@ -162,61 +347,69 @@ fn create_test_from_dto(run_id: EntityId, item: &TestDtoItem) -> Test {
         suite: item.suite.clone(),
         guidance: item.guidance.clone().unwrap_or_default(),
         status,
-        duration_ms: item.duration_ms.unwrap_or(0) as u64,
-        error: item
-            .error
-            .as_ref()
-            .and_then(|e| serde_json::from_value(e.clone()).ok()),
+        duration_ms: item.duration_ms.map_or(0, |d| d.max(0) as u64),
+        error: item.error.clone(),
         started_at: item.started_at.unwrap_or_else(chrono::Utc::now),
         completed_at: item.completed_at.unwrap_or_else(chrono::Utc::now),
-        created_at: BiTemporalTime::now(),
+        created_at,
     }
 }
 
-fn create_signal_from_dto(run_id: EntityId, test_id: EntityId, item: &SignalDtoItem) -> Signal {
-    let signal_type = match item.kind.to_lowercase().as_str() {
-        "ui" => SignalType::UI,
-        "api" => SignalType::API,
-        "websocket" | "ws" => SignalType::WebSocket,
-        "grpc" => SignalType::GRPC,
-        "database" | "db" => SignalType::Database,
-        "network" => SignalType::Network,
-        _ => SignalType::System,
-    };
-
-    let metadata = item
-        .meta
-        .as_ref()
-        .and_then(|m| serde_json::from_value(m.clone()).ok())
-        .unwrap_or_default();
-
+fn create_signal_from_dto(
+    run_id: EntityId,
+    test_id: Option<EntityId>,
+    item: &SignalDtoItem,
+    payload_ref: Option<ArtifactRef>,
+    created_at: BiTemporalTime,
+) -> Signal {
     Signal {
         id: EntityId::new(),
         run_id,
         test_id,
-        signal_type,
+        signal_type: SignalType::from_label(&item.kind),
         timestamp: item.at,
         latency_ms: item.latency_ms,
-        payload_ref: None,
-        metadata,
-        created_at: BiTemporalTime::now(),
+        payload_ref,
+        metadata: signal_metadata(item),
+        created_at,
+        sequence: 0, // Assigned when stored
+        correlation_id: item.correlation_id.clone(),
+    }
+}
+
+fn signal_metadata(item: &SignalDtoItem) -> BTreeMap<String, serde_json::Value> {
+    item.meta
+        .as_ref()
+        .and_then(|m| serde_json::from_value(m.clone()).ok())
+        .unwrap_or_default()
+}
+
+/// Refuse signals lacking metadata their kind requires, when `schemas`
+/// reject them
+fn check_signal_items(schemas: &SignalSchemas, signals: &[SignalDtoItem]) -> Result<(), String> {
+    if schemas.enforcement != SchemaEnforcement::Reject {
+        return Ok(());
+    }
+    for item in signals {
+        let signal_type = SignalType::from_label(&item.kind);
+        if let Some(violation) = schemas.check(signal_type, &signal_metadata(item)) {
+            return Err(format!(
+                "{} signal is missing required metadata: {}",
+                item.kind,
+                violation.missing.join(", ")
+            ));
+        }
     }
+    Ok(())
 }
 
 fn create_artifact_from_dto(
     run_id: EntityId,
     test_id: EntityId,
     item: &ArtifactDtoItem,
+    created_at: BiTemporalTime,
 ) -> Artifact {
-    let artifact_type = match item.kind.to_lowercase().as_str() {
-        "screenshot" => ArtifactType::Screenshot,
-        "apiresponse" => ArtifactType::ApiResponse,
-        "wsmessage" => ArtifactType::WsMessage,
-        "grpctrace" => ArtifactType::GrpcTrace,
-        "log" => ArtifactType::Log,
-        "video" => ArtifactType::Video,
-        _ => ArtifactType::Trace,
-    };
+    let artifact_type = ArtifactType::from_label(&item.kind);
 
     Artifact {
         id: EntityId::new(),
@@ -224,7 +417,10 @@ fn create_artifact_from_dto(
         test_id,
         artifact_ref: ArtifactRef {
             sha256: item.path_sha256.clone(),
-            path: item.path.clone(),
+            location: item
+                .location
+                .clone()
+                .unwrap_or_else(|| ArtifactLocation::parse(&item.path)),
             size_bytes: item
                 .size_bytes
                 .filter(|&v| v >= 0)
@@ -234,10 +430,27 @@ fn create_artifact_from_dto(
         },
         artifact_type,
         description: None,
-        created_at: BiTemporalTime::now(),
+        created_at,
     }
 }
 
+/// `per_test` key for an attachment: its test's name when known, else the id
+fn attachment_key(
+    test_id_map: &HashMap<String, EntityId>,
+    test_id: EntityId,
+    test_name: Option<&str>,
+) -> String {
+    test_name
+        .map(str::to_string)
+        .or_else(|| {
+            test_id_map
+                .iter()
+                .find(|(_, &id)| id == test_id)
+                .map(|(name, _)| name.clone())
+        })
+        .unwrap_or_else(|| test_id.to_string())
+}
+
 fn resolve_test_id(
     db: &LiminalDB,
     test_id_map: &HashMap<String, EntityId>,
@@ -257,6 +470,7 @@ fn resolve_test_id(
                         message: "Either test_id or test_name must be provided".to_string(),
                         counts: BatchCounts::default(),
                         test_id_map: None,
+                        per_test: None,
                         partial_counts: Some(current_counts.clone()),
                         error_details: None,
                     }),
@@ -278,6 +492,7 @@ fn resolve_test_id(
                         message: format!("Test '{}' not found", name),
                         counts: BatchCounts::default(),
                         test_id_map: None,
+                        per_test: None,
                         partial_counts: Some(current_counts.clone()),
                         error_details: Some(format!(
                             "Test '{}' not found in batch or database",
@@ -292,6 +507,7 @@ fn resolve_test_id(
                         message: "Database error".to_string(),
                         counts: BatchCounts::default(),
                         test_id_map: None,
+                        per_test: None,
                         partial_counts: Some(current_counts.clone()),
                         error_details: Some(format!("DB lookup failed: {}", e)),
                     }),
@@ -303,83 +519,132 @@ fn resolve_test_id(
 
 // --- Handlers ---
 
+/// POST /ingest/run — Ingest a run
+///
+/// With an `If-Match: "<version>"` header the run is only replaced while it
+/// is still at that version, else 409 Conflict. The new version comes back
+/// in `ETag`.
 pub async fn ingest_run(
-    State(state): State<AppState>,
-    Json(dto): Json<RunDto>,
+    TenantDb(db): TenantDb,
+    scopes: Option<Extension<GrantedScopes>>,
+    headers: HeaderMap,
+    JsonBody(dto): JsonBody<RunDto>,
 ) -> impl IntoResponse {
     info!("Ingesting run: id={}", dto.run_id);
+    let checked = known_at(scopes.as_deref(), dto.tx_time)
+        .and_then(|t| Ok((t, expected_run_version(&headers)?)));
+    let (created_at, expected_version) = match checked {
+        Ok(checked) => checked,
+        Err(rejection) => return rejection.into_response(),
+    };
 
-    match create_run_from_dto(&dto) {
-        Ok(run) => match state.db.put_run(&run) {
-            Ok(_) => {
-                if let Err(e) = state.db.flush() {
+    match create_run_from_dto(&dto, created_at) {
+        Ok(run) => match store_run(&db, &run, &dto, expected_version) {
+            Ok(version) => {
+                if let Err(e) = db.flush() {
                     error!("Failed to flush db: {}", e);
                 }
                 (
                     StatusCode::OK,
+                    run_version_etag(version),
                     Json(ApiResponse::ok("Run ingested successfully")),
                 )
+                    .into_response()
             }
             Err(e) => {
-                error!("Failed to ingest run: {}", e);
+                let status = match e.downcast_ref::<DbError>() {
+                    Some(DbError::RunVersionConflict { .. }) => StatusCode::CONFLICT,
+                    _ => {
+                        error!("Failed to ingest run: {}", e);
+                        StatusCode::INTERNAL_SERVER_ERROR
+                    }
+                };
                 (
-                    StatusCode::INTERNAL_SERVER_ERROR,
+                    status,
                     Json(ApiResponse::error(format!("Failed to ingest run: {}", e))),
                 )
+                    .into_response()
             }
         },
-        Err(e) => (StatusCode::BAD_REQUEST, Json(ApiResponse::error(e))),
+        Err(e) => (StatusCode::BAD_REQUEST, Json(ApiResponse::error(e))).into_response(),
+    }
+}
+
+/// POST /runs/:id/cancel — Mark an in-progress run as cancelled, honouring
+/// `If-Match` like [`ingest_run`]
+pub async fn cancel_run(
+    TenantDb(db): TenantDb,
+    Path(run_id): Path<EntityId>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    info!("Cancelling run: id={}", run_id);
+    let expected_version = match expected_run_version(&headers) {
+        Ok(v) => v,
+        Err(rejection) => return rejection.into_response(),
+    };
+
+    match db.cancel_run_if_version(run_id, expected_version) {
+        Ok(version) => {
+            if let Err(e) = db.flush() {
+                error!("Failed to flush db: {}", e);
+            }
+            (
+                StatusCode::OK,
+                run_version_etag(version),
+                Json(ApiResponse::ok("Run cancelled")),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            let status = match e.downcast_ref::<DbError>() {
+                Some(DbError::RunNotFound(_)) => StatusCode::NOT_FOUND,
+                Some(DbError::RunAlreadyEnded { .. } | DbError::RunVersionConflict { .. }) => {
+                    StatusCode::CONFLICT
+                }
+                _ => {
+                    error!("Failed to cancel run: {}", e);
+                    StatusCode::INTERNAL_SERVER_ERROR
+                }
+            };
+            (
+                status,
+                Json(ApiResponse::error(format!("Failed to cancel run: {}", e))),
+            )
+                .into_response()
+        }
     }
 }
 
 pub async fn ingest_tests(
     State(state): State<AppState>,
-    Json(dto): Json<TestsDto>,
+    TenantDb(db): TenantDb,
+    scopes: Option<Extension<GrantedScopes>>,
+    JsonBody(dto): JsonBody<TestsDto>,
 ) -> impl IntoResponse {
     info!("Ingesting {} tests", dto.tests.len());
+    let created_at = match known_at(scopes.as_deref(), dto.tx_time) {
+        Ok(t) => t,
+        Err(rejection) => return rejection,
+    };
+    if let Err(e) = check_test_items(&dto.tests) {
+        return (StatusCode::BAD_REQUEST, Json(ApiResponse::error(e)));
+    }
 
     for item in &dto.tests {
-        let test = create_test_from_dto(dto.run_id, item);
+        let test = create_test_from_dto(dto.run_id, item, &state.status_aliases, created_at);
 
-        if let Err(e) = state.db.put_test(&test) {
+        let stored =
+            store_test(&state, &db, &test).and_then(|()| store_test_facts(&db, &test, item));
+        if let Err(e) = stored {
             error!("Failed to ingest test: {}", e);
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(ApiResponse::error(format!("Failed to ingest test: {}", e))),
             );
         }
-
-        // Check for flakiness
-        check_and_record_flakiness(&state.db, &test);
-
-        // Check for baseline drift
-        check_baseline_drift(&state.db, &state.metrics, &test);
-
-        // Record metrics
-        let labels = TestLabels {
-            name: test.name.clone(),
-            suite: test.suite.clone(),
-            status: format!("{:?}", test.status).to_lowercase(),
-        };
-        state
-            .metrics
-            .test_duration
-            .get_or_create(&labels)
-            .observe(test.duration_ms as f64 / 1000.0);
-        state.metrics.tests_total.get_or_create(&labels).inc();
-
-        match test.status {
-            TestStatus::Pass => {
-                state.metrics.tests_passed.get_or_create(&labels).inc();
-            }
-            TestStatus::Fail => {
-                state.metrics.tests_failed.get_or_create(&labels).inc();
-            }
-            _ => {}
-        }
     }
 
-    if let Err(e) = state.db.flush() {
+    if let Err(e) = db.flush() {
         error!("Failed to flush db: {}", e);
     }
 
@@ -392,74 +657,248 @@ pub async fn ingest_tests(
     )
 }
 
-pub async fn ingest_signals(
-    State(state): State<AppState>,
-    Json(dto): Json<SignalsDto>,
+/// Record what a test item reports beyond the entity itself: its alignment
+/// score, owner, SLA and attempts
+fn store_test_facts(db: &LiminalDB, test: &Test, item: &TestDtoItem) -> anyhow::Result<()> {
+    if let Some(score) = item.alignment_score {
+        db.put_test_alignment(test, score)?;
+    }
+    if let Some(owner) = &item.owner {
+        db.put_test_owner(test, owner)?;
+    }
+    if let Some(sla_ms) = item.sla_ms {
+        db.put_test_sla(test, sla_ms)?;
+    }
+    if let Some(attempts) = item.attempts {
+        db.put_test_attempts(test, attempts)?;
+    }
+    Ok(())
+}
+
+/// Store one test, then run pattern detectors and baseline checks and
+/// record its metrics
+pub(crate) fn store_test(state: &AppState, db: &LiminalDB, test: &Test) -> anyhow::Result<()> {
+    db.put_test(test)?;
+
+    // Run pattern detectors (flakiness, custom ones)
+    check_and_record_patterns(db, &state.metrics, &state.pattern_detectors, test);
+
+    // Check for baseline drift
+    check_baseline_drift(db, &state.metrics, &state.drift_thresholds, test);
+
+    // Record metrics
+    let labels = TestLabels {
+        name: test.name.clone(),
+        suite: test.suite.clone(),
+        status: format!("{:?}", test.status).to_lowercase(),
+    };
+    state
+        .metrics
+        .test_duration
+        .get_or_create(&labels)
+        .observe(test.duration_ms as f64 / 1000.0);
+    state.metrics.tests_total.get_or_create(&labels).inc();
+
+    match test.status {
+        TestStatus::Pass => {
+            state.metrics.tests_passed.get_or_create(&labels).inc();
+        }
+        TestStatus::Fail => {
+            state.metrics.tests_failed.get_or_create(&labels).inc();
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// The `payload_ref` of each signal: the location of the artifact its
+/// `payload_artifact_id` names. Only files stored by the upload endpoint
+/// qualify, so a signal cannot point at any other file of the host.
+fn signal_payload_refs(
+    state: &AppState,
+    db: &LiminalDB,
+    signals: &[SignalDtoItem],
+) -> Result<Vec<Option<ArtifactRef>>, String> {
+    signals
+        .iter()
+        .map(|item| {
+            let Some(artifact_id) = item.payload_artifact_id else {
+                return Ok(None);
+            };
+            db.get_entity::<Artifact>(artifact_id)
+                .ok()
+                .flatten()
+                .map(|artifact| artifact.artifact_ref)
+                .filter(|artifact_ref| {
+                    state
+                        .artifact_store
+                        .as_ref()
+                        .is_some_and(|store| store.holds(artifact_ref))
+                })
+                .map(Some)
+                .ok_or_else(|| {
+                    format!(
+                        "payload_artifact_id {} is not an uploaded artifact",
+                        artifact_id
+                    )
+                })
+        })
+        .collect()
+}
+
+/// Store one signal, spilling oversized metadata first when configured
+fn store_signal(state: &AppState, db: &LiminalDB, mut signal: Signal) -> anyhow::Result<()> {
+    // Checked before spilling, which moves the metadata out of the signal
+    let violation = state
+        .signal_schemas
+        .check(signal.signal_type, &signal.metadata);
+    // A payload the client referenced is kept; the metadata stays inline
+    let spillover = state
+        .signal_spillover
+        .as_ref()
+        .filter(|_| signal.payload_ref.is_none());
+    if let Some(spillover) = spillover {
+        if let Some(artifact) = spillover.apply(&mut signal)? {
+            info!("Spilled oversized metadata of signal {}", signal.id);
+            db.put_artifact(&artifact)?;
+        }
+    }
+    db.put_signal(&signal)?;
+    if let Some(violation) = violation {
+        info!(
+            "Signal {} is missing metadata: {}",
+            signal.id,
+            violation.missing.join(", ")
+        );
+        db.put_schema_violation(&signal, &violation)?;
+    }
+    check_signal_latency(db, &state.drift_thresholds, &signal)?;
+    Ok(())
+}
+
+pub async fn ingest_test_progress(
+    TenantDb(db): TenantDb,
+    Path(test_id): Path<EntityId>,
+    JsonBody(dto): JsonBody<TestProgressDto>,
 ) -> impl IntoResponse {
-    // Validate that all signals have either test_id or valid test_name
-    for item in &dto.signals {
-        if item.test_id.is_none() && item.test_name.is_none() {
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(ApiResponse::error(
-                    "Each signal must have either test_id or test_name",
-                )),
-            );
+    if dto.phase.trim().is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error("phase must not be empty")),
+        );
+    }
+    if dto.percent.is_some_and(|p| p > 100) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error("percent must be between 0 and 100")),
+        );
+    }
+
+    let progress = TestProgress {
+        test_id,
+        run_id: dto.run_id,
+        phase: dto.phase,
+        percent: dto.percent,
+        message: dto.message,
+        at: dto.at.unwrap_or_else(chrono::Utc::now),
+    };
+    info!("Test {} progress: {}", test_id, progress.phase);
+
+    match db.put_test_progress(&progress) {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(ApiResponse::ok("Progress recorded successfully")),
+        ),
+        Err(e) => {
+            error!("Failed to record progress: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error(format!(
+                    "Failed to record progress: {}",
+                    e
+                ))),
+            )
         }
     }
+}
 
-    info!("Ingesting {} signals", dto.signals.len());
+/// GET /api/tests/:id/progress — Phases reported so far, in order
+pub async fn get_test_progress(
+    TenantDb(db): TenantDb,
+    Path(test_id): Path<EntityId>,
+) -> impl IntoResponse {
+    match db.get_test_progress(test_id) {
+        Ok(progress) => (StatusCode::OK, Json(progress)).into_response(),
+        Err(e) => {
+            error!("Failed to read progress: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error(format!(
+                    "Failed to read progress: {}",
+                    e
+                ))),
+            )
+                .into_response()
+        }
+    }
+}
 
-    for item in &dto.signals {
-        // Resolve test_id from test_name if needed
-        let test_id = match item.test_id {
-            Some(id) => id,
-            None => {
-                let test_name = match item.test_name.as_ref() {
-                    Some(name) => name,
-                    None => {
-                        error!("Neither test_id nor test_name provided for signal");
-                        return (
-                            StatusCode::BAD_REQUEST,
-                            Json(ApiResponse::error(
-                                "Either test_id or test_name must be provided",
-                            )),
-                        );
-                    }
-                };
+pub async fn ingest_signals(
+    State(state): State<AppState>,
+    TenantDb(db): TenantDb,
+    scopes: Option<Extension<GrantedScopes>>,
+    JsonBody(dto): JsonBody<SignalsDto>,
+) -> impl IntoResponse {
+    info!("Ingesting {} signals", dto.signals.len());
+    let created_at = match known_at(scopes.as_deref(), dto.tx_time) {
+        Ok(t) => t,
+        Err(rejection) => return rejection,
+    };
+    if let Err(e) = check_signal_items(&state.signal_schemas, &dto.signals) {
+        return (StatusCode::BAD_REQUEST, Json(ApiResponse::error(e)));
+    }
+    let payload_refs = match signal_payload_refs(&state, &db, &dto.signals) {
+        Ok(refs) => refs,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(ApiResponse::error(e))),
+    };
 
-                match state.db.find_test_by_name(dto.run_id, test_name) {
-                    Ok(Some(id)) => {
-                        info!("Resolved test_id {} for test '{}'", id, test_name);
-                        id
-                    }
-                    Ok(None) => {
-                        error!("Test '{}' not found in run {}", test_name, dto.run_id);
-                        return (
-                            StatusCode::NOT_FOUND,
-                            Json(ApiResponse::error(format!(
-                                "Test '{}' not found in run {}. Ensure tests are ingested via POST /ingest/tests before sending signals.",
-                                test_name, dto.run_id
-                            ))),
-                        );
-                    }
-                    Err(e) => {
-                        error!("Database error during test lookup: {}", e);
-                        return (
-                            StatusCode::INTERNAL_SERVER_ERROR,
-                            Json(ApiResponse::error(format!(
-                                "Database error during test lookup: {}",
-                                e
-                            ))),
-                        );
-                    }
+    for (item, payload_ref) in dto.signals.iter().zip(payload_refs) {
+        // Resolve test_id from test_name if needed; a signal naming no test
+        // is a run-level signal
+        let test_id = match (item.test_id, item.test_name.as_ref()) {
+            (Some(id), _) => Some(id),
+            (None, None) => None,
+            (None, Some(test_name)) => match db.find_test_by_name(dto.run_id, test_name) {
+                Ok(Some(id)) => {
+                    info!("Resolved test_id {} for test '{}'", id, test_name);
+                    Some(id)
                 }
-            }
+                Ok(None) => {
+                    error!("Test '{}' not found in run {}", test_name, dto.run_id);
+                    return (
+                        StatusCode::NOT_FOUND,
+                        Json(ApiResponse::error(format!(
+                            "Test '{}' not found in run {}. Ensure tests are ingested via POST /ingest/tests before sending signals.",
+                            test_name, dto.run_id
+                        ))),
+                    );
+                }
+                Err(e) => {
+                    error!("Database error during test lookup: {}", e);
+                    return (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(ApiResponse::error(format!(
+                            "Database error during test lookup: {}",
+                            e
+                        ))),
+                    );
+                }
+            },
         };
 
-        let signal = create_signal_from_dto(dto.run_id, test_id, item);
+        let signal = create_signal_from_dto(dto.run_id, test_id, item, payload_ref, created_at);
 
-        if let Err(e) = state.db.put_signal(&signal) {
+        if let Err(e) = store_signal(&state, &db, signal) {
             error!("Failed to ingest signal: {}", e);
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
@@ -471,7 +910,7 @@ pub async fn ingest_signals(
         }
     }
 
-    if let Err(e) = state.db.flush() {
+    if let Err(e) = db.flush() {
         error!("Failed to flush db: {}", e);
     }
 
@@ -485,9 +924,14 @@ pub async fn ingest_signals(
 }
 
 pub async fn ingest_artifacts(
-    State(state): State<AppState>,
-    Json(dto): Json<ArtifactsDto>,
+    TenantDb(db): TenantDb,
+    scopes: Option<Extension<GrantedScopes>>,
+    JsonBody(dto): JsonBody<ArtifactsDto>,
 ) -> impl IntoResponse {
+    let created_at = match known_at(scopes.as_deref(), dto.tx_time) {
+        Ok(t) => t,
+        Err(rejection) => return rejection,
+    };
     // Validate that all artifacts have either test_id or valid test_name
     for item in &dto.artifacts {
         if item.test_id.is_none() && item.test_name.is_none() {
@@ -520,7 +964,7 @@ pub async fn ingest_artifacts(
                     }
                 };
 
-                match state.db.find_test_by_name(dto.run_id, test_name) {
+                match db.find_test_by_name(dto.run_id, test_name) {
                     Ok(Some(id)) => {
                         info!("Resolved test_id {} for test '{}'", id, test_name);
                         id
@@ -549,9 +993,9 @@ pub async fn ingest_artifacts(
             }
         };
 
-        let artifact = create_artifact_from_dto(dto.run_id, test_id, item);
+        let artifact = create_artifact_from_dto(dto.run_id, test_id, item, created_at);
 
-        if let Err(e) = state.db.put_artifact(&artifact) {
+        if let Err(e) = db.put_artifact(&artifact) {
             error!("Failed to ingest artifact: {}", e);
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
@@ -563,7 +1007,7 @@ pub async fn ingest_artifacts(
         }
     }
 
-    if let Err(e) = state.db.flush() {
+    if let Err(e) = db.flush() {
         error!("Failed to flush db: {}", e);
     }
 
@@ -576,10 +1020,79 @@ pub async fn ingest_artifacts(
     )
 }
 
+#[derive(Debug, Default, Deserialize)]
+pub struct BatchParams {
+    /// Queue the batch and answer 202 with a job id instead of waiting for
+    /// the write, see [`crate::jobs`]
+    #[serde(default, rename = "async")]
+    pub async_mode: bool,
+}
+
+/// POST /ingest/batch — Ingest a whole run, optionally in the background
 pub async fn ingest_batch(
     State(state): State<AppState>,
-    Json(batch): Json<BatchIngestDto>,
+    TenantDb(db): TenantDb,
+    scopes: Option<Extension<GrantedScopes>>,
+    extract::Query(params): extract::Query<BatchParams>,
+    JsonBody(batch): JsonBody<BatchIngestDto>,
 ) -> impl IntoResponse {
+    let created_at = match known_at(scopes.as_deref(), batch.run.tx_time) {
+        Ok(t) => t,
+        Err(rejection) => return rejection.into_response(),
+    };
+    if params.async_mode {
+        let job_id = match state
+            .batch_jobs
+            .submit(state.clone(), db, batch, created_at)
+        {
+            Ok(job_id) => job_id,
+            Err(e) => {
+                warn!("Refused async batch: {}", e);
+                let mut response = (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    [(header::RETRY_AFTER, "1")],
+                    Json(ApiResponse::error(e.to_string())),
+                )
+                    .into_response();
+                response.extensions_mut().insert(BatchOutcome::Error);
+                return response;
+            }
+        };
+        info!("Queued batch job {}", job_id);
+        let mut response = (StatusCode::ACCEPTED, Json(JobAccepted::new(job_id))).into_response();
+        response.extensions_mut().insert(BatchOutcome::Queued);
+        return response;
+    }
+
+    let (status, Json(response)) = write_batch(&state, &db, batch, created_at);
+    let outcome = batch_outcome(&response);
+
+    let mut response = (status, Json(response)).into_response();
+    response.extensions_mut().insert(outcome);
+    response
+}
+
+pub(crate) fn batch_outcome(response: &BatchIngestResponse) -> BatchOutcome {
+    if response.ok {
+        BatchOutcome::Ok
+    } else if response
+        .partial_counts
+        .as_ref()
+        .is_some_and(|c| c.run + c.tests + c.signals + c.artifacts > 0)
+    {
+        BatchOutcome::Partial
+    } else {
+        BatchOutcome::Error
+    }
+}
+
+/// Write a whole batch, every entity being known at `created_at`
+pub(crate) fn write_batch(
+    state: &AppState,
+    db: &LiminalDB,
+    batch: BatchIngestDto,
+    created_at: BiTemporalTime,
+) -> (StatusCode, Json<BatchIngestResponse>) {
     info!(
         "Ingesting batch: run={}, tests={}, signals={}, artifacts={}",
         batch.run.run_id,
@@ -590,9 +1103,14 @@ pub async fn ingest_batch(
 
     let mut counts = BatchCounts::default();
     let mut test_id_map: HashMap<String, EntityId> = HashMap::new();
+    let mut per_test: BTreeMap<String, TestAttachmentCounts> = BTreeMap::new();
 
     // Step 1: Ingest run
-    let run = match create_run_from_dto(&batch.run) {
+    let (run, payload_refs) = match create_run_from_dto(&batch.run, created_at)
+        .and_then(|run| check_test_items(&batch.tests).map(|()| run))
+        .and_then(|run| check_signal_items(&state.signal_schemas, &batch.signals).map(|()| run))
+        .and_then(|run| signal_payload_refs(state, db, &batch.signals).map(|refs| (run, refs)))
+    {
         Ok(r) => r,
         Err(e) => {
             error!("Failed to ingest run: {}", e);
@@ -603,14 +1121,15 @@ pub async fn ingest_batch(
                     message: "Batch ingestion failed".to_string(),
                     counts: counts.clone(),
                     test_id_map: None,
+                    per_test: None,
                     partial_counts: Some(counts),
-                    error_details: Some(format!("Invalid run data: {}", e)),
+                    error_details: Some(format!("Invalid batch data: {}", e)),
                 }),
             );
         }
     };
 
-    if let Err(e) = state.db.put_run(&run) {
+    if let Err(e) = store_run(db, &run, &batch.run, None) {
         error!("Failed to ingest run: {}", e);
         return (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -619,6 +1138,7 @@ pub async fn ingest_batch(
                 message: "Batch ingestion failed".to_string(),
                 counts: counts.clone(),
                 test_id_map: None,
+                per_test: None,
                 partial_counts: Some(counts),
                 error_details: Some(format!("Run ingestion failed: {}", e)),
             }),
@@ -628,12 +1148,21 @@ pub async fn ingest_batch(
 
     // Step 2: Ingest tests and build name -> id map
     for test_item in &batch.tests {
-        let test = create_test_from_dto(batch.run.run_id, test_item);
+        let test = create_test_from_dto(
+            batch.run.run_id,
+            test_item,
+            &state.status_aliases,
+            created_at,
+        );
 
         // Store test_name -> test_id mapping for later use
         test_id_map.insert(test.name.clone(), test.id);
+        per_test.entry(test.name.clone()).or_default();
 
-        if let Err(e) = state.db.put_test(&test) {
+        let stored = db
+            .put_test(&test)
+            .and_then(|()| store_test_facts(db, &test, test_item));
+        if let Err(e) = stored {
             error!("Failed to ingest test '{}': {}", test.name, e);
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
@@ -642,17 +1171,18 @@ pub async fn ingest_batch(
                     message: "Batch ingestion failed".to_string(),
                     counts: BatchCounts::default(),
                     test_id_map: None,
+                    per_test: None,
                     partial_counts: Some(counts),
                     error_details: Some(format!("Test ingestion failed: {}", e)),
                 }),
             );
         }
 
-        // Check for flakiness
-        check_and_record_flakiness(&state.db, &test);
+        // Run pattern detectors (flakiness, custom ones)
+        check_and_record_patterns(db, &state.metrics, &state.pattern_detectors, &test);
 
         // Check for baseline drift
-        check_baseline_drift(&state.db, &state.metrics, &test);
+        check_baseline_drift(db, &state.metrics, &state.drift_thresholds, &test);
 
         // Record metrics
         let labels = TestLabels {
@@ -681,22 +1211,33 @@ pub async fn ingest_batch(
     }
 
     // Step 3: Ingest signals (using test_id_map for resolution)
-    for signal_item in &batch.signals {
-        let test_id = match resolve_test_id(
-            &state.db,
-            &test_id_map,
-            batch.run.run_id,
-            signal_item.test_id,
-            signal_item.test_name.as_deref(),
-            &counts,
-        ) {
-            Ok(id) => id,
-            Err(boxed_resp) => return *boxed_resp,
+    for (signal_item, payload_ref) in batch.signals.iter().zip(payload_refs) {
+        // A signal naming no test is a run-level signal
+        let test_id = if signal_item.test_id.is_none() && signal_item.test_name.is_none() {
+            None
+        } else {
+            match resolve_test_id(
+                db,
+                &test_id_map,
+                batch.run.run_id,
+                signal_item.test_id,
+                signal_item.test_name.as_deref(),
+                &counts,
+            ) {
+                Ok(id) => Some(id),
+                Err(boxed_resp) => return *boxed_resp,
+            }
         };
 
-        let signal = create_signal_from_dto(batch.run.run_id, test_id, signal_item);
+        let signal = create_signal_from_dto(
+            batch.run.run_id,
+            test_id,
+            signal_item,
+            payload_ref,
+            created_at,
+        );
 
-        if let Err(e) = state.db.put_signal(&signal) {
+        if let Err(e) = store_signal(state, db, signal) {
             error!("Failed to ingest signal: {}", e);
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
@@ -705,18 +1246,29 @@ pub async fn ingest_batch(
                     message: "Batch ingestion failed".to_string(),
                     counts: BatchCounts::default(),
                     test_id_map: None,
+                    per_test: None,
                     partial_counts: Some(counts),
                     error_details: Some(format!("Signal ingestion failed: {}", e)),
                 }),
             );
         }
         counts.signals += 1;
+        if let Some(test_id) = test_id {
+            per_test
+                .entry(attachment_key(
+                    &test_id_map,
+                    test_id,
+                    signal_item.test_name.as_deref(),
+                ))
+                .or_default()
+                .signals += 1;
+        }
     }
 
     // Step 4: Ingest artifacts (using test_id_map for resolution)
     for artifact_item in &batch.artifacts {
         let test_id = match resolve_test_id(
-            &state.db,
+            db,
             &test_id_map,
             batch.run.run_id,
             artifact_item.test_id,
@@ -727,9 +1279,10 @@ pub async fn ingest_batch(
             Err(boxed_resp) => return *boxed_resp,
         };
 
-        let artifact = create_artifact_from_dto(batch.run.run_id, test_id, artifact_item);
+        let artifact =
+            create_artifact_from_dto(batch.run.run_id, test_id, artifact_item, created_at);
 
-        if let Err(e) = state.db.put_artifact(&artifact) {
+        if let Err(e) = db.put_artifact(&artifact) {
             error!("Failed to ingest artifact: {}", e);
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
@@ -738,16 +1291,25 @@ pub async fn ingest_batch(
                     message: "Batch ingestion failed".to_string(),
                     counts: BatchCounts::default(),
                     test_id_map: None,
+                    per_test: None,
                     partial_counts: Some(counts),
                     error_details: Some(format!("Artifact ingestion failed: {}", e)),
                 }),
             );
         }
         counts.artifacts += 1;
+        per_test
+            .entry(attachment_key(
+                &test_id_map,
+                test_id,
+                artifact_item.test_name.as_deref(),
+            ))
+            .or_default()
+            .artifacts += 1;
     }
 
     // Step 5: Flush to disk
-    if let Err(e) = state.db.flush() {
+    if let Err(e) = db.flush() {
         error!("Failed to flush db: {}", e);
         return (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -756,6 +1318,7 @@ pub async fn ingest_batch(
                 message: "Batch ingestion failed during flush".to_string(),
                 counts: BatchCounts::default(),
                 test_id_map: None,
+                per_test: None,
                 partial_counts: Some(counts),
                 error_details: Some(format!("Flush failed: {}", e)),
             }),
@@ -770,7 +1333,8 @@ pub async fn ingest_batch(
             ok: true,
             message: "Batch ingestion successful".to_string(),
             counts,
-            test_id_map: Some(test_id_map),
+            test_id_map: Some(test_id_map.into_iter().collect()),
+            per_test: Some(per_test),
             partial_counts: None,
             error_details: None,
         }),
@@ -779,7 +1343,7 @@ pub async fn ingest_batch(
 
 pub async fn query_handler(
     State(_state): State<AppState>,
-    Json(query): Json<Query>,
+    JsonBody(query): JsonBody<Query>,
 ) -> impl IntoResponse {
     info!("Executing query: {:?}", query);
 
@@ -0,0 +1,151 @@
+//! Per-request HTTP metrics
+
+use crate::AppState;
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use liminalqa_core::metrics::{HttpLabels, OutcomeLabels};
+use std::time::Instant;
+
+/// Label used for requests that did not match any route
+pub const UNMATCHED_ROUTE: &str = "unmatched";
+
+/// Route template of a request, e.g. `/runs/:id` rather than `/runs/01J...`.
+///
+/// Uses axum's [`MatchedPath`] extension, so it must run inside the router.
+/// Requests that matched no route share the [`UNMATCHED_ROUTE`] label.
+pub fn route_label(req: &Request) -> String {
+    req.extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| UNMATCHED_ROUTE.to_string())
+}
+
+/// Middleware counting requests and their duration per route template
+pub async fn track_requests(State(state): State<AppState>, req: Request, next: Next) -> Response {
+    let method = req.method().to_string();
+    let route = route_label(&req);
+    let start = Instant::now();
+
+    let response = next.run(req).await;
+
+    let labels = HttpLabels {
+        method,
+        route,
+        status: response.status().as_u16(),
+    };
+    state.metrics.http_requests.get_or_create(&labels).inc();
+    state
+        .metrics
+        .http_request_duration
+        .get_or_create(&labels)
+        .observe(start.elapsed().as_secs_f64());
+
+    response
+}
+
+/// How a batch ingest ended, attached to the response by the handler
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchOutcome {
+    /// Everything was stored
+    Ok,
+    /// Failed after part of the batch was stored
+    Partial,
+    /// Rejected before anything was stored
+    Error,
+    /// Accepted for background processing; the job records its own outcome
+    /// when it finishes
+    Queued,
+}
+
+impl BatchOutcome {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BatchOutcome::Ok => "ok",
+            BatchOutcome::Partial => "partial",
+            BatchOutcome::Error => "error",
+            BatchOutcome::Queued => "queued",
+        }
+    }
+}
+
+/// Route middleware timing a whole batch ingest, including body parsing.
+///
+/// Requests rejected before the handler ran (malformed JSON) carry no
+/// [`BatchOutcome`] and count as `error`.
+pub async fn track_batch_duration(
+    State(state): State<AppState>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let start = Instant::now();
+    let response = next.run(req).await;
+
+    let outcome = response
+        .extensions()
+        .get::<BatchOutcome>()
+        .copied()
+        .unwrap_or(BatchOutcome::Error);
+    state
+        .metrics
+        .ingest_batch_duration
+        .get_or_create(&OutcomeLabels {
+            outcome: outcome.as_str().to_string(),
+        })
+        .observe(start.elapsed().as_secs_f64());
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::StatusCode, middleware, routing::get, Router};
+    use liminalqa_core::{metrics::MetricsRegistry, types::EntityId};
+    use liminalqa_db::LiminalDB;
+    use std::sync::Arc;
+    use tower::util::ServiceExt;
+
+    #[tokio::test]
+    async fn test_requests_are_labelled_by_route_template() -> anyhow::Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let db = Arc::new(LiminalDB::open(temp_dir.path())?);
+        let metrics = Arc::new(MetricsRegistry::new());
+        let state = AppState::new(db, None, metrics.clone());
+
+        let app = Router::new()
+            .route("/runs/:id", get(|| async { "ok" }))
+            .layer(middleware::from_fn_with_state(
+                state.clone(),
+                track_requests,
+            ))
+            .with_state(state);
+
+        for _ in 0..2 {
+            let uri = format!("/runs/{}", EntityId::new());
+            let response = app
+                .clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty())?)
+                .await?;
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        let labels = HttpLabels {
+            method: "GET".to_string(),
+            route: "/runs/:id".to_string(),
+            status: 200,
+        };
+        assert_eq!(metrics.http_requests.get_or_create(&labels).get(), 2);
+
+        let series = metrics
+            .export()
+            .lines()
+            .filter(|l| l.starts_with("liminalqa_http_requests_total{"))
+            .count();
+        assert_eq!(series, 1);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,314 @@
+//! Background processing of batches submitted with `?async=true`
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, OnceLock, RwLock},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use liminalqa_core::{metrics::OutcomeLabels, temporal::BiTemporalTime, types::EntityId};
+use liminalqa_db::LiminalDB;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tracing::{error, info};
+
+use crate::{
+    extract::TenantDb,
+    handlers::{batch_outcome, write_batch, BatchCounts, BatchIngestDto},
+    http_metrics::BatchOutcome,
+    ApiResponse, AppState,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+}
+
+/// State of one async batch, returned by `GET /ingest/jobs/:id`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchJob {
+    pub id: EntityId,
+    pub status: JobStatus,
+    pub submitted_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    /// What was stored so far; final once the job is completed or failed
+    pub counts: BatchCounts,
+    pub error: Option<String>,
+}
+
+struct QueuedBatch {
+    id: EntityId,
+    state: AppState,
+    db: Arc<LiminalDB>,
+    batch: BatchIngestDto,
+    created_at: BiTemporalTime,
+}
+
+struct JobEntry {
+    job: BatchJob,
+    /// Tenant database the batch is written to; other tenants can't see it
+    db: Arc<LiminalDB>,
+}
+
+/// Batches waiting for the worker before submissions are refused
+pub const DEFAULT_JOB_QUEUE_CAPACITY: usize = 64;
+
+/// Finished jobs kept for polling, oldest dropped first
+pub const DEFAULT_RETAINED_JOBS: usize = 1_000;
+
+/// How long a finished job can be polled
+pub const DEFAULT_JOB_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Why a batch was not queued
+#[derive(Debug, thiserror::Error)]
+pub enum SubmitError {
+    #[error("Batch queue is full ({0} batches waiting)")]
+    QueueFull(usize),
+}
+
+/// In-process queue of async batches.
+///
+/// Batches are written one at a time, in submission order, by a worker task
+/// started on the first submission. At most `queue_capacity` batches wait;
+/// finished jobs can be polled for `job_ttl`, and only the latest
+/// `retained_jobs` of them are kept. Jobs only live in memory: queued
+/// batches and job states are lost on restart.
+pub struct BatchJobs {
+    sender: OnceLock<mpsc::Sender<QueuedBatch>>,
+    jobs: Arc<RwLock<HashMap<EntityId, JobEntry>>>,
+    queue_capacity: usize,
+    retained_jobs: usize,
+    job_ttl: Duration,
+}
+
+impl Default for BatchJobs {
+    fn default() -> Self {
+        Self::new(DEFAULT_JOB_QUEUE_CAPACITY)
+    }
+}
+
+impl BatchJobs {
+    /// Queue holding up to `queue_capacity` waiting batches
+    pub fn new(queue_capacity: usize) -> Self {
+        Self {
+            sender: OnceLock::new(),
+            jobs: Arc::default(),
+            queue_capacity: queue_capacity.max(1),
+            retained_jobs: DEFAULT_RETAINED_JOBS,
+            job_ttl: DEFAULT_JOB_TTL,
+        }
+    }
+
+    /// Keep at most `max` finished jobs, each for at most `ttl`
+    pub fn with_retention(mut self, max: usize, ttl: Duration) -> Self {
+        self.retained_jobs = max;
+        self.job_ttl = ttl;
+        self
+    }
+
+    /// Queue `batch` for `db`, its entities known at `created_at`, returning
+    /// the job id. Fails when the queue is full. Must be called from within
+    /// a tokio runtime.
+    pub fn submit(
+        &self,
+        state: AppState,
+        db: Arc<LiminalDB>,
+        batch: BatchIngestDto,
+        created_at: BiTemporalTime,
+    ) -> Result<EntityId, SubmitError> {
+        self.evict_finished();
+        let id = EntityId::new();
+        let job = BatchJob {
+            id,
+            status: JobStatus::Queued,
+            submitted_at: Utc::now(),
+            finished_at: None,
+            counts: BatchCounts::default(),
+            error: None,
+        };
+        self.jobs.write().expect("jobs lock poisoned").insert(
+            id,
+            JobEntry {
+                job,
+                db: db.clone(),
+            },
+        );
+
+        let sender = self.sender.get_or_init(|| {
+            let (sender, receiver) = mpsc::channel(self.queue_capacity);
+            tokio::spawn(run_worker(receiver, self.jobs.clone()));
+            sender
+        });
+        match sender.try_send(QueuedBatch {
+            id,
+            state,
+            db,
+            batch,
+            created_at,
+        }) {
+            Ok(()) => {}
+            Err(mpsc::error::TrySendError::Full(_)) => {
+                self.jobs.write().expect("jobs lock poisoned").remove(&id);
+                return Err(SubmitError::QueueFull(self.queue_capacity));
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => {
+                update_job(&self.jobs, id, |job| {
+                    job.status = JobStatus::Failed;
+                    job.finished_at = Some(Utc::now());
+                    job.error = Some("Batch worker stopped".to_string());
+                });
+            }
+        }
+        Ok(id)
+    }
+
+    /// Drop finished jobs past their TTL, then the oldest beyond the
+    /// retention limit. Queued and running jobs are never dropped.
+    fn evict_finished(&self) {
+        let mut jobs = self.jobs.write().expect("jobs lock poisoned");
+        let expired_before =
+            Utc::now() - chrono::Duration::from_std(self.job_ttl).unwrap_or(chrono::Duration::MAX);
+        jobs.retain(|_, entry| {
+            entry
+                .job
+                .finished_at
+                .is_none_or(|finished| finished >= expired_before)
+        });
+
+        let mut finished: Vec<(DateTime<Utc>, EntityId)> = jobs
+            .values()
+            .filter_map(|entry| Some((entry.job.finished_at?, entry.job.id)))
+            .collect();
+        if finished.len() > self.retained_jobs {
+            finished.sort();
+            let excess = finished.len() - self.retained_jobs;
+            for (_, id) in &finished[..excess] {
+                jobs.remove(id);
+            }
+        }
+    }
+
+    /// Current state of a job written to `db`
+    pub fn get(&self, id: EntityId, db: &Arc<LiminalDB>) -> Option<BatchJob> {
+        let jobs = self.jobs.read().expect("jobs lock poisoned");
+        jobs.get(&id)
+            .filter(|entry| Arc::ptr_eq(&entry.db, db))
+            .map(|entry| entry.job.clone())
+    }
+}
+
+fn update_job(
+    jobs: &RwLock<HashMap<EntityId, JobEntry>>,
+    id: EntityId,
+    f: impl FnOnce(&mut BatchJob),
+) {
+    if let Some(entry) = jobs.write().expect("jobs lock poisoned").get_mut(&id) {
+        f(&mut entry.job);
+    }
+}
+
+async fn run_worker(
+    mut receiver: mpsc::Receiver<QueuedBatch>,
+    jobs: Arc<RwLock<HashMap<EntityId, JobEntry>>>,
+) {
+    while let Some(QueuedBatch {
+        id,
+        state,
+        db,
+        batch,
+        created_at,
+    }) = receiver.recv().await
+    {
+        update_job(&jobs, id, |job| job.status = JobStatus::Running);
+        let start = Instant::now();
+        let metrics = state.metrics.clone();
+        let written =
+            tokio::task::spawn_blocking(move || write_batch(&state, &db, batch, created_at)).await;
+
+        let (outcome, counts, error) = match written {
+            Ok((_, Json(response))) => {
+                let outcome = batch_outcome(&response);
+                let error = (!response.ok).then(|| {
+                    response
+                        .error_details
+                        .clone()
+                        .unwrap_or_else(|| response.message.clone())
+                });
+                let counts = response.partial_counts.unwrap_or(response.counts);
+                (outcome, counts, error)
+            }
+            Err(e) => {
+                error!("Batch job {} panicked: {}", id, e);
+                (
+                    BatchOutcome::Error,
+                    BatchCounts::default(),
+                    Some(format!("Batch job panicked: {}", e)),
+                )
+            }
+        };
+        metrics
+            .ingest_batch_duration
+            .get_or_create(&OutcomeLabels {
+                outcome: outcome.as_str().to_string(),
+            })
+            .observe(start.elapsed().as_secs_f64());
+        info!("Batch job {} finished: {}", id, outcome.as_str());
+
+        update_job(&jobs, id, |job| {
+            job.status = if error.is_none() {
+                JobStatus::Completed
+            } else {
+                JobStatus::Failed
+            };
+            job.finished_at = Some(Utc::now());
+            job.counts = counts;
+            job.error = error;
+        });
+    }
+}
+
+/// Body of the 202 returned for an async batch
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JobAccepted {
+    pub ok: bool,
+    pub job_id: EntityId,
+    /// Where to poll the job
+    pub status_url: String,
+}
+
+impl JobAccepted {
+    pub fn new(job_id: EntityId) -> Self {
+        Self {
+            ok: true,
+            job_id,
+            status_url: format!("/ingest/jobs/{}", job_id),
+        }
+    }
+}
+
+/// GET /ingest/jobs/:id — State of an async batch
+pub async fn get_job(
+    State(state): State<AppState>,
+    TenantDb(db): TenantDb,
+    Path(id): Path<EntityId>,
+) -> impl IntoResponse {
+    match state.batch_jobs.get(id, &db) {
+        Some(job) => (StatusCode::OK, Json(job)).into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::error(format!("Unknown job: {}", id))),
+        )
+            .into_response(),
+    }
+}
@@ -0,0 +1,240 @@
+//! JUnit XML ingestion
+
+use crate::{
+    extract::TenantDb,
+    handlers::{create_test_from_dto, store_test, TestDtoItem},
+    ApiResponse, AppState,
+};
+use anyhow::{Context, Result};
+use axum::{
+    extract::{Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use liminalqa_core::{entities::Run, temporal::BiTemporalTime, types::*};
+use quick_xml::{
+    events::{BytesStart, Event},
+    Reader,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+#[derive(Debug, Deserialize)]
+pub struct JunitParams {
+    /// Existing run to attach the tests to; a new run is created when absent
+    pub run_id: Option<EntityId>,
+    /// Plan name of the new run
+    pub plan_name: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JunitIngestResponse {
+    pub ok: bool,
+    pub run_id: EntityId,
+    pub tests: usize,
+}
+
+/// POST /ingest/junit?run_id=&plan_name= — Ingest a JUnit XML report
+pub async fn ingest_junit(
+    State(state): State<AppState>,
+    TenantDb(db): TenantDb,
+    Query(params): Query<JunitParams>,
+    headers: HeaderMap,
+    body: String,
+) -> impl IntoResponse {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    if !content_type.contains("xml") {
+        return (
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            Json(ApiResponse::error(
+                "Expected Content-Type application/xml or text/xml",
+            )),
+        )
+            .into_response();
+    }
+
+    let items = match parse_junit(&body) {
+        Ok(items) => items,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::error(format!("Invalid JUnit XML: {:#}", e))),
+            )
+                .into_response();
+        }
+    };
+
+    let run_id = match params.run_id {
+        Some(run_id) => run_id,
+        None => {
+            let run = Run {
+                id: EntityId::new(),
+                build_id: EntityId::new(),
+                plan_name: params.plan_name.unwrap_or_else(|| "junit".to_string()),
+                env: Environment::default(),
+                started_at: chrono::Utc::now(),
+                ended_at: None,
+                runner_version: "junit".to_string(),
+                liminal_os_version: None,
+                created_at: BiTemporalTime::now(),
+            };
+            if let Err(e) = db.put_run(&run) {
+                error!("Failed to ingest run: {}", e);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse::error(format!("Failed to ingest run: {}", e))),
+                )
+                    .into_response();
+            }
+            run.id
+        }
+    };
+    info!("Ingesting {} JUnit tests into run {}", items.len(), run_id);
+
+    for item in &items {
+        let test = create_test_from_dto(run_id, item, &state.status_aliases, BiTemporalTime::now());
+        if let Err(e) = store_test(&state, &db, &test) {
+            error!("Failed to ingest test: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error(format!("Failed to ingest test: {}", e))),
+            )
+                .into_response();
+        }
+    }
+
+    if let Err(e) = db.flush() {
+        error!("Failed to flush db: {}", e);
+    }
+
+    (
+        StatusCode::OK,
+        Json(JunitIngestResponse {
+            ok: true,
+            run_id,
+            tests: items.len(),
+        }),
+    )
+        .into_response()
+}
+
+/// Parse the `<testcase>` elements of a JUnit report (`<testsuites>` or a
+/// single `<testsuite>` root).
+///
+/// `classname` becomes the suite (falling back to the enclosing
+/// `<testsuite name>`), `time` in seconds the duration. A `<failure>` child
+/// yields status `failed`, `<error>` yields `error` and `<skipped>` yields
+/// `skipped`, so status aliases apply to them like to any other label.
+pub fn parse_junit(xml: &str) -> Result<Vec<TestDtoItem>> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut items = Vec::new();
+    let mut suites: Vec<String> = Vec::new();
+    let mut current: Option<TestDtoItem> = None;
+    // Error element whose text (the stack trace) is being read
+    let mut in_error = false;
+
+    loop {
+        let event = reader
+            .read_event()
+            .with_context(|| format!("at byte {}", reader.buffer_position()))?;
+        let is_start = matches!(event, Event::Start(_));
+        match event {
+            Event::Start(e) if e.name().as_ref() == b"testsuite" => {
+                suites.push(attr(&e, "name")?.unwrap_or_default());
+            }
+            Event::End(e) if e.name().as_ref() == b"testsuite" => {
+                suites.pop();
+            }
+            Event::Start(e) if e.name().as_ref() == b"testcase" => {
+                current = Some(test_case(&e, suites.last())?);
+            }
+            Event::Empty(e) if e.name().as_ref() == b"testcase" => {
+                items.push(test_case(&e, suites.last())?);
+            }
+            Event::End(e) if e.name().as_ref() == b"testcase" => {
+                items.extend(current.take());
+            }
+            Event::Start(e) | Event::Empty(e) if current.is_some() => {
+                let status = match e.name().as_ref() {
+                    b"failure" => "failed",
+                    b"error" => "error",
+                    b"skipped" => "skipped",
+                    _ => continue,
+                };
+                let item = current.as_mut().context("testcase missing")?;
+                item.status = status.to_string();
+                if status != "skipped" {
+                    item.error = Some(TestError::new(
+                        attr(&e, "type")?.unwrap_or_else(|| status.to_string()),
+                        attr(&e, "message")?.unwrap_or_default(),
+                    ));
+                    in_error = is_start;
+                }
+            }
+            Event::Text(text) if in_error => {
+                set_stack_trace(&mut current, text.unescape()?.into_owned());
+            }
+            Event::CData(data) if in_error => {
+                set_stack_trace(&mut current, String::from_utf8(data.into_inner().into())?);
+            }
+            Event::End(e) if matches!(e.name().as_ref(), b"failure" | b"error") => {
+                in_error = false;
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(items)
+}
+
+fn test_case(e: &BytesStart, suite: Option<&String>) -> Result<TestDtoItem> {
+    let name = attr(e, "name")?.context("testcase without a name")?;
+    let suite = attr(e, "classname")?
+        .or_else(|| suite.cloned())
+        .unwrap_or_default();
+    let duration_ms = match attr(e, "time")? {
+        Some(time) => {
+            let secs: f64 = time
+                .trim()
+                .parse()
+                .with_context(|| format!("Invalid time '{}' of testcase {}", time, name))?;
+            Some((secs * 1000.0).round() as i64)
+        }
+        None => None,
+    };
+
+    Ok(TestDtoItem {
+        name,
+        suite,
+        guidance: None,
+        status: "passed".to_string(),
+        duration_ms,
+        error: None,
+        started_at: None,
+        completed_at: None,
+        alignment_score: None,
+        owner: None,
+        sla_ms: None,
+        attempts: None,
+    })
+}
+
+fn attr(e: &BytesStart, name: &str) -> Result<Option<String>> {
+    match e.try_get_attribute(name)? {
+        Some(a) => Ok(Some(a.unescape_value()?.into_owned())),
+        None => Ok(None),
+    }
+}
+
+fn set_stack_trace(current: &mut Option<TestDtoItem>, trace: String) {
+    if let Some(error) = current.as_mut().and_then(|item| item.error.as_mut()) {
+        error.stack_trace = Some(trace);
+    }
+}
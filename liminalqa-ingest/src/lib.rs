@@ -1,31 +1,217 @@
 //! LiminalQA Ingest Library
 
+pub mod auth;
 pub mod baseline;
+pub mod extract;
 pub mod handlers;
+pub mod http_metrics;
+pub mod jobs;
+pub mod junit;
+pub mod report;
 pub mod resonance;
+pub mod server;
+pub mod stats;
+pub mod upload;
+
+/// Spillover lives with the storage so every ingest path can share it
+pub use liminalqa_db::spillover;
 
 use axum::{
-    extract::{Request, State},
+    error_handling::HandleErrorLayer,
+    extract::{DefaultBodyLimit, Request, State},
     http::{header, StatusCode},
     middleware::{self, Next},
     response::IntoResponse,
     routing::{get, post},
     Json, Router,
 };
-use liminalqa_core::metrics::SharedMetrics;
+use liminalqa_core::{
+    baseline::DriftThresholds,
+    metrics::SharedMetrics,
+    resonance::{DetectorRegistry, PatternDetector},
+    signal_schema::SignalSchemas,
+    types::TestStatus,
+};
 use liminalqa_db::LiminalDB;
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+use tower::{limit::GlobalConcurrencyLimitLayer, BoxError, ServiceBuilder};
 use tower_http::cors::CorsLayer;
+use tracing::debug;
 
+use crate::auth::{GrantedScopes, JwtConfig, Scope};
 use crate::handlers::*;
-use crate::resonance::get_flaky_tests;
+use crate::jobs::{get_job, BatchJobs};
+use crate::report::{
+    get_build_runs, get_run_report, get_run_signals, get_run_status, get_runs, get_signal_payload,
+    get_tests,
+};
+use crate::resonance::{get_flake_score, get_flaky_tests};
+use crate::spillover::SignalSpillover;
+use crate::stats::{get_drift_series, get_duration_histogram, get_signal_timeline};
+use crate::upload::{upload_artifact, ArtifactStore, DEFAULT_MAX_UPLOAD_BYTES};
+
+/// Tenant name that always resolves to [`AppState::db`]
+pub const DEFAULT_TENANT: &str = "default";
 
 #[derive(Clone)]
 pub struct AppState {
+    /// Database of the default tenant
     pub db: Arc<LiminalDB>,
+    /// Static token granting every scope
     pub auth_token: Option<String>,
+    /// Static tokens limited to the listed scopes
+    pub scoped_tokens: Arc<HashMap<String, HashSet<Scope>>>,
+    /// JWT validation; when set, bearer tokens are checked as JWTs first and
+    /// the static tokens remain accepted as a fallback. A JWT grants the
+    /// scopes of its `scope` claim.
+    pub jwt: Option<Arc<JwtConfig>>,
     pub metrics: SharedMetrics,
+    /// Additional tenants, selected with the `X-Tenant-Id` header
+    pub tenants: Arc<HashMap<String, Arc<LiminalDB>>>,
+    /// Extra test status labels, keyed by lowercase label
+    pub status_aliases: Arc<HashMap<String, TestStatus>>,
+    /// Where oversized signal metadata is spilled; kept inline when `None`
+    pub signal_spillover: Option<Arc<SignalSpillover>>,
+    /// Where uploaded artifact files are stored; uploads are refused when
+    /// `None`
+    pub artifact_store: Option<Arc<ArtifactStore>>,
+    /// Detectors run over the history of every ingested test
+    pub pattern_detectors: Arc<DetectorRegistry>,
+    /// Batches submitted with `POST /ingest/batch?async=true`
+    pub batch_jobs: Arc<BatchJobs>,
+    /// Sigma thresholds of duration and latency drift, per suite
+    pub drift_thresholds: Arc<DriftThresholds>,
+    /// Metadata required of each signal kind and what to do without it
+    pub signal_schemas: Arc<SignalSchemas>,
+    /// Requests handled at once before the overflow is refused with 503;
+    /// unlimited when `None`
+    pub max_in_flight: Option<usize>,
+}
+
+impl AppState {
+    /// Create a single-tenant state
+    pub fn new(db: Arc<LiminalDB>, auth_token: Option<String>, metrics: SharedMetrics) -> Self {
+        Self {
+            db,
+            auth_token,
+            scoped_tokens: Arc::new(HashMap::new()),
+            jwt: None,
+            metrics,
+            tenants: Arc::new(HashMap::new()),
+            status_aliases: Arc::new(HashMap::new()),
+            signal_spillover: None,
+            artifact_store: None,
+            pattern_detectors: Arc::new(DetectorRegistry::default()),
+            batch_jobs: Arc::new(BatchJobs::default()),
+            drift_thresholds: Arc::new(DriftThresholds::default()),
+            signal_schemas: Arc::new(SignalSchemas::default()),
+            max_in_flight: None,
+        }
+    }
+
+    /// Judge duration and latency drift with `thresholds`
+    pub fn with_drift_thresholds(mut self, thresholds: DriftThresholds) -> Self {
+        self.drift_thresholds = Arc::new(thresholds);
+        self
+    }
+
+    /// Check signal metadata against `schemas` on ingest
+    pub fn with_signal_schemas(mut self, schemas: SignalSchemas) -> Self {
+        self.signal_schemas = Arc::new(schemas);
+        self
+    }
+
+    /// Queue async batches in `jobs` instead of the default queue
+    pub fn with_batch_jobs(mut self, jobs: BatchJobs) -> Self {
+        self.batch_jobs = Arc::new(jobs);
+        self
+    }
+
+    /// Refuse requests with 503 while `max` are already being handled
+    pub fn with_max_in_flight(mut self, max: usize) -> Self {
+        self.max_in_flight = Some(max);
+        self
+    }
+
+    /// Spill signal metadata over the configured size to payload files
+    pub fn with_signal_spillover(mut self, spillover: SignalSpillover) -> Self {
+        self.signal_spillover = Some(Arc::new(spillover));
+        self
+    }
+
+    /// Accept artifact file uploads, storing them in `store`
+    pub fn with_artifact_store(mut self, store: ArtifactStore) -> Self {
+        self.artifact_store = Some(Arc::new(store));
+        self
+    }
+
+    /// Run `detectors` over the history of every ingested test instead of
+    /// the built-in ones
+    pub fn with_pattern_detectors(mut self, detectors: DetectorRegistry) -> Self {
+        self.pattern_detectors = Arc::new(detectors);
+        self
+    }
+
+    /// Also run `detector` over the history of every ingested test
+    pub fn with_pattern_detector(mut self, detector: impl PatternDetector + 'static) -> Self {
+        self.pattern_detectors =
+            Arc::new((*self.pattern_detectors).clone().with_detector(detector));
+        self
+    }
+
+    /// Map the test status `label` to `status` on ingest
+    pub fn with_status_alias(mut self, label: &str, status: TestStatus) -> Self {
+        Arc::make_mut(&mut self.status_aliases).insert(label.to_lowercase(), status);
+        self
+    }
+
+    /// Accept a static bearer `token` granting only `scopes`
+    pub fn with_scoped_token(
+        mut self,
+        token: impl Into<String>,
+        scopes: impl IntoIterator<Item = Scope>,
+    ) -> Self {
+        Arc::make_mut(&mut self.scoped_tokens).insert(token.into(), scopes.into_iter().collect());
+        self
+    }
+
+    /// Scopes granted to a bearer token, `None` if it is not valid
+    pub fn token_scopes(&self, token: &str) -> Option<HashSet<Scope>> {
+        if let Some(jwt) = &self.jwt {
+            match jwt.validate(token) {
+                Ok(claims) => return Some(claims.scopes()),
+                Err(e) => debug!("Rejected JWT: {}", e),
+            }
+        }
+        if self.auth_token.as_deref() == Some(token) {
+            return Some(Scope::ALL.into_iter().collect());
+        }
+        self.scoped_tokens.get(token).cloned()
+    }
+
+    /// Accept JWT bearer tokens validated against `config`
+    pub fn with_jwt(mut self, config: JwtConfig) -> Self {
+        self.jwt = Some(Arc::new(config));
+        self
+    }
+
+    /// Register a tenant with its own database
+    pub fn with_tenant(mut self, tenant: impl Into<String>, db: Arc<LiminalDB>) -> Self {
+        Arc::make_mut(&mut self.tenants).insert(tenant.into(), db);
+        self
+    }
+
+    /// Resolve a tenant name to its database
+    pub fn tenant_db(&self, tenant: &str) -> Option<Arc<LiminalDB>> {
+        if tenant == DEFAULT_TENANT {
+            return Some(self.db.clone());
+        }
+        self.tenants.get(tenant).cloned()
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -51,24 +237,81 @@ impl ApiResponse {
 }
 
 pub fn app(state: AppState) -> Router {
-    Router::new()
+    let max_upload_bytes = state
+        .artifact_store
+        .as_ref()
+        .map_or(DEFAULT_MAX_UPLOAD_BYTES, |store| store.max_upload_bytes);
+    let router = Router::new()
         .route("/ingest/run", post(ingest_run))
+        .route("/runs/:id/cancel", post(cancel_run))
         .route("/ingest/tests", post(ingest_tests))
+        .route("/ingest/tests/:id/progress", post(ingest_test_progress))
         .route("/ingest/signals", post(ingest_signals))
         .route("/ingest/artifacts", post(ingest_artifacts))
-        .route("/ingest/batch", post(ingest_batch))
+        .route(
+            "/ingest/artifacts/upload",
+            post(upload_artifact).layer(DefaultBodyLimit::max(
+                usize::try_from(max_upload_bytes).unwrap_or(usize::MAX),
+            )),
+        )
+        .route(
+            "/ingest/batch",
+            post(ingest_batch).route_layer(middleware::from_fn_with_state(
+                state.clone(),
+                http_metrics::track_batch_duration,
+            )),
+        )
+        .route("/ingest/jobs/:id", get(get_job))
+        .route("/ingest/junit", post(junit::ingest_junit))
         .route("/query", post(query_handler))
         .route("/api/resonance/flaky", get(get_flaky_tests))
+        .route("/api/stats/duration_histogram", get(get_duration_histogram))
+        .route("/api/tests", get(get_tests))
+        .route("/api/tests/:id/progress", get(get_test_progress))
+        .route("/api/tests/:suite/:name/drift", get(get_drift_series))
+        .route("/api/tests/:suite/:name/flake", get(get_flake_score))
+        .route("/api/runs", get(get_runs))
+        .route("/api/signals/:id/payload", get(get_signal_payload))
+        .route("/api/runs/:id/timeline", get(get_signal_timeline))
+        .route("/api/runs/:id/signals", get(get_run_signals))
+        .route("/api/runs/:id/status", get(get_run_status))
+        .route("/api/runs/:id/report", get(get_run_report))
+        .route("/api/builds/:id/runs", get(get_build_runs))
         .route("/metrics", get(metrics_handler))
+        .route("/metrics/json", get(metrics_json_handler))
         .layer(middleware::from_fn_with_state(
             state.clone(),
             auth_middleware,
-        ))
+        ));
+    // Shed rather than queue: waiting requests would hold their bodies in
+    // memory all the same. The limit is global, not per route; health
+    // checks stay exempt.
+    let router = match state.max_in_flight {
+        Some(max) => router.layer(
+            ServiceBuilder::new()
+                .layer(HandleErrorLayer::new(overloaded))
+                .load_shed()
+                .layer(GlobalConcurrencyLimitLayer::new(max)),
+        ),
+        None => router,
+    };
+    router
         .route("/health", get(health_check))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            http_metrics::track_requests,
+        ))
         .layer(CorsLayer::permissive())
         .with_state(state)
 }
 
+async fn overloaded(err: BoxError) -> (StatusCode, Json<ApiResponse>) {
+    (
+        StatusCode::SERVICE_UNAVAILABLE,
+        Json(ApiResponse::error(format!("Server overloaded: {}", err))),
+    )
+}
+
 async fn health_check() -> impl IntoResponse {
     #[derive(Serialize)]
     struct HealthCheck {
@@ -96,32 +339,46 @@ async fn metrics_handler(State(state): State<AppState>) -> impl IntoResponse {
     )
 }
 
+async fn metrics_json_handler(State(state): State<AppState>) -> impl IntoResponse {
+    Json(state.metrics.snapshot_json())
+}
+
 async fn auth_middleware(
     State(state): State<AppState>,
-    req: Request,
+    mut req: Request,
     next: Next,
 ) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse>)> {
-    if let Some(ref expected_token) = state.auth_token {
-        let auth_header = req
-            .headers()
-            .get(header::AUTHORIZATION)
-            .and_then(|h| h.to_str().ok());
-
-        let authenticated = match auth_header {
-            Some(auth_str) if auth_str.starts_with("Bearer ") => {
-                let token = &auth_str[7..];
-                token == expected_token
-            }
-            _ => false,
-        };
-
-        if !authenticated {
-            return Err((
-                StatusCode::UNAUTHORIZED,
-                Json(ApiResponse::error("Unauthorized: Invalid or missing token")),
-            ));
-        }
+    if state.auth_token.is_none() && state.scoped_tokens.is_empty() && state.jwt.is_none() {
+        req.extensions_mut()
+            .insert(GrantedScopes(Scope::ALL.into_iter().collect()));
+        return Ok(next.run(req).await);
+    }
+
+    let scopes = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|auth_str| auth_str.strip_prefix("Bearer "))
+        .and_then(|token| state.token_scopes(token));
+
+    let Some(scopes) = scopes else {
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            Json(ApiResponse::error("Unauthorized: Invalid or missing token")),
+        ));
+    };
+
+    let required = Scope::required_for(req.uri().path());
+    if !auth::allows(&scopes, required) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(ApiResponse::error(format!(
+                "Forbidden: token lacks the '{}' scope",
+                required.as_str()
+            ))),
+        ));
     }
 
+    req.extensions_mut().insert(GrantedScopes(scopes));
     Ok(next.run(req).await)
 }
@@ -1,15 +1,29 @@
 //! LiminalQA Ingest Server — REST API for test run data ingestion
 
 use anyhow::Result;
-use liminalqa_db::LiminalDB;
-use std::{net::SocketAddr, path::PathBuf, sync::Arc};
+use liminalqa_db::{LiminalDB, DEFAULT_MAX_FACT_VALUE_BYTES, DEFAULT_MIN_BASELINE_SAMPLES};
+use std::{collections::HashMap, net::SocketAddr, path::PathBuf, sync::Arc};
 use tower_http::trace::TraceLayer;
 use tracing::{info, Level};
 use tracing_subscriber::FmtSubscriber;
 
-use liminalqa_core::metrics::MetricsRegistry;
-use liminalqa_grpc::{IngestServiceServer, MyIngestService};
-use liminalqa_ingest::AppState;
+use liminalqa_core::{
+    baseline::{DriftThresholds, DEFAULT_SIGMA_THRESHOLD},
+    metrics::{HistogramBuckets, MetricsConfig, MetricsRegistry},
+    resonance::{DetectorRegistry, FlakeDetector},
+    signal_schema::{SchemaEnforcement, SignalSchemas},
+    types::TestStatus,
+};
+use liminalqa_grpc::{
+    health_service, reflection_service, IngestServiceServer, MtlsConfig, MyIngestService,
+};
+use liminalqa_ingest::{
+    auth::{parse_scopes, JwtConfig},
+    server::{self, ServerConfig},
+    spillover::{SignalSpillover, DEFAULT_MAX_INLINE_META_BYTES},
+    upload::{ArtifactStore, DEFAULT_MAX_UPLOAD_BYTES},
+    AppState,
+};
 use tonic::transport::Server;
 
 #[tokio::main]
@@ -28,26 +42,220 @@ async fn main() -> Result<()> {
     let db_path =
         std::env::var("LIMINAL_DB_PATH").unwrap_or_else(|_| "./data/liminaldb".to_string());
     info!("Opening database at: {}", db_path);
-    let db = LiminalDB::open(PathBuf::from(db_path))?;
+    let signal_index_keys: Vec<String> = std::env::var("LIMINAL_SIGNAL_INDEX_KEYS")
+        .map(|keys| {
+            keys.split(',')
+                .map(|k| k.trim().to_string())
+                .filter(|k| !k.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+    if !signal_index_keys.is_empty() {
+        info!("Indexing signal metadata keys: {:?}", signal_index_keys);
+    }
+    let max_fact_value_bytes = match std::env::var("LIMINAL_MAX_FACT_VALUE_BYTES") {
+        Ok(limit) => limit
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Invalid LIMINAL_MAX_FACT_VALUE_BYTES: {}", e))?,
+        Err(_) => DEFAULT_MAX_FACT_VALUE_BYTES,
+    };
+    let min_baseline_samples = match std::env::var("LIMINAL_MIN_BASELINE_SAMPLES") {
+        Ok(min) => min
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Invalid LIMINAL_MIN_BASELINE_SAMPLES: {}", e))?,
+        Err(_) => DEFAULT_MIN_BASELINE_SAMPLES,
+    };
+    // Results of up to LIMINAL_QUERY_CACHE_SIZE distinct queries are cached;
+    // disabled when unset or 0
+    let query_cache_size = match std::env::var("LIMINAL_QUERY_CACHE_SIZE") {
+        Ok(size) => size
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Invalid LIMINAL_QUERY_CACHE_SIZE: {}", e))?,
+        Err(_) => 0,
+    };
+    // LIMINAL_ENTITY_FACTS=true also records run, test and signal writes
+    // in the fact log
+    let entity_facts = match std::env::var("LIMINAL_ENTITY_FACTS") {
+        Ok(enabled) => enabled
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Invalid LIMINAL_ENTITY_FACTS: {}", e))?,
+        Err(_) => false,
+    };
+    let db = LiminalDB::open(PathBuf::from(db_path))?
+        .with_indexed_signal_meta_keys(signal_index_keys.clone())
+        .with_max_fact_value_bytes(max_fact_value_bytes)
+        .with_min_baseline_samples(min_baseline_samples)
+        .with_query_cache(query_cache_size)
+        .with_entity_facts(entity_facts);
     let db_arc = Arc::new(db);
 
     let auth_token = std::env::var("LIMINAL_AUTH_TOKEN").ok();
-    if auth_token.is_none() {
+    let scoped_tokens = std::env::var("LIMINAL_SCOPED_TOKENS").ok();
+    let jwt = jwt_config_from_env()?;
+    if auth_token.is_none() && scoped_tokens.is_none() && jwt.is_none() {
         tracing::error!(
-            "LIMINAL_AUTH_TOKEN not set! Authentication is DISABLED. \
-            This is a SECURITY RISK in production. Set LIMINAL_AUTH_TOKEN \
-            environment variable to enable authentication."
+            "Neither LIMINAL_AUTH_TOKEN nor LIMINAL_JWT_* is set! Authentication \
+            is DISABLED. This is a SECURITY RISK in production. Set \
+            LIMINAL_AUTH_TOKEN or configure JWT validation to enable authentication."
         );
     }
 
-    // Initialize metrics
-    let metrics = Arc::new(MetricsRegistry::new());
+    // Initialize metrics; LIMINAL_BATCH_DURATION_BUCKETS overrides the batch
+    // ingest histogram buckets (comma-separated seconds) and
+    // LIMINAL_METRICS_LABELS="instance=ingest-1,region=eu" labels every metric
+    let mut metrics_config = MetricsConfig::default();
+    if let Ok(buckets) = std::env::var("LIMINAL_BATCH_DURATION_BUCKETS") {
+        let buckets = buckets
+            .split(',')
+            .map(|b| b.trim().parse::<f64>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| anyhow::anyhow!("Invalid LIMINAL_BATCH_DURATION_BUCKETS: {}", e))?;
+        metrics_config.batch_duration_buckets = HistogramBuckets(buckets);
+    }
+    if let Ok(labels) = std::env::var("LIMINAL_METRICS_LABELS") {
+        for entry in labels.split(',').filter(|e| !e.trim().is_empty()) {
+            let (name, value) = entry.split_once('=').ok_or_else(|| {
+                anyhow::anyhow!("Invalid LIMINAL_METRICS_LABELS entry (expected name=value)")
+            })?;
+            metrics_config = metrics_config.with_const_label(name.trim(), value.trim());
+        }
+    }
+    let metrics = Arc::new(MetricsRegistry::with_config(metrics_config));
+
+    let mut state = AppState::new(db_arc.clone(), auth_token, metrics.clone());
+    if let Some(jwt) = jwt {
+        info!("JWT authentication enabled: {:?}", jwt);
+        state = state.with_jwt(jwt);
+    }
+
+    // Scoped static tokens: LIMINAL_SCOPED_TOKENS="ci-token=ingest;dash-token=query"
+    if let Some(tokens) = scoped_tokens {
+        for entry in tokens.split(';').filter(|e| !e.trim().is_empty()) {
+            let (token, scopes) = entry.split_once('=').ok_or_else(|| {
+                anyhow::anyhow!("Invalid LIMINAL_SCOPED_TOKENS entry (expected token=scopes)")
+            })?;
+            let scopes = parse_scopes(scopes)
+                .map_err(|e| anyhow::anyhow!("Invalid LIMINAL_SCOPED_TOKENS entry: {}", e))?;
+            info!("Scoped token enabled with scopes: {:?}", scopes);
+            state = state.with_scoped_token(token.trim(), scopes);
+        }
+    }
 
-    let state = AppState {
-        db: db_arc.clone(),
-        auth_token,
-        metrics,
+    // Signal metadata over LIMINAL_SIGNAL_MAX_META_BYTES is spilled to files
+    // in LIMINAL_SIGNAL_SPILL_DIR
+    let spill_dir = std::env::var("LIMINAL_SIGNAL_SPILL_DIR")
+        .unwrap_or_else(|_| "./data/signal_payloads".to_string());
+    let max_inline_meta_bytes = match std::env::var("LIMINAL_SIGNAL_MAX_META_BYTES") {
+        Ok(limit) => limit
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Invalid LIMINAL_SIGNAL_MAX_META_BYTES: {}", e))?,
+        Err(_) => DEFAULT_MAX_INLINE_META_BYTES,
     };
+    let spillover = Arc::new(SignalSpillover::new(spill_dir, max_inline_meta_bytes));
+    state = state.with_signal_spillover((*spillover).clone());
+
+    // Files uploaded to /ingest/artifacts/upload are kept in LIMINAL_ARTIFACT_DIR,
+    // bodies up to LIMINAL_MAX_UPLOAD_BYTES
+    let artifact_dir =
+        std::env::var("LIMINAL_ARTIFACT_DIR").unwrap_or_else(|_| "./data/artifacts".to_string());
+    let max_upload_bytes = match std::env::var("LIMINAL_MAX_UPLOAD_BYTES") {
+        Ok(limit) => limit
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Invalid LIMINAL_MAX_UPLOAD_BYTES: {}", e))?,
+        Err(_) => DEFAULT_MAX_UPLOAD_BYTES,
+    };
+    state = state.with_artifact_store(
+        ArtifactStore::new(artifact_dir).with_max_upload_bytes(max_upload_bytes),
+    );
+
+    // Drift beyond LIMINAL_DRIFT_SIGMA stddevs, overridden per suite by
+    // LIMINAL_DRIFT_SUITE_SIGMAS="ui=3.5,unit=1.5"
+    let default_sigma = match std::env::var("LIMINAL_DRIFT_SIGMA") {
+        Ok(sigma) => sigma
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Invalid LIMINAL_DRIFT_SIGMA: {}", e))?,
+        Err(_) => DEFAULT_SIGMA_THRESHOLD,
+    };
+    let suite_sigmas = std::env::var("LIMINAL_DRIFT_SUITE_SIGMAS").unwrap_or_default();
+    let drift_thresholds = DriftThresholds::parse(default_sigma, &suite_sigmas)?;
+    if !drift_thresholds.per_suite.is_empty() {
+        info!(
+            "Drift thresholds per suite: {:?}",
+            drift_thresholds.per_suite
+        );
+    }
+    state = state.with_drift_thresholds(drift_thresholds);
+
+    // Signals lacking required metadata are flagged or rejected per
+    // LIMINAL_SIGNAL_SCHEMA (off, flag, reject); LIMINAL_SIGNAL_SCHEMA_KEYS
+    //="api=method|status|url,ws=channel" overrides the keys per kind
+    let enforcement = match std::env::var("LIMINAL_SIGNAL_SCHEMA") {
+        Ok(mode) => mode
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Invalid LIMINAL_SIGNAL_SCHEMA: {}", e))?,
+        Err(_) => SchemaEnforcement::Off,
+    };
+    let schema_keys = std::env::var("LIMINAL_SIGNAL_SCHEMA_KEYS").unwrap_or_default();
+    state = state.with_signal_schemas(SignalSchemas::parse(enforcement, &schema_keys)?);
+
+    // Flaky labels clear after LIMINAL_FLAKY_STABLE_WINDOWS non-flaky windows
+    if let Ok(windows) = std::env::var("LIMINAL_FLAKY_STABLE_WINDOWS") {
+        let windows: usize = windows
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Invalid LIMINAL_FLAKY_STABLE_WINDOWS: {}", e))?;
+        state = state.with_pattern_detectors(
+            DetectorRegistry::empty()
+                .with_detector(FlakeDetector::default().with_stable_windows(windows)),
+        );
+    }
+
+    // Test status aliases: LIMINAL_STATUS_ALIASES="error=fail,xpass=xfail"
+    let status_aliases = status_aliases_from_env()?;
+    if !status_aliases.is_empty() {
+        info!("Test status aliases: {:?}", status_aliases);
+    }
+    for (label, status) in &status_aliases {
+        state = state.with_status_alias(label, *status);
+    }
+
+    // Additional tenants: LIMINAL_TENANTS="team-a=/data/a,team-b=/data/b"
+    if let Ok(tenants) = std::env::var("LIMINAL_TENANTS") {
+        for entry in tenants.split(',').filter(|e| !e.trim().is_empty()) {
+            let (tenant, path) = entry
+                .split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("Invalid LIMINAL_TENANTS entry: {}", entry))?;
+            info!(
+                "Opening database for tenant '{}' at: {}",
+                tenant.trim(),
+                path
+            );
+            let tenant_db = LiminalDB::open(PathBuf::from(path.trim()))?
+                .with_indexed_signal_meta_keys(signal_index_keys.clone())
+                .with_max_fact_value_bytes(max_fact_value_bytes)
+                .with_min_baseline_samples(min_baseline_samples)
+                .with_query_cache(query_cache_size)
+                .with_entity_facts(entity_facts);
+            state = state.with_tenant(tenant.trim(), Arc::new(tenant_db));
+        }
+    }
+
+    // Requests beyond LIMINAL_MAX_IN_FLIGHT at once get 503
+    if let Ok(max) = std::env::var("LIMINAL_MAX_IN_FLIGHT") {
+        let max: usize = max
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Invalid LIMINAL_MAX_IN_FLIGHT: {}", e))?;
+        info!("Handling at most {} requests at once", max);
+        state = state.with_max_in_flight(max);
+    }
+
+    // Async batches waiting beyond LIMINAL_BATCH_QUEUE_CAPACITY get 503
+    if let Ok(capacity) = std::env::var("LIMINAL_BATCH_QUEUE_CAPACITY") {
+        let capacity: usize = capacity
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Invalid LIMINAL_BATCH_QUEUE_CAPACITY: {}", e))?;
+        info!("Queueing at most {} async batches", capacity);
+        state = state.with_batch_jobs(liminalqa_ingest::jobs::BatchJobs::new(capacity));
+    }
 
     // Build REST Router
     let app = liminalqa_ingest::app(state).layer(TraceLayer::new_for_http());
@@ -59,15 +267,28 @@ async fn main() -> Result<()> {
     info!("REST Listening on http://{}", rest_addr);
     info!("gRPC Listening on {}", grpc_addr);
 
+    let server_config = server_config_from_env()?;
     let rest_server = async {
         let listener = tokio::net::TcpListener::bind(rest_addr).await?;
-        axum::serve(listener, app)
-            .await
-            .map_err(|e| anyhow::anyhow!(e))
+        server::serve(listener, app, metrics, server_config).await;
+        Ok::<_, anyhow::Error>(())
     };
 
-    let grpc_service = MyIngestService::new(db_arc.clone());
-    let grpc_server = Server::builder()
+    let grpc_service = MyIngestService::new(db_arc.clone())
+        .with_status_aliases(status_aliases)
+        .with_signal_spillover(spillover);
+    // Plaintext unless LIMINAL_GRPC_TLS_{CERT,KEY,CLIENT_CA} are set
+    let mut grpc_builder = Server::builder();
+    if let Some(mtls) = MtlsConfig::from_env()? {
+        info!(
+            "gRPC requires client certificates signed by {:?}",
+            mtls.client_ca_path
+        );
+        grpc_builder = grpc_builder.tls_config(mtls.server_tls_config()?)?;
+    }
+    let grpc_server = grpc_builder
+        .add_service(health_service().await)
+        .add_service(reflection_service()?)
         .add_service(IngestServiceServer::new(grpc_service))
         .serve(grpc_addr);
 
@@ -87,3 +308,68 @@ async fn main() -> Result<()> {
     #[allow(clippy::disallowed_methods)]
     Ok(())
 }
+
+/// REST connection settings from LIMINAL_HTTP_KEEP_ALIVE (`true`/`false`)
+/// and LIMINAL_HTTP_HEADER_READ_TIMEOUT_SECS
+fn server_config_from_env() -> Result<ServerConfig> {
+    let mut config = ServerConfig::default();
+    if let Ok(keep_alive) = std::env::var("LIMINAL_HTTP_KEEP_ALIVE") {
+        config.keep_alive = keep_alive
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Invalid LIMINAL_HTTP_KEEP_ALIVE: {}", e))?;
+    }
+    if let Ok(secs) = std::env::var("LIMINAL_HTTP_HEADER_READ_TIMEOUT_SECS") {
+        let secs: u64 = secs
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Invalid LIMINAL_HTTP_HEADER_READ_TIMEOUT_SECS: {}", e))?;
+        config.header_read_timeout = std::time::Duration::from_secs(secs);
+    }
+    Ok(config)
+}
+
+/// Status aliases from LIMINAL_STATUS_ALIASES (`label=status,...`); statuses
+/// use their canonical names (`pass`, `fail`, `xfail`, `flake`, `timeout`,
+/// `skip`)
+fn status_aliases_from_env() -> Result<HashMap<String, TestStatus>> {
+    let Ok(aliases) = std::env::var("LIMINAL_STATUS_ALIASES") else {
+        return Ok(HashMap::new());
+    };
+    aliases
+        .split(',')
+        .filter(|e| !e.trim().is_empty())
+        .map(|entry| {
+            let (label, status) = entry.split_once('=').ok_or_else(|| {
+                anyhow::anyhow!("Invalid LIMINAL_STATUS_ALIASES entry: {}", entry)
+            })?;
+            let status: TestStatus =
+                serde_json::from_value(serde_json::Value::String(status.trim().to_lowercase()))
+                    .map_err(|_| anyhow::anyhow!("Unknown test status in alias: {}", entry))?;
+            Ok((label.trim().to_lowercase(), status))
+        })
+        .collect()
+}
+
+/// JWT validation from LIMINAL_JWT_HS256_SECRET or LIMINAL_JWT_RS256_PUBLIC_KEY
+/// (path to a PEM file), with optional LIMINAL_JWT_ISSUER and
+/// LIMINAL_JWT_AUDIENCE
+fn jwt_config_from_env() -> Result<Option<JwtConfig>> {
+    let config = if let Ok(secret) = std::env::var("LIMINAL_JWT_HS256_SECRET") {
+        JwtConfig::hs256(secret.as_bytes())
+    } else if let Ok(path) = std::env::var("LIMINAL_JWT_RS256_PUBLIC_KEY") {
+        let pem = std::fs::read(&path)
+            .map_err(|e| anyhow::anyhow!("Failed to read JWT public key {}: {}", path, e))?;
+        JwtConfig::rs256_pem(&pem)?
+    } else {
+        return Ok(None);
+    };
+
+    let config = match std::env::var("LIMINAL_JWT_ISSUER") {
+        Ok(issuer) => config.with_issuer(issuer),
+        Err(_) => config,
+    };
+    let config = match std::env::var("LIMINAL_JWT_AUDIENCE") {
+        Ok(audience) => config.with_audience(audience),
+        Err(_) => config,
+    };
+    Ok(Some(config))
+}
@@ -0,0 +1,287 @@
+//! Reflection reports and run, test and signal lookups over HTTP
+
+use crate::{extract::TenantDb, spillover::fetch_payload, ApiResponse, AppState};
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use chrono::Utc;
+use liminalqa_core::{
+    entities::Run,
+    report::CausalityWindow,
+    types::{EntityId, RunStatus},
+};
+use liminalqa_db::{build_owner_report, build_report_with_window, build_runs, DbError};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::warn;
+
+#[derive(Debug, Deserialize)]
+pub struct ReportParams {
+    /// Only report on the tests of this owner
+    pub owner: Option<String>,
+}
+
+/// GET /api/runs/:id/report?owner= — Reflection report of a run
+pub async fn get_run_report(
+    TenantDb(db): TenantDb,
+    Path(run_id): Path<EntityId>,
+    Query(params): Query<ReportParams>,
+) -> impl IntoResponse {
+    match db.get_entity::<Run>(run_id) {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ApiResponse::error(format!("Run not found: {}", run_id))),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error(format!("Failed to load run: {}", e))),
+            )
+                .into_response()
+        }
+    }
+
+    let (now, window) = (Utc::now(), CausalityWindow::default());
+    let report = match params.owner.as_deref() {
+        Some(owner) => build_owner_report(&db, run_id, now, window, owner),
+        None => build_report_with_window(&db, run_id, now, window),
+    };
+    match report {
+        Ok(report) => (StatusCode::OK, Json(report)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error(format!("Failed to build report: {}", e))),
+        )
+            .into_response(),
+    }
+}
+
+/// GET /api/builds/:id/runs — Every run of a build with its test summary
+pub async fn get_build_runs(
+    TenantDb(db): TenantDb,
+    Path(build_id): Path<EntityId>,
+) -> impl IntoResponse {
+    match build_runs(&db, build_id) {
+        Ok(runs) => (StatusCode::OK, Json(runs)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error(format!(
+                "Failed to query build runs: {}",
+                e
+            ))),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RunsParams {
+    /// `key=value`: only runs carrying this annotation
+    pub annotation: Option<String>,
+}
+
+/// A run with its current annotations
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AnnotatedRun {
+    #[serde(flatten)]
+    pub run: Run,
+    pub annotations: HashMap<String, String>,
+    /// Expected back in `If-Match` by updates of the run
+    #[serde(default)]
+    pub version: u64,
+}
+
+/// GET /api/runs?annotation=pr=123 — Runs, oldest first, optionally
+/// filtered by annotation
+pub async fn get_runs(
+    TenantDb(db): TenantDb,
+    Query(params): Query<RunsParams>,
+) -> impl IntoResponse {
+    let annotation = match params.annotation.as_deref().map(|a| a.split_once('=')) {
+        None => None,
+        Some(Some((key, value))) if !key.trim().is_empty() => Some((key.trim(), value.trim())),
+        Some(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::error("annotation must be key=value")),
+            )
+                .into_response();
+        }
+    };
+    let runs = db.annotated_runs(annotation).and_then(|runs| {
+        runs.into_iter()
+            .map(|(run, annotations)| {
+                Ok(AnnotatedRun {
+                    version: db.run_version(run.id)?,
+                    run,
+                    annotations,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()
+    });
+    match runs {
+        Ok(runs) => (StatusCode::OK, Json(runs)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error(format!("Failed to query runs: {}", e))),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TestsParams {
+    pub owner: Option<String>,
+    pub run_id: Option<EntityId>,
+}
+
+/// GET /api/tests?owner= | ?run_id= — Every execution of the tests an owner
+/// owns, or every test of a run in completion order
+pub async fn get_tests(
+    TenantDb(db): TenantDb,
+    Query(params): Query<TestsParams>,
+) -> impl IntoResponse {
+    let tests = match (params.owner, params.run_id) {
+        (Some(owner), None) => db.tests_by_owner(&owner),
+        (None, Some(run_id)) => db.tests_of_run(run_id),
+        _ => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::error("Give exactly one of owner or run_id")),
+            )
+                .into_response();
+        }
+    };
+    match tests {
+        Ok(tests) => (StatusCode::OK, Json(tests)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error(format!("Failed to query tests: {}", e))),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RunSignalsParams {
+    /// Only signals with a higher sequence number
+    pub after: Option<u64>,
+}
+
+/// GET /api/runs/:id/signals?after= — Signals of a run in sequence order
+pub async fn get_run_signals(
+    TenantDb(db): TenantDb,
+    Path(run_id): Path<EntityId>,
+    Query(params): Query<RunSignalsParams>,
+) -> impl IntoResponse {
+    match db.signals_of_run(run_id, params.after) {
+        Ok(signals) => (StatusCode::OK, Json(signals)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error(format!(
+                "Failed to query signals: {}",
+                e
+            ))),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RunStatusResponse {
+    pub run_id: EntityId,
+    pub status: RunStatus,
+}
+
+/// GET /api/runs/:id/status — Whether a run is still running
+pub async fn get_run_status(
+    TenantDb(db): TenantDb,
+    Path(run_id): Path<EntityId>,
+) -> impl IntoResponse {
+    match db.run_status(run_id) {
+        Ok(status) => (StatusCode::OK, Json(RunStatusResponse { run_id, status })).into_response(),
+        Err(e) => {
+            let status = match e.downcast_ref::<DbError>() {
+                Some(DbError::RunNotFound(_)) => StatusCode::NOT_FOUND,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            (
+                status,
+                Json(ApiResponse::error(format!(
+                    "Failed to read run status: {}",
+                    e
+                ))),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// GET /api/signals/:id/payload — The full payload a signal references,
+/// whether uploaded by the client or spilled from oversized metadata.
+///
+/// Only files at the content address of the artifact store or the spillover
+/// directory are served. Every payload that cannot be served gets the same
+/// 404, so the endpoint reveals nothing about other files of the host.
+pub async fn get_signal_payload(
+    State(state): State<AppState>,
+    TenantDb(db): TenantDb,
+    Path(signal_id): Path<EntityId>,
+) -> impl IntoResponse {
+    let payload_ref = match db.get_signal(signal_id) {
+        Ok(signal) => signal.and_then(|s| s.payload_ref),
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error(format!("Failed to load signal: {}", e))),
+            )
+                .into_response();
+        }
+    };
+    let served = payload_ref.filter(|payload_ref| {
+        state
+            .artifact_store
+            .as_ref()
+            .is_some_and(|store| store.holds(payload_ref))
+            || state
+                .signal_spillover
+                .as_ref()
+                .is_some_and(|spillover| spillover.holds(payload_ref))
+    });
+    let payload = served.and_then(|payload_ref| match fetch_payload(&payload_ref) {
+        Ok(bytes) => Some((payload_ref, bytes)),
+        Err(e) => {
+            warn!("Failed to fetch payload of signal {}: {:#}", signal_id, e);
+            None
+        }
+    });
+
+    match payload {
+        Some((payload_ref, bytes)) => {
+            let content_type = payload_ref
+                .mime_type
+                .unwrap_or_else(|| "application/octet-stream".to_string());
+            (
+                StatusCode::OK,
+                [(header::CONTENT_TYPE, content_type)],
+                bytes,
+            )
+                .into_response()
+        }
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::error(format!(
+                "No payload available for signal {}",
+                signal_id
+            ))),
+        )
+            .into_response(),
+    }
+}
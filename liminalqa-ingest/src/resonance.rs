@@ -1,40 +1,153 @@
-use crate::{ApiResponse, AppState};
-use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
-use liminalqa_core::{entities::*, resonance::FlakeDetector, types::*};
+use crate::{extract::TenantDb, ApiResponse};
+use axum::{
+    extract::{Path, Query},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use liminalqa_core::{
+    entities::*,
+    metrics::{SharedMetrics, SuiteLabels},
+    resonance::{DetectorRegistry, FlakeDetector},
+};
 use liminalqa_db::LiminalDB;
+use serde::{Deserialize, Serialize};
 use tracing::{info, warn};
 
-/// GET /api/resonance/flaky
-pub async fn get_flaky_tests(State(state): State<AppState>) -> impl IntoResponse {
-    let db = &state.db;
+fn default_flaky_limit() -> usize {
+    100
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FlakyParams {
+    /// Number of resonances returned, highest score first
+    #[serde(default = "default_flaky_limit")]
+    pub limit: usize,
+}
+
+/// GET /api/resonance/flaky?limit=100 — Resonances not resolved yet,
+/// highest score first
+pub async fn get_flaky_tests(
+    TenantDb(db): TenantDb,
+    Query(params): Query<FlakyParams>,
+) -> impl IntoResponse {
+    if params.limit == 0 {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error("limit must be greater than zero")),
+        )
+            .into_response();
+    }
 
-    // Scan all Resonance entities
-    let flaky_ids = match db.get_entities_by_type(EntityType::Resonance) {
-        Ok(ids) => ids,
+    match db.open_resonances_by_score(params.limit) {
+        Ok(flaky_tests) => (StatusCode::OK, Json(flaky_tests)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error(format!(
+                "Failed to scan resonance entities: {}",
+                e
+            ))),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FlakeParams {
+    /// Executions scored, newest first; the detector default when absent
+    pub window: Option<usize>,
+    /// Score above which the test is flagged; the detector default when absent
+    pub threshold: Option<f64>,
+}
+
+/// Flake score of a test computed from its latest executions
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FlakeScore {
+    pub name: String,
+    pub suite: String,
+    pub score: f64,
+    pub flagged: bool,
+    pub window: usize,
+    pub threshold: f64,
+    /// Executions in the window; fewer than `window` for a young test
+    pub executions: usize,
+}
+
+/// GET /api/tests/:suite/:name/flake?window=10&threshold=0.3 — Flake score
+/// of a test recomputed from its history
+pub async fn get_flake_score(
+    TenantDb(db): TenantDb,
+    Path((suite, name)): Path<(String, String)>,
+    Query(params): Query<FlakeParams>,
+) -> impl IntoResponse {
+    let defaults = FlakeDetector::default();
+    let window = params.window.unwrap_or(defaults.window_size());
+    let threshold = params.threshold.unwrap_or(defaults.threshold());
+    if window == 0 {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error("window must be greater than zero")),
+        )
+            .into_response();
+    }
+    if !(threshold.is_finite() && threshold >= 0.0) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(
+                "threshold must be a non-negative number",
+            )),
+        )
+            .into_response();
+    }
+
+    let history = match db.get_test_history(&name, &suite, window) {
+        Ok(history) => history,
         Err(e) => {
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(ApiResponse::error(format!(
-                    "Failed to scan resonance entities: {}",
+                    "Failed to get test history: {}",
                     e
                 ))),
             )
-                .into_response();
+                .into_response()
         }
     };
-
-    let mut flaky_tests = Vec::new();
-    for id in flaky_ids {
-        if let Ok(Some(resonance)) = db.get_entity::<Resonance>(id) {
-            flaky_tests.push(resonance);
-        }
+    if history.is_empty() {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::error(format!(
+                "No executions of test {} in suite {}",
+                name, suite
+            ))),
+        )
+            .into_response();
     }
 
-    (StatusCode::OK, Json(flaky_tests)).into_response()
+    let score = FlakeDetector::new(window, threshold).score_latest(&history);
+    (
+        StatusCode::OK,
+        Json(FlakeScore {
+            name,
+            suite,
+            score,
+            flagged: score > threshold,
+            window,
+            threshold,
+            executions: history.len(),
+        }),
+    )
+        .into_response()
 }
 
-/// Helper to check if a test is flaky and record it
-pub fn check_and_record_flakiness(db: &LiminalDB, test: &Test) {
+/// Run the pattern detectors over the history of a test and record what
+/// they find
+pub fn check_and_record_patterns(
+    db: &LiminalDB,
+    metrics: &SharedMetrics,
+    detectors: &DetectorRegistry,
+    test: &Test,
+) {
     // 1. Get history (last 20 runs)
     let history = match db.get_test_history(&test.name, &test.suite, 20) {
         Ok(h) => h,
@@ -44,36 +157,227 @@ pub fn check_and_record_flakiness(db: &LiminalDB, test: &Test) {
         }
     };
 
-    // 2. Extract statuses
-    let statuses: Vec<TestStatus> = history.iter().map(|t| t.status).collect();
+    // 2. Detect
+    let found = detectors.detect(&history);
+    if found.is_empty() {
+        resolve_stable_patterns(db, detectors, test, &history);
+    }
+    for (detector, resonance) in found {
+        info!(
+            "Detector '{}' found a pattern for test {}: {}",
+            detector, test.name, resonance.pattern.description
+        );
+
+        if let Err(e) = db.put_resonance(&resonance) {
+            warn!("Failed to store resonance: {}", e);
+            continue;
+        }
 
-    // 3. Detect
-    let detector = FlakeDetector::default();
-    let score = detector.calculate_score(&statuses);
+        if detector == FlakeDetector::NAME {
+            metrics
+                .flaky_detections
+                .get_or_create(&SuiteLabels {
+                    suite: test.suite.clone(),
+                })
+                .inc();
+        }
+    }
+}
 
-    if detector.is_flaky(&statuses) {
+/// Resolve the open resonances of `test` its detectors no longer see in
+/// `history`, so stale flaky labels clear once the test settles down
+fn resolve_stable_patterns(
+    db: &LiminalDB,
+    detectors: &DetectorRegistry,
+    test: &Test,
+    history: &[Test],
+) {
+    let open = match db.open_resonances_for_test(&test.name, &test.suite) {
+        Ok(open) => open,
+        Err(e) => {
+            warn!("Failed to get resonances for test {}: {}", test.name, e);
+            return;
+        }
+    };
+    for resonance in open {
+        let Some(detector) = detectors.resolves(&resonance, history) else {
+            continue;
+        };
         info!(
-            "Test {} identified as flaky! Score: {:.2}",
-            test.name, score
+            "Detector '{}' resolved a pattern for test {}: {}",
+            detector, test.name, resonance.pattern.description
         );
+        if let Err(e) = db.resolve_resonance(resonance.id, detector) {
+            warn!("Failed to resolve resonance: {}", e);
+        }
+    }
+}
 
-        let resonance = Resonance {
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use liminalqa_core::{
+        facts::Attribute, metrics::MetricsRegistry, resonance::PatternDetector,
+        temporal::BiTemporalTime, types::*,
+    };
+    use std::sync::Arc;
+
+    fn make_test(status: TestStatus, minutes_ago: i64) -> Test {
+        let started_at = chrono::Utc::now() - chrono::Duration::minutes(minutes_ago);
+        Test {
             id: EntityId::new(),
-            pattern: ResonancePattern {
-                pattern_id: EntityId::new(),
-                description: format!("Flaky test detected: {} (Score: {:.2})", test.name, score),
-                score,
-                occurrences: 1,
-                first_seen: chrono::Utc::now(),
-                last_seen: chrono::Utc::now(),
-            },
-            affected_tests: vec![test.id],
-            root_cause: None,
-            created_at: liminalqa_core::temporal::BiTemporalTime::now(),
+            run_id: EntityId::new(),
+            name: "test_checkout".to_string(),
+            suite: "payments".to_string(),
+            guidance: "".to_string(),
+            status,
+            duration_ms: 100,
+            error: None,
+            started_at,
+            completed_at: started_at,
+            created_at: BiTemporalTime::now(),
+        }
+    }
+
+    #[test]
+    fn test_flaky_detection_increments_counter() -> anyhow::Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+        let metrics: SharedMetrics = Arc::new(MetricsRegistry::new());
+        let detectors = DetectorRegistry::default();
+        let labels = SuiteLabels {
+            suite: "payments".to_string(),
         };
 
-        if let Err(e) = db.put_resonance(&resonance) {
-            warn!("Failed to store resonance: {}", e);
+        // Stable history: no detection
+        let stable = make_test(TestStatus::Pass, 10);
+        db.put_test(&stable)?;
+        check_and_record_patterns(&db, &metrics, &detectors, &stable);
+        assert_eq!(metrics.flaky_detections.get_or_create(&labels).get(), 0);
+
+        // Alternating pass/fail is flaky
+        let statuses = [TestStatus::Fail, TestStatus::Pass, TestStatus::Fail];
+        for (i, status) in statuses.into_iter().enumerate() {
+            db.put_test(&make_test(status, 9 - i as i64))?;
+        }
+        let latest = make_test(TestStatus::Pass, 1);
+        db.put_test(&latest)?;
+        check_and_record_patterns(&db, &metrics, &detectors, &latest);
+
+        assert_eq!(metrics.flaky_detections.get_or_create(&labels).get(), 1);
+        assert!(metrics
+            .export()
+            .contains("liminalqa_flaky_detections_total"));
+
+        Ok(())
+    }
+
+    /// Flags every test slower than 1s
+    struct SlowTests;
+
+    impl PatternDetector for SlowTests {
+        fn name(&self) -> &str {
+            "slow"
         }
+
+        fn detect(&self, history: &[Test]) -> Vec<Resonance> {
+            history
+                .first()
+                .filter(|t| t.duration_ms > 1000)
+                .map(|t| Resonance {
+                    id: EntityId::new(),
+                    detector: "slow".to_string(),
+                    pattern: ResonancePattern {
+                        pattern_id: EntityId::new(),
+                        description: format!("{} is slow", t.name),
+                        score: 1.0,
+                        occurrences: 1,
+                        first_seen: t.started_at,
+                        last_seen: t.started_at,
+                    },
+                    affected_tests: vec![t.id],
+                    root_cause: None,
+                    created_at: BiTemporalTime::now(),
+                })
+                .into_iter()
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_custom_detector_resonance_is_stored() -> anyhow::Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+        let metrics: SharedMetrics = Arc::new(MetricsRegistry::new());
+        let detectors = DetectorRegistry::empty().with_detector(SlowTests);
+
+        let mut slow = make_test(TestStatus::Pass, 1);
+        slow.duration_ms = 4000;
+        db.put_test(&slow)?;
+        check_and_record_patterns(&db, &metrics, &detectors, &slow);
+
+        let stored = db.get_entities_by_type(EntityType::Resonance)?;
+        assert_eq!(stored.len(), 1);
+        let resonance: Resonance = db.get_entity(stored[0])?.expect("resonance stored");
+        assert_eq!(resonance.affected_tests, vec![slow.id]);
+        // Only the flake detector counts as a flaky detection
+        let labels = SuiteLabels {
+            suite: "payments".to_string(),
+        };
+        assert_eq!(metrics.flaky_detections.get_or_create(&labels).get(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_flaky_resonance_resolves_after_stable_windows() -> anyhow::Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let db = LiminalDB::open(temp_dir.path())?;
+        let metrics: SharedMetrics = Arc::new(MetricsRegistry::new());
+        let detectors = DetectorRegistry::empty()
+            .with_detector(FlakeDetector::new(4, 0.3).with_stable_windows(2));
+
+        let mut minutes_ago = 100;
+        let mut ingest = |status: TestStatus| -> anyhow::Result<()> {
+            let test = make_test(status, minutes_ago);
+            minutes_ago -= 1;
+            db.put_test(&test)?;
+            check_and_record_patterns(&db, &metrics, &detectors, &test);
+            Ok(())
+        };
+        for status in [
+            TestStatus::Pass,
+            TestStatus::Fail,
+            TestStatus::Pass,
+            TestStatus::Fail,
+            TestStatus::Pass,
+        ] {
+            ingest(status)?;
+        }
+        let open = db.open_resonances_for_test("test_checkout", "payments")?;
+        assert!(!open.is_empty());
+
+        // The newest window has to slide past the failures first: passes 1
+        // and 2 still see a flaky window among the latest two, and flag again
+        for _ in 0..2 {
+            ingest(TestStatus::Pass)?;
+        }
+        assert!(!db
+            .open_resonances_for_test("test_checkout", "payments")?
+            .is_empty());
+        ingest(TestStatus::Pass)?;
+        assert!(db
+            .open_resonances_for_test("test_checkout", "payments")?
+            .is_empty());
+        for resonance in &open {
+            assert!(db.resonance_resolved_at(resonance.id)?.is_some());
+        }
+        let facts = db.scan_facts_by_entities(&[open[0].id])?;
+        assert!(facts
+            .iter()
+            .any(|f| f.attribute == Attribute::ResonanceResolved
+                && f.value == serde_json::json!(FlakeDetector::NAME)));
+
+        Ok(())
     }
 }
@@ -0,0 +1,116 @@
+//! REST server: HTTP keep-alive settings and connection tracking
+
+use std::time::Duration;
+
+use axum::Router;
+use hyper_util::{
+    rt::{TokioExecutor, TokioIo, TokioTimer},
+    server::conn::auto,
+    service::TowerToHyperService,
+};
+use liminalqa_core::metrics::{ConnectionLabels, SharedMetrics};
+use tokio::net::TcpListener;
+use tracing::{debug, warn};
+
+/// HTTP connection settings of the REST server
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    /// Keep HTTP/1 connections open between requests
+    pub keep_alive: bool,
+    /// Close connections that do not send a complete request header in time,
+    /// including idle kept-alive connections
+    pub header_read_timeout: Duration,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            keep_alive: true,
+            header_read_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Counts a connection as open for as long as it is alive
+struct ConnectionGuard {
+    metrics: SharedMetrics,
+    labels: ConnectionLabels,
+}
+
+impl ConnectionGuard {
+    fn open(metrics: SharedMetrics, kind: &str) -> Self {
+        let labels = ConnectionLabels {
+            kind: kind.to_string(),
+        };
+        metrics.connections_total.get_or_create(&labels).inc();
+        metrics.active_connections.get_or_create(&labels).inc();
+        Self { metrics, labels }
+    }
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        self.metrics
+            .active_connections
+            .get_or_create(&self.labels)
+            .dec();
+    }
+}
+
+/// Serve `app` on `listener` forever.
+///
+/// Every accepted connection is tracked in `liminalqa_active_connections`
+/// and `liminalqa_connections_total` (kind `http`).
+pub async fn serve(
+    listener: TcpListener,
+    app: Router,
+    metrics: SharedMetrics,
+    config: ServerConfig,
+) {
+    let mut builder = auto::Builder::new(TokioExecutor::new());
+    builder
+        .http1()
+        .keep_alive(config.keep_alive)
+        .timer(TokioTimer::new())
+        .header_read_timeout(config.header_read_timeout);
+
+    loop {
+        let (stream, peer) = match listener.accept().await {
+            Ok(connection) => connection,
+            // The peer gave up before the connection was accepted
+            Err(e) if is_connection_error(&e) => continue,
+            Err(e) => {
+                // Typically out of file descriptors: back off instead of
+                // spinning until connections are released
+                warn!("Failed to accept connection: {}", e);
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                continue;
+            }
+        };
+        if let Err(e) = stream.set_nodelay(true) {
+            debug!("Failed to set TCP_NODELAY for {}: {}", peer, e);
+        }
+
+        let guard = ConnectionGuard::open(metrics.clone(), "http");
+        let service = TowerToHyperService::new(app.clone());
+        let builder = builder.clone();
+        tokio::spawn(async move {
+            let _guard = guard;
+            if let Err(e) = builder
+                .serve_connection_with_upgrades(TokioIo::new(stream), service)
+                .await
+            {
+                debug!("Connection from {} closed with error: {}", peer, e);
+            }
+        });
+    }
+}
+
+fn is_connection_error(e: &std::io::Error) -> bool {
+    matches!(
+        e.kind(),
+        std::io::ErrorKind::ConnectionRefused
+            | std::io::ErrorKind::ConnectionAborted
+            | std::io::ErrorKind::ConnectionReset
+    )
+}
@@ -0,0 +1,226 @@
+use crate::{extract::TenantDb, ApiResponse, AppState};
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::Utc;
+use liminalqa_core::{
+    baseline::{BaselineBand, DriftDataPoint, DriftDetector},
+    types::EntityId,
+};
+use liminalqa_db::TimelineBucket;
+use serde::{Deserialize, Serialize};
+
+fn default_bucket_ms() -> u32 {
+    100
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DurationHistogramParams {
+    pub suite: Option<String>,
+    pub run_id: Option<EntityId>,
+    #[serde(default = "default_bucket_ms")]
+    pub bucket_ms: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DurationBucket {
+    pub start_ms: u32,
+    pub count: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DurationHistogram {
+    pub bucket_ms: u32,
+    pub buckets: Vec<DurationBucket>,
+}
+
+/// GET /api/stats/duration_histogram?suite=&run_id=&bucket_ms=
+pub async fn get_duration_histogram(
+    TenantDb(db): TenantDb,
+    Query(params): Query<DurationHistogramParams>,
+) -> impl IntoResponse {
+    if params.bucket_ms == 0 {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error("bucket_ms must be greater than zero")),
+        )
+            .into_response();
+    }
+
+    match db.duration_histogram(params.suite.as_deref(), params.run_id, params.bucket_ms) {
+        Ok(buckets) => {
+            let body = DurationHistogram {
+                bucket_ms: params.bucket_ms,
+                buckets: buckets
+                    .into_iter()
+                    .map(|(start_ms, count)| DurationBucket { start_ms, count })
+                    .collect(),
+            };
+            (StatusCode::OK, Json(body)).into_response()
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error(format!(
+                "Failed to build duration histogram: {}",
+                e
+            ))),
+        )
+            .into_response(),
+    }
+}
+
+fn default_timeline_bucket() -> String {
+    "1s".to_string()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TimelineParams {
+    /// Bucket width: a number followed by `ms`, `s`, `m` or `h`
+    #[serde(default = "default_timeline_bucket")]
+    pub bucket: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SignalTimeline {
+    pub run_id: EntityId,
+    pub bucket_ms: u64,
+    pub buckets: Vec<TimelineBucket>,
+}
+
+/// Parse a bucket width such as `500ms`, `1s`, `5m` or `1h` into milliseconds
+pub fn parse_bucket(bucket: &str) -> Option<u64> {
+    let bucket = bucket.trim();
+    let split = bucket.find(|c: char| !c.is_ascii_digit())?;
+    let (amount, unit) = bucket.split_at(split);
+    let unit_ms = match unit {
+        "ms" => 1,
+        "s" => 1_000,
+        "m" => 60_000,
+        "h" => 3_600_000,
+        _ => return None,
+    };
+    amount
+        .parse::<u64>()
+        .ok()
+        .and_then(|amount| amount.checked_mul(unit_ms))
+        .filter(|ms| *ms > 0)
+}
+
+/// GET /api/runs/:id/timeline?bucket=1s — Signal counts per time bucket and type
+pub async fn get_signal_timeline(
+    TenantDb(db): TenantDb,
+    Path(run_id): Path<EntityId>,
+    Query(params): Query<TimelineParams>,
+) -> impl IntoResponse {
+    let Some(bucket_ms) = parse_bucket(&params.bucket) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(format!(
+                "Invalid bucket '{}': expected a positive width such as 500ms, 1s, 5m or 1h",
+                params.bucket
+            ))),
+        )
+            .into_response();
+    };
+
+    match db.signal_timeline(run_id, bucket_ms) {
+        Ok(buckets) => (
+            StatusCode::OK,
+            Json(SignalTimeline {
+                run_id,
+                bucket_ms,
+                buckets,
+            }),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error(format!(
+                "Failed to build signal timeline: {}",
+                e
+            ))),
+        )
+            .into_response(),
+    }
+}
+
+fn default_drift_days() -> u32 {
+    30
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DriftParams {
+    /// How far back the series goes
+    #[serde(default = "default_drift_days")]
+    pub days: u32,
+    /// Half-width of the baseline band, in stddevs; defaults to the
+    /// configured threshold of the suite
+    pub sigma: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DriftSeries {
+    pub name: String,
+    pub suite: String,
+    pub days: u32,
+    pub points: Vec<DriftDataPoint>,
+    /// Absent until the test has a baseline
+    pub band: Option<BaselineBand>,
+}
+
+/// GET /api/tests/:suite/:name/drift?days=30&sigma=2 — Durations of a test
+/// against its baseline band
+pub async fn get_drift_series(
+    State(state): State<AppState>,
+    TenantDb(db): TenantDb,
+    Path((suite, name)): Path<(String, String)>,
+    Query(params): Query<DriftParams>,
+) -> impl IntoResponse {
+    if params.days == 0 {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error("days must be greater than zero")),
+        )
+            .into_response();
+    }
+    let detector = match params.sigma {
+        Some(sigma) if !(sigma.is_finite() && sigma > 0.0) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::error("sigma must be a positive number")),
+            )
+                .into_response();
+        }
+        Some(sigma) => DriftDetector::new(sigma),
+        None => state.drift_thresholds.detector_for(&suite),
+    };
+
+    let since = Utc::now() - chrono::Duration::days(params.days.into());
+    let series = db
+        .get_drift_data(&name, &suite, since, &detector)
+        .and_then(|points| Ok((points, db.get_baseline(&name, &suite)?)));
+    match series {
+        Ok((points, baseline)) => (
+            StatusCode::OK,
+            Json(DriftSeries {
+                band: baseline.map(|b| detector.band(&b)),
+                name,
+                suite,
+                days: params.days,
+                points,
+            }),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error(format!(
+                "Failed to build drift series: {}",
+                e
+            ))),
+        )
+            .into_response(),
+    }
+}
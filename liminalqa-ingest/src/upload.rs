@@ -0,0 +1,391 @@
+//! Artifact uploads: the file itself rather than a reference to it
+
+use crate::{extract::TenantDb, ApiResponse, AppState};
+use anyhow::{Context, Result};
+use axum::{
+    extract::{
+        multipart::{MultipartError, MultipartRejection},
+        Multipart, State,
+    },
+    http::{header, HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use liminalqa_core::{
+    entities::{Artifact, ArtifactType},
+    temporal::BiTemporalTime,
+    types::{ArtifactLocation, ArtifactRef, EntityId},
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{collections::HashMap, io::Write, path::PathBuf};
+use tempfile::NamedTempFile;
+use tracing::{error, info};
+
+/// Upload body size accepted by default, see
+/// [`ArtifactStore::with_max_upload_bytes`]
+pub const DEFAULT_MAX_UPLOAD_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Content-addressed blob store on the local file system.
+///
+/// A blob is stored at `{dir}/{sha[..2]}/{sha}`, without an extension. It is
+/// written to a temporary file in `dir` first and renamed into place, so a
+/// blob path never holds partial content; uploading the same bytes again
+/// replaces the blob with an identical one.
+#[derive(Debug, Clone)]
+pub struct ArtifactStore {
+    pub dir: PathBuf,
+    /// Largest upload body accepted, in bytes
+    pub max_upload_bytes: u64,
+}
+
+impl ArtifactStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            max_upload_bytes: DEFAULT_MAX_UPLOAD_BYTES,
+        }
+    }
+
+    /// Refuse upload bodies over `max_upload_bytes`
+    pub fn with_max_upload_bytes(mut self, max_upload_bytes: u64) -> Self {
+        self.max_upload_bytes = max_upload_bytes;
+        self
+    }
+
+    /// Path of the blob with hash `sha256`
+    pub fn path(&self, sha256: &str) -> PathBuf {
+        self.dir.join(&sha256[..2.min(sha256.len())]).join(sha256)
+    }
+
+    /// Whether `artifact_ref` points at a blob of this store, at the path its
+    /// sha256 addresses
+    pub fn holds(&self, artifact_ref: &ArtifactRef) -> bool {
+        matches!(&artifact_ref.location, ArtifactLocation::Local(path)
+            if *path == self.path(&artifact_ref.sha256))
+    }
+
+    /// Start writing a blob
+    pub fn writer(&self) -> Result<BlobWriter> {
+        std::fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create {}", self.dir.display()))?;
+        let file = tempfile::Builder::new()
+            .prefix(".upload-")
+            .tempfile_in(&self.dir)
+            .with_context(|| format!("Failed to create a file in {}", self.dir.display()))?;
+        Ok(BlobWriter {
+            store: self.clone(),
+            file,
+            hasher: Sha256::new(),
+            size_bytes: 0,
+        })
+    }
+
+    /// Store `bytes` under their sha256, returning the hash and the path
+    pub fn put(&self, bytes: &[u8]) -> Result<(String, PathBuf)> {
+        let mut writer = self.writer()?;
+        writer.write(bytes)?;
+        writer.finish()
+    }
+}
+
+/// A blob being written to a temporary file of an [`ArtifactStore`]
+///
+/// Bytes are hashed as they are written, and [`BlobWriter::finish`] renames
+/// the file to the path of the hash. A writer dropped before that removes
+/// its file.
+pub struct BlobWriter {
+    store: ArtifactStore,
+    file: NamedTempFile,
+    hasher: Sha256,
+    size_bytes: u64,
+}
+
+impl BlobWriter {
+    pub fn write(&mut self, bytes: &[u8]) -> Result<()> {
+        self.file
+            .write_all(bytes)
+            .with_context(|| format!("Failed to write {}", self.file.path().display()))?;
+        self.hasher.update(bytes);
+        self.size_bytes += bytes.len() as u64;
+        Ok(())
+    }
+
+    /// Hash of the bytes written so far
+    pub fn sha256(&self) -> String {
+        format!("{:x}", self.hasher.clone().finalize())
+    }
+
+    pub fn size_bytes(&self) -> u64 {
+        self.size_bytes
+    }
+
+    /// Move the blob to the path its hash addresses, returning the hash and
+    /// the path
+    pub fn finish(self) -> Result<(String, PathBuf)> {
+        let sha256 = self.sha256();
+        let path = self.store.path(&sha256);
+        let shard = path.parent().unwrap_or(&self.store.dir);
+        std::fs::create_dir_all(shard)
+            .with_context(|| format!("Failed to create {}", shard.display()))?;
+        self.file
+            .as_file()
+            .sync_all()
+            .with_context(|| format!("Failed to write {}", self.file.path().display()))?;
+        self.file
+            .persist(&path)
+            .map_err(|e| e.error)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok((sha256, path))
+    }
+}
+
+/// An upload body larger than the limit, in bytes
+#[derive(Debug, thiserror::Error)]
+#[error("Upload exceeds {0} bytes")]
+pub struct UploadTooLarge(pub u64);
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArtifactUploadResponse {
+    pub ok: bool,
+    /// Send it as a signal's `payload_artifact_id` to reference the file
+    /// from the signal
+    pub artifact_id: EntityId,
+    pub sha256: String,
+    pub size_bytes: u64,
+}
+
+type Rejection = (StatusCode, Json<ApiResponse>);
+
+fn reject(status: StatusCode, message: impl Into<String>) -> Rejection {
+    (status, Json(ApiResponse::error(message)))
+}
+
+/// POST /ingest/artifacts/upload — Upload an artifact file.
+///
+/// A `multipart/form-data` body with a `file` part and the text fields
+/// `run_id`, `test_id` or `test_name`, `kind` and optionally `sha256` and
+/// `mime_type`. The sha256 is computed here; a declared one must match.
+///
+/// The body is streamed, the file straight to the store. The route's body
+/// limit is the store's `max_upload_bytes` rather than axum's default;
+/// bodies over it are refused with 413.
+pub async fn upload_artifact(
+    State(state): State<AppState>,
+    TenantDb(db): TenantDb,
+    headers: HeaderMap,
+    multipart: Result<Multipart, MultipartRejection>,
+) -> impl IntoResponse {
+    let Some(store) = &state.artifact_store else {
+        return reject(
+            StatusCode::NOT_IMPLEMENTED,
+            "Artifact uploads are not enabled on this server",
+        )
+        .into_response();
+    };
+    let declared_len = headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    if declared_len.is_some_and(|len| len > store.max_upload_bytes) {
+        return reject(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            UploadTooLarge(store.max_upload_bytes).to_string(),
+        )
+        .into_response();
+    }
+    let multipart = match multipart {
+        Ok(multipart) => multipart,
+        Err(e) => {
+            return reject(
+                e.status(),
+                format!("Invalid multipart body: {}", e.body_text()),
+            )
+            .into_response()
+        }
+    };
+
+    let result = match read_upload(store, multipart).await {
+        Ok(upload) => store_upload(&db, upload),
+        Err(rejection) => Err(rejection),
+    };
+    match result {
+        Ok(response) => (StatusCode::OK, Json(response)).into_response(),
+        Err(rejection) => rejection.into_response(),
+    }
+}
+
+fn invalid_body(e: MultipartError) -> Rejection {
+    reject(
+        e.status(),
+        format!("Invalid multipart body: {}", e.body_text()),
+    )
+}
+
+fn blob_failed(e: anyhow::Error) -> Rejection {
+    error!("Failed to store artifact blob: {:#}", e);
+    reject(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        format!("Failed to store artifact: {:#}", e),
+    )
+}
+
+/// The uploaded file, written to the store but not yet in place, and the
+/// text fields sent with it
+struct Upload {
+    file: Option<(UploadedFile, BlobWriter)>,
+    fields: HashMap<String, String>,
+}
+
+/// What the `file` part says about the file it holds
+struct UploadedFile {
+    filename: Option<String>,
+    content_type: Option<String>,
+}
+
+async fn read_upload(store: &ArtifactStore, mut multipart: Multipart) -> Result<Upload, Rejection> {
+    let mut upload = Upload {
+        file: None,
+        fields: HashMap::new(),
+    };
+    while let Some(mut field) = multipart.next_field().await.map_err(invalid_body)? {
+        let Some(name) = field.name().map(str::to_string) else {
+            continue;
+        };
+        if name == "file" && upload.file.is_none() {
+            let file = UploadedFile {
+                filename: field.file_name().map(str::to_string),
+                content_type: field.content_type().map(str::to_string),
+            };
+            let mut writer = store.writer().map_err(blob_failed)?;
+            while let Some(chunk) = field.chunk().await.map_err(invalid_body)? {
+                writer.write(&chunk).map_err(blob_failed)?;
+            }
+            upload.file = Some((file, writer));
+        } else if field.file_name().is_none() {
+            let value = field.text().await.map_err(invalid_body)?;
+            upload
+                .fields
+                .entry(name)
+                .or_insert_with(|| value.trim().to_string());
+        }
+    }
+    Ok(upload)
+}
+
+fn store_upload(
+    db: &liminalqa_db::LiminalDB,
+    upload: Upload,
+) -> Result<ArtifactUploadResponse, Rejection> {
+    let field = |name: &str| upload.fields.get(name).filter(|v| !v.is_empty()).cloned();
+    let Some((file, writer)) = upload.file else {
+        return Err(reject(StatusCode::BAD_REQUEST, "Missing 'file' part"));
+    };
+    let run_id = field("run_id")
+        .ok_or_else(|| reject(StatusCode::BAD_REQUEST, "Missing 'run_id' field"))
+        .and_then(|id| {
+            EntityId::from_string(&id)
+                .map_err(|_| reject(StatusCode::BAD_REQUEST, "Invalid 'run_id' field"))
+        })?;
+
+    let test_id = match (field("test_id"), field("test_name")) {
+        (Some(id), _) => EntityId::from_string(&id)
+            .map_err(|_| reject(StatusCode::BAD_REQUEST, "Invalid 'test_id' field"))?,
+        (None, Some(name)) => match db.find_test_by_name(run_id, &name) {
+            Ok(Some(id)) => id,
+            Ok(None) => {
+                return Err(reject(
+                    StatusCode::NOT_FOUND,
+                    format!("Test '{}' not found in run {}", name, run_id),
+                ));
+            }
+            Err(e) => {
+                error!("Database error during test lookup: {}", e);
+                return Err(reject(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Database error during test lookup: {}", e),
+                ));
+            }
+        },
+        (None, None) => {
+            return Err(reject(
+                StatusCode::BAD_REQUEST,
+                "Either test_id or test_name must be provided",
+            ));
+        }
+    };
+
+    let sha256 = writer.sha256();
+    if let Some(declared) = field("sha256") {
+        if !declared.eq_ignore_ascii_case(&sha256) {
+            return Err(reject(
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "Declared sha256 {} does not match the uploaded file ({})",
+                    declared, sha256
+                ),
+            ));
+        }
+    }
+
+    let size_bytes = writer.size_bytes();
+    let (_, path) = writer.finish().map_err(blob_failed)?;
+    let artifact = Artifact {
+        id: EntityId::new(),
+        run_id,
+        test_id,
+        artifact_ref: ArtifactRef {
+            sha256: sha256.clone(),
+            location: ArtifactLocation::Local(path),
+            size_bytes,
+            mime_type: field("mime_type").or_else(|| file.content_type.clone()),
+        },
+        artifact_type: ArtifactType::from_label(&field("kind").unwrap_or_default()),
+        description: file.filename,
+        created_at: BiTemporalTime::now(),
+    };
+    if let Err(e) = db.put_artifact(&artifact) {
+        error!("Failed to ingest artifact: {}", e);
+        return Err(reject(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to ingest artifact: {}", e),
+        ));
+    }
+    if let Err(e) = db.flush() {
+        error!("Failed to flush db: {}", e);
+    }
+    info!(
+        "Uploaded artifact {} ({} bytes) for test {}",
+        sha256, artifact.artifact_ref.size_bytes, test_id
+    );
+
+    Ok(ArtifactUploadResponse {
+        ok: true,
+        artifact_id: artifact.id,
+        sha256,
+        size_bytes: artifact.artifact_ref.size_bytes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unfinished_blob_leaves_no_file() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let store = ArtifactStore::new(dir.path());
+        let mut writer = store.writer()?;
+        writer.write(b"partial")?;
+        drop(writer);
+        assert_eq!(std::fs::read_dir(dir.path())?.count(), 0);
+
+        let (sha256, path) = store.put(b"whole")?;
+        assert_eq!(path, store.path(&sha256));
+        assert_eq!(std::fs::read(&path)?, b"whole");
+        // Stored again, the blob is replaced by the same bytes
+        assert_eq!(store.put(b"whole")?.1, path);
+        assert_eq!(std::fs::read_dir(dir.path())?.count(), 1);
+        Ok(())
+    }
+}
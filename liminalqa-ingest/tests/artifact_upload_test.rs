@@ -0,0 +1,185 @@
+#![cfg(test)]
+#![allow(clippy::disallowed_methods)]
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use liminalqa_core::{
+    entities::{Artifact, ArtifactType, Test},
+    temporal::BiTemporalTime,
+    types::{ArtifactLocation, EntityId, TestStatus},
+};
+use liminalqa_db::LiminalDB;
+use liminalqa_ingest::{
+    upload::{ArtifactStore, ArtifactUploadResponse},
+    AppState,
+};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use tower::util::ServiceExt; // for `oneshot`
+
+const BOUNDARY: &str = "liminal-boundary";
+
+/// A multipart body with text `fields` and a `file` part holding `bytes`
+fn multipart(fields: &[(&str, &str)], bytes: &[u8]) -> Vec<u8> {
+    let mut body = Vec::new();
+    for (name, value) in fields {
+        body.extend_from_slice(
+            format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+                BOUNDARY, name, value
+            )
+            .as_bytes(),
+        );
+    }
+    body.extend_from_slice(
+        format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"login; retry.png\"\r\n\
+             Content-Type: image/png\r\n\r\n",
+            BOUNDARY
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(bytes);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", BOUNDARY).as_bytes());
+    body
+}
+
+async fn upload(app: &Router, body: Vec<u8>) -> (StatusCode, serde_json::Value) {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/ingest/artifacts/upload")
+                .header(
+                    "Content-Type",
+                    format!("multipart/form-data; boundary={}", BOUNDARY),
+                )
+                .body(Body::from(body))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap())
+}
+
+#[tokio::test]
+async fn test_upload_stores_blob_and_artifact_record() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let blob_dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(LiminalDB::open(db_dir.path()).unwrap());
+    let run_id = EntityId::new();
+    let test = Test {
+        id: EntityId::new(),
+        run_id,
+        name: "test_login".to_string(),
+        suite: "auth".to_string(),
+        guidance: String::new(),
+        status: TestStatus::Fail,
+        duration_ms: 420,
+        error: None,
+        started_at: chrono::Utc::now(),
+        completed_at: chrono::Utc::now(),
+        created_at: BiTemporalTime::now(),
+    };
+    db.put_test(&test).unwrap();
+
+    let metrics = Arc::new(liminalqa_core::metrics::MetricsRegistry::new());
+    let app = liminalqa_ingest::app(
+        AppState::new(db.clone(), None, metrics)
+            .with_artifact_store(ArtifactStore::new(blob_dir.path())),
+    );
+
+    let bytes = b"\x89PNG\r\n\x1a\nnot really a png";
+    let sha256 = format!("{:x}", Sha256::digest(bytes));
+    let run = run_id.to_string();
+    let fields = [
+        ("run_id", run.as_str()),
+        ("test_name", "test_login"),
+        ("kind", "screenshot"),
+        ("sha256", sha256.as_str()),
+    ];
+    let (status, json) = upload(&app, multipart(&fields, bytes)).await;
+    assert_eq!(status, StatusCode::OK, "{}", json);
+    let response: ArtifactUploadResponse = serde_json::from_value(json).unwrap();
+    assert_eq!(response.sha256, sha256);
+    assert_eq!(response.size_bytes, bytes.len() as u64);
+
+    let artifact: Artifact = db.get_entity(response.artifact_id).unwrap().unwrap();
+    assert_eq!(artifact.test_id, test.id);
+    assert_eq!(artifact.artifact_type, ArtifactType::Screenshot);
+    assert_eq!(artifact.artifact_ref.sha256, sha256);
+    assert_eq!(
+        artifact.artifact_ref.mime_type.as_deref(),
+        Some("image/png")
+    );
+    let ArtifactLocation::Local(path) = &artifact.artifact_ref.location else {
+        panic!(
+            "expected a local blob, got {}",
+            artifact.artifact_ref.location
+        );
+    };
+    assert_eq!(
+        *path,
+        blob_dir.path().join(&sha256[..2]).join(&sha256),
+        "content-addressed"
+    );
+    assert_eq!(std::fs::read(path).unwrap(), bytes);
+    // A quoted filename keeps its `;`
+    assert_eq!(artifact.description.as_deref(), Some("login; retry.png"));
+
+    // A declared hash that does not match the bytes is refused
+    let wrong = format!("{:x}", Sha256::digest(b"other bytes"));
+    let fields = [
+        ("run_id", run.as_str()),
+        ("test_name", "test_login"),
+        ("kind", "screenshot"),
+        ("sha256", wrong.as_str()),
+    ];
+    let (status, json) = upload(&app, multipart(&fields, bytes)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(json["message"].as_str().unwrap().contains("does not match"));
+}
+
+#[tokio::test]
+async fn test_upload_size_is_limited_by_the_store() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let blob_dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(LiminalDB::open(db_dir.path()).unwrap());
+    let metrics = Arc::new(liminalqa_core::metrics::MetricsRegistry::new());
+    let run = EntityId::new().to_string();
+    let test_id = EntityId::new().to_string();
+    let fields = [
+        ("run_id", run.as_str()),
+        ("test_id", test_id.as_str()),
+        ("kind", "video"),
+    ];
+
+    // Larger than axum's default body limit of 2 MB
+    let video = vec![7u8; 3 * 1024 * 1024];
+    let app = liminalqa_ingest::app(
+        AppState::new(db.clone(), None, metrics.clone())
+            .with_artifact_store(ArtifactStore::new(blob_dir.path())),
+    );
+    let (status, json) = upload(&app, multipart(&fields, &video)).await;
+    assert_eq!(status, StatusCode::OK, "{}", json);
+    assert_eq!(json["size_bytes"], video.len() as u64);
+
+    let small = tempfile::tempdir().unwrap();
+    let app = liminalqa_ingest::app(
+        AppState::new(db, None, metrics)
+            .with_artifact_store(ArtifactStore::new(small.path()).with_max_upload_bytes(1024)),
+    );
+    let (status, _) = upload(&app, multipart(&fields, &video)).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    // Nothing is left behind of the refused upload
+    let left = std::fs::read_dir(small.path()).map_or(0, |entries| entries.count());
+    assert_eq!(left, 0);
+}
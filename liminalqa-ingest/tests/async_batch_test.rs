@@ -0,0 +1,221 @@
+#![cfg(test)]
+#![allow(clippy::disallowed_methods)]
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use liminalqa_core::{entities::Test, types::EntityId};
+use liminalqa_db::LiminalDB;
+use liminalqa_ingest::{
+    jobs::{BatchJob, BatchJobs, JobAccepted, JobStatus},
+    AppState,
+};
+use std::sync::Arc;
+use tower::util::ServiceExt; // for `oneshot`
+
+fn batch_body(run_id: EntityId, tests: usize) -> serde_json::Value {
+    serde_json::json!({
+        "run": {
+            "run_id": run_id,
+            "build_id": EntityId::new(),
+            "plan_name": "nightly",
+            "env": {},
+            "started_at": chrono::Utc::now(),
+            "runner_version": "1.0.0",
+        },
+        "tests": (0..tests)
+            .map(|i| serde_json::json!({
+                "name": format!("test_{i}"),
+                "suite": "bulk",
+                "status": "pass",
+                "duration_ms": 10,
+            }))
+            .collect::<Vec<_>>(),
+        "signals": [],
+        "artifacts": [],
+    })
+}
+
+async fn body_json<T: serde::de::DeserializeOwned>(response: axum::response::Response) -> T {
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    serde_json::from_slice(&bytes).unwrap()
+}
+
+#[tokio::test]
+async fn test_async_batch_completes_in_background() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(LiminalDB::open(db_dir.path()).unwrap());
+    let metrics = Arc::new(liminalqa_core::metrics::MetricsRegistry::new());
+    let app = liminalqa_ingest::app(AppState::new(db.clone(), None, metrics));
+    let run_id = EntityId::new();
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/ingest/batch?async=true")
+                .header("Content-Type", "application/json")
+                .body(Body::from(batch_body(run_id, 50).to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+    let accepted: JobAccepted = body_json(response).await;
+    assert!(accepted.ok);
+
+    let mut job: Option<BatchJob> = None;
+    for _ in 0..100 {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(&accepted.status_url)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let polled: BatchJob = body_json(response).await;
+        if matches!(polled.status, JobStatus::Completed | JobStatus::Failed) {
+            job = Some(polled);
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+
+    let job = job.expect("job did not finish");
+    assert_eq!(job.id, accepted.job_id);
+    assert_eq!(job.status, JobStatus::Completed, "{:?}", job.error);
+    assert!(job.finished_at.is_some());
+    assert_eq!(job.counts.run, 1);
+    assert_eq!(job.counts.tests, 50);
+    for name in ["test_0", "test_49"] {
+        let id = db.find_test_by_name(run_id, name).unwrap().unwrap();
+        let test: Test = db.get_entity(id).unwrap().unwrap();
+        assert_eq!(test.suite, "bulk");
+    }
+
+    // Unknown jobs are not found
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri(format!("/ingest/jobs/{}", EntityId::new()))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+async fn submit(app: &axum::Router, body: &serde_json::Value) -> axum::response::Response {
+    app.clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/ingest/batch?async=true")
+                .header("Content-Type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+}
+
+async fn poll(app: &axum::Router, job_id: EntityId) -> (StatusCode, Option<BatchJob>) {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("/ingest/jobs/{}", job_id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = response.status();
+    if status != StatusCode::OK {
+        return (status, None);
+    }
+    (status, Some(body_json(response).await))
+}
+
+async fn wait_finished(app: &axum::Router, job_id: EntityId) {
+    for _ in 0..100 {
+        if let (_, Some(job)) = poll(app, job_id).await {
+            if matches!(job.status, JobStatus::Completed | JobStatus::Failed) {
+                return;
+            }
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    panic!("job {} did not finish", job_id);
+}
+
+#[tokio::test]
+async fn test_full_batch_queue_refuses_with_503() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(LiminalDB::open(db_dir.path()).unwrap());
+    let metrics = Arc::new(liminalqa_core::metrics::MetricsRegistry::new());
+    let state = AppState::new(db.clone(), None, metrics).with_batch_jobs(BatchJobs::new(1));
+    let app = liminalqa_ingest::app(state.clone());
+
+    // Nothing yields to the worker in between, so the first batch is still
+    // waiting when the next arrives
+    let batch = serde_json::from_value(batch_body(EntityId::new(), 1)).unwrap();
+    let queued = state.batch_jobs.submit(
+        state.clone(),
+        db.clone(),
+        batch,
+        liminalqa_core::temporal::BiTemporalTime::now(),
+    );
+    assert!(queued.is_ok());
+
+    let response = submit(&app, &batch_body(EntityId::new(), 1)).await;
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert!(response.headers().contains_key("retry-after"));
+
+    // Once the worker caught up, batches are accepted again
+    wait_finished(&app, queued.unwrap()).await;
+    let response = submit(&app, &batch_body(EntityId::new(), 1)).await;
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+}
+
+#[tokio::test]
+async fn test_finished_jobs_beyond_retention_are_dropped() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(LiminalDB::open(db_dir.path()).unwrap());
+    let metrics = Arc::new(liminalqa_core::metrics::MetricsRegistry::new());
+    let jobs = BatchJobs::default().with_retention(1, std::time::Duration::from_secs(3600));
+    let app = liminalqa_ingest::app(AppState::new(db, None, metrics).with_batch_jobs(jobs));
+
+    let mut ids = Vec::new();
+    for _ in 0..3 {
+        let accepted: JobAccepted =
+            body_json(submit(&app, &batch_body(EntityId::new(), 1)).await).await;
+        wait_finished(&app, accepted.job_id).await;
+        ids.push(accepted.job_id);
+    }
+
+    // Each submission drops the finished jobs beyond the latest one
+    assert_eq!(poll(&app, ids[0]).await.0, StatusCode::NOT_FOUND);
+    assert_eq!(poll(&app, ids[1]).await.0, StatusCode::OK);
+    assert_eq!(poll(&app, ids[2]).await.0, StatusCode::OK);
+
+    // Past the TTL, finished jobs are dropped on the next submission
+    let db_dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(LiminalDB::open(db_dir.path()).unwrap());
+    let metrics = Arc::new(liminalqa_core::metrics::MetricsRegistry::new());
+    let jobs = BatchJobs::default().with_retention(10, std::time::Duration::ZERO);
+    let app = liminalqa_ingest::app(AppState::new(db, None, metrics).with_batch_jobs(jobs));
+    let first: JobAccepted = body_json(submit(&app, &batch_body(EntityId::new(), 1)).await).await;
+    wait_finished(&app, first.job_id).await;
+    submit(&app, &batch_body(EntityId::new(), 1)).await;
+    assert_eq!(poll(&app, first.job_id).await.0, StatusCode::NOT_FOUND);
+}
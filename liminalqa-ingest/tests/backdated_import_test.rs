@@ -0,0 +1,83 @@
+#![cfg(test)]
+#![allow(clippy::disallowed_methods)]
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use chrono::{DateTime, Duration, Utc};
+use liminalqa_core::types::EntityId;
+use liminalqa_db::{build_report_at, LiminalDB};
+use liminalqa_ingest::{auth::Scope, AppState};
+use std::sync::Arc;
+use tower::util::ServiceExt; // for `oneshot`
+
+fn import_request(token: &str, run_id: EntityId, tx_time: DateTime<Utc>) -> Request<Body> {
+    let batch = serde_json::json!({
+        "run": {
+            "run_id": run_id,
+            "build_id": EntityId::new(),
+            "plan_name": "legacy-nightly",
+            "env": {},
+            "started_at": tx_time - Duration::minutes(10),
+            "runner_version": "1.0.0",
+            "tx_time": tx_time,
+        },
+        "tests": [
+            {"name": "test_login", "suite": "auth", "status": "pass", "duration_ms": 120},
+            {"name": "test_pay", "suite": "checkout", "status": "fail", "duration_ms": 900},
+        ],
+    });
+    Request::builder()
+        .method("POST")
+        .uri("/ingest/batch")
+        .header("Content-Type", "application/json")
+        .header("Authorization", format!("Bearer {}", token))
+        .body(Body::from(batch.to_string()))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_import_run_with_backdated_tx_time() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(LiminalDB::open(db_dir.path()).unwrap());
+    let metrics = Arc::new(liminalqa_core::metrics::MetricsRegistry::new());
+    let state = AppState::new(db.clone(), None, metrics)
+        .with_scoped_token("admin-token", [Scope::Admin])
+        .with_scoped_token("ingest-token", [Scope::Ingest]);
+    let app = liminalqa_ingest::app(state);
+
+    let imported_at = Utc::now() - Duration::days(30);
+    let run_id = EntityId::new();
+
+    // Backdating needs the admin scope, and never reaches into the future
+    let response = app
+        .clone()
+        .oneshot(import_request("ingest-token", run_id, imported_at))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let future = Utc::now() + Duration::hours(1);
+    let response = app
+        .clone()
+        .oneshot(import_request("admin-token", run_id, future))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert!(db
+        .get_entity::<liminalqa_core::entities::Run>(run_id)
+        .unwrap()
+        .is_none());
+
+    let response = app
+        .oneshot(import_request("admin-token", run_id, imported_at))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // The run and its tests were known from the original time on
+    let report = build_report_at(&db, run_id, imported_at + Duration::minutes(1)).unwrap();
+    assert_eq!(report.summary.total, 2);
+    assert_eq!(report.summary.failed, 1);
+    assert!(build_report_at(&db, run_id, imported_at - Duration::minutes(1)).is_err());
+}
@@ -13,7 +13,7 @@ use liminalqa_db::LiminalDB;
 use liminalqa_ingest::{
     handlers::{
         ingest_batch, ArtifactDtoItem, BatchIngestDto, BatchIngestResponse, RunDto, SignalDtoItem,
-        TestDtoItem,
+        TestAttachmentCounts, TestDtoItem,
     },
     AppState,
 };
@@ -26,11 +26,7 @@ async fn test_batch_ingestion_full_flow() {
     let db_dir = tempfile::tempdir().unwrap();
     let db = LiminalDB::open(db_dir.path()).unwrap();
     let metrics = Arc::new(liminalqa_core::metrics::MetricsRegistry::new());
-    let state = AppState {
-        db: Arc::new(db),
-        auth_token: None,
-        metrics,
-    };
+    let state = AppState::new(Arc::new(db), None, metrics);
 
     // Setup Router
     let app = Router::new()
@@ -46,6 +42,8 @@ async fn test_batch_ingestion_full_flow() {
             env: serde_json::json!({}),
             started_at: chrono::Utc::now(),
             runner_version: Some("1.0.0".to_string()),
+            annotations: Default::default(),
+            tx_time: None,
         },
         tests: vec![
             TestDtoItem {
@@ -57,6 +55,10 @@ async fn test_batch_ingestion_full_flow() {
                 error: None,
                 started_at: None,
                 completed_at: None,
+                alignment_score: None,
+                owner: None,
+                sla_ms: None,
+                attempts: None,
             },
             TestDtoItem {
                 name: "test_b".to_string(),
@@ -67,6 +69,10 @@ async fn test_batch_ingestion_full_flow() {
                 error: None,
                 started_at: None,
                 completed_at: None,
+                alignment_score: None,
+                owner: None,
+                sla_ms: None,
+                attempts: None,
             },
         ],
         signals: vec![SignalDtoItem {
@@ -75,6 +81,8 @@ async fn test_batch_ingestion_full_flow() {
             kind: "api".to_string(),
             latency_ms: Some(50),
             at: chrono::Utc::now(),
+            correlation_id: None,
+            payload_artifact_id: None,
             value: None,
             meta: None,
         }],
@@ -83,6 +91,7 @@ async fn test_batch_ingestion_full_flow() {
             test_name: Some("test_b".to_string()),
             kind: "screenshot".to_string(),
             path: "/screenshots/fail.png".to_string(),
+            location: None,
             path_sha256: "abc123456".to_string(),
             size_bytes: Some(1024),
             mime_type: Some("image/png".to_string()),
@@ -123,17 +132,129 @@ async fn test_batch_ingestion_full_flow() {
     assert!(map.contains_key("test_b"));
 }
 
+fn test_item(name: &str) -> TestDtoItem {
+    TestDtoItem {
+        name: name.to_string(),
+        suite: "suite1".to_string(),
+        status: "pass".to_string(),
+        duration_ms: Some(100),
+        guidance: None,
+        error: None,
+        started_at: None,
+        completed_at: None,
+        alignment_score: None,
+        owner: None,
+        sla_ms: None,
+        attempts: None,
+    }
+}
+
+fn signal_for(test_name: &str) -> SignalDtoItem {
+    SignalDtoItem {
+        test_id: None,
+        test_name: Some(test_name.to_string()),
+        kind: "api".to_string(),
+        latency_ms: Some(50),
+        at: chrono::Utc::now(),
+        correlation_id: None,
+        payload_artifact_id: None,
+        value: None,
+        meta: None,
+    }
+}
+
+fn artifact_for(test_name: &str) -> ArtifactDtoItem {
+    ArtifactDtoItem {
+        test_id: None,
+        test_name: Some(test_name.to_string()),
+        kind: "screenshot".to_string(),
+        path: format!("/screenshots/{}.png", test_name),
+        location: None,
+        path_sha256: "abc123456".to_string(),
+        size_bytes: Some(1024),
+        mime_type: Some("image/png".to_string()),
+    }
+}
+
+#[tokio::test]
+async fn test_batch_ingestion_reports_per_test_counts() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let db = LiminalDB::open(db_dir.path()).unwrap();
+    let metrics = Arc::new(liminalqa_core::metrics::MetricsRegistry::new());
+    let state = AppState::new(Arc::new(db), None, metrics);
+
+    let app = Router::new()
+        .route("/ingest/batch", post(ingest_batch))
+        .with_state(state);
+
+    let batch = BatchIngestDto {
+        run: RunDto {
+            run_id: EntityId::new(),
+            build_id: EntityId::new(),
+            plan_name: "smoke".to_string(),
+            env: serde_json::json!({}),
+            started_at: chrono::Utc::now(),
+            runner_version: None,
+            annotations: Default::default(),
+            tx_time: None,
+        },
+        tests: vec![
+            test_item("test_a"),
+            test_item("test_b"),
+            test_item("test_c"),
+        ],
+        signals: vec![
+            signal_for("test_a"),
+            signal_for("test_a"),
+            signal_for("test_b"),
+        ],
+        artifacts: vec![artifact_for("test_b")],
+    };
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/ingest/batch")
+                .header("Content-Type", "application/json")
+                .body(Body::from(serde_json::to_string(&batch).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body_bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body: BatchIngestResponse = serde_json::from_slice(&body_bytes).unwrap();
+
+    let per_test = body.per_test.expect("per_test should be present");
+    assert_eq!(per_test.len(), 3);
+    assert_eq!(
+        per_test["test_a"],
+        TestAttachmentCounts {
+            signals: 2,
+            artifacts: 0
+        }
+    );
+    assert_eq!(
+        per_test["test_b"],
+        TestAttachmentCounts {
+            signals: 1,
+            artifacts: 1
+        }
+    );
+    assert_eq!(per_test["test_c"], TestAttachmentCounts::default());
+}
+
 #[tokio::test]
 async fn test_batch_ingestion_partial_failure() {
     // Setup database
     let db_dir = tempfile::tempdir().unwrap();
     let db = LiminalDB::open(db_dir.path()).unwrap();
     let metrics = Arc::new(liminalqa_core::metrics::MetricsRegistry::new());
-    let state = AppState {
-        db: Arc::new(db),
-        auth_token: None,
-        metrics,
-    };
+    let state = AppState::new(Arc::new(db), None, metrics);
 
     let app = Router::new()
         .route("/ingest/batch", post(ingest_batch))
@@ -148,6 +269,8 @@ async fn test_batch_ingestion_partial_failure() {
             env: serde_json::json!({}),
             started_at: chrono::Utc::now(),
             runner_version: Some("1.0.0".to_string()),
+            annotations: Default::default(),
+            tx_time: None,
         },
         tests: vec![],
         signals: vec![SignalDtoItem {
@@ -156,6 +279,8 @@ async fn test_batch_ingestion_partial_failure() {
             kind: "api".to_string(),
             latency_ms: Some(50),
             at: chrono::Utc::now(),
+            correlation_id: None,
+            payload_artifact_id: None,
             value: None,
             meta: None,
         }],
@@ -189,3 +314,201 @@ async fn test_batch_ingestion_partial_failure() {
     assert_eq!(partial_counts.run, 1);
     assert_eq!(partial_counts.signals, 0);
 }
+
+#[tokio::test]
+async fn test_batch_ingestion_reports_field_path_of_malformed_json() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let db = LiminalDB::open(db_dir.path()).unwrap();
+    let metrics = Arc::new(liminalqa_core::metrics::MetricsRegistry::new());
+    let state = AppState::new(Arc::new(db), None, metrics);
+
+    let app = Router::new()
+        .route("/ingest/batch", post(ingest_batch))
+        .with_state(state);
+
+    // duration_ms must be an integer
+    let batch = serde_json::json!({
+        "run": {
+            "run_id": EntityId::new(),
+            "build_id": EntityId::new(),
+            "plan_name": "smoke",
+            "env": {},
+            "started_at": chrono::Utc::now(),
+            "runner_version": "1.0.0"
+        },
+        "tests": [
+            { "name": "test_a", "suite": "suite1", "status": "pass", "duration_ms": 100 },
+            { "name": "test_b", "suite": "suite1", "status": "pass", "duration_ms": "slow" }
+        ],
+        "signals": [],
+        "artifacts": []
+    });
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/ingest/batch")
+                .header("Content-Type", "application/json")
+                .body(Body::from(batch.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let body_bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body: liminalqa_ingest::ApiResponse = serde_json::from_slice(&body_bytes).unwrap();
+
+    assert!(!body.ok);
+    assert!(
+        body.message.contains("tests[1].duration_ms"),
+        "unexpected message: {}",
+        body.message
+    );
+}
+
+#[tokio::test]
+async fn test_batch_duration_observed_by_outcome() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let db = LiminalDB::open(db_dir.path()).unwrap();
+    let metrics = Arc::new(liminalqa_core::metrics::MetricsRegistry::new());
+    let app = liminalqa_ingest::app(AppState::new(Arc::new(db), None, metrics.clone()));
+
+    let batch = |test_name: &str| BatchIngestDto {
+        run: RunDto {
+            run_id: EntityId::new(),
+            build_id: EntityId::new(),
+            plan_name: "smoke".to_string(),
+            env: serde_json::json!({}),
+            started_at: chrono::Utc::now(),
+            runner_version: Some("1.0.0".to_string()),
+            annotations: Default::default(),
+            tx_time: None,
+        },
+        tests: vec![TestDtoItem {
+            name: "test_a".to_string(),
+            suite: "suite1".to_string(),
+            status: "pass".to_string(),
+            duration_ms: Some(100),
+            guidance: None,
+            error: None,
+            started_at: None,
+            completed_at: None,
+            alignment_score: None,
+            owner: None,
+            sla_ms: None,
+            attempts: None,
+        }],
+        signals: vec![SignalDtoItem {
+            test_id: None,
+            test_name: Some(test_name.to_string()),
+            kind: "api".to_string(),
+            latency_ms: Some(50),
+            at: chrono::Utc::now(),
+            correlation_id: None,
+            payload_artifact_id: None,
+            value: None,
+            meta: None,
+        }],
+        artifacts: vec![],
+    };
+    let post = |body: String| {
+        app.clone().oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/ingest/batch")
+                .header("Content-Type", "application/json")
+                .body(Body::from(body))
+                .unwrap(),
+        )
+    };
+
+    let ok = post(serde_json::to_string(&batch("test_a")).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(ok.status(), StatusCode::OK);
+    let partial = post(serde_json::to_string(&batch("test_missing")).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(partial.status(), StatusCode::NOT_FOUND);
+    let malformed = post("{".to_string()).await.unwrap();
+    assert!(malformed.status().is_client_error());
+
+    let export = metrics.export();
+    let sample = |name: &str, outcome: &str| -> f64 {
+        let prefix = format!(
+            "liminalqa_ingest_batch_duration_seconds_{}{{outcome=\"{}\"}} ",
+            name, outcome
+        );
+        export
+            .lines()
+            .find_map(|l| l.strip_prefix(&prefix))
+            .unwrap_or_else(|| panic!("missing sample {}", prefix))
+            .parse()
+            .unwrap()
+    };
+    assert!(sample("sum", "ok") > 0.0);
+    assert_eq!(sample("count", "ok"), 1.0);
+    assert_eq!(sample("count", "partial"), 1.0);
+    assert_eq!(sample("count", "error"), 1.0);
+}
+
+#[tokio::test]
+async fn test_duration_beyond_i32_is_preserved() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(LiminalDB::open(db_dir.path()).unwrap());
+    let metrics = Arc::new(liminalqa_core::metrics::MetricsRegistry::new());
+    let app = liminalqa_ingest::app(AppState::new(db.clone(), None, metrics));
+
+    // A little over 34 days
+    let duration_ms = i32::MAX as i64 * 3 / 2;
+    let run_id = EntityId::new();
+    let mut long = test_item("test_soak");
+    long.duration_ms = Some(duration_ms);
+    let mut negative = test_item("test_negative");
+    negative.duration_ms = Some(-1);
+    let batch = |tests: Vec<TestDtoItem>| BatchIngestDto {
+        run: RunDto {
+            run_id,
+            build_id: EntityId::new(),
+            plan_name: "soak".to_string(),
+            env: serde_json::json!({}),
+            started_at: chrono::Utc::now(),
+            runner_version: None,
+            annotations: Default::default(),
+            tx_time: None,
+        },
+        tests,
+        signals: vec![],
+        artifacts: vec![],
+    };
+    let post = |batch: BatchIngestDto| {
+        app.clone().oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/ingest/batch")
+                .header("Content-Type", "application/json")
+                .body(Body::from(serde_json::to_string(&batch).unwrap()))
+                .unwrap(),
+        )
+    };
+
+    let response = post(batch(vec![negative])).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = post(batch(vec![long])).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let test_id = db
+        .find_test_by_name(run_id, "test_soak")
+        .unwrap()
+        .expect("test stored");
+    let test: liminalqa_core::entities::Test = db.get_entity(test_id).unwrap().unwrap();
+    assert_eq!(test.duration_ms, duration_ms as u64);
+
+    let report = liminalqa_db::build_report(&db, run_id).unwrap();
+    assert_eq!(report.top_slow_tests[0].duration_ms, duration_ms);
+}
@@ -0,0 +1,106 @@
+#![cfg(test)]
+#![allow(clippy::disallowed_methods)]
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use liminalqa_core::{
+    report::BuildRun,
+    types::{EntityId, RunStatus},
+};
+use liminalqa_db::LiminalDB;
+use liminalqa_ingest::AppState;
+use std::sync::Arc;
+use tower::util::ServiceExt; // for `oneshot`
+
+/// Ingest a run of `build_id` with one passing and one failing test
+async fn ingest_run(app: &Router, build_id: EntityId, plan_name: &str) -> EntityId {
+    let run_id = EntityId::new();
+    let batch = serde_json::json!({
+        "run": {
+            "run_id": run_id,
+            "build_id": build_id,
+            "plan_name": plan_name,
+            "env": {},
+            "started_at": chrono::Utc::now(),
+            "runner_version": "1.0.0",
+        },
+        "tests": [
+            {"name": "test_login", "suite": "auth", "status": "pass", "duration_ms": 120},
+            {"name": "test_checkout", "suite": "payments", "status": "fail", "duration_ms": 900},
+        ],
+    });
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/ingest/batch")
+                .header("Content-Type", "application/json")
+                .body(Body::from(batch.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    run_id
+}
+
+async fn build_runs(app: &Router, build_id: EntityId) -> Vec<BuildRun> {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/builds/{}/runs", build_id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    serde_json::from_slice(&bytes).unwrap()
+}
+
+#[tokio::test]
+async fn test_build_runs_with_summaries() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(LiminalDB::open(db_dir.path()).unwrap());
+    let metrics = Arc::new(liminalqa_core::metrics::MetricsRegistry::new());
+    let app = liminalqa_ingest::app(AppState::new(db, None, metrics));
+
+    let build_id = EntityId::new();
+    let smoke = ingest_run(&app, build_id, "smoke").await;
+    let nightly = ingest_run(&app, build_id, "nightly").await;
+    ingest_run(&app, EntityId::new(), "smoke").await;
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/runs/{}/cancel", nightly))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let runs = build_runs(&app, build_id).await;
+    let ids: Vec<String> = runs.iter().map(|r| r.run_id.clone()).collect();
+    assert_eq!(ids, vec![smoke.to_string(), nightly.to_string()]);
+    assert_eq!(runs[0].status, RunStatus::Running);
+    assert_eq!(runs[1].status, RunStatus::Cancelled);
+    for run in &runs {
+        assert_eq!(
+            (run.summary.total, run.summary.passed, run.summary.failed),
+            (2, 1, 1)
+        );
+    }
+
+    assert!(build_runs(&app, EntityId::new()).await.is_empty());
+}
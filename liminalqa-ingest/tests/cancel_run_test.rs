@@ -0,0 +1,54 @@
+#![cfg(test)]
+#![allow(clippy::disallowed_methods)]
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use liminalqa_core::{
+    entities::Run,
+    temporal::BiTemporalTime,
+    types::{EntityId, RunStatus},
+};
+use liminalqa_db::LiminalDB;
+use liminalqa_ingest::AppState;
+use std::sync::Arc;
+use tower::util::ServiceExt; // for `oneshot`
+
+fn cancel_request(run_id: EntityId) -> Request<Body> {
+    Request::builder()
+        .method("POST")
+        .uri(format!("/runs/{}/cancel", run_id))
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_cancel_running_run() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(LiminalDB::open(db_dir.path()).unwrap());
+    let run = Run {
+        id: EntityId::new(),
+        build_id: EntityId::new(),
+        plan_name: "nightly".to_string(),
+        env: Default::default(),
+        started_at: chrono::Utc::now(),
+        ended_at: None,
+        runner_version: "test".to_string(),
+        liminal_os_version: None,
+        created_at: BiTemporalTime::now(),
+    };
+    db.put_run(&run).unwrap();
+    let metrics = Arc::new(liminalqa_core::metrics::MetricsRegistry::new());
+    let app = liminalqa_ingest::app(AppState::new(db.clone(), None, metrics));
+
+    let response = app.clone().oneshot(cancel_request(run.id)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(db.run_status(run.id).unwrap(), RunStatus::Cancelled);
+
+    // Cancelling twice conflicts, unknown runs are not found
+    let response = app.clone().oneshot(cancel_request(run.id)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let response = app.oneshot(cancel_request(EntityId::new())).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
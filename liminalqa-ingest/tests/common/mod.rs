@@ -0,0 +1,53 @@
+//! Fixtures shared by the integration tests
+
+// Each test crate uses only some of them
+#![allow(dead_code)]
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use chrono::{DateTime, Duration, Utc};
+use liminalqa_core::{
+    entities::Test,
+    temporal::BiTemporalTime,
+    types::{EntityId, TestStatus},
+};
+use tower::util::ServiceExt; // for `oneshot`
+
+/// An execution of `test_checkout` in suite `payments`, in a run of its own
+pub fn execution(status: TestStatus, duration_ms: u64, started_at: DateTime<Utc>) -> Test {
+    Test {
+        id: EntityId::new(),
+        run_id: EntityId::new(),
+        name: "test_checkout".to_string(),
+        suite: "payments".to_string(),
+        guidance: String::new(),
+        status,
+        duration_ms,
+        error: None,
+        started_at,
+        completed_at: started_at + Duration::milliseconds(duration_ms as i64),
+        created_at: BiTemporalTime::now(),
+    }
+}
+
+/// Status and body of the response of `app` to `request`
+pub async fn send(app: &Router, request: Request<Body>) -> (StatusCode, Vec<u8>) {
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, bytes.to_vec())
+}
+
+/// Status and body of `GET uri`
+pub async fn get(app: &Router, uri: &str) -> (StatusCode, Vec<u8>) {
+    send(
+        app,
+        Request::builder().uri(uri).body(Body::empty()).unwrap(),
+    )
+    .await
+}
@@ -0,0 +1,68 @@
+#![cfg(test)]
+#![allow(clippy::disallowed_methods)]
+
+use axum::{
+    body::{Body, Bytes},
+    http::{Request, StatusCode},
+    Router,
+};
+use liminalqa_db::LiminalDB;
+use liminalqa_ingest::AppState;
+use std::{sync::Arc, time::Duration};
+use tower::util::ServiceExt; // for `oneshot`
+
+/// An ingest request whose body never finishes arriving, holding its slot
+fn stalled_ingest(app: &Router) -> tokio::task::JoinHandle<StatusCode> {
+    let request = Request::builder()
+        .method("POST")
+        .uri("/ingest/tests")
+        .header("Content-Type", "application/json")
+        .body(Body::from_stream(futures::stream::pending::<
+            Result<Bytes, std::io::Error>,
+        >()))
+        .unwrap();
+    let app = app.clone();
+    tokio::spawn(async move { app.oneshot(request).await.unwrap().status() })
+}
+
+async fn get_status(app: &Router, uri: &str) -> StatusCode {
+    app.clone()
+        .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap()
+        .status()
+}
+
+#[tokio::test]
+async fn test_requests_over_limit_get_503() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(LiminalDB::open(db_dir.path()).unwrap());
+    let metrics = Arc::new(liminalqa_core::metrics::MetricsRegistry::new());
+    let app = liminalqa_ingest::app(AppState::new(db, None, metrics).with_max_in_flight(2));
+
+    let stalled = [stalled_ingest(&app), stalled_ingest(&app)];
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // The overflow is refused without waiting for its body
+    assert_eq!(
+        stalled_ingest(&app).await.unwrap(),
+        StatusCode::SERVICE_UNAVAILABLE
+    );
+    // The limit is shared by every route; health checks are exempt
+    assert_eq!(
+        get_status(&app, "/api/resonance/flaky").await,
+        StatusCode::SERVICE_UNAVAILABLE
+    );
+    assert_eq!(get_status(&app, "/health").await, StatusCode::OK);
+
+    for handle in &stalled {
+        handle.abort();
+    }
+    for handle in stalled {
+        let _ = handle.await;
+    }
+    assert_eq!(
+        get_status(&app, "/api/resonance/flaky").await,
+        StatusCode::OK
+    );
+}
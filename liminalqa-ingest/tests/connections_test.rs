@@ -0,0 +1,60 @@
+#![cfg(test)]
+#![allow(clippy::disallowed_methods)]
+
+use liminalqa_core::metrics::{ConnectionLabels, MetricsRegistry};
+use liminalqa_db::LiminalDB;
+use liminalqa_ingest::{
+    server::{self, ServerConfig},
+    AppState,
+};
+use std::{sync::Arc, time::Duration};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+#[tokio::test]
+async fn test_connection_gauge_follows_open_connections() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(LiminalDB::open(db_dir.path()).unwrap());
+    let metrics = Arc::new(MetricsRegistry::new());
+    let app = liminalqa_ingest::app(AppState::new(db, None, metrics.clone()));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(server::serve(
+        listener,
+        app,
+        metrics.clone(),
+        ServerConfig::default(),
+    ));
+
+    let labels = ConnectionLabels {
+        kind: "http".to_string(),
+    };
+    let active = || metrics.active_connections.get_or_create(&labels).get();
+    assert_eq!(active(), 0);
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream
+        .write_all(b"GET /health HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .await
+        .unwrap();
+    let mut response = vec![0; 1024];
+    let n = stream.read(&mut response).await.unwrap();
+    assert!(String::from_utf8_lossy(&response[..n]).starts_with("HTTP/1.1 200"));
+
+    // Kept alive after the response
+    assert_eq!(active(), 1);
+    assert_eq!(metrics.connections_total.get_or_create(&labels).get(), 1);
+
+    drop(stream);
+    for _ in 0..100 {
+        if active() == 0 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(active(), 0);
+    assert_eq!(metrics.connections_total.get_or_create(&labels).get(), 1);
+}
@@ -0,0 +1,135 @@
+#![cfg(test)]
+#![allow(clippy::disallowed_methods)]
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use chrono::{Duration, Utc};
+use liminalqa_core::{
+    baseline::{Baseline, DriftThresholds},
+    types::TestStatus,
+};
+use liminalqa_db::LiminalDB;
+use liminalqa_ingest::{stats::DriftSeries, AppState};
+use std::sync::Arc;
+use tower::util::ServiceExt; // for `oneshot`
+
+mod common;
+
+#[tokio::test]
+async fn test_drift_series_against_baseline_band() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(
+        LiminalDB::open(db_dir.path())
+            .unwrap()
+            .with_min_baseline_samples(3),
+    );
+    for (duration_ms, days_ago) in [(400, 40), (95, 3), (100, 2), (130, 1)] {
+        db.put_test(&common::execution(
+            TestStatus::Pass,
+            duration_ms,
+            Utc::now() - Duration::days(days_ago),
+        ))
+        .unwrap();
+    }
+    // mean 100ms, stddev 10ms
+    db.upsert_baseline(&Baseline::from_samples(
+        "test_checkout",
+        "payments",
+        &[90.0, 100.0, 110.0],
+    ))
+    .unwrap();
+
+    let metrics = Arc::new(liminalqa_core::metrics::MetricsRegistry::new());
+    let app = liminalqa_ingest::app(AppState::new(db, None, metrics));
+    let get = |uri: &str| {
+        app.clone()
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+    };
+
+    let response = get("/api/tests/payments/test_checkout/drift?days=30")
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let series: DriftSeries = serde_json::from_slice(&bytes).unwrap();
+
+    // The 40 day old execution is outside the window; oldest first
+    let durations: Vec<u64> = series.points.iter().map(|p| p.duration_ms).collect();
+    assert_eq!(durations, vec![95, 100, 130]);
+    let z_scores: Vec<f64> = series.points.iter().map(|p| p.z_score).collect();
+    assert_eq!(z_scores, vec![-0.5, 0.0, 3.0]);
+    let drifts: Vec<bool> = series.points.iter().map(|p| p.drift).collect();
+    assert_eq!(drifts, vec![false, false, true]);
+
+    let band = series.band.expect("baseline band");
+    assert_eq!(band.mean_ms, 100.0);
+    assert_eq!(band.stddev_ms, 10.0);
+    assert_eq!((band.lower_ms, band.upper_ms), (80.0, 120.0));
+    assert!(!band.provisional);
+
+    // A wider band no longer flags the slow execution
+    let response = get("/api/tests/payments/test_checkout/drift?days=30&sigma=3.5")
+        .await
+        .unwrap();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let series: DriftSeries = serde_json::from_slice(&bytes).unwrap();
+    assert!(series.points.iter().all(|p| !p.drift));
+    assert_eq!(series.band.map(|b| b.upper_ms), Some(135.0));
+
+    let response = get("/api/tests/payments/test_checkout/drift?days=0")
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_drift_series_uses_suite_threshold() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(LiminalDB::open(db_dir.path()).unwrap());
+    // 125ms is 2.5 stddevs above the baseline
+    db.put_test(&common::execution(
+        TestStatus::Pass,
+        125,
+        Utc::now() - Duration::days(1),
+    ))
+    .unwrap();
+    db.upsert_baseline(&Baseline::from_samples(
+        "test_checkout",
+        "payments",
+        &[90.0, 110.0, 90.0, 110.0, 100.0],
+    ))
+    .unwrap();
+
+    let metrics = Arc::new(liminalqa_core::metrics::MetricsRegistry::new());
+    let drift = |thresholds: DriftThresholds| {
+        let app = liminalqa_ingest::app(
+            AppState::new(db.clone(), None, metrics.clone()).with_drift_thresholds(thresholds),
+        );
+        async move {
+            let response = app
+                .oneshot(
+                    Request::builder()
+                        .uri("/api/tests/payments/test_checkout/drift")
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let series: DriftSeries = serde_json::from_slice(&bytes).unwrap();
+            series.points[0].drift
+        }
+    };
+
+    assert!(drift(DriftThresholds::new(2.0)).await);
+    assert!(!drift(DriftThresholds::new(2.0).with_suite("payments", 3.0)).await);
+    assert!(drift(DriftThresholds::new(3.0).with_suite("payments", 2.0)).await);
+}
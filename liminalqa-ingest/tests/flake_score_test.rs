@@ -0,0 +1,65 @@
+#![cfg(test)]
+#![allow(clippy::disallowed_methods)]
+
+use axum::http::StatusCode;
+use chrono::{Duration, Utc};
+use liminalqa_core::types::TestStatus;
+use liminalqa_db::LiminalDB;
+use liminalqa_ingest::{resonance::FlakeScore, AppState};
+use std::sync::Arc;
+
+mod common;
+
+#[tokio::test]
+async fn test_flake_score_is_computed_from_latest_history() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(LiminalDB::open(db_dir.path()).unwrap());
+    // Ten stable passes, then ten executions flipping between pass and fail
+    for minutes_ago in (10..20).rev() {
+        db.put_test(&common::execution(
+            TestStatus::Pass,
+            100,
+            Utc::now() - Duration::minutes(minutes_ago),
+        ))
+        .unwrap();
+    }
+    for minutes_ago in (0..10).rev() {
+        let status = if minutes_ago % 2 == 0 {
+            TestStatus::Fail
+        } else {
+            TestStatus::Pass
+        };
+        db.put_test(&common::execution(
+            status,
+            100,
+            Utc::now() - Duration::minutes(minutes_ago),
+        ))
+        .unwrap();
+    }
+
+    let metrics = Arc::new(liminalqa_core::metrics::MetricsRegistry::new());
+    let app = liminalqa_ingest::app(AppState::new(db, None, metrics));
+
+    let (status, body) = common::get(&app, "/api/tests/payments/test_checkout/flake").await;
+    assert_eq!(status, StatusCode::OK);
+    let score: FlakeScore = serde_json::from_slice(&body).unwrap();
+    assert_eq!(score.window, 10);
+    assert_eq!(score.executions, 10);
+    assert!((score.score - 0.9).abs() < 1e-9, "score {}", score.score);
+    assert!(score.flagged);
+
+    // A stricter threshold clears the flag for the same history
+    let (_, body) = common::get(
+        &app,
+        "/api/tests/payments/test_checkout/flake?threshold=0.95",
+    )
+    .await;
+    let score: FlakeScore = serde_json::from_slice(&body).unwrap();
+    assert!((score.score - 0.9).abs() < 1e-9);
+    assert!(!score.flagged);
+
+    let (status, _) = common::get(&app, "/api/tests/payments/test_checkout/flake?window=0").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = common::get(&app, "/api/tests/payments/test_unknown/flake").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
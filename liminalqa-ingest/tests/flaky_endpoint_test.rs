@@ -0,0 +1,72 @@
+#![cfg(test)]
+#![allow(clippy::disallowed_methods)]
+
+use axum::{http::StatusCode, Router};
+use liminalqa_core::{
+    entities::Resonance,
+    temporal::BiTemporalTime,
+    types::{EntityId, ResonancePattern},
+};
+use liminalqa_db::LiminalDB;
+use liminalqa_ingest::AppState;
+use std::sync::Arc;
+
+mod common;
+
+fn make_resonance(score: f64) -> Resonance {
+    Resonance {
+        id: EntityId::new(),
+        detector: "flake".to_string(),
+        pattern: ResonancePattern {
+            pattern_id: EntityId::new(),
+            description: format!("flaky at {}", score),
+            score,
+            occurrences: 1,
+            first_seen: chrono::Utc::now(),
+            last_seen: chrono::Utc::now(),
+        },
+        affected_tests: vec![EntityId::new()],
+        root_cause: None,
+        created_at: BiTemporalTime::now(),
+    }
+}
+
+async fn flaky(app: &Router, query: &str) -> (StatusCode, Vec<u8>) {
+    common::get(app, &format!("/api/resonance/flaky{}", query)).await
+}
+
+#[tokio::test]
+async fn test_flaky_endpoint_returns_top_scores_in_order() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(LiminalDB::open(temp_dir.path()).unwrap());
+
+    // 50 resonances with scrambled scores; the best one is resolved
+    let mut resolved = None;
+    for i in 0..50u32 {
+        let resonance = make_resonance(f64::from((i * 37) % 50) / 50.0);
+        db.put_resonance(&resonance).unwrap();
+        if (i * 37) % 50 == 49 {
+            resolved = Some(resonance.id);
+        }
+    }
+    db.resolve_resonance(resolved.unwrap(), "flake").unwrap();
+
+    let metrics = Arc::new(liminalqa_core::metrics::MetricsRegistry::new());
+    let app = liminalqa_ingest::app(AppState::new(db, None, metrics));
+
+    let (status, body) = flaky(&app, "?limit=5").await;
+    assert_eq!(status, StatusCode::OK);
+    let top: Vec<Resonance> = serde_json::from_slice(&body).unwrap();
+    let scores: Vec<f64> = top.iter().map(|r| r.pattern.score).collect();
+    assert_eq!(scores, vec![0.96, 0.94, 0.92, 0.9, 0.88]);
+
+    let (_, body) = flaky(&app, "").await;
+    let all: Vec<Resonance> = serde_json::from_slice(&body).unwrap();
+    assert_eq!(all.len(), 49);
+    assert!(all
+        .windows(2)
+        .all(|w| w[0].pattern.score >= w[1].pattern.score));
+
+    let (status, _) = flaky(&app, "?limit=0").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
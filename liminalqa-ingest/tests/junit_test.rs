@@ -0,0 +1,106 @@
+#![cfg(test)]
+#![allow(clippy::disallowed_methods)]
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use liminalqa_core::{
+    entities::{EntityType, Run, Test},
+    types::TestStatus,
+};
+use liminalqa_db::LiminalDB;
+use liminalqa_ingest::{junit::JunitIngestResponse, AppState};
+use std::sync::Arc;
+use tower::util::ServiceExt; // for `oneshot`
+
+const REPORT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites>
+  <testsuite name="checkout" tests="4">
+    <testcase name="test_pay" classname="checkout.PaymentTest" time="1.25"/>
+    <testcase name="test_refund" classname="checkout.PaymentTest" time="0.5">
+      <failure message="expected 200, got 500" type="AssertionError">Traceback &lt;line 12&gt;</failure>
+    </testcase>
+    <testcase name="test_cart" time="0.1">
+      <error message="connection reset"/>
+    </testcase>
+    <testcase name="test_coupon" classname="checkout.PaymentTest">
+      <skipped/>
+    </testcase>
+  </testsuite>
+</testsuites>"#;
+
+#[tokio::test]
+async fn test_junit_report_is_ingested_with_statuses() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(LiminalDB::open(db_dir.path()).unwrap());
+    let metrics = Arc::new(liminalqa_core::metrics::MetricsRegistry::new());
+    let app = liminalqa_ingest::app(AppState::new(db.clone(), None, metrics));
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/ingest/junit?plan_name=nightly")
+                .header("Content-Type", "application/xml")
+                .body(Body::from(REPORT))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body: JunitIngestResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body.tests, 4);
+
+    let run = db.get_entity::<Run>(body.run_id).unwrap().unwrap();
+    assert_eq!(run.plan_name, "nightly");
+
+    let tests: Vec<Test> = db
+        .get_entities_by_type(EntityType::Test)
+        .unwrap()
+        .into_iter()
+        .filter_map(|id| db.get_entity::<Test>(id).unwrap())
+        .collect();
+    let test = |name: &str| tests.iter().find(|t| t.name == name).unwrap();
+
+    assert_eq!(test("test_pay").status, TestStatus::Pass);
+    assert_eq!(test("test_pay").suite, "checkout.PaymentTest");
+    assert_eq!(test("test_pay").duration_ms, 1250);
+    assert_eq!(test("test_pay").run_id, body.run_id);
+
+    let refund = test("test_refund");
+    assert_eq!(refund.status, TestStatus::Fail);
+    let error = refund.error.as_ref().unwrap();
+    assert_eq!(error.kind, "AssertionError");
+    assert_eq!(error.message, "expected 200, got 500");
+    assert_eq!(error.stack_trace.as_deref(), Some("Traceback <line 12>"));
+
+    // Without a classname the enclosing suite name is used
+    assert_eq!(test("test_cart").suite, "checkout");
+    assert_eq!(test("test_cart").status, TestStatus::Fail);
+    assert_eq!(test("test_coupon").status, TestStatus::Skip);
+}
+
+#[tokio::test]
+async fn test_junit_rejects_malformed_xml() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(LiminalDB::open(db_dir.path()).unwrap());
+    let metrics = Arc::new(liminalqa_core::metrics::MetricsRegistry::new());
+    let app = liminalqa_ingest::app(AppState::new(db, None, metrics));
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/ingest/junit")
+                .header("Content-Type", "application/xml")
+                .body(Body::from("<testsuite><testcase name=\"a\"></testsuite>"))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,87 @@
+#![cfg(test)]
+#![allow(clippy::disallowed_methods)]
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    Router,
+};
+use jsonwebtoken::{encode, EncodingKey, Header};
+use liminalqa_db::LiminalDB;
+use liminalqa_ingest::{auth::JwtConfig, AppState};
+use std::sync::Arc;
+use tower::util::ServiceExt; // for `oneshot`
+
+const SECRET: &[u8] = b"test-secret";
+
+fn app(db_dir: &tempfile::TempDir, auth_token: Option<String>) -> Router {
+    let db = Arc::new(LiminalDB::open(db_dir.path()).unwrap());
+    let metrics = Arc::new(liminalqa_core::metrics::MetricsRegistry::new());
+    let jwt = JwtConfig::hs256(SECRET)
+        .with_issuer("https://idp.example.com")
+        .with_audience("liminalqa");
+    liminalqa_ingest::app(AppState::new(db, auth_token, metrics).with_jwt(jwt))
+}
+
+fn token(audience: &str, expires_in_secs: i64) -> String {
+    let claims = serde_json::json!({
+        "sub": "ci-bot",
+        "iss": "https://idp.example.com",
+        "aud": audience,
+        "exp": chrono::Utc::now().timestamp() + expires_in_secs,
+        "scope": "query",
+    });
+    encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(SECRET),
+    )
+    .unwrap()
+}
+
+async fn status_with(app: Router, bearer: Option<&str>) -> StatusCode {
+    let mut request = Request::builder().uri("/metrics");
+    if let Some(bearer) = bearer {
+        request = request.header(header::AUTHORIZATION, format!("Bearer {}", bearer));
+    }
+    app.oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap()
+        .status()
+}
+
+#[tokio::test]
+async fn test_valid_jwt_is_accepted() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let status = status_with(app(&db_dir, None), Some(&token("liminalqa", 3600))).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_expired_jwt_is_rejected() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let status = status_with(app(&db_dir, None), Some(&token("liminalqa", -3600))).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_wrong_audience_jwt_is_rejected() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let status = status_with(app(&db_dir, None), Some(&token("other-service", 3600))).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_static_token_still_accepted_alongside_jwt() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let app = app(&db_dir, Some("static-token".to_string()));
+    assert_eq!(
+        status_with(app.clone(), Some("static-token")).await,
+        StatusCode::OK
+    );
+    assert_eq!(
+        status_with(app.clone(), Some("wrong")).await,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(status_with(app, None).await, StatusCode::UNAUTHORIZED);
+}
@@ -0,0 +1,96 @@
+#![cfg(test)]
+#![allow(clippy::disallowed_methods)]
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use liminalqa_core::{
+    baseline::LatencyAnomaly,
+    facts::Attribute,
+    types::{EntityId, SignalType},
+};
+use liminalqa_db::LiminalDB;
+use liminalqa_ingest::AppState;
+use std::sync::Arc;
+use tower::util::ServiceExt; // for `oneshot`
+
+/// Ingest one run of `test_checkout` with an API and a websocket signal
+async fn ingest_run(app: &Router, api_latency_ms: u64) {
+    let batch = serde_json::json!({
+        "run": {
+            "run_id": EntityId::new(),
+            "build_id": EntityId::new(),
+            "plan_name": "nightly",
+            "env": {},
+            "started_at": chrono::Utc::now(),
+            "runner_version": "1.0.0",
+        },
+        "tests": [
+            {"name": "test_checkout", "suite": "payments", "status": "pass", "duration_ms": 900},
+        ],
+        "signals": [
+            {"test_name": "test_checkout", "kind": "api", "latency_ms": api_latency_ms,
+             "at": chrono::Utc::now()},
+            {"test_name": "test_checkout", "kind": "ws", "latency_ms": 20,
+             "at": chrono::Utc::now()},
+        ],
+    });
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/ingest/batch")
+                .header("Content-Type", "application/json")
+                .body(Body::from(batch.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+fn anomalies(db: &LiminalDB) -> Vec<LatencyAnomaly> {
+    db.scan_facts()
+        .unwrap()
+        .into_iter()
+        .filter(|f| f.attribute == Attribute::SignalLatencyAnomaly)
+        .map(|f| serde_json::from_value(f.value).unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_slow_api_signal_flagged_against_baseline() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(LiminalDB::open(db_dir.path()).unwrap());
+    let metrics = Arc::new(liminalqa_core::metrics::MetricsRegistry::new());
+    let app = liminalqa_ingest::app(AppState::new(db.clone(), None, metrics));
+
+    for latency_ms in [95, 100, 105, 98, 102] {
+        ingest_run(&app, latency_ms).await;
+    }
+    let baseline = db
+        .get_latency_baseline("test_checkout", "payments", SignalType::API)
+        .unwrap()
+        .expect("API latency baseline");
+    assert_eq!(baseline.sample_size, 5);
+    assert!(!baseline.provisional);
+    assert!(anomalies(&db).is_empty());
+
+    ingest_run(&app, 1000).await;
+    let flagged = anomalies(&db);
+    assert_eq!(flagged.len(), 1);
+    assert_eq!(flagged[0].signal_type, SignalType::API);
+    assert_eq!(flagged[0].latency_ms, 1000);
+    assert_eq!(flagged[0].mean_ms, 100.0);
+    assert!(flagged[0].z_score > flagged[0].sigma);
+
+    // Each signal type has its own baseline
+    let ws = db
+        .get_latency_baseline("test_checkout", "payments", SignalType::WebSocket)
+        .unwrap()
+        .expect("websocket latency baseline");
+    assert_eq!((ws.mean_ms, ws.sample_size), (20.0, 6));
+}
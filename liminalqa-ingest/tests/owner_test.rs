@@ -0,0 +1,95 @@
+#![cfg(test)]
+#![allow(clippy::disallowed_methods)]
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use liminalqa_core::{entities::Test, report::ReflectionReport, types::EntityId};
+use liminalqa_db::LiminalDB;
+use liminalqa_ingest::AppState;
+use std::sync::Arc;
+use tower::util::ServiceExt; // for `oneshot`
+
+mod common;
+
+async fn get_json<T: serde::de::DeserializeOwned>(app: &Router, uri: &str) -> (StatusCode, T) {
+    let (status, bytes) = common::get(app, uri).await;
+    (status, serde_json::from_slice(&bytes).unwrap())
+}
+
+#[tokio::test]
+async fn test_filter_tests_and_report_by_owner() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(LiminalDB::open(db_dir.path()).unwrap());
+    let metrics = Arc::new(liminalqa_core::metrics::MetricsRegistry::new());
+    let app = liminalqa_ingest::app(AppState::new(db, None, metrics));
+    let run_id = EntityId::new();
+
+    let batch = serde_json::json!({
+        "run": {
+            "run_id": run_id,
+            "build_id": EntityId::new(),
+            "plan_name": "nightly",
+            "env": {},
+            "started_at": chrono::Utc::now(),
+            "runner_version": "1.0.0",
+        },
+        "tests": [
+            {"name": "test_pay", "suite": "checkout", "status": "fail", "duration_ms": 900,
+             "owner": "payments"},
+            {"name": "test_refund", "suite": "checkout", "status": "pass", "duration_ms": 300,
+             "owner": "payments"},
+            {"name": "test_login", "suite": "auth", "status": "fail", "duration_ms": 120,
+             "owner": "identity"},
+            {"name": "test_logout", "suite": "auth", "status": "pass", "duration_ms": 80},
+        ],
+    });
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/ingest/batch")
+                .header("Content-Type", "application/json")
+                .body(Body::from(batch.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let (status, tests): (_, Vec<Test>) = get_json(&app, "/api/tests?owner=payments").await;
+    assert_eq!(status, StatusCode::OK);
+    let names: Vec<&str> = tests.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names.len(), 2);
+    assert!(names.contains(&"test_pay") && names.contains(&"test_refund"));
+
+    let uri = format!("/api/runs/{}/report?owner=payments", run_id);
+    let (status, report): (_, ReflectionReport) = get_json(&app, &uri).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(report.summary.total, 2);
+    assert_eq!(report.summary.failed, 1);
+    assert_eq!(report.causality_trails.len(), 1);
+    assert_eq!(report.causality_trails[0].test_name, "test_pay");
+    assert_eq!(
+        report.causality_trails[0].owner.as_deref(),
+        Some("payments")
+    );
+
+    // Unfiltered, every test is reported with its owner where known
+    let uri = format!("/api/runs/{}/report", run_id);
+    let (_, report): (_, ReflectionReport) = get_json(&app, &uri).await;
+    assert_eq!(report.summary.total, 4);
+    let owners: Vec<Option<&str>> = report
+        .causality_trails
+        .iter()
+        .map(|t| t.owner.as_deref())
+        .collect();
+    assert_eq!(owners, vec![Some("identity"), Some("payments")]);
+
+    let (status, _): (_, serde_json::Value) =
+        get_json(&app, &format!("/api/runs/{}/report", EntityId::new())).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,84 @@
+#![cfg(test)]
+#![allow(clippy::disallowed_methods)]
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use liminalqa_core::{entities::TestProgress, types::EntityId};
+use liminalqa_db::LiminalDB;
+use liminalqa_ingest::{handlers::TestProgressDto, AppState};
+use std::sync::Arc;
+use tower::util::ServiceExt; // for `oneshot`
+
+fn progress_request(test_id: EntityId, dto: &TestProgressDto) -> Request<Body> {
+    Request::builder()
+        .method("POST")
+        .uri(format!("/ingest/tests/{}/progress", test_id))
+        .header("Content-Type", "application/json")
+        .body(Body::from(serde_json::to_string(dto).unwrap()))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_progress_updates_read_back_in_order() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(LiminalDB::open(db_dir.path()).unwrap());
+    let metrics = Arc::new(liminalqa_core::metrics::MetricsRegistry::new());
+    let app = liminalqa_ingest::app(AppState::new(db, None, metrics));
+
+    let run_id = EntityId::new();
+    let test_id = EntityId::new();
+    let t0 = chrono::Utc::now();
+    for (phase, percent, offset_secs) in [("login", 30, 0), ("checkout", 80, 5)] {
+        let dto = TestProgressDto {
+            run_id,
+            phase: phase.to_string(),
+            percent: Some(percent),
+            message: None,
+            at: Some(t0 + chrono::Duration::seconds(offset_secs)),
+        };
+        let response = app
+            .clone()
+            .oneshot(progress_request(test_id, &dto))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/tests/{}/progress", test_id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let progress: Vec<TestProgress> = serde_json::from_slice(&body).unwrap();
+    let phases: Vec<(&str, Option<u8>)> = progress
+        .iter()
+        .map(|p| (p.phase.as_str(), p.percent))
+        .collect();
+    assert_eq!(phases, [("login", Some(30)), ("checkout", Some(80))]);
+
+    // Out-of-range percentages are rejected
+    let invalid = TestProgressDto {
+        run_id,
+        phase: "payment".to_string(),
+        percent: Some(150),
+        message: None,
+        at: None,
+    };
+    let response = app
+        .oneshot(progress_request(test_id, &invalid))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
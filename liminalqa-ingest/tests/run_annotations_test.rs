@@ -0,0 +1,80 @@
+#![cfg(test)]
+#![allow(clippy::disallowed_methods)]
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use liminalqa_core::types::EntityId;
+use liminalqa_db::LiminalDB;
+use liminalqa_ingest::{report::AnnotatedRun, AppState};
+use std::sync::Arc;
+
+mod common;
+
+async fn ingest_run(app: &Router, annotations: serde_json::Value) -> (StatusCode, EntityId) {
+    let run_id = EntityId::new();
+    let run = serde_json::json!({
+        "run_id": run_id,
+        "build_id": EntityId::new(),
+        "plan_name": "smoke",
+        "env": {},
+        "started_at": chrono::Utc::now(),
+        "runner_version": "1.0.0",
+        "annotations": annotations,
+    });
+    let request = Request::builder()
+        .method("POST")
+        .uri("/ingest/run")
+        .header("Content-Type", "application/json")
+        .body(Body::from(run.to_string()))
+        .unwrap();
+    let (status, _) = common::send(app, request).await;
+    (status, run_id)
+}
+
+async fn runs(app: &Router, query: &str) -> (StatusCode, Vec<u8>) {
+    let request = Request::builder()
+        .uri(format!("/api/runs{}", query))
+        .body(Body::empty())
+        .unwrap();
+    common::send(app, request).await
+}
+
+#[tokio::test]
+async fn test_runs_are_filtered_by_annotation() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(LiminalDB::open(temp_dir.path()).unwrap());
+    let metrics = Arc::new(liminalqa_core::metrics::MetricsRegistry::new());
+    let app = liminalqa_ingest::app(AppState::new(db, None, metrics));
+
+    let (status, pr_123) = ingest_run(
+        &app,
+        serde_json::json!({"pr": "123", "triggered_by": "ci-bot"}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, pr_456) = ingest_run(&app, serde_json::json!({"pr": "456"})).await;
+    let (_, plain) = ingest_run(&app, serde_json::json!({})).await;
+
+    let (status, body) = runs(&app, "?annotation=pr=123").await;
+    assert_eq!(status, StatusCode::OK);
+    let filtered: Vec<AnnotatedRun> = serde_json::from_slice(&body).unwrap();
+    assert_eq!(filtered.len(), 1);
+    assert_eq!(filtered[0].run.id, pr_123);
+    assert_eq!(filtered[0].annotations["triggered_by"], "ci-bot");
+
+    let (_, body) = runs(&app, "").await;
+    let all: Vec<AnnotatedRun> = serde_json::from_slice(&body).unwrap();
+    let ids: Vec<EntityId> = all.iter().map(|r| r.run.id).collect();
+    assert_eq!(ids, vec![pr_123, pr_456, plain]);
+    assert!(all[2].annotations.is_empty());
+
+    let (status, _) = runs(&app, "?annotation=pr").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Empty keys are refused at ingest
+    let (status, _) = ingest_run(&app, serde_json::json!({" ": "x"})).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,62 @@
+#![cfg(test)]
+#![allow(clippy::disallowed_methods)]
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use liminalqa_core::{entities::EntityType, types::EntityId};
+use liminalqa_db::LiminalDB;
+use liminalqa_ingest::{
+    handlers::{SignalDtoItem, SignalsDto},
+    AppState,
+};
+use std::sync::Arc;
+use tower::util::ServiceExt; // for `oneshot`
+
+#[tokio::test]
+async fn test_run_level_signal_is_ingested_without_test() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(LiminalDB::open(db_dir.path()).unwrap());
+    let metrics = Arc::new(liminalqa_core::metrics::MetricsRegistry::new());
+    let app = liminalqa_ingest::app(AppState::new(db.clone(), None, metrics));
+
+    let run_id = EntityId::new();
+    let dto = SignalsDto {
+        run_id,
+        signals: vec![SignalDtoItem {
+            test_id: None,
+            test_name: None,
+            kind: "system".to_string(),
+            latency_ms: None,
+            value: None,
+            meta: Some(serde_json::json!({"event": "db_connection_dropped"})),
+            at: chrono::Utc::now(),
+            correlation_id: None,
+            payload_artifact_id: None,
+        }],
+        tx_time: None,
+    };
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/ingest/signals")
+                .header("Content-Type", "application/json")
+                .body(Body::from(serde_json::to_string(&dto).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let ids = db.get_entities_by_type(EntityType::Signal).unwrap();
+    assert_eq!(ids.len(), 1);
+    let signal = db.get_signal(ids[0]).unwrap().unwrap();
+    assert_eq!(signal.run_id, run_id);
+    assert!(signal.is_run_level());
+    assert_eq!(
+        signal.metadata["event"],
+        serde_json::json!("db_connection_dropped")
+    );
+}
@@ -0,0 +1,114 @@
+#![cfg(test)]
+#![allow(clippy::disallowed_methods)]
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    response::Response,
+    Router,
+};
+use liminalqa_core::types::{EntityId, RunStatus};
+use liminalqa_db::LiminalDB;
+use liminalqa_ingest::{report::AnnotatedRun, AppState};
+use std::sync::Arc;
+use tower::util::ServiceExt; // for `oneshot`
+
+async fn put_run(
+    app: &Router,
+    run_id: EntityId,
+    plan_name: &str,
+    if_match: Option<&str>,
+) -> Response {
+    let run = serde_json::json!({
+        "run_id": run_id,
+        "build_id": EntityId::new(),
+        "plan_name": plan_name,
+        "env": {},
+        "started_at": chrono::Utc::now(),
+    });
+    let mut builder = Request::builder()
+        .method("POST")
+        .uri("/ingest/run")
+        .header("Content-Type", "application/json");
+    if let Some(version) = if_match {
+        builder = builder.header(header::IF_MATCH, version);
+    }
+    app.clone()
+        .oneshot(builder.body(Body::from(run.to_string())).unwrap())
+        .await
+        .unwrap()
+}
+
+async fn cancel(app: &Router, run_id: EntityId, if_match: &str) -> Response {
+    app.clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/runs/{}/cancel", run_id))
+                .header(header::IF_MATCH, if_match)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+}
+
+fn etag(response: &Response) -> &str {
+    response.headers()[header::ETAG].to_str().unwrap()
+}
+
+async fn listed_version(app: &Router) -> u64 {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/runs")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let runs: Vec<AnnotatedRun> = serde_json::from_slice(&bytes).unwrap();
+    runs[0].version
+}
+
+#[tokio::test]
+async fn test_stale_run_update_is_rejected() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(LiminalDB::open(temp_dir.path()).unwrap());
+    let metrics = Arc::new(liminalqa_core::metrics::MetricsRegistry::new());
+    let app = liminalqa_ingest::app(AppState::new(db.clone(), None, metrics));
+
+    let run_id = EntityId::new();
+    let created = put_run(&app, run_id, "smoke", None).await;
+    assert_eq!(created.status(), StatusCode::OK);
+    assert_eq!(etag(&created), "\"1\"");
+
+    // Two clients read version 1; the first update wins
+    assert_eq!(listed_version(&app).await, 1);
+    let first = put_run(&app, run_id, "smoke-renamed", Some("\"1\"")).await;
+    assert_eq!(first.status(), StatusCode::OK);
+    assert_eq!(etag(&first), "\"2\"");
+
+    let stale = put_run(&app, run_id, "smoke-other", Some("\"1\"")).await;
+    assert_eq!(stale.status(), StatusCode::CONFLICT);
+    assert_eq!(
+        cancel(&app, run_id, "\"1\"").await.status(),
+        StatusCode::CONFLICT
+    );
+    assert_eq!(db.run_status(run_id).unwrap(), RunStatus::Running);
+
+    // Re-reading the run gives the version to base the update on
+    let version = listed_version(&app).await;
+    assert_eq!(version, 2);
+    let cancelled = cancel(&app, run_id, &format!("\"{}\"", version)).await;
+    assert_eq!(cancelled.status(), StatusCode::OK);
+    assert_eq!(etag(&cancelled), "\"3\"");
+    assert_eq!(db.run_status(run_id).unwrap(), RunStatus::Cancelled);
+
+    let unparsable = put_run(&app, run_id, "smoke", Some("latest")).await;
+    assert_eq!(unparsable.status(), StatusCode::BAD_REQUEST);
+}
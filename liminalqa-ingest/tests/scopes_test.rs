@@ -0,0 +1,158 @@
+#![cfg(test)]
+#![allow(clippy::disallowed_methods)]
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    Router,
+};
+use jsonwebtoken::{encode, EncodingKey, Header};
+use liminalqa_core::types::EntityId;
+use liminalqa_db::LiminalDB;
+use liminalqa_ingest::{
+    auth::{JwtConfig, Scope},
+    AppState,
+};
+use std::sync::Arc;
+use tower::util::ServiceExt; // for `oneshot`
+
+const SECRET: &[u8] = b"test-secret";
+
+fn app(db_dir: &tempfile::TempDir) -> Router {
+    let db = Arc::new(LiminalDB::open(db_dir.path()).unwrap());
+    let metrics = Arc::new(liminalqa_core::metrics::MetricsRegistry::new());
+    let state = AppState::new(db, Some("admin-token".to_string()), metrics)
+        .with_scoped_token("query-token", [Scope::Query])
+        .with_scoped_token("ingest-token", [Scope::Ingest])
+        .with_jwt(JwtConfig::hs256(SECRET));
+    liminalqa_ingest::app(state)
+}
+
+fn jwt(scope: &str) -> String {
+    let claims = serde_json::json!({
+        "sub": "dashboard",
+        "exp": chrono::Utc::now().timestamp() + 3600,
+        "scope": scope,
+    });
+    encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(SECRET),
+    )
+    .unwrap()
+}
+
+async fn ingest_run(app: Router, bearer: &str) -> StatusCode {
+    ingest_run_with_id(app, bearer, EntityId::new()).await
+}
+
+async fn ingest_run_with_id(app: Router, bearer: &str, run_id: EntityId) -> StatusCode {
+    let body = serde_json::json!({
+        "run_id": run_id,
+        "build_id": EntityId::new(),
+        "plan_name": "scopes",
+        "env": {},
+        "started_at": chrono::Utc::now(),
+        "runner_version": "test",
+    });
+    app.oneshot(
+        Request::builder()
+            .method("POST")
+            .uri("/ingest/run")
+            .header("Content-Type", "application/json")
+            .header(header::AUTHORIZATION, format!("Bearer {}", bearer))
+            .body(Body::from(body.to_string()))
+            .unwrap(),
+    )
+    .await
+    .unwrap()
+    .status()
+}
+
+async fn cancel_run(app: Router, bearer: &str, run_id: EntityId) -> StatusCode {
+    app.oneshot(
+        Request::builder()
+            .method("POST")
+            .uri(format!("/runs/{}/cancel", run_id))
+            .header(header::AUTHORIZATION, format!("Bearer {}", bearer))
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await
+    .unwrap()
+    .status()
+}
+
+async fn metrics(app: Router, bearer: &str) -> StatusCode {
+    app.oneshot(
+        Request::builder()
+            .uri("/metrics")
+            .header(header::AUTHORIZATION, format!("Bearer {}", bearer))
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await
+    .unwrap()
+    .status()
+}
+
+#[tokio::test]
+async fn test_query_only_token_cannot_ingest() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let app = app(&db_dir);
+    assert_eq!(
+        ingest_run(app.clone(), "query-token").await,
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(
+        ingest_run(app.clone(), &jwt("query")).await,
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(metrics(app, "query-token").await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_ingest_token_cannot_query() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let app = app(&db_dir);
+    assert_eq!(
+        metrics(app.clone(), "ingest-token").await,
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(ingest_run(app, "ingest-token").await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_ingest_token_can_cancel_its_run() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let app = app(&db_dir);
+    let run_id = EntityId::new();
+    assert_eq!(
+        ingest_run_with_id(app.clone(), "ingest-token", run_id).await,
+        StatusCode::OK
+    );
+    assert_eq!(
+        cancel_run(app.clone(), "query-token", run_id).await,
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(
+        cancel_run(app, "ingest-token", run_id).await,
+        StatusCode::OK
+    );
+}
+
+#[tokio::test]
+async fn test_admin_scope_grants_every_route() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let app = app(&db_dir);
+    assert_eq!(ingest_run(app.clone(), &jwt("admin")).await, StatusCode::OK);
+    assert_eq!(metrics(app.clone(), &jwt("admin")).await, StatusCode::OK);
+    assert_eq!(ingest_run(app, "admin-token").await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_jwt_without_scope_is_forbidden() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let app = app(&db_dir);
+    assert_eq!(metrics(app, &jwt("")).await, StatusCode::FORBIDDEN);
+}
@@ -0,0 +1,197 @@
+#![cfg(test)]
+#![allow(clippy::disallowed_methods)]
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    Router,
+};
+use liminalqa_core::{
+    entities::{Artifact, ArtifactType, EntityType},
+    temporal::BiTemporalTime,
+    types::{ArtifactLocation, ArtifactRef, EntityId},
+};
+use liminalqa_db::LiminalDB;
+use liminalqa_ingest::{
+    handlers::{SignalDtoItem, SignalsDto},
+    upload::ArtifactStore,
+    AppState,
+};
+use sha2::{Digest, Sha256};
+use std::{path::PathBuf, sync::Arc};
+use tower::util::ServiceExt; // for `oneshot`
+
+fn signal(payload_artifact_id: Option<EntityId>) -> SignalDtoItem {
+    SignalDtoItem {
+        test_id: None,
+        test_name: None,
+        kind: "api".to_string(),
+        latency_ms: Some(120),
+        value: None,
+        meta: Some(serde_json::json!({"endpoint": "/api/orders"})),
+        at: chrono::Utc::now(),
+        correlation_id: None,
+        payload_artifact_id,
+    }
+}
+
+async fn ingest(app: &Router, payload_artifact_id: Option<EntityId>) -> StatusCode {
+    let dto = SignalsDto {
+        run_id: EntityId::new(),
+        signals: vec![signal(payload_artifact_id)],
+        tx_time: None,
+    };
+    app.clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/ingest/signals")
+                .header("Content-Type", "application/json")
+                .body(Body::from(serde_json::to_string(&dto).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+        .status()
+}
+
+async fn payload(app: &Router, signal_id: EntityId) -> (StatusCode, Vec<u8>, Option<String>) {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/signals/{}/payload", signal_id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = response.status();
+    let content_type = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .map(|v| v.to_str().unwrap().to_string());
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, bytes.to_vec(), content_type)
+}
+
+/// An artifact record pointing at `path`, as `/ingest/artifacts` would
+/// store it
+fn artifact(db: &LiminalDB, bytes: &[u8], path: PathBuf) -> EntityId {
+    let artifact = Artifact {
+        id: EntityId::new(),
+        run_id: EntityId::new(),
+        test_id: EntityId::new(),
+        artifact_ref: ArtifactRef {
+            sha256: format!("{:x}", Sha256::digest(bytes)),
+            location: ArtifactLocation::Local(path),
+            size_bytes: bytes.len() as u64,
+            mime_type: Some("application/json".to_string()),
+        },
+        artifact_type: ArtifactType::ApiResponse,
+        description: None,
+        created_at: BiTemporalTime::now(),
+    };
+    db.put_artifact(&artifact).unwrap();
+    artifact.id
+}
+
+fn signal_ids(db: &LiminalDB) -> Vec<EntityId> {
+    db.get_entities_by_type(EntityType::Signal).unwrap()
+}
+
+#[tokio::test]
+async fn test_signal_payload_is_served_from_its_artifact() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let blob_dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(LiminalDB::open(db_dir.path()).unwrap());
+    let store = ArtifactStore::new(blob_dir.path());
+    let metrics = Arc::new(liminalqa_core::metrics::MetricsRegistry::new());
+    let app = liminalqa_ingest::app(
+        AppState::new(db.clone(), None, metrics).with_artifact_store(store.clone()),
+    );
+
+    // The full response body was uploaded separately; the signal only refers to it
+    let body = serde_json::json!({"orders": vec!["o-1"; 2000]}).to_string();
+    let (_, path) = store.put(body.as_bytes()).unwrap();
+    let artifact_id = artifact(&db, body.as_bytes(), path);
+
+    assert_eq!(ingest(&app, Some(artifact_id)).await, StatusCode::OK);
+    let ids = signal_ids(&db);
+    assert_eq!(ids.len(), 1);
+    let stored = db.get_signal(ids[0]).unwrap().unwrap();
+    assert_eq!(
+        stored.payload_ref.as_ref().unwrap().sha256,
+        format!("{:x}", Sha256::digest(body.as_bytes()))
+    );
+    assert_eq!(stored.metadata["endpoint"], "/api/orders");
+
+    let (status, bytes, content_type) = payload(&app, stored.id).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type.as_deref(), Some("application/json"));
+    assert_eq!(bytes, body.as_bytes());
+
+    // Unknown artifacts are refused at ingest
+    assert_eq!(
+        ingest(&app, Some(EntityId::new())).await,
+        StatusCode::BAD_REQUEST
+    );
+    let (status, _, _) = payload(&app, EntityId::new()).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_signal_payload_outside_artifact_store_is_refused() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let blob_dir = tempfile::tempdir().unwrap();
+    let other_dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(LiminalDB::open(db_dir.path()).unwrap());
+    let metrics = Arc::new(liminalqa_core::metrics::MetricsRegistry::new());
+    let app = liminalqa_ingest::app(
+        AppState::new(db.clone(), None, metrics)
+            .with_artifact_store(ArtifactStore::new(blob_dir.path())),
+    );
+
+    // An artifact reported by reference may point anywhere on the host
+    let secret = b"not for clients";
+    let path = other_dir.path().join("secret.txt");
+    std::fs::write(&path, secret).unwrap();
+    let artifact_id = artifact(&db, secret, path);
+    assert_eq!(
+        ingest(&app, Some(artifact_id)).await,
+        StatusCode::BAD_REQUEST
+    );
+    assert!(signal_ids(&db).is_empty());
+
+    // A stored reference outside the store is not served, and answers the
+    // same as a signal without a payload
+    assert_eq!(ingest(&app, None).await, StatusCode::OK);
+    let plain = signal_ids(&db)[0];
+    let mut forged = db.get_signal(plain).unwrap().unwrap();
+    forged.id = EntityId::new();
+    forged.payload_ref = Some(ArtifactRef {
+        sha256: format!("{:x}", Sha256::digest(secret)),
+        location: ArtifactLocation::Local(other_dir.path().join("secret.txt")),
+        size_bytes: secret.len() as u64,
+        mime_type: None,
+    });
+    db.put_signal(&forged).unwrap();
+
+    let (plain_status, plain_body, _) = payload(&app, plain).await;
+    let (forged_status, forged_body, _) = payload(&app, forged.id).await;
+    assert_eq!(plain_status, StatusCode::NOT_FOUND);
+    assert_eq!(forged_status, StatusCode::NOT_FOUND);
+    let message = |body: &[u8], id: EntityId| {
+        let json: serde_json::Value = serde_json::from_slice(body).unwrap();
+        json["message"]
+            .as_str()
+            .unwrap()
+            .replace(&id.to_string(), "")
+    };
+    assert_eq!(
+        message(&plain_body, plain),
+        message(&forged_body, forged.id)
+    );
+}
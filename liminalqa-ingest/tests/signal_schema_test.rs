@@ -0,0 +1,102 @@
+#![cfg(test)]
+#![allow(clippy::disallowed_methods)]
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use liminalqa_core::{
+    facts::Attribute,
+    signal_schema::{SchemaEnforcement, SchemaViolation, SignalSchemas},
+    types::{EntityId, SignalType},
+};
+use liminalqa_db::LiminalDB;
+use liminalqa_ingest::AppState;
+use std::sync::Arc;
+use tower::util::ServiceExt; // for `oneshot`
+
+/// Ingest a run with one complete API signal and one lacking `status`
+async fn ingest_batch(app: &Router) -> StatusCode {
+    let batch = serde_json::json!({
+        "run": {
+            "run_id": EntityId::new(),
+            "build_id": EntityId::new(),
+            "plan_name": "smoke",
+            "env": {},
+            "started_at": chrono::Utc::now(),
+            "runner_version": "1.0.0",
+        },
+        "tests": [
+            {"name": "test_checkout", "suite": "payments", "status": "pass", "duration_ms": 900},
+        ],
+        "signals": [
+            {"test_name": "test_checkout", "kind": "api", "latency_ms": 80,
+             "at": chrono::Utc::now(), "meta": {"method": "POST", "status": 201}},
+            {"test_name": "test_checkout", "kind": "api", "latency_ms": 95,
+             "at": chrono::Utc::now(), "meta": {"method": "GET"}},
+        ],
+    });
+    app.clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/ingest/batch")
+                .header("Content-Type", "application/json")
+                .body(Body::from(batch.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+        .status()
+}
+
+fn violations(db: &LiminalDB) -> Vec<SchemaViolation> {
+    db.scan_facts()
+        .unwrap()
+        .into_iter()
+        .filter(|f| f.attribute == Attribute::SignalSchemaViolation)
+        .map(|f| serde_json::from_value(f.value).unwrap())
+        .collect()
+}
+
+fn app(db: Arc<LiminalDB>, enforcement: SchemaEnforcement) -> Router {
+    let metrics = Arc::new(liminalqa_core::metrics::MetricsRegistry::new());
+    liminalqa_ingest::app(
+        AppState::new(db, None, metrics).with_signal_schemas(SignalSchemas::new(enforcement)),
+    )
+}
+
+#[tokio::test]
+async fn test_api_signal_missing_status_is_flagged() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(LiminalDB::open(db_dir.path()).unwrap());
+
+    assert_eq!(
+        ingest_batch(&app(db.clone(), SchemaEnforcement::Flag)).await,
+        StatusCode::OK
+    );
+    assert_eq!(
+        violations(&db),
+        [SchemaViolation {
+            signal_type: SignalType::API,
+            missing: vec!["status".to_string()],
+        }]
+    );
+}
+
+#[tokio::test]
+async fn test_signal_schema_rejects_or_ignores() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(LiminalDB::open(db_dir.path()).unwrap());
+
+    assert_eq!(
+        ingest_batch(&app(db.clone(), SchemaEnforcement::Reject)).await,
+        StatusCode::BAD_REQUEST
+    );
+    assert_eq!(
+        ingest_batch(&app(db.clone(), SchemaEnforcement::Off)).await,
+        StatusCode::OK
+    );
+    assert!(violations(&db).is_empty());
+}
@@ -0,0 +1,106 @@
+#![cfg(test)]
+#![allow(clippy::disallowed_methods)]
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use liminalqa_core::{
+    entities::{Artifact, ArtifactType, EntityType},
+    types::EntityId,
+};
+use liminalqa_db::LiminalDB;
+use liminalqa_ingest::{
+    handlers::{SignalDtoItem, SignalsDto},
+    spillover::{resolve_payload, SignalSpillover, PREVIEW_BYTES, PREVIEW_KEY},
+    AppState,
+};
+use std::sync::Arc;
+use tower::util::ServiceExt; // for `oneshot`
+
+fn signal(meta: serde_json::Value) -> SignalDtoItem {
+    SignalDtoItem {
+        test_id: None,
+        test_name: None,
+        kind: "api".to_string(),
+        latency_ms: Some(120),
+        value: None,
+        meta: Some(meta),
+        at: chrono::Utc::now(),
+        correlation_id: None,
+        payload_artifact_id: None,
+    }
+}
+
+#[tokio::test]
+async fn test_oversized_signal_metadata_spills_to_payload() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let spill_dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(LiminalDB::open(db_dir.path()).unwrap());
+    let metrics = Arc::new(liminalqa_core::metrics::MetricsRegistry::new());
+    let state = AppState::new(db.clone(), None, metrics)
+        .with_signal_spillover(SignalSpillover::new(spill_dir.path(), 1024));
+    let app = liminalqa_ingest::app(state);
+
+    let big_meta = serde_json::json!({
+        "endpoint": "/api/orders",
+        "response_body": "x".repeat(4096),
+    });
+    let dto = SignalsDto {
+        run_id: EntityId::new(),
+        signals: vec![
+            signal(big_meta.clone()),
+            signal(serde_json::json!({"status": 200})),
+        ],
+        tx_time: None,
+    };
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/ingest/signals")
+                .header("Content-Type", "application/json")
+                .body(Body::from(serde_json::to_string(&dto).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let signals: Vec<_> = db
+        .get_entities_by_type(EntityType::Signal)
+        .unwrap()
+        .into_iter()
+        .map(|id| db.get_signal(id).unwrap().unwrap())
+        .collect();
+    let (spilled, inline): (Vec<_>, Vec<_>) =
+        signals.into_iter().partition(|s| s.payload_ref.is_some());
+    assert_eq!(spilled.len(), 1);
+    assert_eq!(inline.len(), 1);
+    assert_eq!(inline[0].metadata["status"], serde_json::json!(200));
+
+    let spilled = &spilled[0];
+    let preview = spilled.metadata[PREVIEW_KEY].as_str().unwrap();
+    assert!(preview.len() <= PREVIEW_BYTES);
+    assert!(!spilled.metadata.contains_key("response_body"));
+
+    let payload_ref = spilled.payload_ref.as_ref().unwrap();
+    let payload = resolve_payload(payload_ref).unwrap();
+    assert_eq!(serde_json::to_value(payload).unwrap(), big_meta);
+    assert!(preview.starts_with(
+        &String::from_utf8_lossy(&std::fs::read(payload_ref.path()).unwrap()[..16]).into_owned()
+    ));
+
+    // The payload is recorded as an artifact of the run
+    let artifacts: Vec<Artifact> = db
+        .get_entities_by_type(EntityType::Artifact)
+        .unwrap()
+        .into_iter()
+        .map(|id| db.get_entity(id).unwrap().unwrap())
+        .collect();
+    assert_eq!(artifacts.len(), 1);
+    assert_eq!(artifacts[0].artifact_ref.sha256, payload_ref.sha256);
+    assert_eq!(artifacts[0].artifact_ref.location, payload_ref.location);
+    assert_eq!(artifacts[0].run_id, spilled.run_id);
+    assert_eq!(artifacts[0].artifact_type, ArtifactType::ApiResponse);
+}
@@ -0,0 +1,79 @@
+#![cfg(test)]
+#![allow(clippy::disallowed_methods)]
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use liminalqa_core::{
+    entities::{EntityType, Test},
+    types::{EntityId, TestStatus},
+};
+use liminalqa_db::LiminalDB;
+use liminalqa_ingest::{
+    handlers::{TestDtoItem, TestsDto},
+    AppState,
+};
+use std::sync::Arc;
+use tower::util::ServiceExt; // for `oneshot`
+
+fn test_item(name: &str, status: &str) -> TestDtoItem {
+    TestDtoItem {
+        name: name.to_string(),
+        suite: "imported".to_string(),
+        guidance: None,
+        status: status.to_string(),
+        duration_ms: Some(10),
+        error: None,
+        started_at: None,
+        completed_at: None,
+        alignment_score: None,
+        owner: None,
+        sla_ms: None,
+        attempts: None,
+    }
+}
+
+#[tokio::test]
+async fn test_status_aliases_applied_on_ingest() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(LiminalDB::open(db_dir.path()).unwrap());
+    let metrics = Arc::new(liminalqa_core::metrics::MetricsRegistry::new());
+    let state = AppState::new(db.clone(), None, metrics)
+        .with_status_alias("error", TestStatus::Fail)
+        .with_status_alias("XPass", TestStatus::XFail);
+    let app = liminalqa_ingest::app(state);
+
+    let dto = TestsDto {
+        run_id: EntityId::new(),
+        tests: vec![
+            test_item("test_crash", "error"),
+            test_item("test_lucky", "xpass"),
+        ],
+        valid_from: chrono::Utc::now(),
+        tx_time: None,
+    };
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/ingest/tests")
+                .header("Content-Type", "application/json")
+                .body(Body::from(serde_json::to_string(&dto).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let status_of = |name: &str| {
+        db.get_entities_by_type(EntityType::Test)
+            .unwrap()
+            .into_iter()
+            .filter_map(|id| db.get_entity::<Test>(id).unwrap())
+            .find(|t| t.name == name)
+            .map(|t| t.status)
+    };
+    assert_eq!(status_of("test_crash"), Some(TestStatus::Fail));
+    assert_eq!(status_of("test_lucky"), Some(TestStatus::XFail));
+}
@@ -0,0 +1,97 @@
+#![cfg(test)]
+#![allow(clippy::disallowed_methods)]
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use liminalqa_core::{
+    entities::{EntityType, Run},
+    types::EntityId,
+};
+use liminalqa_db::LiminalDB;
+use liminalqa_ingest::{handlers::RunDto, AppState};
+use std::sync::Arc;
+use tower::util::ServiceExt; // for `oneshot`
+
+fn run_request(tenant: Option<&str>, run_id: EntityId) -> Request<Body> {
+    let dto = RunDto {
+        run_id,
+        build_id: EntityId::new(),
+        plan_name: "smoke".to_string(),
+        env: serde_json::json!({}),
+        started_at: chrono::Utc::now(),
+        runner_version: Some("1.0.0".to_string()),
+        annotations: Default::default(),
+        tx_time: None,
+    };
+
+    let mut builder = Request::builder()
+        .method("POST")
+        .uri("/ingest/run")
+        .header("Content-Type", "application/json");
+    if let Some(tenant) = tenant {
+        builder = builder.header("X-Tenant-Id", tenant);
+    }
+    builder
+        .body(Body::from(serde_json::to_string(&dto).unwrap()))
+        .unwrap()
+}
+
+fn run_ids(db: &LiminalDB) -> Vec<EntityId> {
+    db.get_entities_by_type(EntityType::Run).unwrap()
+}
+
+#[tokio::test]
+async fn test_tenants_are_isolated() {
+    let default_dir = tempfile::tempdir().unwrap();
+    let team_a_dir = tempfile::tempdir().unwrap();
+    let team_b_dir = tempfile::tempdir().unwrap();
+    let default_db = Arc::new(LiminalDB::open(default_dir.path()).unwrap());
+    let team_a_db = Arc::new(LiminalDB::open(team_a_dir.path()).unwrap());
+    let team_b_db = Arc::new(LiminalDB::open(team_b_dir.path()).unwrap());
+
+    let metrics = Arc::new(liminalqa_core::metrics::MetricsRegistry::new());
+    let state = AppState::new(default_db.clone(), None, metrics)
+        .with_tenant("team-a", team_a_db.clone())
+        .with_tenant("team-b", team_b_db.clone());
+    let app = liminalqa_ingest::app(state);
+
+    let run_a = EntityId::new();
+    let run_b = EntityId::new();
+    let run_default = EntityId::new();
+
+    for (tenant, run_id) in [
+        (Some("team-a"), run_a),
+        (Some("team-b"), run_b),
+        (None, run_default),
+    ] {
+        let response = app
+            .clone()
+            .oneshot(run_request(tenant, run_id))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    assert_eq!(run_ids(&team_a_db), vec![run_a]);
+    assert_eq!(run_ids(&team_b_db), vec![run_b]);
+    assert_eq!(run_ids(&default_db), vec![run_default]);
+    assert!(team_b_db.get_entity::<Run>(run_a).unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_unknown_tenant_is_rejected() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(LiminalDB::open(db_dir.path()).unwrap());
+    let metrics = Arc::new(liminalqa_core::metrics::MetricsRegistry::new());
+    let app = liminalqa_ingest::app(AppState::new(db.clone(), None, metrics));
+
+    let response = app
+        .oneshot(run_request(Some("team-z"), EntityId::new()))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert!(run_ids(&db).is_empty());
+}
@@ -0,0 +1,110 @@
+#![cfg(test)]
+#![allow(clippy::disallowed_methods)]
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use liminalqa_core::{
+    entities::Signal,
+    temporal::BiTemporalTime,
+    types::{EntityId, SignalType},
+};
+use liminalqa_db::LiminalDB;
+use liminalqa_ingest::{stats::SignalTimeline, AppState};
+use std::sync::Arc;
+use tower::util::ServiceExt; // for `oneshot`
+
+fn signal(run_id: EntityId, signal_type: SignalType, offset_ms: i64, latency_ms: u64) -> Signal {
+    let t0 = chrono::DateTime::from_timestamp_millis(1_700_000_000_000).unwrap();
+    Signal {
+        id: EntityId::new(),
+        run_id,
+        test_id: None,
+        signal_type,
+        timestamp: t0 + chrono::Duration::milliseconds(offset_ms),
+        latency_ms: Some(latency_ms),
+        payload_ref: None,
+        metadata: Default::default(),
+        created_at: BiTemporalTime::now(),
+        sequence: 0,
+        correlation_id: None,
+    }
+}
+
+fn timeline_request(run_id: EntityId, bucket: &str) -> Request<Body> {
+    Request::builder()
+        .uri(format!("/api/runs/{}/timeline?bucket={}", run_id, bucket))
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_timeline_aggregates_signals_into_buckets() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(LiminalDB::open(db_dir.path()).unwrap());
+    let run_id = EntityId::new();
+    for (signal_type, offset_ms, latency_ms) in [
+        (SignalType::API, 100, 20),
+        (SignalType::API, 800, 40),
+        (SignalType::WebSocket, 1_200, 5),
+        (SignalType::API, 2_500, 90),
+        (SignalType::API, 2_900, 10),
+    ] {
+        db.put_signal(&signal(run_id, signal_type, offset_ms, latency_ms))
+            .unwrap();
+    }
+    let metrics = Arc::new(liminalqa_core::metrics::MetricsRegistry::new());
+    let app = liminalqa_ingest::app(AppState::new(db, None, metrics));
+
+    let response = app
+        .clone()
+        .oneshot(timeline_request(run_id, "1s"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let timeline: SignalTimeline = serde_json::from_slice(&body).unwrap();
+    assert_eq!(timeline.bucket_ms, 1_000);
+    let counts: Vec<_> = timeline
+        .buckets
+        .iter()
+        .map(|b| (b.signal_type, b.count, b.avg_latency_ms))
+        .collect();
+    assert_eq!(
+        counts,
+        vec![
+            (SignalType::API, 2, Some(30.0)),
+            (SignalType::WebSocket, 1, Some(5.0)),
+            (SignalType::API, 2, Some(50.0)),
+        ]
+    );
+
+    // Wider buckets merge everything of a type
+    let response = app
+        .clone()
+        .oneshot(timeline_request(run_id, "1m"))
+        .await
+        .unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let timeline: SignalTimeline = serde_json::from_slice(&body).unwrap();
+    let counts: Vec<_> = timeline
+        .buckets
+        .iter()
+        .map(|b| (b.signal_type, b.count))
+        .collect();
+    assert_eq!(
+        counts,
+        vec![(SignalType::API, 4), (SignalType::WebSocket, 1)]
+    );
+
+    let response = app
+        .oneshot(timeline_request(run_id, "fortnight"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
@@ -2,8 +2,14 @@
 
 use anyhow::Result;
 use async_trait::async_trait;
+use liminalqa_core::metrics::SharedMetrics;
+use prometheus_client::metrics::counter::Counter;
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc,
+};
+use std::time::{Duration, Instant};
 use tracing::{debug, warn};
 
 /// Co-Navigator handles adaptive execution strategies
@@ -12,6 +18,13 @@ pub struct CoNavigator {
     pub max_retries: u32,
     pub retry_delay_ms: u64,
     pub flexible_wait_ms: u64,
+    /// `liminalqa_retries_total`, when metrics are attached
+    #[serde(skip)]
+    retries: Option<Counter>,
+    /// Most attempts any navigation took, when recording (see
+    /// [`Self::recording`])
+    #[serde(skip)]
+    attempts: Option<Arc<AtomicU32>>,
 }
 
 impl Default for CoNavigator {
@@ -20,6 +33,8 @@ impl Default for CoNavigator {
             max_retries: 3,
             retry_delay_ms: 1000,
             flexible_wait_ms: 5000,
+            retries: None,
+            attempts: None,
         }
     }
 }
@@ -39,6 +54,35 @@ impl CoNavigator {
         self
     }
 
+    /// Count every retry in `liminalqa_retries_total`
+    pub fn with_metrics(mut self, metrics: &SharedMetrics) -> Self {
+        self.retries = Some(metrics.retries.clone());
+        self
+    }
+
+    /// A copy of this navigator that records the attempts its navigations
+    /// take, read back with [`Self::attempts`]
+    pub fn recording(&self) -> Self {
+        Self {
+            attempts: Some(Arc::new(AtomicU32::new(0))),
+            ..self.clone()
+        }
+    }
+
+    /// Most attempts any navigation of a recording navigator took; 1 when
+    /// nothing was retried or nothing is recorded
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+            .as_ref()
+            .map_or(1, |a| a.load(Ordering::SeqCst).max(1))
+    }
+
+    fn record_attempts(&self, attempts: u32) {
+        if let Some(recorded) = &self.attempts {
+            recorded.fetch_max(attempts, Ordering::SeqCst);
+        }
+    }
+
     /// Execute with automatic retries on failure
     pub async fn execute_with_retry<F, Fut, T, E>(&self, operation: F) -> Result<T, E>
     where
@@ -46,6 +90,21 @@ impl CoNavigator {
         Fut: std::future::Future<Output = Result<T, E>>,
         E: std::fmt::Display,
     {
+        self.navigate_with_retry(operation).await.0
+    }
+
+    /// Like [`Self::execute_with_retry`], also reporting how many attempts
+    /// were made and how long they took
+    pub async fn navigate_with_retry<F, Fut, T, E>(
+        &self,
+        operation: F,
+    ) -> (Result<T, E>, NavigationResult)
+    where
+        F: Fn() -> Fut,
+        Fut: std::future::Future<Output = Result<T, E>>,
+        E: std::fmt::Display,
+    {
+        let start = Instant::now();
         let mut attempts = 0;
 
         loop {
@@ -56,15 +115,24 @@ impl CoNavigator {
                     if attempts > 1 {
                         debug!("Operation succeeded after {} attempts", attempts);
                     }
-                    return Ok(result);
+                    self.record_attempts(attempts);
+                    let elapsed_ms = start.elapsed().as_millis() as u64;
+                    return (Ok(result), NavigationResult::success(attempts, elapsed_ms));
                 }
                 Err(e) => {
                     if attempts >= self.max_retries {
                         warn!("Operation failed after {} attempts: {}", attempts, e);
-                        return Err(e);
+                        self.record_attempts(attempts);
+                        let elapsed_ms = start.elapsed().as_millis() as u64;
+                        let navigation =
+                            NavigationResult::failure(attempts, elapsed_ms, e.to_string());
+                        return (Err(e), navigation);
                     }
 
                     warn!("Attempt {} failed: {}. Retrying...", attempts, e);
+                    if let Some(retries) = &self.retries {
+                        retries.inc();
+                    }
                     tokio::time::sleep(Duration::from_millis(self.retry_delay_ms)).await;
                 }
             }
@@ -117,3 +185,48 @@ impl NavigationResult {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use liminalqa_core::metrics::MetricsRegistry;
+
+    #[tokio::test]
+    async fn test_retries_are_counted() {
+        let metrics = Arc::new(MetricsRegistry::new());
+        let navigator = CoNavigator::new()
+            .with_retries(3)
+            .with_retry_delay(0)
+            .with_metrics(&metrics);
+
+        let calls = AtomicU32::new(0);
+        let (result, navigation) = navigator
+            .navigate_with_retry(|| async {
+                if calls.fetch_add(1, Ordering::SeqCst) < 2 {
+                    Err("not yet")
+                } else {
+                    Ok("done")
+                }
+            })
+            .await;
+
+        assert_eq!(result, Ok("done"));
+        assert!(navigation.success);
+        assert_eq!(navigation.attempts, 3);
+        assert_eq!(metrics.retries.get(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_exhausted_retries_report_failure() {
+        let navigator = CoNavigator::new().with_retries(2).with_retry_delay(0);
+
+        let (result, navigation) = navigator
+            .navigate_with_retry(|| async { Err::<(), _>("down") })
+            .await;
+
+        assert!(result.is_err());
+        assert!(!navigation.success);
+        assert_eq!(navigation.attempts, 2);
+        assert_eq!(navigation.notes, vec!["down".to_string()]);
+    }
+}
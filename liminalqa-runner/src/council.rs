@@ -1,23 +1,116 @@
 //! Inner Council — Signal reconciliation and unified view
 
-use liminalqa_core::{entities::Signal, types::SignalType};
+use anyhow::{Context, Result};
+use liminalqa_core::{
+    entities::Signal,
+    types::{ArtifactLocation, EntityId, SignalType},
+};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::{Arc, Mutex};
 use tracing::debug;
 
+/// Metadata key marking a signal as the `"start"` or `"end"` of a span
+pub const SPAN_META_KEY: &str = "span";
+
+/// A UI signal with an API signal of its test this close is consistent
+pub const API_MATCH_WINDOW_MS: u64 = 1_000;
+
+/// A UI signal whose nearest API signal missed [`API_MATCH_WINDOW_MS`] but
+/// is within this is reported as [`Severity::High`]; further off, or with no
+/// API signal at all, as [`Severity::Low`]
+pub const API_BORDERLINE_WINDOW_MS: u64 = 5_000;
+
+type MetaPredicate = Arc<dyn Fn(&BTreeMap<String, serde_json::Value>) -> bool + Send + Sync>;
+
+/// Noise filter applied to signals before reconciliation.
+///
+/// A signal is dropped when its type is excluded, when any metadata
+/// predicate matches, or when it reports a latency below the threshold.
+/// Signals without a latency are never dropped by the threshold.
+#[derive(Clone, Default)]
+pub struct SignalFilter {
+    dropped_types: HashSet<SignalType>,
+    meta_predicates: Vec<MetaPredicate>,
+    min_latency_ms: Option<u64>,
+}
+
+impl SignalFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drop every signal of `signal_type`
+    pub fn drop_type(mut self, signal_type: SignalType) -> Self {
+        self.dropped_types.insert(signal_type);
+        self
+    }
+
+    /// Drop signals whose metadata matches `predicate`
+    pub fn drop_when<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&BTreeMap<String, serde_json::Value>) -> bool + Send + Sync + 'static,
+    {
+        self.meta_predicates.push(Arc::new(predicate));
+        self
+    }
+
+    /// Drop signals whose metadata `key` equals `value`
+    pub fn drop_meta(self, key: impl Into<String>, value: serde_json::Value) -> Self {
+        let key = key.into();
+        self.drop_when(move |meta| meta.get(&key) == Some(&value))
+    }
+
+    /// Drop signals faster than `min_latency_ms`
+    pub fn drop_below_latency(mut self, min_latency_ms: u64) -> Self {
+        self.min_latency_ms = Some(min_latency_ms);
+        self
+    }
+
+    /// Whether `signal` survives the filter
+    pub fn allows(&self, signal: &Signal) -> bool {
+        !(self.dropped_types.contains(&signal.signal_type)
+            || self.meta_predicates.iter().any(|p| p(&signal.metadata))
+            || self
+                .min_latency_ms
+                .zip(signal.latency_ms)
+                .is_some_and(|(min, latency)| latency < min))
+    }
+}
+
+impl std::fmt::Debug for SignalFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SignalFilter")
+            .field("dropped_types", &self.dropped_types)
+            .field("meta_predicates", &self.meta_predicates.len())
+            .field("min_latency_ms", &self.min_latency_ms)
+            .finish()
+    }
+}
+
 /// Inner Council reconciles signals from multiple sources
 #[derive(Debug, Clone)]
 pub struct InnerCouncil {
     signals: Vec<Signal>,
+    filter: SignalFilter,
 }
 
 impl InnerCouncil {
     pub fn new() -> Self {
         Self {
             signals: Vec::new(),
+            filter: SignalFilter::default(),
         }
     }
 
+    /// Ignore signals rejected by `filter` when reconciling. Recorded signals
+    /// are kept, so [`Self::signals`] still returns everything.
+    pub fn with_filter(mut self, filter: SignalFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
     /// Record a signal
     pub fn record(&mut self, signal: Signal) {
         debug!(
@@ -27,16 +120,91 @@ impl InnerCouncil {
         self.signals.push(signal);
     }
 
+    /// Full payload of a recorded signal, read on demand from its
+    /// `payload_ref` and checked against its sha256. `None` when no such
+    /// signal was recorded or it carries no payload; only payloads on the
+    /// local file system can be read.
+    pub fn payload(&self, signal_id: EntityId) -> Result<Option<Vec<u8>>> {
+        let Some(payload_ref) = self
+            .signals
+            .iter()
+            .find(|s| s.id == signal_id)
+            .and_then(|s| s.payload_ref.as_ref())
+        else {
+            return Ok(None);
+        };
+        let ArtifactLocation::Local(path) = &payload_ref.location else {
+            anyhow::bail!("Payload {} is not stored locally", payload_ref.location);
+        };
+        let payload =
+            std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        if !format!("{:x}", Sha256::digest(&payload)).eq_ignore_ascii_case(&payload_ref.sha256) {
+            anyhow::bail!("Payload {} does not match its sha256", path.display());
+        }
+        Ok(Some(payload))
+    }
+
     /// Get all signals
     pub fn signals(&self) -> &[Signal] {
         &self.signals
     }
 
+    /// Collapse duplicate signals recorded within `window_ms` of each other.
+    ///
+    /// Two signals are duplicates when they belong to the same test, have the
+    /// same type and carry the same metadata; ids, latency and the exact
+    /// timestamp are ignored, as those are what differ when instrumentation
+    /// double-records one event. The earliest signal of each group is kept and
+    /// the window is measured from it. Returns the number of signals removed.
+    pub fn dedup(&mut self, window_ms: u64) -> usize {
+        let mut order: Vec<usize> = (0..self.signals.len()).collect();
+        order.sort_by_key(|&i| self.signals[i].timestamp);
+
+        let mut kept: Vec<usize> = Vec::new();
+        let mut keep = vec![false; self.signals.len()];
+        for i in order {
+            let signal = &self.signals[i];
+            let duplicate = kept.iter().any(|&k| {
+                let original = &self.signals[k];
+                original.test_id == signal.test_id
+                    && original.signal_type == signal.signal_type
+                    && original.metadata == signal.metadata
+                    && (signal.timestamp - original.timestamp).num_milliseconds() as u64
+                        <= window_ms
+            });
+            if !duplicate {
+                kept.push(i);
+                keep[i] = true;
+            }
+        }
+
+        let before = self.signals.len();
+        let mut flags = keep.into_iter();
+        self.signals.retain(|_| flags.next().unwrap_or(true));
+        let removed = before - self.signals.len();
+        if removed > 0 {
+            debug!("Deduplicated {} signals (window={}ms)", removed, window_ms);
+        }
+        removed
+    }
+
     /// Reconcile signals into a unified view
     pub fn reconcile(&self) -> ReconciliationResult {
-        let mut by_type: HashMap<SignalType, Vec<&Signal>> = HashMap::new();
+        // Ordered by type so by_type and patterns serialize deterministically
+        let mut by_type: BTreeMap<SignalType, Vec<&Signal>> = BTreeMap::new();
 
-        for signal in &self.signals {
+        let signals: Vec<&Signal> = self
+            .signals
+            .iter()
+            .filter(|s| self.filter.allows(s))
+            .collect();
+        if signals.len() < self.signals.len() {
+            debug!(
+                "Filtered {} noise signals before reconciliation",
+                self.signals.len() - signals.len()
+            );
+        }
+        for signal in &signals {
             by_type.entry(signal.signal_type).or_default().push(signal);
         }
 
@@ -48,18 +216,37 @@ impl InnerCouncil {
             if let Some(api_signals) = by_type.get(&SignalType::API) {
                 // Look for UI changes without corresponding API calls
                 for ui_sig in ui_signals {
-                    let has_corresponding_api = api_signals.iter().any(|api_sig| {
-                        (ui_sig.timestamp - api_sig.timestamp)
-                            .num_milliseconds()
-                            .abs()
-                            < 1000
-                    });
-
-                    if !has_corresponding_api {
-                        inconsistencies.push(format!(
-                            "UI signal at {} has no corresponding API signal",
-                            ui_sig.timestamp
-                        ));
+                    let nearest_api_ms = api_signals
+                        .iter()
+                        .map(|api_sig| {
+                            (ui_sig.timestamp - api_sig.timestamp)
+                                .num_milliseconds()
+                                .unsigned_abs()
+                        })
+                        .min();
+
+                    // An API call just outside the window most likely belongs
+                    // to this UI change and came late or out of order; a UI
+                    // change with nothing near is often one that needs no
+                    // call at all
+                    match nearest_api_ms {
+                        Some(gap_ms) if gap_ms < API_MATCH_WINDOW_MS => {}
+                        Some(gap_ms) if gap_ms < API_BORDERLINE_WINDOW_MS => {
+                            inconsistencies.push(Inconsistency::new(
+                                Severity::High,
+                                format!(
+                                    "UI signal at {} has no API signal within {}ms; nearest is {}ms away",
+                                    ui_sig.timestamp, API_MATCH_WINDOW_MS, gap_ms
+                                ),
+                            ));
+                        }
+                        _ => inconsistencies.push(Inconsistency::new(
+                            Severity::Low,
+                            format!(
+                                "UI signal at {} has no corresponding API signal within {}ms",
+                                ui_sig.timestamp, API_BORDERLINE_WINDOW_MS
+                            ),
+                        )),
                     }
                 }
             }
@@ -84,13 +271,73 @@ impl InnerCouncil {
             }
         }
 
+        let (spans, unpaired) = pair_spans(&signals);
+        inconsistencies.extend(unpaired);
+        // Most suspicious first, in detection order within a severity
+        inconsistencies.sort_by_key(|i| std::cmp::Reverse(i.severity));
+
         ReconciliationResult {
-            total_signals: self.signals.len(),
+            total_signals: signals.len(),
+            run_level_signals: signals.iter().filter(|s| s.is_run_level()).count(),
             by_type: by_type.iter().map(|(k, v)| (*k, v.len())).collect(),
             inconsistencies,
             patterns,
+            spans,
+        }
+    }
+}
+
+/// Pair span start and end signals, see [`SignalSpan`]. Returns the spans
+/// and an inconsistency for each start never closed and each end never
+/// opened.
+///
+/// An end closes the most recent open start of the same test with the same
+/// `correlation_id`; without one, the most recent uncorrelated start of the
+/// same type. Signals are walked in timestamp then sequence order.
+fn pair_spans(signals: &[&Signal]) -> (Vec<SignalSpan>, Vec<Inconsistency>) {
+    let mut marked: Vec<(&Signal, &str)> = signals
+        .iter()
+        .filter_map(|s| Some((*s, s.metadata.get(SPAN_META_KEY)?.as_str()?)))
+        .collect();
+    marked.sort_by_key(|(s, _)| s.ordering_key());
+
+    let mut open: Vec<&Signal> = Vec::new();
+    let mut spans = Vec::new();
+    let mut unpaired = Vec::new();
+    for (signal, phase) in marked {
+        match phase {
+            "start" => open.push(signal),
+            "end" => {
+                let start = open.iter().rposition(|start| {
+                    start.test_id == signal.test_id
+                        && start.correlation_id == signal.correlation_id
+                        && (signal.correlation_id.is_some()
+                            || start.signal_type == signal.signal_type)
+                });
+                match start {
+                    Some(i) => spans.push(SignalSpan::between(open.remove(i), signal)),
+                    None => unpaired.push(Inconsistency::new(
+                        Severity::Low,
+                        format!(
+                            "Span end {:?} signal at {} has no matching start",
+                            signal.signal_type, signal.timestamp
+                        ),
+                    )),
+                }
+            }
+            _ => {}
         }
     }
+    unpaired.extend(open.into_iter().map(|start| {
+        Inconsistency::new(
+            Severity::Medium,
+            format!(
+                "Span start {:?} signal at {} was never closed",
+                start.signal_type, start.timestamp
+            ),
+        )
+    }));
+    (spans, unpaired)
 }
 
 impl Default for InnerCouncil {
@@ -99,10 +346,508 @@ impl Default for InnerCouncil {
     }
 }
 
+/// Cloneable handle to one [`InnerCouncil`] shared between tasks.
+///
+/// Each clone records into the same council, so parallel steps of a test can
+/// record signals without coordinating; reconcile once they are done.
+#[derive(Debug, Clone, Default)]
+pub struct SharedCouncil {
+    inner: Arc<Mutex<InnerCouncil>>,
+}
+
+impl SharedCouncil {
+    pub fn new(council: InnerCouncil) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(council)),
+        }
+    }
+
+    /// Record a signal
+    pub fn record(&self, signal: Signal) {
+        self.lock().record(signal);
+    }
+
+    /// Snapshot of the signals recorded so far
+    pub fn signals(&self) -> Vec<Signal> {
+        self.lock().signals().to_vec()
+    }
+
+    /// Number of signals recorded so far
+    pub fn len(&self) -> usize {
+        self.lock().signals().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Collapse duplicates, see [`InnerCouncil::dedup`]
+    pub fn dedup(&self, window_ms: u64) -> usize {
+        self.lock().dedup(window_ms)
+    }
+
+    /// Reconcile the signals recorded so far
+    pub fn reconcile(&self) -> ReconciliationResult {
+        self.lock().reconcile()
+    }
+
+    /// The council itself, cloned if other handles are still alive
+    pub fn into_inner(self) -> InnerCouncil {
+        match Arc::try_unwrap(self.inner) {
+            Ok(mutex) => mutex.into_inner().unwrap_or_else(|e| e.into_inner()),
+            Err(shared) => shared.lock().unwrap_or_else(|e| e.into_inner()).clone(),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, InnerCouncil> {
+        // A panic while recording leaves the council usable
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl From<InnerCouncil> for SharedCouncil {
+    fn from(council: InnerCouncil) -> Self {
+        Self::new(council)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReconciliationResult {
     pub total_signals: usize,
-    pub by_type: HashMap<SignalType, usize>,
-    pub inconsistencies: Vec<String>,
+    /// Signals not tied to a test, included in `total_signals`
+    #[serde(default)]
+    pub run_level_signals: usize,
+    pub by_type: BTreeMap<SignalType, usize>,
+    /// Most severe first. Each is a `{severity, message}` object; results
+    /// written before severities existed hold plain strings, which read back
+    /// as [`Severity::Medium`].
+    pub inconsistencies: Vec<Inconsistency>,
     pub patterns: Vec<String>,
+    /// Start and end signals paired into spans, in the order they closed
+    #[serde(default)]
+    pub spans: Vec<SignalSpan>,
+}
+
+/// How suspicious an inconsistency is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+}
+
+/// Signals that do not add up, e.g. a UI change with no API call behind it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "InconsistencyRepr")]
+pub struct Inconsistency {
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// Wire forms of an [`Inconsistency`], current first
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum InconsistencyRepr {
+    Ranked { severity: Severity, message: String },
+    Unranked(String),
+}
+
+impl From<InconsistencyRepr> for Inconsistency {
+    fn from(repr: InconsistencyRepr) -> Self {
+        match repr {
+            InconsistencyRepr::Ranked { severity, message } => Self { severity, message },
+            InconsistencyRepr::Unranked(message) => Self::new(Severity::Medium, message),
+        }
+    }
+}
+
+impl Inconsistency {
+    pub fn new(severity: Severity, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            message: message.into(),
+        }
+    }
+}
+
+/// One operation observed as a pair of point signals: a start and an end,
+/// marked by their [`SPAN_META_KEY`] metadata
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignalSpan {
+    pub start_signal_id: EntityId,
+    pub end_signal_id: EntityId,
+    pub signal_type: SignalType,
+    pub duration_ms: u64,
+}
+
+impl SignalSpan {
+    fn between(start: &Signal, end: &Signal) -> Self {
+        Self {
+            start_signal_id: start.id,
+            end_signal_id: end.id,
+            signal_type: start.signal_type,
+            duration_ms: (end.timestamp - start.timestamp).num_milliseconds().max(0) as u64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+    use liminalqa_core::temporal::BiTemporalTime;
+
+    fn signal(
+        test_id: EntityId,
+        signal_type: SignalType,
+        at: chrono::DateTime<Utc>,
+        target: &str,
+    ) -> Signal {
+        Signal {
+            id: EntityId::new(),
+            run_id: EntityId::new(),
+            test_id: Some(test_id),
+            signal_type,
+            timestamp: at,
+            latency_ms: None,
+            payload_ref: None,
+            metadata: BTreeMap::from([("target".to_string(), serde_json::json!(target))]),
+            created_at: BiTemporalTime::now(),
+            sequence: 0,
+            correlation_id: None,
+        }
+    }
+
+    #[test]
+    fn test_payload_is_read_on_demand() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let body = br#"{"orders":["o-1","o-2"]}"#;
+        let path = dir.path().join("orders.json");
+        std::fs::write(&path, body)?;
+
+        let mut with_payload = signal(EntityId::new(), SignalType::API, Utc::now(), "/orders");
+        with_payload.payload_ref = Some(liminalqa_core::types::ArtifactRef {
+            sha256: format!("{:x}", Sha256::digest(body)),
+            location: ArtifactLocation::Local(path.clone()),
+            size_bytes: body.len() as u64,
+            mime_type: Some("application/json".to_string()),
+        });
+        let without = signal(EntityId::new(), SignalType::API, Utc::now(), "/health");
+        let mut council = InnerCouncil::new();
+        council.record(with_payload.clone());
+        council.record(without.clone());
+
+        assert_eq!(
+            council.payload(with_payload.id)?.as_deref(),
+            Some(&body[..])
+        );
+        assert_eq!(council.payload(without.id)?, None);
+        assert_eq!(council.payload(EntityId::new())?, None);
+
+        // A payload changed after it was referenced is refused
+        std::fs::write(&path, b"{}")?;
+        assert!(council.payload(with_payload.id).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_level_signals_are_reconciled_with_the_run() {
+        let t0 = Utc::now();
+        let mut council = InnerCouncil::new();
+        council.record(signal(EntityId::new(), SignalType::UI, t0, "#submit"));
+        council.record(signal(
+            EntityId::new(),
+            SignalType::UI,
+            t0 + Duration::milliseconds(100),
+            "#cancel",
+        ));
+        let mut run_level = signal(
+            EntityId::new(),
+            SignalType::API,
+            t0 + Duration::milliseconds(200),
+            "/health",
+        );
+        run_level.test_id = None;
+        council.record(run_level);
+
+        let result = council.reconcile();
+        assert_eq!(result.total_signals, 3);
+        assert_eq!(result.run_level_signals, 1);
+        assert!(result.inconsistencies.is_empty());
+    }
+
+    #[test]
+    fn test_inconsistencies_ranked_by_severity() {
+        let test_id = EntityId::new();
+        let t0 = Utc::now();
+        let mut council = InnerCouncil::new();
+        // The API call landed just outside the window: a close miss
+        council.record(signal(test_id, SignalType::UI, t0, "#submit"));
+        council.record(signal(
+            test_id,
+            SignalType::API,
+            t0 + Duration::milliseconds(1_500),
+            "/order",
+        ));
+        // Nothing near this UI change at all
+        council.record(signal(
+            test_id,
+            SignalType::UI,
+            t0 + Duration::seconds(20),
+            "#confirm",
+        ));
+
+        let result = council.reconcile();
+        let severities: Vec<Severity> = result.inconsistencies.iter().map(|i| i.severity).collect();
+        assert_eq!(severities, [Severity::High, Severity::Low]);
+        assert!(result.inconsistencies[0].message.contains("1500ms away"));
+        assert!(result.inconsistencies[1].message.contains("within 5000ms"));
+    }
+
+    #[test]
+    fn test_unranked_inconsistencies_still_deserialize() {
+        let json = serde_json::json!({
+            "total_signals": 2,
+            "by_type": {},
+            "inconsistencies": [
+                "UI signal has no corresponding API signal",
+                {"severity": "high", "message": "ranked"}
+            ],
+            "patterns": []
+        });
+        let result: ReconciliationResult = serde_json::from_value(json).unwrap();
+        assert_eq!(
+            result.inconsistencies,
+            [
+                Inconsistency::new(
+                    Severity::Medium,
+                    "UI signal has no corresponding API signal"
+                ),
+                Inconsistency::new(Severity::High, "ranked"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dedup_collapses_double_recorded_click() {
+        let test_id = EntityId::new();
+        let t0 = Utc::now();
+        let mut council = InnerCouncil::new();
+        council.record(signal(test_id, SignalType::UI, t0, "#submit"));
+        council.record(signal(
+            test_id,
+            SignalType::UI,
+            t0 + Duration::milliseconds(50),
+            "#submit",
+        ));
+
+        assert_eq!(council.dedup(100), 1);
+        assert_eq!(council.signals().len(), 1);
+        assert_eq!(council.signals()[0].timestamp, t0);
+        assert_eq!(council.reconcile().total_signals, 1);
+    }
+
+    #[test]
+    fn test_dedup_keeps_distinct_signals() {
+        let test_id = EntityId::new();
+        let t0 = Utc::now();
+        let mut council = InnerCouncil::new();
+        council.record(signal(test_id, SignalType::UI, t0, "#submit"));
+        // Different metadata
+        council.record(signal(
+            test_id,
+            SignalType::UI,
+            t0 + Duration::milliseconds(10),
+            "#cancel",
+        ));
+        // Different type
+        council.record(signal(
+            test_id,
+            SignalType::API,
+            t0 + Duration::milliseconds(20),
+            "#submit",
+        ));
+        // Outside the window
+        council.record(signal(
+            test_id,
+            SignalType::UI,
+            t0 + Duration::milliseconds(500),
+            "#submit",
+        ));
+
+        assert_eq!(council.dedup(100), 0);
+        assert_eq!(council.signals().len(), 4);
+    }
+
+    #[test]
+    fn test_filter_hides_heartbeats_from_reconciliation() {
+        let test_id = EntityId::new();
+        let t0 = Utc::now();
+        let heartbeat = |offset_ms: i64| {
+            let mut s = signal(
+                test_id,
+                SignalType::WebSocket,
+                t0 + Duration::milliseconds(offset_ms),
+                "/ws",
+            );
+            s.metadata
+                .insert("kind".to_string(), serde_json::json!("heartbeat"));
+            s.latency_ms = Some(if offset_ms == 0 { 900 } else { 5 });
+            s
+        };
+
+        let mut council = InnerCouncil::new().with_filter(
+            SignalFilter::new()
+                .drop_meta("kind", serde_json::json!("heartbeat"))
+                .drop_type(SignalType::System),
+        );
+        for offset_ms in [0, 100, 200, 300] {
+            council.record(heartbeat(offset_ms));
+        }
+        council.record(signal(test_id, SignalType::System, t0, "cpu"));
+        council.record(signal(test_id, SignalType::API, t0, "/login"));
+
+        let result = council.reconcile();
+        assert_eq!(result.total_signals, 1);
+        assert_eq!(result.by_type.get(&SignalType::API), Some(&1));
+        assert!(!result.by_type.contains_key(&SignalType::WebSocket));
+        // The heartbeats' latency spike is not reported either
+        assert!(result.patterns.is_empty());
+        assert_eq!(council.signals().len(), 6);
+    }
+
+    #[test]
+    fn test_reconciliation_serializes_identically() {
+        let t0 = Utc::now();
+        let test_id = EntityId::new();
+        let build = || {
+            let mut council = InnerCouncil::new();
+            for (i, signal_type) in [
+                SignalType::WebSocket,
+                SignalType::API,
+                SignalType::Database,
+                SignalType::GRPC,
+                SignalType::Network,
+            ]
+            .into_iter()
+            .enumerate()
+            {
+                for latency_ms in [5, 5, 5, 400] {
+                    let mut s = signal(test_id, signal_type, t0, &format!("target-{}", i));
+                    s.latency_ms = Some(latency_ms);
+                    council.record(s);
+                }
+            }
+            council.reconcile()
+        };
+
+        let first = serde_json::to_string(&build()).unwrap();
+        let second = serde_json::to_string(&build()).unwrap();
+        assert_eq!(first, second);
+        assert!(first
+            .contains(r#""by_type":{"api":4,"websocket":4,"grpc":4,"database":4,"network":4}"#));
+    }
+
+    #[test]
+    fn test_pairs_span_signals_and_flags_unclosed_start() {
+        let test_id = EntityId::new();
+        let t0 = Utc::now();
+        let marked = |offset_ms: i64, phase: &str, correlation_id: Option<&str>| {
+            let mut s = signal(
+                test_id,
+                SignalType::API,
+                t0 + Duration::milliseconds(offset_ms),
+                "/checkout",
+            );
+            s.metadata
+                .insert(SPAN_META_KEY.to_string(), serde_json::json!(phase));
+            s.correlation_id = correlation_id.map(str::to_string);
+            s
+        };
+
+        let start = marked(0, "start", Some("req-1"));
+        let unclosed = marked(50, "start", Some("req-2"));
+        let end = marked(320, "end", Some("req-1"));
+        let mut council = InnerCouncil::new();
+        for s in [&end, &unclosed, &start] {
+            council.record(s.clone());
+        }
+
+        let result = council.reconcile();
+        assert_eq!(
+            result.spans,
+            [SignalSpan {
+                start_signal_id: start.id,
+                end_signal_id: end.id,
+                signal_type: SignalType::API,
+                duration_ms: 320,
+            }]
+        );
+        assert_eq!(result.inconsistencies.len(), 1);
+        assert_eq!(result.inconsistencies[0].severity, Severity::Medium);
+        assert!(result.inconsistencies[0].message.contains("never closed"));
+
+        // Without a correlation id, an end closes the latest start of its type
+        let mut council = InnerCouncil::new();
+        council.record(marked(0, "start", None));
+        council.record(marked(100, "start", None));
+        council.record(marked(150, "end", None));
+        council.record(marked(400, "end", None));
+        let result = council.reconcile();
+        let durations: Vec<u64> = result.spans.iter().map(|s| s.duration_ms).collect();
+        assert_eq!(durations, [50, 400]);
+        assert!(result.inconsistencies.is_empty());
+    }
+
+    #[test]
+    fn test_filter_drops_signals_below_latency_threshold() {
+        let filter = SignalFilter::new().drop_below_latency(50);
+        let mut fast = signal(EntityId::new(), SignalType::API, Utc::now(), "/ping");
+        fast.latency_ms = Some(3);
+        let mut slow = fast.clone();
+        slow.latency_ms = Some(80);
+        let untimed = signal(EntityId::new(), SignalType::UI, Utc::now(), "#ok");
+
+        assert!(!filter.allows(&fast));
+        assert!(filter.allows(&slow));
+        assert!(filter.allows(&untimed));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_shared_council_records_from_concurrent_tasks() {
+        let shared = SharedCouncil::new(InnerCouncil::new());
+        let test_id = EntityId::new();
+        let t0 = Utc::now();
+
+        let tasks: Vec<_> = (0..8)
+            .map(|task| {
+                let council = shared.clone();
+                tokio::spawn(async move {
+                    for i in 0..25 {
+                        council.record(signal(
+                            test_id,
+                            SignalType::API,
+                            t0 + Duration::milliseconds(i),
+                            &format!("/task-{}/{}", task, i),
+                        ));
+                        tokio::task::yield_now().await;
+                    }
+                })
+            })
+            .collect();
+        for task in futures::future::join_all(tasks).await {
+            task.unwrap();
+        }
+
+        assert_eq!(shared.len(), 200);
+        assert_eq!(shared.reconcile().total_signals, 200);
+        let council = shared.into_inner();
+        let targets: HashSet<_> = council
+            .signals()
+            .iter()
+            .map(|s| s.metadata["target"].clone())
+            .collect();
+        assert_eq!(targets.len(), 200);
+    }
 }
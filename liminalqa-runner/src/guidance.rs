@@ -1,6 +1,8 @@
 //! Guidance — Test intention and observable goals
 
+use liminalqa_core::{entities::Signal, types::SignalType};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// Guidance defines what we want to observe in the system
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,6 +16,15 @@ pub struct Guidance {
     /// Timeout for overall guidance (ms)
     pub timeout_ms: u64,
 
+    /// Per-signal-type latency deadlines (ms), overriding `timeout_ms`
+    #[serde(default)]
+    pub signal_timeouts_ms: HashMap<SignalType, u64>,
+
+    /// Longest the test is expected to take (ms); exceeding it is reported
+    /// as an SLA breach regardless of the test's baseline
+    #[serde(default)]
+    pub sla_ms: Option<u64>,
+
     /// Whether this is a happy path or edge case
     pub category: GuidanceCategory,
 }
@@ -39,6 +50,99 @@ pub enum Observable {
     Custom { description: String },
 }
 
+impl Observable {
+    /// `type` labels of observables in plan YAML, with their required fields
+    pub const PLAN_TYPES: &'static [(&'static str, &'static [&'static str])] = &[
+        ("ui_visible", &["selector"]),
+        ("ui_contains_text", &["selector", "text"]),
+        ("api_status", &["endpoint", "status"]),
+        ("ws_message", &["pattern"]),
+        ("grpc_success", &["method"]),
+        ("custom", &["description"]),
+    ];
+
+    /// Required fields of the plan observable `type`, `None` if unknown
+    pub fn required_plan_fields(type_label: &str) -> Option<&'static [&'static str]> {
+        Self::PLAN_TYPES
+            .iter()
+            .find(|(label, _)| *label == type_label)
+            .map(|(_, fields)| *fields)
+    }
+
+    /// Whether one of `signals` shows the observable was met, judging by
+    /// the signal type and its metadata:
+    ///
+    /// - `UiVisible`: a UI signal with this `selector`, unless `visible` is false
+    /// - `UiContainsText`: a UI signal with this `selector` whose `text` contains the text
+    /// - `ApiStatus`: an API signal with this `endpoint` (or `path`) and `status`
+    /// - `WsMessage`: a WebSocket signal whose `message` contains the pattern
+    /// - `GrpcSuccess`: a gRPC signal with this `method` and an `OK` (or no) `status`
+    /// - `Custom`: any signal whose `observable` is the description
+    pub fn is_satisfied_by(&self, signals: &[Signal]) -> bool {
+        signals.iter().any(|s| self.matches(s))
+    }
+
+    fn matches(&self, signal: &Signal) -> bool {
+        let meta = |key: &str| signal.metadata.get(key);
+        let meta_str = |key: &str| meta(key).and_then(|v| v.as_str());
+        match self {
+            Observable::UiVisible { selector } => {
+                signal.signal_type == SignalType::UI
+                    && meta_str("selector") == Some(selector.as_str())
+                    && meta("visible").and_then(|v| v.as_bool()) != Some(false)
+            }
+            Observable::UiContainsText { selector, text } => {
+                signal.signal_type == SignalType::UI
+                    && meta_str("selector") == Some(selector.as_str())
+                    && meta_str("text").is_some_and(|t| t.contains(text.as_str()))
+            }
+            Observable::ApiStatus { endpoint, status } => {
+                let code = meta("status").and_then(|v| match v {
+                    serde_json::Value::Number(n) => n.as_u64(),
+                    serde_json::Value::String(s) => s.parse().ok(),
+                    _ => None,
+                });
+                signal.signal_type == SignalType::API
+                    && (meta_str("endpoint") == Some(endpoint.as_str())
+                        || meta_str("path") == Some(endpoint.as_str()))
+                    && code == Some(u64::from(*status))
+            }
+            Observable::WsMessage { pattern } => {
+                signal.signal_type == SignalType::WebSocket
+                    && meta_str("message").is_some_and(|m| m.contains(pattern.as_str()))
+            }
+            Observable::GrpcSuccess { method } => {
+                signal.signal_type == SignalType::GRPC
+                    && meta_str("method") == Some(method.as_str())
+                    && match meta("status") {
+                        None => true,
+                        Some(serde_json::Value::Number(n)) => n.as_u64() == Some(0),
+                        Some(v) => v.as_str().is_some_and(|s| s.eq_ignore_ascii_case("ok")),
+                    }
+            }
+            Observable::Custom { description } => {
+                meta_str("observable") == Some(description.as_str())
+            }
+        }
+    }
+}
+
+/// Which observables of a [`Guidance`] the signals of a test satisfied
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuidanceEvaluation {
+    pub satisfied: Vec<Observable>,
+    pub unsatisfied: Vec<Observable>,
+}
+
+impl GuidanceEvaluation {
+    /// Fraction of the observables that were satisfied, `None` when the
+    /// guidance states none
+    pub fn alignment_score(&self) -> Option<f64> {
+        let total = self.satisfied.len() + self.unsatisfied.len();
+        (total > 0).then(|| self.satisfied.len() as f64 / total as f64)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum GuidanceCategory {
     HappyPath,
@@ -54,6 +158,8 @@ impl Guidance {
             intent: intent.into(),
             observables: vec![],
             timeout_ms: 30_000, // 30s default
+            signal_timeouts_ms: HashMap::new(),
+            sla_ms: None,
             category: GuidanceCategory::HappyPath,
         }
     }
@@ -68,8 +174,153 @@ impl Guidance {
         self
     }
 
+    /// Set the latency deadline for one signal type
+    pub fn with_timeout_for(mut self, signal_type: SignalType, timeout_ms: u64) -> Self {
+        self.signal_timeouts_ms.insert(signal_type, timeout_ms);
+        self
+    }
+
+    /// Set the longest the test is expected to take
+    pub fn with_sla(mut self, sla_ms: u64) -> Self {
+        self.sla_ms = Some(sla_ms);
+        self
+    }
+
+    /// Latency deadline for a signal type, falling back to the global timeout
+    pub fn timeout_for(&self, signal_type: SignalType) -> u64 {
+        self.signal_timeouts_ms
+            .get(&signal_type)
+            .copied()
+            .unwrap_or(self.timeout_ms)
+    }
+
+    /// Check every observable against the signals recorded by a test, see
+    /// [`Observable::is_satisfied_by`]
+    pub fn evaluate(&self, signals: &[Signal]) -> GuidanceEvaluation {
+        let (satisfied, unsatisfied) = self
+            .observables
+            .iter()
+            .cloned()
+            .partition(|o| o.is_satisfied_by(signals));
+        GuidanceEvaluation {
+            satisfied,
+            unsatisfied,
+        }
+    }
+
+    /// Signals whose latency exceeded the deadline for their type
+    pub fn late_signals<'a>(&self, signals: &'a [Signal]) -> Vec<&'a Signal> {
+        signals
+            .iter()
+            .filter(|s| {
+                s.latency_ms
+                    .is_some_and(|latency| latency > self.timeout_for(s.signal_type))
+            })
+            .collect()
+    }
+
     pub fn with_category(mut self, category: GuidanceCategory) -> Self {
         self.category = category;
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use liminalqa_core::{temporal::BiTemporalTime, types::EntityId};
+
+    fn signal(signal_type: SignalType, latency_ms: u64) -> Signal {
+        Signal {
+            id: EntityId::new(),
+            run_id: EntityId::new(),
+            test_id: Some(EntityId::new()),
+            signal_type,
+            timestamp: chrono::Utc::now(),
+            latency_ms: Some(latency_ms),
+            payload_ref: None,
+            metadata: Default::default(),
+            created_at: BiTemporalTime::now(),
+            sequence: 0,
+            correlation_id: None,
+        }
+    }
+
+    fn signal_with(signal_type: SignalType, meta: serde_json::Value) -> Signal {
+        Signal {
+            metadata: serde_json::from_value(meta).unwrap_or_default(),
+            ..signal(signal_type, 10)
+        }
+    }
+
+    #[test]
+    fn test_alignment_score_counts_satisfied_observables() {
+        let guidance = Guidance::new("User logs in")
+            .with_observable(Observable::UiVisible {
+                selector: "#dashboard".to_string(),
+            })
+            .with_observable(Observable::ApiStatus {
+                endpoint: "/api/login".to_string(),
+                status: 200,
+            })
+            .with_observable(Observable::WsMessage {
+                pattern: "welcome".to_string(),
+            });
+        let signals = vec![
+            signal_with(
+                SignalType::UI,
+                serde_json::json!({"selector": "#dashboard"}),
+            ),
+            signal_with(
+                SignalType::API,
+                serde_json::json!({"endpoint": "/api/login", "status": 200}),
+            ),
+            // Wrong status: the API observable stays met by the one above,
+            // but nothing says "welcome"
+            signal_with(
+                SignalType::API,
+                serde_json::json!({"endpoint": "/api/login", "status": 500}),
+            ),
+        ];
+
+        let evaluation = guidance.evaluate(&signals);
+        assert_eq!(evaluation.satisfied.len(), 2);
+        assert!(matches!(
+            evaluation.unsatisfied.as_slice(),
+            [Observable::WsMessage { .. }]
+        ));
+        let score = evaluation
+            .alignment_score()
+            .expect("observables were stated");
+        assert!((score - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(format!("{:.2}", score), "0.67");
+
+        assert_eq!(
+            Guidance::new("No intent")
+                .evaluate(&signals)
+                .alignment_score(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_timeout_for_falls_back_to_global() {
+        let guidance = Guidance::new("Chat connects")
+            .with_timeout(1_000)
+            .with_timeout_for(SignalType::WebSocket, 5_000);
+
+        assert_eq!(guidance.timeout_for(SignalType::WebSocket), 5_000);
+        assert_eq!(guidance.timeout_for(SignalType::UI), 1_000);
+    }
+
+    #[test]
+    fn test_ws_signal_within_its_own_timeout_is_not_late() {
+        let signals = vec![signal(SignalType::WebSocket, 3_000)];
+
+        let global_only = Guidance::new("Chat connects").with_timeout(1_000);
+        assert_eq!(global_only.late_signals(&signals).len(), 1);
+
+        let per_type = global_only.with_timeout_for(SignalType::WebSocket, 5_000);
+        assert!(per_type.late_signals(&signals).is_empty());
+    }
+}
@@ -1,10 +1,14 @@
 //! Ingest layer: send test data to storage
 
+use crate::runner::ExecutionResult;
 use anyhow::{Context, Result};
 use async_trait::async_trait;
 use liminalqa_core::{entities::*, types::*};
-use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use tracing::debug;
 
 /// Ingest mode configuration
@@ -16,7 +20,14 @@ pub enum IngestConfig {
     Fs { root: PathBuf },
     /// HTTP-based (production)
     #[serde(rename = "http")]
-    Http { url: String, token: String },
+    Http {
+        url: String,
+        token: String,
+        /// Retries shared by every request of the ingest, see
+        /// [`IngestHttp::with_retry_budget`]
+        #[serde(default)]
+        retry_budget: Option<u32>,
+    },
 }
 
 impl Default for IngestConfig {
@@ -27,25 +38,114 @@ impl Default for IngestConfig {
     }
 }
 
+/// What an execution reported about a test beyond the [`Test`] entity; the
+/// collector records it as facts of the test
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TestOutcome {
+    pub test_id: EntityId,
+    /// Fraction of the guidance observables the test met
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alignment_score: Option<f64>,
+    /// Longest the test was expected to take (ms)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sla_ms: Option<u64>,
+    /// Attempts the test took; `None` when it was not executed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attempts: Option<u32>,
+}
+
+impl From<&ExecutionResult> for TestOutcome {
+    fn from(result: &ExecutionResult) -> Self {
+        Self {
+            test_id: result.test.id,
+            alignment_score: result.reflection.alignment_score,
+            sla_ms: result.sla_ms,
+            attempts: (result.attempts > 0).then_some(result.attempts),
+        }
+    }
+}
+
 /// Unified ingest interface
 #[async_trait]
 pub trait Ingest: Send + Sync {
     async fn put_run(&self, run: &Run) -> Result<()>;
     async fn put_tests(&self, tests: &[Test]) -> Result<()>;
+    /// Store executed tests along with their [`TestOutcome`]
+    async fn put_results(&self, results: &[ExecutionResult]) -> Result<()>;
     async fn put_signals(&self, signals: &[Signal]) -> Result<()>;
     async fn put_artifacts(&self, artifacts: &[Artifact]) -> Result<()>;
+
+    /// Start a new logical batch; the HTTP ingest refills its retry budget
+    fn begin_batch(&self) {}
+
+    /// Store a run with everything it produced as one logical batch
+    async fn put_batch(
+        &self,
+        run: &Run,
+        results: &[ExecutionResult],
+        signals: &[Signal],
+        artifacts: &[Artifact],
+    ) -> Result<()> {
+        self.begin_batch();
+        self.put_run(run).await?;
+        self.put_results(results).await?;
+        self.put_signals(signals).await?;
+        self.put_artifacts(artifacts).await
+    }
 }
 
 /// Create ingest from config
 pub fn create_ingest(config: IngestConfig) -> Box<dyn Ingest> {
     match config {
         IngestConfig::Fs { root } => Box::new(IngestFs::new(root)),
-        IngestConfig::Http { url, token } => Box::new(IngestHttp::new(url, token)),
+        IngestConfig::Http {
+            url,
+            token,
+            retry_budget,
+        } => {
+            let ingest = IngestHttp::new(url, token);
+            match retry_budget {
+                Some(retries) => Box::new(ingest.with_retry_budget(retries)),
+                None => Box::new(ingest),
+            }
+        }
     }
 }
 
 // --- File-system ingest ---
 
+/// Append-only signal log of a run in the file-system backend
+pub const SIGNALS_FILE: &str = "signals.ndjson";
+
+/// JSON array of signals written before [`SIGNALS_FILE`], still read back
+pub const LEGACY_SIGNALS_FILE: &str = "signals.json";
+
+/// Directory under the root holding artifact blobs of every run, by content
+pub const BLOBS_DIR: &str = "blobs";
+
+/// Outcomes of the tests of a run, see [`IngestFs::read_outcomes`]
+pub const OUTCOMES_FILE: &str = "outcomes.json";
+
+/// Logical names of the blobs a run stored, see [`IngestFs::store_blob`]
+pub const BLOB_NAMES_FILE: &str = "blobs.json";
+
+/// `signals` as the lines of a [`SIGNALS_FILE`]
+pub fn signals_ndjson<'a>(signals: impl IntoIterator<Item = &'a Signal>) -> Result<Vec<u8>> {
+    let mut lines = Vec::new();
+    for signal in signals {
+        serde_json::to_writer(&mut lines, signal)?;
+        lines.push(b'\n');
+    }
+    Ok(lines)
+}
+
+/// A blob stored for a run under its logical name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredBlob {
+    pub name: String,
+    pub artifact_ref: ArtifactRef,
+}
+
 pub struct IngestFs {
     root: PathBuf,
 }
@@ -55,6 +155,15 @@ impl IngestFs {
         Self { root }
     }
 
+    fn read_json<T: DeserializeOwned + Default>(&self, run_id: &EntityId, name: &str) -> Result<T> {
+        let path = self.root.join(run_id.to_string()).join(name);
+        if !path.exists() {
+            return Ok(T::default());
+        }
+        serde_json::from_slice(&std::fs::read(&path)?)
+            .with_context(|| format!("Invalid JSON in {:?}", path))
+    }
+
     fn write_json<T: Serialize>(&self, run_id: &EntityId, name: &str, value: &T) -> Result<()> {
         let dir = self.root.join(run_id.to_string());
         std::fs::create_dir_all(&dir)?;
@@ -64,6 +173,180 @@ impl IngestFs {
         debug!("Wrote {} to {:?}", name, path);
         Ok(())
     }
+
+    /// Append `signals` of one run to its `signals.ndjson`, one JSON object
+    /// per line, in a single write so concurrent appends don't interleave
+    fn append_ndjson(&self, run_id: &EntityId, signals: &[&Signal]) -> Result<()> {
+        use std::io::Write;
+
+        let dir = self.root.join(run_id.to_string());
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(SIGNALS_FILE);
+        let lines = signals_ndjson(signals.iter().copied())?;
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open {:?}", path))?
+            .write_all(&lines)?;
+        debug!("Appended {} signals to {:?}", signals.len(), path);
+        Ok(())
+    }
+
+    /// Store the bytes of an artifact named `name` for a run.
+    ///
+    /// Blobs are content-addressed, at `blobs/{sha256[..2]}/{sha256}.{ext}`
+    /// with the extension of `name` (`bin` without one), so artifacts sharing
+    /// a name never overwrite each other and identical content is stored
+    /// once. The logical name is recorded in the run's [`BLOB_NAMES_FILE`].
+    pub fn store_blob(
+        &self,
+        run_id: &EntityId,
+        name: &str,
+        bytes: &[u8],
+        mime_type: Option<&str>,
+    ) -> Result<ArtifactRef> {
+        self.put_blob(run_id, name, bytes, mime_type)
+    }
+
+    /// Store the file at `path` as a blob named `name`, see
+    /// [`IngestFs::store_blob`]
+    pub fn store_blob_file(
+        &self,
+        run_id: &EntityId,
+        name: &str,
+        path: &Path,
+        mime_type: Option<&str>,
+    ) -> Result<ArtifactRef> {
+        let file =
+            std::fs::File::open(path).with_context(|| format!("Failed to open {:?}", path))?;
+        self.put_blob(run_id, name, file, mime_type)
+    }
+
+    /// Copy `source` to a temporary file under [`BLOBS_DIR`], hashing it on
+    /// the way, then rename it to its content address. A blob path only
+    /// ever holds complete content; storing the same bytes again replaces
+    /// it with an identical file.
+    fn put_blob(
+        &self,
+        run_id: &EntityId,
+        name: &str,
+        mut source: impl std::io::Read,
+        mime_type: Option<&str>,
+    ) -> Result<ArtifactRef> {
+        use std::io::Write;
+
+        let blobs = self.root.join(BLOBS_DIR);
+        std::fs::create_dir_all(&blobs)?;
+        let mut file = tempfile::Builder::new()
+            .prefix(".blob-")
+            .tempfile_in(&blobs)
+            .with_context(|| format!("Failed to create a file in {:?}", blobs))?;
+        let mut hasher = Sha256::new();
+        let mut size_bytes = 0;
+        let mut buf = vec![0; 64 * 1024];
+        loop {
+            let read = source.read(&mut buf)?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buf[..read]);
+            file.write_all(&buf[..read])?;
+            size_bytes += read as u64;
+        }
+        file.as_file().sync_all()?;
+
+        let sha256 = format!("{:x}", hasher.finalize());
+        let path = self.root.join(blob_path(&sha256, name));
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        file.persist(&path)
+            .map_err(|e| e.error)
+            .with_context(|| format!("Failed to write {:?}", path))?;
+        debug!("Stored blob {} at {:?}", name, path);
+
+        let artifact_ref = ArtifactRef {
+            sha256,
+            location: ArtifactLocation::Local(path),
+            size_bytes,
+            mime_type: mime_type.map(str::to_string),
+        };
+        let mut names = self.read_blobs(run_id)?;
+        let known = names
+            .iter()
+            .any(|b| b.name == name && b.artifact_ref.sha256 == artifact_ref.sha256);
+        if !known {
+            names.push(StoredBlob {
+                name: name.to_string(),
+                artifact_ref: artifact_ref.clone(),
+            });
+            self.write_json(run_id, BLOB_NAMES_FILE, &names)?;
+        }
+        Ok(artifact_ref)
+    }
+
+    /// `artifact_ref` pointing into the blob store, the local file it
+    /// points at stored as a blob named after the file
+    fn blob_ref(&self, run_id: &EntityId, artifact_ref: &ArtifactRef) -> Result<ArtifactRef> {
+        let ArtifactLocation::Local(path) = &artifact_ref.location else {
+            return Ok(artifact_ref.clone());
+        };
+        if path.starts_with(self.root.join(BLOBS_DIR)) {
+            return Ok(artifact_ref.clone());
+        }
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default();
+        self.store_blob_file(run_id, name, path, artifact_ref.mime_type.as_deref())
+    }
+
+    /// Blobs a run stored, in the order they were stored
+    pub fn read_blobs(&self, run_id: &EntityId) -> Result<Vec<StoredBlob>> {
+        self.read_json(run_id, BLOB_NAMES_FILE)
+    }
+
+    /// Outcomes written for the tests of a run
+    pub fn read_outcomes(&self, run_id: &EntityId) -> Result<Vec<TestOutcome>> {
+        self.read_json(run_id, OUTCOMES_FILE)
+    }
+
+    /// Artifacts written for a run
+    pub fn read_artifacts(&self, run_id: &EntityId) -> Result<Vec<Artifact>> {
+        self.read_json(run_id, "artifacts.json")
+    }
+
+    /// Read every signal written for a run, in append order.
+    ///
+    /// Safe while the run is still being written: a trailing line without
+    /// its newline is an append in progress and is skipped. Runs written
+    /// before signals were appended are read from [`LEGACY_SIGNALS_FILE`].
+    pub fn read_signals(&self, run_id: &EntityId) -> Result<Vec<Signal>> {
+        let dir = self.root.join(run_id.to_string());
+        let path = dir.join(SIGNALS_FILE);
+        if !path.exists() {
+            let legacy = dir.join(LEGACY_SIGNALS_FILE);
+            if legacy.exists() {
+                return Ok(serde_json::from_slice(&std::fs::read(&legacy)?)?);
+            }
+            return Ok(Vec::new());
+        }
+
+        let content = std::fs::read_to_string(&path)?;
+        let complete = match content.rfind('\n') {
+            Some(end) => &content[..end],
+            None => "",
+        };
+        complete
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                serde_json::from_str(line)
+                    .with_context(|| format!("Invalid signal line in {:?}", path))
+            })
+            .collect()
+    }
 }
 
 #[async_trait]
@@ -80,32 +363,134 @@ impl Ingest for IngestFs {
         self.write_json(&run_id, "tests.json", &tests)
     }
 
-    async fn put_signals(&self, signals: &[Signal]) -> Result<()> {
-        if signals.is_empty() {
+    async fn put_results(&self, results: &[ExecutionResult]) -> Result<()> {
+        let tests: Vec<Test> = results.iter().map(|r| r.test.clone()).collect();
+        self.put_tests(&tests).await?;
+        let Some(run_id) = tests.first().map(|t| t.run_id) else {
             return Ok(());
+        };
+        // Merged like artifacts: a test stored again replaces its outcome
+        let mut stored = self.read_outcomes(&run_id)?;
+        for outcome in results.iter().map(TestOutcome::from) {
+            stored.retain(|o| o.test_id != outcome.test_id);
+            stored.push(outcome);
         }
-        let run_id = signals[0].run_id;
-        self.write_json(&run_id, "signals.json", &signals)
+        self.write_json(&run_id, OUTCOMES_FILE, &stored)
     }
 
+    async fn put_signals(&self, signals: &[Signal]) -> Result<()> {
+        let mut by_run: BTreeMap<EntityId, Vec<&Signal>> = BTreeMap::new();
+        for signal in signals {
+            by_run.entry(signal.run_id).or_default().push(signal);
+        }
+        for (run_id, signals) in by_run {
+            self.append_ndjson(&run_id, &signals)?;
+        }
+        Ok(())
+    }
+
+    /// Local files the artifacts point at are copied into the blob store
+    /// and their records point at the blob; other locations are kept
     async fn put_artifacts(&self, artifacts: &[Artifact]) -> Result<()> {
-        if artifacts.is_empty() {
-            return Ok(());
+        let mut by_run: BTreeMap<EntityId, Vec<&Artifact>> = BTreeMap::new();
+        for artifact in artifacts {
+            by_run.entry(artifact.run_id).or_default().push(artifact);
         }
-        let run_id = artifacts[0].run_id;
-        self.write_json(&run_id, "artifacts.json", &artifacts)
+        // Merged into what earlier calls wrote; an artifact written again
+        // replaces its previous record
+        for (run_id, artifacts) in by_run {
+            let mut stored = self.read_artifacts(&run_id)?;
+            for artifact in artifacts {
+                let mut artifact = artifact.clone();
+                artifact.artifact_ref = self.blob_ref(&run_id, &artifact.artifact_ref)?;
+                stored.retain(|a| a.id != artifact.id);
+                stored.push(artifact);
+            }
+            self.write_json(&run_id, "artifacts.json", &stored)?;
+        }
+        Ok(())
     }
 }
 
+/// `{sha256[..2]}/{sha256}.{ext}` under [`BLOBS_DIR`], the extension taken
+/// from the logical `name`
+fn blob_path(sha256: &str, name: &str) -> PathBuf {
+    let ext = Path::new(name)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .filter(|ext| !ext.is_empty())
+        .unwrap_or("bin");
+    Path::new(BLOBS_DIR)
+        .join(&sha256[..2])
+        .join(format!("{}.{}", sha256, ext.to_lowercase()))
+}
+
 // --- HTTP ingest ---
 
+/// Default number of tests, signals or artifacts sent per POST
+pub const DEFAULT_CHUNK_SIZE: usize = 1000;
+
+/// Ingest over the REST API.
+///
+/// Tests, signals and artifacts are sent in chunks of at most `chunk_size`
+/// items, one POST each. A batch is therefore not atomic: when a chunk fails
+/// the others are still sent, and the ones that succeeded stay ingested.
+/// Callers needing all-or-nothing semantics must keep batches within one
+/// chunk.
+///
+/// Each POST is retried up to `max_retries` times. With a retry budget, the
+/// retries of every POST also draw from one shared pool, so a collector that
+/// keeps failing costs a bounded number of attempts for the whole batch
+/// rather than per request.
 pub struct IngestHttp {
     url: String,
     token: String,
     client: reqwest::Client,
     max_retries: u32,
+    chunk_size: usize,
+    retry_backoff_ms: u64,
+    retry_budget: Option<RetryBudget>,
 }
 
+/// Retries shared by the requests of one logical batch.
+///
+/// Once a request fails with the budget spent, the batch is aborted: its
+/// remaining requests fail without being sent until [`Ingest::begin_batch`].
+#[derive(Debug)]
+struct RetryBudget {
+    total: u32,
+    remaining: AtomicU32,
+    aborted: AtomicBool,
+}
+
+impl RetryBudget {
+    fn new(total: u32) -> Self {
+        Self {
+            total,
+            remaining: AtomicU32::new(total),
+            aborted: AtomicBool::new(false),
+        }
+    }
+
+    /// Take one retry, `false` if none is left
+    fn take(&self) -> bool {
+        self.remaining
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+            .is_ok()
+    }
+
+    fn reset(&self) {
+        self.remaining.store(self.total, Ordering::SeqCst);
+        self.aborted.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Error of a request refused or given up on because the batch's retry
+/// budget is spent
+#[derive(Debug, thiserror::Error)]
+#[error("Retry budget of {0} exhausted; batch aborted")]
+pub struct RetryBudgetExhausted(pub u32);
+
 impl IngestHttp {
     pub fn new(url: String, token: String) -> Self {
         let client = reqwest::Client::builder()
@@ -120,15 +505,59 @@ impl IngestHttp {
             token,
             client,
             max_retries: 3,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            retry_backoff_ms: 1000,
+            retry_budget: None,
         }
     }
 
+    /// Share `retries` retries between every request until the next
+    /// [`Ingest::begin_batch`], on top of the per-request limit
+    pub fn with_retry_budget(mut self, retries: u32) -> Self {
+        self.retry_budget = Some(RetryBudget::new(retries));
+        self
+    }
+
+    /// Wait `backoff_ms` before the first retry of a request, doubling for
+    /// each further one
+    pub fn with_retry_backoff(mut self, backoff_ms: u64) -> Self {
+        self.retry_backoff_ms = backoff_ms;
+        self
+    }
+
+    /// Whether another retry may be made, drawing it from the budget. Aborts
+    /// the batch when the budget is spent.
+    fn may_retry(&self, attempt: u32) -> Result<bool, RetryBudgetExhausted> {
+        if attempt > self.max_retries {
+            return Ok(false);
+        }
+        match &self.retry_budget {
+            Some(budget) if !budget.take() => {
+                budget.aborted.store(true, Ordering::SeqCst);
+                Err(RetryBudgetExhausted(budget.total))
+            }
+            _ => Ok(true),
+        }
+    }
+
+    /// Send at most `chunk_size` items per POST (at least 1)
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
     fn is_retryable_error(status: reqwest::StatusCode) -> bool {
         // Retry on 5xx server errors and 429 rate limiting
         status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
     }
 
     async fn post<T: Serialize>(&self, endpoint: &str, body: &T) -> Result<()> {
+        if let Some(budget) = &self.retry_budget {
+            if budget.aborted.load(Ordering::SeqCst) {
+                return Err(RetryBudgetExhausted(budget.total).into());
+            }
+        }
+
         let url = format!("{}{}", self.url, endpoint);
         let mut attempt = 0;
 
@@ -150,18 +579,24 @@ impl IngestHttp {
                 .await
             {
                 Ok(r) => r,
-                Err(e) if attempt <= self.max_retries => {
-                    let backoff_ms = 2u64.pow(attempt - 1) * 1000; // Exponential: 1s, 2s, 4s
+                Err(e) => {
+                    match self.may_retry(attempt) {
+                        Ok(true) => {}
+                        Ok(false) => {
+                            return Err(e).context(format!(
+                                "Failed to POST {} after {} attempts",
+                                endpoint, attempt
+                            ));
+                        }
+                        Err(exhausted) => {
+                            return Err(anyhow::Error::new(e).context(exhausted));
+                        }
+                    }
+                    let backoff_ms = self.backoff_ms(attempt); // Exponential: 1s, 2s, 4s
                     debug!("Request failed: {}. Retrying in {}ms...", e, backoff_ms);
                     tokio::time::sleep(std::time::Duration::from_millis(backoff_ms)).await;
                     continue;
                 }
-                Err(e) => {
-                    return Err(e).context(format!(
-                        "Failed to POST {} after {} attempts",
-                        endpoint, attempt
-                    ));
-                }
             };
 
             let status = resp.status();
@@ -170,17 +605,25 @@ impl IngestHttp {
             if !status.is_success() {
                 let text = resp.text().await.unwrap_or_default();
 
-                if Self::is_retryable_error(status) && attempt <= self.max_retries {
-                    let backoff_ms = 2u64.pow(attempt - 1) * 1000;
-                    debug!(
-                        "HTTP {} {}. Retrying in {}ms...",
-                        status, endpoint, backoff_ms
-                    );
-                    tokio::time::sleep(std::time::Duration::from_millis(backoff_ms)).await;
-                    continue;
-                } else {
-                    anyhow::bail!("HTTP {} {}: {}", status, endpoint, text);
+                if Self::is_retryable_error(status) {
+                    match self.may_retry(attempt) {
+                        Ok(true) => {
+                            let backoff_ms = self.backoff_ms(attempt);
+                            debug!(
+                                "HTTP {} {}. Retrying in {}ms...",
+                                status, endpoint, backoff_ms
+                            );
+                            tokio::time::sleep(std::time::Duration::from_millis(backoff_ms)).await;
+                            continue;
+                        }
+                        Ok(false) => {}
+                        Err(exhausted) => {
+                            return Err(anyhow::anyhow!("HTTP {} {}: {}", status, endpoint, text)
+                                .context(exhausted));
+                        }
+                    }
                 }
+                anyhow::bail!("HTTP {} {}: {}", status, endpoint, text);
             }
 
             // Success - parse response
@@ -197,10 +640,119 @@ impl IngestHttp {
             return Ok(());
         }
     }
+
+    fn backoff_ms(&self, attempt: u32) -> u64 {
+        self.retry_backoff_ms * 2u64.pow(attempt - 1)
+    }
+
+    /// POST `items` in chunks, `body` building the request of each chunk.
+    /// Every chunk is attempted unless the retry budget runs out; the error
+    /// lists the ones that failed.
+    /// POST tests with the outcome each execution reported, if any
+    async fn post_tests(&self, tests: &[(&Test, Option<TestOutcome>)]) -> Result<()> {
+        if tests.is_empty() {
+            return Ok(());
+        }
+
+        #[derive(Serialize)]
+        struct TestsDto {
+            run_id: EntityId,
+            tests: Vec<TestDtoItem>,
+            valid_from: chrono::DateTime<chrono::Utc>,
+        }
+
+        #[derive(Serialize)]
+        struct TestDtoItem {
+            name: String,
+            suite: String,
+            guidance: Option<String>,
+            status: String,
+            duration_ms: Option<i64>,
+            error: Option<TestError>,
+            started_at: Option<chrono::DateTime<chrono::Utc>>,
+            completed_at: Option<chrono::DateTime<chrono::Utc>>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            alignment_score: Option<f64>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            sla_ms: Option<u64>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            attempts: Option<u32>,
+        }
+
+        let run_id = tests[0].0.run_id;
+        let valid_from = chrono::Utc::now();
+        self.post_chunked("/ingest/tests", tests, |chunk| {
+            let items: Vec<TestDtoItem> = chunk
+                .iter()
+                .map(|(t, outcome)| TestDtoItem {
+                    name: t.name.clone(),
+                    suite: t.suite.clone(),
+                    guidance: Some(t.guidance.clone()),
+                    status: format!("{:?}", t.status).to_lowercase(),
+                    duration_ms: Some(t.duration_ms_i64()),
+                    error: t.error.clone(),
+                    started_at: Some(t.started_at),
+                    completed_at: Some(t.completed_at),
+                    alignment_score: outcome.as_ref().and_then(|o| o.alignment_score),
+                    sla_ms: outcome.as_ref().and_then(|o| o.sla_ms),
+                    attempts: outcome.as_ref().and_then(|o| o.attempts),
+                })
+                .collect();
+
+            Ok(TestsDto {
+                run_id,
+                tests: items,
+                valid_from,
+            })
+        })
+        .await
+    }
+
+    async fn post_chunked<T, B: Serialize>(
+        &self,
+        endpoint: &str,
+        items: &[T],
+        body: impl Fn(&[T]) -> Result<B>,
+    ) -> Result<()> {
+        let chunks = items.len().div_ceil(self.chunk_size);
+        let mut failures = Vec::new();
+        for (i, chunk) in items.chunks(self.chunk_size).enumerate() {
+            let result = match body(chunk) {
+                Ok(dto) => self.post(endpoint, &dto).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                debug!("Chunk {}/{} of {} failed: {:#}", i + 1, chunks, endpoint, e);
+                failures.push(format!("chunk {}: {:#}", i + 1, e));
+                if e.is::<RetryBudgetExhausted>() {
+                    failures.push(format!("{} chunks not sent", chunks - i - 1));
+                    break;
+                }
+            }
+        }
+
+        if !failures.is_empty() {
+            anyhow::bail!(
+                "{} of {} chunks to {} failed: {}",
+                failures.len(),
+                chunks,
+                endpoint,
+                failures.join("; ")
+            );
+        }
+        Ok(())
+    }
 }
 
 #[async_trait]
 impl Ingest for IngestHttp {
+    /// Refills the retry budget
+    fn begin_batch(&self) {
+        if let Some(budget) = &self.retry_budget {
+            budget.reset();
+        }
+    }
+
     async fn put_run(&self, run: &Run) -> Result<()> {
         #[derive(Serialize)]
         struct RunDto {
@@ -225,51 +777,16 @@ impl Ingest for IngestHttp {
     }
 
     async fn put_tests(&self, tests: &[Test]) -> Result<()> {
-        if tests.is_empty() {
-            return Ok(());
-        }
-
-        #[derive(Serialize)]
-        struct TestsDto {
-            run_id: EntityId,
-            tests: Vec<TestDtoItem>,
-            valid_from: chrono::DateTime<chrono::Utc>,
-        }
-
-        #[derive(Serialize)]
-        struct TestDtoItem {
-            name: String,
-            suite: String,
-            guidance: Option<String>,
-            status: String,
-            duration_ms: Option<i32>,
-            error: Option<serde_json::Value>,
-            started_at: Option<chrono::DateTime<chrono::Utc>>,
-            completed_at: Option<chrono::DateTime<chrono::Utc>>,
-        }
+        let tests: Vec<(&Test, Option<TestOutcome>)> = tests.iter().map(|t| (t, None)).collect();
+        self.post_tests(&tests).await
+    }
 
-        let run_id = tests[0].run_id;
-        let items: Vec<TestDtoItem> = tests
+    async fn put_results(&self, results: &[ExecutionResult]) -> Result<()> {
+        let tests: Vec<(&Test, Option<TestOutcome>)> = results
             .iter()
-            .map(|t| TestDtoItem {
-                name: t.name.clone(),
-                suite: t.suite.clone(),
-                guidance: Some(t.guidance.clone()),
-                status: format!("{:?}", t.status).to_lowercase(),
-                duration_ms: Some(t.duration_ms as i32),
-                error: t.error.as_ref().map(|e| serde_json::to_value(e).unwrap()),
-                started_at: Some(t.started_at),
-                completed_at: Some(t.completed_at),
-            })
+            .map(|r| (&r.test, Some(TestOutcome::from(r))))
             .collect();
-
-        let dto = TestsDto {
-            run_id,
-            tests: items,
-            valid_from: chrono::Utc::now(),
-        };
-
-        self.post("/ingest/tests", &dto).await
+        self.post_tests(&tests).await
     }
 
     async fn put_signals(&self, signals: &[Signal]) -> Result<()> {
@@ -291,27 +808,32 @@ impl Ingest for IngestHttp {
             value: Option<f64>,
             meta: Option<serde_json::Value>,
             at: chrono::DateTime<chrono::Utc>,
+            correlation_id: Option<String>,
         }
 
         let run_id = signals[0].run_id;
-        let items: Vec<SignalDtoItem> = signals
-            .iter()
-            .map(|s| SignalDtoItem {
-                test_name: None, // TODO: track test name
-                kind: format!("{:?}", s.signal_type).to_lowercase(),
-                latency_ms: s.latency_ms.map(|v| v as i32),
-                value: None,
-                meta: Some(serde_json::to_value(&s.metadata).unwrap()),
-                at: s.timestamp,
-            })
-            .collect();
+        self.post_chunked("/ingest/signals", signals, |chunk| {
+            let items = chunk
+                .iter()
+                .map(|s| {
+                    Ok(SignalDtoItem {
+                        test_name: None, // TODO: track test name
+                        kind: format!("{:?}", s.signal_type).to_lowercase(),
+                        latency_ms: s.latency_ms.map(|v| v as i32),
+                        value: None,
+                        meta: Some(serde_json::to_value(&s.metadata)?),
+                        at: s.timestamp,
+                        correlation_id: s.correlation_id.clone(),
+                    })
+                })
+                .collect::<Result<Vec<SignalDtoItem>>>()?;
 
-        let dto = SignalsDto {
-            run_id,
-            signals: items,
-        };
-
-        self.post("/ingest/signals", &dto).await
+            Ok(SignalsDto {
+                run_id,
+                signals: items,
+            })
+        })
+        .await
     }
 
     async fn put_artifacts(&self, artifacts: &[Artifact]) -> Result<()> {
@@ -331,28 +853,436 @@ impl Ingest for IngestHttp {
             kind: String,
             path_sha256: String,
             path: String,
+            location: ArtifactLocation,
             size_bytes: Option<i64>,
             mime_type: Option<String>,
         }
 
         let run_id = artifacts[0].run_id;
-        let items: Vec<ArtifactDtoItem> = artifacts
-            .iter()
-            .map(|a| ArtifactDtoItem {
-                test_name: None, // TODO: track test name
-                kind: format!("{:?}", a.artifact_type).to_lowercase(),
-                path_sha256: a.artifact_ref.sha256.clone(),
-                path: a.artifact_ref.path.clone(),
-                size_bytes: Some(a.artifact_ref.size_bytes as i64),
-                mime_type: a.artifact_ref.mime_type.clone(),
+        self.post_chunked("/ingest/artifacts", artifacts, |chunk| {
+            let items: Vec<ArtifactDtoItem> = chunk
+                .iter()
+                .map(|a| ArtifactDtoItem {
+                    test_name: None, // TODO: track test name
+                    kind: format!("{:?}", a.artifact_type).to_lowercase(),
+                    path_sha256: a.artifact_ref.sha256.clone(),
+                    path: a.artifact_ref.path(),
+                    location: a.artifact_ref.location.clone(),
+                    size_bytes: Some(a.artifact_ref.size_bytes as i64),
+                    mime_type: a.artifact_ref.mime_type.clone(),
+                })
+                .collect();
+
+            Ok(ArtifactsDto {
+                run_id,
+                artifacts: items,
             })
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use liminalqa_core::temporal::BiTemporalTime;
+
+    fn signal(run_id: EntityId, latency_ms: u64) -> Signal {
+        Signal {
+            id: EntityId::new(),
+            run_id,
+            test_id: None,
+            signal_type: SignalType::API,
+            timestamp: chrono::Utc::now(),
+            latency_ms: Some(latency_ms),
+            payload_ref: None,
+            metadata: Default::default(),
+            created_at: BiTemporalTime::now(),
+            sequence: 0,
+            correlation_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_signal_batches_are_appended() -> Result<()> {
+        let root = tempfile::TempDir::new()?;
+        let ingest = IngestFs::new(root.path().to_path_buf());
+        let run_id = EntityId::new();
+
+        ingest
+            .put_signals(&[signal(run_id, 1), signal(run_id, 2)])
+            .await?;
+        ingest.put_signals(&[signal(run_id, 3)]).await?;
+
+        let latencies: Vec<Option<u64>> = ingest
+            .read_signals(&run_id)?
+            .into_iter()
+            .map(|s| s.latency_ms)
             .collect();
+        assert_eq!(latencies, [Some(1), Some(2), Some(3)]);
+
+        // A half-written line at the end is an append still in progress
+        let path = root.path().join(run_id.to_string()).join(SIGNALS_FILE);
+        let mut file = std::fs::OpenOptions::new().append(true).open(path)?;
+        std::io::Write::write_all(&mut file, b"{\"id\":")?;
+        assert_eq!(ingest.read_signals(&run_id)?.len(), 3);
+        assert!(ingest.read_signals(&EntityId::new())?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_signals_of_legacy_runs_are_read_back() -> Result<()> {
+        let root = tempfile::TempDir::new()?;
+        let ingest = IngestFs::new(root.path().to_path_buf());
+        let run_id = EntityId::new();
 
-        let dto = ArtifactsDto {
+        let dir = root.path().join(run_id.to_string());
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(
+            dir.join(LEGACY_SIGNALS_FILE),
+            serde_json::to_vec_pretty(&[signal(run_id, 1), signal(run_id, 2)])?,
+        )?;
+        let latencies: Vec<Option<u64>> = ingest
+            .read_signals(&run_id)?
+            .into_iter()
+            .map(|s| s.latency_ms)
+            .collect();
+        assert_eq!(latencies, [Some(1), Some(2)]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_blobs_are_content_addressed() -> Result<()> {
+        let root = tempfile::TempDir::new()?;
+        let ingest = IngestFs::new(root.path().to_path_buf());
+        let run_id = EntityId::new();
+
+        let login = ingest.store_blob(&run_id, "login.png", b"login pixels", Some("image/png"))?;
+        let cart = ingest.store_blob(&run_id, "cart.png", b"cart pixels", Some("image/png"))?;
+        assert_ne!(login.location, cart.location);
+        let ArtifactLocation::Local(path) = &login.location else {
+            panic!("blob stored locally");
+        };
+        assert_eq!(
+            path,
+            &root
+                .path()
+                .join(BLOBS_DIR)
+                .join(&login.sha256[..2])
+                .join(format!("{}.png", login.sha256))
+        );
+        assert_eq!(std::fs::read(path)?, b"login pixels");
+
+        // Same bytes under another name share one blob; both names are kept
+        let retry = ingest.store_blob(&run_id, "login_retry.png", b"login pixels", None)?;
+        assert_eq!(retry.location, login.location);
+        let blobs = ingest.read_blobs(&run_id)?;
+        let names: Vec<&str> = blobs.iter().map(|b| b.name.as_str()).collect();
+        assert_eq!(names, ["login.png", "cart.png", "login_retry.png"]);
+        let stored = std::fs::read_dir(root.path().join(BLOBS_DIR))?
+            .map(|dir| Ok(std::fs::read_dir(dir?.path())?.count()))
+            .sum::<Result<usize>>()?;
+        assert_eq!(stored, 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_artifact_records_are_merged() -> Result<()> {
+        let root = tempfile::TempDir::new()?;
+        let ingest = IngestFs::new(root.path().to_path_buf());
+        let run_id = EntityId::new();
+        let artifact = |name: &str| -> Result<Artifact> {
+            Ok(Artifact {
+                id: EntityId::new(),
+                run_id,
+                test_id: EntityId::new(),
+                artifact_ref: ingest.store_blob(&run_id, name, name.as_bytes(), None)?,
+                artifact_type: ArtifactType::Screenshot,
+                description: Some(name.to_string()),
+                created_at: BiTemporalTime::now(),
+            })
+        };
+
+        ingest.put_artifacts(&[artifact("a.png")?]).await?;
+        ingest.put_artifacts(&[artifact("b.png")?]).await?;
+        assert_eq!(ingest.read_artifacts(&run_id)?.len(), 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_local_artifact_files_are_stored_as_blobs() -> Result<()> {
+        let root = tempfile::TempDir::new()?;
+        let outside = tempfile::TempDir::new()?;
+        let ingest = IngestFs::new(root.path().to_path_buf());
+        let run_id = EntityId::new();
+        let screenshot = outside.path().join("checkout.PNG");
+        std::fs::write(&screenshot, b"checkout pixels")?;
+        let artifact = Artifact {
+            id: EntityId::new(),
             run_id,
-            artifacts: items,
+            test_id: EntityId::new(),
+            artifact_ref: ArtifactRef {
+                sha256: String::new(),
+                location: ArtifactLocation::Local(screenshot.clone()),
+                size_bytes: 0,
+                mime_type: Some("image/png".to_string()),
+            },
+            artifact_type: ArtifactType::Screenshot,
+            description: None,
+            created_at: BiTemporalTime::now(),
         };
 
-        self.post("/ingest/artifacts", &dto).await
+        ingest.put_artifacts(&[artifact]).await?;
+        // The test may clean up its own files; the blob stays
+        std::fs::remove_file(&screenshot)?;
+        let artifacts = ingest.read_artifacts(&run_id)?;
+        let stored = &artifacts[0].artifact_ref;
+        let stored_location = &stored.location;
+        assert_eq!(
+            stored.sha256,
+            format!("{:x}", Sha256::digest(b"checkout pixels"))
+        );
+        assert_eq!(stored.size_bytes, 15);
+        assert_eq!(stored.mime_type.as_deref(), Some("image/png"));
+        let ArtifactLocation::Local(path) = &stored.location else {
+            panic!("blob stored locally");
+        };
+        assert_eq!(
+            path,
+            &root.path().join(blob_path(&stored.sha256, "checkout.PNG"))
+        );
+        assert_eq!(std::fs::read(path)?, b"checkout pixels");
+
+        // Stored again, the record already points at the blob
+        let again = ingest.read_artifacts(&run_id)?;
+        ingest.put_artifacts(&again).await?;
+        assert_eq!(
+            ingest.read_artifacts(&run_id)?[0].artifact_ref.location,
+            *stored_location
+        );
+        let blobs = ingest.read_blobs(&run_id)?;
+        assert_eq!(blobs.len(), 1);
+        assert_eq!(blobs[0].name, "checkout.PNG");
+        let leftovers = std::fs::read_dir(root.path().join(BLOBS_DIR))?
+            .filter(|entry| entry.as_ref().is_ok_and(|e| e.path().is_file()))
+            .count();
+        assert_eq!(leftovers, 0, "no temporary files remain");
+        Ok(())
+    }
+
+    fn result(run_id: EntityId, name: &str, alignment_score: Option<f64>) -> ExecutionResult {
+        let test = test(run_id, name.to_string());
+        let mut reflection = crate::reflection::Reflection::from_test(&test);
+        reflection.alignment_score = alignment_score;
+        ExecutionResult {
+            test,
+            reflection,
+            signals: vec![],
+            attempts: 1,
+            sla_ms: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_outcomes_are_kept_with_tests() -> Result<()> {
+        let root = tempfile::TempDir::new()?;
+        let ingest = IngestFs::new(root.path().to_path_buf());
+        let run_id = EntityId::new();
+        let login = result(run_id, "test_login", Some(0.5));
+
+        ingest
+            .put_results(&[login.clone(), result(run_id, "test_cart", None)])
+            .await?;
+        assert_eq!(ingest.read_outcomes(&run_id)?.len(), 2);
+
+        // A rerun of a test replaces its outcome
+        let mut rerun = login.clone();
+        rerun.reflection.alignment_score = Some(1.0);
+        ingest.put_results(&[rerun]).await?;
+        let outcomes = ingest.read_outcomes(&run_id)?;
+        assert_eq!(outcomes.len(), 2);
+        let score = outcomes
+            .iter()
+            .find(|o| o.test_id == login.test.id)
+            .and_then(|o| o.alignment_score);
+        assert_eq!(score, Some(1.0));
+
+        Ok(())
+    }
+
+    fn test(run_id: EntityId, name: String) -> Test {
+        let now = chrono::Utc::now();
+        Test {
+            id: EntityId::new(),
+            run_id,
+            name,
+            suite: "bulk".to_string(),
+            guidance: String::new(),
+            status: TestStatus::Pass,
+            duration_ms: 1,
+            error: None,
+            started_at: now,
+            completed_at: now,
+            created_at: BiTemporalTime::now(),
+        }
+    }
+
+    type Batches = std::sync::Arc<std::sync::Mutex<Vec<Vec<serde_json::Value>>>>;
+
+    /// Serve `/ingest/tests` on a local port, recording the tests of each batch
+    async fn mock_ingest() -> Result<(String, Batches)> {
+        use axum::{routing::post, Json, Router};
+        use std::sync::{Arc, Mutex};
+
+        let batches = Arc::new(Mutex::new(Vec::new()));
+        let recorded = batches.clone();
+        let app = Router::new().route(
+            "/ingest/tests",
+            post(move |Json(body): Json<serde_json::Value>| {
+                let recorded: Batches = recorded.clone();
+                async move {
+                    let tests = body["tests"].as_array().cloned().unwrap_or_default();
+                    recorded.lock().expect("batches lock").push(tests);
+                    Json(serde_json::json!({ "ok": true }))
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}", listener.local_addr()?);
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        Ok((url, batches))
+    }
+
+    #[tokio::test]
+    async fn test_large_test_batches_are_chunked() -> Result<()> {
+        let (url, batches) = mock_ingest().await?;
+        let ingest = IngestHttp::new(url, "token".to_string()).with_chunk_size(1000);
+        let run_id = EntityId::new();
+        let tests: Vec<Test> = (0..2500)
+            .map(|i| test(run_id, format!("test_{i}")))
+            .collect();
+
+        ingest.put_tests(&tests).await?;
+
+        let sizes: Vec<usize> = batches
+            .lock()
+            .expect("batches lock")
+            .iter()
+            .map(Vec::len)
+            .collect();
+        assert_eq!(sizes, [1000, 1000, 500]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_results_carry_their_outcome() -> Result<()> {
+        let (url, batches) = mock_ingest().await?;
+        let ingest = IngestHttp::new(url, "token".to_string());
+        let run_id = EntityId::new();
+
+        ingest
+            .put_results(&[
+                ExecutionResult {
+                    sla_ms: Some(200),
+                    attempts: 3,
+                    ..result(run_id, "test_login", Some(0.5))
+                },
+                ExecutionResult {
+                    attempts: 0,
+                    ..result(run_id, "test_cart", None)
+                },
+            ])
+            .await?;
+
+        let batches = batches.lock().expect("batches lock");
+        let tests = &batches[0];
+        assert_eq!(tests[0]["alignment_score"], 0.5);
+        assert_eq!(tests[0]["sla_ms"], 200);
+        assert_eq!(tests[0]["attempts"], 3);
+        assert!(tests[1].get("alignment_score").is_none());
+        assert!(tests[1].get("sla_ms").is_none());
+        // A skipped test made no attempt, which the collector would reject
+        assert!(tests[1].get("attempts").is_none());
+        Ok(())
+    }
+
+    /// Serve a collector failing every request with 503, counting requests
+    async fn failing_ingest() -> Result<(String, std::sync::Arc<std::sync::atomic::AtomicUsize>)> {
+        use axum::{http::StatusCode, Router};
+        use std::sync::{atomic::AtomicUsize, Arc};
+
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counted = hits.clone();
+        let app = Router::new().fallback(move || {
+            let counted = counted.clone();
+            async move {
+                counted.fetch_add(1, Ordering::SeqCst);
+                StatusCode::SERVICE_UNAVAILABLE
+            }
+        });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}", listener.local_addr()?);
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        Ok((url, hits))
+    }
+
+    #[tokio::test]
+    async fn test_retry_budget_is_shared_across_batch() -> Result<()> {
+        let (url, hits) = failing_ingest().await?;
+        let ingest = IngestHttp::new(url, "token".to_string())
+            .with_chunk_size(1)
+            .with_retry_backoff(1)
+            .with_retry_budget(2);
+        let run_id = EntityId::new();
+        let tests: Vec<Test> = (0..3).map(|i| test(run_id, format!("test_{i}"))).collect();
+
+        // The first chunk spends the budget; the rest of the batch is not sent
+        let err = ingest.put_tests(&tests).await.unwrap_err();
+        assert!(format!("{:#}", err).contains("2 chunks not sent"));
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+        let err = ingest.put_signals(&[signal(run_id, 5)]).await.unwrap_err();
+        assert!(format!("{:#}", err).contains("Retry budget of 2 exhausted"));
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+
+        // A new batch gets a fresh budget
+        ingest.begin_batch();
+        assert!(ingest.put_signals(&[signal(run_id, 5)]).await.is_err());
+        assert_eq!(hits.load(Ordering::SeqCst), 6);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_each_batch_gets_a_fresh_retry_budget() -> Result<()> {
+        let (url, hits) = failing_ingest().await?;
+        let ingest: Box<dyn Ingest> = Box::new(
+            IngestHttp::new(url, "token".to_string())
+                .with_retry_backoff(1)
+                .with_retry_budget(2),
+        );
+        let run = Run {
+            id: EntityId::new(),
+            build_id: EntityId::new(),
+            plan_name: "smoke".to_string(),
+            env: Default::default(),
+            started_at: chrono::Utc::now(),
+            ended_at: None,
+            runner_version: "1.0.0".to_string(),
+            liminal_os_version: None,
+            created_at: BiTemporalTime::now(),
+        };
+
+        // The run is tried once plus the two budgeted retries, and the rest
+        // of the batch is not sent
+        let err = ingest.put_batch(&run, &[], &[], &[]).await.unwrap_err();
+        assert!(format!("{:#}", err).contains("Retry budget of 2 exhausted"));
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+        let err = ingest.put_batch(&run, &[], &[], &[]).await.unwrap_err();
+        assert!(format!("{:#}", err).contains("Retry budget of 2 exhausted"));
+        assert_eq!(hits.load(Ordering::SeqCst), 6);
+        Ok(())
     }
 }
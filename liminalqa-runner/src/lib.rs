@@ -16,8 +16,8 @@ pub mod reflection;
 pub mod runner;
 
 pub use conavigation::CoNavigator;
-pub use council::InnerCouncil;
-pub use guidance::Guidance;
+pub use council::{InnerCouncil, SharedCouncil, SignalFilter};
+pub use guidance::{Guidance, GuidanceEvaluation, Observable};
 pub use ingest::{create_ingest, Ingest, IngestConfig};
 pub use metrics::TestMetrics;
 pub use reflection::Reflection;
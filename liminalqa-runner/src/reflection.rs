@@ -1,6 +1,6 @@
 //! Reflection — Causality-based test reporting
 
-use crate::council::ReconciliationResult;
+use crate::{council::ReconciliationResult, guidance::GuidanceEvaluation};
 use liminalqa_core::{entities::Test, types::TestStatus};
 use serde::{Deserialize, Serialize};
 
@@ -14,6 +14,10 @@ pub struct Reflection {
     pub causality_trail: Vec<CausalityNode>,
     pub reconciliation: Option<ReconciliationResult>,
     pub insights: Vec<String>,
+    /// Fraction of the guidance observables the test met, `None` when the
+    /// guidance states none
+    #[serde(default)]
+    pub alignment_score: Option<f64>,
 }
 
 impl Reflection {
@@ -26,9 +30,24 @@ impl Reflection {
             causality_trail: vec![],
             reconciliation: None,
             insights: vec![],
+            alignment_score: None,
         }
     }
 
+    /// Record how well the test met its guidance
+    pub fn with_alignment(mut self, evaluation: &GuidanceEvaluation) -> Self {
+        self.alignment_score = evaluation.alignment_score();
+        if !evaluation.unsatisfied.is_empty() {
+            self.insights.push(format!(
+                "Met {} of {} guidance observables; missing: {:?}",
+                evaluation.satisfied.len(),
+                evaluation.satisfied.len() + evaluation.unsatisfied.len(),
+                evaluation.unsatisfied
+            ));
+        }
+        self
+    }
+
     pub fn with_reconciliation(mut self, reconciliation: ReconciliationResult) -> Self {
         // Generate insights from reconciliation
         if !reconciliation.inconsistencies.is_empty() {
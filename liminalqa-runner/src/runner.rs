@@ -1,18 +1,27 @@
 //! Test runner orchestration
 
 use crate::{
-    conavigation::CoNavigator, council::InnerCouncil, guidance::Guidance, reflection::Reflection,
+    conavigation::CoNavigator,
+    council::{InnerCouncil, SignalFilter},
+    guidance::Guidance,
+    reflection::Reflection,
 };
 use anyhow::Result;
 use async_trait::async_trait;
+use futures::stream::{self, StreamExt};
 use liminalqa_core::{entities::Test, temporal::BiTemporalTime, types::*};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
 use tracing::info;
 
 /// Test runner that orchestrates the testing philosophy
 pub struct TestRunner {
     run_id: EntityId,
     navigator: CoNavigator,
+    signal_dedup_window_ms: Option<u64>,
+    signal_filter: SignalFilter,
+    id_generator: Arc<dyn IdGenerator>,
 }
 
 impl TestRunner {
@@ -20,6 +29,9 @@ impl TestRunner {
         Self {
             run_id,
             navigator: CoNavigator::default(),
+            signal_dedup_window_ms: None,
+            signal_filter: SignalFilter::default(),
+            id_generator: Arc::new(RandomIdGenerator),
         }
     }
 
@@ -28,18 +40,40 @@ impl TestRunner {
         self
     }
 
+    /// Collapse duplicate signals recorded within `window_ms` of each other
+    /// before reconciliation (see [`InnerCouncil::dedup`]). Off by default.
+    pub fn with_signal_dedup(mut self, window_ms: u64) -> Self {
+        self.signal_dedup_window_ms = Some(window_ms);
+        self
+    }
+
+    /// Ignore noise signals (heartbeats, polling) when reconciling, see
+    /// [`SignalFilter`]
+    pub fn with_signal_filter(mut self, filter: SignalFilter) -> Self {
+        self.signal_filter = filter;
+        self
+    }
+
+    /// Generate test IDs from `generator`, e.g. a [`SeededIdGenerator`] to
+    /// pin IDs in fixtures. Random by default.
+    pub fn with_id_generator(mut self, generator: Arc<dyn IdGenerator>) -> Self {
+        self.id_generator = generator;
+        self
+    }
+
     /// Execute a test following the LIMINAL philosophy
-    pub async fn execute<T: TestCase>(&self, test_case: &T) -> Result<ExecutionResult> {
+    pub async fn execute<T: TestCase + ?Sized>(&self, test_case: &T) -> Result<ExecutionResult> {
         let guidance = test_case.guidance();
-        let test_id = new_entity_id();
+        let test_id = self.id_generator.next_id();
 
         info!("Executing test: {} ({})", test_case.name(), guidance.intent);
 
         let start = chrono::Utc::now();
-        let mut council = InnerCouncil::new();
+        let mut council = InnerCouncil::new().with_filter(self.signal_filter.clone());
+        let navigator = self.navigator.recording();
 
         // Execute test with co-navigation
-        let status = match test_case.execute(&self.navigator, &mut council).await {
+        let status = match test_case.execute(&navigator, &mut council).await {
             Ok(_) => TestStatus::Pass,
             Err(e) => {
                 tracing::error!("Test failed: {}", e);
@@ -50,6 +84,29 @@ impl TestRunner {
         let end = chrono::Utc::now();
         let duration_ms = (end - start).num_milliseconds() as u64;
 
+        if let Some(window_ms) = self.signal_dedup_window_ms {
+            council.dedup(window_ms);
+        }
+
+        // A passing test still times out if a signal missed its deadline
+        let late_signals: Vec<String> = guidance
+            .late_signals(council.signals())
+            .iter()
+            .map(|s| {
+                format!(
+                    "{:?} signal took {}ms (deadline {}ms)",
+                    s.signal_type,
+                    s.latency_ms.unwrap_or_default(),
+                    guidance.timeout_for(s.signal_type)
+                )
+            })
+            .collect();
+        let status = if status == TestStatus::Pass && !late_signals.is_empty() {
+            TestStatus::Timeout
+        } else {
+            status
+        };
+
         // Create test entity
         let test = Test {
             id: test_id,
@@ -67,14 +124,184 @@ impl TestRunner {
 
         // Generate reflection
         let reconciliation = council.reconcile();
-        let reflection = Reflection::from_test(&test).with_reconciliation(reconciliation);
+        let evaluation = guidance.evaluate(council.signals());
+        let attempts = navigator.attempts();
+        let mut reflection = late_signals.into_iter().fold(
+            Reflection::from_test(&test)
+                .with_reconciliation(reconciliation)
+                .with_alignment(&evaluation),
+            Reflection::add_insight,
+        );
+        if attempts > 1 && test.status == TestStatus::Pass {
+            reflection = reflection.add_insight(format!("Passed after {} attempts", attempts));
+        }
 
         Ok(ExecutionResult {
             test,
             reflection,
             signals: council.signals().to_vec(),
+            attempts,
+            sla_ms: guidance.sla_ms,
         })
     }
+
+    /// Execute many test cases, running up to `concurrency` of them at once.
+    ///
+    /// Tests are scheduled in dependency order (see [`TestCase::depends_on`]).
+    /// A test whose dependency did not pass is not executed and is reported
+    /// as `Skip`. Each test gets its own `InnerCouncil`, so signals never leak
+    /// between tests.
+    ///
+    /// Fails if a dependency refers to an unknown test or forms a cycle.
+    pub async fn execute_all(
+        &self,
+        cases: Vec<Box<dyn TestCase>>,
+        concurrency: usize,
+    ) -> Result<Vec<ExecutionResult>> {
+        let waves = dependency_waves(&cases)?;
+        let mut statuses: HashMap<String, Vec<TestStatus>> = HashMap::new();
+        let mut results = Vec::with_capacity(cases.len());
+
+        for wave in waves {
+            let (runnable, blocked): (Vec<usize>, Vec<usize>) = wave.into_iter().partition(|&i| {
+                cases[i].depends_on().iter().all(|dep| {
+                    statuses
+                        .get(*dep)
+                        .is_some_and(|s| s.iter().all(TestStatus::is_pass))
+                })
+            });
+
+            for i in blocked {
+                let result = self.skipped(cases[i].as_ref());
+                statuses
+                    .entry(result.test.name.clone())
+                    .or_default()
+                    .push(result.test.status);
+                results.push(result);
+            }
+
+            let executed: Vec<ExecutionResult> = stream::iter(runnable)
+                .map(|i| {
+                    let case = cases[i].as_ref();
+                    async move {
+                        match self.execute(case).await {
+                            Ok(result) => Some(result),
+                            Err(e) => {
+                                tracing::error!("Failed to execute test {}: {}", case.name(), e);
+                                None
+                            }
+                        }
+                    }
+                })
+                .buffer_unordered(concurrency.max(1))
+                .filter_map(|result| async move { result })
+                .collect()
+                .await;
+
+            for result in executed {
+                statuses
+                    .entry(result.test.name.clone())
+                    .or_default()
+                    .push(result.test.status);
+                results.push(result);
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Build a `Skip` result for a test whose dependencies did not pass
+    fn skipped(&self, test_case: &dyn TestCase) -> ExecutionResult {
+        let now = chrono::Utc::now();
+        let test = Test {
+            id: self.id_generator.next_id(),
+            run_id: self.run_id,
+            name: test_case.name().to_string(),
+            suite: test_case.suite().to_string(),
+            guidance: test_case.guidance().intent,
+            status: TestStatus::Skip,
+            duration_ms: 0,
+            error: None,
+            started_at: now,
+            completed_at: now,
+            created_at: BiTemporalTime::now(),
+        };
+
+        info!(
+            "Skipping test {}: dependencies {:?} did not pass",
+            test.name,
+            test_case.depends_on()
+        );
+
+        let reflection = Reflection::from_test(&test).add_insight(format!(
+            "Skipped because a dependency did not pass: {}",
+            test_case.depends_on().join(", ")
+        ));
+
+        ExecutionResult {
+            test,
+            reflection,
+            signals: vec![],
+            attempts: 0,
+            sla_ms: None,
+        }
+    }
+}
+
+/// Group test cases into waves so that every test runs after all of its
+/// dependencies (Kahn's algorithm).
+fn dependency_waves(cases: &[Box<dyn TestCase>]) -> Result<Vec<Vec<usize>>> {
+    let mut by_name: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (i, case) in cases.iter().enumerate() {
+        by_name.entry(case.name()).or_default().push(i);
+    }
+
+    let mut in_degree = vec![0usize; cases.len()];
+    let mut dependents: Vec<Vec<usize>> = vec![Vec::new(); cases.len()];
+
+    for (i, case) in cases.iter().enumerate() {
+        for dep in case.depends_on() {
+            let Some(dep_indices) = by_name.get(dep) else {
+                anyhow::bail!("Test '{}' depends on unknown test '{}'", case.name(), dep);
+            };
+            for &d in dep_indices {
+                dependents[d].push(i);
+                in_degree[i] += 1;
+            }
+        }
+    }
+
+    let mut waves = Vec::new();
+    let mut current: Vec<usize> = (0..cases.len()).filter(|&i| in_degree[i] == 0).collect();
+    let mut scheduled = 0;
+
+    while !current.is_empty() {
+        scheduled += current.len();
+        let mut next = Vec::new();
+        for &i in &current {
+            for &dependent in &dependents[i] {
+                in_degree[dependent] -= 1;
+                if in_degree[dependent] == 0 {
+                    next.push(dependent);
+                }
+            }
+        }
+        waves.push(current);
+        current = next;
+    }
+
+    if scheduled < cases.len() {
+        let cyclic: Vec<&str> = (0..cases.len())
+            .filter(|&i| in_degree[i] > 0)
+            .map(|i| cases[i].name())
+            .collect();
+        anyhow::bail!(
+            "Dependency cycle detected between tests: {}",
+            cyclic.join(", ")
+        );
+    }
+
+    Ok(waves)
 }
 
 /// Trait for test cases
@@ -84,6 +311,11 @@ pub trait TestCase: Send + Sync {
     fn suite(&self) -> &str;
     fn guidance(&self) -> Guidance;
     async fn execute(&self, navigator: &CoNavigator, council: &mut InnerCouncil) -> Result<()>;
+
+    /// Names of tests that must pass before this one runs
+    fn depends_on(&self) -> Vec<&str> {
+        vec![]
+    }
 }
 
 /// Result of test execution
@@ -92,4 +324,302 @@ pub struct ExecutionResult {
     pub test: Test,
     pub reflection: Reflection,
     pub signals: Vec<liminalqa_core::entities::Signal>,
+    /// Most attempts a navigation of the test took (see
+    /// [`CoNavigator::recording`]); 0 for a skipped test
+    #[serde(default)]
+    pub attempts: u32,
+    /// SLA the test's guidance set (see [`Guidance::with_sla`]); `None` for
+    /// a skipped test
+    #[serde(default)]
+    pub sla_ms: Option<u64>,
+}
+
+impl ExecutionResult {
+    /// Passed, but only after a retry
+    pub fn passed_with_retries(&self) -> bool {
+        self.test.status == TestStatus::Pass && self.attempts > 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+    use std::time::{Duration, Instant};
+
+    struct ScriptedTest {
+        name: String,
+        delay_ms: u64,
+        fails: bool,
+        /// Failed navigation attempts before the test's step succeeds
+        transient_failures: usize,
+        deps: Vec<&'static str>,
+        guidance: Guidance,
+        signals: Vec<(SignalType, u64)>,
+        executions: Arc<AtomicUsize>,
+    }
+
+    impl ScriptedTest {
+        fn new(name: &str) -> Self {
+            Self {
+                name: name.to_string(),
+                delay_ms: 0,
+                fails: false,
+                transient_failures: 0,
+                deps: vec![],
+                guidance: Guidance::new("Behaves as scripted"),
+                signals: vec![],
+                executions: Arc::new(AtomicUsize::new(0)),
+            }
+        }
+
+        fn guided(mut self, guidance: Guidance) -> Self {
+            self.guidance = guidance;
+            self
+        }
+
+        fn emits(mut self, signal_type: SignalType, latency_ms: u64) -> Self {
+            self.signals.push((signal_type, latency_ms));
+            self
+        }
+
+        fn delay(mut self, delay_ms: u64) -> Self {
+            self.delay_ms = delay_ms;
+            self
+        }
+
+        fn failing(mut self) -> Self {
+            self.fails = true;
+            self
+        }
+
+        fn transient(mut self, failures: usize) -> Self {
+            self.transient_failures = failures;
+            self
+        }
+
+        fn after(mut self, dep: &'static str) -> Self {
+            self.deps.push(dep);
+            self
+        }
+    }
+
+    #[async_trait]
+    impl TestCase for ScriptedTest {
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        fn suite(&self) -> &str {
+            "scripted"
+        }
+
+        fn guidance(&self) -> Guidance {
+            self.guidance.clone()
+        }
+
+        async fn execute(&self, navigator: &CoNavigator, council: &mut InnerCouncil) -> Result<()> {
+            self.executions.fetch_add(1, Ordering::SeqCst);
+            let tries = AtomicUsize::new(0);
+            navigator
+                .execute_with_retry(|| async {
+                    if tries.fetch_add(1, Ordering::SeqCst) < self.transient_failures {
+                        anyhow::bail!("transient failure")
+                    }
+                    Ok(())
+                })
+                .await?;
+            for &(signal_type, latency_ms) in &self.signals {
+                council.record(liminalqa_core::entities::Signal {
+                    id: new_entity_id(),
+                    run_id: new_entity_id(),
+                    test_id: Some(new_entity_id()),
+                    signal_type,
+                    timestamp: chrono::Utc::now(),
+                    latency_ms: Some(latency_ms),
+                    payload_ref: None,
+                    metadata: Default::default(),
+                    created_at: BiTemporalTime::now(),
+                    sequence: 0,
+                    correlation_id: None,
+                });
+            }
+            tokio::time::sleep(Duration::from_millis(self.delay_ms)).await;
+            if self.fails {
+                anyhow::bail!("scripted failure");
+            }
+            Ok(())
+        }
+
+        fn depends_on(&self) -> Vec<&str> {
+            self.deps.clone()
+        }
+    }
+
+    fn delayed_cases(count: usize, delay_ms: u64) -> Vec<Box<dyn TestCase>> {
+        (0..count)
+            .map(|i| {
+                Box::new(ScriptedTest::new(&format!("test_{}", i)).delay(delay_ms))
+                    as Box<dyn TestCase>
+            })
+            .collect()
+    }
+
+    fn find<'a>(results: &'a [ExecutionResult], name: &str) -> &'a ExecutionResult {
+        results
+            .iter()
+            .find(|r| r.test.name == name)
+            .expect("result should be present")
+    }
+
+    #[tokio::test]
+    async fn test_retried_pass_is_told_apart_from_clean_pass() -> Result<()> {
+        let runner = TestRunner::new(new_entity_id())
+            .with_navigator(CoNavigator::new().with_retries(3).with_retry_delay(0));
+        let cases: Vec<Box<dyn TestCase>> = vec![
+            Box::new(ScriptedTest::new("test_clean")),
+            Box::new(ScriptedTest::new("test_retried").transient(2)),
+        ];
+
+        let results = runner.execute_all(cases, 2).await?;
+        let clean = find(&results, "test_clean");
+        let retried = find(&results, "test_retried");
+        assert_eq!(clean.test.status, TestStatus::Pass);
+        assert_eq!(retried.test.status, TestStatus::Pass);
+        assert_eq!((clean.attempts, retried.attempts), (1, 3));
+        assert!(!clean.passed_with_retries());
+        assert!(retried.passed_with_retries());
+        assert!(retried
+            .reflection
+            .insights
+            .contains(&"Passed after 3 attempts".to_string()));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_execute_all_concurrently() -> Result<()> {
+        let runner = TestRunner::new(new_entity_id());
+
+        let start = Instant::now();
+        let serial = runner.execute_all(delayed_cases(10, 50), 1).await?;
+        let serial_elapsed = start.elapsed();
+
+        let start = Instant::now();
+        let parallel = runner.execute_all(delayed_cases(10, 50), 5).await?;
+        let parallel_elapsed = start.elapsed();
+
+        assert_eq!(serial.len(), 10);
+        assert_eq!(parallel.len(), 10);
+        assert!(parallel_elapsed < serial_elapsed);
+
+        let mut names: Vec<&str> = parallel.iter().map(|r| r.test.name.as_str()).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), 10);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_execute_all_respects_dependency_chain() -> Result<()> {
+        let runner = TestRunner::new(new_entity_id());
+        // Declared out of order on purpose
+        let cases: Vec<Box<dyn TestCase>> = vec![
+            Box::new(ScriptedTest::new("checkout").after("login")),
+            Box::new(ScriptedTest::new("login").after("setup")),
+            Box::new(ScriptedTest::new("setup").delay(20)),
+        ];
+
+        let results = runner.execute_all(cases, 3).await?;
+
+        assert_eq!(results.len(), 3);
+        let setup = find(&results, "setup");
+        let login = find(&results, "login");
+        let checkout = find(&results, "checkout");
+        assert!(results.iter().all(|r| r.test.status == TestStatus::Pass));
+        assert!(login.test.started_at >= setup.test.completed_at);
+        assert!(checkout.test.started_at >= login.test.completed_at);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_execute_all_skips_dependent_of_failed_test() -> Result<()> {
+        let runner = TestRunner::new(new_entity_id());
+        let dependent = ScriptedTest::new("dependent").after("setup");
+        let dependent_executions = dependent.executions.clone();
+        let cases: Vec<Box<dyn TestCase>> = vec![
+            Box::new(ScriptedTest::new("setup").failing()),
+            Box::new(dependent),
+            Box::new(ScriptedTest::new("independent")),
+        ];
+
+        let results = runner.execute_all(cases, 2).await?;
+
+        assert_eq!(find(&results, "setup").test.status, TestStatus::Fail);
+        assert_eq!(find(&results, "dependent").test.status, TestStatus::Skip);
+        assert_eq!(find(&results, "independent").test.status, TestStatus::Pass);
+        assert_eq!(dependent_executions.load(Ordering::SeqCst), 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_execute_all_rejects_dependency_cycle() {
+        let runner = TestRunner::new(new_entity_id());
+        let cases: Vec<Box<dyn TestCase>> = vec![
+            Box::new(ScriptedTest::new("a").after("b")),
+            Box::new(ScriptedTest::new("b").after("a")),
+            Box::new(ScriptedTest::new("c")),
+        ];
+
+        let err = runner
+            .execute_all(cases, 2)
+            .await
+            .expect_err("cycle should be rejected");
+        assert!(err.to_string().contains("cycle"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_honors_per_signal_type_timeout() -> Result<()> {
+        let runner = TestRunner::new(new_entity_id());
+        let guidance = Guidance::new("Chat connects").with_timeout(1_000);
+
+        let global_only = ScriptedTest::new("ws_global")
+            .guided(guidance.clone())
+            .emits(SignalType::WebSocket, 3_000);
+        let result = runner.execute(&global_only).await?;
+        assert_eq!(result.test.status, TestStatus::Timeout);
+        assert_eq!(result.reflection.insights.len(), 1);
+
+        let per_type = ScriptedTest::new("ws_per_type")
+            .guided(guidance.with_timeout_for(SignalType::WebSocket, 5_000))
+            .emits(SignalType::WebSocket, 3_000);
+        let result = runner.execute(&per_type).await?;
+        assert_eq!(result.test.status, TestStatus::Pass);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_seeded_id_generator_pins_test_ids() -> Result<()> {
+        let run_id = new_entity_id();
+        let mut ids = Vec::new();
+        for _ in 0..2 {
+            let runner =
+                TestRunner::new(run_id).with_id_generator(Arc::new(SeededIdGenerator::new(7)));
+            let first = runner.execute(&ScriptedTest::new("first")).await?;
+            let second = runner.execute(&ScriptedTest::new("second")).await?;
+            ids.push((first.test.id, second.test.id));
+        }
+
+        assert_eq!(ids[0], ids[1]);
+        assert_ne!(ids[0].0, ids[0].1);
+
+        Ok(())
+    }
 }
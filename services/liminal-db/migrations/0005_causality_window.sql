@@ -0,0 +1,86 @@
+-- Configurable causality window: how far before/after a failure signals count
+
+-- Replaced by the windowed versions below; dropped so calls without the
+-- window arguments are not ambiguous
+drop function if exists causality_walk(uuid);
+drop function if exists causality_walk_at(uuid, timestamptz);
+
+-- Causality walk: find signals from p_before_secs before to p_after_secs after failed tests
+create or replace function causality_walk(
+  p_run_id uuid,
+  p_before_secs int default 300,
+  p_after_secs int default 300
+)
+returns table(
+  test_name text,
+  test_failed_at timestamptz,
+  signal_kind signal_kind,
+  signal_at timestamptz,
+  signal_value double precision,
+  signal_meta jsonb,
+  time_diff_seconds int
+) language sql as $$
+  with fails as (
+    select tf.test_name, tf.completed_at as failed_at
+    from test_fact tf
+    where tf.run_id = p_run_id
+      and tf.status in ('fail', 'timeout')
+      and tf.valid_to = 'infinity'::timestamptz
+  )
+  select
+    f.test_name,
+    f.failed_at as test_failed_at,
+    s.kind as signal_kind,
+    s.at as signal_at,
+    s.value as signal_value,
+    s.meta as signal_meta,
+    extract(epoch from (s.at - f.failed_at))::int as time_diff_seconds
+  from fails f
+  join signal s on s.run_id = p_run_id
+    and s.at between f.failed_at - make_interval(secs => p_before_secs)
+                 and f.failed_at + make_interval(secs => p_after_secs)
+  order by f.test_name, abs(extract(epoch from (s.at - f.failed_at)));
+$$;
+
+-- Causality walk over the facts known at transaction time p_as_of
+create or replace function causality_walk_at(
+  p_run_id uuid,
+  p_as_of timestamptz,
+  p_before_secs int default 300,
+  p_after_secs int default 300
+)
+returns table(
+  test_name text,
+  test_failed_at timestamptz,
+  signal_kind signal_kind,
+  signal_at timestamptz,
+  signal_value double precision,
+  signal_meta jsonb,
+  time_diff_seconds int
+) language sql as $$
+  with fails as (
+    select tf.test_name, tf.completed_at as failed_at
+    from test_fact tf
+    where tf.run_id = p_run_id
+      and tf.status in ('fail', 'timeout')
+      and tf.tx_at <= p_as_of
+      and (tf.valid_to = 'infinity'::timestamptz or tf.valid_to > p_as_of)
+  )
+  select
+    f.test_name,
+    f.failed_at as test_failed_at,
+    s.kind as signal_kind,
+    s.at as signal_at,
+    s.value as signal_value,
+    s.meta as signal_meta,
+    extract(epoch from (s.at - f.failed_at))::int as time_diff_seconds
+  from fails f
+  join signal s on s.run_id = p_run_id
+    and s.tx_at <= p_as_of
+    and s.at between f.failed_at - make_interval(secs => p_before_secs)
+                 and f.failed_at + make_interval(secs => p_after_secs)
+  order by f.test_name, abs(extract(epoch from (s.at - f.failed_at)));
+$$;
+
+comment on function causality_walk is 'Find signals near failed tests to identify root causes';
+comment on function causality_walk_at is 'Causality walk as known at a past transaction time';
@@ -0,0 +1,17 @@
+-- Latest run of every plan, for dashboards showing the current state of each plan
+
+create index if not exists run_plan_started_at_idx on run (plan_name, started_at desc);
+
+-- One row per plan_name: its most recently started run (ties broken by run_id)
+create or replace view latest_run_per_plan as
+select distinct on (plan_name)
+  run_id,
+  build_id,
+  plan_name,
+  env,
+  started_at,
+  ended_at,
+  runner_version,
+  tx_at
+from run
+order by plan_name, started_at desc, run_id desc;
@@ -0,0 +1,78 @@
+-- Cached test summary of completed runs, so reports do not recount test facts
+
+create table if not exists run_summary(
+  run_id         uuid primary key references run(run_id) on delete cascade,
+  total          bigint not null,
+  passed         bigint not null,
+  failed         bigint not null,
+  flake          bigint not null,
+  timeout        bigint not null,
+  skip           bigint not null,
+  flaky_failures bigint not null,
+  computed_at    timestamptz not null default now()
+);
+
+comment on table run_summary is 'Test summary of each completed run, dropped when its test facts change';
+
+-- Recount the current test facts of a run into run_summary
+create or replace function refresh_run_summary(p_run_id uuid)
+returns void language sql as $$
+  insert into run_summary(
+    run_id, total, passed, failed, flake, timeout, skip, flaky_failures, computed_at
+  )
+  select
+    p_run_id,
+    count(*),
+    count(*) filter (where tf.status = 'pass'),
+    count(*) filter (where tf.status = 'fail'),
+    count(*) filter (where tf.status = 'flake'),
+    count(*) filter (where tf.status = 'timeout'),
+    count(*) filter (where tf.status = 'skip'),
+    count(*) filter (
+      where tf.status in ('fail', 'timeout')
+        and exists (
+          select 1 from resonance r where tf.test_name = any(r.affected_tests)
+        )
+    ),
+    now()
+  from test_fact tf
+  where tf.run_id = p_run_id
+    and tf.valid_to = 'infinity'::timestamptz
+  on conflict (run_id) do update set
+    total = excluded.total,
+    passed = excluded.passed,
+    failed = excluded.failed,
+    flake = excluded.flake,
+    timeout = excluded.timeout,
+    skip = excluded.skip,
+    flaky_failures = excluded.flaky_failures,
+    computed_at = excluded.computed_at;
+$$;
+
+-- Populate the cache when a run completes
+create or replace function run_summary_on_complete()
+returns trigger language plpgsql as $$
+begin
+  if new.ended_at is not null then
+    perform refresh_run_summary(new.run_id);
+  end if;
+  return new;
+end $$;
+
+drop trigger if exists run_summary_on_complete on run;
+create trigger run_summary_on_complete
+  after insert or update of ended_at on run
+  for each row execute function run_summary_on_complete();
+
+-- A late or corrected test fact makes the cached summary stale
+create or replace function run_summary_invalidate()
+returns trigger language plpgsql as $$
+begin
+  delete from run_summary where run_id = new.run_id;
+  return new;
+end $$;
+
+drop trigger if exists run_summary_invalidate on test_fact;
+create trigger run_summary_invalidate
+  after insert on test_fact
+  for each row execute function run_summary_invalidate();
@@ -0,0 +1,89 @@
+-- Widen test durations to bigint: an int column overflows at ~24 days,
+-- while the core model records durations as u64 milliseconds.
+-- Durations up to i64::MAX ms are supported end to end.
+
+alter table test_fact alter column duration_ms type bigint;
+
+-- Argument and result types are part of the signatures, so the functions
+-- are replaced rather than redefined
+drop function if exists upsert_test_fact(
+  uuid, text, text, text, test_status, int, jsonb, timestamptz, timestamptz, timestamptz
+);
+drop function if exists get_current_test_facts(uuid);
+drop function if exists timeshift_test_facts(uuid, timestamptz, timestamptz);
+
+create or replace function upsert_test_fact(
+  p_run_id uuid,
+  p_test_name text,
+  p_suite text,
+  p_guidance text,
+  p_status test_status,
+  p_duration_ms bigint,
+  p_error jsonb,
+  p_started_at timestamptz,
+  p_completed_at timestamptz,
+  p_valid_from timestamptz
+) returns bigint language plpgsql as $$
+declare
+  v_fact_id bigint;
+begin
+  -- Close any open facts for this test in this run
+  update test_fact tf
+     set valid_to = p_valid_from
+   where tf.run_id = p_run_id
+     and tf.test_name = p_test_name
+     and tf.valid_to = 'infinity'::timestamptz;
+
+  -- Insert new version
+  insert into test_fact(
+    run_id, test_name, suite, guidance, status,
+    duration_ms, error, started_at, completed_at, valid_from
+  )
+  values (
+    p_run_id, p_test_name, p_suite, p_guidance, p_status,
+    p_duration_ms, p_error, p_started_at, p_completed_at, p_valid_from
+  )
+  returning fact_id into v_fact_id;
+
+  return v_fact_id;
+end $$;
+
+create or replace function get_current_test_facts(p_run_id uuid)
+returns table(
+  fact_id bigint,
+  test_name text,
+  suite text,
+  status test_status,
+  duration_ms bigint,
+  valid_from timestamptz
+) language sql as $$
+  select fact_id, test_name, suite, status, duration_ms, valid_from
+  from test_fact
+  where run_id = p_run_id
+    and valid_to = 'infinity'::timestamptz
+  order by test_name;
+$$;
+
+create or replace function timeshift_test_facts(
+  p_run_id uuid,
+  p_valid_at timestamptz,
+  p_tx_at timestamptz default now()
+)
+returns table(
+  fact_id bigint,
+  test_name text,
+  status test_status,
+  duration_ms bigint,
+  valid_from timestamptz
+) language sql as $$
+  select fact_id, test_name, status, duration_ms, valid_from
+  from test_fact
+  where run_id = p_run_id
+    and tsrange(valid_from, valid_to, '[)') @> p_valid_at
+    and tx_at <= p_tx_at
+  order by test_name;
+$$;
+
+comment on column test_fact.duration_ms is 'Test duration in milliseconds, 0 to 9223372036854775807';
+comment on function upsert_test_fact is 'Close previous fact version and insert new (bi-temporal update)';
+comment on function timeshift_test_facts is 'Query test facts as they were at a specific valid_time and tx_time';
@@ -0,0 +1,7 @@
+-- Give test facts the id of the test execution they record, so facts read
+-- back as tests keep the id they were ingested with. Facts stored before
+-- this migration get a fresh id each.
+
+alter table test_fact add column test_id uuid not null default uuid_generate_v4();
+
+comment on column test_fact.test_id is 'Id of the test execution; shared by every version of its fact';
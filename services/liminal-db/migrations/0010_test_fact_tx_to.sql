@@ -0,0 +1,96 @@
+-- Close test facts in transaction time as well. valid_to is valid time: it
+-- says from when a newer version holds, not when the database learned it,
+-- so as-of reads compare a transaction time against tx_at/tx_to instead.
+
+alter table test_fact add column tx_to timestamptz not null default 'infinity';
+
+-- Facts closed before this migration were superseded when the next version
+-- of the same test was recorded
+update test_fact tf
+   set tx_to = coalesce(
+     (select min(n.tx_at)
+        from test_fact n
+       where n.run_id = tf.run_id
+         and n.test_name = tf.test_name
+         and n.fact_id > tf.fact_id),
+     now())
+ where tf.valid_to <> 'infinity'::timestamptz;
+
+create or replace function upsert_test_fact(
+  p_run_id uuid,
+  p_test_name text,
+  p_suite text,
+  p_guidance text,
+  p_status test_status,
+  p_duration_ms bigint,
+  p_error jsonb,
+  p_started_at timestamptz,
+  p_completed_at timestamptz,
+  p_valid_from timestamptz
+) returns bigint language plpgsql as $$
+declare
+  v_fact_id bigint;
+begin
+  -- Close any open facts for this test in this run
+  update test_fact tf
+     set valid_to = p_valid_from,
+         tx_to = now()
+   where tf.run_id = p_run_id
+     and tf.test_name = p_test_name
+     and tf.valid_to = 'infinity'::timestamptz;
+
+  -- Insert new version
+  insert into test_fact(
+    run_id, test_name, suite, guidance, status,
+    duration_ms, error, started_at, completed_at, valid_from
+  )
+  values (
+    p_run_id, p_test_name, p_suite, p_guidance, p_status,
+    p_duration_ms, p_error, p_started_at, p_completed_at, p_valid_from
+  )
+  returning fact_id into v_fact_id;
+
+  return v_fact_id;
+end $$;
+
+-- Causality walk over the facts known at transaction time p_as_of
+create or replace function causality_walk_at(
+  p_run_id uuid,
+  p_as_of timestamptz,
+  p_before_secs int default 300,
+  p_after_secs int default 300
+)
+returns table(
+  test_name text,
+  test_failed_at timestamptz,
+  signal_kind signal_kind,
+  signal_at timestamptz,
+  signal_value double precision,
+  signal_meta jsonb,
+  time_diff_seconds int
+) language sql as $$
+  with fails as (
+    select tf.test_name, tf.completed_at as failed_at
+    from test_fact tf
+    where tf.run_id = p_run_id
+      and tf.status in ('fail', 'timeout')
+      and tf.tx_at <= p_as_of
+      and tf.tx_to > p_as_of
+  )
+  select
+    f.test_name,
+    f.failed_at as test_failed_at,
+    s.kind as signal_kind,
+    s.at as signal_at,
+    s.value as signal_value,
+    s.meta as signal_meta,
+    extract(epoch from (s.at - f.failed_at))::int as time_diff_seconds
+  from fails f
+  join signal s on s.run_id = p_run_id
+    and s.tx_at <= p_as_of
+    and s.at between f.failed_at - make_interval(secs => p_before_secs)
+                 and f.failed_at + make_interval(secs => p_after_secs)
+  order by f.test_name, abs(extract(epoch from (s.at - f.failed_at)));
+$$;
+
+comment on column test_fact.tx_to is 'Transaction time the fact was superseded; infinity while current';
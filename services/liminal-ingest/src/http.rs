@@ -3,6 +3,8 @@
 use crate::models::{ApiResponse, ArtifactsDto, RunDto, SignalsDto, TestsDto};
 use crate::store::Store;
 use actix_web::{get, post, web, HttpResponse, Responder};
+use liminalqa_core::entities::Test;
+use liminalqa_db::Storage;
 use tracing::{error, info};
 
 /// Health check endpoint
@@ -16,14 +18,27 @@ pub async fn health() -> impl Responder {
 }
 
 /// Ingest a test run
+///
+/// Postgres stores the DTO as sent, env included; other backends store the
+/// run entity, whose env must be flat.
 #[post("/ingest/run")]
 pub async fn ingest_run(
     dto: web::Json<RunDto>,
-    store: web::Data<Store>,
+    storage: web::Data<dyn Storage>,
+    store: Option<web::Data<Store>>,
 ) -> impl Responder {
     info!("Ingesting run: {}", dto.run_id);
 
-    match store.put_run(&dto).await {
+    let result = match store {
+        Some(store) => store.put_run(&dto).await,
+        None => match dto.to_run() {
+            Ok(run) => storage.put_run(&run).await,
+            Err(e) => {
+                return HttpResponse::BadRequest().json(ApiResponse::error(format!("{:#}", e)));
+            }
+        },
+    };
+    match result {
         Ok(_) => {
             info!("Run ingested successfully: {}", dto.run_id);
             HttpResponse::Ok().json(ApiResponse::ok())
@@ -39,14 +54,33 @@ pub async fn ingest_run(
 }
 
 /// Ingest test results
+///
+/// Unknown statuses are refused on every backend; Postgres stores the DTOs
+/// as sent, other backends store test entities.
 #[post("/ingest/tests")]
 pub async fn ingest_tests(
     dto: web::Json<TestsDto>,
-    store: web::Data<Store>,
+    storage: web::Data<dyn Storage>,
+    store: Option<web::Data<Store>>,
 ) -> impl Responder {
     info!("Ingesting {} tests for run: {}", dto.tests.len(), dto.run_id);
 
-    match store.put_tests(dto.run_id, &dto.tests, dto.valid_from).await {
+    let tests = match dto
+        .tests
+        .iter()
+        .map(|test| test.to_test(dto.run_id, dto.valid_from))
+        .collect::<anyhow::Result<Vec<Test>>>()
+    {
+        Ok(tests) => tests,
+        Err(e) => {
+            return HttpResponse::BadRequest().json(ApiResponse::error(format!("{:#}", e)));
+        }
+    };
+    let result = match store {
+        Some(store) => store.put_tests(dto.run_id, &dto.tests, dto.valid_from).await,
+        None => storage.put_tests(&tests).await,
+    };
+    match result {
         Ok(_) => {
             info!("Tests ingested successfully for run: {}", dto.run_id);
             HttpResponse::Ok().json(ApiResponse::ok())
@@ -106,3 +140,18 @@ pub async fn ingest_artifacts(
         }
     }
 }
+
+/// Most recent run of every plan
+#[get("/runs/latest")]
+pub async fn latest_runs(store: web::Data<Store>) -> impl Responder {
+    match store.latest_run_per_plan().await {
+        Ok(runs) => HttpResponse::Ok().json(runs),
+        Err(e) => {
+            error!("Failed to fetch latest runs: {}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::error(format!(
+                "Failed to fetch latest runs: {}",
+                e
+            )))
+        }
+    }
+}
@@ -5,9 +5,11 @@ mod http;
 mod models;
 mod store;
 
-use actix_web::{middleware, App, HttpServer};
+use actix_web::{middleware, web, App, HttpServer};
 use anyhow::Result;
+use liminalqa_db::{LiminalDB, Storage};
 use std::env;
+use std::sync::Arc;
 use store::Store;
 use tracing::info;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
@@ -28,26 +30,52 @@ async fn main() -> Result<()> {
     let api_token = env::var("LIMINAL_API_TOKEN").unwrap_or_else(|_| "devtoken".to_string());
     let bind_addr = env::var("LIMINAL_BIND_ADDR").unwrap_or_else(|_| "0.0.0.0:8088".to_string());
 
-    info!("Connecting to database: {}", pg_url);
+    let backend = env::var("LIMINAL_STORAGE").unwrap_or_else(|_| "postgres".to_string());
 
-    // Initialize store
-    let store = Store::new(&pg_url).await?;
-    info!("Database connection pool created");
+    // Runs and tests go through the selected backend (Postgres keeps their
+    // DTOs as sent); signals, artifacts and the latest-runs view are only
+    // served by Postgres
+    let (storage, store): (Arc<dyn Storage>, Option<Store>) = match backend.as_str() {
+        "postgres" => {
+            info!("Connecting to database: {}", pg_url);
+            let store = Store::new(&pg_url).await?;
+            info!("Database connection pool created");
+            (Arc::new(store.clone()), Some(store))
+        }
+        "sled" => {
+            let db_path =
+                env::var("LIMINAL_DB_PATH").unwrap_or_else(|_| "./data/liminaldb".to_string());
+            info!("Opening database at: {}", db_path);
+            (Arc::new(LiminalDB::open(&db_path)?), None)
+        }
+        other => anyhow::bail!(
+            "Unknown LIMINAL_STORAGE '{}' (expected 'postgres' or 'sled')",
+            other
+        ),
+    };
+    info!("Using {} storage", storage.backend());
 
     // Start HTTP server
     info!("Starting HTTP server on {}", bind_addr);
 
     HttpServer::new(move || {
+        let store = store.clone();
         App::new()
-            .app_data(actix_web::web::Data::new(store.clone()))
-            .app_data(actix_web::web::Data::new(api_token.clone()))
+            .app_data(web::Data::from(storage.clone()))
+            .app_data(web::Data::new(api_token.clone()))
             .wrap(middleware::Logger::default())
             .wrap(tracing_actix_web::TracingLogger::default())
             .service(http::health)
             .service(http::ingest_run)
             .service(http::ingest_tests)
-            .service(http::ingest_signals)
-            .service(http::ingest_artifacts)
+            .configure(move |cfg| {
+                if let Some(store) = store {
+                    cfg.app_data(web::Data::new(store))
+                        .service(http::ingest_signals)
+                        .service(http::ingest_artifacts)
+                        .service(http::latest_runs);
+                }
+            })
     })
     .bind(bind_addr)?
     .run()
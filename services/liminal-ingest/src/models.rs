@@ -1,9 +1,25 @@
 //! Data models for ingest API
 
+use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
+use liminalqa_core::{
+    entities::{Run, Test},
+    temporal::BiTemporalTime,
+    types::{EntityId, Environment, TestError, TestStatus},
+};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+/// A Postgres uuid as a workspace entity id; both are 128 bits
+pub fn entity_id(id: Uuid) -> EntityId {
+    EntityId::from(id.as_u128())
+}
+
+/// An entity id as a Postgres uuid
+pub fn row_id(id: EntityId) -> Uuid {
+    Uuid::from_u128(id.into())
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ApiResponse {
     pub ok: bool,
@@ -35,6 +51,51 @@ pub struct RunDto {
     pub runner_version: Option<String>,
 }
 
+impl RunDto {
+    /// The run as a storage entity. A run without a build gets the nil
+    /// build id, which the Postgres store maps back to no build.
+    ///
+    /// Entity environments are flat string maps, so this refuses nested
+    /// env values and turns numbers into strings; the Postgres store takes
+    /// the DTO itself and keeps the env as sent.
+    pub fn to_run(&self) -> Result<Run> {
+        Ok(Run {
+            id: entity_id(self.run_id),
+            build_id: self.build_id.map(entity_id).unwrap_or_else(EntityId::nil),
+            plan_name: self.plan_name.clone(),
+            env: Environment::from_json(&self.env).context("Invalid run env")?,
+            started_at: self.started_at,
+            ended_at: None,
+            runner_version: self.runner_version.clone().unwrap_or_default(),
+            liminal_os_version: None,
+            created_at: BiTemporalTime::now(),
+        })
+    }
+
+    /// Row of a run entity
+    pub fn from_run(run: &Run) -> Self {
+        Self {
+            run_id: row_id(run.id),
+            build_id: (!run.build_id.is_nil()).then(|| row_id(run.build_id)),
+            plan_name: run.plan_name.clone(),
+            env: serde_json::to_value(&run.env).unwrap_or_default(),
+            started_at: run.started_at,
+            runner_version: (!run.runner_version.is_empty()).then(|| run.runner_version.clone()),
+        }
+    }
+}
+
+/// A run as listed by `GET /runs/latest`
+#[derive(Debug, Serialize)]
+pub struct RunSummary {
+    pub run_id: Uuid,
+    pub build_id: Option<Uuid>,
+    pub plan_name: String,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub runner_version: Option<String>,
+}
+
 // Tests envelope
 #[derive(Debug, Deserialize)]
 pub struct TestsDto {
@@ -46,16 +107,95 @@ pub struct TestsDto {
 
 #[derive(Debug, Deserialize)]
 pub struct TestDto {
+    /// Id of the execution; a new one is assigned when missing
+    #[serde(default)]
+    pub test_id: Option<Uuid>,
     pub name: String,
     pub suite: String,
     pub guidance: Option<String>,
     pub status: String, // "pass", "fail", "xfail", "flake", "timeout", "skip"
-    pub duration_ms: Option<i32>,
+    /// Non-negative, up to `i64::MAX` (the `bigint` column)
+    pub duration_ms: Option<i64>,
     pub error: Option<serde_json::Value>,
     pub started_at: Option<DateTime<Utc>>,
     pub completed_at: Option<DateTime<Utc>>,
 }
 
+impl TestDto {
+    /// Status of the test; only the labels of the `test_status` enum are
+    /// accepted
+    pub fn status(&self) -> Result<TestStatus> {
+        parse_status(&self.status).with_context(|| {
+            format!("Unknown status '{}' of test {}", self.status, self.name)
+        })
+    }
+
+    /// The test as a storage entity of run `run_id`, true from `valid_from`
+    pub fn to_test(&self, run_id: Uuid, valid_from: DateTime<Utc>) -> Result<Test> {
+        let started_at = self.started_at.unwrap_or(valid_from);
+        Ok(Test {
+            id: self.test_id.map(entity_id).unwrap_or_else(EntityId::new),
+            run_id: entity_id(run_id),
+            name: self.name.clone(),
+            suite: self.suite.clone(),
+            guidance: self.guidance.clone().unwrap_or_default(),
+            status: self.status()?,
+            duration_ms: self.duration_ms.unwrap_or(0).max(0) as u64,
+            error: self.error.clone().map(TestError::from_value),
+            started_at,
+            completed_at: self.completed_at.unwrap_or(started_at),
+            created_at: BiTemporalTime {
+                valid_time: valid_from,
+                tx_time: Utc::now(),
+            },
+        })
+    }
+
+    /// Row of a test entity; empty guidance is stored as none
+    pub fn from_test(test: &Test) -> Self {
+        Self {
+            test_id: Some(row_id(test.id)),
+            name: test.name.clone(),
+            suite: test.suite.clone(),
+            guidance: (!test.guidance.is_empty()).then(|| test.guidance.clone()),
+            status: status_label(test.status).to_string(),
+            duration_ms: Some(i64::try_from(test.duration_ms).unwrap_or(i64::MAX)),
+            error: test
+                .error
+                .as_ref()
+                .and_then(|e| serde_json::to_value(e).ok()),
+            started_at: Some(test.started_at),
+            completed_at: Some(test.completed_at),
+        }
+    }
+}
+
+/// Value of the `test_status` enum for `status`
+pub fn status_label(status: TestStatus) -> &'static str {
+    match status {
+        TestStatus::Pass => "pass",
+        TestStatus::Fail => "fail",
+        TestStatus::XFail => "xfail",
+        TestStatus::Flake => "flake",
+        TestStatus::Timeout => "timeout",
+        TestStatus::Skip => "skip",
+    }
+}
+
+/// Status of a `test_status` enum value, the inverse of [`status_label`]
+pub fn parse_status(label: &str) -> Option<TestStatus> {
+    [
+        TestStatus::Pass,
+        TestStatus::Fail,
+        TestStatus::XFail,
+        TestStatus::Flake,
+        TestStatus::Timeout,
+        TestStatus::Skip,
+    ]
+    .into_iter()
+    .find(|status| status_label(*status) == label)
+}
+
 // Signals envelope
 #[derive(Debug, Deserialize)]
 pub struct SignalsDto {
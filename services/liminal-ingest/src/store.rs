@@ -1,8 +1,17 @@
 //! PostgreSQL store with bi-temporal operations
 
-use crate::models::{ArtifactDto, RunDto, SignalDto, TestDto};
+use crate::models::{
+    entity_id, parse_status, row_id, ArtifactDto, RunDto, RunSummary, SignalDto, TestDto,
+};
 use anyhow::{Context, Result};
+use async_trait::async_trait;
 use chrono::{DateTime, Utc};
+use liminalqa_core::{
+    entities::{Run, Test},
+    temporal::BiTemporalTime,
+    types::{EntityId, TestError},
+};
+use liminalqa_db::Storage;
 use sqlx::{postgres::PgPoolOptions, PgPool};
 use tracing::{debug, error};
 use uuid::Uuid;
@@ -74,7 +83,7 @@ impl Store {
                     $3::text,           -- suite
                     $4::text,           -- guidance
                     $5::test_status,    -- status
-                    $6::int,            -- duration_ms
+                    $6::bigint,         -- duration_ms
                     $7::jsonb,          -- error
                     $8::timestamptz,    -- started_at
                     $9::timestamptz,    -- completed_at
@@ -96,6 +105,15 @@ impl Store {
             .await
             .context(format!("Failed to upsert test fact: {}", test.name))?;
 
+            if let Some(test_id) = test.test_id {
+                sqlx::query("update test_fact set test_id = $2 where fact_id = $1")
+                    .bind(fact_id)
+                    .bind(test_id)
+                    .execute(&mut *tx)
+                    .await
+                    .context(format!("Failed to set test id: {}", test.name))?;
+            }
+
             debug!("Test fact created: {} (id: {})", test.name, fact_id);
         }
 
@@ -168,4 +186,120 @@ impl Store {
         debug!("All artifacts stored successfully for run: {}", run_id);
         Ok(())
     }
+
+    /// The most recently started run of every plan, ordered by plan name
+    pub async fn latest_run_per_plan(&self) -> Result<Vec<RunSummary>> {
+        sqlx::query_as!(
+            RunSummary,
+            r#"
+            select run_id as "run_id!", build_id, plan_name as "plan_name!",
+                   started_at as "started_at!", ended_at, runner_version
+            from latest_run_per_plan
+            order by plan_name
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch latest run per plan")
+    }
+}
+
+/// Current (open) test fact, as read back for history
+#[derive(sqlx::FromRow)]
+struct TestFactRow {
+    test_id: Uuid,
+    run_id: Option<Uuid>,
+    test_name: String,
+    suite: String,
+    guidance: Option<String>,
+    status: String,
+    duration_ms: Option<i64>,
+    error: Option<serde_json::Value>,
+    started_at: Option<DateTime<Utc>>,
+    completed_at: Option<DateTime<Utc>>,
+    valid_from: DateTime<Utc>,
+    tx_at: DateTime<Utc>,
+}
+
+impl TestFactRow {
+    /// The fact as a test entity
+    fn into_test(self) -> Result<Test> {
+        let started_at = self.started_at.unwrap_or(self.valid_from);
+        Ok(Test {
+            id: entity_id(self.test_id),
+            run_id: self.run_id.map(entity_id).unwrap_or_else(EntityId::nil),
+            name: self.test_name,
+            suite: self.suite,
+            guidance: self.guidance.unwrap_or_default(),
+            status: parse_status(&self.status)
+                .with_context(|| format!("Unknown test status '{}'", self.status))?,
+            duration_ms: self.duration_ms.unwrap_or(0).max(0) as u64,
+            error: self.error.map(TestError::from_value),
+            started_at,
+            completed_at: self.completed_at.unwrap_or(started_at),
+            created_at: BiTemporalTime {
+                valid_time: self.valid_from,
+                tx_time: self.tx_at,
+            },
+        })
+    }
+}
+
+#[async_trait]
+impl Storage for Store {
+    fn backend(&self) -> &'static str {
+        "postgres"
+    }
+
+    async fn put_run(&self, run: &Run) -> Result<()> {
+        Store::put_run(self, &RunDto::from_run(run)).await
+    }
+
+    /// One transaction per run of consecutive tests sharing a run and
+    /// valid time
+    async fn put_tests(&self, tests: &[Test]) -> Result<()> {
+        let mut rest = tests;
+        while let Some(first) = rest.first() {
+            let len = rest
+                .iter()
+                .take_while(|t| {
+                    t.run_id == first.run_id
+                        && t.created_at.valid_time == first.created_at.valid_time
+                })
+                .count();
+            let rows: Vec<TestDto> = rest[..len].iter().map(TestDto::from_test).collect();
+            Store::put_tests(
+                self,
+                row_id(first.run_id),
+                &rows,
+                first.created_at.valid_time,
+            )
+            .await?;
+            rest = &rest[len..];
+        }
+        Ok(())
+    }
+
+    async fn test_history(&self, name: &str, suite: &str, limit: usize) -> Result<Vec<Test>> {
+        let rows: Vec<TestFactRow> = sqlx::query_as(
+            r#"
+            select test_id, run_id, test_name, suite, guidance, status::text as status,
+                   duration_ms, error, started_at, completed_at, valid_from, tx_at
+            from test_fact
+            where test_name = $1
+              and suite = $2
+              and valid_to = 'infinity'::timestamptz
+            order by coalesce(started_at, valid_from) desc
+            limit $3
+            "#,
+        )
+        .bind(name)
+        .bind(suite)
+        .bind(i64::try_from(limit).unwrap_or(i64::MAX))
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch test history")?;
+
+        rows.into_iter().map(TestFactRow::into_test).collect()
+    }
 }
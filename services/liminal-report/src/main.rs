@@ -43,9 +43,26 @@ async fn main() -> Result<()> {
     info!("Connecting to database");
     let pool = sqlx::PgPool::connect(&pg_url).await?;
 
+    // Signals this many seconds before/after a failure join its causality trail
+    let default_window = liminalqa_core::report::CausalityWindow::default();
+    let window = liminalqa_core::report::CausalityWindow::new(
+        env_secs("LIMINAL_CAUSALITY_BEFORE_SECS", default_window.before_secs)?,
+        env_secs("LIMINAL_CAUSALITY_AFTER_SECS", default_window.after_secs)?,
+    );
+
     // Query data
     info!("Querying data for run {}", run_id);
-    let report = query::build_report(&pool, run_id).await?;
+    // Optionally replay the report as it looked at a past transaction time
+    let report = match env::var("LIMINAL_REPORT_AS_OF") {
+        Ok(as_of) => {
+            let as_of = chrono::DateTime::parse_from_rfc3339(&as_of)
+                .context("Invalid LIMINAL_REPORT_AS_OF (expected RFC 3339)")?
+                .with_timezone(&chrono::Utc);
+            info!("Replaying report as of {}", as_of);
+            query::build_report_at(&pool, run_id, as_of, window).await?
+        }
+        Err(_) => query::build_report(&pool, run_id, window).await?,
+    };
 
     // Render HTML
     info!("Rendering HTML report");
@@ -62,3 +79,17 @@ async fn main() -> Result<()> {
 
     Ok(())
 }
+
+/// Read a non-negative number of seconds from `name`, or `default` if unset
+fn env_secs(name: &str, default: i64) -> Result<i64> {
+    match env::var(name) {
+        Ok(value) => {
+            let secs: i64 = value
+                .parse()
+                .with_context(|| format!("Invalid {} (expected seconds)", name))?;
+            anyhow::ensure!(secs >= 0, "{} must not be negative", name);
+            Ok(secs)
+        }
+        Err(_) => Ok(default),
+    }
+}
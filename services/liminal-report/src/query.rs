@@ -1,141 +1,255 @@
 //! Database queries for report data
 
 use anyhow::{Context, Result};
-use liminalqa_core::report::*;
+use chrono::{DateTime, Utc};
+use liminalqa_core::{
+    entities::Test,
+    report::*,
+    temporal::BiTemporalTime,
+    types::{EntityId, RunStatus, TestError, TestStatus},
+};
 use sqlx::PgPool;
+use std::collections::{HashMap, HashSet};
 use tracing::debug;
 use uuid::Uuid;
 
-pub async fn build_report(pool: &PgPool, run_id: Uuid) -> Result<ReflectionReport> {
-    debug!("Building report for run {}", run_id);
+pub async fn build_report(
+    pool: &PgPool,
+    run_id: Uuid,
+    window: CausalityWindow,
+) -> Result<ReflectionReport> {
+    build_report_at(pool, run_id, Utc::now(), window).await
+}
+
+/// Build the report as it looked at transaction time `as_of`.
+///
+/// Rows recorded after `as_of` (`tx_at`) are ignored, and a test fact counts
+/// if it had not been superseded by `as_of` (`tx_to`). Summary, timeline,
+/// slow tests and comparison are computed from those facts the same way the
+/// sled report computes them. Causality trails collect signals within
+/// `window` of each failure.
+pub async fn build_report_at(
+    pool: &PgPool,
+    run_id: Uuid,
+    as_of: DateTime<Utc>,
+    window: CausalityWindow,
+) -> Result<ReflectionReport> {
+    debug!("Building report for run {} as of {}", run_id, as_of);
 
     // Get run metadata
     let run_row = sqlx::query!(
         r#"
         select plan_name, started_at, ended_at
         from run
-        where run_id = $1
+        where run_id = $1 and tx_at <= $2
         "#,
-        run_id
+        run_id,
+        as_of
     )
     .fetch_one(pool)
     .await
     .context("Failed to fetch run metadata")?;
 
-    // Get test summary
-    let summary = get_test_summary(pool, run_id).await?;
+    let tests = get_tests(pool, run_id, as_of).await?;
 
-    // Get timeline
-    let timeline = get_timeline(pool, run_id).await?;
+    // Get test summary, cached once the run completed
+    let cached = match run_row.ended_at {
+        Some(_) => get_cached_summary(pool, run_id, as_of).await?,
+        None => None,
+    };
+    let summary = match cached {
+        Some(summary) => summary,
+        None => {
+            // Failures of tests a resonance pattern already flagged as flaky
+            let flaky = get_flaky_test_names(pool, as_of).await?;
+            test_summary(&tests, |t| flaky.contains(&t.name), &HashMap::new())
+        }
+    };
 
-    // Get top slow tests
-    let top_slow_tests = get_top_slow_tests(pool, run_id).await?;
+    // Compare against the previous run of the same plan
+    let comparison =
+        get_comparison(pool, &tests, &run_row.plan_name, run_row.started_at, as_of).await?;
 
     // Get causality trails
-    let causality_trails = get_causality_trails(pool, run_id).await?;
+    let causality_trails = get_causality_trails(pool, run_id, as_of, window).await?;
 
     Ok(ReflectionReport {
+        schema_version: REPORT_SCHEMA_VERSION.to_string(),
         run_id: run_id.to_string(),
         plan_name: run_row.plan_name,
         started_at: run_row.started_at,
         ended_at: run_row.ended_at,
+        status: Some(if run_row.ended_at.is_some() {
+            RunStatus::Completed
+        } else {
+            RunStatus::Running
+        }),
         summary,
-        timeline,
-        top_slow_tests,
+        timeline: timeline(&tests)?,
+        top_slow_tests: top_slow_tests(&tests),
+        failure_clusters: cluster_failures(&causality_trails),
+        alignment: vec![],
+        sla_breaches: vec![],
+        retried_passes: vec![],
+        in_progress: vec![],
         causality_trails,
+        causality_window: window,
+        comparison,
     })
 }
 
-async fn get_test_summary(pool: &PgPool, run_id: Uuid) -> Result<TestSummary> {
-    let rows = sqlx::query!(
+async fn get_comparison(
+    pool: &PgPool,
+    tests: &[Test],
+    plan_name: &str,
+    started_at: DateTime<Utc>,
+    as_of: DateTime<Utc>,
+) -> Result<Option<RunComparison>> {
+    let previous = sqlx::query!(
         r#"
-        select status as "status!: String", count(*) as "count!"
-        from test_fact
-        where run_id = $1 and valid_to = 'infinity'
-        group by status
+        select run_id, started_at
+        from run
+        where plan_name = $1
+          and started_at < $2
+          and tx_at <= $3
+        order by started_at desc
+        limit 1
         "#,
-        run_id
+        plan_name,
+        started_at,
+        as_of
     )
-    .fetch_all(pool)
-    .await?;
+    .fetch_optional(pool)
+    .await
+    .context("Failed to fetch previous run")?;
 
-    let mut summary = TestSummary {
-        total: 0,
-        passed: 0,
-        failed: 0,
-        flake: 0,
-        timeout: 0,
-        skip: 0,
+    let Some(previous) = previous else {
+        return Ok(None);
     };
 
-    for row in rows {
-        summary.total += row.count;
-        match row.status.as_str() {
-            "pass" => summary.passed = row.count,
-            "fail" => summary.failed = row.count,
-            "flake" => summary.flake = row.count,
-            "timeout" => summary.timeout = row.count,
-            "skip" => summary.skip = row.count,
-            _ => {}
+    let previous_tests = get_tests(pool, previous.run_id, as_of).await?;
+    Ok(Some(RunComparison::between(
+        previous.run_id.to_string(),
+        previous.started_at,
+        &outcomes(&previous_tests),
+        &outcomes(tests),
+    )))
+}
+
+/// Test fact as known at some transaction time
+#[derive(sqlx::FromRow)]
+struct TestFactRow {
+    test_id: Uuid,
+    run_id: Uuid,
+    test_name: String,
+    suite: String,
+    guidance: Option<String>,
+    status: String,
+    duration_ms: Option<i64>,
+    error: Option<serde_json::Value>,
+    started_at: Option<DateTime<Utc>>,
+    completed_at: Option<DateTime<Utc>>,
+    valid_from: DateTime<Utc>,
+    tx_at: DateTime<Utc>,
+}
+
+impl From<TestFactRow> for Test {
+    fn from(row: TestFactRow) -> Self {
+        let started_at = row.started_at.unwrap_or(row.valid_from);
+        Test {
+            id: EntityId::from(row.test_id.as_u128()),
+            run_id: EntityId::from(row.run_id.as_u128()),
+            name: row.test_name,
+            suite: row.suite,
+            guidance: row.guidance.unwrap_or_default(),
+            status: TestStatus::from_label(&row.status),
+            duration_ms: row.duration_ms.unwrap_or(0).max(0) as u64,
+            error: row.error.map(TestError::from_value),
+            started_at,
+            completed_at: row.completed_at.unwrap_or(started_at),
+            created_at: BiTemporalTime {
+                valid_time: row.valid_from,
+                tx_time: row.tx_at,
+            },
         }
     }
-
-    Ok(summary)
 }
 
-async fn get_timeline(pool: &PgPool, run_id: Uuid) -> Result<Vec<TimelineBucket>> {
-    let rows = sqlx::query!(
+/// Test facts of a run that were recorded by `as_of` and not yet superseded
+async fn get_tests(pool: &PgPool, run_id: Uuid, as_of: DateTime<Utc>) -> Result<Vec<Test>> {
+    let rows: Vec<TestFactRow> = sqlx::query_as(
         r#"
-        select
-            date_trunc('minute', valid_from) as "bucket!",
-            status as "status!: String",
-            count(*) as "count!"
+        select test_id, run_id, test_name, suite, guidance, status::text as status,
+               duration_ms, error, started_at, completed_at, valid_from, tx_at
         from test_fact
-        where run_id = $1 and valid_to = 'infinity'
-        group by 1, 2
-        order by 1, 2
+        where run_id = $1
+          and tx_at <= $2
+          and tx_to > $2
         "#,
-        run_id
     )
+    .bind(run_id)
+    .bind(as_of)
     .fetch_all(pool)
-    .await?;
+    .await
+    .context("Failed to fetch test facts")?;
 
-    Ok(rows
-        .into_iter()
-        .map(|row| TimelineBucket {
-            bucket: row.bucket,
-            status: row.status,
-            count: row.count,
-        })
-        .collect())
+    Ok(rows.into_iter().map(Test::from).collect())
 }
 
-async fn get_top_slow_tests(pool: &PgPool, run_id: Uuid) -> Result<Vec<SlowTest>> {
-    let rows = sqlx::query!(
+/// Names of the tests resonance patterns had flagged by `as_of`
+async fn get_flaky_test_names(pool: &PgPool, as_of: DateTime<Utc>) -> Result<HashSet<String>> {
+    let names: Vec<String> = sqlx::query_scalar(
         r#"
-        select test_name, suite, duration_ms, status as "status!: String"
-        from test_fact
-        where run_id = $1 and valid_to = 'infinity'
-        order by duration_ms desc nulls last
-        limit 10
+        select distinct unnest(affected_tests)
+        from resonance
+        where created_at <= $1
         "#,
-        run_id
     )
+    .bind(as_of)
     .fetch_all(pool)
-    .await?;
+    .await
+    .context("Failed to fetch flaky tests")?;
 
-    Ok(rows
-        .into_iter()
-        .map(|row| SlowTest {
-            name: row.test_name,
-            suite: row.suite,
-            duration_ms: row.duration_ms.unwrap_or(0),
-            status: row.status,
-        })
-        .collect())
+    Ok(names.into_iter().collect())
 }
 
-async fn get_causality_trails(pool: &PgPool, run_id: Uuid) -> Result<Vec<CausalityTrail>> {
+/// Summary from `run_summary`, if it was computed by `as_of`
+async fn get_cached_summary(
+    pool: &PgPool,
+    run_id: Uuid,
+    as_of: DateTime<Utc>,
+) -> Result<Option<TestSummary>> {
+    let row = sqlx::query!(
+        r#"
+        select total, passed, failed, flake, timeout, skip, flaky_failures
+        from run_summary
+        where run_id = $1 and computed_at <= $2
+        "#,
+        run_id,
+        as_of
+    )
+    .fetch_optional(pool)
+    .await
+    .context("Failed to fetch cached run summary")?;
+
+    Ok(row.map(|row| TestSummary {
+        total: row.total,
+        passed: row.passed,
+        failed: row.failed,
+        flake: row.flake,
+        timeout: row.timeout,
+        skip: row.skip,
+        flaky_failures: row.flaky_failures,
+        passed_with_retries: 0,
+    }))
+}
+
+async fn get_causality_trails(
+    pool: &PgPool,
+    run_id: Uuid,
+    as_of: DateTime<Utc>,
+    window: CausalityWindow,
+) -> Result<Vec<CausalityTrail>> {
     let rows = sqlx::query!(
         r#"
         select
@@ -146,9 +260,12 @@ async fn get_causality_trails(pool: &PgPool, run_id: Uuid) -> Result<Vec<Causali
             signal_value,
             signal_meta,
             time_diff_seconds
-        from causality_walk($1)
+        from causality_walk_at($1, $2, $3, $4)
         "#,
-        run_id
+        run_id,
+        as_of,
+        i32::try_from(window.before_secs).unwrap_or(i32::MAX),
+        i32::try_from(window.after_secs).unwrap_or(i32::MAX)
     )
     .fetch_all(pool)
     .await?;
@@ -158,11 +275,14 @@ async fn get_causality_trails(pool: &PgPool, run_id: Uuid) -> Result<Vec<Causali
         std::collections::HashMap::new();
 
     for row in rows {
-        let trail = trails.entry(row.test_name.clone()).or_insert(CausalityTrail {
-            test_name: row.test_name.clone(),
-            test_failed_at: row.test_failed_at,
-            signals: vec![],
-        });
+        let trail = trails
+            .entry(row.test_name.clone())
+            .or_insert(CausalityTrail {
+                test_name: row.test_name.clone(),
+                test_failed_at: row.test_failed_at,
+                signals: vec![],
+                owner: None,
+            });
 
         trail.signals.push(NearbySignal {
             kind: row.signal_kind,
@@ -170,8 +290,17 @@ async fn get_causality_trails(pool: &PgPool, run_id: Uuid) -> Result<Vec<Causali
             value: row.signal_value,
             meta: row.signal_meta,
             time_diff_seconds: row.time_diff_seconds,
+            sequence: None,
+            likely_cause: false,
+            correlation_id: None,
         });
     }
 
-    Ok(trails.into_values().collect())
+    Ok(trails
+        .into_values()
+        .map(|mut trail| {
+            trail.mark_likely_cause();
+            trail
+        })
+        .collect())
 }
@@ -2,7 +2,7 @@
 
 use anyhow::Result;
 use handlebars::Handlebars;
-use liminalqa_core::report::ReflectionReport;
+use liminalqa_core::report::{format_duration_ms, pass_rate, weighted_pass_rate, ReflectionReport};
 
 const TEMPLATE: &str = include_str!("../templates/reflection.html");
 
@@ -15,11 +15,11 @@ pub fn render_html(report: &ReflectionReport) -> Result<String> {
         "run_id": report.run_id,
         "plan_name": report.plan_name,
         "started_at": report.started_at.format("%Y-%m-%d %H:%M:%S UTC"),
+        "status": report.status.map(|status| status.to_string()),
         "ended_at": report.ended_at.map(|dt| dt.format("%Y-%m-%d %H:%M:%S UTC").to_string()),
-        "duration": report.ended_at.map(|end| {
-            let duration = (end - report.started_at).num_seconds();
-            format_duration(duration)
-        }),
+        "duration": report
+            .ended_at
+            .map(|end| format_duration_ms((end - report.started_at).num_milliseconds() as f64)),
         "summary": {
             "total": report.summary.total,
             "passed": report.summary.passed,
@@ -27,11 +27,12 @@ pub fn render_html(report: &ReflectionReport) -> Result<String> {
             "flake": report.summary.flake,
             "timeout": report.summary.timeout,
             "skip": report.summary.skip,
-            "pass_rate": if report.summary.total > 0 {
-                (report.summary.passed as f64 / report.summary.total as f64 * 100.0).round() as i64
-            } else {
-                0
-            },
+            "flaky_failures": report.summary.flaky_failures,
+            "pass_rate": percent_or_na(report.summary.total, pass_rate(&report.summary)),
+            "weighted_pass_rate": percent_or_na(
+                report.summary.total,
+                weighted_pass_rate(&report.summary, report.summary.flaky_failures),
+            ),
         },
         "timeline": report.timeline.iter().map(|b| {
             serde_json::json!({
@@ -46,11 +47,34 @@ pub fn render_html(report: &ReflectionReport) -> Result<String> {
                 "name": t.name,
                 "suite": t.suite,
                 "duration_ms": t.duration_ms,
-                "duration_sec": format!("{:.2}s", t.duration_ms as f64 / 1000.0),
+                "duration": format_duration_ms(t.duration_ms as f64),
                 "status": t.status,
                 "status_class": status_class(&t.status),
             })
         }).collect::<Vec<_>>(),
+        "comparison": report.comparison.as_ref().map(|c| {
+            serde_json::json!({
+                "previous_run_id": c.previous_run_id,
+                "previous_started_at": c.previous_started_at.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+                "regressions": c.regressions,
+                "new_failures": c.new_failures,
+                "fixed": c.fixed,
+            })
+        }),
+        "failure_clusters": report.failure_clusters.iter().map(|cluster| {
+            serde_json::json!({
+                "kind": cluster.signal.kind,
+                "at": cluster.signal.at.format("%H:%M:%S%.3f").to_string(),
+                "meta": cluster.signal.meta,
+                "test_count": cluster.test_names.len(),
+                "test_names": cluster.test_names,
+            })
+        }).collect::<Vec<_>>(),
+        "causality_window": format!(
+            "{} before to {} after",
+            format_duration_ms(report.causality_window.before_secs as f64 * 1000.0),
+            format_duration_ms(report.causality_window.after_secs as f64 * 1000.0),
+        ),
         "causality_trails": report.causality_trails.iter().map(|trail| {
             serde_json::json!({
                 "test_name": trail.test_name,
@@ -62,6 +86,8 @@ pub fn render_html(report: &ReflectionReport) -> Result<String> {
                         "time_diff": format_time_diff(sig.time_diff_seconds),
                         "value": sig.value,
                         "meta": sig.meta,
+                        "likely_cause": sig.likely_cause,
+                        "correlation_id": sig.correlation_id,
                     })
                 }).collect::<Vec<_>>(),
             })
@@ -82,16 +108,6 @@ fn status_class(status: &str) -> &str {
     }
 }
 
-fn format_duration(seconds: i64) -> String {
-    let mins = seconds / 60;
-    let secs = seconds % 60;
-    if mins > 0 {
-        format!("{}m {}s", mins, secs)
-    } else {
-        format!("{}s", secs)
-    }
-}
-
 fn format_time_diff(seconds: i32) -> String {
     if seconds < 0 {
         format!("{}s before", -seconds)
@@ -101,3 +117,12 @@ fn format_time_diff(seconds: i32) -> String {
         "at same time".to_string()
     }
 }
+
+/// `rate` as a whole percentage, or "n/a" for a run without tests
+fn percent_or_na(total: i64, rate: f64) -> String {
+    if total == 0 {
+        "n/a".to_string()
+    } else {
+        format!("{}%", rate.round() as i64)
+    }
+}